@@ -0,0 +1,12 @@
+#![no_main]
+#[macro_use] extern crate libfuzzer_sys;
+extern crate rat_core;
+extern crate serde_json;
+
+use rat_core::clients::centerdevice::client::collections::CollectionsResult;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(collections) = serde_json::from_slice::<CollectionsResult>(data) {
+        let _ = collections.filter("rat");
+    }
+});
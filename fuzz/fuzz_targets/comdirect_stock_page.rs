@@ -0,0 +1,9 @@
+#![no_main]
+#[macro_use] extern crate libfuzzer_sys;
+extern crate rat_core;
+
+use rat_core::clients::stocks::comdirect;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = comdirect::parse_stock_price(data);
+});
@@ -0,0 +1,14 @@
+#![no_main]
+#[macro_use] extern crate libfuzzer_sys;
+extern crate rat_core;
+extern crate serde_json;
+
+use rat_core::clients::pocket::client::list::{stream, ListResult};
+use std::time::Duration;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(list) = serde_json::from_slice::<ListResult>(data) {
+        let _ = list.filter(&Some(Duration::from_secs(1_500_000_000)), &Some(Duration::from_secs(1_600_000_000)));
+    }
+    let _ = stream(data, |article| article.time_added().map(|_| ()));
+});
@@ -0,0 +1,12 @@
+#![no_main]
+#[macro_use] extern crate libfuzzer_sys;
+extern crate rat_core;
+
+use rat_core::net::redirect;
+use std::str;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(request_line) = str::from_utf8(data) {
+        let _ = redirect::parse_request_line(request_line);
+    }
+});
@@ -0,0 +1,70 @@
+//! Progress of a long running operation, e.g., an import, so a run that stopped, e.g., because the access token was
+//! rejected, resumes where it stopped instead of starting over. A checkpoint belongs to the input of the operation by a
+//! fingerprint; a different input starts from scratch.
+
+use super::{Cache, Result};
+use config::Config;
+use utils::time::unix_now;
+
+use crypto::digest::Digest;
+use crypto::sha2::Sha256;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct State {
+    fingerprint: String,
+    /// Number of items done, counted from the start of the input
+    done: usize,
+    /// Unix timestamp of the last update
+    updated: u64,
+}
+
+#[derive(Debug)]
+pub struct Checkpoint {
+    cache: Cache,
+    fingerprint: String,
+}
+
+impl Checkpoint {
+    /// Opens the checkpoint of `operation` of a module and the selected account for `input`, e.g., the imported file.
+    pub fn open(config: &Config, module_name: &str, operation: &str, input: &str) -> Self {
+        let mut sha = Sha256::new();
+        sha.input_str(input);
+        Checkpoint { cache: Cache::new(config, module_name, &format!("{}.checkpoint", operation)), fingerprint: sha.result_str() }
+    }
+
+    /// Number of items a previous run of the same input has done; 0 if there is none.
+    pub fn done(&self) -> usize {
+        self.cache.load::<State>().ok()
+            .and_then(|state| if state.fingerprint == self.fingerprint { Some(state.done) } else { None })
+            .unwrap_or(0)
+    }
+
+    pub fn save(&self, done: usize) -> Result<()> {
+        self.cache.write(&State { fingerprint: self.fingerprint.clone(), done: done, updated: unix_now() })
+    }
+
+    /// Removes the checkpoint once the operation has finished.
+    pub fn clear(self) -> Result<()> {
+        self.cache.remove()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing;
+
+    #[test]
+    fn test_checkpoint_belongs_to_input() {
+        let config = testing::config("checkpoint", "");
+        let checkpoint = Checkpoint::open(&config, "test", "import", "a\nb\nc");
+        assert_eq!(checkpoint.done(), 0);
+
+        checkpoint.save(2).unwrap();
+
+        assert_eq!(Checkpoint::open(&config, "test", "import", "a\nb\nc").done(), 2);
+        assert_eq!(Checkpoint::open(&config, "test", "import", "a\nb\nc\nd").done(), 0);
+        checkpoint.clear().unwrap();
+        assert_eq!(Checkpoint::open(&config, "test", "import", "a\nb\nc").done(), 0);
+    }
+}
@@ -0,0 +1,194 @@
+//! Size and age limits for directory caches, i.e., caches of many files like article snapshots; cf. `Cache::dir`.
+//! Directory caches only hold data that can be downloaded again, so the least recently used files are removed once a
+//! limit is exceeded. Credentials, indexes, and other single file caches are never touched.
+
+use super::{Cache, Result, ResultExt};
+use config::Config;
+use utils::console::*;
+use utils::time::{parse_interval, unix_now};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Opportunistic collections run at most once per interval.
+const GC_INTERVAL_SECS: u64 = 24 * 60 * 60;
+
+const MB: u64 = 1024 * 1024;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Limits {
+    pub max_bytes: Option<u64>,
+    pub max_age: Option<Duration>,
+}
+
+impl Limits {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let max_age = match config.general.cache_max_age {
+            Some(ref max_age) => Some(parse_interval(max_age).chain_err(|| "Could not parse 'cache_max_age'")?),
+            None => None,
+        };
+
+        Ok(Limits { max_bytes: config.general.cache_max_size_mb.map(|mb| mb * MB), max_age: max_age })
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.max_bytes.is_none() && self.max_age.is_none()
+    }
+}
+
+/// Files and bytes of the directory caches before and removed by a collection.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct GcResult {
+    pub files: usize,
+    pub bytes: u64,
+    pub removed_files: usize,
+    pub removed_bytes: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct State {
+    last_gc: Option<u64>,
+}
+
+struct CacheFile {
+    path: PathBuf,
+    bytes: u64,
+    /// Latest access or modification as Unix timestamp
+    last_used: u64,
+}
+
+/// Removes files older than the age limit and then the least recently used files until the size limit holds. A dry
+/// run only computes what would be removed.
+pub fn collect(config: &Config, limits: &Limits, dry_run: bool) -> Result<GcResult> {
+    let mut files = Vec::new();
+    let cache_dir = Path::new(&config.general.cache_dir);
+    if cache_dir.is_dir() {
+        for entry in fs::read_dir(cache_dir).chain_err(|| "Could not read cache directory")? {
+            let path = entry.chain_err(|| "Could not read cache directory")?.path();
+            if path.is_dir() {
+                list_files(&path, &mut files)?;
+            }
+        }
+    }
+    let mut result = GcResult { files: files.len(), bytes: files.iter().map(|f| f.bytes).sum(), ..GcResult::default() };
+
+    // Least recently used first
+    files.sort_by_key(|f| f.last_used);
+    let max_age = limits.max_age.map(|max_age| unix_now().saturating_sub(max_age.as_secs()));
+    let mut remaining = result.bytes;
+    for file in files {
+        let too_old = max_age.map(|oldest| file.last_used < oldest).unwrap_or(false);
+        let too_big = limits.max_bytes.map(|max_bytes| remaining > max_bytes).unwrap_or(false);
+        if !too_old && !too_big {
+            continue;
+        }
+        if !dry_run {
+            fs::remove_file(&file.path).chain_err(|| format!("Could not remove cache file '{}'", file.path.display()))?;
+            verboseln(format!("Removed cache file '{}'", file.path.display()));
+        }
+        remaining -= file.bytes;
+        result.removed_files += 1;
+        result.removed_bytes += file.bytes;
+    }
+
+    Ok(result)
+}
+
+/// Runs a collection if limits are configured and the last one is older than a day. Failures are only reported, so
+/// they never fail the command that triggered the collection.
+pub fn collect_if_due(config: &Config) {
+    let limits = match Limits::from_config(config) {
+        Ok(ref limits) if limits.is_unlimited() => return,
+        Ok(limits) => limits,
+        Err(e) => { warning(format!("Skipping cache collection because {}", e)); return }
+    };
+    let state_cache = Cache::new(config, "cache", "gc-state");
+    let state: State = state_cache.load().unwrap_or_default();
+    let now = unix_now();
+    if state.last_gc.map(|last_gc| now < last_gc + GC_INTERVAL_SECS).unwrap_or(false) {
+        return;
+    }
+
+    match collect(config, &limits, false) {
+        Ok(result) => verboseln(format!("Cache collection removed {} file(s)", result.removed_files)),
+        Err(e) => warning(format!("Cache collection failed because {}", e)),
+    }
+    if let Err(e) = state_cache.write(&State { last_gc: Some(now) }) {
+        warning(format!("Could not record cache collection because {}", e));
+    }
+}
+
+fn list_files(dir: &Path, files: &mut Vec<CacheFile>) -> Result<()> {
+    for entry in fs::read_dir(dir).chain_err(|| format!("Could not read cache directory '{}'", dir.display()))? {
+        let path = entry.chain_err(|| "Could not read cache directory")?.path();
+        let metadata = fs::metadata(&path).chain_err(|| format!("Could not read '{}'", path.display()))?;
+        if metadata.is_dir() {
+            list_files(&path, files)?;
+            continue;
+        }
+        let modified = metadata.modified().ok();
+        let accessed = metadata.accessed().ok();
+        let last_used = modified.into_iter().chain(accessed)
+            .filter_map(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .max()
+            .unwrap_or(0);
+        files.push(CacheFile { path: path, bytes: metadata.len(), last_used: last_used });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing;
+
+    use std::fs::File;
+    use std::io::Write;
+    use std::thread;
+
+    fn config(name: &str) -> Config {
+        testing::config(&format!("gc-{}", name), "")
+    }
+
+    fn write_file(path: &Path, bytes: usize) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        File::create(path).unwrap().write_all(&vec![b'x'; bytes]).unwrap();
+    }
+
+    #[test]
+    fn test_collect_removes_least_recently_used_files() {
+        let config = config("lru");
+        let dir = Cache::new(&config, "pocket", "snapshots").dir();
+        write_file(&dir.join("1.html"), 600);
+        // Timestamps have a resolution of one second
+        thread::sleep(Duration::from_millis(1100));
+        write_file(&dir.join("2.html"), 600);
+        let index = Cache::new(&config, "pocket", "articles").path("jsonl");
+        write_file(&index, 2000);
+        let limits = Limits { max_bytes: Some(1000), max_age: None };
+
+        let dry_run = collect(&config, &limits, true).unwrap();
+        assert_eq!(dry_run, GcResult { files: 2, bytes: 1200, removed_files: 1, removed_bytes: 600 });
+        assert!(dir.join("1.html").exists());
+
+        collect(&config, &limits, false).unwrap();
+        assert!(!dir.join("1.html").exists());
+        assert!(dir.join("2.html").exists());
+        assert!(index.exists(), "single file caches are never removed");
+    }
+
+    #[test]
+    fn test_collect_without_limits_keeps_files() {
+        let config = config("unlimited");
+        let dir = Cache::new(&config, "pocket", "snapshots").dir();
+        write_file(&dir.join("1.html"), 10);
+
+        let result = collect(&config, &Limits::default(), false).unwrap();
+
+        assert_eq!(result.removed_files, 0);
+        assert!(dir.join("1.html").exists());
+    }
+}
@@ -0,0 +1,436 @@
+//! Persistent local index of a module's items, e.g., all Pocket articles. The index is an append-only log of JSON
+//! records keyed by item id: an update appends the new version of an item, a deletion appends a tombstone. Opening the
+//! index replays the log, so a sync only has to append the delta since the last sync. The log is compacted once it
+//! holds much more records than live items.
+//!
+//! Queries run on indexed columns, e.g., the words of a title, instead of scanning all items.
+//!
+//! Every sync but the first also appends its delta to a change log, so questions like "what has been added this month"
+//! are answered from the deltas instead of by comparing snapshots of all items.
+
+use super::{Cache, Result, ResultExt};
+use config::Config;
+use utils::console::*;
+use utils::intern::Interner;
+
+use serde::{Deserialize, Serialize};
+use serde_json;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// Compaction starts when the log holds this many records more than twice the number of live items.
+const COMPACTION_SLACK: usize = 100;
+
+/// An item of an index.
+pub trait Indexed: Serialize + Deserialize {
+    fn id(&self) -> &str;
+
+    /// Values of the indexed columns, e.g., `("domain", "rust-lang.org")`. A column may have several values per item,
+    /// e.g., all words of a title.
+    fn columns(&self) -> Vec<(&'static str, String)>;
+}
+
+/// Changes applied by a sync, identified by item id; an entry of the change log.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Delta {
+    /// Watermark of the sync
+    pub at: u64,
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub deleted: Vec<String>,
+}
+
+impl Delta {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.deleted.is_empty()
+    }
+}
+
+/// A missing item marks a tombstone.
+#[derive(Debug, Serialize, Deserialize)]
+struct Record<T> {
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    item: Option<T>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct State {
+    /// Provider-specific watermark of the last sync, e.g., a Unix timestamp
+    since: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct Index<T> {
+    path: PathBuf,
+    changes_path: PathBuf,
+    state_cache: Cache,
+    since: Option<u64>,
+    records: usize,
+    items: BTreeMap<String, T>,
+    /// Ids and column values are interned, so an id indexed by many words or a domain of many items is stored once
+    columns: HashMap<&'static str, HashMap<Rc<str>, BTreeSet<Rc<str>>>>,
+    strings: Interner,
+}
+
+impl<T: Indexed> Index<T> {
+    /// Opens the index `index_name` of a module; an index that has never been synced is empty.
+    pub fn open(config: &Config, module_name: &str, index_name: &str) -> Result<Self> {
+        let cache = Cache::new(config, module_name, index_name);
+        let path = cache.path("jsonl");
+        let changes_path = Cache::new(config, module_name, &format!("{}-changes", index_name)).path("jsonl");
+        let state_cache = Cache::new(config, module_name, &format!("{}-state", index_name));
+        let state: State = if path.exists() { state_cache.load().unwrap_or_default() } else { State::default() };
+        let mut index = Index {
+            path: path, changes_path: changes_path, state_cache: state_cache, since: state.since, records: 0, items: BTreeMap::new(), columns: HashMap::new(),
+            strings: Interner::new(),
+        };
+        index.replay().chain_err(|| format!("Could not read index '{}'", index.path.display()))?;
+
+        Ok(index)
+    }
+
+    /// Watermark of the last sync, if any
+    pub fn since(&self) -> Option<u64> {
+        self.since
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&T> {
+        self.items.get(id)
+    }
+
+    /// All items ordered by id.
+    pub fn items(&self) -> Vec<&T> {
+        self.items.values().collect()
+    }
+
+    /// Items whose column has the value.
+    pub fn lookup(&self, column: &str, value: &str) -> Vec<&T> {
+        self.ids(column, value).iter().filter_map(|id| self.items.get(&**id)).collect()
+    }
+
+    /// Items whose column has all the values, e.g., all words of a search term.
+    pub fn lookup_all(&self, column: &str, values: &[&str]) -> Vec<&T> {
+        let mut values = values.iter();
+        let mut ids = match values.next() {
+            Some(value) => self.ids(column, value),
+            None => return Vec::new(),
+        };
+        for value in values {
+            ids = ids.intersection(&self.ids(column, value)).cloned().collect();
+        }
+
+        ids.iter().filter_map(|id| self.items.get(&**id)).collect()
+    }
+
+    /// Values of a column with their number of items, most frequent first.
+    pub fn counts(&self, column: &str) -> Vec<(&str, usize)> {
+        let mut counts: Vec<(&str, usize)> = self.columns.get(column)
+            .map(|values| values.iter().map(|(value, ids)| (&**value, ids.len())).collect())
+            .unwrap_or_default();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+        counts
+    }
+
+    /// Groups of items sharing a value of a column, e.g., articles with the same URL.
+    pub fn duplicates(&self, column: &str) -> Vec<(&str, Vec<&T>)> {
+        self.columns.get(column)
+            .map(|values| values.iter()
+                .filter(|&(_, ids)| ids.len() > 1)
+                .map(|(value, ids)| (&**value, ids.iter().filter_map(|id| self.items.get(&**id)).collect()))
+                .collect())
+            .unwrap_or_default()
+    }
+
+    /// Appends updated items and tombstones for deleted ids to the log and records the watermark of this sync. The
+    /// delta is added to the change log unless this is the first sync, which only establishes the baseline.
+    pub fn apply(&mut self, updated: Vec<T>, deleted: Vec<String>, since: u64) -> Result<Delta> {
+        let mut delta = Delta { at: since, ..Delta::default() };
+        {
+            let file = self.open_log()?;
+            let mut writer = BufWriter::new(file);
+            for item in updated {
+                let record = Record { id: item.id().to_string(), item: Some(item) };
+                write_record(&mut writer, &record)?;
+                if self.items.contains_key(&record.id) {
+                    delta.updated.push(record.id.clone());
+                } else {
+                    delta.added.push(record.id.clone());
+                }
+                self.insert(record.id, record.item.unwrap());
+            }
+            for id in deleted {
+                if !self.items.contains_key(&id) {
+                    continue;
+                }
+                write_record::<T, _>(&mut writer, &Record { id: id.clone(), item: None })?;
+                self.remove(&id);
+                delta.deleted.push(id);
+            }
+            writer.flush().chain_err(|| "Could not write index")?;
+        }
+        self.records += delta.added.len() + delta.updated.len() + delta.deleted.len();
+        if self.since.is_some() && !delta.is_empty() {
+            self.append_change(&delta)?;
+        }
+        self.since = Some(since);
+        self.state_cache.write(&State { since: self.since }).chain_err(|| "Could not write index state")?;
+
+        if self.records > 2 * self.items.len() + COMPACTION_SLACK {
+            self.compact()?;
+        }
+
+        Ok(delta)
+    }
+
+    /// Deltas of all syncs since the watermark `since`, oldest first.
+    pub fn changes(&self, since: u64) -> Result<Vec<Delta>> {
+        if !self.changes_path.exists() {
+            return Ok(Vec::new());
+        }
+        let file = File::open(&self.changes_path).chain_err(|| "Could not open change log")?;
+        let mut changes = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.chain_err(|| "Could not read change log")?;
+            // Like the index, the change log may end with an incomplete entry after a crash
+            if let Ok(delta) = serde_json::from_str::<Delta>(&line) {
+                if delta.at >= since {
+                    changes.push(delta);
+                }
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Removes all items and the watermark, so the next sync downloads everything. The change log is kept.
+    pub fn clear(&mut self) -> Result<()> {
+        self.items.clear();
+        self.columns.clear();
+        self.strings = Interner::new();
+        self.since = None;
+        self.state_cache.write(&State { since: None }).chain_err(|| "Could not write index state")?;
+        self.compact()
+    }
+
+    /// Rewrites the log with one record per live item.
+    fn compact(&mut self) -> Result<()> {
+        verboseln(format!("Compacting index '{}' from {} to {} records", self.path.display(), self.records, self.items.len()));
+        let tmp_path = self.path.with_extension("jsonl.tmp");
+        {
+            self.open_log()?;
+            let file = File::create(&tmp_path).chain_err(|| "Could not create index")?;
+            let mut writer = BufWriter::new(file);
+            for (id, item) in &self.items {
+                write_record(&mut writer, &Record { id: id.clone(), item: Some(item) })?;
+            }
+            writer.flush().chain_err(|| "Could not write index")?;
+        }
+        fs::rename(&tmp_path, &self.path).chain_err(|| "Could not replace index")?;
+        self.records = self.items.len();
+        self.strings.shrink();
+
+        Ok(())
+    }
+
+    fn replay(&mut self) -> Result<()> {
+        if !self.path.exists() {
+            return Ok(());
+        }
+        let file = File::open(&self.path).chain_err(|| "Could not open index")?;
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.chain_err(|| "Could not read index")?;
+            if line.is_empty() {
+                continue;
+            }
+            let record: Record<T> = match serde_json::from_str(&line) {
+                Ok(record) => record,
+                // The last record may be incomplete after a crash during a sync; the next sync writes it again
+                Err(_) => {
+                    warning(format!("Ignoring corrupt record {} of index '{}'", number + 1, self.path.display()));
+                    continue;
+                }
+            };
+            self.records += 1;
+            match record.item {
+                Some(item) => self.insert(record.id, item),
+                None => self.remove(&record.id),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn append_change(&self, delta: &Delta) -> Result<()> {
+        let json = serde_json::to_string(delta).chain_err(|| "Could not serialize change")?;
+        OpenOptions::new().create(true).append(true).open(&self.changes_path)
+            .and_then(|mut file| writeln!(file, "{}", json))
+            .chain_err(|| format!("Could not write change log '{}'", self.changes_path.display()))
+    }
+
+    fn open_log(&self) -> Result<File> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).chain_err(|| "Could not create cache directory")?;
+        }
+        OpenOptions::new().create(true).append(true).open(&self.path)
+            .chain_err(|| format!("Could not open index '{}'", self.path.display()))
+    }
+
+    fn insert(&mut self, id: String, item: T) {
+        self.remove(&id);
+        let interned_id = self.strings.intern(&id);
+        for (column, value) in item.columns() {
+            let value = self.strings.intern(&value);
+            self.columns.entry(column).or_insert_with(HashMap::new).entry(value).or_insert_with(BTreeSet::new).insert(interned_id.clone());
+        }
+        self.items.insert(id, item);
+    }
+
+    fn remove(&mut self, id: &str) {
+        if let Some(item) = self.items.remove(id) {
+            for (column, value) in item.columns() {
+                let now_empty = match self.columns.get_mut(column).and_then(|values| values.get_mut(value.as_str())) {
+                    Some(ids) => { ids.remove(id); ids.is_empty() }
+                    None => false,
+                };
+                if now_empty {
+                    self.columns.get_mut(column).map(|values| values.remove(value.as_str()));
+                }
+            }
+        }
+    }
+
+    fn ids(&self, column: &str, value: &str) -> BTreeSet<Rc<str>> {
+        self.columns.get(column).and_then(|values| values.get(value)).cloned().unwrap_or_default()
+    }
+}
+
+fn write_record<T: Serialize, W: Write>(writer: &mut W, record: &Record<T>) -> Result<()> {
+    let json = serde_json::to_string(record).chain_err(|| "Could not serialize index record")?;
+    writeln!(writer, "{}", json).chain_err(|| "Could not write index")
+}
+
+/// Lower-case words of a text for a word column; words shorter than two characters are skipped.
+pub fn words(text: &str) -> Vec<String> {
+    let words: BTreeSet<String> = text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() > 1)
+        .map(|word| word.to_lowercase())
+        .collect();
+
+    words.into_iter().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Item {
+        id: String,
+        title: String,
+    }
+
+    impl Indexed for Item {
+        fn id(&self) -> &str {
+            &self.id
+        }
+
+        fn columns(&self) -> Vec<(&'static str, String)> {
+            words(&self.title).into_iter().map(|word| ("word", word)).collect()
+        }
+    }
+
+    fn item(id: &str, title: &str) -> Item {
+        Item { id: id.to_string(), title: title.to_string() }
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn config(name: &str) -> Config {
+        testing::config(&format!("index-{}", name), "")
+    }
+
+    #[test]
+    fn test_apply_and_reopen() {
+        let config = config("reopen");
+        let mut index: Index<Item> = Index::open(&config, "test", "items").unwrap();
+        assert!(index.is_empty());
+        assert_eq!(index.since(), None);
+
+        let delta = index.apply(vec![item("1", "Rust 1.20 released"), item("2", "Rust in production")], vec![], 10).unwrap();
+        assert_eq!(delta, Delta { at: 10, added: ids(&["1", "2"]), updated: vec![], deleted: vec![] });
+        let delta = index.apply(vec![item("2", "Go in production")], vec!["1".to_string(), "3".to_string()], 20).unwrap();
+        assert_eq!(delta, Delta { at: 20, added: vec![], updated: ids(&["2"]), deleted: ids(&["1"]) });
+
+        let index: Index<Item> = Index::open(&config, "test", "items").unwrap();
+        assert_eq!(index.since(), Some(20));
+        assert_eq!(index.items(), vec![&item("2", "Go in production")]);
+        assert!(index.lookup("word", "rust").is_empty());
+        assert_eq!(index.lookup_all("word", &["go", "production"]).len(), 1);
+    }
+
+    #[test]
+    fn test_counts_and_duplicates() {
+        let config = config("counts");
+        let mut index: Index<Item> = Index::open(&config, "test", "items").unwrap();
+
+        index.apply(vec![item("1", "Rust news"), item("2", "Rust"), item("3", "News")], vec![], 1).unwrap();
+
+        assert_eq!(index.counts("word"), vec![("news", 2), ("rust", 2)]);
+        let duplicates = index.duplicates("word");
+        assert_eq!(duplicates.len(), 2);
+        assert_eq!(duplicates[0].1.len(), 2);
+    }
+
+    #[test]
+    fn test_compaction() {
+        let config = config("compaction");
+        let mut index: Index<Item> = Index::open(&config, "test", "items").unwrap();
+
+        for i in 0..(COMPACTION_SLACK as u64 + 10) {
+            index.apply(vec![item("1", &format!("version {}", i))], vec![], i).unwrap();
+        }
+
+        assert!(index.records <= COMPACTION_SLACK + 2);
+        let index: Index<Item> = Index::open(&config, "test", "items").unwrap();
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.lookup("word", "109").len(), 1);
+    }
+
+    #[test]
+    fn test_changes() {
+        let config = config("changes");
+        let mut index: Index<Item> = Index::open(&config, "test", "items").unwrap();
+
+        index.apply(vec![item("1", "Rust"), item("2", "Go")], vec![], 10).unwrap();
+        index.apply(vec![item("3", "Haskell")], vec![], 20).unwrap();
+        index.apply(vec![], vec![], 30).unwrap();
+        index.apply(vec![item("3", "Haskell 8")], vec!["1".to_string()], 40).unwrap();
+
+        let changes = index.changes(0).unwrap();
+        assert_eq!(changes.len(), 2, "first sync is the baseline and empty deltas are not logged");
+        assert_eq!(changes[0], Delta { at: 20, added: ids(&["3"]), updated: vec![], deleted: vec![] });
+        assert_eq!(index.changes(21).unwrap(), vec![Delta { at: 40, added: vec![], updated: ids(&["3"]), deleted: ids(&["1"]) }]);
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(words("Rust 1.20 released: a-b"), vec!["20", "released", "rust"]);
+    }
+}
@@ -9,24 +9,51 @@ use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 use toml;
 
+pub mod checkpoint;
+pub mod gc;
+pub mod index;
+pub mod queue;
+pub mod search;
+pub mod responses;
+
 error_chain! {}
 
 #[derive(Debug)]
 pub struct Cache {
     cache_dir: String,
-    filename: String,
+    /// File name without extension
+    name: String,
 }
 
 impl Cache {
     pub fn new(config: &Config, module_name: &str, cache_name: &str) -> Self {
-        let filename = format!("{}.{}.toml", module_name, cache_name);
+        let name = if let Some(ref account) = config.account {
+            format!("{}.{}.{}", module_name, account, cache_name)
+        } else {
+            format!("{}.{}", module_name, cache_name)
+        };
 
-        Cache { cache_dir: config.general.cache_dir.clone(), filename: filename }
+        Cache { cache_dir: config.general.cache_dir.clone(), name: name }
     }
 
-    pub fn load<T: Deserialize>(self) -> Result<T> {
+    /// Path of the cache file with the given extension; caches written by `write` use 'toml'.
+    pub fn path(&self, extension: &str) -> PathBuf {
         let mut path = PathBuf::from(&self.cache_dir);
-        path.push(&self.filename);
+        path.push(format!("{}.{}", self.name, extension));
+
+        path
+    }
+
+    /// Directory for caches of many files, e.g., article snapshots.
+    pub fn dir(&self) -> PathBuf {
+        let mut path = PathBuf::from(&self.cache_dir);
+        path.push(&self.name);
+
+        path
+    }
+
+    pub fn load<T: Deserialize>(&self) -> Result<T> {
+        let path = self.path("toml");
         let mut file = File::open(path.into_os_string()).chain_err(|| "Could not open cache file.")?;
         let mut content = String::new();
         file.read_to_string(&mut content).chain_err(|| "Could not read config file.")?;
@@ -44,6 +71,16 @@ impl Cache {
         Ok(())
     }
 
+    pub fn remove(self) -> Result<()> {
+        let path = self.path("toml");
+        if path.exists() {
+            fs::remove_file(&path).chain_err(|| "Could not remove cache file.")?;
+            verboseln(format!("Removed cache file '{}'", path.display()));
+        }
+
+        Ok(())
+    }
+
     fn check_or_create_cache_dir(&self) -> Result<()> {
         let path = Path::new(&self.cache_dir);
         if !path.is_dir() {
@@ -55,8 +92,7 @@ impl Cache {
     }
 
     fn write_toml_file(&self, toml: &str) -> Result<()> {
-        let mut path = PathBuf::from(&self.cache_dir);
-        path.push(&self.filename);
+        let path = self.path("toml");
 
         let mut file = File::create(path.into_os_string()).chain_err(|| "Failed to create cache file")?;
         file.write_all(&toml.as_bytes()).chain_err(|| "Failed to write data to cache file")?;
@@ -0,0 +1,197 @@
+//! Durable queue of a module's mutations that could not be sent, e.g., Pocket actions while the network is down. The
+//! queue is a file of JSON records, one per line, so queuing only appends and a crash loses at most the line being
+//! written. Replaying removes the records from the head of the queue; records queued meanwhile are kept. Writers take a
+//! lock file, so concurrent `rat` processes neither lose nor duplicate records.
+
+use super::{Cache, Result, ResultExt};
+use config::Config;
+use utils::time::unix_now;
+
+use serde_json::{self, Value};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// How long a writer waits for the lock before giving up
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const LOCK_RETRY_DELAY: Duration = Duration::from_millis(50);
+/// A lock older than this is left over by a crashed process; writers hold the lock for milliseconds only.
+const STALE_LOCK_AGE: Duration = Duration::from_secs(60);
+
+/// A queued mutation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Unix timestamp of queuing
+    pub queued: u64,
+    /// Provider-specific mutation, e.g., a Pocket send action
+    pub action: Value,
+}
+
+#[derive(Debug)]
+pub struct Queue {
+    path: PathBuf,
+}
+
+impl Queue {
+    /// Opens the queue `queue_name` of a module and the selected account.
+    pub fn open(config: &Config, module_name: &str, queue_name: &str) -> Self {
+        Queue { path: Cache::new(config, module_name, queue_name).path("ndjson") }
+    }
+
+    /// Appends the actions and syncs them to disk.
+    pub fn push(&self, actions: Vec<Value>) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).chain_err(|| "Could not create cache directory")?;
+        }
+        let _lock = Lock::acquire(&self.path)?;
+        let file = OpenOptions::new().create(true).append(true).open(&self.path)
+            .chain_err(|| format!("Could not open queue '{}'", self.path.display()))?;
+        let mut writer = BufWriter::new(file);
+        let queued = unix_now();
+        for action in actions {
+            let line = serde_json::to_string(&Entry { queued: queued, action: action }).chain_err(|| "Could not serialize queue entry")?;
+            writeln!(writer, "{}", line).chain_err(|| "Could not write queue")?;
+        }
+        writer.flush().chain_err(|| "Could not write queue")?;
+        writer.get_ref().sync_all().chain_err(|| "Could not sync queue")
+    }
+
+    /// All entries, oldest first; a missing queue is empty.
+    pub fn entries(&self) -> Result<Vec<Entry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let file = File::open(&self.path).chain_err(|| format!("Could not open queue '{}'", self.path.display()))?;
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.chain_err(|| "Could not read queue")?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            entries.push(serde_json::from_str(&line).chain_err(|| format!("Could not parse queue '{}'", self.path.display()))?);
+        }
+
+        Ok(entries)
+    }
+
+    /// Replaces the first `count` entries by `kept`, e.g., the replayed entries by the ones that failed again.
+    pub fn replace_head(&self, count: usize, kept: Vec<Entry>) -> Result<()> {
+        let _lock = Lock::acquire(&self.path)?;
+        let mut entries = kept;
+        entries.extend(self.entries()?.into_iter().skip(count));
+        if entries.is_empty() {
+            if self.path.exists() {
+                fs::remove_file(&self.path).chain_err(|| "Could not remove queue")?;
+            }
+            return Ok(());
+        }
+
+        let tmp_path = self.path.with_extension("ndjson.tmp");
+        {
+            let file = File::create(&tmp_path).chain_err(|| "Could not create queue")?;
+            let mut writer = BufWriter::new(file);
+            for entry in &entries {
+                let line = serde_json::to_string(entry).chain_err(|| "Could not serialize queue entry")?;
+                writeln!(writer, "{}", line).chain_err(|| "Could not write queue")?;
+            }
+            writer.flush().chain_err(|| "Could not write queue")?;
+            writer.get_ref().sync_all().chain_err(|| "Could not sync queue")?;
+        }
+        fs::rename(&tmp_path, &self.path).chain_err(|| "Could not replace queue")
+    }
+}
+
+/// Exclusive lock of a queue by a lock file next to it; released on drop.
+struct Lock {
+    path: PathBuf,
+}
+
+impl Lock {
+    fn acquire(queue_path: &Path) -> Result<Self> {
+        let path = queue_path.with_extension("ndjson.lock");
+        let started = SystemTime::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(Lock { path: path }),
+                Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    if Lock::is_stale(&path) {
+                        let _ = fs::remove_file(&path);
+                        continue;
+                    }
+                    if started.elapsed().map(|elapsed| elapsed >= LOCK_TIMEOUT).unwrap_or(false) {
+                        bail!("Could not lock queue; remove '{}' if no other rat process is running", path.display());
+                    }
+                    thread::sleep(LOCK_RETRY_DELAY);
+                }
+                Err(e) => return Err(e).chain_err(|| format!("Could not lock queue '{}'", queue_path.display())),
+            }
+        }
+    }
+
+    fn is_stale(path: &Path) -> bool {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .map(|age| age >= STALE_LOCK_AGE)
+            .unwrap_or(false)
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing;
+
+    fn config(name: &str) -> Config {
+        testing::config(&format!("queue-{}", name), "")
+    }
+
+    fn actions(entries: &[Entry]) -> Vec<String> {
+        entries.iter().map(|e| e.action.to_string()).collect()
+    }
+
+    #[test]
+    fn test_push_and_replace_head() {
+        let queue = Queue::open(&config("replace"), "test", "queue");
+        assert!(queue.entries().unwrap().is_empty());
+
+        queue.push(vec![Value::from("a"), Value::from("b")]).unwrap();
+        let replayed = queue.entries().unwrap();
+        // Queued while replaying
+        queue.push(vec![Value::from("c")]).unwrap();
+        queue.replace_head(replayed.len(), vec![replayed[1].clone()]).unwrap();
+
+        assert_eq!(actions(&queue.entries().unwrap()), vec!["\"b\"", "\"c\""]);
+        queue.replace_head(2, Vec::new()).unwrap();
+        assert!(!queue.path.exists());
+    }
+
+    #[test]
+    fn test_concurrent_pushes_and_replace_keep_all_entries() {
+        let queue = Queue::open(&config("concurrent"), "test", "queue");
+        queue.push(vec![Value::from("a")]).unwrap();
+        let path = queue.path.clone();
+
+        let pushers: Vec<_> = (0..4).map(|i| {
+            let queue = Queue { path: path.clone() };
+            thread::spawn(move || for j in 0..10 { queue.push(vec![Value::from(format!("{}-{}", i, j))]).unwrap() })
+        }).collect();
+        queue.replace_head(1, Vec::new()).unwrap();
+        for pusher in pushers {
+            pusher.join().unwrap();
+        }
+
+        assert_eq!(queue.entries().unwrap().len(), 40);
+        assert!(!path.with_extension("ndjson.lock").exists());
+    }
+}
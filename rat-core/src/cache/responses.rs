@@ -0,0 +1,185 @@
+//! Cache of HTTP responses of requests that only read data, e.g., Pocket lists. A response is cached per module by
+//! endpoint and a hash of the request, which includes the credentials, so accounts never share responses. Responses are
+//! used for `general.response_cache_ttl` after they have been received; without the setting, nothing is cached.
+//!
+//! If a request fails, an expired response is used nevertheless with a warning, so listings work offline. `--refresh`
+//! skips cached responses but caches the new ones, and `--no-cache` bypasses the cache completely. Commands that modify
+//! data of a module clear its responses, so the next listing shows the change.
+
+use super::Cache;
+use config::Config;
+use utils::console::*;
+use utils::time::parse_interval;
+
+use crypto::digest::Digest;
+use crypto::sha2::Sha256;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+
+const CACHE_NAME: &'static str = "responses";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Uses responses younger than the TTL
+    Use,
+    /// Sends every request, but caches the responses; set by `--refresh`
+    Refresh,
+    /// Neither uses nor caches responses; set by `--no-cache`
+    Bypass,
+}
+
+static MODE: AtomicUsize = AtomicUsize::new(0);
+
+pub fn set_mode(mode: Mode) {
+    MODE.store(mode as usize, Ordering::Relaxed);
+}
+
+pub fn mode() -> Mode {
+    match MODE.load(Ordering::Relaxed) {
+        1 => Mode::Refresh,
+        2 => Mode::Bypass,
+        _ => Mode::Use,
+    }
+}
+
+#[derive(Debug)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttl: Option<Duration>,
+    mode: Mode,
+}
+
+impl ResponseCache {
+    /// An invalid TTL disables the cache with a warning instead of failing the command.
+    pub fn new(config: &Config, module_name: &str) -> Self {
+        let ttl = config.general.response_cache_ttl.as_ref().and_then(|ttl| match parse_interval(ttl) {
+            Ok(ttl) => Some(ttl),
+            Err(_) => {
+                warning(format!("Ignoring invalid 'response_cache_ttl' '{}'; responses are not cached.", ttl));
+                None
+            }
+        });
+
+        ResponseCache { dir: Cache::new(config, module_name, CACHE_NAME).dir(), ttl: ttl, mode: mode() }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.ttl.is_some() && self.mode != Mode::Bypass
+    }
+
+    /// Returns the cached response of `request` to `endpoint` if it is younger than the TTL, or the response received by
+    /// `fetch` otherwise. If `fetch` fails, an expired response is returned with a warning instead of the error.
+    pub fn get_or_fetch<E, F>(&self, endpoint: &str, request: &str, fetch: F) -> ::std::result::Result<Vec<u8>, E>
+        where F: FnOnce() -> ::std::result::Result<Vec<u8>, E>
+    {
+        let ttl = match self.ttl {
+            Some(ttl) if self.mode != Mode::Bypass => ttl,
+            _ => return fetch(),
+        };
+        let path = self.path(endpoint, request);
+        let age = age(&path);
+        if self.mode == Mode::Use && age.map(|age| age <= ttl).unwrap_or(false) {
+            if let Some(body) = read(&path) {
+                verboseln(format!("Using cached response '{}'", path.display()));
+                return Ok(body);
+            }
+        }
+
+        match fetch() {
+            Ok(body) => {
+                if let Err(e) = self.write(&path, &body) {
+                    verboseln(format!("Could not cache response '{}': {}", path.display(), e));
+                }
+                Ok(body)
+            }
+            Err(e) => match (age, read(&path)) {
+                (Some(age), Some(body)) => {
+                    warning(format!("Request failed; using the cached response from {} seconds ago.", age.as_secs()));
+                    Ok(body)
+                }
+                _ => Err(e),
+            },
+        }
+    }
+
+    /// Removes all cached responses of the module.
+    pub fn clear(&self) {
+        if self.dir.is_dir() {
+            match fs::remove_dir_all(&self.dir) {
+                Ok(_) => verboseln(format!("Cleared cached responses '{}'", self.dir.display())),
+                Err(e) => warning(format!("Could not clear cached responses '{}': {}", self.dir.display(), e)),
+            }
+        }
+    }
+
+    fn path(&self, endpoint: &str, request: &str) -> PathBuf {
+        let mut sha = Sha256::new();
+        sha.input_str(endpoint);
+        sha.input_str("\n");
+        sha.input_str(request);
+        let name: String = endpoint.chars().map(|c| if c.is_alphanumeric() { c } else { '_' }).collect();
+
+        self.dir.join(format!("{}-{}.json", name, &sha.result_str()[..32]))
+    }
+
+    fn write(&self, path: &PathBuf, body: &[u8]) -> ::std::io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let mut file = File::create(path)?;
+        file.write_all(body)
+    }
+}
+
+fn age(path: &PathBuf) -> Option<Duration> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    // A modification time in the future counts as just received
+    Some(SystemTime::now().duration_since(modified).unwrap_or_else(|_| Duration::from_secs(0)))
+}
+
+fn read(path: &PathBuf) -> Option<Vec<u8>> {
+    let mut body = Vec::new();
+    File::open(path).and_then(|mut f| f.read_to_end(&mut body)).ok().map(|_| body)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing::ConfigBuilder;
+
+    fn config(name: &str, ttl: &str) -> Config {
+        ConfigBuilder::new(name).general("response_cache_ttl", &format!("'{}'", ttl)).build()
+    }
+
+    #[test]
+    fn test_get_or_fetch() {
+        let cache = ResponseCache::new(&config("response-cache", "1h"), "pocket");
+
+        let first: Result<Vec<u8>, ()> = cache.get_or_fetch("get", "request", || Ok(b"first".to_vec()));
+        let cached: Result<Vec<u8>, ()> = cache.get_or_fetch("get", "request", || Ok(b"second".to_vec()));
+        let other: Result<Vec<u8>, ()> = cache.get_or_fetch("get", "other request", || Ok(b"other".to_vec()));
+
+        assert_eq!(first, Ok(b"first".to_vec()));
+        assert_eq!(cached, Ok(b"first".to_vec()));
+        assert_eq!(other, Ok(b"other".to_vec()));
+
+        cache.clear();
+        let refetched: Result<Vec<u8>, ()> = cache.get_or_fetch("get", "request", || Ok(b"third".to_vec()));
+        assert_eq!(refetched, Ok(b"third".to_vec()));
+    }
+
+    #[test]
+    fn test_expired_response_is_used_if_fetch_fails() {
+        let cache = ResponseCache::new(&config("response-cache-expired", "0s"), "pocket");
+
+        let _: Result<Vec<u8>, ()> = cache.get_or_fetch("get", "request", || Ok(b"first".to_vec()));
+        let fetched: Result<Vec<u8>, ()> = cache.get_or_fetch("get", "request", || Ok(b"second".to_vec()));
+        let offline: Result<Vec<u8>, &str> = cache.get_or_fetch("get", "request", || Err("offline"));
+        let unknown: Result<Vec<u8>, &str> = cache.get_or_fetch("get", "unknown request", || Err("offline"));
+
+        assert_eq!(fetched, Ok(b"second".to_vec()));
+        assert_eq!(offline, Ok(b"second".to_vec()));
+        assert_eq!(unknown, Err("offline"));
+    }
+}
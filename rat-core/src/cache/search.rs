@@ -0,0 +1,299 @@
+//! Local full-text search of a module's items, e.g., Pocket articles with their text. The index is an inverted index:
+//! for every word, the items containing it and how often, weighted by the field the word occurs in. Queries are answered
+//! without contacting the provider and ranked by BM25, the ranking function of most search engines.
+//!
+//! The index is kept as one JSON file in the cache directory. Every item records a version, e.g., the time of its last
+//! change, so an update only re-indexes items that changed since.
+
+use super::{Cache, Result, ResultExt};
+use super::index::words;
+use config::Config;
+
+use serde_json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::PathBuf;
+
+/// Saturation of the term frequency; BM25's usual value
+const K1: f64 = 1.2;
+
+/// Weight of the length normalization; BM25's usual value
+const B: f64 = 0.75;
+
+/// An indexed item
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    /// Provider-specific version, e.g., the time of the last change; cf. `SearchIndex::is_current`
+    pub version: String,
+    /// Whether the item's text is indexed besides its metadata
+    pub text: bool,
+    /// Weighted number of words
+    length: u32,
+    /// Distinct words, to remove the document from the postings
+    words: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Data {
+    documents: BTreeMap<String, Document>,
+    /// Weighted term frequency of each word by item id
+    postings: BTreeMap<String, BTreeMap<String, u32>>,
+}
+
+/// A document matching a query
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Hit {
+    pub id: String,
+    pub score: f64,
+}
+
+#[derive(Debug)]
+pub struct SearchIndex {
+    path: PathBuf,
+    data: Data,
+}
+
+impl SearchIndex {
+    /// Opens the search index `index_name` of a module; an index that has never been saved is empty.
+    pub fn open(config: &Config, module_name: &str, index_name: &str) -> Result<Self> {
+        let path = Cache::new(config, module_name, index_name).path("json");
+        let data = if path.exists() {
+            let file = File::open(&path).chain_err(|| format!("Could not open search index '{}'", path.display()))?;
+            serde_json::from_reader(BufReader::new(file))
+                .chain_err(|| format!("Could not read search index '{}'; please rebuild it", path.display()))?
+        } else {
+            Data::default()
+        };
+
+        Ok(SearchIndex { path: path, data: data })
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.documents.is_empty()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Document> {
+        self.data.documents.get(id)
+    }
+
+    /// Ids of all documents in order.
+    pub fn ids(&self) -> Vec<&str> {
+        self.data.documents.keys().map(|id| id.as_str()).collect()
+    }
+
+    /// Whether the document `id` is indexed in `version`, and with its text if `text` is requested.
+    pub fn is_current(&self, id: &str, version: &str, text: bool) -> bool {
+        self.get(id).map(|d| d.version == version && (d.text || !text)).unwrap_or(false)
+    }
+
+    /// Indexes the fields of a document, replacing the document if it is indexed already. Every field is a text and its
+    /// weight, e.g., 3 for a title and 1 for the body, so matches in titles rank higher.
+    pub fn insert(&mut self, id: &str, version: &str, fields: &[(&str, u32)], text: bool) {
+        self.remove(id);
+        let mut frequencies: BTreeMap<String, u32> = BTreeMap::new();
+        for &(field, weight) in fields {
+            for word in all_words(field) {
+                *frequencies.entry(word).or_insert(0) += weight;
+            }
+        }
+        let length = frequencies.values().sum();
+        for (word, frequency) in &frequencies {
+            self.data.postings.entry(word.clone()).or_insert_with(BTreeMap::new).insert(id.to_string(), *frequency);
+        }
+        let document = Document { version: version.to_string(), text: text, length: length, words: frequencies.into_iter().map(|(w, _)| w).collect() };
+        self.data.documents.insert(id.to_string(), document);
+    }
+
+    pub fn remove(&mut self, id: &str) -> bool {
+        let document = match self.data.documents.remove(id) {
+            Some(document) => document,
+            None => return false,
+        };
+        for word in &document.words {
+            let now_empty = match self.data.postings.get_mut(word) {
+                Some(ids) => { ids.remove(id); ids.is_empty() }
+                None => false,
+            };
+            if now_empty {
+                self.data.postings.remove(word);
+            }
+        }
+
+        true
+    }
+
+    /// Documents containing all words of `query`, best matches first.
+    pub fn search(&self, query: &str) -> Vec<Hit> {
+        let terms = words(query);
+        if terms.is_empty() || self.is_empty() {
+            return Vec::new();
+        }
+        let mut postings = Vec::new();
+        for term in &terms {
+            match self.data.postings.get(term) {
+                Some(ids) => postings.push(ids),
+                None => return Vec::new(),
+            }
+        }
+
+        let count = self.len() as f64;
+        let average_length = self.data.documents.values().map(|d| d.length as f64).sum::<f64>() / count;
+        let mut candidates: BTreeSet<&String> = postings[0].keys().collect();
+        for ids in &postings[1..] {
+            candidates = candidates.into_iter().filter(|id| ids.contains_key(*id)).collect();
+        }
+        let mut hits: Vec<Hit> = candidates.into_iter().map(|id| {
+            let length = self.data.documents.get(id).map(|d| d.length as f64).unwrap_or(average_length);
+            let score = postings.iter().map(|ids| {
+                let frequency = ids[id] as f64;
+                let found_in = ids.len() as f64;
+                let idf = (1.0 + (count - found_in + 0.5) / (found_in + 0.5)).ln();
+                idf * frequency * (K1 + 1.0) / (frequency + K1 * (1.0 - B + B * length / average_length))
+            }).sum();
+            Hit { id: id.clone(), score: score }
+        }).collect();
+        hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(::std::cmp::Ordering::Equal).then(a.id.cmp(&b.id)));
+
+        hits
+    }
+
+    /// Writes the index; a crash while writing keeps the previous version.
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).chain_err(|| "Could not create cache directory")?;
+        }
+        let tmp_path = self.path.with_extension("json.tmp");
+        {
+            let file = File::create(&tmp_path).chain_err(|| "Could not create search index")?;
+            let mut writer = BufWriter::new(file);
+            serde_json::to_writer(&mut writer, &self.data).chain_err(|| "Could not serialize search index")?;
+            writer.flush().chain_err(|| "Could not write search index")?;
+        }
+        fs::rename(&tmp_path, &self.path).chain_err(|| "Could not replace search index")
+    }
+}
+
+/// Lower-case words of a text including repetitions; cf. `index::words`.
+fn all_words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() > 1)
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+/// Returns the part of `text` around the first word of `query` it contains, at most about `max_chars` characters, with
+/// all words of the query passed through `mark`, e.g., to print them in bold; `None` if the text contains none of them.
+pub fn highlight<F: Fn(&str) -> String>(text: &str, query: &str, max_chars: usize, mark: F) -> Option<String> {
+    let terms = words(query);
+    let is_term = |word: &str| terms.iter().any(|t| *t == word.to_lowercase());
+    let tokens = tokens(text);
+    let first = tokens.iter().position(|&(is_word, token)| is_word && is_term(token))?;
+
+    // Some context before the first match, then as much as fits
+    let mut start = first;
+    let mut chars = 0;
+    while start > 0 && chars + tokens[start - 1].1.chars().count() <= max_chars / 2 {
+        start -= 1;
+        chars += tokens[start].1.chars().count();
+    }
+    let mut snippet = String::new();
+    let mut chars = 0;
+    let mut end = start;
+    while end < tokens.len() && chars < max_chars {
+        let (is_word, token) = tokens[end];
+        chars += token.chars().count();
+        if is_word && is_term(token) {
+            snippet.push_str(&mark(token));
+        } else {
+            snippet.push_str(token);
+        }
+        end += 1;
+    }
+
+    Some(format!("{}{}{}", if start > 0 { "…" } else { "" }, snippet.trim(), if end < tokens.len() { "…" } else { "" }))
+}
+
+/// Splits a text into words and what is between them, so the tokens concatenated are the text.
+fn tokens(text: &str) -> Vec<(bool, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut in_word = false;
+    for (i, c) in text.char_indices() {
+        if c.is_alphanumeric() != in_word {
+            if i > start {
+                tokens.push((in_word, &text[start..i]));
+            }
+            start = i;
+            in_word = !in_word;
+        }
+    }
+    if start < text.len() {
+        tokens.push((in_word, &text[start..]));
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing;
+
+    fn config(name: &str) -> Config {
+        testing::config(&format!("search-{}", name), "")
+    }
+
+    fn ids(hits: &[Hit]) -> Vec<&str> {
+        hits.iter().map(|h| h.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_search_ranks_and_requires_all_words() {
+        let mut index = SearchIndex::open(&config("rank"), "test", "search").unwrap();
+        index.insert("1", "v1", &[("Rust in production", 3), ("Some text about the Go runtime", 1)], true);
+        index.insert("2", "v1", &[("Go in production", 3), ("Rust is mentioned once", 1)], true);
+        index.insert("3", "v1", &[("Haskell", 3), ("Nothing in common", 1)], false);
+
+        assert_eq!(ids(&index.search("rust")), vec!["1", "2"]);
+        assert_eq!(ids(&index.search("go production")), vec!["2", "1"]);
+        assert!(index.search("rust haskell").is_empty());
+        assert!(index.search("").is_empty());
+    }
+
+    #[test]
+    fn test_insert_replaces_and_save_persists() {
+        let config = config("persist");
+        let mut index = SearchIndex::open(&config, "test", "search").unwrap();
+        index.insert("1", "v1", &[("Rust", 3)], false);
+        index.insert("1", "v2", &[("Go", 3)], true);
+        index.insert("2", "v1", &[("Go", 3)], false);
+        assert!(index.remove("2"));
+        index.save().unwrap();
+
+        let index = SearchIndex::open(&config, "test", "search").unwrap();
+
+        assert_eq!(index.ids(), vec!["1"]);
+        assert!(index.search("rust").is_empty());
+        assert_eq!(ids(&index.search("go")), vec!["1"]);
+        assert!(index.is_current("1", "v2", true));
+        assert!(!index.is_current("1", "v1", false));
+        assert_eq!(index.data.postings.keys().collect::<Vec<_>>(), vec!["go"]);
+    }
+
+    #[test]
+    fn test_highlight() {
+        let text = "The Rust compiler is written in Rust, and rustc compiles itself.";
+
+        assert_eq!(highlight(text, "rust", 100, |w| format!("*{}*", w)),
+                   Some("The *Rust* compiler is written in *Rust*, and rustc compiles itself.".to_string()));
+        assert_eq!(highlight(text, "compiles", 20, |w| format!("*{}*", w)),
+                   Some("…and rustc *compiles* itself…".to_string()));
+        assert_eq!(highlight(text, "written", 10, |w| w.to_uppercase()), Some("…is WRITTEN…".to_string()));
+        assert_eq!(highlight(text, "go", 100, |w| w.to_string()), None);
+    }
+}
@@ -1,8 +1,7 @@
-pub use self::collections::search_collections;
+pub use self::collections::{search_collections, search_collections_to};
 pub use self::delete::delete_documents;
 pub use self::download::download_document;
-pub use self::refresh_token::refresh_token;
-pub use self::search::search_documents;
+pub use self::search::{search_documents, search_documents_to};
 pub use self::upload::upload_document;
 
 use hyper::Client;
@@ -10,39 +9,30 @@ use hyper::method::Method;
 use hyper::client::RequestBuilder;
 use hyper::header::{Authorization, Bearer};
 
-error_chain! {}
-
-fn prepare_request<'a, 'b>(client: &'a Client, method: Method, url: &'b str, token: String) -> Result<RequestBuilder<'a>> {
-    let request = match method {
+fn prepare_request<'a, 'b>(client: &'a Client, method: Method, url: &'b str, token: String) -> RequestBuilder<'a> {
+    match method {
         Method::Get => client.get(url),
         Method::Post => client.post(url),
         _ => unimplemented!(),
-    }.header(Authorization(Bearer { token: token }));
-
-    Ok(request)
+    }.header(Authorization(Bearer { token: token }))
 }
 
 pub mod collections {
     use super::prepare_request;
-    use net::http::tls_client;
+    use errors::*;
+    use net::http::{check_status, tls_client};
+    use net::oauth::oauth2::TokenSession;
 
     use utils::console::*;
+    use utils::output::copy_json;
 
+    use hyper::client::Response;
     use hyper::method::Method;
     use serde_json;
     use serde_urlencoded;
-    use std::io::Read;
+    use std::io::{Read, Write};
     use std::str;
 
-    error_chain! {
-        errors {
-            HttpCollectionCallFailed {
-                description("failed to make http collection call")
-                display("failed to make http collection call")
-            }
-        }
-    }
-
     #[derive(Deserialize, Serialize, Debug)]
     pub struct CollectionsResult {
         pub collections: Vec<Collection>,
@@ -64,13 +54,14 @@ pub mod collections {
     }
 
     pub fn search_collections(
-        access_token: &str,
+        session: &TokenSession,
         name: Option<&str>,
         include_public: bool,
         filter: Option<&str>
     ) -> Result<String> {
-        let json = do_search_collections(access_token, name, include_public)
-            .chain_err(|| ErrorKind::HttpCollectionCallFailed);
+        let json = do_search_collections(session, name, include_public)
+            .and_then(read_body)
+            .chain_err(|| "failed to make http collection call");
         if filter.is_none() {
             return json;
         }
@@ -83,11 +74,23 @@ pub mod collections {
         Ok(json)
     }
 
+    /// Copies the unfiltered response body to `writer` as it arrives, without decoding it first.
+    pub fn search_collections_to<W: Write>(
+        session: &TokenSession,
+        name: Option<&str>,
+        include_public: bool,
+        writer: W
+    ) -> Result<u64> {
+        let mut response = do_search_collections(session, name, include_public)
+            .chain_err(|| "failed to make http collection call")?;
+        copy_json(&mut response, writer).chain_err(|| "Failed to read server response")
+    }
+
     fn do_search_collections(
-        access_token: &str,
+        session: &TokenSession,
         name: Option<&str>,
         include_public: bool
-    ) -> Result<String> {
+    ) -> Result<Response> {
         let mut parameters = vec![
             ("include-public", format!("{}", include_public)),
         ];
@@ -100,11 +103,15 @@ pub mod collections {
         verboseln(format!("collections search = {}", url));
 
         let client = tls_client().chain_err(|| "Failed to create HTTP client")?;
-        let request = prepare_request(&client, Method::Get, &url, access_token.to_string())
-            .chain_err(|| "Failed to create CenterDevice client")?;
+        let mut response = session
+            .send_idempotent(|token| prepare_request(&client, Method::Get, &url, token.to_string()).send())
+            .chain_err(|| "Failed to finish http request")?;
+        check_status(&mut response)?;
 
-        let mut response = request.send().chain_err(|| "Failed to finish http request")?;
+        Ok(response)
+    }
 
+    fn read_body(mut response: Response) -> Result<String> {
         let mut body = Vec::new();
         response.read_to_end(&mut body).chain_err(|| "Failed to read server response")?;
         let response_body = String::from_utf8_lossy(&body).to_string();
@@ -115,7 +122,9 @@ pub mod collections {
 
 mod delete {
     use super::prepare_request;
-    use net::http::tls_client;
+    use errors::*;
+    use net::http::{check_status, tls_client};
+    use net::oauth::oauth2::TokenSession;
 
     use hyper::method::Method;
     use hyper::header::{ContentType, Accept, qitem};
@@ -123,15 +132,6 @@ mod delete {
     use std::io::Read;
     use std::str;
 
-    error_chain! {
-        errors {
-            HttpUploadCallFailed {
-                description("failed to make http delete call")
-                display("failed to make http delete call")
-            }
-        }
-    }
-
     #[derive(Serialize, Debug)]
     struct DeleteAction<'a> {
         action: &'a str,
@@ -150,23 +150,24 @@ mod delete {
         }
     }
 
-    pub fn delete_documents(access_token: &str, document_ids: Vec<&str>) -> Result<String> {
-        do_delete_documents(access_token, document_ids).chain_err(|| ErrorKind::HttpUploadCallFailed)
+    pub fn delete_documents(session: &TokenSession, document_ids: Vec<&str>) -> Result<String> {
+        do_delete_documents(session, document_ids).chain_err(|| "failed to make http delete call")
     }
 
-    fn do_delete_documents(access_token: &str, document_ids: Vec<&str>) -> Result<String> {
+    fn do_delete_documents(session: &TokenSession, document_ids: Vec<&str>) -> Result<String> {
         let delete = DeleteAction::new(document_ids);
         let delete_json = serde_json::to_string(&delete).chain_err(|| "JSON serialization failed")?;
 
         let url = "https://api.centerdevice.de/v2/documents";
         let client = tls_client().chain_err(|| "Failed to create HTTP client")?;
-        let request = prepare_request(&client, Method::Post, url, access_token.to_string())
-            .chain_err(|| "Failed to create CenterDevice client")?
-            .header(ContentType(mime!(Application / Json)))
-            .header(Accept(vec![qitem(mime!(Application/ Json; Charset = Utf8))]))
-            .body(&delete_json);
-
-        let mut response = request.send().chain_err(|| "Failed to finish http request")?;
+        let mut response = session
+            .send(|token| prepare_request(&client, Method::Post, url, token.to_string())
+                .header(ContentType(mime!(Application / Json)))
+                .header(Accept(vec![qitem(mime!(Application/ Json; Charset = Utf8))]))
+                .body(&delete_json)
+                .send())
+            .chain_err(|| "Failed to finish http request")?;
+        check_status(&mut response)?;
 
         let mut body = Vec::new();
         response.read_to_end(&mut body).chain_err(|| "Failed to read server response")?;
@@ -174,66 +175,73 @@ mod delete {
 
         Ok(response_body)
     }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use utils::json::*;
+
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn test_delete_action_serialization(ref ids in prop::collection::vec("[0-9a-f-]{36}", 1..10)) {
+                let action = DeleteAction::new(ids.iter().map(|id| id.as_str()).collect());
+                let json = to_value(&action);
+
+                prop_assert_eq!(keys(&json), sorted(&["action", "params"]));
+                prop_assert_eq!(json["action"].as_str(), Some("delete"));
+                prop_assert_eq!(keys(&json["params"]), sorted(&["documents"]));
+                let documents: Vec<&str> = json["params"]["documents"].as_array().unwrap().iter()
+                    .map(|id| id.as_str().unwrap())
+                    .collect();
+                prop_assert_eq!(documents, ids.iter().map(|id| id.as_str()).collect::<Vec<_>>());
+            }
+        }
+    }
 }
 
 mod download {
     use super::prepare_request;
-    use net::http::tls_client;
+    use errors::*;
+    use net::http::{check_status, tls_client};
+    use net::oauth::oauth2::TokenSession;
     use utils::io::ReadWithProgress;
     use utils::console::*;
 
     use hyper::client::Response;
-    use hyper::header::{ContentType, ContentDisposition, ContentLength, DispositionParam, Authorization, Bearer};
+    use hyper::header::{ContentType, ContentDisposition, ContentLength, DispositionParam};
     use hyper::method::Method;
     use std::fs::File;
     use std::io;
     use std::io::{Read, Write};
     use std::str;
 
-    error_chain! {
-        errors {
-            HttpDownloadCallFailed {
-                description("failed to make http download call")
-                display("failed to make http download call")
-            }
-
-            FailedSetFilename {
-                description("failed set filename from cli parameter and content disposition header")
-                display("failed set filename from cli parameter and content disposition header")
-            }
-
-            FailedContentLength {
-                description("failed get content length from response header")
-                display("failed get content length from response header")
-            }
-        }
-    }
-
 
     pub fn download_document<T: FnMut(usize, usize) -> ()>(
-        access_token: &str,
+        session: &TokenSession,
         filename: Option<&str>,
         document_id: &str,
         progress: Option<T>,
     ) -> Result<()> {
-        do_download_document(access_token, filename, document_id, progress)
-            .chain_err(|| ErrorKind::HttpDownloadCallFailed)
+        do_download_document(session, filename, document_id, progress)
+            .chain_err(|| "failed to make http download call")
     }
 
     fn do_download_document<T: FnMut(usize, usize) -> ()>(
-        access_token: &str,
+        session: &TokenSession,
         filename: Option<&str>,
         document_id: &str,
         progress: Option<T>,
     ) -> Result<()> {
         let url = format!("https://api.centerdevice.de/v2/document/{}", document_id);
         let client = tls_client().chain_err(|| "Failed to create HTTP client")?;
-        let request = prepare_request(&client, Method::Get, &url, access_token.to_string())
-            .chain_err(|| "Failed to create CenterDevice client")?
-            .header(Authorization(Bearer { token: access_token.to_string() }))
-            .header(ContentType(mime!(Star/Star)));
-
-        let mut response = request.send().chain_err(|| "Failed to finish http request")?;
+        let mut response = session
+            .send_idempotent(|token| prepare_request(&client, Method::Get, &url, token.to_string())
+                .header(ContentType(mime!(Star/Star)))
+                .send())
+            .chain_err(|| "Failed to finish http request")?;
+        check_status(&mut response)?;
 
         let filename = get_filename(filename, &response)?;
         let size = get_content_length(&response)? as usize;
@@ -255,15 +263,15 @@ mod download {
         } else {
             let mut _filename = None;
             let content_disposition: &ContentDisposition = response.headers.get()
-                .ok_or(ErrorKind::FailedSetFilename)?;
+                .ok_or("failed set filename from cli parameter and content disposition header")?;
             for cp in &content_disposition.parameters {
                 if let DispositionParam::Filename(_, _, ref f) = *cp {
                     _filename = Some(
-                        str::from_utf8(f).chain_err(|| ErrorKind::FailedSetFilename)?);
+                        str::from_utf8(f).chain_err(|| "failed set filename from cli parameter and content disposition header")?);
                     break;
                 }
             }
-            _filename.ok_or(ErrorKind::FailedSetFilename)?
+            _filename.ok_or("failed set filename from cli parameter and content disposition header")?
         };
 
         Ok(filename.to_string())
@@ -271,7 +279,7 @@ mod download {
 
     fn get_content_length(response: &Response) -> Result<u64> {
         let content_length: &ContentLength = response.headers.get()
-            .ok_or(ErrorKind::FailedContentLength)?;
+            .ok_or("failed get content length from response header")?;
         let &ContentLength(size) = content_length;
         Ok(size)
     }
@@ -296,68 +304,23 @@ mod download {
     }
 }
 
-mod refresh_token {
-    use net::http::tls_client;
-
-    use hyper::header::{ContentType, Authorization, Basic};
-    use std::io::Read;
-    use std::str;
-
-    error_chain! {
-        errors {
-            HttpUploadCallFailed {
-                description("failed to make http refresh access token call")
-                display("failed to make http refresh access token call")
-            }
-        }
-    }
-
-    pub fn refresh_token(refresh_token: &str, client_id: &str, client_secret: &str) -> Result<String> {
-        do_refresh_token(refresh_token, client_id, client_secret).chain_err(|| ErrorKind::HttpUploadCallFailed)
-    }
-
-    fn do_refresh_token(refresh_token: &str, client_id: &str, client_secret: &str) -> Result<String> {
-        let body = format!("grant_type=refresh_token&refresh_token={}", refresh_token);
-        let url = "https://auth.centerdevice.de/token";
-        let client = tls_client().chain_err(|| "Could not create TLS client")?;
-        let mut response = client
-            .post(url)
-            .header(Authorization(Basic { username: client_id.to_string(), password: Some(client_secret.to_string()) }))
-            .header(ContentType(mime!(Application / WwwFormUrlEncoded)))
-            .body(&body)
-            .send()
-            .chain_err(|| "Failed to finish HTTP request")?;
-
-        let mut body = Vec::new();
-        response.read_to_end(&mut body).chain_err(|| "Failed to read HTTP response")?;
-        let response_body = String::from_utf8_lossy(&body).to_string();
-
-        Ok(response_body)
-    }
-}
-
 pub mod search {
     use super::prepare_request;
-    use net::http::tls_client;
+    use errors::*;
+    use net::http::{check_status, tls_client};
+    use net::oauth::oauth2::TokenSession;
 
     use utils::console::*;
+    use utils::output::copy_json;
 
+    use hyper::client::Response;
     use hyper::header::{ContentType, Accept, qitem};
     use hyper::method::Method;
     use serde::Serialize;
     use serde_json;
-    use std::io::Read;
+    use std::io::{Read, Write};
     use std::str;
 
-    error_chain! {
-        errors {
-            HttpSearchCallFailed {
-                description("failed to make http search call")
-                display("failed to make http search call")
-            }
-        }
-    }
-
     #[derive(PartialEq, Debug)]
     pub enum NamedSearches {
         None,
@@ -414,21 +377,35 @@ pub mod search {
     }
 
     pub fn search_documents(
-        access_token: &str,
+        session: &TokenSession,
         filenames: Option<Vec<&str>>,
         tags: Option<Vec<&str>>,
         fulltext: Option<&str>,
         named_searches: NamedSearches) -> Result<String> {
-        do_search_documents(access_token, filenames, tags, fulltext, named_searches)
-            .chain_err(|| ErrorKind::HttpSearchCallFailed)
+        do_search_documents(session, filenames, tags, fulltext, named_searches)
+            .and_then(read_body)
+            .chain_err(|| "failed to make http search call")
+    }
+
+    /// Copies the response body to `writer` as it arrives, without decoding it first.
+    pub fn search_documents_to<W: Write>(
+        session: &TokenSession,
+        filenames: Option<Vec<&str>>,
+        tags: Option<Vec<&str>>,
+        fulltext: Option<&str>,
+        named_searches: NamedSearches,
+        writer: W) -> Result<u64> {
+        let mut response = do_search_documents(session, filenames, tags, fulltext, named_searches)
+            .chain_err(|| "failed to make http search call")?;
+        copy_json(&mut response, writer).chain_err(|| "Failed to read server response")
     }
 
     fn do_search_documents(
-        access_token: &str,
+        session: &TokenSession,
         filenames: Option<Vec<&str>>,
         tags: Option<Vec<&str>>,
         fulltext: Option<&str>,
-        named_searches: NamedSearches) -> Result<String> {
+        named_searches: NamedSearches) -> Result<Response> {
         let named: Option<Vec<Named<Include>>> = match named_searches {
             NamedSearches::None => None,
             NamedSearches::PublicCollections => {
@@ -444,33 +421,96 @@ pub mod search {
 
         let url = "https://api.centerdevice.de/v2/documents";
         let client = tls_client().chain_err(|| "Failed to create HTTP client")?;
-        let request = prepare_request(&client, Method::Post, url, access_token.to_string())
-            .chain_err(|| "Failed to create CenterDevice client")?
-            .header(ContentType(mime!(Application / Json)))
-            .header(Accept(vec![qitem(mime!(Application/ Json; Charset = Utf8))]))
-            .body(&search_json);
+        let mut response = session
+            .send_idempotent(|token| prepare_request(&client, Method::Post, url, token.to_string())
+                .header(ContentType(mime!(Application / Json)))
+                .header(Accept(vec![qitem(mime!(Application/ Json; Charset = Utf8))]))
+                .body(&search_json)
+                .send())
+            .chain_err(|| "Failed to finish http request")?;
+        check_status(&mut response)?;
 
-        let mut response = request.send().chain_err(|| "Failed to finish http request")?;
+        Ok(response)
+    }
 
+    fn read_body(mut response: Response) -> Result<String> {
         let mut body = Vec::new();
         response.read_to_end(&mut body).chain_err(|| "Failed to read server response")?;
         let response_body = String::from_utf8_lossy(&body).to_string();
 
         Ok(response_body)
     }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use utils::json::*;
+
+        use proptest::prelude::*;
+
+        fn as_strs(values: &Option<Vec<String>>) -> Option<Vec<&str>> {
+            values.as_ref().map(|v| v.iter().map(|s| s.as_str()).collect())
+        }
+
+        proptest! {
+            #[test]
+            fn test_search_serialization(
+                ref filenames in prop::option::of(prop::collection::vec("[a-z0-9_.]{1,20}", 1..5)),
+                ref tags in prop::option::of(prop::collection::vec("[a-z0-9-]{1,10}", 1..5)),
+                ref fulltext in prop::option::of(".{0,30}"),
+                public_collections in prop::bool::ANY
+            ) {
+                let named = if public_collections {
+                    Some(vec![Named { name: "public-collections", params: Include { include: true } }])
+                } else {
+                    None
+                };
+                let search = Search::new(as_strs(filenames), as_strs(tags), fulltext.as_ref().map(|s| s.as_str()), named);
+                let json = to_value(&search);
+
+                prop_assert_eq!(keys(&json), sorted(&["action", "params"]));
+                prop_assert_eq!(json["action"].as_str(), Some("search"));
+
+                let params = &json["params"];
+                let mut expected = vec!["query", "filter"];
+                if public_collections { expected.push("named") }
+                prop_assert_eq!(keys(params), sorted(&expected));
+
+                prop_assert_eq!(params["query"]["text"].as_str(), fulltext.as_ref().map(|s| s.as_str()));
+                prop_assert_eq!(keys(&params["query"]).len(), fulltext.iter().count());
+
+                let mut expected = Vec::new();
+                if filenames.is_some() { expected.push("filenames") }
+                if tags.is_some() { expected.push("tags") }
+                prop_assert_eq!(keys(&params["filter"]), sorted(&expected));
+                if let Some(ref filenames) = *filenames {
+                    prop_assert_eq!(params["filter"]["filenames"].as_array().unwrap().len(), filenames.len());
+                }
+                if let Some(ref tags) = *tags {
+                    prop_assert_eq!(params["filter"]["tags"].as_array().unwrap().len(), tags.len());
+                }
+
+                if public_collections {
+                    prop_assert_eq!(params["named"][0]["name"].as_str(), Some("public-collections"));
+                    prop_assert_eq!(params["named"][0]["params"]["include"].as_bool(), Some(true));
+                }
+            }
+        }
+    }
 }
 
 mod upload {
+    use errors::*;
+    use net::http::{self, check_status};
+    use net::oauth::oauth2::TokenSession;
     use utils::console::*;
 
     use crypto::digest::Digest;
     use crypto::sha2::Sha256;
-    use hyper::client::Request;
+    use hyper::client::{Request, Response};
     use hyper::method::Method;
     use hyper::header::{Headers, ContentDisposition, DispositionParam, DispositionType, ContentType,
                         Authorization, Bearer, Accept, qitem};
-    use hyper::net::HttpsConnector;
-    use hyper_native_tls::NativeTlsClient;
     use mime::Mime;
     use mime_multipart::{Node, Part, FilePart, write_multipart};
     use serde_json;
@@ -479,15 +519,6 @@ mod upload {
     use std::path::Path;
     use std::str;
 
-    error_chain! {
-        errors {
-            HttpUploadCallFailed {
-                description("failed to make http upload call")
-                display("failed to make http upload call")
-            }
-        }
-    }
-
     #[derive(Serialize, Debug)]
     struct DocumentMetadata<'a> {
         metadata: Metadata<'a>,
@@ -529,7 +560,7 @@ mod upload {
     }
 
     pub fn upload_document(
-        access_token: &str,
+        session: &TokenSession,
         file: &Path,
         filename: &str,
         mime: Mime,
@@ -537,12 +568,12 @@ mod upload {
         tags: Option<Vec<&str>>,
         collections: Option<Vec<&str>>
     ) -> Result<String> {
-        do_upload_document(access_token, file, filename, mime, title, tags, collections)
-            .chain_err(|| ErrorKind::HttpUploadCallFailed)
+        do_upload_document(session, file, filename, mime, title, tags, collections)
+            .chain_err(|| "failed to make http upload call")
     }
 
     fn do_upload_document(
-        access_token: &str,
+        session: &TokenSession,
         file: &Path,
         filename: &str,
         mime: Mime,
@@ -556,8 +587,21 @@ mod upload {
         let nodes = create_multipart_nodes(
             doc_metadata_json_bytes, filename.to_string(), file, mime).chain_err(|| "Failed to create form-data")?;
 
-        let ssl = NativeTlsClient::new().chain_err(|| "Failed to create TLS client")?;
-        let connector = HttpsConnector::new(ssl);
+        let mut response = session
+            .send(|token| send_multipart(token, &boundary, &boundary_bytes, &nodes))
+            .chain_err(|| "Failed to finish http request")?;
+
+        check_status(&mut response)?;
+
+        let mut body = Vec::new();
+        response.read_to_end(&mut body).chain_err(|| "Failed to read server response")?;
+        let response_body = String::from_utf8_lossy(&body).to_string();
+
+        Ok(response_body)
+    }
+
+    fn send_multipart(access_token: &str, boundary: &str, boundary_bytes: &Vec<u8>, nodes: &Vec<Node>) -> Result<Response> {
+        let connector = http::connector().chain_err(|| "Failed to create TLS client")?;
         let url = ::hyper::Url::parse("https://api.centerdevice.de/v2/documents").chain_err(|| "Failed to parse URL")?;
         let mut client = Request::with_connector(Method::Post, url, &connector).chain_err(|| "Failed to create client")?;
         client.headers_mut().set(Authorization(Bearer { token: access_token.to_string() }));
@@ -565,14 +609,10 @@ mod upload {
         client.headers_mut().set(Accept(vec![qitem(mime!(Application / Json; Charset = Utf8))]));
 
         let mut request = client.start().chain_err(|| "Failed to start HTTP connection")?;
-        write_multipart(&mut request, &boundary_bytes, &nodes).chain_err(|| "Failed to send multipart form-data")?;
-        let mut response = request.send().chain_err(|| "Failed to finish http request")?;
+        write_multipart(&mut request, boundary_bytes, nodes).chain_err(|| "Failed to send multipart form-data")?;
+        let response = request.send().chain_err(|| "Failed to finish http request")?;
 
-        let mut body = Vec::new();
-        response.read_to_end(&mut body).chain_err(|| "Failed to read server response")?;
-        let response_body = String::from_utf8_lossy(&body).to_string();
-
-        Ok(response_body)
+        Ok(response)
     }
 
     fn create_doc_metadata(
@@ -625,6 +665,49 @@ mod upload {
 
         output
     }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use utils::json::*;
+
+        use proptest::prelude::*;
+
+        fn as_strs(values: &Option<Vec<String>>) -> Option<Vec<&str>> {
+            values.as_ref().map(|v| v.iter().map(|s| s.as_str()).collect())
+        }
+
+        proptest! {
+            #[test]
+            fn test_document_metadata_serialization(
+                ref filename in "[a-zA-Z0-9_. -]{1,30}",
+                size in 0u64..1_000_000_000,
+                ref title in prop::option::of(".{0,30}"),
+                ref tags in prop::option::of(prop::collection::vec("[a-z0-9-]{1,10}", 1..5)),
+                ref collections in prop::option::of(prop::collection::vec("[0-9a-f-]{36}", 1..5))
+            ) {
+                let metadata = DocumentMetadata::new(
+                    filename, size, title.as_ref().map(|s| s.as_str()), as_strs(tags), as_strs(collections));
+                let json = to_value(&metadata);
+
+                prop_assert_eq!(keys(&json), sorted(&["metadata"]));
+                prop_assert_eq!(keys(&json["metadata"]), sorted(&["document", "actions"]));
+
+                let document = &json["metadata"]["document"];
+                let mut expected = vec!["filename", "size"];
+                if title.is_some() { expected.push("title") }
+                prop_assert_eq!(keys(document), sorted(&expected));
+                prop_assert_eq!(document["filename"].as_str(), Some(filename.as_str()));
+                prop_assert_eq!(document["size"].as_u64(), Some(size));
+                prop_assert_eq!(document["title"].as_str(), title.as_ref().map(|s| s.as_str()));
+
+                let mut expected = Vec::new();
+                if tags.is_some() { expected.push("add-tag") }
+                if collections.is_some() { expected.push("add-to-collection") }
+                prop_assert_eq!(keys(&json["metadata"]["actions"]), sorted(&expected));
+            }
+        }
+    }
 }
 
 
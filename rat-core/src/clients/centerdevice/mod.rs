@@ -0,0 +1,83 @@
+use config::Config;
+use net::oauth::oauth2::OAuth2Client;
+
+use std::collections::HashMap;
+
+pub const NAME: &'static str = "centerdevice";
+
+pub static AUTH_ENDPOINT: &'static str = "https://auth.centerdevice.de/authorize";
+pub static TOKEN_ENDPOINT: &'static str = "https://auth.centerdevice.de/token";
+
+#[cfg(feature = "centerdevice")]
+pub mod client;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CenterDeviceConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    pub refresh_token: Option<String>,
+    pub access_token: Option<String>,
+    #[serde(default)]
+    pub accounts: HashMap<String, CenterDeviceAccountConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CenterDeviceAccountConfig {
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub refresh_token: Option<String>,
+    pub access_token: Option<String>,
+}
+
+impl CenterDeviceConfig {
+    pub fn select_account(&mut self, account: &str) -> bool {
+        if let Some(account) = self.accounts.remove(account) {
+            if let Some(client_id) = account.client_id {
+                self.client_id = client_id;
+            }
+            if let Some(client_secret) = account.client_secret {
+                self.client_secret = client_secret;
+            }
+            // The tokens of the default account must not leak into the selected account
+            self.refresh_token = account.refresh_token;
+            self.access_token = account.access_token;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn access_token(&self) -> Option<&String> {
+        self.access_token.as_ref()
+    }
+
+    pub fn refresh_token(&self) -> Option<&String> {
+        self.refresh_token.as_ref()
+    }
+
+    /// Returns the secrets of this section and its accounts by their setting names, e.g., 'accounts.work.access_token'.
+    pub fn secrets(&self) -> Vec<(String, &String)> {
+        let mut secrets = vec![("client_secret".to_string(), &self.client_secret)];
+        secrets.extend(self.refresh_token.iter().map(|s| ("refresh_token".to_string(), s)));
+        secrets.extend(self.access_token.iter().map(|s| ("access_token".to_string(), s)));
+        for (name, account) in &self.accounts {
+            secrets.extend(account.client_secret.iter().map(|s| (format!("accounts.{}.client_secret", name), s)));
+            secrets.extend(account.refresh_token.iter().map(|s| (format!("accounts.{}.refresh_token", name), s)));
+            secrets.extend(account.access_token.iter().map(|s| (format!("accounts.{}.access_token", name), s)));
+        }
+        secrets
+    }
+}
+
+pub fn oauth_client(config: &Config) -> OAuth2Client {
+    OAuth2Client {
+        client_id: config.centerdevice.client_id.clone(),
+        client_secret: Some(config.centerdevice.client_secret.clone()),
+        auth_endpoint: AUTH_ENDPOINT.to_string(),
+        token_endpoint: TOKEN_ENDPOINT.to_string(),
+        device_endpoint: None,
+        revocation_endpoint: None,
+        scope: None,
+        read_only_scope: None,
+    }
+}
@@ -0,0 +1,337 @@
+//! Client of RSS and Atom feeds. Feeds are read by a small scanner instead of an XML parser: it picks the elements of
+//! items and entries rat shows, i.e., title, link, id, and time of publication, and skips everything else. That is
+//! lenient enough for the feeds found in the wild, which are often not well-formed XML anyway.
+
+use errors::*;
+use net::http::{check_status, tls_client};
+use net::retry::{self, RetryPolicy};
+use utils::console::*;
+use utils::datetime;
+
+use std::io::Read;
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct Entry {
+    /// Name of the feed in section '[feeds]'
+    pub feed: String,
+    /// Guid or id of the entry, else its URL
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    /// Unix timestamp of publication
+    #[serde(skip_serializing_if = "Option::is_none")] pub time: Option<u64>,
+}
+
+/// Returns the entries of the feed at `url` in the order of the feed; `name` is the name of the feed in section
+/// '[feeds]'.
+pub fn fetch(policy: &RetryPolicy, name: &str, url: &str) -> Result<Vec<Entry>> {
+    info(format!("Getting feed '{}' ...", name));
+    let xml = get(policy, url).chain_err(|| format!("HTTP call to get feed '{}' failed", name))?;
+
+    parse(name, &xml).chain_err(|| format!("Failed to parse feed '{}'", name))
+}
+
+fn get(policy: &RetryPolicy, url: &str) -> Result<String> {
+    verboseln(format!("url = {}", url));
+    let client = tls_client().chain_err(|| "Failed to create TLS client")?;
+    let mut response = retry::send(policy, || client.get(url).send()).chain_err(|| "Failed to finish HTTP request")?;
+    check_status(&mut response)?;
+
+    let mut buffer = Vec::new();
+    response.read_to_end(&mut buffer).chain_err(|| "Failed to read HTTP response")?;
+
+    Ok(String::from_utf8_lossy(&buffer).into_owned())
+}
+
+/// Parses RSS 2.0 and 1.0 items and Atom entries.
+pub fn parse(name: &str, xml: &str) -> Result<Vec<Entry>> {
+    let entries = if elements(xml, "feed").next().is_some() {
+        elements(xml, "entry").map(|e| atom_entry(name, e.content)).collect()
+    } else if elements(xml, "rss").next().is_some() || elements(xml, "rdf:RDF").next().is_some() {
+        elements(xml, "item").map(|e| rss_item(name, e.content)).collect()
+    } else {
+        bail!(ErrorKind::Parse("feed; neither RSS nor Atom".to_string()));
+    };
+
+    Ok(entries)
+}
+
+fn rss_item(name: &str, item: &str) -> Entry {
+    let url = child_text(item, "link").unwrap_or_default();
+    let id = child_text(item, "guid").unwrap_or_else(|| url.clone());
+    // Permalinks may stand in for missing links
+    let url = if url.is_empty() && id.starts_with("http") { id.clone() } else { url };
+    let time = child_text(item, "pubDate").and_then(|t| datetime::parse_rfc2822(&t).ok())
+        .or_else(|| child_text(item, "dc:date").and_then(|t| datetime::parse_iso8601(&t).ok()));
+
+    Entry {
+        feed: name.to_string(),
+        id: id,
+        title: child_text(item, "title").unwrap_or_default(),
+        url: url,
+        time: time.map(|t| t.as_secs()),
+    }
+}
+
+fn atom_entry(name: &str, entry: &str) -> Entry {
+    // The alternate link points to the page; others, e.g., 'self' or 'enclosure', do not
+    let url = elements(entry, "link")
+        .find(|l| attribute(l.attributes, "rel").map(|rel| rel == "alternate").unwrap_or(true))
+        .and_then(|l| attribute(l.attributes, "href"))
+        .unwrap_or_default();
+    let time = child_text(entry, "published").or_else(|| child_text(entry, "updated"))
+        .and_then(|t| datetime::parse_iso8601(&t).ok());
+
+    Entry {
+        feed: name.to_string(),
+        id: child_text(entry, "id").unwrap_or_else(|| url.clone()),
+        title: child_text(entry, "title").unwrap_or_default(),
+        url: url,
+        time: time.map(|t| t.as_secs()),
+    }
+}
+
+/// An element found by `elements`; the content of empty elements like `<link href="..."/>` is empty.
+#[derive(Debug, PartialEq)]
+struct Element<'a> {
+    attributes: &'a str,
+    content: &'a str,
+}
+
+/// The elements `name` in document order. Elements of the same name must not be nested, which holds for the
+/// elements of feeds rat reads.
+fn elements<'a, 'b>(xml: &'a str, name: &'b str) -> Elements<'a, 'b> {
+    Elements { rest: xml, name: name }
+}
+
+struct Elements<'a, 'b> {
+    rest: &'a str,
+    name: &'b str,
+}
+
+impl<'a, 'b> Iterator for Elements<'a, 'b> {
+    type Item = Element<'a>;
+
+    fn next(&mut self) -> Option<Element<'a>> {
+        loop {
+            let start = self.rest.find('<')?;
+            let tag = &self.rest[start + 1..];
+            if !tag.starts_with(self.name) {
+                self.rest = tag;
+                continue;
+            }
+            let after_name = &tag[self.name.len()..];
+            match after_name.chars().next() {
+                Some(c) if c == '>' || c == '/' || c.is_whitespace() => {}
+                _ => {
+                    self.rest = tag;
+                    continue;
+                }
+            }
+            let end_of_tag = after_name.find('>')?;
+            let attributes = &after_name[..end_of_tag];
+            let after_tag = &after_name[end_of_tag + 1..];
+            if attributes.ends_with('/') {
+                self.rest = after_tag;
+                return Some(Element { attributes: &attributes[..attributes.len() - 1], content: "" });
+            }
+            let close = format!("</{}>", self.name);
+            let end = after_tag.find(&close).unwrap_or_else(|| after_tag.len());
+            self.rest = &after_tag[end..];
+            return Some(Element { attributes: attributes, content: &after_tag[..end] });
+        }
+    }
+}
+
+/// The text of the first child element `name`, if it is not blank
+fn child_text(xml: &str, name: &str) -> Option<String> {
+    elements(xml, name).next().map(|e| text(e.content)).and_then(|t| if t.is_empty() { None } else { Some(t) })
+}
+
+/// The value of attribute `name`, e.g., of `href="https://example.com"`
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes;
+    while let Some(i) = rest.find(name) {
+        let before = rest[..i].chars().last();
+        let after = rest[i + name.len()..].trim_matches(|c: char| c.is_whitespace());
+        rest = &rest[i + name.len()..];
+        if before.map(|c| !c.is_whitespace()).unwrap_or(false) || !after.starts_with('=') {
+            continue;
+        }
+        let value = after[1..].trim_matches(|c: char| c.is_whitespace());
+        let quote = value.chars().next()?;
+        if quote != '"' && quote != '\'' {
+            return None;
+        }
+        let end = value[1..].find(quote)?;
+        return Some(decode_entities(&value[1..end + 1]));
+    }
+
+    None
+}
+
+/// Text content without markup: CDATA sections are taken verbatim, tags are dropped, entities decoded, and whitespace
+/// collapsed.
+fn text(content: &str) -> String {
+    let mut out = String::new();
+    let mut rest = content;
+    while !rest.is_empty() {
+        let (plain, cdata, after) = match rest.find("<![CDATA[") {
+            Some(start) => {
+                let cdata = &rest[start + 9..];
+                let end = cdata.find("]]>").unwrap_or_else(|| cdata.len());
+                (&rest[..start], &cdata[..end], &cdata[(end + 3).min(cdata.len())..])
+            }
+            None => (rest, "", ""),
+        };
+        out.push_str(&decode_entities(&strip_tags(plain)));
+        out.push_str(cdata);
+        rest = after;
+    }
+
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn strip_tags(markup: &str) -> String {
+    let mut out = String::with_capacity(markup.len());
+    let mut in_tag = false;
+    for c in markup.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+
+    out
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        let entity = &rest[start..];
+        let decoded = entity.find(';').and_then(|end| {
+            let c = match &entity[1..end] {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                code if code.starts_with("#x") || code.starts_with("#X") =>
+                    u32::from_str_radix(&code[2..], 16).ok().and_then(::std::char::from_u32),
+                code if code.starts_with('#') => code[1..].parse().ok().and_then(::std::char::from_u32),
+                _ => None,
+            };
+            c.map(|c| (c, end + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &entity[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &entity[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use net::http::with_client;
+    use net::mock::MockClient;
+
+    use std::rc::Rc;
+
+    static RSS: &'static str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom">
+          <channel>
+            <title>Rust Blog</title>
+            <atom:link href="https://blog.rust-lang.org/feed.xml" rel="self"/>
+            <item>
+              <title>Announcing Rust 1.19</title>
+              <link>https://blog.rust-lang.org/2017/07/20/Rust-1.19.html</link>
+              <guid isPermaLink="false">rust-1.19</guid>
+              <pubDate>Thu, 20 Jul 2017 00:00:00 +0000</pubDate>
+            </item>
+            <item>
+              <title><![CDATA[Rust & <Friends>]]></title>
+              <guid>https://example.com/friends</guid>
+            </item>
+          </channel>
+        </rss>"#;
+
+    static ATOM: &'static str = r#"<?xml version="1.0" encoding="utf-8"?>
+        <feed xmlns="http://www.w3.org/2005/Atom">
+          <title>This Week in Rust</title>
+          <link href="https://this-week-in-rust.org/"/>
+          <entry>
+            <title type="html">This Week in Rust 190 &amp; more</title>
+            <link href="https://this-week-in-rust.org/atom.xml" rel="self"/>
+            <link rel="alternate" href="https://this-week-in-rust.org/blog/2017/07/11/this-week-in-rust-190/"/>
+            <id>tag:this-week-in-rust.org,2017-07-11:/blog/2017/07/11/this-week-in-rust-190/</id>
+            <updated>2017-07-14T02:40:00Z</updated>
+          </entry>
+        </feed>"#;
+
+    #[test]
+    fn test_parse_rss() {
+        let entries = parse("rust", RSS).unwrap();
+
+        assert_eq!(entries, vec![
+            Entry {
+                feed: "rust".to_string(),
+                id: "rust-1.19".to_string(),
+                title: "Announcing Rust 1.19".to_string(),
+                url: "https://blog.rust-lang.org/2017/07/20/Rust-1.19.html".to_string(),
+                time: Some(1500508800),
+            },
+            Entry {
+                feed: "rust".to_string(),
+                id: "https://example.com/friends".to_string(),
+                title: "Rust & <Friends>".to_string(),
+                url: "https://example.com/friends".to_string(),
+                time: None,
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_parse_atom() {
+        let entries = parse("twir", ATOM).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].title, "This Week in Rust 190 & more");
+        assert_eq!(entries[0].url, "https://this-week-in-rust.org/blog/2017/07/11/this-week-in-rust-190/");
+        assert_eq!(entries[0].time, Some(1500000000));
+        assert!(parse("html", "<html><body>Not a feed</body></html>").is_err());
+    }
+
+    #[test]
+    fn test_text() {
+        assert_eq!(text("  Caf&#233; &lt;b&gt;\n <em>bold</em> &unknown; "), "Café <b> bold &unknown;");
+        assert_eq!(attribute(r#" rel='alternate' href = "a?b=1&amp;c=2""#, "href"), Some("a?b=1&c=2".to_string()));
+        assert_eq!(attribute(r#" xhref="wrong""#, "href"), None);
+    }
+
+    #[test]
+    fn test_fetch() {
+        ::utils::console::init(::config::Verbosity::QUIET);
+        let mock = Rc::new(MockClient::new().respond(200, &[], ATOM));
+        let policy = RetryPolicy { max_attempts: 1, ..Default::default() };
+
+        let entries = with_client(mock.clone(), || fetch(&policy, "twir", "https://this-week-in-rust.org/atom.xml")).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert!(mock.requests()[0].starts_with("GET /atom.xml HTTP/1.1\r\n"));
+    }
+}
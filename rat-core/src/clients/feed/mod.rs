@@ -0,0 +1,4 @@
+pub const NAME: &'static str = "feed";
+
+#[cfg(feature = "feed")]
+pub mod client;
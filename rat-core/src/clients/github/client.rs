@@ -0,0 +1,196 @@
+use super::request_auth;
+use config::Config;
+use errors::*;
+use net::auth::Auth;
+use net::http::{check_status, tls_client};
+use net::retry;
+use utils::console::*;
+
+use hyper::client::Response;
+use hyper::header::{Accept, UserAgent, qitem};
+use serde::Deserialize;
+use serde_json;
+use serde_urlencoded;
+use std::cmp;
+use std::io::Read;
+
+static BASE_URL: &'static str = "https://api.github.com";
+
+/// Maximum number of items GitHub returns per page
+const MAX_PER_PAGE: usize = 100;
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct Repository {
+    pub full_name: String,
+    pub html_url: String,
+    #[serde(default)] pub description: Option<String>,
+    #[serde(default)] pub language: Option<String>,
+    #[serde(default)] pub stargazers_count: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct Notification {
+    /// Id of the thread
+    pub id: String,
+    pub unread: bool,
+    /// Why you have been notified, e.g., 'mention', 'review_requested', or 'subscribed'
+    pub reason: String,
+    /// Time of the last change, e.g., '2017-07-14T02:40:00Z'
+    pub updated_at: String,
+    pub subject: Subject,
+    pub repository: NotificationRepository,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct Subject {
+    pub title: String,
+    /// API URL of the issue, pull request, etc., if any
+    #[serde(default)] pub url: Option<String>,
+    /// E.g., 'Issue', 'PullRequest', or 'Release'
+    #[serde(rename = "type")] pub subject_type: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct NotificationRepository {
+    pub full_name: String,
+}
+
+/// Lists at most `count` starred repositories, most recently starred first.
+pub fn starred(config: &Config, count: usize) -> Result<Vec<Repository>> {
+    info("Getting list of your starred repositories ...");
+    get_pages(config, "/user/starred", &[], count).chain_err(|| "HTTP call to list starred repositories failed")
+}
+
+/// Lists at most `count` unread or, if `all` is set, also read notifications, most recently updated first.
+pub fn notifications(config: &Config, all: bool, count: usize) -> Result<Vec<Notification>> {
+    info("Getting list of your notifications ...");
+    let params = [("all", all.to_string())];
+    get_pages(config, "/notifications", &params, count).chain_err(|| "HTTP call to list notifications failed")
+}
+
+/// Marks a notification thread as done, i.e., removes it from the inbox.
+pub fn done(config: &Config, thread_id: &str) -> Result<()> {
+    let url = format!("{}/notifications/threads/{}", BASE_URL, thread_id);
+    let auth = request_auth(config).chain_err(|| "Failed to get access token")?;
+
+    let client = tls_client().chain_err(|| "Failed to create TLS client")?;
+    let mut response = client
+        .delete(&url)
+        .headers(auth.headers("DELETE", &url, &[]))
+        .header(UserAgent(user_agent()))
+        .send()
+        .chain_err(|| format!("HTTP call to mark notification {} as done failed", thread_id))?;
+    check_status(&mut response)?;
+
+    Ok(())
+}
+
+/// Requests page after page until `count` items have been received or a page is not full.
+fn get_pages<T: Deserialize>(config: &Config, path: &str, params: &[(&str, String)], count: usize) -> Result<Vec<T>> {
+    let per_page = cmp::min(count, MAX_PER_PAGE);
+    let mut items = Vec::new();
+    let mut page = 1;
+    while items.len() < count {
+        let mut params = params.to_vec();
+        params.push(("per_page", per_page.to_string()));
+        params.push(("page", page.to_string()));
+        let json = get(config, path, &params)?;
+        let mut page_items: Vec<T> = serde_json::from_str(&json).chain_err(|| "JSON parsing failed")?;
+        let received = page_items.len();
+        verboseln(format!("Received {} item(s) on page {}.", received, page));
+        items.append(&mut page_items);
+        if received < per_page {
+            break;
+        }
+        page += 1;
+    }
+    items.truncate(count);
+
+    Ok(items)
+}
+
+fn get(config: &Config, path: &str, params: &[(&str, String)]) -> Result<String> {
+    let auth = request_auth(config).chain_err(|| "Failed to get access token")?;
+    let parameters_enc = serde_urlencoded::to_string(&params).chain_err(|| "URL serialization failed")?;
+    let url = format!("{}{}?{}", BASE_URL, path, parameters_enc);
+
+    let client = tls_client().chain_err(|| "Failed to create TLS client")?;
+    let mut response = retry::send(&config.general.retry_policy(), || client
+        .get(&url)
+        .headers(auth.headers("GET", &url, &[]))
+        .header(UserAgent(user_agent()))
+        .header(Accept(vec![qitem(mime!(Application / Json))]))
+        .send())
+        .chain_err(|| "Failed to finish HTTP request")?;
+
+    read(&mut response)
+}
+
+fn read(response: &mut Response) -> Result<String> {
+    check_status(response)?;
+    let mut buffer = String::new();
+    response.read_to_string(&mut buffer).chain_err(|| "Failed to read HTTP response")?;
+
+    Ok(buffer)
+}
+
+/// GitHub rejects requests without user agent.
+fn user_agent() -> String {
+    format!("rat/{}", env!("CARGO_PKG_VERSION"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use net::http::with_client;
+    use net::mock::MockClient;
+    use testing;
+
+    use std::rc::Rc;
+
+    fn config() -> Config {
+        testing::config("github-client", r#"
+            [github]
+            access_token = 'ghp_0123456789'
+        "#)
+    }
+
+    fn repository(name: &str) -> String {
+        format!(r#"{{"full_name":"{}","html_url":"https://github.com/{}","description":null,"stargazers_count":1}}"#, name, name)
+    }
+
+    #[test]
+    fn test_starred_pages() {
+        ::utils::console::init(::config::Verbosity::QUIET);
+        let first = (0..100).map(|i| repository(&format!("user/repo-{}", i))).collect::<Vec<_>>().join(",");
+        let mock = Rc::new(MockClient::new()
+            .respond(200, &[], &format!("[{}]", first))
+            .respond(200, &[], &format!("[{}]", repository("user/last"))));
+
+        let starred = with_client(mock.clone(), || starred(&config(), 150)).unwrap();
+
+        assert_eq!(starred.len(), 101);
+        assert_eq!(starred[100].full_name, "user/last");
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("GET /user/starred?per_page=100&page=1 HTTP/1.1\r\n"));
+        assert!(requests[1].starts_with("GET /user/starred?per_page=100&page=2 HTTP/1.1\r\n"));
+        assert!(requests[0].contains("Authorization: Bearer ghp_0123456789\r\n"));
+        assert!(requests[0].contains("User-Agent: rat/"));
+    }
+
+    #[test]
+    fn test_notifications() {
+        ::utils::console::init(::config::Verbosity::QUIET);
+        let json = r#"[{"id":"1","unread":true,"reason":"mention","updated_at":"2017-07-14T02:40:00Z",
+                        "subject":{"title":"Fix it","url":"https://api.github.com/repos/user/repo/issues/1","latest_comment_url":null,"type":"Issue"},
+                        "repository":{"id":1,"full_name":"user/repo"},"url":"https://api.github.com/notifications/threads/1"}]"#;
+        let mock = Rc::new(MockClient::new().respond(200, &[], json));
+
+        let notifications = with_client(mock.clone(), || notifications(&config(), false, 10)).unwrap();
+
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].subject.subject_type, "Issue");
+        assert!(mock.requests()[0].starts_with("GET /notifications?all=false&per_page=10&page=1 HTTP/1.1\r\n"));
+    }
+}
@@ -0,0 +1,30 @@
+use config::Config;
+use credentials;
+use net::auth::BearerAuth;
+
+pub const NAME: &'static str = "github";
+
+#[cfg(feature = "github")]
+pub mod client;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GitHubConfig {
+    /// Personal access token with scopes 'notifications' and 'read:user'; cf. https://github.com/settings/tokens
+    pub access_token: Option<String>,
+}
+
+impl GitHubConfig {
+    /// Returns the secrets of this section by their setting names.
+    pub fn secrets(&self) -> Vec<(String, &String)> {
+        self.access_token.iter().map(|s| ("access_token".to_string(), s)).collect()
+    }
+}
+
+pub fn access_token(config: &Config) -> credentials::Result<String> {
+    credentials::access_token(config, NAME, &config.github.access_token)
+}
+
+/// GitHub expects the access token as bearer token.
+pub fn request_auth(config: &Config) -> credentials::Result<BearerAuth> {
+    access_token(config).map(BearerAuth::new)
+}
@@ -0,0 +1,159 @@
+//! Client of the public Hacker News API; cf. https://github.com/HackerNews/API. The API needs no authentication and
+//! returns only ids for lists of stories, so every story is requested on its own.
+
+use errors::*;
+use net::concurrent;
+use net::http::{check_status, tls_client};
+use net::retry::{self, RetryPolicy};
+use utils::console::*;
+
+use serde_json;
+use std::io::Read;
+
+static BASE_URL: &'static str = "https://hacker-news.firebaseio.com/v0";
+static ITEM_URL: &'static str = "https://news.ycombinator.com/item?id=";
+
+/// Maximum number of stories of the story lists
+pub const MAX_STORIES: usize = 500;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Stories {
+    Top,
+    New,
+}
+
+impl Stories {
+    fn path(&self) -> &'static str {
+        match *self {
+            Stories::Top => "/topstories.json",
+            Stories::New => "/newstories.json",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct Item {
+    pub id: u64,
+    /// One of 'story', 'comment', 'job', 'poll', or 'pollopt'
+    #[serde(rename = "type")] pub item_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")] pub by: Option<String>,
+    /// Unix timestamp of submission
+    #[serde(default)] pub time: u64,
+    #[serde(skip_serializing_if = "Option::is_none")] pub title: Option<String>,
+    /// Missing for Ask HN stories
+    #[serde(skip_serializing_if = "Option::is_none")] pub url: Option<String>,
+    /// HTML text of comments and Ask HN stories
+    #[serde(skip_serializing_if = "Option::is_none")] pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")] pub score: Option<u64>,
+    /// Number of comments
+    #[serde(skip_serializing_if = "Option::is_none")] pub descendants: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")] pub kids: Vec<u64>,
+    #[serde(default, skip_serializing_if = "is_false")] pub deleted: bool,
+    #[serde(default, skip_serializing_if = "is_false")] pub dead: bool,
+}
+
+fn is_false(b: &bool) -> bool {
+    !*b
+}
+
+impl Item {
+    /// The discussion on Hacker News
+    pub fn item_url(&self) -> String {
+        format!("{}{}", ITEM_URL, self.id)
+    }
+
+    /// The linked page or, e.g., for Ask HN stories, the discussion
+    pub fn link(&self) -> String {
+        self.url.clone().unwrap_or_else(|| self.item_url())
+    }
+}
+
+/// Returns the first `count` stories of a list; deleted and dead stories are left out. The stories are requested
+/// concurrently with at most `concurrency` requests in flight.
+pub fn stories(policy: &RetryPolicy, stories: Stories, count: usize, concurrency: usize) -> Result<Vec<Item>> {
+    info("Getting list of stories ...");
+    let json = get(policy, stories.path()).chain_err(|| "HTTP call to list stories failed")?;
+    let mut ids: Vec<u64> = serde_json::from_str(&json).chain_err(|| "JSON parsing failed")?;
+    ids.truncate(count);
+
+    info(format!("Getting {} stories ...", ids.len()));
+    let policy = *policy;
+    let items = concurrent::map_bounded(ids, concurrency, move |id| item(&policy, id));
+    let mut stories = Vec::new();
+    for item in items {
+        let item = item?;
+        if !item.deleted && !item.dead {
+            stories.push(item);
+        }
+    }
+
+    Ok(stories)
+}
+
+pub fn item(policy: &RetryPolicy, id: u64) -> Result<Item> {
+    let json = get(policy, &format!("/item/{}.json", id)).chain_err(|| format!("HTTP call to get item {} failed", id))?;
+
+    parse_item(id, &json)
+}
+
+fn get(policy: &RetryPolicy, path: &str) -> Result<String> {
+    let url = format!("{}{}", BASE_URL, path);
+    verboseln(format!("url = {}", url));
+
+    let client = tls_client().chain_err(|| "Failed to create TLS client")?;
+    let mut response = retry::send(policy, || client.get(&url).send()).chain_err(|| "Failed to finish HTTP request")?;
+    check_status(&mut response)?;
+
+    let mut buffer = String::new();
+    response.read_to_string(&mut buffer).chain_err(|| "Failed to read HTTP response")?;
+
+    Ok(buffer)
+}
+
+/// Unknown items are answered with `null` instead of status 404.
+fn parse_item(id: u64, json: &str) -> Result<Item> {
+    let item: Option<Item> = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
+
+    item.ok_or_else(|| format!("Item {} does not exist", id).into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use net::http::with_client;
+    use net::mock::MockClient;
+
+    use std::rc::Rc;
+
+    #[test]
+    fn test_stories() {
+        ::utils::console::init(::config::Verbosity::QUIET);
+        let mock = Rc::new(MockClient::new()
+            .respond(200, &[], "[3, 2, 1]")
+            .respond(200, &[], r#"{"by":"pg","descendants":15,"id":3,"kids":[4],"score":57,"time":1160418111,
+                                  "title":"Y Combinator","type":"story","url":"http://ycombinator.com"}"#)
+            .respond(200, &[], r#"{"id":2,"type":"story","deleted":true,"time":1160418111}"#));
+        let policy = RetryPolicy { max_attempts: 1, ..Default::default() };
+
+        // With one request in flight, the items are requested on this thread, i.e., by the mock client
+        let stories = with_client(mock.clone(), || stories(&policy, Stories::New, 2, 1)).unwrap();
+
+        assert_eq!(stories.len(), 1);
+        assert_eq!(stories[0].title, Some("Y Combinator".to_string()));
+        assert_eq!(stories[0].link(), "http://ycombinator.com");
+        let requests = mock.requests();
+        assert!(requests[0].starts_with("GET /v0/newstories.json HTTP/1.1\r\n"));
+        assert!(requests[2].starts_with("GET /v0/item/2.json HTTP/1.1\r\n"));
+    }
+
+    #[test]
+    fn test_parse_item_ask_hn() {
+        let json = r#"{"by":"tel","descendants":16,"id":121003,"score":25,"text":"<i>or</i> HN: the Next Iteration",
+                      "time":1203647620,"title":"Ask HN: The Arc Effect","type":"story"}"#;
+
+        let item = parse_item(121003, json).unwrap();
+
+        assert_eq!(item.link(), "https://news.ycombinator.com/item?id=121003");
+        assert!(parse_item(1, "null").is_err());
+    }
+}
@@ -0,0 +1,4 @@
+pub const NAME: &'static str = "hackernews";
+
+#[cfg(feature = "hackernews")]
+pub mod client;
@@ -0,0 +1,240 @@
+use super::{access_token, NAME};
+use config::Config;
+use credentials;
+use credentials::Credential;
+use errors::*;
+use net::auth::{Auth, OAuth1Auth};
+use net::http::{check_status, tls_client};
+use utils::console::*;
+
+use hyper::header::ContentType;
+use serde_json;
+use serde_json::Value;
+use serde_urlencoded;
+use std::collections::HashMap;
+use std::io::Read;
+
+static BASE_URL: &'static str = "https://www.instapaper.com/api";
+
+/// Maximum number of bookmarks Instapaper returns per list request
+pub const MAX_LIMIT: usize = 500;
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, PartialEq)]
+pub enum Folder {
+    unread,
+    starred,
+    archive,
+}
+
+impl<'a> From<&'a str> for Folder {
+    fn from(s: &'a str) -> Self {
+        match s {
+            "starred" => Folder::starred,
+            "archive" => Folder::archive,
+            _ => Folder::unread,
+        }
+    }
+}
+
+impl Folder {
+    fn id(&self) -> &'static str {
+        match *self {
+            Folder::unread => "unread",
+            Folder::starred => "starred",
+            Folder::archive => "archive",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct Bookmark {
+    pub bookmark_id: u64,
+    pub url: String,
+    #[serde(default)] pub title: String,
+    #[serde(default)] pub description: String,
+    /// Unix timestamp of saving
+    #[serde(default)] pub time: u64,
+    /// "1" for starred bookmarks, "0" otherwise
+    #[serde(default)] pub starred: String,
+    /// Reading progress between 0 and 1
+    #[serde(default)] pub progress: f64,
+}
+
+/// Exchanges user name and password for an access token by xAuth and stores the token in the credential store. The
+/// password itself is not stored.
+pub fn auth(config: &Config, username: &str, password: &str) -> Result<()> {
+    info("Requesting access token ...");
+    let params = [("x_auth_username", username), ("x_auth_password", password), ("x_auth_mode", "client_auth")];
+    let body = post(&consumer_auth(config), "/1/oauth/access_token", &params).chain_err(|| "HTTP call to authenticate failed")?;
+    let mut credential = parse_token(&body)?;
+    credential.username = Some(username.to_string());
+
+    credentials::store(config, NAME, &credential).chain_err(|| "Failed to store access token")?;
+    msgln(format!("Received access token for user '{}' and stored it in the credential store.", username));
+
+    Ok(())
+}
+
+/// Lists at most `limit` bookmarks of `folder`, most recently saved first.
+pub fn list(config: &Config, folder: &Folder, limit: usize) -> Result<Vec<Bookmark>> {
+    info("Getting list of your bookmarks ...");
+    let limit = limit.to_string();
+    let params = [("folder_id", folder.id()), ("limit", limit.as_str())];
+    let body = post_signed(config, "/1.1/bookmarks/list", &params).chain_err(|| "HTTP call to list bookmarks failed")?;
+
+    parse_bookmarks(&body)
+}
+
+pub fn add(config: &Config, url: &str, title: Option<&str>, description: Option<&str>) -> Result<Bookmark> {
+    verboseln(format!("add = {}", url));
+    let mut params = vec![("url", url)];
+    params.extend(title.map(|title| ("title", title)));
+    params.extend(description.map(|description| ("description", description)));
+    let body = post_signed(config, "/1/bookmarks/add", &params).chain_err(|| format!("HTTP call to add '{}' failed", url))?;
+
+    parse_bookmark(&body)
+}
+
+pub fn archive(config: &Config, bookmark_id: &str) -> Result<Bookmark> {
+    let body = post_signed(config, "/1/bookmarks/archive", &[("bookmark_id", bookmark_id)])
+        .chain_err(|| format!("HTTP call to archive bookmark {} failed", bookmark_id))?;
+
+    parse_bookmark(&body)
+}
+
+pub fn delete(config: &Config, bookmark_id: &str) -> Result<()> {
+    post_signed(config, "/1/bookmarks/delete", &[("bookmark_id", bookmark_id)])
+        .chain_err(|| format!("HTTP call to delete bookmark {} failed", bookmark_id))?;
+
+    Ok(())
+}
+
+fn post_signed(config: &Config, path: &str, params: &[(&str, &str)]) -> Result<String> {
+    let credential = access_token(config).chain_err(|| "Failed to get access token")?;
+    let auth = consumer_auth(config).with_token(&credential.access_token, credential.token_secret.as_ref().unwrap());
+
+    post(&auth, path, params)
+}
+
+/// Requests are signed by the consumer and, once authenticated, the access token.
+fn consumer_auth(config: &Config) -> OAuth1Auth {
+    OAuth1Auth::new(&config.instapaper.consumer_key, &config.instapaper.consumer_secret)
+}
+
+/// All calls of the Full API are form encoded POST requests signed by OAuth 1.0a.
+fn post<A: Auth>(auth: &A, path: &str, params: &[(&str, &str)]) -> Result<String> {
+    let url = format!("{}{}", BASE_URL, path);
+    let body = serde_urlencoded::to_string(&params).chain_err(|| "URL serialization failed")?;
+
+    let client = tls_client().chain_err(|| "Failed to create TLS client")?;
+    let mut response = client
+        .post(&url)
+        .headers(auth.headers("POST", &url, params))
+        .header(ContentType(mime!(Application / WwwFormUrlEncoded)))
+        .body(&body)
+        .send()
+        .chain_err(|| "Failed to finish HTTP request")?;
+    check_status(&mut response)?;
+
+    let mut buffer = String::new();
+    response.read_to_string(&mut buffer).chain_err(|| "Failed to read HTTP response")?;
+
+    Ok(buffer)
+}
+
+/// The access token is returned form encoded, e.g., 'oauth_token_secret=...&oauth_token=...'.
+fn parse_token(body: &str) -> Result<Credential> {
+    let mut token: HashMap<String, String> = serde_urlencoded::from_str(body.trim()).chain_err(|| "Failed to parse access token")?;
+    let access_token = token.remove("oauth_token").ok_or_else(|| ErrorKind::Parse("access token".to_string()))?;
+    let token_secret = token.remove("oauth_token_secret").ok_or_else(|| ErrorKind::Parse("access token secret".to_string()))?;
+
+    let mut credential = Credential::new(access_token);
+    credential.token_secret = Some(token_secret);
+
+    Ok(credential)
+}
+
+/// Version 1.1 of the API returns an object with the bookmarks, version 1 a list of typed objects, e.g., 'user' and
+/// 'bookmark'.
+fn parse_bookmarks(json: &str) -> Result<Vec<Bookmark>> {
+    let value: Value = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
+    let bookmarks = match value {
+        Value::Object(mut object) => object.remove("bookmarks").unwrap_or(Value::Array(Vec::new())),
+        Value::Array(objects) => Value::Array(objects.into_iter().filter(is_bookmark).collect()),
+        _ => bail!(ErrorKind::Parse("bookmarks".to_string())),
+    };
+
+    serde_json::from_value(bookmarks).chain_err(|| "JSON parsing failed")
+}
+
+fn parse_bookmark(json: &str) -> Result<Bookmark> {
+    parse_bookmarks(json)?.into_iter().next().ok_or_else(|| ErrorKind::Parse("bookmark".to_string()).into())
+}
+
+fn is_bookmark(value: &Value) -> bool {
+    value.get("type").and_then(|t| t.as_str()) == Some("bookmark")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use net::http::with_client;
+    use net::mock::MockClient;
+    use testing;
+
+    use std::rc::Rc;
+
+    fn config() -> Config {
+        testing::config("instapaper-client", r#"
+            [instapaper]
+            consumer_key = 'consumer key'
+            consumer_secret = 'consumer secret'
+        "#)
+    }
+
+    #[test]
+    fn test_parse_token() {
+        let credential = parse_token("oauth_token_secret=secret&oauth_token=token\n").unwrap();
+
+        assert_eq!(credential.access_token, "token");
+        assert_eq!(credential.token_secret, Some("secret".to_string()));
+        assert!(parse_token("oauth_token=token").is_err());
+    }
+
+    #[test]
+    fn test_parse_bookmarks() {
+        let v1 = r#"[{"type":"meta"},{"type":"user","user_id":1},
+                     {"type":"bookmark","bookmark_id":42,"url":"https://example.com","title":"Example","time":1500000000,"starred":"1","progress":0.5}]"#;
+        let v11 = r#"{"user":{"type":"user","user_id":1},"highlights":[],"delete_ids":[],
+                      "bookmarks":[{"type":"bookmark","bookmark_id":42,"url":"https://example.com","title":"Example","time":1500000000,"starred":"1","progress":0.5}]}"#;
+
+        let expected = vec![Bookmark {
+            bookmark_id: 42,
+            url: "https://example.com".to_string(),
+            title: "Example".to_string(),
+            description: "".to_string(),
+            time: 1500000000,
+            starred: "1".to_string(),
+            progress: 0.5,
+        }];
+        assert_eq!(parse_bookmarks(v1).unwrap(), expected);
+        assert_eq!(parse_bookmarks(v11).unwrap(), expected);
+        assert!(parse_bookmark("[]").is_err());
+    }
+
+    #[test]
+    fn test_post_signs_request() {
+        ::utils::console::init(::config::Verbosity::QUIET);
+        let mock = Rc::new(MockClient::new().respond(200, &[], "oauth_token_secret=secret&oauth_token=token"));
+        let auth = consumer_auth(&config()).with_token("token", "secret");
+
+        with_client(mock.clone(), || post(&auth, "/1/bookmarks/delete", &[("bookmark_id", "42")])).unwrap();
+
+        let requests = mock.requests();
+        assert!(requests[0].starts_with("POST /api/1/bookmarks/delete HTTP/1.1\r\n"));
+        assert!(requests[0].contains("Authorization: OAuth oauth_consumer_key=\"consumer%20key\""));
+        assert!(requests[0].contains("oauth_token=\"token\""));
+        assert!(requests[0].ends_with("\r\n\r\nbookmark_id=42"));
+    }
+}
@@ -0,0 +1,45 @@
+use config::Config;
+use credentials;
+use credentials::{Credential, ResultExt};
+
+pub const NAME: &'static str = "instapaper";
+
+#[cfg(feature = "instapaper")]
+pub mod client;
+
+/// Instapaper's Full API authenticates by xAuth, i.e., it exchanges user name and password for an OAuth 1.0a access
+/// token once; cf. `client::auth`. Consumer key and secret have to be requested from Instapaper.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InstapaperConfig {
+    #[serde(default)]
+    pub consumer_key: String,
+    #[serde(default)]
+    pub consumer_secret: String,
+    /// Email address or user name; asked for by 'rat instapaper auth' if unset
+    pub username: Option<String>,
+    /// Asked for by 'rat instapaper auth' if unset; accounts without password leave it empty
+    pub password: Option<String>,
+}
+
+impl InstapaperConfig {
+    /// Returns the secrets of this section by their setting names.
+    pub fn secrets(&self) -> Vec<(String, &String)> {
+        let mut secrets = vec![
+            ("consumer_key".to_string(), &self.consumer_key),
+            ("consumer_secret".to_string(), &self.consumer_secret),
+        ];
+        secrets.extend(self.password.iter().map(|s| ("password".to_string(), s)));
+        secrets
+    }
+}
+
+/// The stored OAuth 1.0a access token including its secret
+pub fn access_token(config: &Config) -> credentials::Result<Credential> {
+    let credential = credentials::load(config, NAME).chain_err(|| credentials::ErrorKind::NoAccessToken(NAME.to_string()))?;
+    if credential.token_secret.is_none() {
+        bail!(credentials::ErrorKind::NoAccessToken(NAME.to_string()));
+    }
+    credentials::record_usage(config, NAME);
+
+    Ok(credential)
+}
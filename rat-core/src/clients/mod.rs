@@ -0,0 +1,10 @@
+pub mod centerdevice;
+pub mod feed;
+pub mod github;
+pub mod hackernews;
+pub mod instapaper;
+pub mod pinboard;
+pub mod pocket;
+pub mod slack;
+pub mod stocks;
+pub mod wallabag;
@@ -0,0 +1,224 @@
+use super::request_auth;
+use config::Config;
+use errors::*;
+use net::auth::Auth;
+use net::http::{check_status, tls_client};
+use net::retry;
+use utils::console::*;
+
+use serde_json;
+use serde_json::Value;
+use serde_urlencoded;
+use std::io::Read;
+
+static BASE_URL: &'static str = "https://api.pinboard.in/v1";
+
+/// Maximum number of bookmarks of `posts/recent`
+pub const MAX_RECENT: usize = 100;
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct Post {
+    pub href: String,
+    /// The title
+    pub description: String,
+    /// The description
+    #[serde(default)] pub extended: String,
+    #[serde(default)] pub hash: String,
+    /// Time of saving, e.g., '2017-07-14T02:40:00Z'
+    pub time: String,
+    /// "yes" or "no"
+    #[serde(default)] pub shared: String,
+    /// "yes" or "no"
+    #[serde(default)] pub toread: String,
+    /// Separated by spaces
+    #[serde(default)] pub tags: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Recent {
+    posts: Vec<Post>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ResultCode {
+    result_code: String,
+}
+
+#[derive(Debug)]
+pub struct AddRequest<'a> {
+    pub url: &'a str,
+    /// Pinboard requires a title
+    pub title: &'a str,
+    pub description: Option<&'a str>,
+    pub tags: Option<&'a str>,
+    pub to_read: bool,
+    pub private: bool,
+    /// Replaces an existing bookmark of the URL instead of failing
+    pub replace: bool,
+    /// Time of saving, e.g., '2017-07-14T02:40:00Z'; defaults to now
+    pub time: Option<&'a str>,
+}
+
+/// Lists the `count` most recent bookmarks or, without `count`, all bookmarks; Pinboard allows listing all bookmarks
+/// only once every five minutes.
+pub fn list(config: &Config, tag: Option<&str>, count: Option<usize>) -> Result<Vec<Post>> {
+    info("Getting list of your bookmarks ...");
+    let mut params = Vec::new();
+    params.extend(tag.map(|tag| ("tag", tag.to_string())));
+    match count {
+        Some(count) => {
+            params.push(("count", count.to_string()));
+            let json = get(config, "/posts/recent", &params, true).chain_err(|| "HTTP call to list bookmarks failed")?;
+            let recent: Recent = serde_json::from_str(&json).chain_err(|| "JSON parsing failed")?;
+            Ok(recent.posts)
+        }
+        None => {
+            let json = get(config, "/posts/all", &params, true).chain_err(|| "HTTP call to list bookmarks failed")?;
+            serde_json::from_str(&json).chain_err(|| "JSON parsing failed")
+        }
+    }
+}
+
+pub fn add(config: &Config, request: &AddRequest) -> Result<()> {
+    verboseln(format!("add = {}", request.url));
+    let yes_no = |b: bool| if b { "yes" } else { "no" }.to_string();
+    let mut params = vec![
+        ("url", request.url.to_string()),
+        ("description", request.title.to_string()),
+        ("toread", yes_no(request.to_read)),
+        ("shared", yes_no(!request.private)),
+        ("replace", yes_no(request.replace)),
+    ];
+    params.extend(request.description.map(|description| ("extended", description.to_string())));
+    params.extend(request.tags.map(|tags| ("tags", tags.to_string())));
+    params.extend(request.time.map(|time| ("dt", time.to_string())));
+    let json = get(config, "/posts/add", &params, false).chain_err(|| format!("HTTP call to add '{}' failed", request.url))?;
+
+    check_result(&json)
+}
+
+/// Pinboard identifies bookmarks by their URL.
+pub fn delete(config: &Config, url: &str) -> Result<()> {
+    let json = get(config, "/posts/delete", &[("url", url.to_string())], false)
+        .chain_err(|| format!("HTTP call to delete '{}' failed", url))?;
+
+    check_result(&json)
+}
+
+/// All tags with the number of their bookmarks
+pub fn tags(config: &Config) -> Result<Vec<(String, u64)>> {
+    info("Getting your tags ...");
+    let json = get(config, "/tags/get", &[], true).chain_err(|| "HTTP call to get tags failed")?;
+
+    parse_tags(&json)
+}
+
+/// All calls of the API are GET requests authenticated by the API token as parameter. Only requests that do not modify
+/// bookmarks are retried.
+fn get(config: &Config, path: &str, params: &[(&str, String)], read_only: bool) -> Result<String> {
+    let auth = request_auth(config).chain_err(|| "Failed to get API token")?;
+    let mut params: Vec<(&str, &str)> = params.iter().map(|&(name, ref value)| (name, value.as_str())).collect();
+    params.extend(auth.params());
+    params.push(("format", "json"));
+    let parameters_enc = serde_urlencoded::to_string(&params).chain_err(|| "URL serialization failed")?;
+    let url = format!("{}{}?{}", BASE_URL, path, parameters_enc);
+
+    let client = tls_client().chain_err(|| "Failed to create TLS client")?;
+    let mut response = if read_only {
+        retry::send(&config.general.retry_policy(), || client.get(&url).send())
+    } else {
+        client.get(&url).send()
+    }.chain_err(|| "Failed to finish HTTP request")?;
+    check_status(&mut response)?;
+
+    let mut buffer = String::new();
+    response.read_to_string(&mut buffer).chain_err(|| "Failed to read HTTP response")?;
+
+    Ok(buffer)
+}
+
+/// Pinboard reports failures of changes with status 200 and a result code other than 'done', e.g., 'item not found'.
+fn check_result(json: &str) -> Result<()> {
+    let result: ResultCode = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
+    if result.result_code != "done" {
+        bail!(result.result_code);
+    }
+
+    Ok(())
+}
+
+/// Tags are sorted by descending count and then by name.
+fn parse_tags(json: &str) -> Result<Vec<(String, u64)>> {
+    let value: Value = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
+    let object = value.as_object().ok_or_else(|| ErrorKind::Parse("tags".to_string()))?;
+    let mut tags = Vec::new();
+    for (tag, count) in object {
+        // Counts used to be sent as strings
+        let count = count.as_u64().or_else(|| count.as_str().and_then(|c| c.parse().ok()))
+            .ok_or_else(|| ErrorKind::Parse(format!("count of tag '{}'", tag)))?;
+        tags.push((tag.to_string(), count));
+    }
+    tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    Ok(tags)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use net::http::with_client;
+    use net::mock::MockClient;
+    use testing;
+
+    use std::rc::Rc;
+
+    fn config() -> Config {
+        testing::config("pinboard-client", r#"
+            [pinboard]
+            api_token = 'user:0123456789ABCDEF'
+        "#)
+    }
+
+    #[test]
+    fn test_list_recent() {
+        ::utils::console::init(::config::Verbosity::QUIET);
+        let json = r#"{"date":"2017-07-14T02:40:00Z","user":"user","posts":[{"href":"https://example.com","description":"Example",
+                       "extended":"","meta":"abc","hash":"def","time":"2017-07-14T02:40:00Z","shared":"no","toread":"yes","tags":"rust cli"}]}"#;
+        let mock = Rc::new(MockClient::new().respond(200, &[], json));
+
+        let posts = with_client(mock.clone(), || list(&config(), Some("rust"), Some(10))).unwrap();
+
+        assert_eq!(posts.len(), 1);
+        assert_eq!(posts[0].tags, "rust cli");
+        assert!(mock.requests()[0].starts_with("GET /v1/posts/recent?tag=rust&count=10&auth_token=user%3A0123456789ABCDEF&format=json HTTP/1.1\r\n"));
+    }
+
+    #[test]
+    fn test_add_fails_with_result_code() {
+        ::utils::console::init(::config::Verbosity::QUIET);
+        let mock = Rc::new(MockClient::new().respond(200, &[], r#"{"result_code":"item already exists"}"#));
+        let request = AddRequest {
+            url: "https://example.com",
+            title: "Example",
+            description: None,
+            tags: Some("rust cli"),
+            to_read: true,
+            private: false,
+            replace: false,
+            time: None,
+        };
+
+        let error = with_client(mock.clone(), || add(&config(), &request)).unwrap_err();
+
+        assert_eq!(error.iter().last().unwrap().to_string(), "item already exists");
+        assert!(mock.requests()[0].contains("&toread=yes&shared=yes&replace=no&tags=rust+cli&"));
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        let tags = parse_tags(r#"{"rust":12,"cli":"3","api":3}"#).unwrap();
+
+        assert_eq!(tags, vec![("rust".to_string(), 12), ("api".to_string(), 3), ("cli".to_string(), 3)]);
+        assert!(parse_tags("[]").is_err());
+    }
+}
@@ -0,0 +1,32 @@
+use config::Config;
+use credentials;
+use net::auth::ParamAuth;
+
+pub const NAME: &'static str = "pinboard";
+
+#[cfg(feature = "pinboard")]
+pub mod client;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PinboardConfig {
+    /// API token of the form '<user>:<token>'; cf. https://pinboard.in/settings/password
+    pub api_token: Option<String>,
+}
+
+impl PinboardConfig {
+    /// Returns the secrets of this section by their setting names.
+    pub fn secrets(&self) -> Vec<(String, &String)> {
+        self.api_token.iter().map(|s| ("api_token".to_string(), s)).collect()
+    }
+}
+
+pub fn access_token(config: &Config) -> credentials::Result<String> {
+    credentials::access_token(config, NAME, &config.pinboard.api_token)
+}
+
+/// Pinboard expects the API token as parameter 'auth_token' of every request.
+pub fn request_auth(config: &Config) -> credentials::Result<ParamAuth> {
+    let api_token = access_token(config)?;
+
+    Ok(ParamAuth::new(&[("auth_token", &api_token)]))
+}
@@ -0,0 +1,1672 @@
+pub use self::add::add;
+pub use self::api::{ListOptions, PocketClient};
+pub use self::auth::auth;
+pub use self::list::list;
+pub use self::send::{send, send_batched};
+
+mod auth {
+    use config::Config;
+    use credentials;
+    use credentials::Credential;
+    use errors::*;
+    use clients::pocket::NAME;
+    use net::http::{check_status, tls_client};
+    use net::redirect::{self, RedirectListener};
+    use utils::console::*;
+
+    use hyper::Client;
+    use hyper::header::ContentType;
+    use mime::Mime;
+    use rand::{self, Rng};
+    use serde_json;
+    use serde_urlencoded;
+    use std::io;
+    use std::io::Read;
+    use std::str;
+    use webbrowser;
+
+    static REDIRECT_URI: &'static str = "https://lukaspustina.github.io/rat/redirects/pocket.html";
+
+    header! { (XAccept, "X-Accept") => [Mime] }
+
+    #[derive(Serialize, Debug)]
+    struct CodeRequest<'a> {
+        consumer_key: &'a str,
+        redirect_uri: &'a str,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Code {
+        code: String,
+    }
+
+    #[derive(Serialize, Debug)]
+    struct TokenRequest<'a> {
+        consumer_key: &'a str,
+        code: &'a str,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Token {
+        access_token: String,
+        username: String,
+    }
+
+    pub fn auth(config: &Config, open_browser: bool, manual: bool) -> Result<()> {
+        do_auth(config, open_browser, manual).chain_err(|| "failed to make HTTP auth call")
+    }
+
+    fn do_auth(config: &Config, open_browser: bool, manual: bool) -> Result<()> {
+        let client = tls_client().chain_err(|| "Failed to create TLS client")?;
+
+        let code = if manual {
+            let code = get_code(&client, config, REDIRECT_URI)?;
+            web_auth(&code, REDIRECT_URI, open_browser)?;
+            wait_for_return();
+            code
+        } else {
+            let listener = RedirectListener::bind().chain_err(|| "Failed to create redirect listener")?;
+            // Pocket redirects to exactly this URI, so its state tells the redirect apart from other local requests
+            let state: String = rand::thread_rng().gen_ascii_chars().take(16).collect();
+            let redirect_uri = format!("{}?state={}", listener.redirect_uri(), state);
+            let code = get_code(&client, config, &redirect_uri)?;
+            web_auth(&code, &redirect_uri, open_browser)?;
+            listener.wait_for_redirect(&[("state", &state)], redirect::DEFAULT_TIMEOUT)
+                .chain_err(|| "Failed to receive redirect from Pocket")?;
+            code
+        };
+        let token = exchange_token(&client, &code, config)?;
+
+        let mut credential = Credential::new(token.access_token);
+        credential.username = Some(token.username);
+        credentials::store(config, NAME, &credential).chain_err(|| "Failed to store access token")?;
+        msgln(format!("Received access token for user '{}' and stored it in the credential store.",
+                      credential.username.as_ref().unwrap()));
+        if config.pocket.access_token.is_some() {
+            warning("The access token in your configuration, section '[pocket]', takes precedence; please remove it to use the stored token.");
+        }
+
+        Ok(())
+    }
+
+    // Step 1 -- get code
+    fn get_code(client: &Client, config: &Config, redirect_uri: &str) -> Result<Code> {
+        info("Requesting authentication code ...");
+        let code_request = CodeRequest { consumer_key: &config.pocket.consumer_key, redirect_uri: redirect_uri };
+        let code_request_json = serde_json::to_string(&code_request).chain_err(|| "JSON serialization failed")?;
+
+        let url = "https://getpocket.com/v3/oauth/request";
+        let mut response = client
+            .post(url)
+            .header(XAccept(mime!(Application / Json)))
+            .header(ContentType(mime!(Application / Json)))
+            .body(&code_request_json)
+            .send()
+            .chain_err(|| "Failed to send HTTP request")?;
+        check_status(&mut response)?;
+
+        let mut buffer = Vec::new();
+        response.read_to_end(&mut buffer).chain_err(|| "Failed to read HTTP response")?;
+
+        if config.general.output_format.is_json() {
+            info("Received response:");
+            msgln(str::from_utf8(&buffer).chain_err(|| "Failed to print buffer")?);
+        }
+        let code: Code = serde_json::from_slice(&buffer).chain_err(|| "JSON parsing failed")?;
+
+        Ok(code)
+    }
+
+    // Step 2 -- Wait for Web UI authentication
+    fn web_auth(code: &Code, redirect_uri: &str, open_browser: bool) -> Result<()> {
+        info("Authorizing code via Pocket ...");
+        let auth_url = auth_url(code, redirect_uri)?;
+        if open_browser {
+            msgln("Please authenticate in the web browser window ...");
+            webbrowser::open(&auth_url).chain_err(|| "Failed to open web browser")?;
+        } else {
+            msgln("Please authenticate at the following URL ...");
+            msgln(format!("\n\t{}\n", auth_url));
+        }
+
+        Ok(())
+    }
+
+    fn auth_url(code: &Code, redirect_uri: &str) -> Result<String> {
+        let parameters = &[("request_token", code.code.as_str()), ("redirect_uri", redirect_uri)];
+        let parameters_enc = serde_urlencoded::to_string(parameters).chain_err(|| "URL serialization failed")?;
+
+        Ok(format!("https://getpocket.com/auth/authorize?{}", parameters_enc))
+    }
+
+    fn wait_for_return() {
+        msg("... and then press return.");
+        let mut input = String::new();
+        let _ = io::stdin().read_line(&mut input);
+    }
+
+    // Step 3 -- Exchange code for access token
+    fn exchange_token(client: &Client, code: &Code, config: &Config) -> Result<Token> {
+        info("Requesting access token ...");
+        let token_request = TokenRequest { consumer_key: &config.pocket.consumer_key, code: &code.code };
+        let token_request_token = serde_json::to_string(&token_request).chain_err(|| "JSON serialization failed")?;
+        let url = "https://getpocket.com/v3/oauth/authorize";
+        let mut response = client
+            .post(url)
+            .header(XAccept(mime!(Application / Json)))
+            .header(ContentType(mime!(Application / Json)))
+            .body(&token_request_token)
+            .send()
+            .chain_err(|| "Failed to send HTTP request")?;
+        check_status(&mut response)?;
+
+        let mut buffer = Vec::new();
+        response.read_to_end(&mut buffer).chain_err(|| "Failed to read HTTP response")?;
+
+        if config.general.output_format.is_json() {
+            info("Received response:");
+            msgln(str::from_utf8(&buffer).chain_err(|| "Failed to print buffer")?);
+        }
+        let token: Token = serde_json::from_slice(&buffer).chain_err(|| "JSON parsing failed")?;
+
+        Ok(token)
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use utils::json::*;
+
+        proptest! {
+            #[test]
+            fn test_auth_request_serialization(
+                ref consumer_key in "[0-9]{5}-[0-9a-f]{24}",
+                ref redirect_uri in "https?://[a-z.]{1,20}(:[0-9]{1,5})?/[a-z/]{0,20}",
+                ref code in "[0-9a-f-]{30}"
+            ) {
+                let json = to_value(&CodeRequest { consumer_key: consumer_key, redirect_uri: redirect_uri });
+                prop_assert_eq!(keys(&json), sorted(&["consumer_key", "redirect_uri"]));
+                prop_assert_eq!(json["redirect_uri"].as_str(), Some(redirect_uri.as_str()));
+
+                let json = to_value(&TokenRequest { consumer_key: consumer_key, code: code });
+                prop_assert_eq!(keys(&json), sorted(&["consumer_key", "code"]));
+                prop_assert_eq!(json["code"].as_str(), Some(code.as_str()));
+            }
+        }
+
+        #[test]
+        fn test_auth_url_encodes_redirect_uri() {
+            let code = Code { code: "dcba4321-dcba-4321-a1b2-dcba43".to_string() };
+
+            let url = auth_url(&code, "http://127.0.0.1:8042/pocket?state=a&b").unwrap();
+
+            assert_eq!(url, "https://getpocket.com/auth/authorize?request_token=dcba4321-dcba-4321-a1b2-dcba43\
+                             &redirect_uri=http%3A%2F%2F127.0.0.1%3A8042%2Fpocket%3Fstate%3Da%26b");
+        }
+    }
+}
+
+pub mod list {
+    use cache::index::{Indexed, words};
+    use cache::responses::ResponseCache;
+    use clients::pocket::{request_auth, NAME};
+    use config::Config;
+    use errors::*;
+    use net::auth::Auth;
+    use net::concurrent;
+    use net::http::{check_status, tls_client};
+    use net::retry::{self, RetryPolicy};
+    use utils::console::*;
+
+    use hyper::client::Response;
+    use hyper::header::ContentType;
+    use serde::{de, Deserialize, Deserializer};
+    use serde::de::{DeserializeSeed, MapVisitor, SeqVisitor, Visitor};
+    use serde::de::impls::IgnoredAny;
+    use serde_json;
+    use serde_json::Value;
+    use std::cmp;
+    use std::collections::{HashMap, HashSet};
+    use std::fmt;
+    use std::io;
+    use std::io::{Read, Write};
+    use std::str;
+    use std::time::{Duration, Instant};
+
+    /// Reading speed to estimate the reading time of articles Pocket has not estimated
+    pub const WORDS_PER_MINUTE: u64 = 200;
+
+    #[allow(non_camel_case_types)]
+    #[derive(Serialize, Debug, Clone, Copy)]
+    pub enum State {
+        unread,
+        archive,
+        all,
+    }
+
+    impl<'a> From<&'a str> for State {
+        fn from(s: &'a str) -> Self {
+            match s {
+                "archive" => State::archive,
+                "all" => State::all,
+                _ => State::unread,
+            }
+        }
+    }
+
+    #[allow(non_camel_case_types)]
+    #[derive(Serialize, Debug, Clone, Copy)]
+    pub enum Sort {
+        newest,
+        oldest,
+        title,
+        site,
+    }
+
+    impl<'a> From<&'a str> for Sort {
+        fn from(s: &'a str) -> Self {
+            match s {
+                "oldest" => Sort::oldest,
+                "title" => Sort::title,
+                "site" => Sort::site,
+                _ => Sort::newest
+            }
+        }
+    }
+
+    #[allow(non_camel_case_types)]
+    #[derive(Serialize, Debug)]
+    pub enum DetailType {
+        simple,
+        complete,
+    }
+
+    impl From<bool> for DetailType {
+        fn from(b: bool) -> Self {
+            if b {
+                DetailType::complete
+            } else {
+                DetailType::simple
+            }
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[derive(Serialize, Debug)]
+    pub struct Request<'a> {
+        #[serde(skip_serializing_if = "Option::is_none")] pub state: Option<State>,
+        #[serde(skip_serializing_if = "Option::is_none")] pub tag: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")] pub sort: Option<Sort>,
+        pub detailType: DetailType,
+        #[serde(skip_serializing_if = "Option::is_none")] pub search: Option<&'a str>,
+        /// Only articles changed since this Unix timestamp, including deleted ones; cf. `Article::is_deleted`
+        #[serde(skip_serializing_if = "Option::is_none")] pub since: Option<u64>,
+        /// Only favorites if 1, only other articles if 0
+        #[serde(skip_serializing_if = "Option::is_none")] pub favorite: Option<u8>,
+        /// Maximum number of articles to return; all if unset
+        #[serde(skip_serializing_if = "Option::is_none")] pub count: Option<usize>,
+        /// Number of articles to skip; only honored with `count`
+        #[serde(skip_serializing_if = "Option::is_none")] pub offset: Option<usize>,
+    }
+
+    pub fn list(config: &Config, request: &Request, since: Option<Duration>, until: Option<Duration>)
+                -> Result<String> {
+        info("Getting list of your articles ...");
+        let mut json = do_list(config, request).chain_err(|| "HTTP call to list articles failed")?;
+
+        if since.is_some() || until.is_some() {
+            let list: ListResult = serde_json::from_str(&json).chain_err(|| "JSON parsing failed")?;
+            info(format!("Filtering list of your {} article(s) ...", list.list.len()));
+            let list = list.filter(&since, &until)?;
+            json = serde_json::to_string(&list).chain_err(|| "JSON serialization failed")?;
+        }
+
+        Ok(json)
+    }
+
+    /// Like `list`, but passes the articles added within `since` and `until` to `on_article` while the response is still
+    /// being received. Neither the response body nor the whole list is held in memory, which matters for accounts with
+    /// tens of thousands of articles. The articles arrive in the requested sort order.
+    pub fn list_streamed<F>(config: &Config, request: &Request, since: Option<Duration>, until: Option<Duration>, on_article: F)
+                            -> Result<ListSummary> where F: FnMut(Article) -> Result<()> {
+        info("Getting list of your articles ...");
+        let mut on_article = on_article;
+        let response = send_list_request(config, request).chain_err(|| "HTTP call to list articles failed")?;
+
+        stream(response, |article| {
+            if is_within(&article, &since, &until)? {
+                on_article(article)
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    /// Copies the list response unchanged to `writer` while it is being received.
+    pub fn list_to<W: Write>(config: &Config, request: &Request, writer: &mut W) -> Result<u64> {
+        info("Getting list of your articles ...");
+        let mut response = send_list_request(config, request).chain_err(|| "HTTP call to list articles failed")?;
+
+        io::copy(&mut response, writer).chain_err(|| "Failed to read HTTP response")
+    }
+
+    fn do_list(config: &Config, request: &Request) -> Result<String> {
+        let mut response = send_list_request(config, request)?;
+
+        let mut buffer = Vec::new();
+        response.read_to_end(&mut buffer).chain_err(|| "Failed to read HTTP response")?;
+        let json = str::from_utf8(&buffer).chain_err(|| "Data copying failed.")?;
+
+        Ok(json.to_string())
+    }
+
+    /// The list response as it is received, or as it has been cached; cf. `cache::responses`
+    enum ListResponse {
+        Live(Response),
+        Cached(io::Cursor<Vec<u8>>),
+    }
+
+    impl Read for ListResponse {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match *self {
+                ListResponse::Live(ref mut response) => response.read(buf),
+                ListResponse::Cached(ref mut body) => body.read(buf),
+            }
+        }
+    }
+
+    /// With a response cache, the response is read completely before it is passed on.
+    fn send_list_request(config: &Config, request: &Request) -> Result<ListResponse> {
+        let request = authorized(config, request)?;
+        let request_json = serde_json::to_string(&request).chain_err(|| "JSON serialization failed")?;
+        let policy = config.general.retry_policy();
+        let cache = ResponseCache::new(config, NAME);
+        if !cache.is_enabled() {
+            return post_list_request(&request_json, &policy).map(ListResponse::Live);
+        }
+
+        let body = cache.get_or_fetch("get", &request_json, || -> Result<Vec<u8>> {
+            let mut response = post_list_request(&request_json, &policy)?;
+            let mut body = Vec::new();
+            response.read_to_end(&mut body).chain_err(|| "Failed to read HTTP response")?;
+            Ok(body)
+        })?;
+
+        Ok(ListResponse::Cached(io::Cursor::new(body)))
+    }
+
+    /// The request with the consumer key and the access token
+    fn authorized(config: &Config, request: &Request) -> Result<Value> {
+        let auth = request_auth(config).chain_err(|| "Failed to get access token")?;
+        let mut request = serde_json::to_value(request).chain_err(|| "JSON serialization failed")?;
+        auth.inject(&mut request);
+
+        Ok(request)
+    }
+
+    /// Listing does not modify data, so failed requests are retried.
+    fn post_list_request(request_json: &str, policy: &RetryPolicy) -> Result<Response> {
+        verboseln(format!("request = {}", request_json));
+
+        let client = tls_client().chain_err(|| "Failed to create TLS client")?;
+
+        let url = "https://getpocket.com/v3/get";
+        let mut response = retry::send(policy, || client
+            .post(url)
+            .header(ContentType(mime!(Application / Json)))
+            .body(request_json)
+            .send())
+            .chain_err(|| "Failed to finish HTTP request")?;
+        check_status(&mut response)?;
+
+        Ok(response)
+    }
+
+    /// Page sizes adapt to the response latency between these bounds.
+    pub const MIN_PAGE_SIZE: usize = 10;
+    pub const MAX_PAGE_SIZE: usize = 500;
+    /// Pages taking longer shrink; pages taking less than half of it grow.
+    const TARGET_PAGE_LATENCY_MS: u64 = 2000;
+
+    struct Page {
+        summary: ListSummary,
+        articles: Vec<Article>,
+        latency: Duration,
+    }
+
+    /// Like `list_streamed`, but fetches the articles page by page starting with `page_size` articles per page. The first
+    /// page tells the total number of articles, so the remaining pages are fetched concurrently in waves of up to
+    /// `general.max_concurrency` pages; the articles are passed to `on_article` in the order of
+    /// the pages nevertheless. Articles moving to a later page while paging are passed only once.
+    pub fn list_all<F>(config: &Config, request: &Request, since: Option<Duration>, until: Option<Duration>, page_size: usize,
+                       on_article: F) -> Result<ListSummary> where F: FnMut(Article) -> Result<()> {
+        info("Getting list of your articles page by page ...");
+        let mut on_article = on_article;
+        let request = authorized(config, request)?;
+        let mut seen = HashSet::new();
+        let mut deliver = |articles: Vec<Article>| -> Result<usize> {
+            let mut count = 0;
+            for article in articles {
+                if seen.insert(article.item_id.clone()) && is_within(&article, &since, &until)? {
+                    on_article(article)?;
+                }
+                count += 1;
+            }
+            Ok(count)
+        };
+
+        let concurrency = config.general.max_concurrency();
+        let mut page_size = cmp::max(page_size, 1);
+        let policy = config.general.retry_policy();
+        let first = fetch_page(&request, 0, page_size, true, &policy).chain_err(|| "HTTP call to list articles failed")?;
+        let received = first.articles.len();
+        let total = first.summary.total.unwrap_or(received);
+        // A short first page of a longer list reveals the maximum page size of the server
+        let max_page_size = if received < page_size && received < total { cmp::max(received, 1) } else { MAX_PAGE_SIZE };
+        page_size = cmp::min(page_size, max_page_size);
+        let mut latency = first.latency;
+        let mut summary = ListSummary { count: 0, ..first.summary };
+        summary.count += deliver(first.articles)?;
+        let mut offset = received;
+
+        while offset < total && received > 0 {
+            page_size = adapt_page_size(page_size, latency, max_page_size);
+            let offsets: Vec<usize> = (0..cmp::max(concurrency, 1))
+                .map(|i| offset + i * page_size)
+                .take_while(|&o| o < total)
+                .collect();
+            verboseln(format!("Fetching {} page(s) of {} article(s) from offset {} ...", offsets.len(), page_size, offset));
+            offset = offsets.last().map(|o| o + page_size).unwrap_or(total);
+
+            let page_request = request.clone();
+            let pages = concurrent::map_bounded(offsets, concurrency, move |o| fetch_page(&page_request, o, page_size, false, &policy));
+            latency = Duration::from_secs(0);
+            for page in pages {
+                let page = page.chain_err(|| "HTTP call to list articles failed")?;
+                latency = cmp::max(latency, page.latency);
+                if page.articles.is_empty() {
+                    // The list shrank while paging
+                    offset = total;
+                }
+                summary.count += deliver(page.articles)?;
+            }
+        }
+
+        Ok(summary)
+    }
+
+    fn adapt_page_size(page_size: usize, latency: Duration, max_page_size: usize) -> usize {
+        let target = Duration::from_millis(TARGET_PAGE_LATENCY_MS);
+        let page_size = if latency > target {
+            page_size / 2
+        } else if latency < target / 2 {
+            page_size * 2
+        } else {
+            page_size
+        };
+
+        cmp::max(cmp::min(page_size, max_page_size), cmp::min(MIN_PAGE_SIZE, max_page_size))
+    }
+
+    fn fetch_page(request: &Value, offset: usize, count: usize, total: bool, policy: &RetryPolicy) -> Result<Page> {
+        let mut request = request.clone();
+        if let Value::Object(ref mut map) = request {
+            map.insert("offset".to_string(), Value::from(offset));
+            map.insert("count".to_string(), Value::from(count));
+            if total {
+                map.insert("total".to_string(), Value::from("1".to_string()));
+            }
+        }
+        let request_json = serde_json::to_string(&request).chain_err(|| "JSON serialization failed")?;
+
+        let start = Instant::now();
+        let response = post_list_request(&request_json, policy)?;
+        let mut articles = Vec::new();
+        let summary = stream(response, |article| { articles.push(article); Ok(()) })?;
+
+        Ok(Page { summary: summary, articles: articles, latency: start.elapsed() })
+    }
+
+    /// Status of a list response whose articles have been streamed; cf. `stream`.
+    #[derive(Debug, PartialEq)]
+    pub struct ListSummary {
+        pub status: i32,
+        pub complete: i32,
+        /// Server time of the response; pass it as `since` of the next request to receive only the changes
+        pub since: Option<u64>,
+        /// Number of all articles matching the request, if requested by 'total'; cf. `list_all`
+        pub total: Option<usize>,
+        /// Number of articles in the response, including the ones filtered out
+        pub count: usize,
+    }
+
+    /// Reads a list response from `reader` and passes each article to `on_article` as soon as it has been parsed. An
+    /// error returned by `on_article` stops reading and is returned as is.
+    pub fn stream<R: Read, F>(reader: R, on_article: F) -> Result<ListSummary> where F: FnMut(Article) -> Result<()> {
+        let mut on_article = on_article;
+        let mut failure = None;
+        let result = {
+            let seed = ListSeed { on_article: &mut on_article, failure: &mut failure };
+            let mut deserializer = serde_json::Deserializer::from_reader(reader);
+            seed.deserialize(&mut deserializer).and_then(|summary| deserializer.end().map(|_| summary))
+        };
+        if let Some(e) = failure {
+            return Err(e);
+        }
+
+        result.chain_err(|| "JSON parsing failed")
+    }
+
+    struct ListSeed<'a, F: 'a> {
+        on_article: &'a mut F,
+        failure: &'a mut Option<Error>,
+    }
+
+    impl<'a, F> DeserializeSeed for ListSeed<'a, F> where F: FnMut(Article) -> Result<()> {
+        type Value = ListSummary;
+
+        fn deserialize<D: Deserializer>(self, deserializer: D) -> ::std::result::Result<ListSummary, D::Error> {
+            deserializer.deserialize_map(self)
+        }
+    }
+
+    impl<'a, F> Visitor for ListSeed<'a, F> where F: FnMut(Article) -> Result<()> {
+        type Value = ListSummary;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a Pocket list response")
+        }
+
+        fn visit_map<V: MapVisitor>(self, mut visitor: V) -> ::std::result::Result<ListSummary, V::Error> {
+            let (mut status, mut complete, mut since, mut total, mut count) = (None, None, None, None, 0);
+            while let Some(key) = visitor.visit_key::<String>()? {
+                match key.as_str() {
+                    "status" => status = Some(visitor.visit_value()?),
+                    "complete" => complete = Some(visitor.visit_value()?),
+                    "since" => since = visitor.visit_value()?,
+                    // Pocket sends the total as string
+                    "total" => total = match visitor.visit_value()? {
+                        Value::String(ref s) => s.parse().ok(),
+                        Value::Number(ref n) => n.as_u64().map(|n| n as usize),
+                        _ => None,
+                    },
+                    "list" => {
+                        let articles = ArticlesSeed { on_article: &mut *self.on_article, failure: &mut *self.failure };
+                        count = visitor.visit_value_seed(articles)?;
+                    }
+                    _ => { visitor.visit_value::<IgnoredAny>()?; }
+                }
+            }
+            let status = status.ok_or_else(|| de::Error::missing_field("status"))?;
+            let complete = complete.ok_or_else(|| de::Error::missing_field("complete"))?;
+
+            Ok(ListSummary { status: status, complete: complete, since: since, total: total, count: count })
+        }
+    }
+
+    struct ArticlesSeed<'a, F: 'a> {
+        on_article: &'a mut F,
+        failure: &'a mut Option<Error>,
+    }
+
+    impl<'a, F> DeserializeSeed for ArticlesSeed<'a, F> where F: FnMut(Article) -> Result<()> {
+        type Value = usize;
+
+        fn deserialize<D: Deserializer>(self, deserializer: D) -> ::std::result::Result<usize, D::Error> {
+            deserializer.deserialize(self)
+        }
+    }
+
+    impl<'a, F> Visitor for ArticlesSeed<'a, F> where F: FnMut(Article) -> Result<()> {
+        type Value = usize;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a map of articles")
+        }
+
+        fn visit_map<V: MapVisitor>(self, mut visitor: V) -> ::std::result::Result<usize, V::Error> {
+            let mut count = 0;
+            while visitor.visit_key::<IgnoredAny>()?.is_some() {
+                let article: Article = visitor.visit_value()?;
+                count += 1;
+                if let Err(e) = (self.on_article)(article) {
+                    *self.failure = Some(e);
+                    return Err(de::Error::custom("aborted"));
+                }
+            }
+
+            Ok(count)
+        }
+
+        /// Pocket sends an empty array instead of an empty object if no article matches.
+        fn visit_seq<V: SeqVisitor>(self, mut visitor: V) -> ::std::result::Result<usize, V::Error> {
+            match visitor.visit::<IgnoredAny>()? {
+                None => Ok(0),
+                Some(_) => Err(de::Error::custom("expected an empty array")),
+            }
+        }
+    }
+
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct ListResult {
+        pub status: i32,
+        pub complete: i32,
+        #[serde(deserialize_with = "deserialize_list")]
+        pub list: HashMap<String, Article>,
+    }
+
+    /// Pocket sends an empty array instead of an empty object if no article matches.
+    fn deserialize_list<D: Deserializer>(deserializer: D) -> ::std::result::Result<HashMap<String, Article>, D::Error> {
+        match <Value as Deserialize>::deserialize(deserializer)? {
+            Value::Array(ref articles) if articles.is_empty() => Ok(HashMap::new()),
+            value => serde_json::from_value(value).map_err(de::Error::custom),
+        }
+    }
+
+    impl ListResult {
+        pub fn filter(self, since: &Option<Duration>, until: &Option<Duration>) -> Result<Self> {
+            let mut new_list: HashMap<String, Article> = HashMap::new();
+            for (k, v) in self.list {
+                if is_within(&v, since, until)? {
+                    new_list.insert(k, v);
+                }
+            }
+
+            Ok(ListResult { status: self.status, complete: self.complete, list: new_list })
+        }
+    }
+
+    /// Whether the article was added between `since` and `until` ago, both as durations since the Unix epoch
+    pub fn is_within(article: &Article, since: &Option<Duration>, until: &Option<Duration>) -> Result<bool> {
+        if since.is_none() && until.is_none() {
+            return Ok(true);
+        }
+        let time_added = article.time_added()?;
+        if let Some(since) = *since {
+            if time_added < since { return Ok(false) };
+        }
+        if let Some(until) = *until {
+            if time_added > until { return Ok(false) };
+        }
+
+        Ok(true)
+    }
+
+    /// An item of Pocket's list. Pocket omits the fields of articles it has not resolved yet, and of deleted articles, so
+    /// all fields but the id default to empty. Its payloads are inconsistent, too: empty maps arrive as `[]`, and numbers
+    /// as strings or numbers; both are accepted. Empty fields are not serialized.
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    pub struct Article {
+        #[serde(deserialize_with = "lenient_string")]
+        pub item_id: String,
+        #[serde(default, deserialize_with = "lenient_string", skip_serializing_if = "String::is_empty")]
+        pub resolved_id: String,
+        /// As saved by the user; cf. `url`
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        pub given_url: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        pub given_title: String,
+        #[serde(default)]
+        pub resolved_title: String,
+        #[serde(default)]
+        pub resolved_url: String,
+        #[serde(default, deserialize_with = "lenient_string")]
+        pub time_added: String,
+        #[serde(default, deserialize_with = "lenient_string")]
+        pub time_updated: String,
+        /// Unix timestamp, or '0' if unread
+        #[serde(default, deserialize_with = "lenient_string", skip_serializing_if = "String::is_empty")]
+        pub time_read: String,
+        /// Unix timestamp, or '0' if no favorite
+        #[serde(default, deserialize_with = "lenient_string", skip_serializing_if = "String::is_empty")]
+        pub time_favorited: String,
+        /// '0' unread, '1' archived, '2' deleted
+        #[serde(default, deserialize_with = "lenient_string")]
+        pub status: String,
+        /// '1' favorite, '0' other
+        #[serde(default, deserialize_with = "lenient_string")]
+        pub favorite: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        pub excerpt: String,
+        /// '1' if Pocket considers the item an article
+        #[serde(default, deserialize_with = "lenient_string", skip_serializing_if = "String::is_empty")]
+        pub is_article: String,
+        /// '1' if the item is an index page, e.g., a blog's start page
+        #[serde(default, deserialize_with = "lenient_string", skip_serializing_if = "String::is_empty")]
+        pub is_index: String,
+        /// '0' none, '1' has images, '2' is an image
+        #[serde(default, deserialize_with = "lenient_string", skip_serializing_if = "String::is_empty")]
+        pub has_image: String,
+        /// '0' none, '1' has videos, '2' is a video
+        #[serde(default, deserialize_with = "lenient_string", skip_serializing_if = "String::is_empty")]
+        pub has_video: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        pub lang: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        pub top_image_url: String,
+        /// By tag; only sent for detail type 'complete'
+        #[serde(default, deserialize_with = "lenient_map", skip_serializing_if = "HashMap::is_empty")]
+        pub tags: HashMap<String, Tag>,
+        /// By author id; only sent for detail type 'complete'
+        #[serde(default, deserialize_with = "lenient_map", skip_serializing_if = "HashMap::is_empty")]
+        pub authors: HashMap<String, Author>,
+        /// By image id; only sent for detail type 'complete'
+        #[serde(default, deserialize_with = "lenient_map", skip_serializing_if = "HashMap::is_empty")]
+        pub images: HashMap<String, Image>,
+        /// By video id; only sent for detail type 'complete'
+        #[serde(default, deserialize_with = "lenient_map", skip_serializing_if = "HashMap::is_empty")]
+        pub videos: HashMap<String, Video>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub domain_metadata: Option<DomainMetadata>,
+        /// Only sent for detail type 'complete'
+        #[serde(default, deserialize_with = "lenient_string", skip_serializing_if = "String::is_empty")]
+        pub word_count: String,
+        /// Minutes as estimated by Pocket; only sent for detail type 'complete', and not for every article
+        #[serde(default, deserialize_with = "lenient_u64", skip_serializing_if = "Option::is_none")]
+        pub time_to_read: Option<u64>,
+        /// Seconds to listen to the article as estimated by Pocket
+        #[serde(default, deserialize_with = "lenient_u64", skip_serializing_if = "Option::is_none")]
+        pub listen_duration_estimate: Option<u64>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+    pub struct Tag {
+        #[serde(default, deserialize_with = "lenient_string")]
+        pub item_id: String,
+        #[serde(default)]
+        pub tag: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+    pub struct Author {
+        #[serde(default, deserialize_with = "lenient_string")]
+        pub author_id: String,
+        #[serde(default)]
+        pub name: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        pub url: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+    pub struct Image {
+        #[serde(default, deserialize_with = "lenient_string")]
+        pub image_id: String,
+        #[serde(default)]
+        pub src: String,
+        #[serde(default, deserialize_with = "lenient_string", skip_serializing_if = "String::is_empty")]
+        pub width: String,
+        #[serde(default, deserialize_with = "lenient_string", skip_serializing_if = "String::is_empty")]
+        pub height: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        pub credit: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        pub caption: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+    pub struct Video {
+        #[serde(default, deserialize_with = "lenient_string")]
+        pub video_id: String,
+        #[serde(default)]
+        pub src: String,
+        #[serde(default, deserialize_with = "lenient_string", skip_serializing_if = "String::is_empty")]
+        pub width: String,
+        #[serde(default, deserialize_with = "lenient_string", skip_serializing_if = "String::is_empty")]
+        pub height: String,
+        /// Id of the video at its provider, e.g., YouTube
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        pub vid: String,
+        /// Seconds
+        #[serde(default, deserialize_with = "lenient_string", skip_serializing_if = "String::is_empty")]
+        pub length: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+    pub struct DomainMetadata {
+        #[serde(default)]
+        pub name: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        pub logo: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        pub greyscale_logo: String,
+    }
+
+    /// Accepts strings, numbers, and booleans as string, and null as empty string.
+    fn lenient_string<D: Deserializer>(deserializer: D) -> ::std::result::Result<String, D::Error> {
+        match <Value as Deserialize>::deserialize(deserializer)? {
+            Value::String(s) => Ok(s),
+            Value::Null => Ok(String::new()),
+            Value::Bool(b) => Ok(if b { "1" } else { "0" }.to_string()),
+            value @ Value::Number(_) => Ok(value.to_string()),
+            value => Err(de::Error::custom(format!("expected a string or number, found {}", value))),
+        }
+    }
+
+    /// Accepts numbers and strings of numbers; null and empty strings are `None`.
+    fn lenient_u64<D: Deserializer>(deserializer: D) -> ::std::result::Result<Option<u64>, D::Error> {
+        match <Value as Deserialize>::deserialize(deserializer)? {
+            Value::Null => Ok(None),
+            Value::String(ref s) if s.is_empty() => Ok(None),
+            Value::String(ref s) => s.parse().map(Some).map_err(de::Error::custom),
+            Value::Number(ref n) => n.as_u64().map(Some).ok_or_else(|| de::Error::custom(format!("expected an unsigned number, found {}", n))),
+            value => Err(de::Error::custom(format!("expected a number, found {}", value))),
+        }
+    }
+
+    /// Pocket sends an empty array instead of an empty object, like for the list itself; cf. `deserialize_list`.
+    fn lenient_map<D: Deserializer, T>(deserializer: D) -> ::std::result::Result<HashMap<String, T>, D::Error>
+        where T: de::Deserialize {
+        match <Value as Deserialize>::deserialize(deserializer)? {
+            Value::Array(ref values) if values.is_empty() => Ok(HashMap::new()),
+            Value::Null => Ok(HashMap::new()),
+            value => serde_json::from_value(value).map_err(de::Error::custom),
+        }
+    }
+
+    impl Indexed for Article {
+        fn id(&self) -> &str {
+            &self.item_id
+        }
+
+        /// Columns 'word' of title and URL, 'url' without scheme and trailing slash, and 'domain'
+        fn columns(&self) -> Vec<(&'static str, String)> {
+            let mut columns: Vec<(&'static str, String)> = words(&format!("{} {}", self.title(), self.url()))
+                .into_iter().map(|word| ("word", word)).collect();
+            let url = self.normalized_url();
+            if !url.is_empty() {
+                columns.push(("domain", domain_of(&url)));
+                columns.push(("url", url));
+            }
+
+            columns
+        }
+    }
+
+    fn domain_of(normalized_url: &str) -> String {
+        let domain = normalized_url.split('/').next().unwrap_or("");
+        if domain.starts_with("www.") { domain[4..].to_string() } else { domain.to_string() }
+    }
+
+    impl Article {
+        /// Lower case URL without scheme and trailing slash
+        fn normalized_url(&self) -> String {
+            let mut url = self.url().splitn(2, "://").last().unwrap_or("").to_lowercase();
+            while url.ends_with('/') {
+                url.pop();
+            }
+            url
+        }
+
+        /// Domain without 'www.' as indexed by column 'domain'
+        pub fn domain(&self) -> Option<String> {
+            let url = self.normalized_url();
+            if url.is_empty() { None } else { Some(domain_of(&url)) }
+        }
+
+        /// Resolved title, else the title given when saving, else the URL
+        pub fn title(&self) -> &str {
+            if !self.resolved_title.is_empty() {
+                &self.resolved_title
+            } else if !self.given_title.is_empty() {
+                &self.given_title
+            } else {
+                self.url()
+            }
+        }
+
+        /// Resolved URL, else the URL given when saving
+        pub fn url(&self) -> &str {
+            if !self.resolved_url.is_empty() { &self.resolved_url } else { &self.given_url }
+        }
+
+        pub fn is_deleted(&self) -> bool {
+            self.status == "2"
+        }
+
+        pub fn tag_names(&self) -> Vec<&str> {
+            let mut names: Vec<&str> = self.tags.keys().map(|t| t.as_str()).collect();
+            names.sort();
+            names
+        }
+
+        /// Pocket sends '0' for articles it could not count.
+        pub fn words(&self) -> Option<u64> {
+            match self.word_count.parse() {
+                Ok(0) | Err(_) => None,
+                Ok(words) => Some(words),
+            }
+        }
+
+        /// Minutes to read as estimated by Pocket, or else estimated from the word count
+        pub fn read_time(&self) -> Option<u64> {
+            match self.time_to_read {
+                Some(minutes) if minutes > 0 => Some(minutes),
+                _ => self.words().map(|words| (words + WORDS_PER_MINUTE - 1) / WORDS_PER_MINUTE),
+            }
+        }
+
+        pub fn time_added(&self) -> Result<Duration> {
+            let secs: u64 = self.time_added.parse()
+                .chain_err(|| ErrorKind::Parse(format!("time added '{}' of article {}", self.time_added, self.item_id)))?;
+            Ok(Duration::from_secs(secs))
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use utils::json::*;
+
+        use proptest::prelude::*;
+
+        #[test]
+        fn test_parse_empty_list() {
+            let list: ListResult = serde_json::from_str(r#"{"status":2,"complete":1,"list":[]}"#).unwrap();
+
+            assert!(list.list.is_empty());
+        }
+
+        #[test]
+        fn test_stream() {
+            let json = r#"{"status":1,"complete":1,"since":1504188000,"list":{
+                "2":{"item_id":"2","resolved_title":"second","resolved_url":"url","time_added":"2","time_updated":"2","tags":{}},
+                "1":{"item_id":"1","resolved_title":"first","resolved_url":"url","time_added":"1","time_updated":"1"}}}"#;
+            let mut ids = Vec::new();
+
+            let summary = stream(json.as_bytes(), |a| { ids.push(a.item_id); Ok(()) }).unwrap();
+
+            assert_eq!(summary, ListSummary { status: 1, complete: 1, since: Some(1504188000), total: None, count: 2 });
+            assert_eq!(ids, vec!["2", "1"]);
+        }
+
+        #[test]
+        fn test_stream_empty_list() {
+            let summary = stream(r#"{"status":2,"complete":1,"list":[]}"#.as_bytes(), |_| Ok(())).unwrap();
+
+            assert_eq!(summary.count, 0);
+            assert!(stream(r#"{"status":2,"complete":1,"list":[1]}"#.as_bytes(), |_| Ok(())).is_err());
+        }
+
+        #[test]
+        fn test_stream_stops_on_callback_error() {
+            let json = r#"{"status":1,"complete":1,"list":{
+                "1":{"item_id":"1","resolved_title":"first","resolved_url":"url","time_added":"1","time_updated":"1"},
+                "2":{"item_id":"2","resolved_title":"second","resolved_url":"url","time_added":"2","time_updated":"2"}}}"#;
+            let mut calls = 0;
+
+            let result = stream(json.as_bytes(), |_| { calls += 1; Err(ErrorKind::Parse("article".to_string()).into()) });
+
+            assert_eq!(calls, 1);
+            match result {
+                Err(Error(ErrorKind::Parse(_), _)) => {}
+                other => panic!("expected the error of the callback, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn test_stream_total() {
+            let summary = stream(r#"{"status":1,"complete":1,"total":"123","list":[]}"#.as_bytes(), |_| Ok(())).unwrap();
+
+            assert_eq!(summary.total, Some(123));
+        }
+
+        #[test]
+        fn test_adapt_page_size() {
+            assert_eq!(adapt_page_size(100, Duration::from_millis(100), MAX_PAGE_SIZE), 200);
+            assert_eq!(adapt_page_size(400, Duration::from_millis(100), MAX_PAGE_SIZE), MAX_PAGE_SIZE);
+            assert_eq!(adapt_page_size(100, Duration::from_millis(1500), MAX_PAGE_SIZE), 100);
+            assert_eq!(adapt_page_size(100, Duration::from_millis(5000), MAX_PAGE_SIZE), 50);
+            assert_eq!(adapt_page_size(10, Duration::from_millis(5000), MAX_PAGE_SIZE), MIN_PAGE_SIZE);
+            assert_eq!(adapt_page_size(30, Duration::from_millis(100), 30), 30);
+        }
+
+        #[test]
+        fn test_article_columns() {
+            let article: Article = serde_json::from_str(r#"{"item_id":"1","resolved_title":"Rust 1.20 released",
+                "resolved_url":"https://www.rust-lang.org/news/","status":"0"}"#).unwrap();
+
+            let columns = article.columns();
+
+            assert!(columns.contains(&("word", "rust".to_string())));
+            assert!(columns.contains(&("domain", "rust-lang.org".to_string())));
+            assert!(columns.contains(&("url", "www.rust-lang.org/news".to_string())));
+            assert!(!article.is_deleted());
+        }
+
+        #[test]
+        fn test_article_read_time() {
+            let articles: Vec<Article> = serde_json::from_str(r#"[
+                {"item_id":"1","word_count":"1234","time_to_read":7},
+                {"item_id":"2","word_count":"1234"},
+                {"item_id":"3","word_count":"0","time_to_read":0},
+                {"item_id":"4"}
+            ]"#).unwrap();
+
+            assert_eq!(articles.iter().map(|a| a.read_time()).collect::<Vec<_>>(), vec![Some(7), Some(7), None, None]);
+            assert_eq!(articles[2].words(), None);
+        }
+
+        #[test]
+        fn test_article_complete_schema() {
+            let article: Article = serde_json::from_str(r#"{"item_id":"229279689","resolved_id":229279689,
+                "given_url":"http://www.grantland.com/blog/the-triangle/post/_/id/38347/ryder-cup-preview",
+                "given_title":"The Massive Ryder Cup Preview","resolved_title":"","resolved_url":"",
+                "favorite":"0","status":"0","time_added":1346776466,"time_read":"0","excerpt":"The list of things",
+                "is_article":"1","has_video":"1","has_image":0,"word_count":"3197","time_to_read":"15",
+                "listen_duration_estimate":1238,"images":[],
+                "videos":{"1":{"item_id":"229279689","video_id":"1","src":"http://www.youtube.com/v/Er34PbFkVGk","width":"420",
+                    "height":"315","type":"1","vid":"Er34PbFkVGk"}},
+                "authors":{"3":{"item_id":"229279689","author_id":"3","name":"Bill Barnwell","url":""}},
+                "tags":{"golf":{"item_id":"229279689","tag":"golf"}},
+                "domain_metadata":{"name":"Grantland","logo":"https://logo.clearbit.com/grantland.com?size=800"}}"#).unwrap();
+
+            assert_eq!(article.resolved_id, "229279689");
+            assert_eq!(article.time_added, "1346776466");
+            assert_eq!(article.has_image, "0");
+            assert_eq!(article.title(), "The Massive Ryder Cup Preview");
+            assert_eq!(article.url(), "http://www.grantland.com/blog/the-triangle/post/_/id/38347/ryder-cup-preview");
+            assert_eq!(article.time_to_read, Some(15));
+            assert_eq!(article.listen_duration_estimate, Some(1238));
+            assert!(article.images.is_empty());
+            assert_eq!(article.videos["1"].vid, "Er34PbFkVGk");
+            assert_eq!(article.authors["3"].name, "Bill Barnwell");
+            assert_eq!(article.tags["golf"].tag, "golf");
+            assert_eq!(article.domain_metadata.map(|d| d.name), Some("Grantland".to_string()));
+        }
+
+        #[test]
+        fn test_article_without_details_serializes_like_before() {
+            let json = r#"{"item_id":"1","resolved_title":"Rust","resolved_url":"https://www.rust-lang.org","time_added":"1","time_updated":"1","status":"0","favorite":"0"}"#;
+            let article: Article = serde_json::from_str(json).unwrap();
+
+            assert_eq!(serde_json::to_string(&article).unwrap(), json);
+        }
+
+        #[test]
+        fn test_filter_malformed_time_added() {
+            let list: ListResult = serde_json::from_str(r#"{"status":1,"complete":1,"list":{"1":{"item_id":"1",
+                "resolved_title":"title","resolved_url":"url","time_added":"yesterday","time_updated":"0"}}}"#).unwrap();
+
+            assert!(list.filter(&Some(Duration::from_secs(0)), &None).is_err());
+        }
+
+        proptest! {
+            #[test]
+            fn test_request_serialization(
+                state in prop::option::of(prop::sample::select(vec!["unread", "archive", "all"])),
+                ref tag in prop::option::of(".{0,20}"),
+                sort in prop::option::of(prop::sample::select(vec!["newest", "oldest", "title", "site"])),
+                complete in prop::bool::ANY,
+                ref search in prop::option::of(".{0,20}"),
+                since in prop::option::of(0u64..2_000_000_000),
+                favorite in prop::option::of(0u8..2),
+                count in prop::option::of(1usize..10_000),
+                offset in prop::option::of(0usize..10_000)
+            ) {
+                let request = Request {
+                    state: state.map(State::from),
+                    tag: tag.as_ref().map(|s| s.as_str()),
+                    sort: sort.map(Sort::from),
+                    detailType: complete.into(),
+                    search: search.as_ref().map(|s| s.as_str()),
+                    since: since,
+                    favorite: favorite,
+                    count: count,
+                    offset: offset,
+                };
+                let json = to_value(&request);
+
+                let mut expected_keys = vec!["detailType"];
+                if state.is_some() { expected_keys.push("state"); }
+                if tag.is_some() { expected_keys.push("tag"); }
+                if sort.is_some() { expected_keys.push("sort"); }
+                if search.is_some() { expected_keys.push("search"); }
+                if since.is_some() { expected_keys.push("since"); }
+                if favorite.is_some() { expected_keys.push("favorite"); }
+                if count.is_some() { expected_keys.push("count"); }
+                if offset.is_some() { expected_keys.push("offset"); }
+                prop_assert_eq!(keys(&json), sorted(&expected_keys));
+
+                prop_assert_eq!(json["state"].as_str(), state);
+                prop_assert_eq!(json["tag"].as_str(), tag.as_ref().map(|s| s.as_str()));
+                prop_assert_eq!(json["sort"].as_str(), sort);
+                prop_assert_eq!(json["detailType"].as_str(), Some(if complete { "complete" } else { "simple" }));
+                prop_assert_eq!(json["search"].as_str(), search.as_ref().map(|s| s.as_str()));
+                prop_assert_eq!(json["since"].as_u64(), since);
+                prop_assert_eq!(json["favorite"].as_u64(), favorite.map(|f| f as u64));
+                prop_assert_eq!(json["count"].as_u64(), count.map(|c| c as u64));
+                prop_assert_eq!(json["offset"].as_u64(), offset.map(|o| o as u64));
+            }
+        }
+    }
+}
+
+pub mod send {
+    use errors::*;
+    use net::http::{check_status, tls_client};
+    use cache::responses::ResponseCache;
+    use config::Config;
+    use clients::pocket::{request_auth, NAME};
+    use net::auth::Auth;
+    use utils::console::*;
+
+    use serde::Serialize;
+    use serde_json::{self, Map, Value};
+    use serde_urlencoded;
+    use std::cmp;
+    use std::io::Read;
+    use std::str;
+    use std::thread;
+
+    #[allow(non_camel_case_types)]
+    #[derive(Serialize, Debug)]
+    // TODO: Use enum instead of strings in ActionRequest
+    #[allow(dead_code)]
+    enum Action {
+        archive,
+        read,
+        favorite,
+        unfavorite,
+        delete,
+    }
+
+    #[allow(non_snake_case)]
+    #[derive(Serialize, Debug)]
+    pub struct ActionRequest<'a> {
+        pub action: &'a str,
+        pub item_id: &'a str,
+    }
+
+    impl<'a> ActionRequest<'a> {
+        pub fn new(action: &'a str, item_id: &'a str) -> Self {
+            ActionRequest { action: action, item_id: item_id }
+        }
+    }
+
+    /// Adds a URL as part of a batch; its action result is the added item or `false`.
+    #[derive(Serialize, Debug)]
+    pub struct AddAction<'a> {
+        action: &'static str,
+        pub url: &'a str,
+        /// Used only if Pocket cannot determine the title itself
+        #[serde(skip_serializing_if = "Option::is_none")] pub title: Option<&'a str>,
+        /// Comma separated
+        #[serde(skip_serializing_if = "Option::is_none")] pub tags: Option<&'a str>,
+        /// Unix timestamp of the time the URL was saved
+        #[serde(skip_serializing_if = "Option::is_none")] pub time: Option<&'a str>,
+    }
+
+    impl<'a> AddAction<'a> {
+        pub fn new(url: &'a str, title: Option<&'a str>, tags: Option<&'a str>, time: Option<&'a str>) -> Self {
+            AddAction { action: "add", url: url, title: title, tags: tags, time: time }
+        }
+    }
+
+    #[allow(unused_variables)] // for status codes
+    pub fn send<T: Serialize>(config: &Config, actions: &[T]) -> Result<String> {
+        let json = do_send(config, actions).chain_err(|| "HTTP call for action failed")?;
+
+        Ok(json)
+    }
+
+    /// Sends the actions in batches of `batch_size`, at least 1, and calls `on_batch` with the number of actions of every
+    /// batch sent. A batch that failed for a transient reason, cf. `Error::is_retryable`, is sent again according to the
+    /// retry policy; Pocket's actions, e.g., archive or tags_add, have the same effect when applied twice. If a batch still fails, the remaining batches are sent nevertheless and its actions
+    /// count as failed, unless all batches failed. The response combines the action results of all batches; if a batch
+    /// failed, 'action_errors' holds an error per action, `null` for the actions of the successful batches, and tells
+    /// whether the batch failed because Pocket could not be reached, cf. `Error::is_offline`.
+    pub fn send_batched<T: Serialize, F: FnMut(usize)>(config: &Config, actions: &[T], batch_size: usize, on_batch: F)
+                                                        -> Result<String> {
+        let mut on_batch = on_batch;
+        let policy = config.general.retry_policy();
+        let batch_size = cmp::max(batch_size, 1);
+        let batches = (actions.len() + batch_size - 1) / batch_size;
+        let mut action_results = Vec::new();
+        let mut action_errors = Vec::new();
+        let mut status = 1;
+        let mut last_error = None;
+        for (i, batch) in actions.chunks(batch_size).enumerate() {
+            verboseln(format!("Sending batch {} of {} with {} action(s) ...", i + 1, batches, batch.len()));
+            let mut attempt = 1;
+            let results = loop {
+                match send(config, batch).and_then(|json| parse_action_results(&json, batch.len())) {
+                    Ok(results) => break Ok(results),
+                    Err(e) => {
+                        if attempt >= policy.max_attempts || !e.is_retryable() {
+                            break Err(e);
+                        }
+                        let delay = policy.delay(attempt);
+                        info(format!("Batch {} of {} failed; retrying in {} ms ({} of {} attempts) ...", i + 1, batches,
+                                     delay.as_secs() * 1000 + u64::from(delay.subsec_nanos() / 1_000_000), attempt + 1, policy.max_attempts));
+                        thread::sleep(delay);
+                        attempt += 1;
+                    }
+                }
+            };
+            match results {
+                Ok(results) => {
+                    action_results.extend(results);
+                    action_errors.extend(batch.iter().map(|_| Value::Null));
+                }
+                Err(e) => {
+                    // The root cause is the most specific, e.g., the HTTP status
+                    let message = e.iter().last().map(|c| c.to_string()).unwrap_or_default();
+                    warning(format!("Batch {} of {} failed: {}", i + 1, batches, message));
+                    let mut error = Map::new();
+                    error.insert("message".to_string(), Value::from(message));
+                    error.insert("category".to_string(), Value::from(e.category().name()));
+                    error.insert("offline".to_string(), Value::Bool(e.is_offline()));
+                    action_results.extend(batch.iter().map(|_| Value::Bool(false)));
+                    action_errors.extend(batch.iter().map(|_| Value::Object(error.clone())));
+                    status = 0;
+                    last_error = Some(e);
+                }
+            }
+            on_batch(batch.len());
+        }
+        if let Some(e) = last_error {
+            if action_errors.iter().all(|error| !error.is_null()) {
+                return Err(e);
+            }
+        }
+
+        let mut response = Map::new();
+        response.insert("action_results".to_string(), Value::Array(action_results));
+        if status == 0 {
+            response.insert("action_errors".to_string(), Value::Array(action_errors));
+        }
+        response.insert("status".to_string(), Value::from(status));
+        serde_json::to_string(&response).chain_err(|| "JSON serialization failed")
+    }
+
+    fn parse_action_results(json: &str, expected: usize) -> Result<Vec<Value>> {
+        let json: Value = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
+        let results = match json.get("action_results").and_then(|r| r.as_array()) {
+            Some(results) => results.clone(),
+            None => bail!("Pocket did not send action results"),
+        };
+        if results.len() != expected {
+            bail!("Pocket sent {} action results for {} actions", results.len(), expected);
+        }
+
+        Ok(results)
+    }
+
+    fn do_send<T: Serialize>(config: &Config, actions: &[T]) -> Result<String> {
+        let actions_json = serde_json::to_string(&actions).chain_err(|| "JSON serialization failed")?;
+        let auth = request_auth(config).chain_err(|| "Failed to get access token")?;
+
+        let mut parameters = vec![("actions", actions_json.as_str())];
+        parameters.extend(auth.params());
+        let parameters_enc = serde_urlencoded::to_string(&parameters).chain_err(|| "URL serialization failed")?;
+        let url = format!("https://getpocket.com/v3/send?{}", parameters_enc);
+
+        let client = tls_client().chain_err(|| "Failed to create TLS client")?;
+        let mut response = client
+            .get(&url)
+            .send()
+            .chain_err(|| "Failed to finish HTTP request")?;
+        check_status(&mut response)?;
+
+        let mut buffer = Vec::new();
+        response.read_to_end(&mut buffer).chain_err(|| "Failed to read HTTP response")?;
+        let json = str::from_utf8(&buffer).chain_err(|| "Data copying failed.")?;
+
+        // Listings must not show the articles as they were before the actions
+        ResponseCache::new(config, NAME).clear();
+
+        Ok(json.to_string())
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use net::http::with_client;
+        use net::mock::MockClient;
+        use testing;
+        use utils::json::*;
+
+        use proptest::prelude::*;
+        use std::rc::Rc;
+
+        proptest! {
+            #[test]
+            fn test_action_request_serialization(
+                ref actions in prop::collection::vec(
+                    (prop::sample::select(vec!["archive", "readd", "favorite", "unfavorite", "delete"]), "[0-9]{1,12}"),
+                    1..10)
+            ) {
+                let requests: Vec<ActionRequest> = actions.iter().map(|&(action, ref id)| ActionRequest::new(action, id)).collect();
+                let json = to_value(&requests);
+
+                let json = json.as_array().unwrap();
+                prop_assert_eq!(json.len(), actions.len());
+                for (request, &(action, ref id)) in json.iter().zip(actions.iter()) {
+                    prop_assert_eq!(keys(request), sorted(&["action", "item_id"]));
+                    prop_assert_eq!(request["action"].as_str(), Some(action));
+                    prop_assert_eq!(request["item_id"].as_str(), Some(id.as_str()));
+                }
+            }
+        }
+
+        #[test]
+        fn test_send_batched_retries_failed_batch() {
+            ::utils::console::init(::config::Verbosity::QUIET);
+            let config = testing::config("pocket-send", r#"
+                [pocket]
+                consumer_key = 'key'
+                access_token = 'token'
+            "#);
+            let mock = Rc::new(MockClient::new()
+                .respond(200, &[], r#"{"action_results":[true,true],"status":1}"#)
+                .respond(503, &[], "")
+                .respond(200, &[], r#"{"action_results":[false],"status":1}"#));
+            let actions = vec![ActionRequest::new("archive", "1"), ActionRequest::new("archive", "2"), ActionRequest::new("archive", "3")];
+            let mut sent = Vec::new();
+
+            let json = with_client(mock.clone(), || send_batched(&config, &actions, 2, |n| sent.push(n))).unwrap();
+
+            assert_eq!(json, r#"{"action_results":[true,true,false],"status":1}"#);
+            assert_eq!(sent, vec![2, 1]);
+            assert_eq!(mock.requests().len(), 3);
+
+            let mock = Rc::new(MockClient::new().respond(401, &[], ""));
+            let error = with_client(mock.clone(), || send_batched(&config, &actions[..1], 0, |_| {})).unwrap_err();
+
+            assert_eq!(error.category(), Category::Auth);
+            // Only transient failures are retried
+            assert_eq!(mock.requests().len(), 1);
+            assert!(send_batched(&config, &Vec::<ActionRequest>::new(), 0, |_| {}).is_ok());
+        }
+
+        #[test]
+        fn test_add_action_serialization() {
+            let action = AddAction::new("https://example.com", None, Some("a,b"), Some("1500000000"));
+            let json = to_value(&action);
+
+            assert_eq!(keys(&json), sorted(&["action", "tags", "time", "url"]));
+            assert_eq!(json["action"].as_str(), Some("add"));
+        }
+    }
+}
+
+pub mod add {
+    use errors::*;
+    use net::auth::Auth;
+    use net::http::{check_status, tls_client};
+    use utils::console::*;
+
+    use hyper::header::ContentType;
+    use serde_json;
+    use std::io::Read;
+
+    #[derive(Serialize, Debug)]
+    pub struct Request<'a> {
+        pub url: &'a str,
+        /// Used only if Pocket cannot determine the title itself
+        #[serde(skip_serializing_if = "Option::is_none")] pub title: Option<&'a str>,
+        /// Comma separated
+        #[serde(skip_serializing_if = "Option::is_none")] pub tags: Option<&'a str>,
+        /// Unix timestamp of saving; defaults to now
+        #[serde(skip_serializing_if = "Option::is_none")] pub time: Option<u64>,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct AddResult {
+        item: AddedItem,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    pub struct AddedItem {
+        pub item_id: String,
+        pub title: Option<String>,
+    }
+
+    /// Adds an article authorized by `auth`; cf. `clients::pocket::request_auth`.
+    pub fn add<A: Auth>(auth: &A, request: &Request) -> Result<AddedItem> {
+        do_add(auth, request).chain_err(|| format!("HTTP call to add '{}' failed", request.url))
+    }
+
+    fn do_add<A: Auth>(auth: &A, request: &Request) -> Result<AddedItem> {
+        let mut body = serde_json::to_value(request).chain_err(|| "JSON serialization failed")?;
+        auth.inject(&mut body);
+        let request_json = serde_json::to_string(&body).chain_err(|| "JSON serialization failed")?;
+        verboseln(format!("add = {}", request.url));
+
+        let client = tls_client().chain_err(|| "Failed to create TLS client")?;
+        let mut response = client
+            .post("https://getpocket.com/v3/add")
+            .header(ContentType(mime!(Application / Json)))
+            .body(&request_json)
+            .send()
+            .chain_err(|| "Failed to finish HTTP request")?;
+        check_status(&mut response)?;
+
+        let mut buffer = String::new();
+        response.read_to_string(&mut buffer).chain_err(|| "Failed to read HTTP response")?;
+        parse_response(&buffer)
+    }
+
+    fn parse_response(json: &str) -> Result<AddedItem> {
+        let result: AddResult = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
+
+        Ok(result.item)
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use net::http::with_client;
+        use net::mock::MockClient;
+        use utils::json::*;
+
+        use net::auth::ParamAuth;
+        use std::rc::Rc;
+
+        #[test]
+        fn test_request_serialization() {
+            let request = Request { url: "https://example.com", title: None, tags: Some("a,b"), time: None };
+            let json = to_value(&request);
+
+            assert_eq!(keys(&json), sorted(&["tags", "url"]));
+            assert_eq!(json["tags"].as_str(), Some("a,b"));
+        }
+
+        #[test]
+        fn test_parse_response() {
+            let json = r#"{"item":{"item_id":"229279689","normal_url":"http://example.com","title":"Example","extended":1},"status":1}"#;
+
+            let item = parse_response(json).unwrap();
+
+            assert_eq!(item, AddedItem { item_id: "229279689".to_string(), title: Some("Example".to_string()) });
+        }
+
+        #[test]
+        fn test_add_with_mock_client() {
+            ::utils::console::init(::config::Verbosity::QUIET);
+            let mock = Rc::new(MockClient::new().respond(200, &[], r#"{"item":{"item_id":"42","title":"Example"},"status":1}"#));
+            let auth = ParamAuth::new(&[("consumer_key", "key"), ("access_token", "token")]);
+            let request = Request { url: "https://example.com", title: None, tags: None, time: None };
+
+            let item = with_client(mock.clone(), || add(&auth, &request)).unwrap();
+
+            assert_eq!(item.item_id, "42");
+            let requests = mock.requests();
+            assert!(requests[0].starts_with("POST /v3/add HTTP/1.1\r\n"));
+            assert!(requests[0].contains(r#""url":"https://example.com""#));
+            assert!(requests[0].contains(r#""consumer_key":"key""#));
+            assert!(requests[0].contains(r#""access_token":"token""#));
+        }
+
+        #[test]
+        fn test_parse_response_without_title() {
+            let json = r#"{"item":{"item_id":"229279689","normal_url":"http://example.com"},"status":1}"#;
+
+            assert_eq!(parse_response(json).unwrap().title, None);
+        }
+    }
+}
+
+/// Typed access to Pocket for programs that embed rat-core: requests are built from owned options and responses are
+/// parsed into `Article`s and action results, so callers need neither Pocket's request structs nor its JSON.
+mod api {
+    use super::add::{self, AddedItem};
+    use super::list::{self, Article, DetailType, Sort, State};
+    use super::send::{self, ActionRequest};
+    use clients::pocket::request_auth;
+    use config::Config;
+    use errors::*;
+
+    use serde_json::{self, Value};
+    use std::time::Duration;
+
+    /// Selects the articles to list; the defaults list all unread articles without details.
+    #[derive(Debug, Default)]
+    pub struct ListOptions {
+        pub state: Option<State>,
+        pub tag: Option<String>,
+        pub sort: Option<Sort>,
+        /// Adds tags, word counts, and more to the articles
+        pub details: bool,
+        pub search: Option<String>,
+        /// Only favorites if true, only other articles if false
+        pub favorite: Option<bool>,
+        pub count: Option<usize>,
+        /// Only honored with `count`
+        pub offset: Option<usize>,
+        /// Only articles added within this duration until now
+        pub since: Option<Duration>,
+        /// Only articles added before this duration ago
+        pub until: Option<Duration>,
+    }
+
+    /// Pocket client of the account selected in `config`; the access token is taken from the configuration or the
+    /// credential store like the `rat pocket` commands do.
+    pub struct PocketClient<'a> {
+        config: &'a Config,
+    }
+
+    impl<'a> PocketClient<'a> {
+        pub fn new(config: &'a Config) -> Self {
+            PocketClient { config: config }
+        }
+
+        /// Lists the articles selected by `options` in the requested order; deleted articles are left out.
+        pub fn list(&self, options: &ListOptions) -> Result<Vec<Article>> {
+            let request = list::Request {
+                state: options.state,
+                tag: options.tag.as_ref().map(|t| t.as_str()),
+                sort: options.sort,
+                detailType: DetailType::from(options.details),
+                search: options.search.as_ref().map(|s| s.as_str()),
+                since: None,
+                favorite: options.favorite.map(|f| if f { 1 } else { 0 }),
+                count: options.count,
+                offset: options.offset,
+            };
+            let mut articles = Vec::new();
+            list::list_streamed(self.config, &request, options.since, options.until, |article| {
+                if !article.is_deleted() {
+                    articles.push(article);
+                }
+                Ok(())
+            })?;
+
+            Ok(articles)
+        }
+
+        /// Adds `url`; `title` is only used if Pocket cannot determine the title itself.
+        pub fn add(&self, url: &str, title: Option<&str>, tags: &[&str]) -> Result<AddedItem> {
+            let auth = request_auth(self.config).chain_err(|| "Failed to get access token")?;
+            let tags = tags.join(",");
+            let request = add::Request {
+                url: url,
+                title: title,
+                tags: if tags.is_empty() { None } else { Some(&tags) },
+                time: None,
+            };
+
+            add::add(&auth, &request)
+        }
+
+        pub fn archive(&self, item_ids: &[&str]) -> Result<Vec<bool>> {
+            self.act("archive", item_ids)
+        }
+
+        pub fn readd(&self, item_ids: &[&str]) -> Result<Vec<bool>> {
+            self.act("readd", item_ids)
+        }
+
+        pub fn favorite(&self, item_ids: &[&str]) -> Result<Vec<bool>> {
+            self.act("favorite", item_ids)
+        }
+
+        pub fn unfavorite(&self, item_ids: &[&str]) -> Result<Vec<bool>> {
+            self.act("unfavorite", item_ids)
+        }
+
+        pub fn delete(&self, item_ids: &[&str]) -> Result<Vec<bool>> {
+            self.act("delete", item_ids)
+        }
+
+        /// Sends `action` for all items in batches and returns whether it succeeded per item.
+        fn act(&self, action: &str, item_ids: &[&str]) -> Result<Vec<bool>> {
+            let actions: Vec<ActionRequest> = item_ids.iter().map(|id| ActionRequest::new(action, id)).collect();
+            let json = send::send_batched(self.config, &actions, self.config.pocket.batch_size(), |_| {})
+                .chain_err(|| format!("action '{}' failed", action))?;
+
+            parse_action_results(&json)
+        }
+    }
+
+    fn parse_action_results(json: &str) -> Result<Vec<bool>> {
+        let json: Value = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
+        let results = json.get("action_results").and_then(|r| r.as_array()).ok_or("Pocket did not send action results")?;
+
+        // Add actions result in the added item
+        Ok(results.iter().map(|r| r.as_bool().unwrap_or_else(|| r.is_object())).collect())
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use net::http::with_client;
+        use net::mock::MockClient;
+        use testing;
+
+        use std::rc::Rc;
+
+        fn config() -> Config {
+            testing::config("pocket-api", r#"
+                [pocket]
+                consumer_key = 'key'
+                access_token = 'token'
+            "#)
+        }
+
+        #[test]
+        fn test_list() {
+            ::utils::console::init(::config::Verbosity::QUIET);
+            let config = config();
+            let mock = Rc::new(MockClient::new().respond(200, &[], r#"{"status":1,"complete":1,"list":{
+                "1":{"item_id":"1","resolved_title":"Rust","resolved_url":"https://www.rust-lang.org","time_added":"1504188000"},
+                "2":{"item_id":"2","status":"2"}}}"#));
+            let options = ListOptions { state: Some(State::archive), tag: Some("rust".to_string()), ..Default::default() };
+
+            let articles = with_client(mock.clone(), || PocketClient::new(&config).list(&options)).unwrap();
+
+            assert_eq!(articles.iter().map(|a| a.item_id.as_str()).collect::<Vec<_>>(), vec!["1"]);
+            let request = &mock.requests()[0];
+            assert!(request.contains(r#""state":"archive""#));
+            assert!(request.contains(r#""tag":"rust""#));
+        }
+
+        #[test]
+        fn test_parse_action_results() {
+            let json = r#"{"action_results":[true,false,{"item_id":"1"}],"status":1}"#;
+
+            assert_eq!(parse_action_results(json).unwrap(), vec![true, false, true]);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use config::Config;
+use credentials;
+use net::auth::ParamAuth;
+
+use serde::{de, Deserialize, Deserializer};
+use std::collections::HashMap;
+
+pub const NAME: &'static str = "pocket";
+
+/// Actions per send request unless configured otherwise by `pocket.batch_size`
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+
+#[cfg(feature = "pocket")]
+pub mod client;
+
+fn positive_batch_size<D: Deserializer>(deserializer: D) -> ::std::result::Result<Option<usize>, D::Error> {
+    match Option::<usize>::deserialize(deserializer)? {
+        Some(0) => Err(de::Error::custom("batch_size must be at least 1")),
+        batch_size => Ok(batch_size),
+    }
+}
+
+/// Consumer key built into rat with feature 'presets'. It is taken from environment variable
+/// `RAT_POCKET_CONSUMER_KEY` at build time, so new users can try rat without registering a Pocket app.
+#[cfg(feature = "presets")]
+static PRESET_CONSUMER_KEY: Option<&'static str> = option_env!("RAT_POCKET_CONSUMER_KEY");
+#[cfg(not(feature = "presets"))]
+static PRESET_CONSUMER_KEY: Option<&'static str> = None;
+
+fn default_consumer_key() -> String {
+    PRESET_CONSUMER_KEY.unwrap_or("").to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PocketConfig {
+    #[serde(default = "default_consumer_key")]
+    pub consumer_key: String,
+    pub access_token: Option<String>,
+    #[serde(default, deserialize_with = "positive_batch_size")]
+    pub batch_size: Option<usize>,
+    /// Queues actions that fail for lack of connectivity as if '--queue' was given; cf. `rat flush`
+    #[serde(default)]
+    pub queue_offline: bool,
+    #[serde(default)]
+    pub accounts: HashMap<String, PocketAccountConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PocketAccountConfig {
+    pub consumer_key: Option<String>,
+    pub access_token: Option<String>,
+}
+
+impl Default for PocketConfig {
+    fn default() -> Self {
+        PocketConfig {
+            consumer_key: default_consumer_key(),
+            access_token: None,
+            batch_size: None,
+            queue_offline: false,
+            accounts: HashMap::new(),
+        }
+    }
+}
+
+impl PocketConfig {
+    pub fn select_account(&mut self, account: &str) -> bool {
+        if let Some(account) = self.accounts.remove(account) {
+            if let Some(consumer_key) = account.consumer_key {
+                self.consumer_key = consumer_key;
+            }
+            // The access token of the default account must not leak into the selected account
+            self.access_token = account.access_token;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Number of actions sent to Pocket per request, e.g., by `archive` or `import`
+    pub fn batch_size(&self) -> usize {
+        self.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1)
+    }
+
+    /// Returns the secrets of this section and its accounts by their setting names, e.g., 'accounts.work.access_token'.
+    pub fn secrets(&self) -> Vec<(String, &String)> {
+        let mut secrets = vec![("consumer_key".to_string(), &self.consumer_key)];
+        secrets.extend(self.access_token.iter().map(|s| ("access_token".to_string(), s)));
+        for (name, account) in &self.accounts {
+            secrets.extend(account.consumer_key.iter().map(|s| (format!("accounts.{}.consumer_key", name), s)));
+            secrets.extend(account.access_token.iter().map(|s| (format!("accounts.{}.access_token", name), s)));
+        }
+        secrets
+    }
+}
+
+/// Rule of `rat pocket apply-rules` in section '[rules.<name>]'. An article matches if it satisfies all conditions given;
+/// a rule without conditions matches all articles.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleConfig {
+    /// Domain of the article or one of its subdomains, e.g., 'github.com'
+    pub domain: Option<String>,
+    /// Regular expression the title has to match, e.g., '(?i)rust'
+    pub title: Option<String>,
+    /// Regular expression the URL has to match
+    pub url: Option<String>,
+    /// Tag the article has to have
+    pub tag: Option<String>,
+    /// 'unread' or 'archive'
+    pub state: Option<String>,
+    /// Word counts are only known for articles Pocket could parse; other articles never match word count conditions
+    pub min_words: Option<u64>,
+    pub max_words: Option<u64>,
+    #[serde(default)]
+    pub add_tags: Vec<String>,
+    #[serde(default)]
+    pub remove_tags: Vec<String>,
+    #[serde(default)]
+    pub archive: bool,
+    #[serde(default)]
+    pub favorite: bool,
+}
+
+impl RuleConfig {
+    pub fn has_actions(&self) -> bool {
+        !self.add_tags.is_empty() || !self.remove_tags.is_empty() || self.archive || self.favorite
+    }
+}
+
+pub fn access_token(config: &Config) -> credentials::Result<String> {
+    credentials::access_token(config, NAME, &config.pocket.access_token)
+}
+
+/// Pocket expects the consumer key and the access token as parameters of every request.
+pub fn request_auth(config: &Config) -> credentials::Result<ParamAuth> {
+    let access_token = access_token(config)?;
+
+    Ok(ParamAuth::new(&[("consumer_key", &config.pocket.consumer_key), ("access_token", &access_token)]))
+}
@@ -0,0 +1,64 @@
+use config::Config;
+use credentials;
+use net::auth::BearerAuth;
+
+use std::collections::HashMap;
+
+pub const NAME: &'static str = "slack";
+
+#[cfg(feature = "slack")]
+pub mod client;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    pub access_token: Option<String>,
+    #[serde(default)]
+    pub accounts: HashMap<String, SlackAccountConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackAccountConfig {
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub access_token: Option<String>,
+}
+
+impl SlackConfig {
+    pub fn select_account(&mut self, account: &str) -> bool {
+        if let Some(account) = self.accounts.remove(account) {
+            if let Some(client_id) = account.client_id {
+                self.client_id = client_id;
+            }
+            if let Some(client_secret) = account.client_secret {
+                self.client_secret = client_secret;
+            }
+            // The access token of the default account must not leak into the selected account
+            self.access_token = account.access_token;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the secrets of this section and its accounts by their setting names, e.g., 'accounts.work.access_token'.
+    pub fn secrets(&self) -> Vec<(String, &String)> {
+        let mut secrets = vec![("client_secret".to_string(), &self.client_secret)];
+        secrets.extend(self.access_token.iter().map(|s| ("access_token".to_string(), s)));
+        for (name, account) in &self.accounts {
+            secrets.extend(account.client_secret.iter().map(|s| (format!("accounts.{}.client_secret", name), s)));
+            secrets.extend(account.access_token.iter().map(|s| (format!("accounts.{}.access_token", name), s)));
+        }
+        secrets
+    }
+}
+
+pub fn access_token(config: &Config) -> credentials::Result<String> {
+    credentials::access_token(config, NAME, &config.slack.access_token)
+}
+
+/// Slack expects the access token as bearer token.
+pub fn request_auth(config: &Config) -> credentials::Result<BearerAuth> {
+    access_token(config).map(BearerAuth::new)
+}
@@ -1,4 +1,5 @@
 use super::StockPrice;
+use errors::*;
 use net::http::tls_client;
 use utils::console::*;
 
@@ -9,19 +10,7 @@ use serde_urlencoded;
 use std::io::Read;
 
 static BASE_URL: &'static str = "https://www.comdirect.de/inf/search/all.html";
-
-error_chain! {
-    errors {
-        ComdirectScrapingFailed(info: String) {
-            description("Did not find information in HTML document")
-            display("Did not find {} in HTML document", info)
-        }
-        ComdirectSearchResultNotUnique {
-            description("The search did not returned an unique result")
-            display("The search did not returned an unique result")
-        }
-    }
-}
+static NOT_UNIQUE: &'static str = "The search did not returned an unique result";
 
 pub fn scrape_stock_price(search: String) -> Result<StockPrice> {
     let parameters = &[("SEARCH_VALUE", search)];
@@ -29,7 +18,7 @@ pub fn scrape_stock_price(search: String) -> Result<StockPrice> {
         .chain_err(|| "Could not encode URL parameters")?;
     let url = format!("{}?{}", BASE_URL, parameters_enc);
 
-    let body = get_stock_page(&url).unwrap();
+    let body = get_stock_page(&url)?;
     let stock_price = parse_stock_price(&body)?;
 
     Ok(stock_price)
@@ -49,22 +38,22 @@ fn get_stock_page(url: &str) -> Result<Vec<u8>> {
     Ok(body)
 }
 
-fn parse_stock_price(body: &[u8]) -> Result<StockPrice> {
+pub fn parse_stock_price(body: &[u8]) -> Result<StockPrice> {
     let document = Document::from_read(body).chain_err(|| "Could not parse HTML in response body")?;
 
     let no_exact_match = document.find(Class("Informer")).nth(0).is_some();
     if no_exact_match {
-        bail!(ErrorKind::ComdirectSearchResultNotUnique);
+        bail!(NOT_UNIQUE);
     }
 
     let name = document.find(Name("h1")).nth(0)
-        .ok_or_else(|| ErrorKind::ComdirectScrapingFailed("name".to_string()))?.text();
+        .ok_or_else(|| ErrorKind::Parse("name in HTML document".to_string()))?.text();
 
     let price_currency = document.find(Class("price")).nth(0)
-        .ok_or_else(|| ErrorKind::ComdirectScrapingFailed("stock price".to_string()))?.text();
+        .ok_or_else(|| ErrorKind::Parse("stock price in HTML document".to_string()))?.text();
 
     let date = document.find(Attr("id", "keyelement_kurs_update").descendant(Class("date"))).last()
-        .ok_or_else(|| ErrorKind::ComdirectScrapingFailed("stock price date".to_string()))?.text();
+        .ok_or_else(|| ErrorKind::Parse("stock price date in HTML document".to_string()))?.text();
 
     let trs = document.find(Class("clearfix").child(Name("table").and(Class("alternate_2n"))).child(Name("tbody")).child(Name("tr")));
     let wkn: String = trs
@@ -72,17 +61,21 @@ fn parse_stock_price(body: &[u8]) -> Result<StockPrice> {
         .filter(|tr| tr.find(Name("tr").child(Name("td").and(Class("left"))).child(Text)).nth(0).map_or(false, |x| x.text() == "WKN"))
         .flat_map(|tr| tr.find(Name("tr").child(Name("td").and(Class("right"))).child(Text)).nth(0).map(|x| x.text()))
         .nth(0)
-        .ok_or_else(|| ErrorKind::ComdirectScrapingFailed("stock WKN".to_string()))?;
+        .ok_or_else(|| ErrorKind::Parse("stock WKN in HTML document".to_string()))?;
 
     let mut pc_split = price_currency.split_whitespace();
-    let price = pc_split.nth(0)
-        .ok_or_else(|| ErrorKind::ComdirectScrapingFailed("price".to_string()))?
+    let price: f32 = pc_split.nth(0)
+        .ok_or_else(|| ErrorKind::Parse("price in HTML document".to_string()))?
         .trim()
         .replace(".", "") // thousand separation
         .replace(",", ".") // decimal separation
         .parse()
-        .chain_err(|| ErrorKind::ComdirectScrapingFailed("price".to_string()))?;
-    let currency = pc_split.nth(0).ok_or_else(|| ErrorKind::ComdirectScrapingFailed("currency".to_string()))?;
+        .chain_err(|| ErrorKind::Parse("price in HTML document".to_string()))?;
+    // 'NaN' and 'inf' parse as well
+    if !price.is_finite() {
+        bail!(ErrorKind::Parse("price in HTML document".to_string()));
+    }
+    let currency = pc_split.nth(0).ok_or_else(|| ErrorKind::Parse("currency in HTML document".to_string()))?;
 
     let stock_price = StockPrice {
         name: name.trim().to_string(),
@@ -134,6 +127,13 @@ mod test {
         assert_eq! (db.currency, "EUR");
     }
 
+    #[test]
+    fn test_parse_malformed() {
+        assert!(parse_stock_price(b"").is_err());
+        assert!(parse_stock_price(b"\xff\xfe<h1>").is_err());
+        assert!(parse_stock_price(b"<h1>Name</h1><span class=\"price\">NaN EUR</span>").is_err());
+    }
+
     #[test]
     fn test_parse_file_no_exact_match() {
         let body = get_stock_page("test/data/stocks/no_exact_match.html").unwrap();
@@ -143,6 +143,7 @@ mod test {
     }
 
     #[test]
+    #[cfg(feature = "live-tests")]
     fn test_parse_online_ok() {
         ::utils::console::init(::config::Verbosity::QUIET);
         let db = scrape_stock_price("Deutsche Bank".to_string()).unwrap();
@@ -154,6 +155,7 @@ mod test {
     }
 
     #[test]
+    #[cfg(feature = "live-tests")]
     fn test_parse_online_with_thousand_separator() {
         ::utils::console::init(::config::Verbosity::QUIET);
         let db = scrape_stock_price("A0X8ZS".to_string()).unwrap();
@@ -165,13 +167,14 @@ mod test {
     }
 
     #[test]
+    #[cfg(feature = "live-tests")]
     fn test_parse_online_no_exact_match() {
         ::utils::console::init(::config::Verbosity::QUIET);
         let result = scrape_stock_price("Deutsche".to_string());
 
         // TODO: This needs to be nicer.
         let result_is_not_unique = match result.unwrap_err() {
-            Error(ErrorKind::ComdirectSearchResultNotUnique, _) => true,
+            Error(ErrorKind::Msg(ref msg), _) if msg == NOT_UNIQUE => true,
             _ => false
         };
         assert! (result_is_not_unique);
@@ -0,0 +1,21 @@
+use std::fmt;
+
+pub const NAME: &'static str = "stocks";
+
+#[cfg(feature = "stocks")]
+pub mod comdirect;
+
+#[derive(Serialize, Debug)]
+pub struct StockPrice {
+    pub name: String,
+    pub wkn: String,
+    pub date: String,
+    pub price: f32,
+    pub currency: String,
+}
+
+impl fmt::Display for StockPrice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({}) is at {} {} on {}.", self.name, self.wkn, self.price, self.currency, self.date)
+    }
+}
@@ -0,0 +1,210 @@
+use super::{oauth_client, NAME};
+use config::Config;
+use credentials;
+use errors::*;
+use net::auth::{Auth, BearerAuth};
+use net::http::{check_status, tls_client};
+use net::oauth::oauth2::{OAuth2, TokenSession};
+use utils::console::*;
+
+use hyper::client::{Client, RequestBuilder, Response};
+use hyper::header::ContentType;
+use hyper::method::Method;
+use serde_json;
+use serde_urlencoded;
+use std::io::Read;
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct Entry {
+    pub id: u64,
+    pub url: String,
+    #[serde(default)] pub title: String,
+    /// 1 for archived entries, 0 otherwise
+    #[serde(default)] pub is_archived: u8,
+    /// 1 for starred entries, 0 otherwise
+    #[serde(default)] pub is_starred: u8,
+    /// Time of saving, e.g., '2017-07-14T02:40:00+0000'
+    #[serde(default)] pub created_at: String,
+    /// Estimated reading time in minutes
+    #[serde(default)] pub reading_time: u64,
+    #[serde(default)] pub tags: Vec<Tag>,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct Tag {
+    pub label: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Entries {
+    _embedded: Embedded,
+}
+
+#[derive(Deserialize, Debug)]
+struct Embedded {
+    items: Vec<Entry>,
+}
+
+#[derive(Debug, Default)]
+pub struct Filter<'a> {
+    /// Lists archived entries if true, unread entries if false, and both if unset
+    pub archived: Option<bool>,
+    pub starred: Option<bool>,
+    /// Lists only entries with all of these tags
+    pub tags: Vec<&'a str>,
+}
+
+/// Exchanges user name and password for an access and a refresh token by the OAuth 2.0 password grant and stores them
+/// in the credential store. The password itself is not stored.
+pub fn auth(config: &Config, username: &str, password: &str) -> Result<()> {
+    let mut credential = OAuth2::new(&oauth_client(config)).password(username, password)
+        .chain_err(|| "Failed to obtain access token")?
+        .into_credential();
+    credential.username = Some(username.to_string());
+
+    credentials::store(config, NAME, &credential).chain_err(|| "Failed to store access token")?;
+    msgln(format!("Received access and refresh token for user '{}' and stored them in the credential store.", username));
+
+    Ok(())
+}
+
+/// Lists at most `count` entries, most recently saved first.
+pub fn list(session: &TokenSession, config: &Config, filter: &Filter, count: usize) -> Result<Vec<Entry>> {
+    info("Getting list of your entries ...");
+    let mut params = vec![
+        ("sort", "created".to_string()),
+        ("order", "desc".to_string()),
+        ("perPage", count.to_string()),
+        // Omits the content of the entries since Wallabag 2.4
+        ("detail", "metadata".to_string()),
+    ];
+    params.extend(filter.archived.map(|archived| ("archive", bool_param(archived))));
+    params.extend(filter.starred.map(|starred| ("starred", bool_param(starred))));
+    if !filter.tags.is_empty() {
+        params.push(("tags", filter.tags.join(",")));
+    }
+    let parameters_enc = serde_urlencoded::to_string(&params).chain_err(|| "URL serialization failed")?;
+    let url = format!("{}?{}", config.wallabag.url("/api/entries.json"), parameters_enc);
+
+    let client = tls_client().chain_err(|| "Failed to create TLS client")?;
+    let mut response = session.send_idempotent(|token| request(&client, Method::Get, &url, token).send())
+        .chain_err(|| "HTTP call to list entries failed")?;
+    let json = read_json(&mut response)?;
+    let entries: Entries = serde_json::from_str(&json).chain_err(|| "JSON parsing failed")?;
+
+    Ok(entries._embedded.items)
+}
+
+/// Wallabag returns the existing entry if `url` has already been saved.
+pub fn add(session: &TokenSession, config: &Config, url: &str, title: Option<&str>, tags: &[&str]) -> Result<Entry> {
+    verboseln(format!("add = {}", url));
+    let mut params = vec![("url", url.to_string())];
+    params.extend(title.map(|title| ("title", title.to_string())));
+    if !tags.is_empty() {
+        params.push(("tags", tags.join(",")));
+    }
+    let json = send_form(session, Method::Post, &config.wallabag.url("/api/entries.json"), &params)
+        .chain_err(|| format!("HTTP call to add '{}' failed", url))?;
+
+    serde_json::from_str(&json).chain_err(|| "JSON parsing failed")
+}
+
+pub fn archive(session: &TokenSession, config: &Config, id: &str) -> Result<Entry> {
+    let url = config.wallabag.url(&format!("/api/entries/{}.json", id));
+    let json = send_form(session, Method::Patch, &url, &[("archive", bool_param(true))])
+        .chain_err(|| format!("HTTP call to archive entry {} failed", id))?;
+
+    serde_json::from_str(&json).chain_err(|| "JSON parsing failed")
+}
+
+pub fn delete(session: &TokenSession, config: &Config, id: &str) -> Result<()> {
+    let url = config.wallabag.url(&format!("/api/entries/{}.json", id));
+    send_form(session, Method::Delete, &url, &[]).chain_err(|| format!("HTTP call to delete entry {} failed", id))?;
+
+    Ok(())
+}
+
+fn bool_param(b: bool) -> String {
+    if b { "1" } else { "0" }.to_string()
+}
+
+fn request<'a>(client: &'a Client, method: Method, url: &str, token: &str) -> RequestBuilder<'a> {
+    let headers = BearerAuth::new(token).headers(method.as_ref(), url, &[]);
+    client.request(method, url).headers(headers)
+}
+
+/// Requests that modify entries send their parameters form encoded and are not retried.
+fn send_form(session: &TokenSession, method: Method, url: &str, params: &[(&str, String)]) -> Result<String> {
+    let body = serde_urlencoded::to_string(&params).chain_err(|| "URL serialization failed")?;
+
+    let client = tls_client().chain_err(|| "Failed to create TLS client")?;
+    let mut response = session.send(|token| request(&client, method.clone(), url, token)
+            .header(ContentType(mime!(Application / WwwFormUrlEncoded)))
+            .body(&body)
+            .send())
+        .chain_err(|| "Failed to finish HTTP request")?;
+
+    read_json(&mut response)
+}
+
+fn read_json(response: &mut Response) -> Result<String> {
+    check_status(response)?;
+    let mut buffer = String::new();
+    response.read_to_string(&mut buffer).chain_err(|| "Failed to read HTTP response")?;
+
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use net::http::with_client;
+    use net::mock::MockClient;
+    use testing;
+
+    use std::rc::Rc;
+
+    fn config() -> Config {
+        testing::config("wallabag-client", r#"
+            [wallabag]
+            base_url = 'https://wallabag.example.com/'
+            client_id = 'client id'
+            client_secret = 'client secret'
+        "#)
+    }
+
+    #[test]
+    fn test_password_grant() {
+        ::utils::console::init(::config::Verbosity::QUIET);
+        let json = r#"{"access_token":"access","expires_in":3600,"token_type":"bearer","scope":null,"refresh_token":"refresh"}"#;
+        let mock = Rc::new(MockClient::new().respond(200, &[], json));
+
+        let token = with_client(mock.clone(), || OAuth2::new(&oauth_client(&config())).password("me", "secret")).unwrap();
+
+        assert_eq!(token.access_token, "access");
+        assert_eq!(token.refresh_token, Some("refresh".to_string()));
+        let request = &mock.requests()[0];
+        assert!(request.starts_with("POST /oauth/v2/token HTTP/1.1\r\n"));
+        assert!(request.ends_with("\r\n\r\ngrant_type=password&username=me&password=secret"));
+    }
+
+    #[test]
+    fn test_list() {
+        ::utils::console::init(::config::Verbosity::QUIET);
+        let json = r#"{"page":1,"limit":10,"pages":1,"total":1,"_embedded":{"items":[{"id":42,"url":"https://example.com",
+                       "title":"Example","is_archived":0,"is_starred":1,"created_at":"2017-07-14T02:40:00+0000","reading_time":3,
+                       "domain_name":"example.com","tags":[{"id":1,"label":"rust","slug":"rust"}]}]}}"#;
+        let mock = Rc::new(MockClient::new().respond(200, &[], json));
+        let config = config();
+        let session = TokenSession::new(&config, NAME, oauth_client(&config), Some(credentials::Credential::new("access".to_string()))).unwrap();
+        let filter = Filter { archived: Some(false), starred: None, tags: vec!["rust", "cli"] };
+
+        let entries = with_client(mock.clone(), || list(&session, &config, &filter, 10)).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].tags, vec![Tag { label: "rust".to_string() }]);
+        let request = &mock.requests()[0];
+        assert!(request.starts_with("GET /api/entries.json?sort=created&order=desc&perPage=10&detail=metadata&archive=0&tags=rust%2Ccli HTTP/1.1\r\n"));
+        assert!(request.contains("Authorization: Bearer access\r\n"));
+    }
+}
@@ -0,0 +1,65 @@
+use config::Config;
+use net::oauth::oauth2::OAuth2Client;
+
+pub const NAME: &'static str = "wallabag";
+
+#[cfg(feature = "wallabag")]
+pub mod client;
+
+/// Wallabag is self-hosted, so its URL is configured; clients are created in the Wallabag web interface, section 'API
+/// clients management'. Wallabag only supports the OAuth 2.0 password grant; cf. `client::auth`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WallabagConfig {
+    /// URL of the Wallabag instance, e.g., 'https://app.wallabag.it'
+    #[serde(default)]
+    pub base_url: String,
+    #[serde(default)]
+    pub client_id: String,
+    #[serde(default)]
+    pub client_secret: String,
+    /// Asked for by 'rat wallabag auth' if unset
+    pub username: Option<String>,
+    /// Asked for by 'rat wallabag auth' if unset
+    pub password: Option<String>,
+}
+
+impl WallabagConfig {
+    /// Returns the secrets of this section by their setting names.
+    pub fn secrets(&self) -> Vec<(String, &String)> {
+        let mut secrets = vec![("client_secret".to_string(), &self.client_secret)];
+        secrets.extend(self.password.iter().map(|s| ("password".to_string(), s)));
+        secrets
+    }
+
+    /// The URL of `path` on the configured instance, e.g., '/api/entries.json'
+    pub fn url(&self, path: &str) -> String {
+        let base_url = if self.base_url.ends_with('/') { &self.base_url[..self.base_url.len() - 1] } else { &self.base_url };
+        format!("{}{}", base_url, path)
+    }
+}
+
+pub fn oauth_client(config: &Config) -> OAuth2Client {
+    OAuth2Client {
+        client_id: config.wallabag.client_id.clone(),
+        client_secret: Some(config.wallabag.client_secret.clone()),
+        auth_endpoint: config.wallabag.url("/oauth/v2/auth"),
+        token_endpoint: config.wallabag.url("/oauth/v2/token"),
+        device_endpoint: None,
+        revocation_endpoint: None,
+        scope: None,
+        read_only_scope: None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_url() {
+        let mut config = WallabagConfig::default();
+        config.base_url = "https://wallabag.example.com/".to_string();
+
+        assert_eq!(config.url("/api/entries.json"), "https://wallabag.example.com/api/entries.json");
+    }
+}
@@ -0,0 +1,720 @@
+use errors::*;
+use clients::{centerdevice, github, instapaper, pinboard, pocket, slack, wallabag};
+use credentials::InjectedToken;
+use net::concurrent;
+use net::http;
+use net::retry::{self, RetryPolicy};
+use net::oauth::oauth2::OAuth2Client;
+use utils::secrets;
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use toml;
+
+thread_local! {
+    // Parsed configuration files by path, modification time, and length; unchanged files are not parsed again
+    static PARSED: RefCell<HashMap<PathBuf, (Option<SystemTime>, u64, toml::Value)>> = RefCell::new(HashMap::new());
+}
+
+fn parse_file(file_path: &Path) -> Result<toml::Value> {
+    let metadata = fs::metadata(file_path).chain_err(|| "Could not open config file.")?;
+    let (modified, len) = (metadata.modified().ok(), metadata.len());
+    let memoized = PARSED.with(|parsed| {
+        parsed.borrow().get(file_path)
+            .and_then(|&(m, l, ref value)| if m.is_some() && m == modified && l == len { Some(value.clone()) } else { None })
+    });
+    if let Some(value) = memoized {
+        return Ok(value);
+    }
+
+    let mut config_file = File::open(file_path).chain_err(|| "Could not open config file.")?;
+    let mut config_content = String::new();
+    config_file.read_to_string(&mut config_content).chain_err(|| "Could not read config file.")?;
+    let value = config_content.parse::<toml::Value>()
+        .chain_err(|| ErrorKind::Config("could not parse config file".to_string()))?;
+    PARSED.with(|parsed| parsed.borrow_mut().insert(file_path.to_path_buf(), (modified, len, value.clone())));
+
+    Ok(value)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "format")]
+#[derive(PartialOrd, PartialEq, Eq)]
+#[derive(Clone, Copy)]
+pub enum OutputFormat {
+    JSON,
+    HUMAN,
+    /// One JSON document per line; listings write one line per item as they are received, other commands their JSON
+    /// document on a single line
+    NDJSON,
+}
+
+impl OutputFormat {
+    pub fn is_json(&self) -> bool {
+        *self == OutputFormat::JSON || *self == OutputFormat::NDJSON
+    }
+}
+
+impl<'a> From<&'a str> for OutputFormat {
+    fn from(format: &'a str) -> Self {
+        let format_sane: &str = &format.to_string().to_uppercase();
+        match format_sane {
+            "JSON" => OutputFormat::JSON,
+            "NDJSON" => OutputFormat::NDJSON,
+            _ => OutputFormat::HUMAN
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "verbosity")]
+#[derive(PartialOrd, PartialEq, Eq)]
+#[derive(Clone, Copy)]
+pub enum Verbosity {
+    TRACE = 0,
+    VERBOSE = 1,
+    NORMAL = 2,
+    QUIET = 3,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneralConfig {
+    pub cache_dir: String,
+    pub output_format: OutputFormat,
+    pub verbosity: Verbosity,
+    /// 'file' (default), 'encrypted', or 'keyring'
+    pub credential_store: Option<String>,
+    /// Re-authenticate interactively if an access token cannot be refreshed during a command
+    pub interactive_reauth: Option<bool>,
+    /// Command printing the passphrase of the encrypted credential store, e.g., 'pass show rat'
+    pub passphrase_command: Option<String>,
+    /// Maximum number of concurrent requests of batch operations; cf. `net::concurrent`
+    pub max_concurrency: Option<usize>,
+    /// Size limit in MB of directory caches like article snapshots; cf. `cache::gc`
+    pub cache_max_size_mb: Option<u64>,
+    /// Age limit of files in directory caches, e.g., '30days'
+    pub cache_max_age: Option<String>,
+    /// Time responses of requests that only read data are used again, e.g., '5min'; cf. `cache::responses`
+    pub response_cache_ttl: Option<String>,
+    /// Attempts of requests that only read data, including the first one; cf. `net::retry`
+    pub retry_max_attempts: Option<u32>,
+    /// Delay before the first retry; doubles with every further one
+    pub retry_base_delay_ms: Option<u64>,
+    /// Randomize retry delays; enabled by default
+    pub retry_jitter: Option<bool>,
+    /// HTTP proxy of all requests; defaults to environment variable 'https_proxy'
+    pub proxy: Option<String>,
+    /// Hosts to connect to directly, e.g., ['localhost', 'intranet.example.com']; defaults to 'no_proxy'
+    pub no_proxy: Option<Vec<String>>,
+    /// PEM file of CA certificates to trust in addition to the system's
+    pub ca_bundle: Option<String>,
+    /// Time to establish a connection; 0 disables the timeout
+    pub connect_timeout_secs: Option<u64>,
+    /// Time a single read or write of a connection may take; 0 disables the timeout
+    pub read_timeout_secs: Option<u64>,
+}
+
+impl GeneralConfig {
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency.unwrap_or(concurrent::DEFAULT_MAX_CONCURRENCY)
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: self.retry_max_attempts.unwrap_or(retry::DEFAULT_MAX_ATTEMPTS),
+            base_delay: Duration::from_millis(self.retry_base_delay_ms.unwrap_or(retry::DEFAULT_BASE_DELAY_MS)),
+            jitter: self.retry_jitter.unwrap_or(true),
+        }
+    }
+
+    /// Settings of `net::http::init`; the proxy settings fall back to the conventional environment variables.
+    pub fn http_settings(&self) -> http::Settings {
+        let env = |name: &str| env::var(name.to_lowercase()).or_else(|_| env::var(name.to_uppercase())).ok();
+        http::Settings {
+            proxy: self.proxy.clone().or_else(|| env("https_proxy")),
+            no_proxy: self.no_proxy.clone()
+                .or_else(|| env("no_proxy").map(|hosts| hosts.split(',').map(|host| host.to_string()).collect()))
+                .unwrap_or_default(),
+            ca_bundle: self.ca_bundle.clone(),
+            connect_timeout: timeout(self.connect_timeout_secs.unwrap_or(http::DEFAULT_CONNECT_TIMEOUT_SECS)),
+            read_timeout: timeout(self.read_timeout_secs.unwrap_or(http::DEFAULT_READ_TIMEOUT_SECS)),
+        }
+    }
+}
+
+fn timeout(secs: u64) -> Option<Duration> {
+    if secs == 0 { None } else { Some(Duration::from_secs(secs)) }
+}
+
+/// Account name of `--account` that selects all named accounts of a module at once; cf. `Config::accounts`
+pub const ALL_ACCOUNTS: &'static str = "all";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    pub general: GeneralConfig,
+    pub centerdevice: centerdevice::CenterDeviceConfig,
+    /// URLs of RSS and Atom feeds by name; cf. module 'feed'
+    #[serde(default)]
+    pub feeds: BTreeMap<String, String>,
+    #[serde(default)]
+    pub github: github::GitHubConfig,
+    #[serde(default)]
+    pub instapaper: instapaper::InstapaperConfig,
+    #[serde(default)]
+    pub pinboard: pinboard::PinboardConfig,
+    #[serde(default)]
+    pub pocket: pocket::PocketConfig,
+    /// Rules of `rat pocket apply-rules` by name; applied in order of their names
+    #[serde(default)]
+    pub rules: BTreeMap<String, pocket::RuleConfig>,
+    pub slack: slack::SlackConfig,
+    #[serde(default)]
+    pub wallabag: wallabag::WallabagConfig,
+    #[serde(default)]
+    pub oauth: HashMap<String, OAuth2Client>,
+    /// Named profile selected by `--profile`; cf. `apply_profile`
+    #[serde(skip_serializing, skip_deserializing)]
+    pub profile: Option<String>,
+    /// Named account selected by `--account`; cf. `select_account`
+    #[serde(skip_serializing, skip_deserializing)]
+    pub account: Option<String>,
+    /// Access token supplied by `--token-file`, `--token-fd`, or systemd credentials
+    #[serde(skip_serializing, skip_deserializing)]
+    pub injected_token: Option<InjectedToken>,
+}
+
+impl Config {
+    /// Reads the configuration file. The parse is memoized, so loading an unchanged file again is cheap.
+    pub fn from_file(file_path: &Path) -> Result<Config> {
+        Config::from_file_with_profile(file_path, None)
+    }
+
+    /// Reads the configuration file and resolves its settings in layers: the file, the named profile, if any, and
+    /// environment variables; cf. `apply_profile` and `apply_env`. Command line flags are applied by the caller.
+    /// Without a file, e.g., in containers, all required settings have to be given by environment variables.
+    pub fn from_file_with_profile(file_path: &Path, profile: Option<&str>) -> Result<Config> {
+        let exists = file_path.exists();
+        let mut value = if exists { parse_file(file_path)? } else { toml::Value::Table(toml::value::Table::new()) };
+        if let Some(profile) = profile {
+            value = apply_profile(&value, profile)?;
+        }
+        apply_env(&mut value, |name| env::var(name).ok());
+        let mut config: Config = value.try_into().chain_err(|| ErrorKind::Config(if exists {
+            "could not parse config file".to_string()
+        } else {
+            format!("config file '{}' does not exist; please create it with 'rat config init' or set all required settings \
+                     by environment variables", file_path.display())
+        }))?;
+        config.profile = profile.map(|profile| profile.to_string());
+        config.register_secrets();
+
+        Ok(config)
+    }
+
+    /// Returns all client secrets and tokens of the configuration by section and setting name.
+    pub fn secrets(&self) -> Vec<(String, String, &String)> {
+        let mut all: Vec<(String, String, &String)> = Vec::new();
+        all.extend(self.centerdevice.secrets().into_iter().map(|(k, s)| (centerdevice::NAME.to_string(), k, s)));
+        all.extend(self.github.secrets().into_iter().map(|(k, s)| (github::NAME.to_string(), k, s)));
+        all.extend(self.instapaper.secrets().into_iter().map(|(k, s)| (instapaper::NAME.to_string(), k, s)));
+        all.extend(self.pinboard.secrets().into_iter().map(|(k, s)| (pinboard::NAME.to_string(), k, s)));
+        all.extend(self.pocket.secrets().into_iter().map(|(k, s)| (pocket::NAME.to_string(), k, s)));
+        all.extend(self.slack.secrets().into_iter().map(|(k, s)| (slack::NAME.to_string(), k, s)));
+        all.extend(self.wallabag.secrets().into_iter().map(|(k, s)| (wallabag::NAME.to_string(), k, s)));
+        for (name, client) in &self.oauth {
+            all.extend(client.client_secret.iter().map(|s| (format!("oauth.{}", name), "client_secret".to_string(), s)));
+        }
+        all
+    }
+
+    /// Registers all client secrets and tokens of the configuration so they are masked in diagnostic messages.
+    fn register_secrets(&self) {
+        for (_, _, secret) in self.secrets() {
+            secrets::register(secret.as_str());
+        }
+    }
+
+    /// Overlays the settings of a module with the settings of one of its named accounts, e.g., section
+    /// '[pocket.accounts.work]'. Caches and stored credentials are kept separately per account.
+    pub fn select_account(&mut self, module_name: &str, account: &str) -> Result<()> {
+        let found = match module_name {
+            centerdevice::NAME => self.centerdevice.select_account(account),
+            pocket::NAME => self.pocket.select_account(account),
+            slack::NAME => self.slack.select_account(account),
+            _ => bail!(ErrorKind::AccountsNotSupported(module_name.to_string())),
+        };
+        if !found {
+            bail!(ErrorKind::UnknownAccount(module_name.to_string(), account.to_string()));
+        }
+        self.account = Some(account.to_string());
+
+        Ok(())
+    }
+
+    /// Returns the configurations of all named accounts of a module in alphabetical order of their names, each as if
+    /// selected by `select_account`. The settings of the module's own section are not an account of their own.
+    pub fn accounts(&self, module_name: &str) -> Result<Vec<(String, Config)>> {
+        let mut names: Vec<&String> = match module_name {
+            centerdevice::NAME => self.centerdevice.accounts.keys().collect(),
+            pocket::NAME => self.pocket.accounts.keys().collect(),
+            slack::NAME => self.slack.accounts.keys().collect(),
+            _ => bail!(ErrorKind::AccountsNotSupported(module_name.to_string())),
+        };
+        names.sort();
+
+        names.into_iter().map(|name| {
+            let mut config = self.clone();
+            config.select_account(module_name, name)?;
+            Ok((name.clone(), config))
+        }).collect()
+    }
+}
+
+/// Overlays the settings of the configuration file with the settings of a named profile, e.g., section
+/// '[profiles.work.pocket]'. Unless the profile sets its own `cache_dir`, its caches and stored credentials are kept in
+/// the subdirectory 'profiles/<name>' of the cache directory, so profiles do not share access tokens.
+pub fn apply_profile(value: &toml::Value, profile: &str) -> Result<toml::Value> {
+    let mut value = value.clone();
+    let settings = match value.as_table_mut().and_then(|table| table.remove("profiles")) {
+        Some(toml::Value::Table(mut profiles)) => match profiles.remove(profile) {
+            Some(toml::Value::Table(settings)) => settings,
+            _ => bail!(ErrorKind::UnknownProfile(profile.to_string())),
+        },
+        _ => bail!(ErrorKind::UnknownProfile(profile.to_string())),
+    };
+    let has_cache_dir = settings.get("general").and_then(|general| general.get("cache_dir")).is_some();
+    if let Some(table) = value.as_table_mut() {
+        merge(table, settings);
+        if !has_cache_dir {
+            if let Some(general) = table.get_mut("general").and_then(|general| general.as_table_mut()) {
+                let cache_dir = general.get("cache_dir").and_then(|dir| dir.as_str())
+                    .map(|dir| Path::new(dir).join("profiles").join(profile).to_string_lossy().to_string());
+                if let Some(cache_dir) = cache_dir {
+                    general.insert("cache_dir".to_string(), toml::Value::String(cache_dir));
+                }
+            }
+        }
+    }
+    Ok(value)
+}
+
+/// Overrides settings by environment variables named after them, e.g., 'RAT_POCKET_ACCESS_TOKEN' for 'access_token' in
+/// section '[pocket]'; cf. `env_name`. Settings of accounts and OAuth clients cannot be overridden, because their names
+/// may contain underscores. Values are taken as by `setting_value`; a value of a string setting that looks like a
+/// number, e.g., a numeric client ID, remains a string.
+pub fn apply_env<F>(value: &mut toml::Value, lookup: F) where F: Fn(&str) -> Option<String> {
+    for key in SETTINGS.iter().filter(|key| !key.contains('*')) {
+        let raw = match lookup(&env_name(key)) {
+            Some(raw) => raw,
+            None => continue,
+        };
+        insert(value, key, setting_value(key, &raw));
+        let rejected = value.clone().try_into::<Config>()
+            .map(|_| false)
+            .unwrap_or_else(|e| e.to_string().contains(&format!("`{}`", key)));
+        if rejected {
+            insert(value, key, toml::Value::String(raw));
+        }
+    }
+}
+
+/// Name of the environment variable overriding a setting, e.g., 'RAT_GENERAL_OUTPUT_FORMAT' for 'general.output_format'
+pub fn env_name(key: &str) -> String {
+    format!("RAT_{}", key.replace('.', "_").to_uppercase())
+}
+
+/// Environment variable naming the configuration file; cf. `locate`
+pub const CONFIG_ENV: &'static str = "RAT_CONFIG";
+
+const CONFIG_FILE_NAME: &'static str = "rat.toml";
+
+/// The configuration file and the paths looked at to find it
+#[derive(Debug, PartialEq)]
+pub struct ConfigLocation {
+    /// The first existing path tried, or else the first path tried, where `rat config init` creates the file
+    pub path: PathBuf,
+    pub exists: bool,
+    pub tried: Vec<PathBuf>,
+}
+
+/// Finds the configuration file: the file given by `--config` or `$RAT_CONFIG`, whether it exists or not, or else the
+/// first existing file of `$XDG_CONFIG_HOME/rat/rat.toml`, which defaults to '~/.config/rat/rat.toml', and the legacy
+/// '~/.rat.toml'. `lookup` reads environment variables; empty variables and a relative `XDG_CONFIG_HOME` are ignored as
+/// the XDG spec requires.
+pub fn locate<F>(explicit: Option<&Path>, home: Option<&Path>, lookup: F) -> ConfigLocation where F: Fn(&str) -> Option<String> {
+    let lookup = |name: &str| lookup(name).and_then(|value| if value.is_empty() { None } else { Some(value) });
+    let explicit = explicit.map(Path::to_path_buf).or_else(|| lookup(CONFIG_ENV).map(PathBuf::from));
+    let tried = match explicit {
+        Some(path) => vec![path],
+        None => {
+            let mut tried = Vec::new();
+            let config_home = lookup("XDG_CONFIG_HOME").map(PathBuf::from).filter(|dir| dir.is_absolute())
+                .or_else(|| home.map(|home| home.join(".config")));
+            tried.extend(config_home.map(|dir| dir.join("rat").join(CONFIG_FILE_NAME)));
+            tried.extend(home.map(|home| home.join(format!(".{}", CONFIG_FILE_NAME))));
+            tried
+        }
+    };
+    let existing = tried.iter().find(|path| path.exists()).cloned();
+    let exists = existing.is_some();
+    let path = existing.or_else(|| tried.first().cloned()).unwrap_or_else(|| PathBuf::from(CONFIG_FILE_NAME));
+
+    ConfigLocation { path: path, exists: exists, tried: tried }
+}
+
+/// Settings given as tagged tables in the file, e.g., `output_format = { format = 'JSON' }`, and their tags
+pub static TAGGED_SETTINGS: &'static [(&'static str, &'static str)] = &[
+    ("general.output_format", "format"),
+    ("general.verbosity", "verbosity"),
+];
+
+/// Value of a setting given as text, e.g., on the command line: tagged settings are given by the name of the variant,
+/// e.g., 'json'; numbers, booleans, and arrays as in TOML; everything else is a string.
+pub fn setting_value(key: &str, value: &str) -> toml::Value {
+    if let Some(&(_, tag)) = TAGGED_SETTINGS.iter().find(|&&(setting, _)| setting == key) {
+        let mut table = toml::value::Table::new();
+        table.insert(tag.to_string(), toml::Value::String(value.to_uppercase()));
+        return toml::Value::Table(table);
+    }
+    format!("value = {}", value).parse::<toml::Value>().ok()
+        .and_then(|mut parsed| parsed.as_table_mut().and_then(|table| table.remove("value")))
+        .unwrap_or_else(|| toml::Value::String(value.to_string()))
+}
+
+/// Sets a setting given by its dotted key; missing sections are added.
+fn insert(value: &mut toml::Value, key: &str, setting: toml::Value) {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let name = parts.pop().unwrap_or(key);
+    let mut table = value;
+    for part in parts {
+        let current = table;
+        let sections = match current.as_table_mut() {
+            Some(sections) => sections,
+            None => return,
+        };
+        table = sections.entry(part.to_string()).or_insert_with(|| toml::Value::Table(toml::value::Table::new()));
+    }
+    if let Some(table) = table.as_table_mut() {
+        table.insert(name.to_string(), setting);
+    }
+}
+
+/// Tables are merged key by key, all other values are replaced.
+fn merge(base: &mut toml::value::Table, overlay: toml::value::Table) {
+    for (key, value) in overlay {
+        let value = match (base.get_mut(&key), value) {
+            (Some(&mut toml::Value::Table(ref mut base)), toml::Value::Table(overlay)) => {
+                merge(base, overlay);
+                continue;
+            }
+            (_, value) => value,
+        };
+        base.insert(key, value);
+    }
+}
+
+/// All settings of the configuration file by their dotted keys; `*` stands for the name of an account or OAuth client.
+/// Profiles may contain all settings, e.g., 'profiles.work.pocket.access_token'. Settings not listed here are silently
+/// ignored when the configuration is read; cf. `rat config validate`.
+pub static SETTINGS: &'static [&'static str] = &[
+    "general.cache_dir",
+    "general.output_format",
+    "general.verbosity",
+    "general.credential_store",
+    "general.interactive_reauth",
+    "general.passphrase_command",
+    "general.max_concurrency",
+    "general.cache_max_size_mb",
+    "general.cache_max_age",
+    "general.response_cache_ttl",
+    "general.retry_max_attempts",
+    "general.retry_base_delay_ms",
+    "general.retry_jitter",
+    "general.proxy",
+    "general.no_proxy",
+    "general.ca_bundle",
+    "general.connect_timeout_secs",
+    "general.read_timeout_secs",
+    "centerdevice.client_id",
+    "centerdevice.client_secret",
+    "centerdevice.refresh_token",
+    "centerdevice.access_token",
+    "centerdevice.accounts.*.client_id",
+    "centerdevice.accounts.*.client_secret",
+    "centerdevice.accounts.*.refresh_token",
+    "centerdevice.accounts.*.access_token",
+    "feeds.*",
+    "github.access_token",
+    "instapaper.consumer_key",
+    "instapaper.consumer_secret",
+    "instapaper.username",
+    "instapaper.password",
+    "pinboard.api_token",
+    "pocket.consumer_key",
+    "pocket.access_token",
+    "pocket.batch_size",
+    "pocket.queue_offline",
+    "pocket.accounts.*.consumer_key",
+    "pocket.accounts.*.access_token",
+    "rules.*.domain",
+    "rules.*.title",
+    "rules.*.url",
+    "rules.*.tag",
+    "rules.*.state",
+    "rules.*.min_words",
+    "rules.*.max_words",
+    "rules.*.add_tags",
+    "rules.*.remove_tags",
+    "rules.*.archive",
+    "rules.*.favorite",
+    "slack.client_id",
+    "slack.client_secret",
+    "slack.access_token",
+    "slack.accounts.*.client_id",
+    "slack.accounts.*.client_secret",
+    "slack.accounts.*.access_token",
+    "wallabag.base_url",
+    "wallabag.client_id",
+    "wallabag.client_secret",
+    "wallabag.username",
+    "wallabag.password",
+    "oauth.*.client_id",
+    "oauth.*.client_secret",
+    "oauth.*.auth_endpoint",
+    "oauth.*.token_endpoint",
+    "oauth.*.device_endpoint",
+    "oauth.*.revocation_endpoint",
+    "oauth.*.scope",
+    "oauth.*.read_only_scope",
+];
+
+/// Whether `key`, e.g., 'pocket.accounts.work.access_token', is a setting of the configuration file.
+pub fn is_setting(key: &str) -> bool {
+    let key = without_profile(key);
+    SETTINGS.iter().any(|setting| matches_key(setting, key))
+}
+
+/// Whether `key`, e.g., 'pocket.accounts.work', is a section containing settings.
+pub fn is_section(key: &str) -> bool {
+    if key == "profiles" || matches_key("profiles.*", key) {
+        return true;
+    }
+    let key = without_profile(key);
+    SETTINGS.iter().any(|setting| {
+        let parts: Vec<&str> = setting.split('.').collect();
+        (1..parts.len()).any(|n| matches_key(&parts[..n].join("."), key))
+    })
+}
+
+fn without_profile(key: &str) -> &str {
+    if key.starts_with("profiles.") {
+        key.splitn(3, '.').nth(2).unwrap_or("")
+    } else {
+        key
+    }
+}
+
+fn matches_key(pattern: &str, key: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('.').collect();
+    let key: Vec<&str> = key.split('.').collect();
+    pattern.len() == key.len() && pattern.iter().zip(&key).all(|(p, k)| *p == "*" || p == k)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing;
+
+    use std::io::Write;
+
+    static CONFIG: &'static str = r#"
+        [general]
+        cache_dir = '/tmp/rat-config-test'
+        output_format = { format = 'HUMAN' }
+        verbosity = { verbosity = 'QUIET' }
+
+        [centerdevice]
+        client_id = 'client id'
+        client_secret = 'client secret'
+
+        [slack]
+        client_id = 'client id'
+        client_secret = 'client secret'
+    "#;
+
+    fn write_config(path: &Path, content: &str) {
+        let mut file = File::create(path).unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_from_file_parses_changed_file_again() {
+        let path = &testing::temp_path("config-changed").with_extension("toml");
+        write_config(path, CONFIG);
+        let config = Config::from_file(path).unwrap();
+        assert_eq!(config.general.max_concurrency(), concurrent::DEFAULT_MAX_CONCURRENCY);
+
+        write_config(path, &CONFIG.replace("[centerdevice]", "max_concurrency = 12\n\n[centerdevice]"));
+        let config = Config::from_file(path).unwrap();
+
+        assert_eq!(config.general.max_concurrency(), 12);
+    }
+
+    #[test]
+    fn test_from_file_memoizes_parse() {
+        let path = &testing::temp_path("config-memo").with_extension("toml");
+        write_config(path, CONFIG);
+        Config::from_file(path).unwrap();
+
+        let memoized = PARSED.with(|parsed| parsed.borrow().contains_key(path));
+
+        assert!(memoized);
+    }
+
+    #[test]
+    fn test_is_setting() {
+        assert!(is_setting("general.verbosity"));
+        assert!(is_setting("pocket.accounts.work.access_token"));
+        assert!(is_setting("oauth.github.client_id"));
+        assert!(!is_setting("general.verbositiy"));
+        assert!(!is_setting("pocket.accounts.access_token"));
+        assert!(is_setting("profiles.work.pocket.access_token"));
+        assert!(!is_setting("profiles.work.access_token"));
+    }
+
+    #[test]
+    fn test_is_section() {
+        assert!(is_section("general"));
+        assert!(is_section("pocket.accounts"));
+        assert!(is_section("slack.accounts.work"));
+        assert!(!is_section("general.verbosity"));
+        assert!(!is_section("stocks"));
+        assert!(is_section("profiles.work"));
+        assert!(is_section("profiles.work.slack.accounts"));
+    }
+
+    #[test]
+    fn test_from_file_with_profile() {
+        let path = &testing::temp_path("config-profile").with_extension("toml");
+        write_config(path, &format!("{}{}", CONFIG, r#"
+            [profiles.work.general]
+            max_concurrency = 12
+
+            [profiles.work.pocket]
+            access_token = 'work token'
+        "#));
+
+        let config = Config::from_file_with_profile(path, Some("work")).unwrap();
+
+        assert_eq!(config.profile, Some("work".to_string()));
+        assert_eq!(config.general.max_concurrency(), 12);
+        assert_eq!(config.general.cache_dir, "/tmp/rat-config-test/profiles/work");
+        assert_eq!(config.pocket.access_token, Some("work token".to_string()));
+        assert_eq!(config.slack.client_id, "client id");
+    }
+
+    #[test]
+    fn test_accounts() {
+        let config = testing::config("config-accounts", r#"
+            [pocket]
+            consumer_key = 'key'
+            access_token = 'default token'
+
+            [pocket.accounts.team]
+            access_token = 'team token'
+
+            [pocket.accounts.personal]
+            consumer_key = 'personal key'
+        "#);
+
+        let accounts = config.accounts(pocket::NAME).unwrap();
+
+        let names: Vec<&str> = accounts.iter().map(|&(ref name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["personal", "team"]);
+        assert_eq!(accounts[0].1.account, Some("personal".to_string()));
+        assert_eq!(accounts[0].1.pocket.consumer_key, "personal key");
+        assert_eq!(accounts[0].1.pocket.access_token, None);
+        assert_eq!(accounts[1].1.pocket.consumer_key, "key");
+        assert_eq!(accounts[1].1.pocket.access_token, Some("team token".to_string()));
+        assert!(config.accounts("github").is_err());
+    }
+
+    #[test]
+    fn test_apply_env() {
+        let mut value: toml::Value = CONFIG.parse().unwrap();
+        let env = |name: &str| match name {
+            "RAT_GENERAL_OUTPUT_FORMAT" => Some("json".to_string()),
+            "RAT_GENERAL_MAX_CONCURRENCY" => Some("12".to_string()),
+            "RAT_POCKET_ACCESS_TOKEN" => Some("env token".to_string()),
+            "RAT_SLACK_CLIENT_ID" => Some("1234".to_string()),
+            _ => None,
+        };
+
+        apply_env(&mut value, env);
+        let config: Config = value.try_into().unwrap();
+
+        assert_eq!(config.general.output_format, OutputFormat::JSON);
+        assert_eq!(config.general.max_concurrency(), 12);
+        assert_eq!(config.pocket.access_token, Some("env token".to_string()));
+        assert_eq!(config.slack.client_id, "1234");
+    }
+
+    #[test]
+    fn test_env_name() {
+        assert_eq!(env_name("general.output_format"), "RAT_GENERAL_OUTPUT_FORMAT");
+    }
+
+    #[test]
+    fn test_locate() {
+        let dir = &testing::temp_path("config-locate");
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir.join("xdg").join("rat")).unwrap();
+        let home = dir.join("home");
+        fs::create_dir_all(&home).unwrap();
+        let xdg_file = dir.join("xdg").join("rat").join("rat.toml");
+        let legacy_file = home.join(".rat.toml");
+        let env = |name: &str| match name {
+            "RAT_CONFIG" => Some(dir.join("missing.toml").display().to_string()),
+            "XDG_CONFIG_HOME" => Some(dir.join("xdg").display().to_string()),
+            _ => None,
+        };
+
+        let implicit = |name: &str| if name == "RAT_CONFIG" { None } else { env(name) };
+
+        let nothing = locate(None, Some(&home), &implicit);
+        assert_eq!(nothing.tried, vec![xdg_file.clone(), legacy_file.clone()]);
+        assert_eq!((nothing.path, nothing.exists), (xdg_file.clone(), false));
+
+        write_config(&legacy_file, CONFIG);
+        assert_eq!(locate(None, Some(&home), &implicit).path, legacy_file);
+        write_config(&xdg_file, CONFIG);
+        assert_eq!(locate(None, Some(&home), &implicit).path, xdg_file);
+
+        // A file named by RAT_CONFIG is used like one given by --config, even if it is missing
+        let named = locate(None, Some(&home), &env);
+        assert_eq!(named.tried, vec![dir.join("missing.toml")]);
+        assert_eq!((named.path, named.exists), (dir.join("missing.toml"), false));
+
+        let defaults = locate(None, Some(&home), |_| Some(String::new()));
+        assert_eq!(defaults.tried, vec![home.join(".config").join("rat").join("rat.toml"), legacy_file.clone()]);
+        assert_eq!((defaults.path, defaults.exists), (legacy_file.clone(), true));
+
+        let explicit = locate(Some(Path::new("project.toml")), Some(&home), &env);
+        assert_eq!((explicit.path, explicit.tried), (PathBuf::from("project.toml"), vec![PathBuf::from("project.toml")]));
+    }
+
+    #[test]
+    fn test_from_file_with_unknown_profile() {
+        let path = &testing::temp_path("config-unknown-profile").with_extension("toml");
+        write_config(path, CONFIG);
+
+        let error = Config::from_file_with_profile(path, Some("work")).unwrap_err();
+
+        assert_eq!(error.category(), Category::Config);
+    }
+}
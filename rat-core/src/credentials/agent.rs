@@ -0,0 +1,191 @@
+//! Keeps the passphrase of the encrypted credential store for a while, so consecutive runs of rat do not ask for it
+//! again; cf. `rat auth agent`. The agent listens on a Unix socket in a directory of the cache directory that only the
+//! user may access, so the socket is never reachable by others, not even while it is being created. Clients send one command per connection: 'GET', 'SET <passphrase>', or 'FORGET'.
+
+use config::Config;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+error_chain! {
+    errors {
+        AgentNotSupported {
+            description("passphrase agent is not supported on this platform")
+            display("passphrase agent is not supported on this platform")
+        }
+    }
+}
+
+static SOCKET_DIR: &'static str = "passphrase-agent";
+static SOCKET_NAME: &'static str = "agent.sock";
+
+/// How long the agent keeps the passphrase unless configured otherwise by `rat auth agent --timeout`
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3600);
+
+fn socket_path(config: &Config) -> PathBuf {
+    PathBuf::from(&config.general.cache_dir).join(SOCKET_DIR).join(SOCKET_NAME)
+}
+
+/// Returns the passphrase kept by a running agent.
+pub fn get(config: &Config) -> Option<String> {
+    imp::request(&socket_path(config), "GET")
+        .and_then(|answer| if answer.starts_with("OK ") { Some(answer[3..].to_string()) } else { None })
+}
+
+/// Hands the passphrase to a running agent; without an agent, nothing happens.
+pub fn set(config: &Config, passphrase: &str) {
+    let _ = imp::request(&socket_path(config), &format!("SET {}", passphrase));
+}
+
+/// Makes a running agent forget the passphrase, e.g., because it turned out to be wrong.
+pub fn forget(config: &Config) {
+    let _ = imp::request(&socket_path(config), "FORGET");
+}
+
+/// Serves the passphrase until `timeout` has passed since it has been set; then the agent exits.
+pub fn serve(config: &Config, timeout: Duration) -> Result<()> {
+    imp::serve(&socket_path(config), timeout)
+}
+
+#[cfg(unix)]
+mod imp {
+    use super::{Result, ResultExt};
+    use utils::console::*;
+
+    use std::fs;
+    use std::io::{self, BufRead, BufReader, Write};
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::Path;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+    const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+    pub fn request(path: &Path, command: &str) -> Option<String> {
+        let mut stream = UnixStream::connect(path).ok()?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+        writeln!(stream, "{}", command).ok()?;
+        let mut answer = String::new();
+        BufReader::new(stream).read_line(&mut answer).ok()?;
+
+        Some(answer.trim_end_matches('\n').to_string())
+    }
+
+    pub fn serve(path: &Path, timeout: Duration) -> Result<()> {
+        if request(path, "PING").is_some() {
+            bail!("A passphrase agent is running already");
+        }
+        let dir = path.parent().ok_or("Agent socket has no directory")?;
+        create_private_dir(dir)?;
+        // Left over by an agent that has been killed
+        let _ = fs::remove_file(path);
+        let listener = UnixListener::bind(path).chain_err(|| format!("Could not listen on '{}'", path.display()))?;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600)).chain_err(|| "Could not restrict access to agent socket")?;
+        listener.set_nonblocking(true).chain_err(|| "Could not listen on agent socket")?;
+        msgln(format!("Keeping the passphrase for {} s after it has been entered; listening on '{}'.", timeout.as_secs(), path.display()));
+
+        let mut passphrase: Option<String> = None;
+        let mut deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let was_set = passphrase.is_some();
+                    if let Err(e) = answer(stream, &mut passphrase) {
+                        verboseln(format!("Failed to answer agent request: {}", e));
+                    }
+                    if !was_set && passphrase.is_some() {
+                        deadline = Instant::now() + timeout;
+                    }
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                Err(e) => return Err(e).chain_err(|| "Failed to accept agent request"),
+            }
+        }
+        let _ = fs::remove_file(path);
+        info("Passphrase agent timed out; exiting.");
+
+        Ok(())
+    }
+
+    /// Creates `dir`, or restricts it if it exists, so only the user may access it before the socket is bound in it.
+    fn create_private_dir(dir: &Path) -> Result<()> {
+        if let Some(parent) = dir.parent() {
+            fs::create_dir_all(parent).chain_err(|| "Could not create cache directory")?;
+        }
+        match fs::DirBuilder::new().mode(0o700).create(dir) {
+            Ok(()) => {}
+            Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e).chain_err(|| "Could not create agent directory"),
+        }
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700)).chain_err(|| "Could not restrict access to agent directory")
+    }
+
+    fn answer(stream: UnixStream, passphrase: &mut Option<String>) -> io::Result<()> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        let mut reader = BufReader::new(stream);
+        let mut command = String::new();
+        reader.read_line(&mut command)?;
+        let command = command.trim_end_matches('\n');
+        let answer = if command == "GET" {
+            match *passphrase {
+                Some(ref passphrase) => format!("OK {}", passphrase),
+                None => "NONE".to_string(),
+            }
+        } else if command.starts_with("SET ") {
+            *passphrase = Some(command[4..].to_string());
+            "OK".to_string()
+        } else if command == "FORGET" {
+            *passphrase = None;
+            "OK".to_string()
+        } else {
+            "OK".to_string()
+        };
+
+        writeln!(reader.get_mut(), "{}", answer)
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use testing;
+
+        #[test]
+        fn test_serve_passphrase() {
+            let path = testing::temp_path("agent").join("agent.sock");
+            let agent_path = path.clone();
+            let agent = thread::spawn(move || serve(&agent_path, Duration::from_secs(2)));
+            while request(&path, "PING").is_none() {
+                thread::sleep(Duration::from_millis(10));
+            }
+
+            assert_eq!(fs::metadata(path.parent().unwrap()).unwrap().permissions().mode() & 0o777, 0o700);
+            assert_eq!(request(&path, "GET"), Some("NONE".to_string()));
+            request(&path, "SET secret passphrase");
+            assert_eq!(request(&path, "GET"), Some("OK secret passphrase".to_string()));
+            request(&path, "FORGET");
+            assert_eq!(request(&path, "GET"), Some("NONE".to_string()));
+
+            agent.join().unwrap().unwrap();
+            assert!(!path.exists());
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use super::{ErrorKind, Result};
+
+    use std::path::Path;
+    use std::time::Duration;
+
+    pub fn request(_: &Path, _: &str) -> Option<String> {
+        None
+    }
+
+    pub fn serve(_: &Path, _: Duration) -> Result<()> {
+        bail!(ErrorKind::AgentNotSupported)
+    }
+}
@@ -0,0 +1,251 @@
+use super::{agent, Credential};
+use cache::Cache;
+use config::Config;
+use errors::Category;
+use utils::console::*;
+use utils::secrets;
+
+use base64;
+use crypto::aead::{AeadDecryptor, AeadEncryptor};
+use crypto::chacha20poly1305::ChaCha20Poly1305;
+use crypto::scrypt::{scrypt, ScryptParams};
+use rand::{OsRng, Rng};
+use std::env;
+use std::process::{Command, Stdio};
+use std::str;
+use std::sync::Mutex;
+use toml;
+
+static CACHE_NAME: &'static str = "credentials.enc";
+static PASSPHRASE_ENV: &'static str = "RAT_PASSPHRASE";
+static FORMAT_VERSION: u8 = 1;
+// scrypt parameters recommended for interactive use
+static SCRYPT_LOG_N: u8 = 15;
+static SCRYPT_R: u32 = 8;
+static SCRYPT_P: u32 = 1;
+// Bounds of scrypt parameters read from a file; beyond, scrypt panics or needs gigabytes of memory
+static MAX_SCRYPT_LOG_N: u8 = 20;
+static MAX_SCRYPT_R: u32 = 32;
+static MAX_SCRYPT_P: u32 = 16;
+
+error_chain! {
+    errors {
+        NoPassphrase {
+            description("no passphrase for encrypted credential store available")
+            display("no passphrase for encrypted credential store available")
+        }
+
+        WrongPassphrase {
+            description("failed to decrypt credential store")
+            display("failed to decrypt credential store; wrong passphrase?")
+        }
+
+        MalformedCredential(reason: String) {
+            description("malformed encrypted credential")
+            display("malformed encrypted credential: {}", reason)
+        }
+    }
+}
+
+impl ErrorKind {
+    pub fn category(&self) -> Option<Category> {
+        match *self {
+            ErrorKind::NoPassphrase | ErrorKind::WrongPassphrase => Some(Category::Auth),
+            ErrorKind::MalformedCredential(_) => Some(Category::Parse),
+            _ => None,
+        }
+    }
+}
+
+/// On-disk format of an encrypted credential; binary fields are base64 encoded.
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedCredential {
+    version: u8,
+    log_n: u8,
+    r: u32,
+    p: u32,
+    salt: String,
+    nonce: String,
+    tag: String,
+    ciphertext: String,
+}
+
+/// Asked for only once per run; shared by all threads, e.g., of concurrent requests
+static PASSPHRASE: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn store(config: &Config, module_name: &str, credential: &Credential) -> Result<()> {
+    let plaintext = toml::to_string(credential).chain_err(|| "Failed to serialize credential")?;
+    let passphrase = passphrase(config)?;
+    let encrypted = encrypt(&passphrase, module_name, plaintext.as_bytes(), SCRYPT_LOG_N)?;
+
+    Cache::new(config, module_name, CACHE_NAME).write(&encrypted).chain_err(|| "Failed to write credential store")
+}
+
+pub fn load(config: &Config, module_name: &str) -> Result<Credential> {
+    let encrypted: EncryptedCredential = Cache::new(config, module_name, CACHE_NAME).load()
+        .chain_err(|| "Failed to read credential store")?;
+    let passphrase = passphrase(config)?;
+    let plaintext = match decrypt(&passphrase, module_name, &encrypted) {
+        Err(Error(ErrorKind::WrongPassphrase, _)) => {
+            // Ask again next time instead of trying the wrong passphrase over and over
+            *PASSPHRASE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+            agent::forget(config);
+            bail!(ErrorKind::WrongPassphrase)
+        }
+        result => result?,
+    };
+    let plaintext = str::from_utf8(&plaintext).chain_err(|| ErrorKind::WrongPassphrase)?;
+
+    toml::from_str(plaintext).chain_err(|| "Failed to parse credential")
+}
+
+pub fn remove(config: &Config, module_name: &str) -> Result<()> {
+    Cache::new(config, module_name, CACHE_NAME).remove().chain_err(|| "Failed to remove credential")
+}
+
+/// Returns the passphrase from environment variable `RAT_PASSPHRASE`, a running passphrase agent, the output of
+/// `passphrase_command`, or asks for it on the terminal. A passphrase asked for is handed to the agent; cf. `agent`.
+fn passphrase(config: &Config) -> Result<String> {
+    let mut cached = PASSPHRASE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(ref passphrase) = *cached {
+        return Ok(passphrase.clone());
+    }
+
+    let passphrase = if let Ok(passphrase) = env::var(PASSPHRASE_ENV) {
+        verboseln(format!("Using passphrase from environment variable {}.", PASSPHRASE_ENV));
+        passphrase
+    } else if let Some(passphrase) = agent::get(config) {
+        verboseln("Using passphrase from passphrase agent.");
+        passphrase
+    } else {
+        let passphrase = if let Some(ref command) = config.general.passphrase_command {
+            verboseln(format!("Retrieving passphrase by running '{}'.", command));
+            run_passphrase_command(command)?
+        } else {
+            read_secret("Passphrase for credential store: ").chain_err(|| ErrorKind::NoPassphrase)?
+        };
+        if !passphrase.is_empty() {
+            agent::set(config, &passphrase);
+        }
+        passphrase
+    };
+    if passphrase.is_empty() {
+        bail!(ErrorKind::NoPassphrase);
+    }
+    secrets::register(passphrase.as_str());
+    *cached = Some(passphrase.clone());
+
+    Ok(passphrase)
+}
+
+fn run_passphrase_command(command: &str) -> Result<String> {
+    let output = Command::new("sh").arg("-c").arg(command)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()
+        .chain_err(|| ErrorKind::NoPassphrase)?;
+    if !output.status.success() {
+        bail!(ErrorKind::NoPassphrase);
+    }
+    let stdout = String::from_utf8(output.stdout).chain_err(|| ErrorKind::NoPassphrase)?;
+
+    Ok(stdout.lines().next().unwrap_or("").to_string())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], log_n: u8, r: u32, p: u32) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    scrypt(passphrase.as_bytes(), salt, &ScryptParams::new(log_n, r, p), &mut key);
+    key
+}
+
+/// The module name is authenticated as additional data, so credentials cannot be swapped between modules.
+fn encrypt(passphrase: &str, module_name: &str, plaintext: &[u8], log_n: u8) -> Result<EncryptedCredential> {
+    let mut rng = OsRng::new().chain_err(|| "Failed to create random number generator")?;
+    let mut salt = [0u8; 16];
+    rng.fill_bytes(&mut salt);
+    let mut nonce = [0u8; 8];
+    rng.fill_bytes(&mut nonce);
+
+    let key = derive_key(passphrase, &salt, log_n, SCRYPT_R, SCRYPT_P);
+    let mut ciphertext = vec![0u8; plaintext.len()];
+    let mut tag = [0u8; 16];
+    ChaCha20Poly1305::new(&key, &nonce, module_name.as_bytes()).encrypt(plaintext, &mut ciphertext, &mut tag);
+
+    Ok(EncryptedCredential {
+        version: FORMAT_VERSION,
+        log_n: log_n,
+        r: SCRYPT_R,
+        p: SCRYPT_P,
+        salt: base64::encode(&salt),
+        nonce: base64::encode(&nonce),
+        tag: base64::encode(&tag),
+        ciphertext: base64::encode(&ciphertext),
+    })
+}
+
+fn decrypt(passphrase: &str, module_name: &str, encrypted: &EncryptedCredential) -> Result<Vec<u8>> {
+    if encrypted.version != FORMAT_VERSION {
+        bail!(format!("Unsupported credential store format version {}", encrypted.version));
+    }
+    let salt = base64::decode(&encrypted.salt).chain_err(|| "Failed to decode salt")?;
+    let nonce = base64::decode(&encrypted.nonce).chain_err(|| "Failed to decode nonce")?;
+    let tag = base64::decode(&encrypted.tag).chain_err(|| "Failed to decode tag")?;
+    let ciphertext = base64::decode(&encrypted.ciphertext).chain_err(|| "Failed to decode ciphertext")?;
+    if nonce.len() != 8 || tag.len() != 16 {
+        bail!(ErrorKind::MalformedCredential("invalid nonce or tag".to_string()));
+    }
+    if encrypted.log_n == 0 || encrypted.log_n > MAX_SCRYPT_LOG_N || encrypted.r == 0 || encrypted.r > MAX_SCRYPT_R
+        || encrypted.p == 0 || encrypted.p > MAX_SCRYPT_P {
+        bail!(ErrorKind::MalformedCredential(format!("invalid scrypt parameters log_n={}, r={}, p={}",
+                                                     encrypted.log_n, encrypted.r, encrypted.p)));
+    }
+
+    let key = derive_key(passphrase, &salt, encrypted.log_n, encrypted.r, encrypted.p);
+    let mut plaintext = vec![0u8; ciphertext.len()];
+    if !ChaCha20Poly1305::new(&key, &nonce, module_name.as_bytes()).decrypt(&ciphertext, &mut plaintext, &tag) {
+        bail!(ErrorKind::WrongPassphrase);
+    }
+
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let encrypted = encrypt("passphrase", "pocket", b"access_token = \"secret\"", 4).unwrap();
+
+        assert_eq!(decrypt("passphrase", "pocket", &encrypted).unwrap(), b"access_token = \"secret\"".to_vec());
+    }
+
+    #[test]
+    fn test_decrypt_wrong_passphrase() {
+        let encrypted = encrypt("passphrase", "pocket", b"access_token = \"secret\"", 4).unwrap();
+
+        assert!(decrypt("wrong", "pocket", &encrypted).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_other_module() {
+        let encrypted = encrypt("passphrase", "pocket", b"access_token = \"secret\"", 4).unwrap();
+
+        assert!(decrypt("passphrase", "slack", &encrypted).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_rejects_invalid_scrypt_parameters() {
+        for &(log_n, r, p) in &[(0, 8, 1), (64, 8, 1), (4, 0, 1), (4, 8, 0), (4, u32::max_value(), u32::max_value())] {
+            let mut encrypted = encrypt("passphrase", "pocket", b"access_token = \"secret\"", 4).unwrap();
+            encrypted.log_n = log_n;
+            encrypted.r = r;
+            encrypted.p = p;
+
+            match decrypt("passphrase", "pocket", &encrypted) {
+                Err(Error(ErrorKind::MalformedCredential(_), _)) => {}
+                result => panic!("expected malformed credential for ({}, {}, {}), got {:?}", log_n, r, p, result),
+            }
+        }
+    }
+}
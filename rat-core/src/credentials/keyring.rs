@@ -0,0 +1,251 @@
+//! Credentials in the platform's secret store: the Secret Service, e.g., GNOME Keyring or KWallet, by `secret-tool` on
+//! Linux and BSD; the login Keychain by `security` on macOS; and the Credential Manager by PowerShell on Windows. rat
+//! runs these tools instead of linking the platforms' libraries, so builds do not depend on them. Secrets are passed to
+//! the tools by stdin, never as arguments, which other users could see.
+
+use super::Credential;
+use config::Config;
+use errors::Category;
+use utils::console::*;
+
+use serde_json;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+static SERVICE: &'static str = "rat";
+
+error_chain! {
+    errors {
+        Unavailable(tool: String) {
+            description("platform secret store unavailable")
+            display("platform secret store is unavailable; please install '{}'", tool)
+        }
+
+        Failed(entry: String) {
+            description("platform secret store failed")
+            display("platform secret store failed to access credential '{}'", entry)
+        }
+    }
+}
+
+impl ErrorKind {
+    pub fn category(&self) -> Option<Category> {
+        match *self {
+            ErrorKind::Unavailable(_) => Some(Category::Config),
+            _ => None,
+        }
+    }
+}
+
+pub fn store(config: &Config, module_name: &str, credential: &Credential) -> Result<()> {
+    let secret = serde_json::to_string(credential).chain_err(|| "Failed to serialize credential")?;
+    let entry = entry(config, module_name);
+    verboseln(format!("Storing credential '{}' in the platform secret store.", entry));
+
+    run(platform::store(&entry), Some(&platform::encode(&entry, &secret)), &entry).map(|_| ())
+}
+
+pub fn load(config: &Config, module_name: &str) -> Result<Credential> {
+    let entry = entry(config, module_name);
+    let secret = run(platform::lookup(&entry), None, &entry)?;
+
+    serde_json::from_str(secret.trim()).chain_err(|| "Failed to parse credential")
+}
+
+pub fn remove(config: &Config, module_name: &str) -> Result<()> {
+    let entry = entry(config, module_name);
+    // A missing credential has been removed already
+    if run(platform::lookup(&entry), None, &entry).is_err() {
+        return Ok(());
+    }
+
+    run(platform::remove(&entry), None, &entry).map(|_| ())
+}
+
+/// Name of the credential in the secret store; like caches, credentials are kept separately per account and profile.
+fn entry(config: &Config, module_name: &str) -> String {
+    let mut entry = module_name.to_string();
+    if let Some(ref account) = config.account {
+        entry = format!("{}.{}", entry, account);
+    }
+    if let Some(ref profile) = config.profile {
+        entry = format!("{}@{}", entry, profile);
+    }
+    entry
+}
+
+/// Quotes `s` for the command line of `security -i`, which splits words at white space like a shell.
+#[cfg(any(target_os = "macos", test))]
+fn quote_security(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Quotes `s` as a PowerShell string literal; quotes, including the typographic ones PowerShell accepts as well, are
+/// escaped by doubling them.
+#[cfg(any(windows, test))]
+fn quote_powershell(s: &str) -> String {
+    let mut quoted = String::from("'");
+    for c in s.chars() {
+        if c == '\'' || ('\u{2018}'..='\u{201b}').contains(&c) {
+            quoted.push(c);
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Returns stdout of the tool
+fn run(mut command: Command, input: Option<&str>, entry: &str) -> Result<String> {
+    let tool = platform::TOOL.to_string();
+    let mut child = command
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| if e.kind() == io::ErrorKind::NotFound {
+            Error::from(ErrorKind::Unavailable(tool))
+        } else {
+            Error::with_chain(e, ErrorKind::Failed(entry.to_string()))
+        })?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin.write_all(input.as_bytes()).chain_err(|| ErrorKind::Failed(entry.to_string()))?;
+    }
+    let output = child.wait_with_output().chain_err(|| ErrorKind::Failed(entry.to_string()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !stderr.trim().is_empty() {
+            verboseln(format!("{} failed: {}", platform::TOOL, stderr.trim()));
+        }
+        bail!(ErrorKind::Failed(entry.to_string()));
+    }
+
+    String::from_utf8(output.stdout).chain_err(|| ErrorKind::Failed(entry.to_string()))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use super::SERVICE;
+    use std::process::Command;
+
+    pub static TOOL: &'static str = "secret-tool";
+
+    pub fn store(entry: &str) -> Command {
+        let mut command = Command::new(TOOL);
+        command.args(&["store", "--label", &format!("{}: {}", SERVICE, entry), "service", SERVICE, "account", entry]);
+        command
+    }
+
+    pub fn lookup(entry: &str) -> Command {
+        let mut command = Command::new(TOOL);
+        command.args(&["lookup", "service", SERVICE, "account", entry]);
+        command
+    }
+
+    pub fn remove(entry: &str) -> Command {
+        let mut command = Command::new(TOOL);
+        command.args(&["clear", "service", SERVICE, "account", entry]);
+        command
+    }
+
+    pub fn encode(_: &str, secret: &str) -> String {
+        secret.to_string()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{quote_security, SERVICE};
+    use std::process::Command;
+
+    pub static TOOL: &'static str = "security";
+
+    /// Reads the command from stdin, so the secret does not show up in the arguments; cf. `encode`
+    pub fn store(_: &str) -> Command {
+        let mut command = Command::new(TOOL);
+        command.arg("-i");
+        command
+    }
+
+    pub fn lookup(entry: &str) -> Command {
+        let mut command = Command::new(TOOL);
+        command.args(&["find-generic-password", "-s", SERVICE, "-a", entry, "-w"]);
+        command
+    }
+
+    pub fn remove(entry: &str) -> Command {
+        let mut command = Command::new(TOOL);
+        command.args(&["delete-generic-password", "-s", SERVICE, "-a", entry]);
+        command
+    }
+
+    /// The secret is given hex encoded, so it needs no quoting; the entry contains the account and profile, which may
+    /// contain white space or quotes.
+    pub fn encode(entry: &str, secret: &str) -> String {
+        let hex: String = secret.bytes().map(|b| format!("{:02x}", b)).collect();
+        format!("add-generic-password -U -s {} -a {} -X {}\n", SERVICE, quote_security(entry), hex)
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::{quote_powershell, SERVICE};
+    use std::process::Command;
+
+    pub static TOOL: &'static str = "powershell";
+
+    static VAULT: &'static str = "[void][Windows.Security.Credentials.PasswordVault,Windows.Security.Credentials,ContentType=WindowsRuntime]; \
+                                  $vault = New-Object Windows.Security.Credentials.PasswordVault;";
+
+    fn powershell(script: &str) -> Command {
+        let mut command = Command::new(TOOL);
+        command.args(&["-NoProfile", "-NonInteractive", "-Command", &format!("{} {}", VAULT, script)]);
+        command
+    }
+
+    pub fn store(entry: &str) -> Command {
+        powershell(&format!("$vault.Add((New-Object Windows.Security.Credentials.PasswordCredential('{}', {}, [Console]::In.ReadToEnd())))",
+                            SERVICE, quote_powershell(entry)))
+    }
+
+    pub fn lookup(entry: &str) -> Command {
+        powershell(&format!("$c = $vault.Retrieve('{}', {}); $c.RetrievePassword(); [Console]::Out.Write($c.Password)", SERVICE, quote_powershell(entry)))
+    }
+
+    pub fn remove(entry: &str) -> Command {
+        powershell(&format!("$vault.Remove($vault.Retrieve('{}', {}))", SERVICE, quote_powershell(entry)))
+    }
+
+    pub fn encode(_: &str, secret: &str) -> String {
+        secret.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing;
+
+    #[test]
+    fn test_entry_per_account_and_profile() {
+        let mut config = testing::config("keyring", "");
+        assert_eq!(entry(&config, "pocket"), "pocket");
+
+        config.account = Some("work".to_string());
+        config.profile = Some("laptop".to_string());
+
+        assert_eq!(entry(&config, "pocket"), "pocket.work@laptop");
+    }
+
+    #[test]
+    fn test_quote_entry_of_account_with_space_and_quotes() {
+        let mut config = testing::config("keyring-quote", "");
+        config.account = Some("my team's \"work\"".to_string());
+        let entry = entry(&config, "pocket");
+
+        assert_eq!(quote_security(&entry), r#""pocket.my team's \"work\"""#);
+        assert_eq!(quote_powershell(&entry), r#"'pocket.my team''s "work"'"#);
+        assert_eq!(quote_powershell("a\u{2019}b"), "'a\u{2019}\u{2019}b'");
+        assert_eq!(quote_security("back\\slash"), r#""back\\slash""#);
+    }
+}
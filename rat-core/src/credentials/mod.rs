@@ -0,0 +1,353 @@
+use cache::Cache;
+use config::Config;
+use errors::Category;
+use utils::console::*;
+use utils::secrets;
+use utils::time::unix_now;
+
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+pub mod agent;
+pub mod encrypted;
+pub mod keyring;
+
+error_chain! {
+    errors {
+        NoAccessToken(module_name: String) {
+            description("no access token available")
+            display("no access token for module {} available; please run 'rat auth login {}'", module_name, module_name)
+        }
+
+        CredentialStoreFailed(module_name: String) {
+            description("failed to access credential store")
+            display("failed to access credential store for module {}", module_name)
+        }
+
+        UnknownCredentialStore(name: String) {
+            description("unknown credential store")
+            display("unknown credential store '{}'; use 'file', 'encrypted', or 'keyring'", name)
+        }
+
+        ReadOnlyAccessToken(module_name: String) {
+            description("access token lacks write scope")
+            display("access token for module {} lacks write scope; please run 'rat auth login {}' without '--read-only'", module_name, module_name)
+        }
+    }
+}
+
+impl ErrorKind {
+    pub fn category(&self) -> Option<Category> {
+        match *self {
+            ErrorKind::NoAccessToken(_) | ErrorKind::ReadOnlyAccessToken(_) => Some(Category::Auth),
+            ErrorKind::UnknownCredentialStore(_) => Some(Category::Config),
+            _ => None,
+        }
+    }
+}
+
+static CACHE_NAME: &'static str = "credentials";
+static USAGE_CACHE_NAME: &'static str = "credentials.usage";
+static SYSTEMD_CREDENTIALS_DIR_ENV: &'static str = "CREDENTIALS_DIRECTORY";
+/// Tokens expiring within this period are reported as expiring soon.
+static EXPIRY_WARNING_SECS: u64 = 3 * 24 * 60 * 60;
+
+/// Access token supplied at runtime, e.g., by `--token-file`, for daemon deployments that must not persist secrets. It
+/// takes precedence over all other access tokens of its module and is never written to the credential store.
+#[derive(Debug, Clone)]
+pub struct InjectedToken {
+    pub module_name: String,
+    access_token: String,
+}
+
+impl InjectedToken {
+    /// Reads the access token from the first line of `path`; use '/dev/fd/<n>' to read from a file descriptor.
+    pub fn from_file(module_name: &str, path: &Path) -> Result<Self> {
+        let mut file = File::open(path).chain_err(|| format!("Could not open token file '{}'", path.display()))?;
+        let mut content = String::new();
+        file.read_to_string(&mut content).chain_err(|| format!("Could not read token file '{}'", path.display()))?;
+        let access_token = content.lines().next().unwrap_or("").trim().to_string();
+        if access_token.is_empty() {
+            bail!(format!("Token file '{}' is empty", path.display()));
+        }
+        secrets::register(access_token.as_str());
+
+        Ok(InjectedToken { module_name: module_name.to_string(), access_token: access_token })
+    }
+
+    /// systemd passes credentials configured with `LoadCredential=` as files in `$CREDENTIALS_DIRECTORY`; rat uses the
+    /// file '<module>.token' if present.
+    pub fn from_systemd(module_name: &str) -> Result<Option<Self>> {
+        let mut path = match env::var(SYSTEMD_CREDENTIALS_DIR_ENV) {
+            Ok(dir) => PathBuf::from(dir),
+            Err(_) => return Ok(None),
+        };
+        path.push(format!("{}.token", module_name));
+        if !path.is_file() {
+            return Ok(None);
+        }
+        verboseln(format!("Using access token from systemd credential '{}'.", path.display()));
+
+        InjectedToken::from_file(module_name, &path).map(Some)
+    }
+}
+
+fn injected(config: &Config, module_name: &str) -> Option<String> {
+    if let Some(ref injected) = config.injected_token {
+        if injected.module_name == module_name {
+            return Some(injected.access_token.clone());
+        }
+    }
+    match InjectedToken::from_systemd(module_name) {
+        Ok(injected) => injected.map(|i| i.access_token),
+        Err(e) => {
+            warning(format!("Ignoring systemd credential for module {} because {}.", module_name, e));
+            None
+        }
+    }
+}
+
+pub fn is_injected(config: &Config, module_name: &str) -> bool {
+    injected(config, module_name).is_some()
+}
+
+/// Credential store backend selected by `credential_store` in section '[general]'
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Backend {
+    /// Plain TOML files in the cache directory
+    File,
+    /// Passphrase encrypted files in the cache directory
+    Encrypted,
+    /// The platform's secret store, e.g., the macOS Keychain
+    Keyring,
+}
+
+impl Backend {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Backend::File => "file",
+            Backend::Encrypted => "encrypted",
+            Backend::Keyring => "keyring",
+        }
+    }
+}
+
+/// Kept apart from the credential, so recording a use neither rewrites nor decrypts the credential store.
+#[derive(Debug, Serialize, Deserialize)]
+struct Usage {
+    /// Unix timestamp in seconds
+    last_used: u64,
+}
+
+pub fn backend(config: &Config) -> Result<Backend> {
+    match config.general.credential_store.as_ref().map(|s| s.as_str()) {
+        None | Some("file") => Ok(Backend::File),
+        Some("encrypted") => Ok(Backend::Encrypted),
+        Some("keyring") => Ok(Backend::Keyring),
+        Some(name) => bail!(ErrorKind::UnknownCredentialStore(name.to_string())),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Health {
+    /// Token does not expire or expiry is unknown
+    Valid,
+    /// Seconds until the token expires
+    ExpiresSoon(u64),
+    Expired,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Credential {
+    pub access_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")] pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")] pub refresh_token: Option<String>,
+    /// Secret of an OAuth 1.0a access token; cf. `net::oauth::oauth1`
+    #[serde(skip_serializing_if = "Option::is_none")] pub token_secret: Option<String>,
+    /// Unix timestamp in seconds
+    #[serde(skip_serializing_if = "Option::is_none")] pub expires_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")] pub scope: Option<String>,
+    /// Unix timestamp in seconds
+    #[serde(skip_serializing_if = "Option::is_none")] pub obtained_at: Option<u64>,
+    /// Set by `rat auth login --read-only`; mutating commands refuse to run with such a token
+    #[serde(default, skip_serializing_if = "is_false")] pub read_only: bool,
+    /// OAuth 2.0 flow the token has been obtained by, e.g., 'device'; re-authentication runs it again
+    #[serde(skip_serializing_if = "Option::is_none")] pub flow: Option<String>,
+}
+
+fn is_false(b: &bool) -> bool {
+    !*b
+}
+
+impl Credential {
+    pub fn new(access_token: String) -> Self {
+        Credential {
+            access_token: access_token,
+            username: None,
+            refresh_token: None,
+            token_secret: None,
+            expires_at: None,
+            scope: None,
+            obtained_at: Some(unix_now()),
+            read_only: false,
+            flow: None,
+        }
+    }
+
+    pub fn health(&self) -> Health {
+        let now = unix_now();
+        match self.expires_at {
+            Some(expires_at) if expires_at <= now => Health::Expired,
+            Some(expires_at) if expires_at - now <= EXPIRY_WARNING_SECS => Health::ExpiresSoon(expires_at - now),
+            _ => Health::Valid,
+        }
+    }
+
+    /// Tokens with a refresh token are renewed transparently when they expire.
+    pub fn is_refreshable(&self) -> bool {
+        self.refresh_token.is_some()
+    }
+
+    fn register_secrets(&self) {
+        secrets::register(self.access_token.as_str());
+        if let Some(ref refresh_token) = self.refresh_token {
+            secrets::register(refresh_token.as_str());
+        }
+        if let Some(ref token_secret) = self.token_secret {
+            secrets::register(token_secret.as_str());
+        }
+    }
+}
+
+pub fn store(config: &Config, module_name: &str, credential: &Credential) -> Result<()> {
+    credential.register_secrets();
+    match backend(config)? {
+        Backend::File => Cache::new(config, module_name, CACHE_NAME).write(credential)
+            .chain_err(|| ErrorKind::CredentialStoreFailed(module_name.to_string())),
+        Backend::Encrypted => encrypted::store(config, module_name, credential)
+            .chain_err(|| ErrorKind::CredentialStoreFailed(module_name.to_string())),
+        Backend::Keyring => keyring::store(config, module_name, credential)
+            .chain_err(|| ErrorKind::CredentialStoreFailed(module_name.to_string())),
+    }
+}
+
+pub fn load(config: &Config, module_name: &str) -> Result<Credential> {
+    if let Some(access_token) = injected(config, module_name) {
+        let mut credential = Credential::new(access_token);
+        credential.obtained_at = None;
+        return Ok(credential);
+    }
+
+    let credential: Credential = match backend(config)? {
+        Backend::File => Cache::new(config, module_name, CACHE_NAME).load()
+            .chain_err(|| ErrorKind::CredentialStoreFailed(module_name.to_string()))?,
+        Backend::Encrypted => encrypted::load(config, module_name)
+            .chain_err(|| ErrorKind::CredentialStoreFailed(module_name.to_string()))?,
+        Backend::Keyring => keyring::load(config, module_name)
+            .chain_err(|| ErrorKind::CredentialStoreFailed(module_name.to_string()))?,
+    };
+    credential.register_secrets();
+
+    Ok(credential)
+}
+
+pub fn remove(config: &Config, module_name: &str) -> Result<()> {
+    let _ = Cache::new(config, module_name, USAGE_CACHE_NAME).remove();
+    match backend(config)? {
+        Backend::File => Cache::new(config, module_name, CACHE_NAME).remove()
+            .chain_err(|| ErrorKind::CredentialStoreFailed(module_name.to_string())),
+        Backend::Encrypted => encrypted::remove(config, module_name)
+            .chain_err(|| ErrorKind::CredentialStoreFailed(module_name.to_string())),
+        Backend::Keyring => keyring::remove(config, module_name)
+            .chain_err(|| ErrorKind::CredentialStoreFailed(module_name.to_string())),
+    }
+}
+
+/// Records that the access token of `module_name` has been used just now; failures are not worth failing a command.
+pub fn record_usage(config: &Config, module_name: &str) {
+    let usage = Usage { last_used: unix_now() };
+    if Cache::new(config, module_name, USAGE_CACHE_NAME).write(&usage).is_err() {
+        verboseln(format!("Failed to record usage of access token for module {}.", module_name));
+    }
+}
+
+pub fn last_used(config: &Config, module_name: &str) -> Option<u64> {
+    Cache::new(config, module_name, USAGE_CACHE_NAME).load::<Usage>().ok().map(|usage| usage.last_used)
+}
+
+pub fn mark_read_only(config: &Config, module_name: &str) -> Result<()> {
+    let mut credential = load(config, module_name)?;
+    credential.read_only = true;
+    store(config, module_name, &credential)
+}
+
+/// Fails if the stored access token of `module_name` has been granted read-only access. Access tokens from the
+/// configuration file are assumed to be writable.
+pub fn ensure_writable(config: &Config, module_name: &str) -> Result<()> {
+    match load(config, module_name) {
+        Ok(ref credential) if credential.read_only => bail!(ErrorKind::ReadOnlyAccessToken(module_name.to_string())),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the injected access token if any, the access token from the configuration file if set, otherwise the one
+/// from the credential store.
+pub fn access_token(config: &Config, module_name: &str, configured: &Option<String>) -> Result<String> {
+    if let Some(access_token) = injected(config, module_name) {
+        return Ok(access_token);
+    }
+    if let Some(ref access_token) = *configured {
+        return Ok(access_token.clone());
+    }
+
+    let access_token = load(config, module_name)
+        .map(|c| c.access_token)
+        .chain_err(|| ErrorKind::NoAccessToken(module_name.to_string()))?;
+    record_usage(config, module_name);
+
+    Ok(access_token)
+}
+
+/// Prints a notice if the stored access token of `module_name` has expired or expires soon and cannot be refreshed.
+pub fn check_health(config: &Config, module_name: &str) {
+    let credential = match load(config, module_name) {
+        Ok(credential) => credential,
+        Err(_) => return,
+    };
+    if credential.is_refreshable() {
+        return;
+    }
+    match credential.health() {
+        Health::Expired =>
+            warning(format!("Access token for module {} has expired; please run 'rat auth login {}'.", module_name, module_name)),
+        Health::ExpiresSoon(secs) =>
+            warning(format!("Access token for module {} expires in {} hour(s); please run 'rat auth login {}'.",
+                module_name, secs / 60 / 60, module_name)),
+        Health::Valid => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_health() {
+        let mut credential = Credential::new("token".to_string());
+        assert_eq!(credential.health(), Health::Valid);
+
+        credential.expires_at = Some(unix_now() + 60);
+        match credential.health() {
+            Health::ExpiresSoon(secs) => assert!(secs <= 60),
+            health => panic!("unexpected health {:?}", health),
+        }
+
+        credential.expires_at = Some(unix_now() + 30 * 24 * 60 * 60);
+        assert_eq!(credential.health(), Health::Valid);
+
+        credential.expires_at = Some(unix_now() - 1);
+        assert_eq!(credential.health(), Health::Expired);
+    }
+}
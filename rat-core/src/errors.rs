@@ -0,0 +1,316 @@
+use credentials;
+use credentials::encrypted;
+use credentials::keyring;
+use net::basic_auth;
+use net::http;
+use net::oauth::oauth2;
+
+use hyper;
+use serde_json;
+use std::error::Error as StdError;
+use std::io;
+
+error_chain! {
+    errors {
+        NoCommandSpecified {
+            description("no command specified")
+            display("no command specified")
+        }
+
+        NoSubcommandSpecified(module_name: String) {
+            description("no sub command specified")
+            display("no sub command for module {} specified", module_name)
+        }
+
+        ModuleNotIncluded(module_name: String) {
+            description("module not included in this build")
+            display("module {} is not included in this build of rat; please rebuild with feature '{}'", module_name, module_name)
+        }
+
+        ModuleFailed(module_name: String) {
+            description("module failed")
+            display("executing module {} failed", module_name)
+        }
+
+        AccountsNotSupported(module_name: String) {
+            description("module does not support accounts")
+            display("module {} does not support accounts", module_name)
+        }
+
+        UnknownAccount(module_name: String, account: String) {
+            description("unknown account")
+            display("no account {} configured for module {}; please add section '[{}.accounts.{}]' to your configuration",
+                    account, module_name, module_name, account)
+        }
+
+        UnknownProfile(profile: String) {
+            description("unknown profile")
+            display("no profile {} configured; please add section '[profiles.{}]' to your configuration", profile, profile)
+        }
+
+        FlowNotSupported(module_name: String, flow: String) {
+            description("OAuth flow not supported by module")
+            display("module {} does not support OAuth flow '{}'", module_name, flow)
+        }
+
+        OutputFailed {
+            description("output failed")
+            display("output failed")
+        }
+
+        Auth(reason: String) {
+            description("authentication failed")
+            display("authentication failed: {}", reason)
+        }
+
+        Network(reason: String) {
+            description("network failure")
+            display("network failure: {}", reason)
+        }
+
+        Api(status: u16, code: Option<String>) {
+            description("API request failed")
+            display("API responded with status {}{}", status, code.as_ref().map(|c| format!(" and error code '{}'", c)).unwrap_or_default())
+        }
+
+        Parse(what: String) {
+            description("parsing failed")
+            display("failed to parse {}", what)
+        }
+
+        Config(reason: String) {
+            description("invalid configuration")
+            display("invalid configuration: {}", reason)
+        }
+
+        PartialFailure(succeeded: usize, failed: usize) {
+            description("some operations failed")
+            display("{} operation(s) succeeded, {} failed", succeeded, failed)
+        }
+    }
+}
+
+/// Coarse classification of errors. Exit codes, JSON error output, and retries are based on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Usage,
+    Auth,
+    Network,
+    Api,
+    Parse,
+    Config,
+    PartialFailure,
+    Timeout,
+    Other,
+}
+
+impl Category {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Category::Usage => "usage",
+            Category::Auth => "auth",
+            Category::Network => "network",
+            Category::Api => "api",
+            Category::Parse => "parse",
+            Category::Config => "config",
+            Category::PartialFailure => "partial_failure",
+            Category::Timeout => "timeout",
+            Category::Other => "other",
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match *self {
+            Category::Other => 1,
+            Category::Usage => 2,
+            Category::Config => 3,
+            Category::Auth => 4,
+            Category::Network => 5,
+            Category::Api => 6,
+            Category::Parse => 7,
+            Category::PartialFailure => 8,
+            Category::Timeout => 9,
+        }
+    }
+}
+
+impl ErrorKind {
+    pub fn category(&self) -> Option<Category> {
+        match *self {
+            ErrorKind::NoCommandSpecified | ErrorKind::NoSubcommandSpecified(_) | ErrorKind::ModuleNotIncluded(_) =>
+                Some(Category::Usage),
+            ErrorKind::AccountsNotSupported(_) | ErrorKind::UnknownAccount(_, _) | ErrorKind::UnknownProfile(_)
+            | ErrorKind::FlowNotSupported(_, _)
+            | ErrorKind::Config(_) => Some(Category::Config),
+            ErrorKind::Auth(_) => Some(Category::Auth),
+            ErrorKind::Api(401, _) | ErrorKind::Api(403, _) => Some(Category::Auth),
+            ErrorKind::Api(_, _) => Some(Category::Api),
+            ErrorKind::Network(_) => Some(Category::Network),
+            ErrorKind::Parse(_) => Some(Category::Parse),
+            ErrorKind::PartialFailure(_, _) => Some(Category::PartialFailure),
+            _ => None,
+        }
+    }
+}
+
+impl Error {
+    /// Returns the category of the innermost classified error of the chain, because the root cause is the most
+    /// specific, e.g., a network failure while refreshing an access token.
+    pub fn category(&self) -> Category {
+        cause_category(self).unwrap_or(Category::Other)
+    }
+
+    pub fn http_status(&self) -> Option<u16> {
+        self.api_error().map(|(status, _)| status)
+    }
+
+    pub fn provider_code(&self) -> Option<&str> {
+        self.api_error().and_then(|(_, code)| code)
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        match self.category() {
+            Category::Network | Category::Timeout => true,
+            _ => self.http_status().map(|status| status == 429 || status >= 500).unwrap_or(false),
+        }
+    }
+
+    /// Whether the service could not be reached at all, e.g., while offline, so the request may succeed later unchanged.
+    pub fn is_offline(&self) -> bool {
+        match self.category() {
+            Category::Network | Category::Timeout => true,
+            _ => false,
+        }
+    }
+
+    /// Suggests what to do about the error in human output.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self.category() {
+            Category::Auth => Some("the access token may have expired or been revoked; cf. 'rat auth status' and authenticate again"),
+            Category::Network => Some("please check your network connection and try again"),
+            Category::Timeout => Some("the provider did not respond in time; try again or raise the limit with '--timeout <secs>'"),
+            _ if self.http_status() == Some(429) => Some("the provider limits the rate of requests; please try again later"),
+            _ => None,
+        }
+    }
+
+    pub fn report(&self) -> ErrorReport {
+        ErrorReport {
+            category: self.category().name().to_string(),
+            message: self.to_string(),
+            causes: self.iter().skip(1).map(|e| e.to_string()).collect(),
+            http_status: self.http_status(),
+            code: self.provider_code().map(|c| c.to_string()),
+        }
+    }
+
+    fn api_error(&self) -> Option<(u16, Option<&str>)> {
+        let mut error = Some(self);
+        while let Some(e) = error {
+            if let ErrorKind::Api(status, ref code) = e.0 {
+                return Some((status, code.as_ref().map(|c| c.as_str())));
+            }
+            error = e.1.next_error.as_ref().and_then(|c| c.downcast_ref::<Error>());
+        }
+        None
+    }
+}
+
+/// Machine readable representation of an error for JSON output.
+#[derive(Serialize, Debug)]
+pub struct ErrorReport {
+    pub category: String,
+    pub message: String,
+    pub causes: Vec<String>,
+    pub http_status: Option<u16>,
+    pub code: Option<String>,
+}
+
+fn cause_category(cause: &(StdError + Send + 'static)) -> Option<Category> {
+    macro_rules! descend {
+        ($error:ty) => {
+            if let Some(e) = cause.downcast_ref::<$error>() {
+                return e.1.next_error.as_ref().and_then(|c| cause_category(&**c)).or_else(|| e.0.category());
+            }
+        }
+    }
+    descend!(Error);
+    descend!(credentials::Error);
+    descend!(encrypted::Error);
+    descend!(keyring::Error);
+    descend!(oauth2::Error);
+    descend!(basic_auth::Error);
+    descend!(http::Error);
+
+    match cause.downcast_ref::<hyper::Error>() {
+        Some(&hyper::Error::Io(ref e)) if http::is_timeout(e) => return Some(Category::Timeout),
+        Some(_) => return Some(Category::Network),
+        None => {}
+    }
+    if cause.downcast_ref::<io::Error>().map(http::is_timeout).unwrap_or(false) {
+        Some(Category::Timeout)
+    } else if cause.is::<serde_json::Error>() {
+        Some(Category::Parse)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_category_of_root_cause() {
+        let result: Result<()> = Err(ErrorKind::Api(503, None).into());
+        let error = result.chain_err(|| "failed to list articles")
+            .chain_err(|| ErrorKind::ModuleFailed("pocket".to_string()))
+            .unwrap_err();
+
+        assert_eq!(error.category(), Category::Api);
+        assert_eq!(error.category().exit_code(), 6);
+        assert_eq!(error.http_status(), Some(503));
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn test_category_of_unclassified_error() {
+        let result: Result<()> = Err("something went wrong".into());
+        let error = result.chain_err(|| ErrorKind::ModuleFailed("pocket".to_string())).unwrap_err();
+
+        assert_eq!(error.category(), Category::Other);
+        assert_eq!(error.http_status(), None);
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_timeout_is_distinct_from_network() {
+        let timeout: Result<()> = Err(hyper::Error::Io(io::Error::new(io::ErrorKind::TimedOut, "connect timed out")))
+            .chain_err(|| "failed to list articles");
+        let reset: Result<()> = Err(hyper::Error::Io(io::Error::new(io::ErrorKind::ConnectionReset, "reset")))
+            .chain_err(|| "failed to list articles");
+
+        assert_eq!(timeout.unwrap_err().category(), Category::Timeout);
+        assert_eq!(reset.unwrap_err().category(), Category::Network);
+    }
+
+    #[test]
+    fn test_unauthorized_is_auth() {
+        let error: Error = ErrorKind::Api(401, Some("107".to_string())).into();
+
+        assert_eq!(error.category(), Category::Auth);
+        assert_eq!(error.provider_code(), Some("107"));
+        assert!(error.hint().is_some());
+    }
+
+    #[test]
+    fn test_provider_message_is_cause_of_api_error() {
+        let result: Result<()> = Err(Error::from("Too many requests"));
+        let error = result.chain_err(|| ErrorKind::Api(429, None)).unwrap_err();
+
+        assert_eq!(error.category(), Category::Api);
+        assert_eq!(error.http_status(), Some(429));
+        assert_eq!(error.report().causes, vec!["Too many requests".to_string()]);
+        assert!(error.hint().unwrap().contains("rate"));
+    }
+}
@@ -0,0 +1,32 @@
+#![recursion_limit = "1024"]
+
+extern crate base64;
+extern crate crypto;
+#[macro_use] extern crate error_chain;
+extern crate humantime;
+#[cfg_attr(feature = "pocket", macro_use)] extern crate hyper;
+extern crate hyper_native_tls;
+#[macro_use] extern crate mime;
+#[cfg(feature = "centerdevice")] extern crate mime_multipart;
+extern crate rand;
+#[cfg(feature = "stocks")] extern crate select;
+#[macro_use] extern crate serde_derive;
+extern crate serde;
+extern crate serde_json;
+extern crate serde_urlencoded;
+extern crate toml;
+extern crate webbrowser;
+
+#[cfg(test)] #[macro_use] extern crate pretty_assertions;
+#[cfg(test)] #[macro_use] extern crate proptest;
+
+pub mod cache;
+pub mod clients;
+pub mod config;
+pub mod credentials;
+pub mod errors;
+pub mod net;
+pub mod utils;
+
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
@@ -0,0 +1,129 @@
+/*
+ * How a client authenticates its requests. Services differ: Pocket and Pinboard expect the credentials as parameters
+ * of the query or the JSON body, GitHub, Slack, and wallabag expect a bearer token, and Instapaper expects every request
+ * to be signed by OAuth 1.0a. A client picks the implementation of its service and lets it authorize each request, so
+ * request structs only carry what the request is about.
+ */
+use net::oauth::oauth1::{self, Secrets};
+
+use hyper::header::{Authorization, Bearer, Headers};
+use serde_json::Value;
+
+pub trait Auth {
+    /// Parameters to add to the query, form, or JSON body of a request, e.g., an API token.
+    fn params(&self) -> Vec<(&str, &str)> {
+        Vec::new()
+    }
+
+    /// Headers to add to a request with `method` to `url`; `params` are its query or form parameters without the ones
+    /// of `params()`, because a signature may cover them.
+    fn headers(&self, _method: &str, _url: &str, _params: &[(&str, &str)]) -> Headers {
+        Headers::new()
+    }
+
+    /// Adds the parameters to a JSON object to be sent as body.
+    fn inject(&self, body: &mut Value) {
+        if let Value::Object(ref mut map) = *body {
+            for (name, value) in self.params() {
+                map.insert(name.to_string(), Value::from(value));
+            }
+        }
+    }
+}
+
+/// Credentials sent as parameters, e.g., Pocket's consumer key and access token.
+#[derive(Debug, Clone)]
+pub struct ParamAuth {
+    params: Vec<(String, String)>,
+}
+
+impl ParamAuth {
+    pub fn new(params: &[(&str, &str)]) -> Self {
+        ParamAuth { params: params.iter().map(|&(name, value)| (name.to_string(), value.to_string())).collect() }
+    }
+}
+
+impl Auth for ParamAuth {
+    fn params(&self) -> Vec<(&str, &str)> {
+        self.params.iter().map(|&(ref name, ref value)| (name.as_str(), value.as_str())).collect()
+    }
+}
+
+/// An access token sent as 'Authorization: Bearer <token>'.
+#[derive(Debug, Clone)]
+pub struct BearerAuth {
+    token: String,
+}
+
+impl BearerAuth {
+    pub fn new<T: Into<String>>(token: T) -> Self {
+        BearerAuth { token: token.into() }
+    }
+}
+
+impl Auth for BearerAuth {
+    fn headers(&self, _method: &str, _url: &str, _params: &[(&str, &str)]) -> Headers {
+        let mut headers = Headers::new();
+        headers.set(Authorization(Bearer { token: self.token.clone() }));
+        headers
+    }
+}
+
+/// Signs requests by OAuth 1.0a as the consumer and, once obtained, with the access token; cf. `oauth1`.
+#[derive(Debug, Clone)]
+pub struct OAuth1Auth {
+    consumer_key: String,
+    consumer_secret: String,
+    token: Option<(String, String)>,
+}
+
+impl OAuth1Auth {
+    pub fn new(consumer_key: &str, consumer_secret: &str) -> Self {
+        OAuth1Auth { consumer_key: consumer_key.to_string(), consumer_secret: consumer_secret.to_string(), token: None }
+    }
+
+    pub fn with_token(mut self, token: &str, token_secret: &str) -> Self {
+        self.token = Some((token.to_string(), token_secret.to_string()));
+        self
+    }
+}
+
+impl Auth for OAuth1Auth {
+    fn headers(&self, method: &str, url: &str, params: &[(&str, &str)]) -> Headers {
+        let consumer = Secrets { key: &self.consumer_key, secret: &self.consumer_secret };
+        let token = self.token.as_ref().map(|&(ref key, ref secret)| Secrets { key: key, secret: secret });
+        let mut headers = Headers::new();
+        headers.set(Authorization(oauth1::authorization(&consumer, token.as_ref(), method, url, params)));
+        headers
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn test_param_auth_injects_params() {
+        let auth = ParamAuth::new(&[("consumer_key", "key"), ("access_token", "token")]);
+        let mut body: Value = serde_json::from_str(r#"{"url":"https://example.com"}"#).unwrap();
+
+        auth.inject(&mut body);
+
+        assert_eq!(serde_json::to_string(&body).unwrap(), r#"{"access_token":"token","consumer_key":"key","url":"https://example.com"}"#);
+        assert_eq!(auth.headers("POST", "https://example.com", &[]).len(), 0);
+    }
+
+    #[test]
+    fn test_header_auths() {
+        let bearer = BearerAuth::new("abc123").headers("GET", "https://example.com", &[]);
+        let oauth1 = OAuth1Auth::new("key", "secret").with_token("token", "token secret")
+            .headers("POST", "https://example.com", &[("bookmark_id", "1")]);
+
+        assert_eq!(bearer.get_raw("Authorization").unwrap()[0], b"Bearer abc123".to_vec());
+        let oauth1 = String::from_utf8(oauth1.get_raw("Authorization").unwrap()[0].clone()).unwrap();
+        assert!(oauth1.starts_with("OAuth oauth_consumer_key=\"key\", "));
+        assert!(oauth1.contains("oauth_token=\"token\""));
+        assert!(BearerAuth::new("abc123").params().is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+use config::Config;
+use credentials;
+use credentials::Credential;
+use errors::Category;
+use utils::console::*;
+
+use hyper::client::Response;
+use hyper::header::{Authorization, Basic, Headers};
+use hyper::status::StatusCode;
+
+error_chain! {
+    links {
+        Credentials(credentials::Error, credentials::ErrorKind);
+    }
+
+    errors {
+        NoUsername(module_name: String) {
+            description("no user name available")
+            display("no user name for module {} available; please run 'rat auth login {}'", module_name, module_name)
+        }
+    }
+}
+
+impl ErrorKind {
+    pub fn category(&self) -> Option<Category> {
+        match *self {
+            ErrorKind::Credentials(ref kind) => kind.category(),
+            ErrorKind::NoUsername(_) => Some(Category::Auth),
+            _ => None,
+        }
+    }
+}
+
+/// Asks for user name and password of `module_name` and stores them in the credential store. Accounts with two-factor
+/// authentication usually require an app-specific password instead of the account password.
+pub fn login(config: &Config, module_name: &str, username: Option<&str>) -> Result<Credential> {
+    let username = match username {
+        Some(username) => username.to_string(),
+        None => read_line("User name: ").chain_err(|| "Failed to read user name")?,
+    };
+    msgln("If two-factor authentication is enabled for your account, please create and enter an app-specific password.");
+    let password = read_secret("Password: ").chain_err(|| "Failed to read password")?;
+
+    let mut credential = Credential::new(password);
+    credential.username = Some(username);
+    credentials::store(config, module_name, &credential)?;
+
+    Ok(credential)
+}
+
+/// Authorizes requests with user name and password of a module from the credential store. If the service demands a
+/// one-time password, i.e., it rejects a request with 401 Unauthorized and sets the `otp_header` to 'required', the
+/// session asks for the current TOTP code and retries the request once with the code.
+#[derive(Debug)]
+pub struct BasicAuthSession<'a> {
+    config: &'a Config,
+    module_name: String,
+    username: String,
+    password: String,
+    otp_header: Option<String>,
+}
+
+impl<'a> BasicAuthSession<'a> {
+    pub fn new(config: &'a Config, module_name: &str) -> Result<Self> {
+        let credential = credentials::load(config, module_name)
+            .chain_err(|| credentials::ErrorKind::NoAccessToken(module_name.to_string()))?;
+        let username = credential.username.ok_or_else(|| ErrorKind::NoUsername(module_name.to_string()))?;
+
+        Ok(BasicAuthSession {
+            config: config,
+            module_name: module_name.to_string(),
+            username: username,
+            password: credential.access_token,
+            otp_header: None,
+        })
+    }
+
+    /// Sets the header a service uses to demand and receive one-time passwords, e.g., 'X-GitHub-OTP'.
+    pub fn with_otp_header(mut self, otp_header: &str) -> Self {
+        self.otp_header = Some(otp_header.to_string());
+        self
+    }
+
+    pub fn authorization(&self) -> Authorization<Basic> {
+        Authorization(Basic { username: self.username.clone(), password: Some(self.password.clone()) })
+    }
+
+    /// Calls `request` with the authorization header and, if demanded, the name and value of the one-time password header.
+    pub fn send<F, E>(&self, mut request: F) -> Result<Response>
+        where F: FnMut(Authorization<Basic>, Option<(&str, &str)>) -> ::std::result::Result<Response, E>,
+              E: ::std::error::Error + Send + 'static {
+        let response = request(self.authorization(), None).chain_err(|| "Failed to finish HTTP request")?;
+        credentials::record_usage(self.config, &self.module_name);
+        let otp_header = match self.otp_header {
+            Some(ref otp_header) if response.status == StatusCode::Unauthorized && otp_required(&response.headers, otp_header) => otp_header,
+            _ => return Ok(response),
+        };
+
+        info(format!("Module {} requires a one-time password.", self.module_name));
+        let code = read_line("One-time password: ").chain_err(|| "Failed to read one-time password")?;
+        request(self.authorization(), Some((otp_header, code.trim()))).chain_err(|| "Failed to finish HTTP request")
+    }
+}
+
+fn otp_required(headers: &Headers, otp_header: &str) -> bool {
+    headers.get_raw(otp_header)
+        .map(|values| values.iter().any(|v| String::from_utf8_lossy(v).to_lowercase().starts_with("required")))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_otp_required() {
+        let mut headers = Headers::new();
+        assert!(!otp_required(&headers, "X-GitHub-OTP"));
+
+        headers.set_raw("X-GitHub-OTP", vec![b"required; app".to_vec()]);
+        assert!(otp_required(&headers, "X-GitHub-OTP"));
+        assert!(otp_required(&headers, "x-github-otp"));
+    }
+}
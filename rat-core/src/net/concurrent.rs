@@ -0,0 +1,240 @@
+//! Bounded concurrency for batch operations. The HTTP clients block, so a batch runs on a small pool of worker threads
+//! instead of one request after the other. The pool is bounded, so a batch of hundreds of items neither exhausts file
+//! descriptors nor trips the providers' rate limits.
+//!
+//! Batches with several steps of different kinds, e.g., downloading and then parsing web pages, use a `Pipeline` with
+//! a pool per step.
+
+use std::cmp;
+use std::panic;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, JoinHandle};
+
+/// Number of requests in flight unless configured otherwise by `general.max_concurrency`.
+pub const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// Finished items a stage may buffer per worker of the next stage before it has to wait.
+const BUFFER_PER_WORKER: usize = 2;
+
+/// Applies `f` to all items using at most `limit` threads and returns the results in the order of the items.
+pub fn map_bounded<T, R, F>(items: Vec<T>, limit: usize, f: F) -> Vec<R>
+    where T: Send + 'static, R: Send + 'static, F: Fn(T) -> R + Send + Sync + 'static
+{
+    let count = items.len();
+    let workers = cmp::min(cmp::max(limit, 1), count);
+    if workers <= 1 {
+        return items.into_iter().map(f).collect();
+    }
+
+    let queue = Arc::new(Mutex::new(items.into_iter().enumerate()));
+    let f = Arc::new(f);
+    let (tx, rx) = mpsc::channel();
+    let handles: Vec<_> = (0..workers).map(|_| {
+        let queue = queue.clone();
+        let f = f.clone();
+        let tx = tx.clone();
+        thread::spawn(move || {
+            loop {
+                // The lock is released before `f` runs, so workers only serialize on taking the next item
+                let next = queue.lock().map(|mut queue| queue.next()).unwrap_or(None);
+                match next {
+                    Some((index, item)) => if tx.send((index, f(item))).is_err() { break },
+                    None => break,
+                }
+            }
+        })
+    }).collect();
+    drop(tx);
+
+    let mut results: Vec<(usize, R)> = rx.iter().collect();
+    for handle in handles {
+        // A panicking `f` surfaces here instead of silently dropping its item
+        if let Err(e) = handle.join() {
+            panic::resume_unwind(e);
+        }
+    }
+    results.sort_by_key(|&(index, _)| index);
+
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Receives the name of a stage and its number of finished items.
+pub type Progress = Arc<Fn(&'static str, usize) + Send + Sync>;
+
+/// Items flowing through stages, each with its own pool of worker threads, e.g., a pool for network-bound downloads
+/// followed by a pool for CPU-bound parsing. Stages are connected by bounded channels: a slow stage holds back the
+/// stages before it instead of letting their results pile up in memory.
+///
+/// Stages map every item, so failures are passed on as items, e.g., as `Result`s.
+pub struct Pipeline<T> {
+    rx: Receiver<(usize, T)>,
+    handles: Vec<JoinHandle<()>>,
+    progress: Option<Progress>,
+}
+
+impl<T: Send + 'static> Pipeline<T> {
+    pub fn new(items: Vec<T>) -> Self {
+        let (tx, rx) = mpsc::sync_channel(BUFFER_PER_WORKER);
+        let handle = thread::spawn(move || {
+            for item in items.into_iter().enumerate() {
+                if tx.send(item).is_err() { break }
+            }
+        });
+
+        Pipeline { rx: rx, handles: vec![handle], progress: None }
+    }
+
+    /// Reports the progress of all stages added afterwards.
+    pub fn with_progress<F>(mut self, progress: F) -> Self where F: Fn(&'static str, usize) + Send + Sync + 'static {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    /// Adds a stage applying `f` to all items using `workers` threads.
+    pub fn stage<U, F>(self, name: &'static str, workers: usize, f: F) -> Pipeline<U>
+        where U: Send + 'static, F: Fn(T) -> U + Send + Sync + 'static
+    {
+        let workers = cmp::max(workers, 1);
+        let (tx, rx) = mpsc::sync_channel(workers * BUFFER_PER_WORKER);
+        let input = Arc::new(Mutex::new(self.rx));
+        let f = Arc::new(f);
+        let finished = Arc::new(AtomicUsize::new(0));
+        let mut handles = self.handles;
+        for _ in 0..workers {
+            let input = input.clone();
+            let f = f.clone();
+            let finished = finished.clone();
+            let progress = self.progress.clone();
+            let tx = tx.clone();
+            handles.push(thread::spawn(move || {
+                loop {
+                    let next = input.lock().ok().and_then(|input| input.recv().ok());
+                    let (index, item) = match next {
+                        Some(next) => next,
+                        None => break,
+                    };
+                    let result = f(item);
+                    let finished = finished.fetch_add(1, Ordering::SeqCst) + 1;
+                    if let Some(ref progress) = progress {
+                        progress(name, finished);
+                    }
+                    if tx.send((index, result)).is_err() { break }
+                }
+            }));
+        }
+
+        Pipeline { rx: rx, handles: handles, progress: self.progress }
+    }
+
+    /// Runs the pipeline to completion and returns the results in the order of the items.
+    pub fn collect(self) -> Vec<T> {
+        let mut results: Vec<(usize, T)> = self.rx.iter().collect();
+        for handle in self.handles {
+            if let Err(e) = handle.join() {
+                panic::resume_unwind(e);
+            }
+        }
+        results.sort_by_key(|&(index, _)| index);
+
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::time::Duration;
+
+    #[test]
+    fn test_map_bounded_keeps_order() {
+        let items: Vec<u64> = (0..20).collect();
+
+        let results = map_bounded(items, 4, |i| {
+            thread::sleep(Duration::from_millis(20 - i));
+            i * 2
+        });
+
+        assert_eq!(results, (0..20).map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_map_bounded_respects_limit() {
+        // (running, max running)
+        let counts = Arc::new(Mutex::new((0, 0)));
+        let c = counts.clone();
+
+        map_bounded((0..12).collect(), 3, move |_: u32| {
+            {
+                let mut counts = c.lock().unwrap();
+                counts.0 += 1;
+                counts.1 = cmp::max(counts.0, counts.1);
+            }
+            thread::sleep(Duration::from_millis(10));
+            c.lock().unwrap().0 -= 1;
+        });
+
+        let max_running = counts.lock().unwrap().1;
+        assert!(max_running <= 3);
+        assert!(max_running > 1);
+    }
+
+    #[test]
+    fn test_map_bounded_empty() {
+        let results: Vec<u32> = map_bounded(Vec::new(), 4, |i: u32| i);
+
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_pipeline_keeps_order() {
+        let items: Vec<u64> = (0..20).collect();
+
+        let results = Pipeline::new(items)
+            .stage("sleep", 4, |i| { thread::sleep(Duration::from_millis(20 - i)); i })
+            .stage("double", 2, |i| i * 2)
+            .stage("format", 3, |i| i.to_string())
+            .collect();
+
+        assert_eq!(results, (0..20).map(|i| (i * 2).to_string()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_pipeline_reports_progress() {
+        let finished = Arc::new(Mutex::new(Vec::new()));
+        let f = finished.clone();
+
+        Pipeline::new((0..5).collect())
+            .with_progress(move |stage, count| f.lock().unwrap().push((stage, count)))
+            .stage("first", 2, |i: u32| i)
+            .stage("second", 1, |i| i)
+            .collect();
+
+        let finished = finished.lock().unwrap();
+        assert_eq!(finished.iter().filter(|&&(stage, _)| stage == "first").map(|&(_, c)| c).max(), Some(5));
+        assert_eq!(finished.iter().filter(|&&(stage, _)| stage == "second").map(|&(_, c)| c).max(), Some(5));
+    }
+
+    #[test]
+    fn test_pipeline_applies_backpressure() {
+        // (finished by fast stage, finished by slow stage, max difference)
+        let counts = Arc::new(Mutex::new((0, 0, 0)));
+        let (fast, slow) = (counts.clone(), counts.clone());
+
+        Pipeline::new((0..30).collect())
+            .stage("fast", 1, move |i: u32| { fast.lock().unwrap().0 += 1; i })
+            .stage("slow", 1, move |i| {
+                thread::sleep(Duration::from_millis(5));
+                let mut counts = slow.lock().unwrap();
+                counts.1 += 1;
+                counts.2 = cmp::max(counts.2, counts.0 - counts.1);
+                i
+            })
+            .collect();
+
+        let max_ahead = counts.lock().unwrap().2;
+        assert!(max_ahead <= 1 + BUFFER_PER_WORKER + 1, "fast stage was {} items ahead", max_ahead);
+    }
+}
@@ -0,0 +1,194 @@
+//! HTTP clients answering from memory instead of the network, for tests of clients and modules. Install a
+//! `MockClient` with `http::with_client`, queue its responses, and check the requests it received afterwards:
+//!
+//! ```rust,ignore
+//! let mock = Rc::new(MockClient::new().respond(200, &[], r#"{"status":1}"#));
+//! let result = http::with_client(mock.clone(), || client::send(&config, &actions));
+//! assert!(mock.requests()[0].starts_with("GET /v3/send?"));
+//! ```
+
+use net::http::{self, HttpClient};
+
+use hyper;
+use hyper::Client;
+use hyper::net::{NetworkConnector, NetworkStream};
+use hyper::status::StatusCode;
+use std::collections::VecDeque;
+use std::io::{self, Cursor, Read, Write};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug)]
+enum Exchange {
+    Respond(Vec<u8>),
+    Fail(io::ErrorKind),
+}
+
+#[derive(Debug, Default)]
+struct Exchanges {
+    queued: VecDeque<Exchange>,
+    requests: Vec<Vec<u8>>,
+}
+
+/// Answers every connection with the next queued exchange, regardless of host and scheme.
+#[derive(Debug, Default, Clone)]
+pub struct MockClient {
+    exchanges: Arc<Mutex<Exchanges>>,
+}
+
+impl MockClient {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Queues a response with status, headers, and body.
+    pub fn respond(self, status: u16, headers: &[(&str, &str)], body: &str) -> Self {
+        let status = StatusCode::from_u16(status);
+        let mut response = format!("HTTP/1.1 {} {}\r\n", status.to_u16(), status.canonical_reason().unwrap_or("Unknown"));
+        for &(name, value) in headers {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        response.push_str(&format!("Content-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body));
+        self.queue(Exchange::Respond(response.into_bytes()))
+    }
+
+    /// Queues a connection that fails, e.g., with `io::ErrorKind::ConnectionReset`.
+    pub fn fail(self, kind: io::ErrorKind) -> Self {
+        self.queue(Exchange::Fail(kind))
+    }
+
+    /// The requests received so far as sent, i.e., request line, headers, and body.
+    pub fn requests(&self) -> Vec<String> {
+        self.exchanges.lock().unwrap().requests.iter().map(|r| String::from_utf8_lossy(r).to_string()).collect()
+    }
+
+    fn queue(self, exchange: Exchange) -> Self {
+        self.exchanges.lock().unwrap().queued.push_back(exchange);
+        self
+    }
+}
+
+impl HttpClient for MockClient {
+    fn client(&self) -> http::Result<Client> {
+        Ok(Client::with_connector(MockConnector { exchanges: self.exchanges.clone() }))
+    }
+}
+
+struct MockConnector {
+    exchanges: Arc<Mutex<Exchanges>>,
+}
+
+impl NetworkConnector for MockConnector {
+    type Stream = MockStream;
+
+    fn connect(&self, host: &str, port: u16, scheme: &str) -> hyper::Result<MockStream> {
+        let mut exchanges = self.exchanges.lock().unwrap();
+        exchanges.requests.push(Vec::new());
+        let request = exchanges.requests.len() - 1;
+        match exchanges.queued.pop_front() {
+            Some(Exchange::Respond(response)) =>
+                Ok(MockStream { response: Cursor::new(response), exchanges: self.exchanges.clone(), request: request }),
+            Some(Exchange::Fail(kind)) => Err(hyper::Error::Io(io::Error::new(kind, "mock connection failed"))),
+            None => Err(hyper::Error::Io(io::Error::new(io::ErrorKind::ConnectionRefused,
+                                                        format!("no response queued for {}://{}:{}", scheme, host, port)))),
+        }
+    }
+}
+
+struct MockStream {
+    response: Cursor<Vec<u8>>,
+    exchanges: Arc<Mutex<Exchanges>>,
+    request: usize,
+}
+
+impl Read for MockStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.response.read(buf)
+    }
+}
+
+impl Write for MockStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.exchanges.lock().unwrap().requests[self.request].extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl NetworkStream for MockStream {
+    fn peer_addr(&mut self) -> io::Result<SocketAddr> {
+        Ok(SocketAddr::from(([127, 0, 0, 1], 80)))
+    }
+
+    fn set_read_timeout(&self, _: Option<Duration>) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_write_timeout(&self, _: Option<Duration>) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use net::http::{check_status, tls_client, with_client};
+    use net::retry::{self, RetryPolicy};
+
+    use std::rc::Rc;
+
+    #[test]
+    fn test_mock_client_answers_and_records_requests() {
+        let mock = Rc::new(MockClient::new().respond(200, &[("Content-Type", "application/json")], r#"{"status":1}"#));
+
+        let body = with_client(mock.clone(), || {
+            let mut response = tls_client().unwrap().post("https://example.com/v3/get").body("{}").send().unwrap();
+            let mut body = String::new();
+            response.read_to_string(&mut body).unwrap();
+            body
+        });
+
+        assert_eq!(body, r#"{"status":1}"#);
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("POST /v3/get HTTP/1.1\r\n"));
+        assert!(requests[0].ends_with("\r\n\r\n{}"));
+    }
+
+    #[test]
+    fn test_retry_with_mock_client() {
+        ::utils::console::init(::config::Verbosity::QUIET);
+        let mock = Rc::new(MockClient::new()
+            .fail(io::ErrorKind::ConnectionReset)
+            .respond(503, &[], "")
+            .respond(200, &[], "ok"));
+        let policy = RetryPolicy { max_attempts: 3, base_delay: Duration::from_millis(1), jitter: false };
+
+        let status = with_client(mock.clone(), || {
+            let client = tls_client().unwrap();
+            let mut response = retry::send(&policy, || client.get("https://example.com/").send()).unwrap();
+            check_status(&mut response).map(|_| response.status)
+        });
+
+        assert_eq!(status.unwrap(), StatusCode::Ok);
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[test]
+    fn test_previous_client_is_restored() {
+        let outer = Rc::new(MockClient::new().respond(200, &[], ""));
+        let inner = Rc::new(MockClient::new());
+
+        with_client(outer.clone(), || {
+            with_client(inner.clone(), || ());
+            tls_client().unwrap().get("https://example.com/").send().unwrap();
+        });
+
+        assert_eq!(outer.requests().len(), 1);
+        assert!(inner.requests().is_empty());
+    }
+}
@@ -0,0 +1,17 @@
+pub mod oauth;
+
+pub mod http;
+
+pub mod mock;
+
+pub mod redirect;
+
+pub mod auth;
+
+pub mod basic_auth;
+
+pub mod concurrent;
+
+pub mod retry;
+
+pub mod trace;
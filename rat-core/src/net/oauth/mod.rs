@@ -1,4 +1,4 @@
-use config::{Config, OutputFormat};
+use config::Config;
 use net::http::tls_client;
 use utils::console::*;
 
@@ -13,6 +13,9 @@ use std::str;
 use webbrowser;
 
 
+pub mod oauth1;
+pub mod oauth2;
+
 error_chain! {
 }
 
@@ -145,7 +148,7 @@ impl CodeWithBasicAuthScheme {
         let mut buffer = Vec::new();
         response.read_to_end(&mut buffer).chain_err(|| "Failed to read HTTP response")?;
 
-        if config.general.output_format == OutputFormat::JSON {
+        if config.general.output_format.is_json() {
             info("Received response: ");
             msgln(str::from_utf8(&buffer).chain_err(|| "Failed to print buffer")?);
         }
@@ -210,7 +213,7 @@ impl CodeWithUrlScheme {
         let mut buffer = Vec::new();
         response.read_to_end(&mut buffer).chain_err(|| "Failed to read HTTP response")?;
 
-        if config.general.output_format == OutputFormat::JSON {
+        if config.general.output_format.is_json() {
             info("Received response: ");
             msgln(str::from_utf8(&buffer).chain_err(|| "Failed to print buffer")?);
         }
@@ -218,4 +221,4 @@ impl CodeWithUrlScheme {
 
         Ok(token)
     }
-}
\ No newline at end of file
+}
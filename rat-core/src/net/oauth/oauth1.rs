@@ -0,0 +1,102 @@
+/*
+ * OAuth 1.0a request signing with HMAC-SHA1; cf. https://tools.ietf.org/html/rfc5849. Services like Instapaper still
+ * require it. Requests are signed by the consumer, i.e., the registered app, and -- once obtained -- the access token.
+ */
+use utils::time::unix_now;
+
+use base64;
+use crypto::hmac::Hmac;
+use crypto::mac::Mac;
+use crypto::sha1::Sha1;
+use rand::{self, Rng};
+
+/// Key and secret of a consumer or an access token
+#[derive(Debug)]
+pub struct Secrets<'a> {
+    pub key: &'a str,
+    pub secret: &'a str,
+}
+
+/// Value of the 'Authorization' header of a request; `params` are the query or form parameters of the request.
+pub fn authorization(consumer: &Secrets, token: Option<&Secrets>, method: &str, url: &str, params: &[(&str, &str)]) -> String {
+    let nonce: String = rand::thread_rng().gen_ascii_chars().take(32).collect();
+    authorization_with(consumer, token, method, url, params, &nonce, unix_now())
+}
+
+fn authorization_with(consumer: &Secrets, token: Option<&Secrets>, method: &str, url: &str, params: &[(&str, &str)],
+                      nonce: &str, timestamp: u64) -> String {
+    let timestamp = timestamp.to_string();
+    let mut oauth_params = vec![
+        ("oauth_consumer_key", consumer.key),
+        ("oauth_nonce", nonce),
+        ("oauth_signature_method", "HMAC-SHA1"),
+        ("oauth_timestamp", timestamp.as_str()),
+        ("oauth_version", "1.0"),
+    ];
+    if let Some(token) = token {
+        oauth_params.push(("oauth_token", token.key));
+    }
+
+    let mut all_params = oauth_params.clone();
+    all_params.extend_from_slice(params);
+    let signature = signature(consumer.secret, token.map(|t| t.secret).unwrap_or(""), method, url, &all_params);
+    oauth_params.push(("oauth_signature", signature.as_str()));
+
+    let fields: Vec<String> = oauth_params.iter()
+        .map(|&(name, value)| format!("{}=\"{}\"", percent_encode(name), percent_encode(value)))
+        .collect();
+    format!("OAuth {}", fields.join(", "))
+}
+
+/// Signature of the request in base64; cf. RFC 5849, section 3.4.
+fn signature(consumer_secret: &str, token_secret: &str, method: &str, url: &str, params: &[(&str, &str)]) -> String {
+    let mut encoded: Vec<(String, String)> = params.iter().map(|&(name, value)| (percent_encode(name), percent_encode(value))).collect();
+    encoded.sort();
+    let normalized: Vec<String> = encoded.iter().map(|&(ref name, ref value)| format!("{}={}", name, value)).collect();
+    let base = format!("{}&{}&{}", method.to_uppercase(), percent_encode(url), percent_encode(&normalized.join("&")));
+    let key = format!("{}&{}", percent_encode(consumer_secret), percent_encode(token_secret));
+
+    let mut hmac = Hmac::new(Sha1::new(), key.as_bytes());
+    hmac.input(base.as_bytes());
+    base64::encode(hmac.result().code())
+}
+
+/// Encodes all but the unreserved characters of RFC 3986, as demanded by RFC 5849, section 3.6.
+pub fn percent_encode(s: &str) -> String {
+    s.bytes().map(|b| match b as char {
+        c if c.is_digit(36) || "-._~".contains(c) => c.to_string(),
+        _ => format!("%{:02X}", b),
+    }).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Example of https://developer.twitter.com/en/docs/authentication/oauth-1-0a/creating-a-signature
+    static CONSUMER: Secrets<'static> = Secrets { key: "xvz1evFS4wEEPTGEFPHBog", secret: "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw" };
+    static TOKEN: Secrets<'static> = Secrets {
+        key: "370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb",
+        secret: "LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE",
+    };
+
+    #[test]
+    fn test_authorization() {
+        let params = [("include_entities", "true"), ("status", "Hello Ladies + Gentlemen, a signed OAuth request!")];
+
+        let authorization = authorization_with(&CONSUMER, Some(&TOKEN), "POST", "https://api.twitter.com/1.1/statuses/update.json",
+                                               &params, "kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg", 1318622958);
+
+        assert!(authorization.starts_with("OAuth oauth_consumer_key=\"xvz1evFS4wEEPTGEFPHBog\", "));
+        assert!(authorization.ends_with(", oauth_signature=\"hCtSmYh%2BiHYCEqBWrE7C7hYmtUk%3D\""));
+    }
+
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(percent_encode("Ladies + Gentlemen"), "Ladies%20%2B%20Gentlemen");
+        assert_eq!(percent_encode("An encoded string!"), "An%20encoded%20string%21");
+        assert_eq!(percent_encode("Dogs, Cats & Mice"), "Dogs%2C%20Cats%20%26%20Mice");
+        assert_eq!(percent_encode("☃"), "%E2%98%83");
+        assert_eq!(percent_encode("a-b.c_d~e"), "a-b.c_d~e");
+    }
+}
@@ -0,0 +1,624 @@
+/*
+ * Generic OAuth 2.0 engine. Modules register their OAuth client in the configuration, section '[oauth.<module>]',
+ * and share the flows below as well as the token storage in the credential store.
+ */
+use config::Config;
+use credentials;
+use credentials::Credential;
+use errors::Category;
+use net::http::tls_client;
+use net::redirect::{self, RedirectListener};
+use net::retry;
+use utils::console::*;
+use utils::time::unix_now;
+
+use base64;
+use crypto::digest::Digest;
+use crypto::sha2::Sha256;
+use hyper;
+use hyper::client::Response;
+use hyper::header::{ContentType, Accept, Authorization, Basic, qitem};
+use hyper::status::StatusCode;
+use rand::{OsRng, Rng};
+use serde::Deserialize;
+use serde_json;
+use serde_urlencoded;
+use std::cell::{Ref, RefCell};
+use std::io::Read;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+use webbrowser;
+
+static DEVICE_CODE_GRANT_TYPE: &'static str = "urn:ietf:params:oauth:grant-type:device_code";
+
+error_chain! {
+    links {
+        Credentials(credentials::Error, credentials::ErrorKind);
+    }
+
+    errors {
+        NoClientRegistered(module_name: String) {
+            description("no OAuth client registered")
+            display("no OAuth client registered for module {}; please add section '[oauth.{}]' to your configuration", module_name, module_name)
+        }
+
+        AuthorizationFailed(error: String, error_description: String) {
+            description("OAuth authorization failed")
+            display("OAuth authorization failed with '{}': {}", error, error_description)
+        }
+
+        FlowNotSupported(flow: String) {
+            description("OAuth flow not supported")
+            display("OAuth flow '{}' is not supported by the registered client", flow)
+        }
+
+        UnknownFlow(flow: String) {
+            description("unknown OAuth flow")
+            display("unknown OAuth flow '{}'; valid flows are {}", flow, FLOWS.iter().map(|f| format!("'{}'", f.name())).collect::<Vec<_>>().join(", "))
+        }
+    }
+}
+
+impl ErrorKind {
+    pub fn category(&self) -> Option<Category> {
+        match *self {
+            ErrorKind::Credentials(ref kind) => kind.category(),
+            ErrorKind::NoClientRegistered(_) | ErrorKind::FlowNotSupported(_) | ErrorKind::UnknownFlow(_) =>
+                Some(Category::Config),
+            ErrorKind::AuthorizationFailed(_, _) => Some(Category::Auth),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuth2Client {
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub auth_endpoint: String,
+    pub token_endpoint: String,
+    pub device_endpoint: Option<String>,
+    pub revocation_endpoint: Option<String>,
+    pub scope: Option<String>,
+    /// Scope requested by `rat auth login --read-only`
+    pub read_only_scope: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OAuth2Token {
+    pub access_token: String,
+    pub token_type: Option<String>,
+    pub expires_in: Option<u64>,
+    pub refresh_token: Option<String>,
+    pub scope: Option<String>,
+}
+
+impl OAuth2Token {
+    pub fn into_credential(self) -> Credential {
+        let mut credential = Credential::new(self.access_token);
+        credential.refresh_token = self.refresh_token;
+        credential.expires_at = self.expires_in.map(|expires_in| unix_now() + expires_in);
+        credential.scope = self.scope;
+
+        credential
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OAuth2ErrorResponse {
+    error: String,
+    error_description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceAuthorization {
+    device_code: String,
+    user_code: String,
+    verification_uri: Option<String>,
+    // Google calls it verification_url
+    verification_url: Option<String>,
+    verification_uri_complete: Option<String>,
+    expires_in: u64,
+    interval: Option<u64>,
+}
+
+impl DeviceAuthorization {
+    fn verification_uri(&self) -> Option<&String> {
+        self.verification_uri.as_ref().or_else(|| self.verification_url.as_ref())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Flow {
+    AuthorizationCode,
+    DeviceCode,
+    ClientCredentials,
+}
+
+static FLOWS: &'static [Flow] = &[Flow::AuthorizationCode, Flow::DeviceCode, Flow::ClientCredentials];
+
+impl Flow {
+    /// The name accepted by `FromStr` and `rat auth login --flow`
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Flow::AuthorizationCode => "authorization_code",
+            Flow::DeviceCode => "device",
+            Flow::ClientCredentials => "client_credentials",
+        }
+    }
+}
+
+impl FromStr for Flow {
+    type Err = Error;
+
+    fn from_str(flow: &str) -> Result<Self> {
+        FLOWS.iter()
+            .find(|f| f.name() == flow)
+            .cloned()
+            .ok_or_else(|| ErrorKind::UnknownFlow(flow.to_string()).into())
+    }
+}
+
+#[derive(Debug)]
+pub struct OAuth2<'a> {
+    client: &'a OAuth2Client,
+}
+
+impl<'a> OAuth2<'a> {
+    pub fn new(client: &'a OAuth2Client) -> Self {
+        OAuth2 { client: client }
+    }
+
+    pub fn from_config(config: &'a Config, module_name: &str) -> Result<Self> {
+        let client = config.oauth.get(module_name)
+            .ok_or_else(|| ErrorKind::NoClientRegistered(module_name.to_string()))?;
+        Ok(OAuth2::new(client))
+    }
+
+    pub fn run(&self, flow: Flow, open_browser: bool) -> Result<OAuth2Token> {
+        match flow {
+            Flow::AuthorizationCode => self.authorization_code(open_browser),
+            Flow::DeviceCode => self.device_code(),
+            Flow::ClientCredentials => self.client_credentials(),
+        }
+    }
+
+    // https://tools.ietf.org/html/rfc7636
+    pub fn authorization_code(&self, open_browser: bool) -> Result<OAuth2Token> {
+        let listener = RedirectListener::bind().chain_err(|| "Failed to create redirect listener")?;
+        let redirect_uri = listener.redirect_uri();
+        let verifier = random_string(64)?;
+        let state = random_string(16)?;
+
+        let mut parameters = vec![
+            ("response_type", "code".to_string()),
+            ("client_id", self.client.client_id.clone()),
+            ("redirect_uri", redirect_uri.clone()),
+            ("state", state.clone()),
+            ("code_challenge", pkce_challenge(&verifier)),
+            ("code_challenge_method", "S256".to_string()),
+        ];
+        if let Some(ref scope) = self.client.scope {
+            parameters.push(("scope", scope.clone()));
+        }
+        let parameters_enc = serde_urlencoded::to_string(&parameters).chain_err(|| "URL serialization failed")?;
+        let auth_url = format!("{}?{}", self.client.auth_endpoint, parameters_enc);
+
+        info("Requesting authorization code ...");
+        if open_browser {
+            msgln("Please authenticate in the web browser window ...");
+            webbrowser::open(&auth_url).chain_err(|| "Failed to open web browser")?;
+        } else {
+            msgln("Please authenticate at the following URL ...");
+            msgln(format!("\n\t{}\n", auth_url));
+        }
+        // Only the redirect of this authorization request carries its state
+        let redirect = listener.wait_for_redirect(&[("state", &state)], redirect::DEFAULT_TIMEOUT)
+            .chain_err(|| "Failed to receive redirect")?;
+
+        if let Some(error) = redirect.get("error") {
+            let description = redirect.get("error_description").cloned().unwrap_or_default();
+            bail!(ErrorKind::AuthorizationFailed(error.clone(), description));
+        }
+        let code = redirect.get("code").ok_or("Received redirect without authorization code")?;
+
+        info("Requesting access token ...");
+        self.request_token(vec![
+            ("grant_type", "authorization_code".to_string()),
+            ("code", code.clone()),
+            ("redirect_uri", redirect_uri),
+            ("code_verifier", verifier),
+        ])
+    }
+
+    // https://tools.ietf.org/html/rfc8628
+    pub fn device_code(&self) -> Result<OAuth2Token> {
+        let device_endpoint = self.client.device_endpoint.as_ref()
+            .ok_or_else(|| ErrorKind::FlowNotSupported("device".to_string()))?;
+
+        info("Requesting device code ...");
+        let mut parameters = vec![("client_id", self.client.client_id.clone())];
+        if let Some(ref scope) = self.client.scope {
+            parameters.push(("scope", scope.clone()));
+        }
+        let authorization: DeviceAuthorization = self.post_form(device_endpoint, parameters)?;
+        let verification_uri = authorization.verification_uri()
+            .ok_or("Received device authorization without verification URI")?;
+
+        msgln(format!("Please open the following URL on any device and enter the code '{}' ...", authorization.user_code));
+        msgln(format!("\n\t{}\n", verification_uri));
+        if let Some(ref verification_uri_complete) = authorization.verification_uri_complete {
+            msgln("Alternatively, open the following URL which already contains the code ...");
+            msgln(format!("\n\t{}\n", verification_uri_complete));
+        }
+        info(format!("Waiting for authorization; the code expires in {} minutes ...", authorization.expires_in / 60));
+
+        let mut interval = authorization.interval.unwrap_or(5);
+        let deadline = unix_now() + authorization.expires_in;
+        while unix_now() < deadline {
+            thread::sleep(Duration::from_secs(interval));
+            verbose(".");
+            let result = self.request_token(vec![
+                ("grant_type", DEVICE_CODE_GRANT_TYPE.to_string()),
+                ("device_code", authorization.device_code.clone()),
+            ]);
+            match result {
+                Err(Error(ErrorKind::AuthorizationFailed(ref error, _), _)) if error == "authorization_pending" => continue,
+                Err(Error(ErrorKind::AuthorizationFailed(ref error, _), _)) if error == "slow_down" => {
+                    interval += 5;
+                    continue
+                }
+                _ => return result,
+            }
+        }
+
+        bail!(ErrorKind::AuthorizationFailed("expired_token".to_string(), "device code expired".to_string()))
+    }
+
+    // https://tools.ietf.org/html/rfc6749#section-4.4
+    pub fn client_credentials(&self) -> Result<OAuth2Token> {
+        if self.client.client_secret.is_none() {
+            bail!(ErrorKind::FlowNotSupported("client_credentials".to_string()));
+        }
+        info("Requesting access token ...");
+        let mut parameters = vec![("grant_type", "client_credentials".to_string())];
+        if let Some(ref scope) = self.client.scope {
+            parameters.push(("scope", scope.clone()));
+        }
+        self.request_token(parameters)
+    }
+
+    // https://tools.ietf.org/html/rfc6749#section-4.3; only for self-hosted services like Wallabag that offer no other flow
+    pub fn password(&self, username: &str, password: &str) -> Result<OAuth2Token> {
+        info("Requesting access token ...");
+        let mut parameters = vec![
+            ("grant_type", "password".to_string()),
+            ("username", username.to_string()),
+            ("password", password.to_string()),
+        ];
+        if let Some(ref scope) = self.client.scope {
+            parameters.push(("scope", scope.clone()));
+        }
+        self.request_token(parameters)
+    }
+
+    // https://tools.ietf.org/html/rfc6749#section-6
+    pub fn refresh(&self, refresh_token: &str) -> Result<OAuth2Token> {
+        info("Refreshing access token ...");
+        self.request_token(vec![
+            ("grant_type", "refresh_token".to_string()),
+            ("refresh_token", refresh_token.to_string()),
+        ])
+    }
+
+    // https://tools.ietf.org/html/rfc7009; returns false if the client does not support revocation
+    pub fn revoke(&self, token: &str) -> Result<bool> {
+        let revocation_endpoint = match self.client.revocation_endpoint {
+            Some(ref revocation_endpoint) => revocation_endpoint,
+            None => return Ok(false),
+        };
+        info("Revoking access token ...");
+        let mut parameters = vec![("token", token.to_string())];
+        if self.client.client_secret.is_none() {
+            parameters.push(("client_id", self.client.client_id.clone()));
+        }
+        let body = serde_urlencoded::to_string(&parameters).chain_err(|| "URL serialization failed")?;
+
+        let client = tls_client().chain_err(|| "Could not create TLS client")?;
+        let mut request = client
+            .post(revocation_endpoint)
+            .header(ContentType(mime!(Application / WwwFormUrlEncoded)))
+            .body(&body);
+        if let Some(ref client_secret) = self.client.client_secret {
+            request = request.header(
+                Authorization(Basic { username: self.client.client_id.clone(), password: Some(client_secret.clone()) }));
+        }
+        let response = request.send().chain_err(|| "Failed to finish HTTP request")?;
+        if !response.status.is_success() {
+            bail!("Revocation endpoint responded with status {}", response.status);
+        }
+
+        Ok(true)
+    }
+
+    fn request_token(&self, parameters: Vec<(&str, String)>) -> Result<OAuth2Token> {
+        self.post_form(&self.client.token_endpoint, parameters)
+    }
+
+    // https://tools.ietf.org/html/rfc6749#section-2.3.1
+    fn post_form<T: Deserialize>(&self, url: &str, mut parameters: Vec<(&str, String)>) -> Result<T> {
+        if self.client.client_secret.is_none() {
+            parameters.push(("client_id", self.client.client_id.clone()));
+        }
+        let body = serde_urlencoded::to_string(&parameters).chain_err(|| "URL serialization failed")?;
+
+        let client = tls_client().chain_err(|| "Could not create TLS client")?;
+        let mut request = client
+            .post(url)
+            .header(ContentType(mime!(Application / WwwFormUrlEncoded)))
+            .header(Accept(vec![qitem(mime!(Application / Json))]))
+            .body(&body);
+        if let Some(ref client_secret) = self.client.client_secret {
+            request = request.header(
+                Authorization(Basic { username: self.client.client_id.clone(), password: Some(client_secret.clone()) }));
+        }
+        let mut response = request.send().chain_err(|| "Failed to finish HTTP request")?;
+
+        let mut buffer = Vec::new();
+        response.read_to_end(&mut buffer).chain_err(|| "Failed to read HTTP response")?;
+
+        if !response.status.is_success() {
+            let error: OAuth2ErrorResponse = serde_json::from_slice(&buffer)
+                .chain_err(|| format!("Token endpoint responded with status {}", response.status))?;
+            bail!(ErrorKind::AuthorizationFailed(error.error, error.error_description.unwrap_or_default()));
+        }
+        let result: T = serde_json::from_slice(&buffer).chain_err(|| "JSON parsing failed")?;
+
+        Ok(result)
+    }
+}
+
+/// Runs the interactive authentication of a module and stores the new credential in the credential store.
+pub type Reauth = fn(&Config, &str) -> ::std::result::Result<(), String>;
+
+/// Authorizes requests with the access token of a module. An expired access token is refreshed before the next
+/// request; a request rejected with 401 Unauthorized is retried once after refreshing the access token. Refreshed
+/// tokens are written back to the credential store.
+///
+/// If refreshing fails, e.g., because the refresh token has been revoked, and `interactive_reauth` is enabled in
+/// section '[general]', the session runs the interactive authentication set by `with_reauth` and continues with the
+/// new token instead of aborting the running command.
+#[derive(Debug)]
+pub struct TokenSession<'a> {
+    config: &'a Config,
+    module_name: String,
+    client: OAuth2Client,
+    credential: RefCell<Credential>,
+    reauth: Option<Reauth>,
+}
+
+impl<'a> TokenSession<'a> {
+    /// Uses the credential from the credential store or, if there is none, `fallback`.
+    pub fn new(config: &'a Config, module_name: &str, client: OAuth2Client, fallback: Option<Credential>) -> Result<Self> {
+        let credential = match credentials::load(config, module_name) {
+            Ok(credential) => credential,
+            Err(_) => fallback
+                .ok_or_else(|| credentials::Error::from(credentials::ErrorKind::NoAccessToken(module_name.to_string())))?,
+        };
+        let session = TokenSession {
+            config: config,
+            module_name: module_name.to_string(),
+            client: client,
+            credential: RefCell::new(credential),
+            reauth: None,
+        };
+
+        Ok(session)
+    }
+
+    pub fn from_config(config: &'a Config, module_name: &str) -> Result<Self> {
+        let client = config.oauth.get(module_name).cloned()
+            .ok_or_else(|| ErrorKind::NoClientRegistered(module_name.to_string()))?;
+        let session = TokenSession::new(config, module_name, client, None)?.with_reauth(reauth);
+
+        Ok(session)
+    }
+
+    pub fn with_reauth(mut self, reauth: Reauth) -> Self {
+        self.reauth = Some(reauth);
+        self
+    }
+
+    pub fn access_token(&self) -> String {
+        self.credential.borrow().access_token.clone()
+    }
+
+    pub fn credential<'b>(&'b self) -> Ref<'b, Credential> {
+        self.credential.borrow()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.credential.borrow().expires_at.map(|expires_at| expires_at <= unix_now()).unwrap_or(false)
+    }
+
+    /// Calls `request` with the current access token and retries it once with a refreshed token on 401 Unauthorized.
+    pub fn send<F, E>(&self, mut request: F) -> Result<Response>
+        where F: FnMut(&str) -> ::std::result::Result<Response, E>, E: ::std::error::Error + Send + 'static {
+        if self.is_expired() {
+            info("Access token has expired; refreshing ...");
+            self.renew()?;
+        }
+        let response = request(&self.access_token()).chain_err(|| "Failed to finish HTTP request")?;
+        credentials::record_usage(self.config, &self.module_name);
+        if response.status != StatusCode::Unauthorized {
+            return Ok(response);
+        }
+
+        info("Access token has been rejected; refreshing ...");
+        self.renew()?;
+        let response = request(&self.access_token()).chain_err(|| "Failed to finish HTTP request")?;
+        if response.status == StatusCode::Unauthorized {
+            warning(format!("Refreshed access token for module {} has been rejected as well; please run 'rat auth login {}'.",
+                self.module_name, self.module_name));
+        }
+
+        Ok(response)
+    }
+
+    /// Like `send`, but retries the request according to `general.retry_policy`; only for requests that do not modify
+    /// data.
+    pub fn send_idempotent<F>(&self, mut request: F) -> Result<Response> where F: FnMut(&str) -> hyper::Result<Response> {
+        let policy = self.config.general.retry_policy();
+        self.send(|token| retry::send(&policy, || request(token)))
+    }
+
+    /// Refreshes the access token and falls back to interactive re-authentication if allowed.
+    fn renew(&self) -> Result<()> {
+        let refresh_error = match self.refresh() {
+            Ok(_) => return Ok(()),
+            Err(e) => e,
+        };
+        let reauth = match self.reauth {
+            Some(reauth) if self.config.general.interactive_reauth.unwrap_or(false) => reauth,
+            _ => return Err(refresh_error),
+        };
+
+        warning(format!("Failed to refresh access token for module {} because {}; please authenticate again.",
+            self.module_name, refresh_error));
+        reauth(self.config, &self.module_name)
+            .map_err(Error::from)
+            .chain_err(|| "Re-authentication failed")?;
+        let credential = credentials::load(self.config, &self.module_name)?;
+        *self.credential.borrow_mut() = credential;
+        info("Re-authenticated; continuing ...");
+
+        Ok(())
+    }
+
+    pub fn refresh(&self) -> Result<()> {
+        let (refresh_token, username, scope, read_only, flow) = {
+            let credential = self.credential.borrow();
+            let refresh_token = credential.refresh_token.clone()
+                .ok_or_else(|| format!("No refresh token available; please run 'rat auth login {}'", self.module_name))?;
+            (refresh_token, credential.username.clone(), credential.scope.clone(), credential.read_only, credential.flow.clone())
+        };
+
+        let mut credential = OAuth2::new(&self.client).refresh(&refresh_token)?.into_credential();
+        // Not every provider rotates refresh tokens
+        if credential.refresh_token.is_none() {
+            credential.refresh_token = Some(refresh_token);
+        }
+        credential.username = username;
+        // The scope of a refreshed token is the originally granted scope unless stated otherwise
+        if credential.scope.is_none() {
+            credential.scope = scope;
+        }
+        credential.read_only = read_only;
+        credential.flow = flow;
+        credentials::store(self.config, &self.module_name, &credential)?;
+        verboseln(format!("Stored refreshed access token for module {}.", self.module_name));
+        *self.credential.borrow_mut() = credential;
+
+        Ok(())
+    }
+}
+
+/// Runs `flow` for the OAuth client registered for `module_name` and stores the token in the credential store. If
+/// `read_only` is set, the client's `read_only_scope` is requested instead of its `scope`.
+pub fn login(config: &Config, module_name: &str, flow: Flow, open_browser: bool, read_only: bool) -> Result<Credential> {
+    let mut client = config.oauth.get(module_name)
+        .ok_or_else(|| ErrorKind::NoClientRegistered(module_name.to_string()))?
+        .clone();
+    if read_only {
+        client.scope = Some(client.read_only_scope.clone()
+            .ok_or_else(|| format!("No read-only scope configured in section '[oauth.{}]'", module_name))?);
+    }
+    let token = OAuth2::new(&client).run(flow, open_browser)?;
+    let mut credential = token.into_credential();
+    if credential.scope.is_none() {
+        credential.scope = client.scope.clone();
+    }
+    credential.read_only = read_only;
+    credential.flow = Some(flow.name().to_string());
+    credentials::store(config, module_name, &credential).chain_err(|| "Failed to store access token")?;
+
+    Ok(credential)
+}
+
+/// Runs the flow of the expired login again and keeps a read-only login read-only.
+fn reauth(config: &Config, module_name: &str) -> ::std::result::Result<(), String> {
+    let (flow, read_only) = match credentials::load(config, module_name) {
+        Ok(credential) => {
+            let flow = match credential.flow {
+                Some(ref flow) => flow.parse().map_err(|e: Error| e.to_string())?,
+                None => Flow::AuthorizationCode,
+            };
+            (flow, credential.read_only)
+        }
+        Err(_) => (Flow::AuthorizationCode, false),
+    };
+    login(config, module_name, flow, false, read_only)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+fn random_string(len: usize) -> Result<String> {
+    let mut rng = OsRng::new().chain_err(|| "Failed to create random number generator")?;
+    Ok(rng.gen_ascii_chars().take(len).collect())
+}
+
+fn pkce_challenge(verifier: &str) -> String {
+    let mut sha = Sha256::new();
+    sha.input_str(verifier);
+    let mut digest = [0u8; 32];
+    sha.result(&mut digest);
+
+    base64::encode_config(&digest, base64::URL_SAFE_NO_PAD)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pkce_challenge() {
+        // https://tools.ietf.org/html/rfc7636#appendix-B
+        let challenge = pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk");
+
+        assert_eq!(challenge, "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+    }
+
+    #[test]
+    fn test_flow_names() {
+        for flow in FLOWS {
+            assert_eq!(flow.name().parse::<Flow>().unwrap(), *flow);
+        }
+    }
+
+    #[test]
+    fn test_unknown_flow_names_valid_flows() {
+        let error = "implicit".parse::<Flow>().unwrap_err();
+
+        assert_eq!(error.kind().category(), Some(Category::Config));
+        assert_eq!(error.to_string(),
+            "unknown OAuth flow 'implicit'; valid flows are 'authorization_code', 'device', 'client_credentials'");
+    }
+
+    #[test]
+    fn test_device_authorization_verification_url() {
+        let json = r#"{"device_code":"d","user_code":"ABCD-EFGH","verification_url":"https://example.com/device","expires_in":1800}"#;
+        let authorization: DeviceAuthorization = serde_json::from_str(json).unwrap();
+
+        assert_eq!(authorization.verification_uri(), Some(&"https://example.com/device".to_string()));
+        assert_eq!(authorization.interval, None);
+    }
+
+    #[test]
+    fn test_random_string() {
+        let random = random_string(64).unwrap();
+
+        assert_eq!(random.len(), 64);
+        assert!(random.chars().all(|c| c.is_alphanumeric()));
+    }
+}
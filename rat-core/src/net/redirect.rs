@@ -0,0 +1,171 @@
+use utils::console::*;
+
+use serde_urlencoded;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+static RESPONSE: &'static str = "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nConnection: close\r\n\r\n\
+<html><body><h1>RAT</h1><p>You've successfully authenticated RAT. You may close this window now.</p></body></html>";
+static NOT_FOUND: &'static str = "HTTP/1.1 404 Not Found\r\nConnection: close\r\n\r\n";
+
+/// Path of `redirect_uri`
+static REDIRECT_PATH: &'static str = "/";
+
+/// How long `rat auth login` waits for the user to authenticate in the web browser
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+error_chain! {
+    errors {
+        RedirectListenerFailed {
+            description("failed to receive authentication redirect")
+            display("failed to receive authentication redirect")
+        }
+        RedirectTimeout(timeout: Duration) {
+            description("timed out waiting for authentication redirect")
+            display("timed out waiting for authentication redirect after {} s", timeout.as_secs())
+        }
+    }
+}
+
+/// Loopback HTTP listener that receives a single OAuth redirect from the web browser.
+#[derive(Debug)]
+pub struct RedirectListener {
+    listener: TcpListener,
+    port: u16,
+}
+
+impl RedirectListener {
+    pub fn bind() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").chain_err(|| "Failed to bind loopback listener")?;
+        let port = listener.local_addr().chain_err(|| "Failed to get loopback listener address")?.port();
+        verboseln(format!("Listening for authentication redirect on port {}.", port));
+
+        Ok(RedirectListener { listener: listener, port: port })
+    }
+
+    pub fn redirect_uri(&self) -> String {
+        format!("http://127.0.0.1:{}/", self.port)
+    }
+
+    /// Blocks until the browser has been redirected to `redirect_uri` with all `expected` query parameters, e.g., the
+    /// state of the authorization request, and returns the query parameters. Other requests, e.g., for a favicon or by
+    /// another local process, are answered with 404 and ignored.
+    pub fn wait_for_redirect(self, expected: &[(&str, &str)], timeout: Duration) -> Result<HashMap<String, String>> {
+        self.listener.set_nonblocking(true).chain_err(|| ErrorKind::RedirectListenerFailed)?;
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    match receive(stream, expected) {
+                        Ok(Some(parameters)) => return Ok(parameters),
+                        Ok(None) => {}
+                        Err(e) => verboseln(format!("Ignoring request to redirect listener because {}.", e)),
+                    }
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                Err(e) => return Err(e).chain_err(|| ErrorKind::RedirectListenerFailed),
+            }
+        }
+
+        bail!(ErrorKind::RedirectTimeout(timeout))
+    }
+}
+
+/// Reads one request and answers it; returns its query parameters if it is the expected redirect.
+fn receive(mut stream: TcpStream, expected: &[(&str, &str)]) -> Result<Option<HashMap<String, String>>> {
+    stream.set_nonblocking(false).chain_err(|| ErrorKind::RedirectListenerFailed)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT)).chain_err(|| ErrorKind::RedirectListenerFailed)?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line).chain_err(|| ErrorKind::RedirectListenerFailed)?;
+    verboseln(format!("Received request '{}'.", request_line.trim()));
+
+    let is_redirect = request_path(&request_line) == Some(REDIRECT_PATH);
+    let parameters = parse_request_line(&request_line)?;
+    let is_expected = expected.iter().all(|&(name, value)| parameters.get(name).map(|v| v.as_str()) == Some(value));
+    if is_redirect && is_expected {
+        let _ = stream.write_all(RESPONSE.as_bytes());
+        Ok(Some(parameters))
+    } else {
+        let _ = stream.write_all(NOT_FOUND.as_bytes());
+        Ok(None)
+    }
+}
+
+fn request_path(request_line: &str) -> Option<&str> {
+    request_line.split_whitespace().nth(1).and_then(|target| target.splitn(2, '?').next())
+}
+
+/// Parses the query parameters of an HTTP request line, e.g., 'GET /?code=abc HTTP/1.1'.
+pub fn parse_request_line(request_line: &str) -> Result<HashMap<String, String>> {
+    let target = request_line.split_whitespace().nth(1).ok_or(ErrorKind::RedirectListenerFailed)?;
+    let query = target.splitn(2, '?').nth(1).unwrap_or("");
+    let parameters: HashMap<String, String> = serde_urlencoded::from_str(query)
+        .chain_err(|| ErrorKind::RedirectListenerFailed)?;
+
+    Ok(parameters)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_request_line_with_parameters() {
+        let parameters = parse_request_line("GET /?code=abc%2F123&state=xyz HTTP/1.1\r\n").unwrap();
+
+        assert_eq!(parameters.get("code"), Some(&"abc/123".to_string()));
+        assert_eq!(parameters.get("state"), Some(&"xyz".to_string()));
+    }
+
+    #[test]
+    fn test_parse_request_line_without_parameters() {
+        let parameters = parse_request_line("GET / HTTP/1.1\r\n").unwrap();
+
+        assert!(parameters.is_empty());
+    }
+
+    #[test]
+    fn test_parse_request_line_malformed() {
+        assert!(parse_request_line("").is_err());
+    }
+
+    fn request(port: u16, target: &str) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n", target).unwrap();
+        let mut status_line = String::new();
+        BufReader::new(&stream).read_line(&mut status_line).unwrap();
+        status_line
+    }
+
+    #[test]
+    fn test_wait_for_redirect_ignores_other_requests() {
+        let listener = RedirectListener::bind().unwrap();
+        let port = listener.port;
+        let waiting = thread::spawn(move || listener.wait_for_redirect(&[("state", "xyz")], Duration::from_secs(10)));
+
+        assert!(request(port, "/favicon.ico").contains("404"));
+        assert!(request(port, "/?code=forged&state=abc").contains("404"));
+        assert!(request(port, "/?code=abc&state=xyz").contains("200"));
+
+        let parameters = waiting.join().unwrap().unwrap();
+        assert_eq!(parameters.get("code"), Some(&"abc".to_string()));
+    }
+
+    #[test]
+    fn test_wait_for_redirect_times_out() {
+        let listener = RedirectListener::bind().unwrap();
+
+        let result = listener.wait_for_redirect(&[], Duration::from_millis(200));
+
+        match result {
+            Err(Error(ErrorKind::RedirectTimeout(_), _)) => {}
+            other => panic!("expected timeout, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Retries of idempotent requests. Providers intermittently fail with status 429 or 5xx, and connections break, so
+//! requests that only read data are sent again after a delay that doubles with every attempt. The delay is randomized
+//! to spread retries of concurrent requests. If the provider tells how long to wait, e.g., by header 'Retry-After' or
+//! Pocket's 'X-Limit-*-Reset', rat waits that long instead -- or gives up right away if that is longer than a minute.
+//!
+//! Requests that modify data are never retried, because the failed attempt may have been applied nevertheless.
+
+use utils::console::*;
+
+use hyper;
+use hyper::client::Response;
+use hyper::header::Headers;
+use hyper::status::StatusCode;
+use rand::{self, Rng};
+use std::cmp;
+use std::thread;
+use std::time::Duration;
+
+/// Number of attempts unless configured otherwise by `general.retry_max_attempts`.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+/// Delay before the second attempt unless configured otherwise by `general.retry_base_delay_ms`.
+pub const DEFAULT_BASE_DELAY_MS: u64 = 500;
+/// Longest delay between two attempts; rat gives up instead of waiting longer.
+const MAX_DELAY_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Including the first attempt; 1 disables retries
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    /// Randomizes delays between half and all of the computed delay
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay: Duration::from_millis(DEFAULT_BASE_DELAY_MS),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay after the `attempt`th failed attempt without instructions of the provider
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32 << cmp::min(attempt.saturating_sub(1), 16);
+        let delay = cmp::min(self.base_delay * factor, Duration::from_secs(MAX_DELAY_SECS));
+        if !self.jitter {
+            return delay;
+        }
+        let millis = delay.as_secs() * 1000 + u64::from(delay.subsec_nanos() / 1_000_000);
+        Duration::from_millis(millis / 2 + rand::thread_rng().gen_range(0, millis / 2 + 1))
+    }
+}
+
+/// Sends a request that does not modify data using `send` until it succeeds, fails permanently, or the attempts of
+/// `policy` are exhausted. The last response or error is returned, so unsuccessful responses still need to be checked.
+pub fn send<F>(policy: &RetryPolicy, mut send: F) -> hyper::Result<Response> where F: FnMut() -> hyper::Result<Response> {
+    let mut attempt = 1;
+    loop {
+        let result = send();
+        if attempt >= policy.max_attempts {
+            return result;
+        }
+        let retry = match result {
+            Ok(ref response) if is_retryable(response.status) => match provider_delay(&response.headers) {
+                Some(delay) if delay > Duration::from_secs(MAX_DELAY_SECS) => None,
+                Some(delay) => Some((delay, format!("status {}", response.status))),
+                None => Some((policy.delay(attempt), format!("status {}", response.status))),
+            },
+            Err(hyper::Error::Io(ref e)) => Some((policy.delay(attempt), format!("'{}'", e))),
+            _ => None,
+        };
+        let (delay, reason) = match retry {
+            Some(retry) => retry,
+            None => return result,
+        };
+        info(format!("Request failed with {}; retrying in {} ms ({} of {} attempts) ...",
+                     reason, delay.as_secs() * 1000 + u64::from(delay.subsec_nanos() / 1_000_000), attempt + 1, policy.max_attempts));
+        thread::sleep(delay);
+        attempt += 1;
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TooManyRequests || status.is_server_error()
+}
+
+/// 'Retry-After' in seconds, or the time until Pocket resets an exhausted rate limit
+fn provider_delay(headers: &Headers) -> Option<Duration> {
+    let number = |name: &str| -> Option<u64> {
+        headers.get_raw(name)
+            .and_then(|values| values.first())
+            .and_then(|value| String::from_utf8_lossy(value).trim().parse().ok())
+    };
+    if let Some(secs) = number("Retry-After") {
+        return Some(Duration::from_secs(secs));
+    }
+    ["User", "Key"].iter()
+        .filter(|limit| number(&format!("X-Limit-{}-Remaining", limit)) == Some(0))
+        .filter_map(|limit| number(&format!("X-Limit-{}-Reset", limit)))
+        .max()
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_delay_doubles() {
+        let policy = RetryPolicy { max_attempts: 5, base_delay: Duration::from_millis(100), jitter: false };
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(3), Duration::from_millis(400));
+        assert_eq!(policy.delay(30), Duration::from_secs(MAX_DELAY_SECS));
+    }
+
+    #[test]
+    fn test_delay_with_jitter() {
+        let policy = RetryPolicy { max_attempts: 5, base_delay: Duration::from_millis(100), jitter: true };
+
+        for _ in 0..100 {
+            let delay = policy.delay(2);
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn test_provider_delay() {
+        let mut retry_after = Headers::new();
+        retry_after.set_raw("Retry-After", vec![b"7".to_vec()]);
+        let mut pocket = Headers::new();
+        pocket.set_raw("X-Limit-User-Remaining", vec![b"0".to_vec()]);
+        pocket.set_raw("X-Limit-User-Reset", vec![b"42".to_vec()]);
+        pocket.set_raw("X-Limit-Key-Remaining", vec![b"9000".to_vec()]);
+        pocket.set_raw("X-Limit-Key-Reset", vec![b"3000".to_vec()]);
+
+        assert_eq!(provider_delay(&retry_after), Some(Duration::from_secs(7)));
+        assert_eq!(provider_delay(&pocket), Some(Duration::from_secs(42)));
+        assert_eq!(provider_delay(&Headers::new()), None);
+    }
+
+    #[test]
+    fn test_send_gives_up_after_max_attempts() {
+        ::utils::console::init(::config::Verbosity::QUIET);
+        let policy = RetryPolicy { max_attempts: 3, base_delay: Duration::from_millis(1), jitter: false };
+        let mut attempts = 0;
+
+        let result = send(&policy, || {
+            attempts += 1;
+            Err(hyper::Error::Io(::std::io::Error::new(::std::io::ErrorKind::ConnectionReset, "reset")))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn test_send_does_not_retry_permanent_errors() {
+        let policy = RetryPolicy::default();
+        let mut attempts = 0;
+
+        let result = send(&policy, || {
+            attempts += 1;
+            Err(hyper::Error::Method)
+        });
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+}
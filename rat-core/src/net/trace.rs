@@ -0,0 +1,229 @@
+//! Tracing of HTTP traffic for debugging, e.g., why a Pocket request fails; cf. `--trace-http`. Connections of
+//! `http::TlsClient` are wrapped in a `Traced` stream that passes request lines, headers, and bodies as sent, and
+//! responses as received, to `console::http_trace` -- after TLS, so HTTPS traffic is readable. Credentials are masked:
+//! the values of authorization and cookie headers, of fields like 'access_token' in bodies and query strings, and all
+//! secrets known to `secrets::redact`.
+
+use utils::console;
+use utils::secrets;
+
+use hyper::net::NetworkStream;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr};
+use std::time::Duration;
+
+/// Headers whose values are credentials
+static SECRET_HEADERS: &'static [&'static str] = &[
+    "authorization", "cookie", "proxy-authorization", "set-cookie", "x-api-key",
+];
+
+/// Fields of JSON and form bodies and of query strings whose values are credentials
+static SECRET_FIELDS: &'static [&'static str] = &[
+    "access_token", "api_token", "auth_token", "client_secret", "code", "consumer_key", "oauth_signature",
+    "oauth_token", "password", "refresh_token", "token",
+];
+
+static REDACTED: &'static str = "********";
+
+/// Longer lines, e.g., of binary bodies, are truncated.
+const MAX_LINE_LEN: usize = 2000;
+
+/// Direction of traffic, logged as prefix of its lines like curl does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Sent,
+    Received,
+}
+
+impl Direction {
+    fn prefix(&self) -> &'static str {
+        match *self {
+            Direction::Sent => "> ",
+            Direction::Received => "< ",
+        }
+    }
+}
+
+/// A connection that logs its traffic line by line if tracing is enabled, and only passes it through otherwise
+pub struct Traced<S> {
+    stream: S,
+    enabled: bool,
+    sent: Vec<u8>,
+    received: Vec<u8>,
+}
+
+impl<S> Traced<S> {
+    pub fn new(stream: S) -> Self {
+        Traced { stream: stream, enabled: console::is_tracing_http(), sent: Vec::new(), received: Vec::new() }
+    }
+}
+
+/// Logs the complete lines of `buffer` and keeps the incomplete last one.
+fn log_lines(direction: Direction, buffer: &mut Vec<u8>, flush: bool) {
+    while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+        let line: Vec<u8> = buffer.drain(..end + 1).collect();
+        console::http_trace(format_line(direction, &line));
+    }
+    if flush && !buffer.is_empty() {
+        let line: Vec<u8> = buffer.drain(..).collect();
+        console::http_trace(format_line(direction, &line));
+    }
+}
+
+fn format_line(direction: Direction, line: &[u8]) -> String {
+    let mut end = line.len();
+    while end > 0 && (line[end - 1] == b'\r' || line[end - 1] == b'\n') {
+        end -= 1;
+    }
+    let mut line = redact(&String::from_utf8_lossy(&line[..end]));
+    if line.len() > MAX_LINE_LEN {
+        let mut end = MAX_LINE_LEN;
+        while !line.is_char_boundary(end) {
+            end -= 1;
+        }
+        let omitted = line.len() - end;
+        line.truncate(end);
+        line.push_str(&format!("... ({} more bytes)", omitted));
+    }
+
+    format!("{}{}", direction.prefix(), line)
+}
+
+/// Masks the credentials of a line of HTTP traffic.
+fn redact(line: &str) -> String {
+    if let Some(colon) = line.find(':') {
+        let name = line[..colon].trim().to_lowercase();
+        if SECRET_HEADERS.contains(&name.as_str()) {
+            return format!("{}: {}", &line[..colon], REDACTED);
+        }
+    }
+
+    let mut redacted = line.to_string();
+    for field in SECRET_FIELDS {
+        redacted = redact_field(&redacted, field);
+    }
+
+    secrets::redact(&redacted)
+}
+
+/// Masks the values of `field` as JSON string, e.g., `"token":"abc"`, and as parameter, e.g., `token=abc`.
+fn redact_field(text: &str, field: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find(field) {
+        let after = &rest[i + field.len()..];
+        // Only whole field names, e.g., not 'token' in 'access_token'
+        let is_name = rest[..i].chars().last().map(|c| !(c.is_alphanumeric() || c == '_')).unwrap_or(true);
+        let value = if !is_name {
+            None
+        } else if after.starts_with('=') {
+            Some((1, "& \"'"))
+        } else if after.starts_with('"') {
+            // Between the quotes of name and value, there is a colon and maybe spaces
+            let quote = after[1..].find(|c| c != ' ' && c != ':').map(|i| i + 1);
+            match quote {
+                Some(quote) if after[1..quote].contains(':') && after[quote..].starts_with('"') => Some((quote + 1, "\"")),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        match value {
+            Some((start, terminators)) => {
+                let value = &after[start..];
+                let end = value.find(|c| terminators.contains(c)).unwrap_or_else(|| value.len());
+                out.push_str(&rest[..i + field.len() + start]);
+                if end > 0 {
+                    out.push_str(REDACTED);
+                }
+                rest = &value[end..];
+            }
+            None => {
+                out.push_str(&rest[..i + field.len()]);
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+
+    out
+}
+
+impl<S: Read> Read for Traced<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.stream.read(buf)?;
+        if self.enabled {
+            self.received.extend_from_slice(&buf[..read]);
+            log_lines(Direction::Received, &mut self.received, read == 0);
+        }
+        Ok(read)
+    }
+}
+
+impl<S: Write> Write for Traced<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.stream.write(buf)?;
+        if self.enabled {
+            self.sent.extend_from_slice(&buf[..written]);
+            log_lines(Direction::Sent, &mut self.sent, false);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.enabled {
+            log_lines(Direction::Sent, &mut self.sent, true);
+        }
+        self.stream.flush()
+    }
+}
+
+impl<S> Drop for Traced<S> {
+    fn drop(&mut self) {
+        if self.enabled {
+            log_lines(Direction::Sent, &mut self.sent, true);
+            log_lines(Direction::Received, &mut self.received, true);
+        }
+    }
+}
+
+impl<S: NetworkStream> NetworkStream for Traced<S> {
+    fn peer_addr(&mut self) -> io::Result<SocketAddr> {
+        self.stream.peer_addr()
+    }
+
+    fn set_read_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        self.stream.set_read_timeout(dur)
+    }
+
+    fn set_write_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        self.stream.set_write_timeout(dur)
+    }
+
+    fn close(&mut self, how: Shutdown) -> io::Result<()> {
+        self.stream.close(how)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(redact("Authorization: Bearer abc123"), "Authorization: ********");
+        assert_eq!(redact(r#"{"consumer_key":"1234-abcd","access_token": "xyz","count":10}"#),
+                   r#"{"consumer_key":"********","access_token": "********","count":10}"#);
+        assert_eq!(redact("POST /oauth/token?grant_type=refresh&refresh_token=r3fr3sh&x=1 HTTP/1.1"),
+                   "POST /oauth/token?grant_type=refresh&refresh_token=********&x=1 HTTP/1.1");
+        assert_eq!(redact("X-Error-Code: 107"), "X-Error-Code: 107");
+        assert_eq!(redact(r#"{"tokens":1,"token":""}"#), r#"{"tokens":1,"token":""}"#);
+    }
+
+    #[test]
+    fn test_format_line() {
+        assert_eq!(format_line(Direction::Received, b"HTTP/1.1 200 OK\r\n"), "< HTTP/1.1 200 OK");
+        let long = format_line(Direction::Sent, &[b'x'; MAX_LINE_LEN + 10]);
+        assert!(long.ends_with("x... (10 more bytes)"));
+    }
+}
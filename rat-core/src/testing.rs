@@ -0,0 +1,83 @@
+//! Configurations for tests of clients, caches, credentials, and module commands. Each test gets a cache directory of
+//! its own in the system's temporary directory, so tests share neither caches nor credentials with each other, with
+//! concurrent test runs, or with the user. Built for the unit tests of rat-core and, with feature `testing`, for the
+//! tests of rat.
+
+use config::{Config, Verbosity};
+use utils::console;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use toml;
+
+/// Path in the system's temporary directory for `test`; the process id keeps concurrent test runs apart.
+pub fn temp_path(test: &str) -> PathBuf {
+    env::temp_dir().join(format!("rat-{}-test-{}", test, process::id()))
+}
+
+/// Creates a configuration with an empty cache directory for `test`; `sections` is added to the configuration, e.g.,
+/// a token.
+pub fn config(test: &str, sections: &str) -> Config {
+    ConfigBuilder::new(test).sections(sections).build()
+}
+
+/// Builds a test configuration; settings of section `[general]` default to human output, no diagnostic messages, and
+/// retries without delay.
+#[derive(Debug)]
+pub struct ConfigBuilder {
+    test: String,
+    general: Vec<(&'static str, String)>,
+    sections: String,
+}
+
+impl ConfigBuilder {
+    pub fn new(test: &str) -> Self {
+        ConfigBuilder {
+            test: test.to_string(),
+            general: vec![
+                ("output_format", "{ format = 'HUMAN' }".to_string()),
+                ("verbosity", "{ verbosity = 'QUIET' }".to_string()),
+                ("retry_base_delay_ms", "0".to_string()),
+            ],
+            sections: String::new(),
+        }
+    }
+
+    /// Sets `key` of section `[general]` to the TOML value `value`, e.g., `general("response_cache_ttl", "'1h'")`.
+    pub fn general(mut self, key: &'static str, value: &str) -> Self {
+        self.general.retain(|&(k, _)| k != key);
+        self.general.push((key, value.to_string()));
+        self
+    }
+
+    /// Leaves out `key` of section `[general]`, so its default applies.
+    pub fn without_general(mut self, key: &'static str) -> Self {
+        self.general.retain(|&(k, _)| k != key);
+        self
+    }
+
+    pub fn sections(mut self, sections: &str) -> Self {
+        self.sections.push_str(sections);
+        self.sections.push('\n');
+        self
+    }
+
+    pub fn build(self) -> Config {
+        console::init(Verbosity::QUIET);
+        let cache_dir = temp_path(&self.test);
+        let _ = fs::remove_dir_all(&cache_dir);
+        let general = self.general.iter()
+            .map(|&(key, ref value)| format!("{} = {}\n", key, value))
+            .collect::<String>();
+        let defaults = ["centerdevice", "slack"].iter()
+            .filter(|module| !self.sections.contains(&format!("[{}]", module)))
+            .map(|module| format!("[{}]\nclient_id = 'client id'\nclient_secret = 'client secret'\n", module))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config = format!("[general]\ncache_dir = '{}'\n{}\n{}\n{}", cache_dir.display(), general, defaults, self.sections);
+
+        toml::from_str(&config).expect("test configuration is invalid")
+    }
+}
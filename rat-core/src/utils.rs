@@ -0,0 +1,1622 @@
+/// Diagnostic messages on stderr by level, and regular output on stdout. Keeping diagnostics off stdout keeps the output
+/// of commands clean for pipes. `Verbosity` selects the least important level shown, and colors are only used if
+/// enabled by `set_color`.
+pub mod console {
+    use config::Verbosity;
+    use super::secrets::redact;
+
+    use std;
+    use std::env;
+    use std::fs::{File, OpenOptions};
+    use std::io;
+    use std::io::{BufRead, Write};
+    use std::process::{Command, Stdio};
+    use std::ptr;
+    use std::sync::{Mutex, Once, ONCE_INIT};
+    use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+
+    static mut VERBOSITY: Option<Verbosity> = None;
+    static INIT: Once = ONCE_INIT;
+    static COLOR: AtomicBool = AtomicBool::new(false);
+    static HTTP_TRACE: AtomicPtr<HttpTrace> = AtomicPtr::new(0 as *mut HttpTrace);
+
+    /// Where `http_trace` writes to
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum HttpTraceTarget {
+        Stderr,
+        /// Appended to
+        File(String),
+    }
+
+    struct HttpTrace {
+        file: Option<Mutex<File>>,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Level {
+        Error,
+        Warn,
+        Info,
+        Debug,
+        Trace,
+    }
+
+    impl Level {
+        /// The least verbose verbosity showing this level
+        fn verbosity(&self) -> Verbosity {
+            match *self {
+                Level::Error => Verbosity::QUIET,
+                Level::Warn | Level::Info => Verbosity::NORMAL,
+                Level::Debug => Verbosity::VERBOSE,
+                Level::Trace => Verbosity::TRACE,
+            }
+        }
+
+        /// ANSI color code
+        fn color(&self) -> &'static str {
+            match *self {
+                Level::Error => "31",
+                Level::Warn => "33",
+                Level::Info => "34",
+                Level::Debug => "36",
+                Level::Trace => "90",
+            }
+        }
+    }
+
+    pub fn init(verbosity: Verbosity) {
+        unsafe {
+            INIT.call_once(|| {
+                VERBOSITY = Some(verbosity);
+            });
+        }
+    }
+
+    /// Colors diagnostic messages; cf. `supports_color`.
+    pub fn set_color(color: bool) {
+        COLOR.store(color, Ordering::Relaxed);
+    }
+
+    /// Whether stderr is a terminal that supports colors and the user has not opted out by setting 'NO_COLOR'.
+    pub fn supports_color() -> bool {
+        env::var_os("NO_COLOR").is_none() && env::var("TERM").map(|term| term != "dumb").unwrap_or(true) && is_terminal(2)
+    }
+
+    /// Whether file descriptor `fd`, e.g., 1 for stdout or 2 for stderr, is connected to a terminal
+    #[cfg(unix)]
+    pub fn is_terminal(fd: i32) -> bool {
+        use std::os::raw::c_int;
+        extern "C" {
+            fn isatty(fd: c_int) -> c_int;
+        }
+        unsafe { isatty(fd) == 1 }
+    }
+
+    #[cfg(not(unix))]
+    pub fn is_terminal(_: i32) -> bool {
+        false
+    }
+
+    /// Prints a diagnostic message on stderr if `level` is relevant for the verbosity. Secrets are masked.
+    pub fn log<T: Into<String>>(level: Level, msg: T) {
+        if is_relevant(level.verbosity()) {
+            let _ = writeln!(io::stderr(), "{}", paint(level, &redact(&msg.into())));
+        }
+    }
+
+    /// Like `verboseln`, but without line break, e.g., for progress dots.
+    pub fn verbose<T: Into<String>>(msg: T) {
+        if is_relevant(Verbosity::VERBOSE) {
+            let mut stderr = io::stderr();
+            let _ = write!(stderr, "{}", paint(Level::Debug, &redact(&msg.into())));
+            let _ = stderr.flush();
+        }
+    }
+
+    pub fn verboseln<T: Into<String>>(msg: T) {
+        log(Level::Debug, msg)
+    }
+
+    pub fn trace<T: Into<String>>(msg: T) {
+        log(Level::Trace, msg)
+    }
+
+    pub fn info<T: Into<String>>(msg: T) {
+        log(Level::Info, msg)
+    }
+
+    pub fn msgln<T: Into<String>>(msg: T) {
+        println!("{}", msg.into())
+    }
+
+    pub fn msg<T: Into<String>>(msg: T) {
+        print!("{}", msg.into());
+        let _ = std::io::stdout().flush();
+    }
+
+    /// Errors are shown even in quiet mode.
+    pub fn error<T: Into<String>>(msg: T) {
+        log(Level::Error, msg)
+    }
+
+    pub fn warning<T: Into<String>>(msg: T) {
+        log(Level::Warn, msg)
+    }
+
+    /// Enables `http_trace`; like `init`, only the first call takes effect.
+    pub fn init_http_trace(target: &HttpTraceTarget) -> io::Result<()> {
+        let file = match *target {
+            HttpTraceTarget::Stderr => None,
+            HttpTraceTarget::File(ref path) => Some(Mutex::new(OpenOptions::new().create(true).append(true).open(path)?)),
+        };
+        let http_trace = Box::into_raw(Box::new(HttpTrace { file: file }));
+        if HTTP_TRACE.compare_exchange(ptr::null_mut(), http_trace, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+            drop(unsafe { Box::from_raw(http_trace) });
+        }
+
+        Ok(())
+    }
+
+    pub fn is_tracing_http() -> bool {
+        !HTTP_TRACE.load(Ordering::Relaxed).is_null()
+    }
+
+    /// Prints a line of HTTP traffic if enabled by `init_http_trace`, regardless of the verbosity. The caller masks
+    /// secrets, cf. `secrets::redact`, because header and body fields need masking beyond the registered secrets.
+    pub fn http_trace<T: Into<String>>(line: T) {
+        let http_trace = match unsafe { HTTP_TRACE.load(Ordering::Relaxed).as_ref() } {
+            Some(http_trace) => http_trace,
+            None => return,
+        };
+        match http_trace.file {
+            Some(ref file) => {
+                if let Ok(mut file) = file.lock() {
+                    let _ = writeln!(file, "{}", line.into());
+                }
+            }
+            None => {
+                let _ = writeln!(io::stderr(), "{}", paint(Level::Trace, &line.into()));
+            }
+        }
+    }
+
+    /// Progress of a long running operation, drawn as a bar on stderr, e.g., '[##########----------] 50/100 actions'.
+    /// The bar is only drawn if enabled, stderr is a terminal, and the verbosity shows infos, so pipes stay clean.
+    pub struct Progress {
+        total: usize,
+        done: usize,
+        unit: &'static str,
+        visible: bool,
+    }
+
+    /// Width of the bar in characters
+    const PROGRESS_WIDTH: usize = 30;
+
+    impl Progress {
+        pub fn new(total: usize, unit: &'static str, enabled: bool) -> Self {
+            let visible = enabled && total > 0 && is_terminal(2) && is_relevant(Level::Info.verbosity());
+            let progress = Progress { total: total, done: 0, unit: unit, visible: visible };
+            progress.draw();
+            progress
+        }
+
+        pub fn inc(&mut self, delta: usize) {
+            self.done = std::cmp::min(self.done + delta, self.total);
+            self.draw();
+        }
+
+        /// Ends the line of the bar, so following messages start on a line of their own.
+        pub fn finish(&self) {
+            if self.visible {
+                let _ = writeln!(io::stderr());
+            }
+        }
+
+        fn render(&self) -> String {
+            let filled = if self.total == 0 { PROGRESS_WIDTH } else { PROGRESS_WIDTH * self.done / self.total };
+            format!("[{}{}] {}/{} {}", "#".repeat(filled), "-".repeat(PROGRESS_WIDTH - filled), self.done, self.total, self.unit)
+        }
+
+        fn draw(&self) {
+            if self.visible {
+                let mut stderr = io::stderr();
+                let _ = write!(stderr, "\r{}", paint(Level::Info, &self.render()));
+                let _ = stderr.flush();
+            }
+        }
+    }
+
+    /// Emphasizes part of regular output, e.g., search terms: bold if colors are enabled and stdout is a terminal, and
+    /// else enclosed in '*'.
+    pub fn emphasize(text: &str) -> String {
+        if COLOR.load(Ordering::Relaxed) && (is_terminal(1) || super::pager::is_active()) {
+            format!("\x1b[1m{}\x1b[0m", text)
+        } else {
+            format!("*{}*", text)
+        }
+    }
+
+    fn paint(level: Level, msg: &str) -> String {
+        if COLOR.load(Ordering::Relaxed) {
+            format!("\x1b[{}m{}\x1b[0m", level.color(), msg)
+        } else {
+            msg.to_string()
+        }
+    }
+
+    /// Asks for a line of input on the terminal.
+    pub fn read_line<T: Into<String>>(prompt: T) -> io::Result<String> {
+        msg(prompt);
+        let mut input = String::new();
+        io::stdin().lock().read_line(&mut input)?;
+
+        Ok(input.lines().next().unwrap_or("").to_string())
+    }
+
+    /// Asks for a secret, e.g., a password, on the terminal without echoing it.
+    pub fn read_secret<T: Into<String>>(prompt: T) -> io::Result<String> {
+        msg(prompt);
+        // Without stty, e.g., on Windows, the secret is echoed
+        let hidden = set_echo(false);
+        let mut input = String::new();
+        let result = io::stdin().lock().read_line(&mut input);
+        if hidden {
+            set_echo(true);
+            msgln("");
+        }
+        result?;
+
+        Ok(input.lines().next().unwrap_or("").to_string())
+    }
+
+    fn set_echo(on: bool) -> bool {
+        Command::new("stty").arg(if on { "echo" } else { "-echo" })
+            .stdin(Stdio::inherit())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    }
+
+    fn is_relevant(my_verbosity: Verbosity) -> bool {
+        // Libraries using rat-core may not initialize the console
+        let verbosity = unsafe { VERBOSITY.unwrap_or(Verbosity::NORMAL) };
+        my_verbosity >= verbosity
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn test_levels_by_verbosity() {
+            assert!(Level::Error.verbosity() >= Verbosity::QUIET);
+            assert!(Level::Info.verbosity() < Verbosity::QUIET);
+            assert!(Level::Info.verbosity() >= Verbosity::NORMAL);
+            assert!(Level::Debug.verbosity() < Verbosity::NORMAL);
+            assert!(Level::Trace.verbosity() < Verbosity::VERBOSE);
+            assert!(Level::Trace.verbosity() >= Verbosity::TRACE);
+        }
+
+        #[test]
+        fn test_progress_render() {
+            let mut progress = Progress::new(4, "actions", false);
+            progress.inc(1);
+
+            assert_eq!(progress.render(), "[#######-----------------------] 1/4 actions");
+            progress.inc(10);
+            assert_eq!(progress.render(), "[##############################] 4/4 actions");
+        }
+    }
+}
+
+/// Registry of secrets, e.g., access tokens and client secrets, that are masked in diagnostic messages. Regular
+/// output, i.e., `msg` and `msgln`, is not masked because some commands deliberately print tokens.
+pub mod secrets {
+    use std::sync::Mutex;
+
+    static REDACTED: &'static str = "********";
+    /// Shorter values are not considered secret; masking them would garble unrelated text.
+    static MIN_SECRET_LEN: usize = 6;
+
+    /// Shared by all threads, because concurrent requests log on worker threads, e.g., of `concurrent::map_bounded`.
+    static SECRETS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    pub fn register<T: Into<String>>(secret: T) {
+        let secret = secret.into();
+        if secret.len() < MIN_SECRET_LEN {
+            return;
+        }
+        let mut secrets = SECRETS.lock().unwrap_or_else(|e| e.into_inner());
+        if !secrets.contains(&secret) {
+            secrets.push(secret);
+            // Longer secrets first, so a secret containing another one is masked completely
+            secrets.sort_by(|a, b| b.len().cmp(&a.len()));
+        }
+    }
+
+    pub fn redact(text: &str) -> String {
+        let secrets = SECRETS.lock().unwrap_or_else(|e| e.into_inner());
+        secrets.iter().fold(text.to_string(), |redacted, secret| redacted.replace(secret.as_str(), REDACTED))
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use std::thread;
+
+        #[test]
+        fn test_redact() {
+            register("secret-token-1234");
+            register("secret-token-1234-and-more");
+            register("short");
+
+            assert_eq!(redact("token=secret-token-1234&x=secret-token-1234-and-more"), "token=********&x=********");
+            assert_eq!(redact("short and plain"), "short and plain");
+        }
+
+        #[test]
+        fn test_redact_on_other_thread() {
+            register("secret-of-main-thread");
+
+            let redacted = thread::spawn(|| redact("access_token=secret-of-main-thread")).join().unwrap();
+
+            assert_eq!(redacted, "access_token=********");
+        }
+    }
+}
+
+pub mod output {
+    use super::console::{self, msgln};
+
+    use serde::Serialize;
+    use serde_json;
+    use serde_json::Value;
+    use std::cmp;
+    use std::env;
+    use std::fs::File;
+    use std::io::{self, Read, Write};
+    use std::process::Command;
+    use std::sync::{Once, ONCE_INIT};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    error_chain! {
+        errors {
+           OutputFailed {
+                description("Failed to print message")
+                display("Failed to print message")
+            }
+           InvalidTemplate(reason: String) {
+                description("Invalid format template")
+                display("Invalid format template: {}", reason)
+            }
+           InvalidSelector(selector: String) {
+                description("Invalid selector")
+                display("Invalid selector '{}'; expected a path like '.list.*.resolved_url'", selector)
+            }
+        }
+    }
+
+    static PRETTY: AtomicBool = AtomicBool::new(false);
+
+    /// Pretty-prints all JSON output; set by `--pretty`.
+    pub fn set_pretty(pretty: bool) {
+        PRETTY.store(pretty, Ordering::Relaxed);
+    }
+
+    pub fn is_pretty() -> bool {
+        PRETTY.load(Ordering::Relaxed)
+    }
+
+    static mut TEMPLATE: *const Template = 0 as *const Template;
+    static TEMPLATE_INIT: Once = ONCE_INIT;
+    static mut SELECTOR: *const Selector = 0 as *const Selector;
+    static SELECTOR_INIT: Once = ONCE_INIT;
+
+    /// Formats the items of all listings with `template`; set by `--format`. Only the first call takes effect.
+    pub fn set_template(template: Template) {
+        unsafe {
+            TEMPLATE_INIT.call_once(|| {
+                TEMPLATE = Box::into_raw(Box::new(template));
+            });
+        }
+    }
+
+    pub fn template() -> Option<&'static Template> {
+        unsafe { TEMPLATE.as_ref() }
+    }
+
+    /// Reduces all JSON output to the values selected by `selector`; set by `--select`. Only the first call takes
+    /// effect.
+    pub fn set_selector(selector: Selector) {
+        unsafe {
+            SELECTOR_INIT.call_once(|| {
+                SELECTOR = Box::into_raw(Box::new(selector));
+            });
+        }
+    }
+
+    pub fn selector() -> Option<&'static Selector> {
+        unsafe { SELECTOR.as_ref() }
+    }
+
+    /// Dot path selecting values of a JSON document, e.g., '.list.*.resolved_url'. A segment is the key of an object or
+    /// the index of an array; '*' selects all values of an object or array. Paths with '*' select an array of all
+    /// matching values, paths without the single value or null.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Selector {
+        segments: Vec<Segment>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum Segment {
+        Key(String),
+        Any,
+    }
+
+    impl Selector {
+        pub fn parse(selector: &str) -> Result<Selector> {
+            let path = if selector.starts_with('.') { &selector[1..] } else { selector };
+            if path.is_empty() {
+                return Ok(Selector { segments: Vec::new() });
+            }
+            let mut segments = Vec::new();
+            for segment in path.split('.') {
+                match segment {
+                    "" => bail!(ErrorKind::InvalidSelector(selector.to_string())),
+                    "*" => segments.push(Segment::Any),
+                    key => segments.push(Segment::Key(key.to_string())),
+                }
+            }
+
+            Ok(Selector { segments: segments })
+        }
+
+        pub fn select(&self, document: &Value) -> Value {
+            let mut values = vec![document];
+            for segment in &self.segments {
+                values = values.into_iter().flat_map(|value| children(value, segment)).collect();
+            }
+            if self.segments.contains(&Segment::Any) {
+                Value::Array(values.into_iter().cloned().collect())
+            } else {
+                values.first().map(|v| (*v).clone()).unwrap_or(Value::Null)
+            }
+        }
+    }
+
+    fn children<'a>(value: &'a Value, segment: &Segment) -> Vec<&'a Value> {
+        match (value, segment) {
+            (&Value::Object(ref map), &Segment::Any) => map.values().collect(),
+            (&Value::Array(ref values), &Segment::Any) => values.iter().collect(),
+            (&Value::Object(ref map), &Segment::Key(ref key)) => map.get(key).into_iter().collect(),
+            (&Value::Array(ref values), &Segment::Key(ref key)) =>
+                key.parse::<usize>().ok().and_then(|i| values.get(i)).into_iter().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Line format of listed items, e.g., '{title}\t{url}'. A placeholder names a field of an item's JSON
+    /// representation, or a nested field like '{representations.pdf}'. Strings are inserted as they are, arrays and
+    /// the keys of objects separated by commas, and missing fields as empty string. '{{' and '}}' are literal braces;
+    /// '\t', '\n', and '\\' are escapes, so templates can be given in single quotes on the command line.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Template {
+        parts: Vec<Part>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum Part {
+        Text(String),
+        Field(Vec<String>),
+    }
+
+    impl Template {
+        pub fn parse(template: &str) -> Result<Template> {
+            let mut parts = Vec::new();
+            let mut text = String::new();
+            let mut chars = template.chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    '{' if chars.peek() == Some(&'{') => {
+                        chars.next();
+                        text.push('{');
+                    }
+                    '}' if chars.peek() == Some(&'}') => {
+                        chars.next();
+                        text.push('}');
+                    }
+                    '{' => {
+                        let mut field = String::new();
+                        loop {
+                            match chars.next() {
+                                Some('}') => break,
+                                Some(c) => field.push(c),
+                                None => bail!(ErrorKind::InvalidTemplate(format!("'{{{}' is not closed", field))),
+                            }
+                        }
+                        let path: Vec<String> = field.trim().split('.').map(|p| p.to_string()).collect();
+                        if path.iter().any(|p| p.is_empty()) {
+                            bail!(ErrorKind::InvalidTemplate(format!("'{{{}}}' is not a field", field)));
+                        }
+                        if !text.is_empty() {
+                            parts.push(Part::Text(text.clone()));
+                            text.clear();
+                        }
+                        parts.push(Part::Field(path));
+                    }
+                    '}' => bail!(ErrorKind::InvalidTemplate("'}' without '{'; use '}}' for a literal brace".to_string())),
+                    '\\' => match chars.next() {
+                        Some('t') => text.push('\t'),
+                        Some('n') => text.push('\n'),
+                        Some(c) => text.push(c),
+                        None => text.push('\\'),
+                    },
+                    c => text.push(c),
+                }
+            }
+            if !text.is_empty() {
+                parts.push(Part::Text(text));
+            }
+
+            Ok(Template { parts: parts })
+        }
+
+        pub fn render(&self, item: &Value) -> String {
+            let mut out = String::new();
+            for part in &self.parts {
+                match *part {
+                    Part::Text(ref text) => out.push_str(text),
+                    Part::Field(ref path) => {
+                        let value = path.iter().fold(Some(item), |value, key| value.and_then(|v| v.get(key.as_str())));
+                        out.push_str(&field(value));
+                    }
+                }
+            }
+            out
+        }
+
+        pub fn render_item<T: Serialize>(&self, item: &T) -> String {
+            self.render(&serde_json::to_value(item).unwrap_or(Value::Null))
+        }
+
+        /// Renders each item as one line.
+        pub fn render_all<T: Serialize>(&self, items: &[T]) -> String {
+            items.iter().map(|item| format!("{}\n", self.render_item(item))).collect()
+        }
+    }
+
+    fn field(value: Option<&Value>) -> String {
+        match value {
+            Some(&Value::String(ref s)) => s.clone(),
+            Some(&Value::Array(ref values)) => values.iter().map(|v| field(Some(v))).collect::<Vec<String>>().join(","),
+            Some(&Value::Object(ref map)) => map.keys().cloned().collect::<Vec<String>>().join(","),
+            Some(&Value::Null) | None => String::new(),
+            Some(value) => value.to_string(),
+        }
+    }
+
+    pub fn as_json(json: &str) -> Result<()> {
+        if let Some(selector) = selector() {
+            let document: Value = serde_json::from_str(json).chain_err(|| ErrorKind::OutputFailed)?;
+            let selected = serde_json::to_string(&selector.select(&document)).chain_err(|| ErrorKind::OutputFailed)?;
+            return print_json(&selected);
+        }
+        print_json(json)
+    }
+
+    fn print_json(json: &str) -> Result<()> {
+        if is_pretty() {
+            msgln(render_json(json, true)?);
+        } else {
+            msgln(json);
+        }
+        Ok(())
+    }
+
+    /// Returns `json` as `as_json` prints it without a selector, i.e., pretty-printed if `pretty`.
+    pub fn render_json(json: &str, pretty: bool) -> Result<String> {
+        let mut writer = JsonWriter::with_pretty(Vec::new(), pretty);
+        writer.write_all(json.as_bytes()).chain_err(|| ErrorKind::OutputFailed)?;
+        Ok(String::from_utf8_lossy(&writer.into_inner()).into_owned())
+    }
+
+    /// Copies a JSON document, e.g., a response body, to `writer` without decoding it; cf. `JsonWriter`. With a selector,
+    /// the document has to be decoded and is written to stdout like by `as_json` instead.
+    pub fn copy_json<R: Read, W: Write>(reader: &mut R, writer: W) -> Result<u64> {
+        if selector().is_some() {
+            let mut json = String::new();
+            let size = reader.read_to_string(&mut json).chain_err(|| ErrorKind::OutputFailed)?;
+            as_json(&json)?;
+            return Ok(size as u64);
+        }
+        let mut writer = JsonWriter::new(writer);
+        let size = io::copy(reader, &mut writer).chain_err(|| ErrorKind::OutputFailed)?;
+        writeln!(writer.into_inner()).chain_err(|| ErrorKind::OutputFailed)?;
+
+        Ok(size)
+    }
+
+    /// Writes JSON as it is, or pretty-printed if enabled. Pretty-printing works on the bytes passing through, so large
+    /// documents are neither decoded nor buffered. The input must be valid JSON; it is not validated.
+    pub struct JsonWriter<W: Write> {
+        inner: W,
+        pretty: bool,
+        indent: usize,
+        in_string: bool,
+        escaped: bool,
+        /// An opened array or object whose first element decides whether it breaks the line
+        pending_open: bool,
+    }
+
+    impl<W: Write> JsonWriter<W> {
+        pub fn new(inner: W) -> Self {
+            JsonWriter::with_pretty(inner, is_pretty())
+        }
+
+        /// Pretty-prints if `pretty`, regardless of `--pretty`
+        pub fn with_pretty(inner: W, pretty: bool) -> Self {
+            JsonWriter { inner: inner, pretty: pretty, indent: 0, in_string: false, escaped: false, pending_open: false }
+        }
+
+        pub fn into_inner(self) -> W {
+            self.inner
+        }
+
+        fn push(&mut self, b: u8, out: &mut Vec<u8>) {
+            if self.in_string {
+                out.push(b);
+                if self.escaped {
+                    self.escaped = false;
+                } else if b == b'\\' {
+                    self.escaped = true;
+                } else if b == b'"' {
+                    self.in_string = false;
+                }
+                return;
+            }
+            match b {
+                b' ' | b'\t' | b'\n' | b'\r' => {}
+                b'{' | b'[' => {
+                    self.start_element(out);
+                    out.push(b);
+                    self.indent += 1;
+                    self.pending_open = true;
+                }
+                b'}' | b']' => {
+                    self.indent = self.indent.saturating_sub(1);
+                    if self.pending_open {
+                        self.pending_open = false;
+                    } else {
+                        self.newline(out);
+                    }
+                    out.push(b);
+                }
+                b',' => {
+                    out.push(b);
+                    self.newline(out);
+                }
+                b':' => out.extend_from_slice(b": "),
+                _ => {
+                    self.start_element(out);
+                    out.push(b);
+                    self.in_string = b == b'"';
+                }
+            }
+        }
+
+        fn start_element(&mut self, out: &mut Vec<u8>) {
+            if self.pending_open {
+                self.pending_open = false;
+                self.newline(out);
+            }
+        }
+
+        fn newline(&self, out: &mut Vec<u8>) {
+            out.push(b'\n');
+            for _ in 0..self.indent {
+                out.extend_from_slice(b"  ");
+            }
+        }
+    }
+
+    impl<W: Write> Write for JsonWriter<W> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if !self.pretty {
+                return self.inner.write(buf);
+            }
+            let mut out = Vec::with_capacity(buf.len() + buf.len() / 2);
+            for &b in buf {
+                self.push(b, &mut out);
+            }
+            self.inner.write_all(&out)?;
+
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    /// Narrowest width columns are truncated to
+    const MIN_COLUMN_WIDTH: usize = 5;
+
+    /// Human output as table with aligned columns. If the table is wider than the maximum width, e.g., of the terminal,
+    /// the widest columns are truncated first.
+    #[derive(Debug, Clone)]
+    pub struct Table {
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+        borders: bool,
+        max_width: Option<usize>,
+    }
+
+    impl Table {
+        pub fn new<T: Into<String>>(headers: Vec<T>) -> Self {
+            Table { headers: headers.into_iter().map(|h| h.into()).collect(), rows: Vec::new(), borders: false, max_width: None }
+        }
+
+        /// Draws unicode borders around and between cells.
+        pub fn borders(mut self, borders: bool) -> Self {
+            self.borders = borders;
+            self
+        }
+
+        /// Truncates columns to fit into `max_width` characters; cf. `terminal_width`.
+        pub fn max_width(mut self, max_width: Option<usize>) -> Self {
+            self.max_width = max_width;
+            self
+        }
+
+        /// Line breaks of cells are replaced by spaces to keep one line per row.
+        pub fn add_row<T: Into<String>>(&mut self, row: Vec<T>) {
+            let row = row.into_iter().map(|cell| cell.into().replace(|c| c == '\n' || c == '\r', " ")).collect();
+            self.rows.push(row);
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.rows.is_empty()
+        }
+
+        pub fn render(&self) -> String {
+            let widths = self.widths();
+            let mut out = String::new();
+            if self.borders {
+                out.push_str(&self.rule(&widths, '┌', '┬', '┐'));
+                out.push_str(&self.line(&self.headers, &widths));
+                out.push_str(&self.rule(&widths, '├', '┼', '┤'));
+            } else {
+                out.push_str(&self.line(&self.headers, &widths));
+            }
+            for row in &self.rows {
+                out.push_str(&self.line(row, &widths));
+            }
+            if self.borders {
+                out.push_str(&self.rule(&widths, '└', '┴', '┘'));
+            }
+
+            out
+        }
+
+        fn widths(&self) -> Vec<usize> {
+            let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+            for row in &self.rows {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = cmp::max(*width, cell.chars().count());
+                }
+            }
+            let max_width = match self.max_width {
+                Some(max_width) => max_width,
+                None => return widths,
+            };
+            let separators = if self.borders { 3 * widths.len() + 1 } else { 2 * widths.len().saturating_sub(1) };
+            while widths.iter().sum::<usize>() + separators > max_width {
+                let widest = (0..widths.len()).max_by_key(|&i| widths[i]);
+                match widest {
+                    Some(i) if widths[i] > MIN_COLUMN_WIDTH => widths[i] -= 1,
+                    _ => break,
+                }
+            }
+
+            widths
+        }
+
+        fn line(&self, cells: &[String], widths: &[usize]) -> String {
+            let cells: Vec<String> = widths.iter().enumerate()
+                .map(|(i, &width)| format!("{:<width$}", truncate(cells.get(i).map(|c| c.as_str()).unwrap_or(""), width), width = width))
+                .collect();
+            if self.borders {
+                format!("│ {} │\n", cells.join(" │ "))
+            } else {
+                let mut line = cells.join("  ");
+                while line.ends_with(' ') {
+                    line.pop();
+                }
+                line.push('\n');
+                line
+            }
+        }
+
+        fn rule(&self, widths: &[usize], left: char, middle: char, right: char) -> String {
+            let segments: Vec<String> = widths.iter().map(|&width| "─".repeat(width + 2)).collect();
+            format!("{}{}{}\n", left, segments.join(&middle.to_string()), right)
+        }
+    }
+
+    /// Shortens `text` to `width` characters, marking the cut with '…'.
+    pub fn truncate(text: &str, width: usize) -> String {
+        if text.chars().count() <= width {
+            return text.to_string();
+        }
+        let mut truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+        truncated.push('…');
+        truncated
+    }
+
+    /// Breaks lines between words; words longer than `width` get a line of their own.
+    pub fn wrap(paragraph: &str, width: usize) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        let mut line_width = 0;
+        for word in paragraph.split_whitespace() {
+            let word_width = word.chars().count();
+            if line_width > 0 && line_width + 1 + word_width > width {
+                lines.push(line);
+                line = String::new();
+                line_width = 0;
+            }
+            if line_width > 0 {
+                line.push(' ');
+                line_width += 1;
+            }
+            line.push_str(word);
+            line_width += word_width;
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+
+        lines
+    }
+
+    /// Width of the terminal, or `None` if stdout is neither a terminal nor a pager, so redirected output is never
+    /// truncated.
+    pub fn terminal_width() -> Option<usize> {
+        if !console::is_terminal(1) && !super::pager::is_active() {
+            return None;
+        }
+        if let Some(columns) = env::var("COLUMNS").ok().and_then(|c| c.parse().ok()) {
+            return Some(columns);
+        }
+        File::open("/dev/tty").ok()
+            .and_then(|tty| Command::new("stty").arg("size").stdin(tty).output().ok())
+            .and_then(|output| String::from_utf8_lossy(&output.stdout).split_whitespace().nth(1).and_then(|c| c.parse().ok()))
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn test_template_render() {
+            let template = Template::parse(r"- [{resolved_title}]({resolved_url})\t{tags} {{{missing}}} {image.src}").unwrap();
+            let item: Value = serde_json::from_str(r#"{"resolved_title":"Rust","resolved_url":"https://rust-lang.org",
+                "tags":{"rust":{},"lang":{}},"image":{"src":"logo.png"}}"#).unwrap();
+
+            assert_eq!(template.render(&item), "- [Rust](https://rust-lang.org)\tlang,rust {} logo.png");
+        }
+
+        #[test]
+        fn test_template_parse_errors() {
+            assert!(Template::parse("{title").is_err());
+            assert!(Template::parse("title}").is_err());
+            assert!(Template::parse("{a..b}").is_err());
+            assert_eq!(Template::parse("{ title }").unwrap(), Template::parse("{title}").unwrap());
+        }
+
+        #[test]
+        fn test_selector_select() {
+            let document: Value = serde_json::from_str(r#"{"status":1,"list":{"1":{"resolved_url":"https://a.com","tags":["x"]},
+                "2":{"resolved_url":"https://b.com"}},"documents":[{"id":"d1"},{"id":"d2"}]}"#).unwrap();
+            let select = |selector: &str| serde_json::to_string(&Selector::parse(selector).unwrap().select(&document)).unwrap();
+
+            assert_eq!(select(".list.*.resolved_url"), r#"["https://a.com","https://b.com"]"#);
+            assert_eq!(select(".documents.1.id"), r#""d2""#);
+            assert_eq!(select("status"), "1");
+            assert_eq!(select(".list.*.tags.*"), r#"["x"]"#);
+            assert_eq!(select(".missing"), "null");
+            assert_eq!(select("."), serde_json::to_string(&document).unwrap());
+            assert!(Selector::parse(".list..id").is_err());
+        }
+
+        fn table() -> Table {
+            let mut table = Table::new(vec!["ID", "TITLE", "TAGS"]);
+            table.add_row(vec!["1", "Rust 1.0 released", "rust"]);
+            table.add_row(vec!["23", "Error handling\nin Rust", ""]);
+            table
+        }
+
+        #[test]
+        fn test_wrap_long_word() {
+            assert_eq!(wrap("see https://example.com/a/long/path here", 10), vec!["see", "https://example.com/a/long/path", "here"]);
+        }
+
+        #[test]
+        fn test_table_aligns_columns() {
+            assert_eq!(table().render(), "\
+ID  TITLE                   TAGS
+1   Rust 1.0 released       rust
+23  Error handling in Rust
+");
+        }
+
+        #[test]
+        fn test_table_with_borders() {
+            assert_eq!(table().borders(true).render(), "\
+┌────┬────────────────────────┬──────┐
+│ ID │ TITLE                  │ TAGS │
+├────┼────────────────────────┼──────┤
+│ 1  │ Rust 1.0 released      │ rust │
+│ 23 │ Error handling in Rust │      │
+└────┴────────────────────────┴──────┘
+");
+        }
+
+        #[test]
+        fn test_table_truncates_widest_column() {
+            let out = table().max_width(Some(24)).render();
+
+            assert_eq!(out, "\
+ID  TITLE           TAGS
+1   Rust 1.0 rele…  rust
+23  Error handlin…
+");
+            assert!(out.lines().all(|line| line.chars().count() <= 24));
+        }
+
+        fn pretty(json: &str, chunk_size: usize) -> String {
+            let mut writer = JsonWriter::new(Vec::new());
+            writer.pretty = true;
+            for chunk in json.as_bytes().chunks(chunk_size) {
+                writer.write_all(chunk).unwrap();
+            }
+            String::from_utf8(writer.into_inner()).unwrap()
+        }
+
+        #[test]
+        fn test_json_writer_pretty_prints_like_serde() {
+            // Keys are sorted as by `Value`
+            let json = r#"{"ids" : [1,2.5,true,null],"list":{"1":{"tags":[],"title":"a, \"b\": {c}"},"2":{}},"status":1}"#;
+            let expected = serde_json::to_string_pretty(&serde_json::from_str::<Value>(json).unwrap()).unwrap();
+
+            // Chunks split escapes and strings across writes
+            for &chunk_size in &[1, 3, 1024] {
+                assert_eq!(pretty(json, chunk_size), expected);
+            }
+        }
+
+        #[test]
+        fn test_json_writer_passes_through_by_default() {
+            let mut writer = JsonWriter::new(Vec::new());
+
+            writer.write_all(br#"{"a": [1, 2]}"#).unwrap();
+
+            assert_eq!(writer.into_inner(), br#"{"a": [1, 2]}"#.to_vec());
+        }
+    }
+}
+
+/// Shows regular output in a pager like git does: stdout is redirected to `$PAGER`, or 'less' if unset, until `finish`.
+/// Unless 'LESS' is set, 'less' quits by itself if the output fits on one screen, so short output looks as without pager.
+pub mod pager {
+    use super::console;
+
+    use std::env;
+    use std::io::{self, Write};
+    use std::process::{Child, Command, Stdio};
+    use std::ptr;
+    use std::sync::atomic::{AtomicPtr, Ordering};
+
+    pub const DEFAULT_PAGER: &'static str = "less";
+
+    /// Options of 'less' unless set by 'LESS': quit if one screen, pass colors, and keep the screen on exit
+    const DEFAULT_LESS: &'static str = "FRX";
+
+    struct Pager {
+        child: Child,
+        /// Duplicate of the original stdout
+        stdout: i32,
+    }
+
+    static PAGER: AtomicPtr<Pager> = AtomicPtr::new(0 as *mut Pager);
+
+    /// `$PAGER`, or 'less' if unset; `None` if set to '' or 'cat', which disables paging.
+    pub fn command() -> Option<String> {
+        match env::var("PAGER") {
+            Ok(ref pager) if pager.trim().is_empty() || pager.trim() == "cat" => None,
+            Ok(pager) => Some(pager),
+            Err(_) => Some(DEFAULT_PAGER.to_string()),
+        }
+    }
+
+    /// Whether stdout is redirected to a pager; the pager's terminal counts as stdout's, e.g., for `output::terminal_width`.
+    pub fn is_active() -> bool {
+        !PAGER.load(Ordering::SeqCst).is_null()
+    }
+
+    /// Starts the pager and redirects stdout to it if stdout is a terminal. Does nothing if paging is disabled or the
+    /// pager cannot be started. Writing after the user quit the pager ends rat silently, as it does for other programs
+    /// in a pipe.
+    #[cfg(unix)]
+    pub fn start() -> bool {
+        use std::os::raw::c_int;
+        use std::os::unix::io::AsRawFd;
+        extern "C" {
+            fn dup(fd: c_int) -> c_int;
+            fn dup2(src: c_int, dst: c_int) -> c_int;
+            fn signal(signum: c_int, handler: usize) -> usize;
+        }
+        const SIGPIPE: c_int = 13;
+        const SIG_DFL: usize = 0;
+
+        let pager = match command() {
+            Some(pager) if console::is_terminal(1) && !is_active() => pager,
+            _ => return false,
+        };
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(&pager).stdin(Stdio::piped());
+        if env::var_os("LESS").is_none() {
+            command.env("LESS", DEFAULT_LESS);
+        }
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(_) => return false,
+        };
+        let _ = io::stdout().flush();
+        let stdout = unsafe { dup(1) };
+        // The pipe stays open as stdout after the handle is dropped
+        let redirected = stdout >= 0 && child.stdin.take().map(|stdin| unsafe { dup2(stdin.as_raw_fd(), 1) } >= 0).unwrap_or(false);
+        if !redirected {
+            let _ = child.kill();
+            let _ = child.wait();
+            return false;
+        }
+        unsafe { signal(SIGPIPE, SIG_DFL) };
+        PAGER.store(Box::into_raw(Box::new(Pager { child: child, stdout: stdout })), Ordering::SeqCst);
+
+        true
+    }
+
+    #[cfg(not(unix))]
+    pub fn start() -> bool {
+        false
+    }
+
+    /// Restores stdout and waits until the user quits the pager; does nothing if no pager runs.
+    #[cfg(unix)]
+    pub fn finish() {
+        use std::os::raw::c_int;
+        extern "C" {
+            fn dup2(src: c_int, dst: c_int) -> c_int;
+            fn close(fd: c_int) -> c_int;
+        }
+
+        let pager = PAGER.swap(ptr::null_mut(), Ordering::SeqCst);
+        if pager.is_null() {
+            return;
+        }
+        let mut pager = unsafe { Box::from_raw(pager) };
+        let _ = io::stdout().flush();
+        // Closes the pipe, so the pager sees the end of the output
+        unsafe {
+            dup2(pager.stdout, 1);
+            close(pager.stdout);
+        }
+        let _ = pager.child.wait();
+    }
+
+    #[cfg(not(unix))]
+    pub fn finish() {}
+}
+
+pub mod time {
+    use std::time::{Duration, UNIX_EPOCH, SystemTime};
+    use humantime;
+
+    error_chain! {
+        errors {
+            FailedToParseDuration {
+                description("Failed to parse duration")
+                display("Failed to parse duration")
+            }
+        }
+    }
+    pub fn parse_duration(since: &str) -> Result<Duration> {
+        let duration: Duration = humantime::parse_duration(since).chain_err(|| ErrorKind::FailedToParseDuration)?;
+        let now = SystemTime::now();
+        let history = (now - duration).duration_since(UNIX_EPOCH).chain_err(|| ErrorKind::FailedToParseDuration)?;
+
+        Ok(history)
+    }
+
+    pub fn parse_duration_to_unix_ts(since: &str) -> Result<u64> {
+        let unix_ts = parse_duration(since)?.as_secs();
+
+        Ok(unix_ts)
+    }
+
+    /// Parses a duration like '5m' or '1h 30m'.
+    pub fn parse_interval(interval: &str) -> Result<Duration> {
+        humantime::parse_duration(interval).chain_err(|| ErrorKind::FailedToParseDuration)
+    }
+
+        pub fn unix_now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+    }
+}
+
+/// Points in time as given on the command line, e.g., '2w' for two weeks ago or '2023-01-01', and as sent by APIs,
+/// e.g., '2017-07-14T02:40:00Z'. Points in time are durations since the Unix epoch like the times of Pocket articles.
+pub mod datetime {
+    use std::time::{Duration, UNIX_EPOCH, SystemTime};
+    use humantime;
+
+    error_chain! {
+        errors {
+            InvalidPointInTime(point: String) {
+                description("Invalid point in time")
+                display("Invalid point in time '{}'; expected a duration ago like '2w 3d' or a date like '2023-01-01'", point)
+            }
+        }
+    }
+
+    /// Parses a duration ago like '2w 3d', or an ISO 8601 date or date and time like '2023-01-01' or
+    /// '2023-01-01T12:00:00+02:00'; without time zone, dates and times are UTC.
+    pub fn parse(point: &str) -> Result<Duration> {
+        let point = point.trim();
+        if let Ok(ago) = humantime::parse_duration(point) {
+            return SystemTime::now().checked_sub(ago)
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .ok_or_else(|| ErrorKind::InvalidPointInTime(point.to_string()).into());
+        }
+
+        parse_iso8601(point)
+    }
+
+    /// Parses an ISO 8601 date, or date and time with optional seconds, fraction, and time zone as 'Z', '+02:00', or
+    /// '+0200'.
+    pub fn parse_iso8601(point: &str) -> Result<Duration> {
+        let invalid = || Error::from(ErrorKind::InvalidPointInTime(point.to_string()));
+        let (date, time) = match point.find(|c| c == 'T' || c == ' ') {
+            Some(i) => (&point[..i], &point[i + 1..]),
+            None => (point, ""),
+        };
+        let date: Vec<&str> = date.split('-').collect();
+        if date.len() != 3 || date[0].len() != 4 {
+            return Err(invalid());
+        }
+        let (year, month, day) = (number(date[0])?, number(date[1])?, number(date[2])?);
+        if month < 1 || month > 12 || day < 1 || day > days_in_month(year, month) {
+            return Err(invalid());
+        }
+
+        let (time, offset) = match time.find(|c| c == 'Z' || c == '+' || c == '-') {
+            Some(i) => (&time[..i], zone_offset(&time[i..]).ok_or_else(&invalid)?),
+            None => (time, 0),
+        };
+        let mut seconds = 0;
+        if !time.is_empty() {
+            let time: Vec<&str> = time.splitn(2, '.').next().unwrap_or("").split(':').collect();
+            if time.len() < 2 || time.len() > 3 {
+                return Err(invalid());
+            }
+            let (hours, minutes) = (number(time[0])?, number(time[1])?);
+            let secs = if time.len() == 3 { number(time[2])? } else { 0 };
+            if hours > 23 || minutes > 59 || secs > 60 {
+                return Err(invalid());
+            }
+            seconds = hours * 3600 + minutes * 60 + secs;
+        }
+
+        let timestamp = days_from_civil(year, month, day) * 86400 + seconds - offset;
+        if timestamp < 0 {
+            return Err(invalid());
+        }
+
+        Ok(Duration::from_secs(timestamp as u64))
+    }
+
+    /// Parses an RFC 2822 date and time like '14 Jul 2017 02:40:00 +0000' as in RSS feeds, with optional day of week,
+    /// optional seconds, and time zone as offset like '+0200' or as name like 'GMT' or 'EST'.
+    pub fn parse_rfc2822(point: &str) -> Result<Duration> {
+        let invalid = || Error::from(ErrorKind::InvalidPointInTime(point.to_string()));
+        let point = match point.find(',') {
+            Some(i) => &point[i + 1..],
+            None => point,
+        };
+        let parts: Vec<&str> = point.split_whitespace().collect();
+        if parts.len() < 4 {
+            return Err(invalid());
+        }
+        let day = number(parts[0])?;
+        let month = MONTHS.iter().position(|m| parts[1].to_lowercase().starts_with(m)).ok_or_else(&invalid)? as i64 + 1;
+        let year = match number(parts[2])? {
+            // Two digit years of obsolete syntax
+            year if year < 50 => year + 2000,
+            year if year < 1000 => year + 1900,
+            year => year,
+        };
+        if day < 1 || day > days_in_month(year, month) {
+            return Err(invalid());
+        }
+        let time: Vec<&str> = parts[3].split(':').collect();
+        if time.len() < 2 || time.len() > 3 {
+            return Err(invalid());
+        }
+        let (hours, minutes) = (number(time[0])?, number(time[1])?);
+        let secs = if time.len() == 3 { number(time[2])? } else { 0 };
+        if hours > 23 || minutes > 59 || secs > 60 {
+            return Err(invalid());
+        }
+        let offset = match parts.get(4) {
+            Some(zone) if zone.starts_with('+') || zone.starts_with('-') => zone_offset(zone).ok_or_else(&invalid)?,
+            Some(zone) => ZONES.iter().find(|&&(name, _)| name.eq_ignore_ascii_case(zone)).map(|&(_, hours)| hours * 3600).unwrap_or(0),
+            None => 0,
+        };
+
+        let timestamp = days_from_civil(year, month, day) * 86400 + hours * 3600 + minutes * 60 + secs - offset;
+        if timestamp < 0 {
+            return Err(invalid());
+        }
+
+        Ok(Duration::from_secs(timestamp as u64))
+    }
+
+    static MONTHS: [&'static str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+    /// Time zone names of RFC 2822 with their offsets in hours; unknown names count as UTC.
+    static ZONES: [(&'static str, i64); 10] = [
+        ("UT", 0), ("GMT", 0), ("Z", 0), ("EST", -5), ("EDT", -4), ("CST", -6), ("CDT", -5), ("MST", -7), ("MDT", -6), ("PST", -8),
+    ];
+
+    fn number(s: &str) -> Result<i64> {
+        if s.is_empty() || !s.chars().all(|c| c.is_ascii_digit()) {
+            bail!(ErrorKind::InvalidPointInTime(s.to_string()));
+        }
+        s.parse().chain_err(|| ErrorKind::InvalidPointInTime(s.to_string()))
+    }
+
+    /// Seconds east of UTC
+    fn zone_offset(zone: &str) -> Option<i64> {
+        if zone == "Z" {
+            return Some(0);
+        }
+        let sign = if zone.starts_with('-') { -1 } else { 1 };
+        let digits: String = zone[1..].chars().filter(|&c| c != ':').collect();
+        if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let hours: i64 = digits[..2].parse().ok()?;
+        let minutes: i64 = digits[2..].parse().ok()?;
+
+        Some(sign * (hours * 3600 + minutes * 60))
+    }
+
+    fn days_in_month(year: i64, month: i64) -> i64 {
+        match month {
+            2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    /// Days since 1970-01-01 of a date of the proleptic Gregorian calendar; cf.
+    /// http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = if year >= 0 { year } else { year - 399 } / 400;
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+        era * 146097 + day_of_era - 719468
+    }
+
+    /// Selects items added at or after `since` and before `before`; cf. `parse`.
+    #[derive(Debug, Default, Clone, Copy, PartialEq)]
+    pub struct Range {
+        pub since: Option<Duration>,
+        pub before: Option<Duration>,
+    }
+
+    impl Range {
+        pub fn parse(since: Option<&str>, before: Option<&str>) -> Result<Self> {
+            let parse_optional = |point: Option<&str>| match point {
+                Some(point) => parse(point).map(Some),
+                None => Ok(None),
+            };
+
+            Ok(Range { since: parse_optional(since)?, before: parse_optional(before)? })
+        }
+
+        pub fn is_unbounded(&self) -> bool {
+            self.since.is_none() && self.before.is_none()
+        }
+
+        pub fn contains(&self, point: Duration) -> bool {
+            self.since.map(|since| point >= since).unwrap_or(true) && self.before.map(|before| point < before).unwrap_or(true)
+        }
+
+        /// Like `contains` for an ISO 8601 timestamp; unparsable timestamps are only contained in an unbounded range.
+        pub fn contains_iso8601(&self, timestamp: &str) -> bool {
+            self.is_unbounded() || parse_iso8601(timestamp).map(|point| self.contains(point)).unwrap_or(false)
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn test_parse_iso8601() {
+            assert_eq!(parse_iso8601("1970-01-01").unwrap(), Duration::from_secs(0));
+            assert_eq!(parse_iso8601("2017-07-14T02:40:00Z").unwrap(), Duration::from_secs(1500000000));
+            assert_eq!(parse_iso8601("2017-07-14T04:40:00+02:00").unwrap(), Duration::from_secs(1500000000));
+            assert_eq!(parse_iso8601("2017-07-14T02:40:00+0000").unwrap(), Duration::from_secs(1500000000));
+            assert_eq!(parse_iso8601("2017-07-14 02:40").unwrap(), Duration::from_secs(1500000000));
+            assert_eq!(parse_iso8601("2024-02-29").unwrap(), Duration::from_secs(1709164800));
+            for invalid in &["2023-02-29", "2023-13-01", "23-01-01", "2023-01-01T25:00", "2023-01-01T12:00+2", "yesterday"] {
+                assert!(parse_iso8601(invalid).is_err(), "'{}' should be invalid", invalid);
+            }
+        }
+
+        #[test]
+        fn test_parse_rfc2822() {
+            assert_eq!(parse_rfc2822("Fri, 14 Jul 2017 02:40:00 +0000").unwrap(), Duration::from_secs(1500000000));
+            assert_eq!(parse_rfc2822("14 Jul 2017 04:40 +0200").unwrap(), Duration::from_secs(1500000000));
+            assert_eq!(parse_rfc2822("Thu, 13 Jul 2017 21:40:00 EST").unwrap(), Duration::from_secs(1500000000));
+            assert_eq!(parse_rfc2822("Fri, 14 Jul 17 02:40:00 GMT").unwrap(), Duration::from_secs(1500000000));
+            for invalid in &["Fri, 31 Feb 2017 02:40:00 +0000", "14 Foo 2017 02:40", "14 Jul 2017", "2017-07-14T02:40:00Z"] {
+                assert!(parse_rfc2822(invalid).is_err(), "'{}' should be invalid", invalid);
+            }
+        }
+
+        #[test]
+        fn test_parse_duration_ago() {
+            let two_weeks_ago = parse("2w").unwrap();
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+
+            assert!(now - two_weeks_ago >= Duration::from_secs(14 * 86400));
+            assert!(now - two_weeks_ago < Duration::from_secs(14 * 86400 + 60));
+        }
+
+        #[test]
+        fn test_range() {
+            let range = Range::parse(Some("2017-07-01"), Some("2017-08-01")).unwrap();
+
+            assert!(range.contains(Duration::from_secs(1500000000)));
+            assert!(!range.contains(parse_iso8601("2017-08-01").unwrap()));
+            assert!(range.contains_iso8601("2017-07-14T02:40:00+0000"));
+            assert!(!range.contains_iso8601("unknown"));
+            assert!(Range::default().contains_iso8601("unknown"));
+            assert!(Range::parse(Some("soon"), None).is_err());
+        }
+    }
+}
+
+/// Helpers for asserting the shape of serialized requests.
+#[cfg(test)]
+pub mod json {
+    use serde_json;
+    use serde_json::Value;
+    use serde::Serialize;
+
+    pub fn to_value<T: Serialize>(request: &T) -> Value {
+        let json = serde_json::to_string(request).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    /// Returns the sorted keys of a JSON object.
+    pub fn keys(value: &Value) -> Vec<String> {
+        let mut keys: Vec<String> = value.as_object().expect("JSON object").keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    pub fn sorted(keys: &[&str]) -> Vec<String> {
+        let mut keys: Vec<String> = keys.iter().map(|k| k.to_string()).collect();
+        keys.sort();
+        keys
+    }
+}
+
+pub mod io {
+    use std::io::{Read, Result};
+
+    pub struct ReadWithProgress<'a, T: Read + 'a, F: FnMut(usize, usize) -> () + 'a> {
+        read: &'a mut T,
+        size: usize,
+        progress: Option<F>
+    }
+
+    impl<'a, T: Read + 'a, F: FnMut(usize, usize) -> () + 'a> ReadWithProgress<'a, T, F> {
+        pub fn new(read: &'a mut T, size: usize, progress: Option<F>) -> Self {
+            ReadWithProgress { read: read, size: size, progress: progress }
+        }
+    }
+
+    impl<'a, T: Read + 'a, F: FnMut(usize, usize) -> () + 'a> Read for ReadWithProgress<'a, T, F> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let result = self.read.read(buf);
+            if self.progress.is_some() && result.is_ok() {
+                let delta = *result.as_ref().unwrap();
+                let mut progress = self.progress.as_mut().unwrap();
+                progress(self.size, delta);
+            }
+            result
+        }
+    }
+}
+/// Allocation counters for `rat dev profile`. Allocations are only counted if the binary installs `CountingAllocator`
+/// as its global allocator; `rat` does if built with feature `profiling`.
+#[cfg(feature = "profiling")]
+pub mod alloc {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+    static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+    static CURRENT_BYTES: AtomicUsize = AtomicUsize::new(0);
+    static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+    /// The system allocator plus counters; the counters use relaxed atomics, so they are cheap enough to be always on.
+    pub struct CountingAllocator;
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                count_alloc(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            CURRENT_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                CURRENT_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+                count_alloc(new_size);
+            }
+            new_ptr
+        }
+    }
+
+    fn count_alloc(size: usize) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(size, Ordering::Relaxed);
+        let current = CURRENT_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+        let mut peak = PEAK_BYTES.load(Ordering::Relaxed);
+        while current > peak {
+            match PEAK_BYTES.compare_exchange_weak(peak, current, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => break,
+                Err(p) => peak = p,
+            }
+        }
+    }
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+    pub struct AllocStats {
+        /// Number of allocations and reallocations
+        pub allocations: usize,
+        pub allocated_bytes: usize,
+        /// Heap in use
+        pub current_bytes: usize,
+        /// Maximum heap in use since the last `reset_peak`
+        pub peak_bytes: usize,
+    }
+
+    impl AllocStats {
+        /// Allocations since `earlier`; the peak is the increase of the heap in use over `earlier`.
+        pub fn since(&self, earlier: &AllocStats) -> AllocStats {
+            AllocStats {
+                allocations: self.allocations - earlier.allocations,
+                allocated_bytes: self.allocated_bytes - earlier.allocated_bytes,
+                current_bytes: self.current_bytes,
+                peak_bytes: self.peak_bytes.saturating_sub(earlier.current_bytes),
+            }
+        }
+    }
+
+    pub fn stats() -> AllocStats {
+        AllocStats {
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+            current_bytes: CURRENT_BYTES.load(Ordering::Relaxed),
+            peak_bytes: PEAK_BYTES.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn reset_peak() {
+        PEAK_BYTES.store(CURRENT_BYTES.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    /// Whether `CountingAllocator` is installed; every program allocates before `main`.
+    pub fn is_counting() -> bool {
+        ALLOCATIONS.load(Ordering::Relaxed) > 0
+    }
+}
+
+/// Shares repeated strings, e.g., the domains of thousands of articles, instead of allocating one `String` per item.
+pub mod intern {
+    use std::collections::HashSet;
+    use std::rc::Rc;
+
+    #[derive(Debug, Default)]
+    pub struct Interner {
+        strings: HashSet<Rc<str>>,
+    }
+
+    impl Interner {
+        pub fn new() -> Self {
+            Interner::default()
+        }
+
+        /// Returns the shared copy of `s`; only the first occurrence allocates.
+        pub fn intern(&mut self, s: &str) -> Rc<str> {
+            if let Some(interned) = self.strings.get(s) {
+                return interned.clone();
+            }
+            let interned: Rc<str> = Rc::from(s);
+            self.strings.insert(interned.clone());
+            interned
+        }
+
+        /// Drops strings no longer used outside of the interner.
+        pub fn shrink(&mut self) {
+            self.strings.retain(|s| Rc::strong_count(s) > 1);
+        }
+
+        pub fn len(&self) -> usize {
+            self.strings.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.strings.is_empty()
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn test_intern_shares_strings() {
+            let mut interner = Interner::new();
+
+            let a = interner.intern("rust-lang.org");
+            let b = interner.intern("rust-lang.org");
+            interner.intern("golang.org");
+
+            assert!(Rc::ptr_eq(&a, &b));
+            assert_eq!(interner.len(), 2);
+        }
+
+        #[test]
+        fn test_shrink_drops_unused_strings() {
+            let mut interner = Interner::new();
+            let kept = interner.intern("rust-lang.org");
+            interner.intern("golang.org");
+
+            interner.shrink();
+
+            assert_eq!(interner.len(), 1);
+            assert_eq!(&*kept, "rust-lang.org");
+        }
+    }
+}
@@ -1,56 +1,129 @@
 extern crate rat;
 extern crate clap;
+extern crate serde_json;
 
+use rat::cache::gc;
+use rat::cache::responses;
+use rat::clients::pocket;
 use rat::config::*;
+use rat::credentials;
+use rat::credentials::InjectedToken;
 use rat::errors::*;
-use rat::modules::centerdevice;
-use rat::modules::pocket;
-use rat::modules::slack;
-use rat::modules::stocks;
+use rat::modules;
+use rat::modules::Context;
+use rat::modules::api;
+use rat::modules::auth;
+use rat::modules::cache;
+use rat::modules::config;
+use rat::modules::dev;
+use rat::modules::doctor;
+use rat::modules::flush;
+use rat::net::http;
 use rat::utils::*;
+#[cfg(feature = "profiling")]
+use rat::utils::alloc::CountingAllocator;
 
-use clap::{Arg, ArgMatches, App, Shell};
+use clap::{Arg, ArgMatches, App, AppSettings, Shell, SubCommand};
 use std::env;
-use std::io;
+use std::io::{self, Write};
 use std::path::Path;
 
 static BIN_NAME: &'static str = "rat";
-static DEFAULT_CONFIG_FILE: &'static str = "rat.toml";
 static VERSION: &'static str = env!("CARGO_PKG_VERSION");
+static HELP_NAME: &'static str = "help";
+static COMPLETIONS_NAME: &'static str = "completions";
+
+// Counts allocations for `rat dev profile`
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
 
 fn main() {
-    if let Err(ref e) = run() {
-        console::error(format!("{} failed because {}", BIN_NAME, e));
+    let cli_args = build_cli().get_matches();
+    console::set_color(!cli_args.is_present("no-color") && console::supports_color());
 
-        for e in e.iter().skip(1) {
-            console::error(format!("caused by: {}", e));
-        }
+    let mut output_format = None;
+    let result = run(&cli_args, &mut output_format);
+    // Errors are shown after the user quit the pager
+    pager::finish();
+    if let Err(ref e) = result {
+        // Errors may occur before the configuration could set the verbosity
+        console::init(Verbosity::NORMAL);
+        // The configured output format includes the configuration file and the environment; only if the configuration
+        // could not be loaded, the command line decides. '--select' implies JSON output, so scripts selecting values get
+        // JSON errors, too.
+        let json_output = match output_format {
+            Some(output_format) => output_format.is_json(),
+            None => cli_args.value_of("output-format").map(|f| f.ends_with("json")).unwrap_or(false)
+                || (cli_args.is_present("select") && !cli_args.is_present("format")),
+        };
+        if json_output {
+            // Like all errors on stderr, so stdout only carries the output of the command
+            let report = serde_json::to_string(&e.report()).unwrap_or_default();
+            let _ = writeln!(io::stderr(), "{}", secrets::redact(&report));
+        } else {
+            console::error(format!("{} failed because {}", BIN_NAME, e));
 
-        // The backtrace is not always generated. Try to run this example
-        // with `RUST_BACKTRACE=1`.
-        if let Some(backtrace) = e.backtrace() {
-            console::error(format!("backtrace: {:?}", backtrace));
+            for e in e.iter().skip(1) {
+                console::error(format!("caused by: {}", e));
+            }
+            if let Some(hint) = e.hint() {
+                console::error(format!("hint: {}", hint));
+            }
+
+            // The backtrace is not always generated. Try to run this example
+            // with `RUST_BACKTRACE=1`.
+            if let Some(backtrace) = e.backtrace() {
+                console::error(format!("backtrace: {:?}", backtrace));
+            }
         }
 
-        ::std::process::exit(1);
+        ::std::process::exit(e.category().exit_code());
     }
 }
 
-fn run() -> Result<()> {
-    let cli_args = build_cli().get_matches();
+/// Sets `output_format` to the output format resolved from configuration and command line as soon as it is known.
+fn run(cli_args: &ArgMatches, output_format: &mut Option<OutputFormat>) -> Result<()> {
+    // Kept for scripts written before the subcommand existed
+    if let Some(shell) = cli_args.value_of("completions") {
+        return print_completions(shell);
+    }
 
-    if cli_args.is_present("completions") {
-        let shell= cli_args.value_of("completions").unwrap();
-        build_cli().gen_completions_to(BIN_NAME, shell.parse::<Shell>().unwrap(), &mut io::stdout());
-        return Ok(());
+    let subcommand = cli_args.subcommand_name().ok_or(ErrorKind::NoCommandSpecified)?;
+    if subcommand == HELP_NAME {
+        return print_help(&modules::stub_args(cli_args.subcommand_matches(subcommand)));
     }
+    if subcommand == COMPLETIONS_NAME {
+        return print_completions(cli_args.subcommand_matches(subcommand).and_then(|args| args.value_of("shell")).unwrap());
+    }
+    // Only the dispatched module builds its complete CLI; cf. `Module::build_stub_cli`
+    let registry = modules::registry();
+    let module = registry.iter().find(|module| module.name() == subcommand).ok_or(ErrorKind::NoCommandSpecified)?;
+    let module_args = modules::parse_module_args(module.as_ref(), BIN_NAME, &modules::stub_args(cli_args.subcommand_matches(subcommand)))
+        .unwrap_or_else(|e| e.exit());
 
-    let default_config_path = format!("{}/.{}", env::home_dir().unwrap().display(), DEFAULT_CONFIG_FILE);
-    let config_path = Path::new(cli_args.value_of("configfile").unwrap_or(&default_config_path));
-    let mut config = Config::from_file(config_path)?;
+    let home_dir = env::home_dir();
+    let location = locate(cli_args.value_of("configfile").map(Path::new), home_dir.as_ref().map(|home| home.as_path()), |name| env::var(name).ok());
+    let config_path = location.path.as_path();
+    // Commands managing the configuration file must work while it is missing or invalid
+    if subcommand == config::NAME && config::is_file_command(&module_args) {
+        let output_format = cli_args.value_of("output-format").map(OutputFormat::from).unwrap_or(OutputFormat::HUMAN);
+        return config::call_file_command(&module_args, config_path, &output_format);
+    }
+    // Without a file, all settings have to be given by environment variables
+    let mut config = match Config::from_file_with_profile(config_path, cli_args.value_of("profile")) {
+        Err(e) if !location.exists => {
+            let tried: Vec<String> = location.tried.iter().map(|path| format!("'{}'", path.display())).collect();
+            return Err(e).chain_err(|| ErrorKind::Config(format!("no configuration file found; tried {}", tried.join(", "))));
+        }
+        result => result?,
+    };
 
-    if cli_args.is_present("quiet") {
+    // Clients of the machine API expect nothing but its responses
+    if cli_args.is_present("quiet") || cli_args.subcommand_name() == Some(api::NAME) {
         config.general.verbosity = Verbosity::QUIET;
+    } else if cli_args.occurrences_of("verbose") > 1 {
+        config.general.verbosity = Verbosity::TRACE;
     } else if cli_args.is_present("verbose") {
         config.general.verbosity = Verbosity::VERBOSE;
     }
@@ -59,24 +132,176 @@ fn run() -> Result<()> {
     if let Some(output_format) = cli_args.value_of("output-format") {
         config.general.output_format = output_format.into();
     }
+    output::set_pretty(cli_args.is_present("pretty"));
+    if let Some(selector) = cli_args.value_of("select") {
+        if !config.general.output_format.is_json() {
+            config.general.output_format = OutputFormat::JSON;
+        }
+        output::set_selector(output::Selector::parse(selector).chain_err(|| "Invalid --select")?);
+    }
+    if let Some(template) = cli_args.value_of("format") {
+        config.general.output_format = OutputFormat::HUMAN;
+        output::set_template(output::Template::parse(template).chain_err(|| "Invalid --format")?);
+    }
+    *output_format = Some(config.general.output_format);
+    if let Some(proxy) = cli_args.value_of("proxy") {
+        config.general.proxy = Some(proxy.to_string());
+    }
+    if let Some(timeout) = cli_args.value_of("timeout") {
+        let secs = timeout.parse().unwrap();
+        config.general.connect_timeout_secs = Some(secs);
+        config.general.read_timeout_secs = Some(secs);
+    }
+    if cli_args.is_present("no-cache") {
+        responses::set_mode(responses::Mode::Bypass);
+    } else if cli_args.is_present("refresh") {
+        responses::set_mode(responses::Mode::Refresh);
+    }
+    if let Some(jobs) = cli_args.value_of("jobs") {
+        config.general.max_concurrency = Some(jobs.parse().unwrap());
+    }
+    if cli_args.is_present("trace-http") {
+        let target = match cli_args.value_of("trace-http") {
+            Some(path) => console::HttpTraceTarget::File(path.to_string()),
+            None => console::HttpTraceTarget::Stderr,
+        };
+        console::init_http_trace(&target).chain_err(|| "Failed to open HTTP trace file")?;
+    }
+    http::init(&config.general.http_settings()).chain_err(|| "Failed to set up network")?;
 
     if cli_args.is_present("show-config") {
-        console::msgln(format!("{:?}", &config))
+        console::msgln(secrets::redact(&format!("{:?}", &config)))
     }
 
-    let subcommand = cli_args.subcommand_name().ok_or(ErrorKind::NoCommandSpecified)?;
-    call_module(subcommand, cli_args.subcommand_matches(subcommand), &config)
+    let module_name = target_module_name(subcommand, &module_args);
+    let mut all_accounts = false;
+    if let Some(account) = cli_args.value_of("account") {
+        if module_name == auth::NAME {
+            config.account = Some(account.to_string());
+        } else if account == ALL_ACCOUNTS {
+            if module_name != pocket::NAME {
+                return Err(format!("'--account {}' is only supported by 'pocket list'", ALL_ACCOUNTS).into());
+            }
+            all_accounts = true;
+        } else {
+            config.select_account(module_name, account)?;
+        }
+    }
+    config.injected_token = injected_token(module_name, cli_args)
+        .chain_err(|| "Failed to read injected access token")?;
+    if subcommand != auth::NAME && subcommand != api::NAME && subcommand != config::NAME && subcommand != dev::NAME
+        && subcommand != doctor::NAME && modules::is_included(subcommand) {
+        credentials::check_health(&config, subcommand);
+    }
+    let ctx = Context::new(&config)
+        .with_dry_run(cli_args.is_present("dry-run"))
+        .with_all_accounts(all_accounts);
+    if config.general.output_format == OutputFormat::HUMAN && !cli_args.is_present("no-pager")
+        && modules::is_paged(subcommand, &module_args) {
+        pager::start();
+    }
+    module.call(Some(&module_args), &ctx)?;
+
+    // The machine API runs for long, and `rat cache gc` collects anyway
+    if subcommand != api::NAME && subcommand != cache::NAME && !ctx.dry_run {
+        gc::collect_if_due(&config);
+    }
+
+    Ok(())
+}
+
+// `rat help <module> [<command>]` shows the help of the complete module CLI instead of its stub
+fn print_help(args: &[&str]) -> Result<()> {
+    let registry = modules::registry();
+    match args.first().and_then(|name| registry.iter().find(|module| module.name() == *name)) {
+        Some(module) => {
+            let mut module_args = args[1..].to_vec();
+            module_args.push("--help");
+            modules::parse_module_args(module.as_ref(), BIN_NAME, &module_args).map(|_| ()).unwrap_or_else(|e| e.exit())
+        }
+        None => {
+            build_cli().print_help().chain_err(|| "Failed to print help")?;
+            println!();
+        }
+    }
+
+    Ok(())
 }
 
+// Tokens from systemd credentials are looked up per module by the credential store
+fn injected_token(module_name: &str, cli_args: &ArgMatches) -> credentials::Result<Option<InjectedToken>> {
+    if let Some(path) = cli_args.value_of("token-file") {
+        InjectedToken::from_file(module_name, Path::new(path)).map(Some)
+    } else if let Some(fd) = cli_args.value_of("token-fd") {
+        InjectedToken::from_file(module_name, Path::new(&format!("/dev/fd/{}", fd))).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+// `rat auth login|logout <module>` applies the account and injected tokens to the module given as argument, and
+// `rat flush` to Pocket, whose queued actions it sends
+fn target_module_name<'a>(subcommand: &'a str, module_args: &'a ArgMatches) -> &'a str {
+    if subcommand == flush::NAME {
+        return pocket::NAME;
+    }
+    if subcommand != auth::NAME {
+        return subcommand;
+    }
+    module_args.subcommand().1
+        .and_then(|args| args.value_of("module"))
+        .unwrap_or(subcommand)
+}
 
+// Modules are only represented by their stubs to keep start up fast, no matter how many modules there are
 fn build_cli() -> App<'static, 'static> {
-    let mut app = App::new("rat")
+    let mut app = build_global_cli()
+        .setting(AppSettings::DisableHelpSubcommand)
+        .subcommand(SubCommand::with_name(HELP_NAME)
+            .about("Prints this message or the help of the given subcommand(s)")
+            .arg(Arg::with_name("args").multiple(true)))
+        .subcommand(build_completions_cli());
+    for module in modules::registry() {
+        app = app.subcommand(module.build_stub_cli());
+    }
+
+    app
+}
+
+// Shell completions need to know all commands and arguments
+fn build_full_cli() -> App<'static, 'static> {
+    let mut app = build_global_cli().subcommand(build_completions_cli());
+    for module in modules::registry() {
+        app = app.subcommand(module.build_sub_cli());
+    }
+
+    app
+}
+
+fn build_completions_cli() -> App<'static, 'static> {
+    SubCommand::with_name(COMPLETIONS_NAME)
+        .about("Prints the completion script for a shell, e.g., 'rat completions bash > /etc/bash_completion.d/rat'")
+        .arg(Arg::with_name("shell")
+            .index(1)
+            .required(true)
+            .possible_values(&["bash", "fish", "zsh"])
+            .help("The shell to generate the script for"))
+}
+
+// Completions cover the complete CLIs of all modules, not only their stubs
+fn print_completions(shell: &str) -> Result<()> {
+    build_full_cli().gen_completions_to(BIN_NAME, shell.parse::<Shell>().unwrap(), &mut io::stdout());
+    Ok(())
+}
+
+fn build_global_cli() -> App<'static, 'static> {
+    App::new("rat")
         .version(VERSION)
         .arg(Arg::with_name("configfile")
             .short("c")
             .long("config")
             .value_name("FILE")
-            .help("Sets a custom config file")
+            .help("Sets the config file; defaults to $RAT_CONFIG, or else $XDG_CONFIG_HOME/rat/rat.toml or ~/.rat.toml, whichever exists first")
             .takes_value(true))
         .arg(Arg::with_name("show-config")
             .long("show-config")
@@ -85,40 +310,107 @@ fn build_cli() -> App<'static, 'static> {
             .short("q")
             .long("quiet")
             .conflicts_with("verbose")
-            .help("Sets quiet mode"))
+            .help("Sets quiet mode; only errors are shown"))
         .arg(Arg::with_name("verbose")
             .short("v")
             .long("verbose")
             .conflicts_with("quiet")
-            .help("Set verbose mode"))
+            .multiple(true)
+            .help("Sets verbose mode; '-vv' also traces connections"))
+        .arg(Arg::with_name("no-pager")
+            .long("no-pager")
+            .help("Prints long output of listings directly instead of through $PAGER"))
+        .arg(Arg::with_name("no-color")
+            .long("no-color")
+            .help("Disables colors of messages; also disabled by environment variable 'NO_COLOR'"))
+        .arg(Arg::with_name("profile")
+            .long("profile")
+            .value_name("NAME")
+            .takes_value(true)
+            .env("RAT_PROFILE")
+            .help("Applies a named profile of the config, i.e., section '[profiles.<name>]'"))
+        .arg(Arg::with_name("account")
+            .short("a")
+            .long("account")
+            .value_name("NAME")
+            .takes_value(true)
+            .help("Selects a named account of the module; 'all' lists the articles of all Pocket accounts"))
+        .arg(Arg::with_name("dry-run")
+            .long("dry-run")
+            .help("Shows what commands that modify data would do without doing it"))
+        .arg(Arg::with_name("token-file")
+            .long("token-file")
+            .value_name("FILE")
+            .takes_value(true)
+            .conflicts_with("token-fd")
+            .help("Reads the access token of the module from a file instead of the credential store"))
+        .arg(Arg::with_name("token-fd")
+            .long("token-fd")
+            .value_name("FD")
+            .takes_value(true)
+            .help("Reads the access token of the module from a file descriptor (Unix only)"))
         .arg(Arg::with_name("output-format")
             .short("o")
             .long("output")
             .takes_value(true)
-            .possible_values(&["human", "json"])
+            .possible_values(&["human", "json", "ndjson"])
             .help("Sets output format"))
+        .arg(Arg::with_name("pretty")
+            .long("pretty")
+            .help("Pretty-prints JSON output"))
+        .arg(Arg::with_name("select")
+            .long("select")
+            .value_name("PATH")
+            .takes_value(true)
+            .conflicts_with("format")
+            .validator(|selector| output::Selector::parse(&selector).map(|_| ()).map_err(|e| e.to_string()))
+            .help("Prints only the selected values of JSON output, e.g., '.list.*.resolved_url'; implies '--output json'"))
+        .arg(Arg::with_name("format")
+            .long("format")
+            .value_name("TEMPLATE")
+            .takes_value(true)
+            .conflicts_with("output-format")
+            .validator(|template| output::Template::parse(&template).map(|_| ()).map_err(|e| e.to_string()))
+            .help("Prints one line per listed item with placeholders for its fields, e.g., '{title}\\t{url}'"))
+        .arg(Arg::with_name("proxy")
+            .long("proxy")
+            .value_name("URL")
+            .takes_value(true)
+            .help("Sends all requests through an HTTP proxy; overrides the config"))
+        .arg(Arg::with_name("timeout")
+            .long("timeout")
+            .value_name("SECS")
+            .takes_value(true)
+            .validator(|secs| secs.parse::<u64>().map(|_| ()).map_err(|_| "expected a number of seconds".to_string()))
+            .help("Sets the connect and read timeouts of requests; 0 disables them"))
+        .arg(Arg::with_name("jobs")
+            .long("jobs")
+            .short("j")
+            .value_name("N")
+            .takes_value(true)
+            .validator(|jobs| match jobs.parse::<usize>() {
+                Ok(jobs) if jobs > 0 => Ok(()),
+                _ => Err("expected a positive number".to_string()),
+            })
+            .help("Sends up to <N> requests concurrently, e.g., pages of 'pocket list --all'; overrides the config"))
+        .arg(Arg::with_name("no-cache")
+            .long("no-cache")
+            .conflicts_with("refresh")
+            .help("Neither uses nor stores cached responses; cf. 'response_cache_ttl'"))
+        .arg(Arg::with_name("refresh")
+            .long("refresh")
+            .help("Sends all requests instead of using cached responses, and caches the new responses"))
+        .arg(Arg::with_name("trace-http")
+            .long("trace-http")
+            .value_name("FILE")
+            .takes_value(true)
+            .min_values(0)
+            .require_equals(true)
+            .help("Logs HTTP requests and responses with credentials masked to stderr, or appends them to <FILE>"))
         .arg(Arg::with_name("completions")
             .long("completions")
             .takes_value(true)
             .hidden(true)
             .possible_values(&["bash", "fish", "zsh"])
-            .help("The shell to generate the script for"));
-
-
-    app = app.subcommand(centerdevice::build_sub_cli());
-    app = app.subcommand(pocket::build_sub_cli());
-    app = app.subcommand(slack::build_sub_cli());
-    app = app.subcommand(stocks::build_sub_cli());
-
-    app
-}
-
-fn call_module(subcommand: &str, cli_args: Option<&ArgMatches>, config: &Config) -> Result<()> {
-    match subcommand {
-        centerdevice::NAME => centerdevice::call(cli_args, config),
-        pocket::NAME       => pocket::call(cli_args, config),
-        slack::NAME        => slack::call(cli_args, config),
-        stocks::NAME       => stocks::call(cli_args, config),
-        _ => Ok(())
-    }
+            .help("The shell to generate the script for"))
 }
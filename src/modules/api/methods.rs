@@ -0,0 +1,262 @@
+use super::API_VERSION;
+use super::protocol::Failure;
+#[cfg(feature = "centerdevice")]
+use clients::centerdevice::client as centerdevice_client;
+#[cfg(feature = "pocket")]
+use clients::pocket;
+#[cfg(feature = "pocket")]
+use clients::pocket::client::list::Request as ListRequest;
+#[cfg(feature = "pocket")]
+use clients::pocket::client::send::ActionRequest;
+#[cfg(feature = "stocks")]
+use clients::stocks::comdirect;
+#[cfg(feature = "pocket")]
+use credentials;
+use errors::*;
+use modules;
+use modules::Context;
+#[cfg(feature = "centerdevice")]
+use modules::centerdevice::session;
+
+use serde::{Deserialize, Serialize};
+use serde_json;
+use serde_json::{Map, Value};
+
+static RAT_VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+/// All methods of the current API version; cf. README, section 'Machine API'. Methods of modules left out at compile
+/// time are not listed by `rat.methods` and fail with a module not included error.
+pub static METHODS: &'static [&'static str] = &[
+    "rat.version",
+    "rat.methods",
+    "centerdevice.collections",
+    "centerdevice.search",
+    "pocket.list",
+    "pocket.archive",
+    "pocket.readd",
+    "pocket.favorite",
+    "pocket.unfavorite",
+    "pocket.delete",
+    "stocks.price",
+];
+
+#[derive(Serialize, Debug)]
+struct Version {
+    api_version: u32,
+    rat_version: &'static str,
+}
+
+#[cfg(feature = "pocket")]
+#[derive(Serialize, Debug)]
+struct DryRun<'a> {
+    dry_run: bool,
+    method: &'a str,
+    items: usize,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct NoParams {}
+
+#[cfg(feature = "centerdevice")]
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct CollectionsParams {
+    name: Option<String>,
+    filter: Option<String>,
+    #[serde(default)]
+    public_collections: bool,
+}
+
+#[cfg(feature = "centerdevice")]
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct SearchParams {
+    filenames: Option<Vec<String>>,
+    tags: Option<Vec<String>>,
+    fulltext: Option<String>,
+    #[serde(default)]
+    public_collections: bool,
+}
+
+#[cfg(feature = "pocket")]
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ListParams {
+    state: Option<String>,
+    tag: Option<String>,
+    sort: Option<String>,
+    search: Option<String>,
+    #[serde(default)]
+    details: bool,
+}
+
+#[cfg(feature = "pocket")]
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ActionParams {
+    ids: Vec<String>,
+}
+
+#[cfg(feature = "stocks")]
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct PriceParams {
+    search: String,
+}
+
+#[cfg_attr(not(any(feature = "centerdevice", feature = "pocket")), allow(unused_variables))]
+pub fn call(method: &str, params: Value, ctx: &Context) -> ::std::result::Result<Value, Failure> {
+    match method {
+        "rat.version" => {
+            let _: NoParams = parse_params(params)?;
+            to_value(&Version { api_version: API_VERSION, rat_version: RAT_VERSION })
+        }
+        "rat.methods" => {
+            let _: NoParams = parse_params(params)?;
+            to_value(&included_methods())
+        }
+        #[cfg(feature = "centerdevice")]
+        "centerdevice.collections" => collections(parse_params(params)?, ctx),
+        #[cfg(feature = "centerdevice")]
+        "centerdevice.search" => search(parse_params(params)?, ctx),
+        #[cfg(feature = "pocket")]
+        "pocket.list" => list(parse_params(params)?, ctx),
+        #[cfg(feature = "pocket")]
+        "pocket.archive" | "pocket.readd" | "pocket.favorite" | "pocket.unfavorite" | "pocket.delete" =>
+            action(method, parse_params(params)?, ctx),
+        #[cfg(feature = "stocks")]
+        "stocks.price" => price(parse_params(params)?),
+        _ if METHODS.contains(&method) => {
+            let module_name = method.splitn(2, '.').next().unwrap_or(method);
+            Err(Error::from(ErrorKind::ModuleNotIncluded(module_name.to_string())).into())
+        }
+        _ => Err(Failure::UnknownMethod(method.to_string())),
+    }
+}
+
+#[cfg(feature = "centerdevice")]
+fn collections(params: CollectionsParams, ctx: &Context) -> ::std::result::Result<Value, Failure> {
+    let session = session(ctx.config).chain_err(|| "failed to search for collections")?;
+    let json = centerdevice_client::search_collections(
+        &session, params.name.as_ref().map(|s| s.as_str()), params.public_collections, params.filter.as_ref().map(|s| s.as_str()))
+        .chain_err(|| "failed to search for collections")?;
+
+    parse_result(&json)
+}
+
+#[cfg(feature = "centerdevice")]
+fn search(params: SearchParams, ctx: &Context) -> ::std::result::Result<Value, Failure> {
+    let named = if params.public_collections {
+        centerdevice_client::search::NamedSearches::PublicCollections
+    } else {
+        centerdevice_client::search::NamedSearches::None
+    };
+    let session = session(ctx.config).chain_err(|| "failed to search for documents")?;
+    let json = centerdevice_client::search_documents(
+        &session, as_strs(&params.filenames), as_strs(&params.tags), params.fulltext.as_ref().map(|s| s.as_str()), named)
+        .chain_err(|| "failed to search for documents")?;
+
+    parse_result(&json)
+}
+
+#[cfg(feature = "pocket")]
+fn list(params: ListParams, ctx: &Context) -> ::std::result::Result<Value, Failure> {
+    let config = ctx.config;
+    let request = ListRequest {
+        state: params.state.as_ref().map(|s| s.as_str().into()),
+        tag: params.tag.as_ref().map(|s| s.as_str()),
+        sort: params.sort.as_ref().map(|s| s.as_str().into()),
+        detailType: params.details.into(),
+        search: params.search.as_ref().map(|s| s.as_str()),
+        since: None,
+        favorite: None,
+        count: None,
+        offset: None,
+    };
+    let json = pocket::client::list(config, &request, None, None).chain_err(|| "failed to list Pocket articles")?;
+
+    parse_result(&json)
+}
+
+#[cfg(feature = "pocket")]
+fn action(method: &str, params: ActionParams, ctx: &Context) -> ::std::result::Result<Value, Failure> {
+    let config = ctx.config;
+    let action = method.splitn(2, '.').nth(1).unwrap_or(method);
+    credentials::ensure_writable(config, pocket::NAME)
+        .chain_err(|| format!("action '{}' failed to apply to Pocket article", action))?;
+    if ctx.dry_run {
+        return to_value(&DryRun { dry_run: true, method: method, items: params.ids.len() });
+    }
+    let actions: Vec<ActionRequest> = params.ids.iter().map(|id| ActionRequest::new(action, id)).collect();
+    let json = pocket::client::send(config, &actions)
+        .chain_err(|| format!("action '{}' failed to apply to Pocket article", action))?;
+
+    parse_result(&json)
+}
+
+#[cfg(feature = "stocks")]
+fn price(params: PriceParams) -> ::std::result::Result<Value, Failure> {
+    let stock_price = comdirect::scrape_stock_price(params.search)?;
+
+    to_value(&stock_price)
+}
+
+fn included_methods() -> Vec<&'static str> {
+    METHODS.iter()
+        .filter(|method| modules::is_included(method.splitn(2, '.').next().unwrap_or(method)))
+        .cloned()
+        .collect()
+}
+
+/// Missing params are treated like empty params, so methods without required params may be called without any.
+fn parse_params<T: Deserialize>(params: Value) -> ::std::result::Result<T, Failure> {
+    let params = if params.is_null() { Value::Object(Map::new()) } else { params };
+    serde_json::from_value(params).map_err(|e| Failure::InvalidParams(e.to_string()))
+}
+
+#[cfg(any(feature = "centerdevice", feature = "pocket"))]
+fn parse_result(json: &str) -> ::std::result::Result<Value, Failure> {
+    let value = serde_json::from_str(json).chain_err(|| ErrorKind::Parse("API response".to_string()))?;
+    Ok(value)
+}
+
+fn to_value<T: Serialize>(result: &T) -> ::std::result::Result<Value, Failure> {
+    let value = serde_json::to_value(result).chain_err(|| "JSON serialization failed")?;
+    Ok(value)
+}
+
+#[cfg(feature = "centerdevice")]
+fn as_strs(values: &Option<Vec<String>>) -> Option<Vec<&str>> {
+    values.as_ref().map(|v| v.iter().map(|s| s.as_str()).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_included_methods() {
+        let methods = included_methods();
+
+        assert!(methods.contains(&"rat.version"));
+        assert_eq!(methods.contains(&"pocket.list"), cfg!(feature = "pocket"));
+    }
+
+    #[test]
+    #[cfg(feature = "pocket")]
+    fn test_parse_params() {
+        let params: ListParams = parse_params(Value::Null).unwrap();
+        assert!(params.state.is_none());
+        assert!(!params.details);
+
+        let params: ActionParams = parse_params(serde_json::from_str(r#"{"ids":["1","2"]}"#).unwrap()).unwrap();
+        assert_eq!(params.ids, vec!["1", "2"]);
+
+        let result: ::std::result::Result<ListParams, Failure> = parse_params(serde_json::from_str(r#"{"stat":"all"}"#).unwrap());
+        match result {
+            Err(Failure::InvalidParams(_)) => {}
+            other => panic!("expected invalid params, got {:?}", other),
+        }
+    }
+}
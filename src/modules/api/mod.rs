@@ -0,0 +1,114 @@
+use errors::*;
+use modules::{Context, Module};
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::io;
+use std::io::{BufRead, Write};
+
+pub const NAME: &'static str = "api";
+
+/// Version of the method and result schema; it is increased on incompatible changes only.
+pub const API_VERSION: u32 = 1;
+
+mod methods;
+mod protocol;
+
+use self::protocol::Response;
+
+pub struct Api;
+
+impl Module for Api {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn about(&self) -> &'static str {
+        "Serves module operations as JSON-RPC 2.0 for other programs"
+    }
+
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        SubCommand::with_name(NAME)
+            .about(self.about())
+            .arg(Arg::with_name("stdio")
+                .long("stdio")
+                .required(true)
+                .help("Reads one request per line from stdin and writes one response per line to stdout"))
+    }
+
+    fn call(&self, _: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        let stdin = io::stdin();
+        serve(stdin.lock(), io::stdout(), ctx).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))
+    }
+}
+
+fn serve<R: BufRead, W: Write>(input: R, mut output: W, ctx: &Context) -> Result<()> {
+    for line in input.lines() {
+        let line = line.chain_err(|| "failed to read request")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle(&line, ctx) {
+            let json = serde_json::to_string(&response).chain_err(|| "JSON serialization failed")?;
+            writeln!(output, "{}", json).chain_err(|| "failed to write response")?;
+            output.flush().chain_err(|| "failed to write response")?;
+        }
+    }
+
+    Ok(())
+}
+
+fn handle(line: &str, ctx: &Context) -> Option<Response> {
+    let request = match protocol::parse(line) {
+        Ok(request) => request,
+        Err(response) => return Some(response),
+    };
+    let result = methods::call(&request.method, request.params, ctx);
+    let id = match request.id {
+        Some(id) => id,
+        None => return None,
+    };
+
+    match result {
+        Ok(result) => Some(Response::result(id, result)),
+        Err(failure) => Some(Response::failure(id, failure)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use config::Config;
+
+    use rat_core::testing::ConfigBuilder;
+    use serde_json::Value;
+
+    fn config() -> Config {
+        ConfigBuilder::new("api").general("output_format", "{ format = 'JSON' }").build()
+    }
+
+    #[test]
+    fn test_serve() {
+        let config = config();
+        let ctx = Context::new(&config);
+        let input = concat!(
+            r#"{"jsonrpc":"2.0","method":"rat.version","id":1}"#, "\n",
+            "\n",
+            r#"{"jsonrpc":"2.0","method":"rat.methods"}"#, "\n",
+            r#"{"jsonrpc":"2.0","method":"pocket.unknown","id":"two"}"#, "\n",
+            r#"{"jsonrpc":"2.0","method":"rat.version","params":{"verbose":true},"id":3}"#, "\n");
+        let mut output = Vec::new();
+
+        serve(input.as_bytes(), &mut output, &ctx).unwrap();
+
+        let responses: Vec<Value> = String::from_utf8(output).unwrap().lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0]["result"]["api_version"].as_u64(), Some(API_VERSION as u64));
+        assert_eq!(responses[0]["id"].as_u64(), Some(1));
+        assert_eq!(responses[1]["error"]["code"].as_i64(), Some(protocol::METHOD_NOT_FOUND));
+        assert_eq!(responses[1]["id"].as_str(), Some("two"));
+        assert_eq!(responses[2]["error"]["code"].as_i64(), Some(protocol::INVALID_PARAMS));
+    }
+}
@@ -0,0 +1,148 @@
+use errors::{Error, ErrorReport};
+use utils::secrets;
+
+use serde_json;
+use serde_json::Value;
+
+static JSONRPC_VERSION: &'static str = "2.0";
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// Failures of the called operation; `data` carries the error report, cf. `rat --output json`.
+pub const OPERATION_FAILED: i64 = -32000;
+
+#[derive(Deserialize, Debug)]
+pub struct Request {
+    pub jsonrpc: String,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+    /// Requests without id are notifications, which are not answered
+    pub id: Option<Value>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Response {
+    jsonrpc: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+    id: Value,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RpcError {
+    code: i64,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<ErrorReport>,
+}
+
+/// Reasons a method call fails besides a broken request.
+#[derive(Debug)]
+pub enum Failure {
+    UnknownMethod(String),
+    InvalidParams(String),
+    Failed(Error),
+}
+
+impl From<Error> for Failure {
+    fn from(error: Error) -> Self {
+        Failure::Failed(error)
+    }
+}
+
+impl Response {
+    pub fn result(id: Value, result: Value) -> Self {
+        Response { jsonrpc: JSONRPC_VERSION, result: Some(result), error: None, id: id }
+    }
+
+    pub fn error<T: Into<String>>(id: Value, code: i64, message: T) -> Self {
+        let error = RpcError { code: code, message: message.into(), data: None };
+        Response { jsonrpc: JSONRPC_VERSION, result: None, error: Some(error), id: id }
+    }
+
+    pub fn failure(id: Value, failure: Failure) -> Self {
+        match failure {
+            Failure::UnknownMethod(method) => Response::error(id, METHOD_NOT_FOUND, format!("unknown method '{}'", method)),
+            Failure::InvalidParams(reason) => Response::error(id, INVALID_PARAMS, format!("invalid params: {}", reason)),
+            Failure::Failed(e) => {
+                let mut report = e.report();
+                report.message = secrets::redact(&report.message);
+                report.causes = report.causes.iter().map(|c| secrets::redact(c)).collect();
+                let error = RpcError { code: OPERATION_FAILED, message: report.message.clone(), data: Some(report) };
+                Response { jsonrpc: JSONRPC_VERSION, result: None, error: Some(error), id: id }
+            }
+        }
+    }
+}
+
+/// Parses a single request; a request that cannot be parsed is answered right away with an error response.
+pub fn parse(line: &str) -> ::std::result::Result<Request, Response> {
+    let json: Value = serde_json::from_str(line)
+        .map_err(|e| Response::error(Value::Null, PARSE_ERROR, format!("parse error: {}", e)))?;
+    let id = json.get("id").cloned().unwrap_or(Value::Null);
+    let request: Request = serde_json::from_value(json)
+        .map_err(|e| Response::error(id.clone(), INVALID_REQUEST, format!("invalid request: {}", e)))?;
+    if request.jsonrpc != JSONRPC_VERSION {
+        return Err(Response::error(id, INVALID_REQUEST, format!("invalid request: unsupported JSON-RPC version '{}'", request.jsonrpc)));
+    }
+
+    Ok(request)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use errors::ErrorKind;
+
+    fn to_value(response: &Response) -> Value {
+        serde_json::from_str(&serde_json::to_string(response).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_request() {
+        let request = parse(r#"{"jsonrpc":"2.0","method":"pocket.list","params":{"state":"all"},"id":7}"#).unwrap();
+
+        assert_eq!(request.method, "pocket.list");
+        assert_eq!(request.params["state"].as_str(), Some("all"));
+        assert_eq!(request.id, Some(Value::from(7)));
+    }
+
+    #[test]
+    fn test_parse_notification() {
+        let request = parse(r#"{"jsonrpc":"2.0","method":"rat.version"}"#).unwrap();
+
+        assert_eq!(request.id, None);
+        assert!(request.params.is_null());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let response = to_value(&parse("{not json").unwrap_err());
+        assert_eq!(response["error"]["code"].as_i64(), Some(PARSE_ERROR));
+        assert!(response["id"].is_null());
+
+        let response = to_value(&parse(r#"{"jsonrpc":"2.0","id":3}"#).unwrap_err());
+        assert_eq!(response["error"]["code"].as_i64(), Some(INVALID_REQUEST));
+        assert_eq!(response["id"].as_u64(), Some(3));
+
+        let response = to_value(&parse(r#"{"jsonrpc":"1.0","method":"rat.version","id":4}"#).unwrap_err());
+        assert_eq!(response["error"]["code"].as_i64(), Some(INVALID_REQUEST));
+    }
+
+    #[test]
+    fn test_failure_carries_error_report() {
+        let error: Error = ErrorKind::Api(404, Some("not_found".to_string())).into();
+        let response = to_value(&Response::failure(Value::from(1), Failure::Failed(error)));
+
+        assert!(response.get("result").is_none());
+        assert_eq!(response["error"]["code"].as_i64(), Some(OPERATION_FAILED));
+        assert_eq!(response["error"]["data"]["category"].as_str(), Some("api"));
+        assert_eq!(response["error"]["data"]["http_status"].as_u64(), Some(404));
+        assert_eq!(response["error"]["data"]["code"].as_str(), Some("not_found"));
+    }
+}
@@ -0,0 +1,29 @@
+use credentials::agent;
+use errors::*;
+use modules::Context;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::time::Duration;
+
+pub const NAME: &'static str = "agent";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Keeps the passphrase of the encrypted credential store, so following runs do not ask for it again")
+        .arg(Arg::with_name("timeout")
+            .long("timeout")
+            .takes_value(true)
+            .help("Seconds to keep the passphrase after it has been entered; defaults to 3600"))
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let timeout = match args.and_then(|args| args.value_of("timeout")) {
+        Some(timeout) => match timeout.parse::<u64>() {
+            Ok(secs) if secs > 0 => Duration::from_secs(secs),
+            _ => bail!("Invalid timeout '{}'; expected a positive number of seconds", timeout),
+        },
+        None => agent::DEFAULT_TIMEOUT,
+    };
+
+    agent::serve(ctx.config, timeout).chain_err(|| "passphrase agent failed")
+}
@@ -0,0 +1,158 @@
+use super::{configured_access_token, modules};
+
+use clients::centerdevice;
+use config::{Config, OutputFormat};
+use credentials;
+use credentials::Credential;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use chrono::{DateTime, NaiveDateTime, UTC};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::io::Write;
+use tabwriter::TabWriter;
+
+pub const NAME: &'static str = "audit";
+
+#[derive(Serialize, Deserialize, Debug)]
+struct AuditEntry {
+    section: String,
+    setting: String,
+    /// 'configuration', 'credential store', or 'injected'
+    location: String,
+    backend: Option<String>,
+    obtained_at: Option<u64>,
+    last_used: Option<u64>,
+    /// Secrets stored in plain text in the configuration file
+    flagged: bool,
+}
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Lists all stored secrets and flags secrets in the configuration file")
+        .arg(Arg::with_name("migrate")
+            .long("migrate")
+            .help("Moves access tokens from the configuration file into the credential store"))
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    if args.unwrap().is_present("migrate") {
+        return migrate(config);
+    }
+
+    let entries = audit(config).chain_err(|| "failed to audit secrets")?;
+    let json = serde_json::to_string(&entries).chain_err(|| "JSON serialization failed")?;
+
+    output(&json, &config.general.output_format)
+}
+
+fn audit(config: &Config) -> Result<Vec<AuditEntry>> {
+    let backend = credentials::backend(config).chain_err(|| "Failed to select credential store")?;
+    let mut entries = Vec::new();
+
+    for module_name in modules(config) {
+        if let Ok(credential) = credentials::load(config, &module_name) {
+            let last_used = credentials::last_used(config, &module_name);
+            let injected = credentials::is_injected(config, &module_name);
+            let mut settings = vec!["access_token"];
+            if credential.refresh_token.is_some() {
+                settings.push("refresh_token");
+            }
+            for setting in settings {
+                entries.push(AuditEntry {
+                    section: module_name.clone(),
+                    setting: setting.to_string(),
+                    location: if injected { "injected" } else { "credential store" }.to_string(),
+                    backend: if injected { None } else { Some(backend.name().to_string()) },
+                    obtained_at: credential.obtained_at,
+                    last_used: last_used,
+                    flagged: false,
+                });
+            }
+        }
+    }
+
+    for (section, setting, _) in config.secrets() {
+        entries.push(AuditEntry {
+            section: section,
+            setting: setting,
+            location: "configuration".to_string(),
+            backend: None,
+            obtained_at: None,
+            last_used: None,
+            flagged: true,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Client secrets and consumer keys belong to the configuration; only access and refresh tokens are migrated.
+fn migrate(config: &Config) -> Result<()> {
+    let mut migrated = 0;
+    for module_name in modules(config) {
+        let access_token = match configured_access_token(config, &module_name) {
+            Some(access_token) => access_token.clone(),
+            None => continue,
+        };
+        if credentials::load(config, &module_name).is_ok() {
+            info(format!("Module {} already has an access token in the credential store; skipping.", module_name));
+            continue;
+        }
+
+        let mut credential = Credential::new(access_token);
+        credential.obtained_at = None;
+        if module_name == centerdevice::NAME {
+            credential.refresh_token = config.centerdevice.refresh_token().cloned();
+        }
+        credentials::store(config, &module_name, &credential).chain_err(|| "failed to audit secrets")?;
+        migrated += 1;
+        msgln(format!("Moved access token of module {} into the credential store.", module_name));
+        warning(format!("Please remove the tokens from your configuration, section '[{}]'; they take precedence over the credential store.", module_name));
+    }
+    if migrated == 0 {
+        msgln("There are no access tokens in the configuration file to migrate.");
+    }
+
+    Ok(())
+}
+
+fn output(json: &str, format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => output_human(json),
+        OutputFormat::JSON | OutputFormat::NDJSON => output::as_json(json).chain_err(|| ErrorKind::OutputFailed),
+    }
+}
+
+fn output_human(json: &str) -> Result<()> {
+    let entries: Vec<AuditEntry> = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
+
+    let mut tw = TabWriter::new(vec![]);
+    let _ = write!(&mut tw, "SECTION\tSETTING\tLOCATION\tLAST USED\tNOTE\n");
+    for e in &entries {
+        let location = match e.backend {
+            Some(ref backend) => format!("{} ({})", e.location, backend),
+            None => e.location.clone(),
+        };
+        let last_used = e.last_used
+            .map(|ts| DateTime::<UTC>::from_utc(NaiveDateTime::from_timestamp(ts as i64, 0), UTC).to_rfc3339())
+            .unwrap_or_else(|| "-".to_string());
+        let flag = if e.flagged { "\tplain text" } else { "" };
+        let _ = write!(&mut tw, "{}\t{}\t{}\t{}{}\n", e.section, e.setting, location, last_used, flag);
+    }
+    tw.flush().chain_err(|| "Failed to create output table")?;
+    let written = String::from_utf8(tw.into_inner().chain_err(|| "Failed to create output table")?)
+        .chain_err(|| "Failed to create output table")?;
+    msg(written);
+
+    let flagged = entries.iter().filter(|e| e.flagged && e.setting.ends_with("_token")).count();
+    if flagged > 0 {
+        warning(format!("Found {} token(s) in plain text in the configuration file; run 'rat auth audit --migrate' to move them into the credential store.", flagged));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use super::MODULES;
+
+use errors::*;
+use modules;
+use modules::Context;
+use net::oauth::oauth2;
+use net::oauth::oauth2::Flow;
+use utils::console::*;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+pub const NAME: &'static str = "login";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Runs authentication process of a module and stores the access token")
+        .arg(Arg::with_name("browser")
+            .long("browser")
+            .help("Open authentication page in default web browser"))
+        .arg(Arg::with_name("flow")
+            .long("flow")
+            .takes_value(true)
+            .possible_values(&["authorization_code", "device", "client_credentials"])
+            .default_value("authorization_code")
+            .help("Select OAuth flow for modules registered in section '[oauth.<module>]'"))
+        .arg(Arg::with_name("device")
+            .long("device")
+            .conflicts_with("flow")
+            .help("Use device flow to authenticate on machines without web browser; same as '--flow device'"))
+        .arg(Arg::with_name("read-only")
+            .long("read-only")
+            .help("Request read-only access; commands that modify data refuse to run with this access token"))
+        .arg(Arg::with_name("module")
+            .index(1)
+            .required(true)
+            .help("module to authenticate"))
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    let module_name = args.value_of("module").unwrap();
+    let open_browser = args.is_present("browser");
+    let read_only = args.is_present("read-only");
+    let flow_name = if args.is_present("device") { "device" } else { args.value_of("flow").unwrap() };
+    let flow: Flow = flow_name.parse().chain_err(|| format!("failed to authenticate module {}", module_name))?;
+
+    if !modules::is_included(module_name) {
+        bail!(ErrorKind::ModuleNotIncluded(module_name.to_string()));
+    }
+    if MODULES.contains(&module_name) && flow != Flow::AuthorizationCode {
+        bail!(ErrorKind::FlowNotSupported(module_name.to_string(), flow_name.to_string()));
+    }
+
+    match module_name {
+        #[cfg(feature = "centerdevice")]
+        modules::centerdevice::NAME => modules::centerdevice::login(config, open_browser, read_only),
+        #[cfg(feature = "instapaper")]
+        modules::instapaper::NAME => modules::instapaper::login(config, None, read_only),
+        #[cfg(feature = "pocket")]
+        modules::pocket::NAME => modules::pocket::login(config, open_browser, read_only),
+        #[cfg(feature = "slack")]
+        modules::slack::NAME => modules::slack::login(config, open_browser, read_only),
+        #[cfg(feature = "wallabag")]
+        modules::wallabag::NAME => modules::wallabag::login(config, None, read_only),
+        _ => {
+            oauth2::login(config, module_name, flow, open_browser, read_only)
+                .map(|_| msgln(format!("Received access token for module {} and stored it in the credential store.", module_name)))
+                .map_err(|e| Error::from(e.to_string()))
+        }
+    }.chain_err(|| format!("failed to authenticate module {}", module_name))
+}
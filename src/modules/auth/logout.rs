@@ -0,0 +1,64 @@
+use super::configured_access_token;
+
+use config::Config;
+use credentials;
+use errors::*;
+use modules::Context;
+#[cfg(feature = "slack")]
+use modules::slack;
+use net::oauth::oauth2::OAuth2;
+use utils::console::*;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+pub const NAME: &'static str = "logout";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Revokes the access token of a module if supported and removes it from the credential store")
+        .arg(Arg::with_name("module")
+            .index(1)
+            .required(true)
+            .help("module to log out"))
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    let module_name = args.value_of("module").unwrap();
+
+    // The local credential goes in any case, e.g., if the provider is unreachable or has revoked the token already
+    match revoke(config, module_name) {
+        Ok(true) => {}
+        Ok(false) => info(format!("Module {} does not support revoking access tokens.", module_name)),
+        Err(e) => warning(format!("Failed to revoke access token of module {} because {}; removing it anyway.", module_name, e)),
+    }
+
+    credentials::remove(config, module_name).chain_err(|| format!("failed to log out module {}", module_name))?;
+    msgln(format!("Removed access token of module {} from the credential store.", module_name));
+    if configured_access_token(config, module_name).is_some() {
+        warning(format!("The access token in your configuration, section '[{}]', remains; please remove it.", module_name));
+    }
+
+    Ok(())
+}
+
+fn revoke(config: &Config, module_name: &str) -> Result<bool> {
+    #[cfg(feature = "slack")]
+    {
+        if module_name == slack::NAME {
+            slack::revoke(config)?;
+            return Ok(true);
+        }
+    }
+
+    let client = match config.oauth.get(module_name) {
+        Some(client) => client,
+        None => return Ok(false),
+    };
+    let credential = match credentials::load(config, module_name) {
+        Ok(credential) => credential,
+        Err(_) => return Ok(false),
+    };
+    OAuth2::new(client).revoke(&credential.access_token).map_err(|e| Error::from(e.to_string()))
+}
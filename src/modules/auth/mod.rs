@@ -0,0 +1,84 @@
+use clients::{centerdevice, instapaper, pocket, slack, wallabag};
+use config::Config;
+use errors::*;
+use modules::{Context, Module};
+
+use clap::{App, ArgMatches, SubCommand};
+
+pub const NAME: &'static str = "auth";
+
+mod agent;
+mod audit;
+mod login;
+mod logout;
+mod status;
+
+/// Modules with built-in authentication. Modules registered in section '[oauth.<module>]' are added from the configuration.
+static MODULES: &'static [&'static str] = &[centerdevice::NAME, instapaper::NAME, pocket::NAME, slack::NAME, wallabag::NAME];
+
+fn modules(config: &Config) -> Vec<String> {
+    let mut modules: Vec<String> = MODULES.iter().map(|m| m.to_string()).collect();
+    let mut registered: Vec<String> = config.oauth.keys()
+        .filter(|m| !MODULES.contains(&m.as_str()))
+        .cloned()
+        .collect();
+    registered.sort();
+    modules.append(&mut registered);
+
+    modules
+}
+
+fn configured_access_token<'a>(config: &'a Config, module_name: &str) -> Option<&'a String> {
+    match module_name {
+        centerdevice::NAME => config.centerdevice.access_token(),
+        pocket::NAME => config.pocket.access_token.as_ref(),
+        slack::NAME => config.slack.access_token.as_ref(),
+        _ => None,
+    }
+}
+
+/// Shows the authentication status and token health of all modules like `rat auth status`; used by `rat doctor`.
+pub fn token_health(ctx: &Context) -> Result<()> {
+    status::call(None, ctx)
+}
+
+pub struct Auth;
+
+impl Module for Auth {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn about(&self) -> &'static str {
+        "Manages authentication of all modules"
+    }
+
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        SubCommand::with_name(NAME)
+            .about(self.about())
+            .subcommand(agent::build_sub_cli())
+            .subcommand(audit::build_sub_cli())
+            .subcommand(login::build_sub_cli())
+            .subcommand(logout::build_sub_cli())
+            .subcommand(status::build_sub_cli())
+    }
+
+    fn call(&self, cli_args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        let subcommand = cli_args.unwrap();
+        let subcommand_name = subcommand.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+        match subcommand_name {
+            agent::NAME => agent::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            audit::NAME => audit::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            login::NAME => login::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            logout::NAME => logout::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            status::NAME => status::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            _ => Ok(())
+        }
+    }
+}
+
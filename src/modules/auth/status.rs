@@ -0,0 +1,135 @@
+use super::{configured_access_token, modules};
+
+use config::{Config, OutputFormat};
+use credentials;
+use credentials::Health;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+use utils::time::unix_now;
+
+use clap::{App, ArgMatches, SubCommand};
+use serde_json;
+use std::io::Write;
+use tabwriter::TabWriter;
+
+pub const NAME: &'static str = "status";
+
+#[derive(Serialize, Deserialize, Debug)]
+struct AuthStatus {
+    module: String,
+    authenticated: bool,
+    source: Option<String>,
+    username: Option<String>,
+    obtained_at: Option<u64>,
+    expires_at: Option<u64>,
+    scope: Option<String>,
+    read_only: bool,
+    /// One of 'ok', 'refreshable', 'expiring', 'expired'; unknown for tokens from the configuration
+    health: Option<String>,
+}
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Shows which modules are authenticated")
+}
+
+pub fn call(_: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let statuses: Vec<AuthStatus> = modules(config).into_iter().map(|m| status(config, m)).collect();
+    let json = serde_json::to_string(&statuses).chain_err(|| "JSON serialization failed")?;
+
+    output(&json, &config.general.output_format)
+}
+
+fn status(config: &Config, module_name: String) -> AuthStatus {
+    if let Ok(credential) = credentials::load(config, &module_name) {
+        let health = match credential.health() {
+            _ if credential.is_refreshable() => "refreshable",
+            Health::Valid => "ok",
+            Health::ExpiresSoon(_) => "expiring",
+            Health::Expired => "expired",
+        };
+        let source = if credentials::is_injected(config, &module_name) { "injected" } else { "credential store" };
+        AuthStatus {
+            module: module_name,
+            authenticated: true,
+            source: Some(source.to_string()),
+            username: credential.username,
+            obtained_at: credential.obtained_at,
+            expires_at: credential.expires_at,
+            scope: credential.scope,
+            read_only: credential.read_only,
+            health: Some(health.to_string()),
+        }
+    } else {
+        let configured = configured_access_token(config, &module_name).is_some();
+        AuthStatus {
+            module: module_name,
+            authenticated: configured,
+            source: if configured { Some("configuration".to_string()) } else { None },
+            username: None,
+            obtained_at: None,
+            expires_at: None,
+            scope: None,
+            read_only: false,
+            health: None,
+        }
+    }
+}
+
+fn output(json: &str, format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => output_human(json),
+        OutputFormat::JSON | OutputFormat::NDJSON => output::as_json(json).chain_err(|| ErrorKind::OutputFailed),
+    }
+}
+
+fn output_human(json: &str) -> Result<()> {
+    let statuses: Vec<AuthStatus> = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
+    let now = unix_now();
+
+    let mut tw = TabWriter::new(vec![]);
+    for s in statuses {
+        if !s.authenticated {
+            let _ = write!(&mut tw, "* {}:\tnot authenticated\n", s.module);
+            continue;
+        }
+        let _ = write!(&mut tw, "* {}:\tauthenticated", s.module);
+        if let Some(username) = s.username {
+            let _ = write!(&mut tw, " as '{}'", username);
+        }
+        let _ = write!(&mut tw, " ({})", s.source.unwrap_or_default());
+        if s.read_only {
+            let _ = write!(&mut tw, " read-only");
+        }
+        if let Some(obtained_at) = s.obtained_at {
+            let _ = write!(&mut tw, "\tobtained {} ago", days(now.saturating_sub(obtained_at)));
+        }
+        match s.expires_at {
+            Some(expires_at) if expires_at <= now => { let _ = write!(&mut tw, "\texpired"); }
+            Some(expires_at) => { let _ = write!(&mut tw, "\texpires in {}", days(expires_at - now)); }
+            None => {}
+        }
+        match s.health.as_ref().map(|h| h.as_str()) {
+            Some("expiring") | Some("expired") => { let _ = write!(&mut tw, "\t(run 'rat auth login {}')", s.module); }
+            Some("refreshable") => { let _ = write!(&mut tw, "\t(refreshed automatically)"); }
+            _ => {}
+        }
+        let _ = write!(&mut tw, "\n");
+    }
+    tw.flush().chain_err(|| "Failed to create output table")?;
+    let written = String::from_utf8(tw.into_inner().chain_err(|| "Failed to create output table")?)
+        .chain_err(|| "Failed to create output table")?;
+    msg(written);
+
+    Ok(())
+}
+
+fn days(secs: u64) -> String {
+    match secs / 60 / 60 / 24 {
+        0 => format!("{} hour(s)", secs / 60 / 60),
+        days => format!("{} day(s)", days),
+    }
+}
@@ -0,0 +1,158 @@
+//! Bookmark records move bookmarks between services: list commands print them with `--bookmarks`, one JSON object
+//! per line, and add commands read them from stdin with `--bookmarks`, e.g., `rat pocket list --state all --bookmarks |
+//! rat pinboard add --bookmarks`; `rat xfer` does the same in one command. Records carry only what all services know:
+//! URL, title, tags, and times.
+
+use errors::*;
+
+use chrono::{DateTime, NaiveDateTime, UTC};
+use clap::{Arg, ArgMatches};
+use serde_json;
+use std::io::{self, BufRead, Write};
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq)]
+pub struct Bookmark {
+    pub url: String,
+    /// Empty if the service does not know it
+    #[serde(default)] pub title: String,
+    #[serde(default)] pub tags: Vec<String>,
+    /// Unix timestamp of saving
+    #[serde(default, skip_serializing_if = "Option::is_none")] pub time_added: Option<u64>,
+    /// Unix timestamp of the last change
+    #[serde(default, skip_serializing_if = "Option::is_none")] pub time_updated: Option<u64>,
+}
+
+impl Bookmark {
+    pub fn new<T: Into<String>>(url: T) -> Self {
+        Bookmark { url: url.into(), ..Default::default() }
+    }
+
+    pub fn title_or_url(&self) -> &str {
+        if self.title.is_empty() { &self.url } else { &self.title }
+    }
+
+    /// Skips tags the bookmark has already.
+    pub fn add_tags(&mut self, tags: &[&str]) {
+        for tag in tags {
+            if !self.tags.iter().any(|t| t == tag) {
+                self.tags.push(tag.to_string());
+            }
+        }
+    }
+
+    /// Time of saving as ISO 8601 in UTC, e.g., '2017-07-14T02:40:00Z'
+    pub fn time_added_iso8601(&self) -> Option<String> {
+        self.time_added.map(|secs| {
+            let dt = DateTime::<UTC>::from_utc(NaiveDateTime::from_timestamp(secs as i64, 0), UTC);
+            dt.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+        })
+    }
+}
+
+/// Argument `--bookmarks` of list commands; cf. `print`.
+pub fn list_arg() -> Arg<'static, 'static> {
+    Arg::with_name("bookmarks")
+        .long("bookmarks")
+        .help("Prints bookmark records, one JSON object per line, for 'add --bookmarks' of any service; cf. 'rat xfer'")
+}
+
+/// Argument `--bookmarks` of add commands; cf. `to_add`.
+pub fn add_arg() -> Arg<'static, 'static> {
+    Arg::with_name("bookmarks")
+        .long("bookmarks")
+        .conflicts_with("url")
+        .help("Reads bookmark records from stdin, one JSON object per line, as printed by 'list --bookmarks' of any service")
+}
+
+/// Prints one record per line regardless of the output format, so the records can be piped into add commands.
+pub fn print(bookmark: &Bookmark) -> Result<()> {
+    let json = serde_json::to_string(bookmark).chain_err(|| "JSON serialization failed")?;
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    writeln!(out, "{}", json).chain_err(|| ErrorKind::OutputFailed)
+}
+
+pub fn print_all(bookmarks: &[Bookmark]) -> Result<()> {
+    for b in bookmarks {
+        print(b)?;
+    }
+
+    Ok(())
+}
+
+/// Skips blank lines; records without URL are invalid.
+pub fn read<R: BufRead>(reader: R) -> Result<Vec<Bookmark>> {
+    let mut bookmarks = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.chain_err(|| "Failed to read bookmark records from stdin")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let bookmark: Bookmark = serde_json::from_str(&line).chain_err(|| format!("Invalid bookmark record in line {}", i + 1))?;
+        if bookmark.url.is_empty() {
+            bail!("Invalid bookmark record in line {}; the URL is missing", i + 1);
+        }
+        bookmarks.push(bookmark);
+    }
+
+    Ok(bookmarks)
+}
+
+/// The bookmarks an add command saves: with `--bookmarks` the records on stdin, otherwise `urls`. `title` sets the title
+/// of bookmarks without one, and `tags` are added to every bookmark.
+pub fn to_add(args: &ArgMatches, urls: Vec<String>, title: Option<&str>, tags: &[&str]) -> Result<Vec<Bookmark>> {
+    let mut bookmarks = if args.is_present("bookmarks") {
+        read(io::stdin().lock())?
+    } else {
+        urls.into_iter().map(Bookmark::new).collect()
+    };
+    if bookmarks.is_empty() {
+        bail!("No bookmarks to add");
+    }
+    for b in &mut bookmarks {
+        if b.title.is_empty() {
+            b.title = title.unwrap_or("").to_string();
+        }
+        b.add_tags(tags);
+    }
+
+    Ok(bookmarks)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_read() {
+        let input = "{\"url\":\"https://example.com/a\",\"title\":\"A\",\"tags\":[\"rust\"],\"time_added\":1500000000}\n\
+                     \n\
+                     {\"url\":\"https://example.com/b\"}\n";
+
+        let bookmarks = read(input.as_bytes()).unwrap();
+
+        assert_eq!(bookmarks, vec![
+            Bookmark {
+                url: "https://example.com/a".to_string(),
+                title: "A".to_string(),
+                tags: vec!["rust".to_string()],
+                time_added: Some(1500000000),
+                time_updated: None,
+            },
+            Bookmark::new("https://example.com/b"),
+        ]);
+        assert!(read("{\"title\":\"no url\"}\n".as_bytes()).is_err());
+        assert!(read("https://example.com\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let bookmark = Bookmark { title: "A".to_string(), time_added: Some(1500000000), ..Bookmark::new("https://example.com/a") };
+
+        let json = serde_json::to_string(&bookmark).unwrap();
+
+        assert_eq!(json, r#"{"url":"https://example.com/a","title":"A","tags":[],"time_added":1500000000}"#);
+        assert_eq!(read(json.as_bytes()).unwrap(), vec![bookmark.clone()]);
+        assert_eq!(bookmark.time_added_iso8601(), Some("2017-07-14T02:40:00Z".to_string()));
+    }
+}
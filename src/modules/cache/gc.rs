@@ -0,0 +1,64 @@
+use cache::gc::{self, GcResult, Limits};
+use config::OutputFormat;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+use utils::time::parse_interval;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+
+pub const NAME: &'static str = "gc";
+
+const MB: f64 = 1024.0 * 1024.0;
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Removes the least recently used files of directory caches like article snapshots beyond the configured limits")
+        .arg(Arg::with_name("max-size")
+            .long("max-size")
+            .takes_value(true)
+            .value_name("MB")
+            .help("Overrides 'cache_max_size_mb' of section '[general]'"))
+        .arg(Arg::with_name("max-age")
+            .long("max-age")
+            .takes_value(true)
+            .value_name("DURATION")
+            .help("Overrides 'cache_max_age' of section '[general]', e.g., '30days'"))
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+
+    let mut limits = Limits::from_config(config).chain_err(|| "invalid cache limits")?;
+    if let Some(max_size) = args.value_of("max-size") {
+        let mb: u64 = max_size.parse().chain_err(|| ErrorKind::Parse(format!("max size '{}'", max_size)))?;
+        limits.max_bytes = Some(mb * 1024 * 1024);
+    }
+    if let Some(max_age) = args.value_of("max-age") {
+        limits.max_age = Some(parse_interval(max_age).chain_err(|| "Could not parse max age duration")?);
+    }
+    if limits.is_unlimited() {
+        warning("No cache limits configured; please set 'cache_max_size_mb' or 'cache_max_age' in section '[general]'.");
+    }
+
+    let result = gc::collect(config, &limits, ctx.dry_run).chain_err(|| "failed to collect cache")?;
+    output(&result, ctx.dry_run, &config.general.output_format)
+}
+
+fn output(result: &GcResult, dry_run: bool, format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            let removed = if dry_run { "Dry run: would remove" } else { "Removed" };
+            msgln(format!("{} {} of {} file(s), {:.1} of {:.1} MB.",
+                          removed, result.removed_files, result.files, result.removed_bytes as f64 / MB, result.bytes as f64 / MB));
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(result).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use errors::*;
+use modules::{Context, Module};
+
+use clap::{App, ArgMatches, SubCommand};
+
+pub const NAME: &'static str = "cache";
+
+mod gc;
+
+pub struct Cache;
+
+impl Module for Cache {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn about(&self) -> &'static str {
+        "Manages the cache directory"
+    }
+
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        SubCommand::with_name(NAME)
+            .about(self.about())
+            .subcommand(gc::build_sub_cli())
+    }
+
+    fn call(&self, cli_args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        let subcommand = cli_args.unwrap();
+        let subcommand_name = subcommand.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+        match subcommand_name {
+            gc::NAME => gc::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            _ => Ok(())
+        }
+    }
+}
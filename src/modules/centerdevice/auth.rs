@@ -1,6 +1,12 @@
+use super::{AUTH_ENDPOINT, NAME as MODULE_NAME, TOKEN_ENDPOINT};
 use config::Config;
-use net::oauth::*;
+use credentials;
+use credentials::Credential;
+use errors::*;
+use modules::Context;
+use net::oauth::{CliOAuth, CodeWithBasicAuth, CodeWithBasicAuthSchemeResult};
 use utils::console::*;
+use utils::time::unix_now;
 
 use clap::{App, Arg, ArgMatches, SubCommand};
 use std::str;
@@ -9,15 +15,6 @@ pub const NAME: &'static str = "auth";
 
 static REDIRECT_URI: &'static str = "https://lukaspustina.github.io/rat/redirects/centerdevice.html";
 
-error_chain! {
-    errors {
-       CenterDeviceAuthFailed {
-            description("failed to authenticate with CenterDevice")
-            display("failed to authenticate with CenterDevice")
-        }
-    }
-}
-
 #[derive(Deserialize, Debug)]
 pub struct CenterDeviceToken {
     pub token_type: String,
@@ -34,17 +31,18 @@ pub fn build_sub_cli() -> App<'static, 'static> {
             .help("Open authentication page in default web browser"))
 }
 
-pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
     let open_browser = args.ok_or(false).unwrap().is_present("browser");
-    auth(config, open_browser).chain_err(|| ErrorKind::CenterDeviceAuthFailed)
+    auth(config, open_browser).chain_err(|| "failed to authenticate with CenterDevice")
 }
 
-fn auth(config: &Config, open_browser: bool) -> Result<()> {
+pub fn auth(config: &Config, open_browser: bool) -> Result<()> {
     let oauth = CliOAuth {
         client_id: config.centerdevice.client_id.clone(),
         client_secret: config.centerdevice.client_secret.clone(),
-        auth_endpoint: "https://auth.centerdevice.de/authorize".to_string(),
-        token_endpoint: "https://auth.centerdevice.de/token".to_string(),
+        auth_endpoint: AUTH_ENDPOINT.to_string(),
+        token_endpoint: TOKEN_ENDPOINT.to_string(),
         redirect_uri: REDIRECT_URI.to_string(),
         open_browser: open_browser,
     };
@@ -53,10 +51,13 @@ fn auth(config: &Config, open_browser: bool) -> Result<()> {
         .get_code(&mut vec!(("response_type", "code".to_string())))
         .with_basic_auth()
         .exchange_for_token(config)
-        .chain_err(|| ErrorKind::CenterDeviceAuthFailed)?;
+        .chain_err(|| "failed to authenticate with CenterDevice")?;
 
-    msgln("Received access and refresh token. Please add the following lines to your configuration, section '[centerdevice]'.");
-    msgln(format!("\nrefresh_token = '{}'\naccess_token = '{}'\n", token.refresh_token, token.access_token));
+    let mut credential = Credential::new(token.access_token);
+    credential.refresh_token = Some(token.refresh_token);
+    credential.expires_at = Some(unix_now() + token.expires_in as u64);
+    credentials::store(config, MODULE_NAME, &credential).chain_err(|| "Failed to store access token")?;
+    msgln("Received access and refresh token and stored them in the credential store.");
 
     Ok(())
 }
@@ -1,33 +1,24 @@
 use super::client;
+use super::session;
 use super::client::collections::CollectionsResult;
 
 use cache::Cache;
-use config::{Config, OutputFormat};
+use config::OutputFormat;
+use errors::*;
+use modules::Context;
 use utils::console::*;
 use utils::output;
 
 use clap::{App, Arg, ArgMatches, SubCommand};
 use serde_json;
+use serde_json::Value;
+use std::io;
 use std::io::Write;
 use std::str;
 use tabwriter::TabWriter;
 
 pub const NAME: &'static str = "collections";
 
-error_chain! {
-    errors {
-        CenterDeviceCollectionFailed {
-            description("failed to search for collections")
-            display("failed to search for collections")
-        }
-
-        OutputFailed {
-            description("output failed")
-            display("output failed")
-        }
-    }
-}
-
 pub fn build_sub_cli() -> App<'static, 'static> {
     SubCommand::with_name(NAME)
         .about("Search for collections in CenterDevice")
@@ -49,7 +40,8 @@ pub fn build_sub_cli() -> App<'static, 'static> {
             .help("filters collection names by regex"))
 }
 
-pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
     let args = args.unwrap();
 
     let cache = args.is_present("cache");
@@ -61,9 +53,15 @@ pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
     if include_public {
         info("Including public collections");
     }
-    let json = client::search_collections(
-        config.centerdevice.access_token.as_ref().unwrap(), name, include_public, filter)
-        .chain_err(|| ErrorKind::CenterDeviceCollectionFailed)?;
+    let session = session(config).chain_err(|| "failed to search for collections")?;
+    if config.general.output_format.is_json() && filter.is_none() && !cache {
+        let stdout = io::stdout();
+        client::search_collections_to(&session, name, include_public, stdout.lock())
+            .chain_err(|| "failed to search for collections")?;
+        return Ok(());
+    }
+    let json = client::search_collections(&session, name, include_public, filter)
+        .chain_err(|| "failed to search for collections")?;
 
     if cache {
         info("Updating cache ...");
@@ -78,13 +76,25 @@ pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
 fn output(json: &str, format: &OutputFormat) -> Result<()> {
     match *format {
         OutputFormat::HUMAN => output_human(json),
-        OutputFormat::JSON => output::as_json(json).chain_err(|| ErrorKind::OutputFailed),
+        OutputFormat::JSON | OutputFormat::NDJSON => output::as_json(json).chain_err(|| ErrorKind::OutputFailed),
     }
 }
 
 fn output_human(json: &str) -> Result<()> {
+    if let Some(template) = output::template() {
+        let result: Value = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
+        let items = result.get("collections").and_then(|items| items.as_array()).cloned().unwrap_or_default();
+        msg(template.render_all(&items));
+        return Ok(());
+    }
+    msg(render_human(json)?);
+
+    Ok(())
+}
+
+fn render_human(json: &str) -> Result<String> {
     let result: CollectionsResult = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
-    msgln(format!("Found {} collection(s) matching the search parameters:", result.collections.len()));
+    let mut out = format!("Found {} collection(s) matching the search parameters:\n", result.collections.len());
 
     let mut tw = TabWriter::new(vec![]);
     for c in result.collections {
@@ -97,7 +107,49 @@ fn output_human(json: &str) -> Result<()> {
     }
     tw.flush().unwrap();//.chain_err("|| Failed to create output table");
     let written = String::from_utf8(tw.into_inner().unwrap()).unwrap();
-    msgln(written);
+    out.push_str(&written);
+    out.push('\n');
 
-    Ok(())
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing::golden::*;
+
+    #[test]
+    fn test_render_human() {
+        let json = fixture("centerdevice/collections.json");
+
+        assert_golden("centerdevice/collections.txt", &render_human(&json).unwrap());
+    }
+
+    #[test]
+    fn test_render_json() {
+        let json = fixture("centerdevice/collections.json");
+
+        assert_golden("centerdevice/collections.json", &output::render_json(&json, true).unwrap());
+    }
+}
+
+#[cfg(all(test, feature = "live-tests"))]
+mod live_test {
+    use super::*;
+    use testing::live;
+
+    #[test]
+    fn test_search_collections() {
+        let access_token = match live::credential("RAT_LIVE_CENTERDEVICE_ACCESS_TOKEN") {
+            Some(access_token) => access_token,
+            None => return,
+        };
+        let config = live::config(&format!(
+            "[centerdevice]\nclient_id = 'live-test'\nclient_secret = 'live-test'\naccess_token = '{}'\n", access_token));
+        let session = live::check(session(&config).chain_err(|| "failed to create session"));
+
+        let json = live::check(client::search_collections(&session, None, true, None));
+
+        live::check(render_human(&json));
+    }
 }
@@ -1,6 +1,11 @@
 use super::client;
+use super::session;
 
-use config::{Config, OutputFormat};
+use super::NAME as MODULE_NAME;
+use config::OutputFormat;
+use credentials;
+use errors::*;
+use modules::Context;
 use utils::console::*;
 use utils::output;
 
@@ -11,20 +16,6 @@ use std::str;
 
 pub const NAME: &'static str = "delete";
 
-error_chain! {
-    errors {
-        CenterDeviceDeleteFailed {
-            description("failed to delete document")
-            display("failed to delete document")
-        }
-
-        OutputFailed {
-            description("output failed")
-            display("output failed")
-        }
-    }
-}
-
 
 
 pub fn build_sub_cli() -> App<'static, 'static> {
@@ -37,25 +28,44 @@ pub fn build_sub_cli() -> App<'static, 'static> {
             .help("ID of document to delete"))
 }
 
-pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
     let args = args.unwrap();
+    credentials::ensure_writable(config, MODULE_NAME).chain_err(|| "failed to delete document")?;
 
     let document_ids: Vec<&str> = args.values_of("document-id").map(|c| c.collect()).unwrap();
 
+    if ctx.dry_run {
+        msgln(format!("Dry run: would delete documents with ids '{:?}'.", document_ids));
+        return Ok(());
+    }
+
     info(format!("Deleting documents with ids '{:?}' ...", document_ids));
-    let json = client::delete_documents(
-        config.centerdevice.access_token.as_ref().unwrap(),
-        document_ids
-    ).chain_err(|| ErrorKind::CenterDeviceDeleteFailed)?;
+    let count = document_ids.len();
+    let session = session(config).chain_err(|| "failed to delete document")?;
+    let json = client::delete_documents(&session, document_ids)
+        .chain_err(|| "failed to delete document")?;
+
+    output(&json, &config.general.output_format)?;
+    check_failures(&json, count)
+}
 
-    output(&json, &config.general.output_format)
+fn check_failures(json: &str, count: usize) -> Result<()> {
+    if let Ok(result) = serde_json::from_str::<DeleteResult>(json) {
+        let failed = result.failed_documents.len();
+        if failed > 0 {
+            bail!(ErrorKind::PartialFailure(count.saturating_sub(failed), failed));
+        }
+    }
+
+    Ok(())
 }
 
 
 fn output(json: &str, format: &OutputFormat) -> Result<()> {
     match *format {
         OutputFormat::HUMAN => output_human(json),
-        OutputFormat::JSON => output::as_json(json).chain_err(|| ErrorKind::OutputFailed),
+        OutputFormat::JSON | OutputFormat::NDJSON => output::as_json(json).chain_err(|| ErrorKind::OutputFailed),
     }
 }
 
@@ -1,6 +1,9 @@
 use super::client;
+use super::session;
 
-use config::{Config, OutputFormat, Verbosity};
+use config::OutputFormat;
+use errors::*;
+use modules::Context;
 use utils::console::*;
 use utils::output;
 
@@ -10,20 +13,6 @@ use std::str;
 
 pub const NAME: &'static str = "download";
 
-error_chain! {
-    errors {
-        CenterDeviceDownloadFailed {
-            description("failed to download document")
-            display("failed to download document")
-        }
-
-        OutputFailed {
-            description("output failed")
-            display("output failed")
-        }
-    }
-}
-
 pub fn build_sub_cli() -> App<'static, 'static> {
     SubCommand::with_name(NAME)
         .about("Download document from CenterDevice")
@@ -38,7 +27,8 @@ pub fn build_sub_cli() -> App<'static, 'static> {
             .help("id of document to download"))
 }
 
-pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
     let args = args.unwrap();
     let filename: Option<&str> = args.value_of("filename");
     let doc_id = args.value_of("id").unwrap();
@@ -47,7 +37,7 @@ pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
 
     let mut progress_bar: Option<ProgressBar> = None;
     let mut progress = None;
-    if config.general.output_format == OutputFormat::HUMAN && config.general.verbosity <= Verbosity::NORMAL {
+    if ctx.show_progress() {
         let pb = ProgressBar::new(0);
         pb.set_style(ProgressStyle::default_bar()
             .template("[{elapsed_precise}] [{bar:40.blue/blue}] {bytes}/{total_bytes} ({eta}) {msg} {spinner:.blue}")
@@ -60,8 +50,9 @@ pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
             pb.inc(delta as u64);
         });
     };
-    client::download_document(config.centerdevice.access_token.as_ref().unwrap(), filename, doc_id, progress)
-        .chain_err(|| ErrorKind::CenterDeviceDownloadFailed)?;
+    let session = session(config).chain_err(|| "failed to download document")?;
+    client::download_document(&session, filename, doc_id, progress)
+        .chain_err(|| "failed to download document")?;
 
     if let Some(ref pb) = progress_bar {
         pb.finish_with_message("done");
@@ -73,6 +64,6 @@ pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
 fn output(json: &str, format: &OutputFormat) -> Result<()> {
     match *format {
         OutputFormat::HUMAN => Ok(()),
-        OutputFormat::JSON => output::as_json(json).chain_err(|| ErrorKind::OutputFailed),
+        OutputFormat::JSON | OutputFormat::NDJSON => output::as_json(json).chain_err(|| ErrorKind::OutputFailed),
     }
 }
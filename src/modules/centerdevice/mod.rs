@@ -1,12 +1,17 @@
+pub use clients::centerdevice::{NAME, AUTH_ENDPOINT, TOKEN_ENDPOINT, CenterDeviceConfig, CenterDeviceAccountConfig};
+
+use clients::centerdevice::{client, oauth_client};
 use config::Config;
+use credentials;
+use credentials::Credential;
 use errors::*;
+use modules::{Context, Module};
+use net::oauth::oauth2;
+use net::oauth::oauth2::TokenSession;
 
 use clap::{App, ArgMatches, SubCommand};
 
-pub const NAME: &'static str = "centerdevice";
-
 mod auth;
-mod client;
 mod collections;
 mod delete;
 mod download;
@@ -15,47 +20,80 @@ mod search;
 mod status;
 mod upload;
 
-#[derive(Debug, Deserialize)]
-pub struct CenterDeviceConfig {
-    client_id: String,
-    client_secret: String,
-    refresh_token: Option<String>,
-    access_token: Option<String>,
+/// Tokens from the credential store take precedence over tokens from the configuration file, because the latter
+/// cannot be updated when a token is refreshed.
+pub fn session<'a>(config: &'a Config) -> oauth2::Result<TokenSession<'a>> {
+    let fallback = config.centerdevice.access_token.as_ref().map(|access_token| {
+        let mut credential = Credential::new(access_token.clone());
+        credential.refresh_token = config.centerdevice.refresh_token.clone();
+        credential.obtained_at = None;
+        credential
+    });
+    let session = TokenSession::new(config, NAME, oauth_client(config), fallback)?.with_reauth(reauth);
+
+    Ok(session)
 }
 
-pub fn build_sub_cli() -> App<'static, 'static> {
-    SubCommand::with_name(NAME)
-        .about("CenterDevice status etc.")
-        .subcommand(auth::build_sub_cli())
-        .subcommand(collections::build_sub_cli())
-        .subcommand(delete::build_sub_cli())
-        .subcommand(download::build_sub_cli())
-        .subcommand(refresh_token::build_sub_cli())
-        .subcommand(search::build_sub_cli())
-        .subcommand(status::build_sub_cli())
-        .subcommand(upload::build_sub_cli())
+fn reauth(config: &Config, _: &str) -> ::std::result::Result<(), String> {
+    auth::auth(config, false).map_err(|e| e.to_string())
 }
 
-pub fn call(cli_args: Option<&ArgMatches>, config: &Config) -> Result<()> {
-    let subcommand = cli_args.unwrap();
-    let subcommand_name = subcommand.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
-    match subcommand_name {
-        auth::NAME => auth::call(subcommand.subcommand_matches(subcommand_name), config)
-            .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
-        collections::NAME => collections::call(subcommand.subcommand_matches(subcommand_name), config)
-            .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
-        delete::NAME => delete::call(subcommand.subcommand_matches(subcommand_name), config)
-            .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
-        download::NAME => download::call(subcommand.subcommand_matches(subcommand_name), config)
-            .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
-        refresh_token::NAME => refresh_token::call(subcommand.subcommand_matches(subcommand_name), config)
-            .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
-        search::NAME => search::call(subcommand.subcommand_matches(subcommand_name), config)
-            .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
-        status::NAME => status::call(subcommand.subcommand_matches(subcommand_name), config)
-            .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
-        upload::NAME => upload::call(subcommand.subcommand_matches(subcommand_name), config)
-            .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
-        _ => Ok(())
+/// CenterDevice does not support scopes, so a read-only login only marks the stored token as read-only.
+pub fn login(config: &Config, open_browser: bool, read_only: bool) -> Result<()> {
+    auth::auth(config, open_browser).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+    if read_only {
+        credentials::mark_read_only(config, NAME).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
     }
+
+    Ok(())
 }
+
+pub struct CenterDevice;
+
+impl Module for CenterDevice {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn about(&self) -> &'static str {
+        "CenterDevice status etc."
+    }
+
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        SubCommand::with_name(NAME)
+            .about(self.about())
+            .subcommand(auth::build_sub_cli())
+            .subcommand(collections::build_sub_cli())
+            .subcommand(delete::build_sub_cli())
+            .subcommand(download::build_sub_cli())
+            .subcommand(refresh_token::build_sub_cli())
+            .subcommand(search::build_sub_cli())
+            .subcommand(status::build_sub_cli())
+            .subcommand(upload::build_sub_cli())
+    }
+
+    fn call(&self, cli_args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        let subcommand = cli_args.unwrap();
+        let subcommand_name = subcommand.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+        match subcommand_name {
+            auth::NAME => auth::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            collections::NAME => collections::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            delete::NAME => delete::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            download::NAME => download::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            refresh_token::NAME => refresh_token::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            search::NAME => search::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            status::NAME => status::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            upload::NAME => upload::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            _ => Ok(())
+        }
+    }
+}
+
@@ -1,9 +1,12 @@
-use super::client;
-use super::auth::CenterDeviceToken;
+use super::session;
 
-use config::{Config, OutputFormat};
+use config::OutputFormat;
+use credentials::Credential;
+use errors::*;
+use modules::Context;
 use utils::console::*;
 use utils::output;
+use utils::time::unix_now;
 
 use clap::{App, ArgMatches, SubCommand};
 use serde_json;
@@ -11,32 +14,17 @@ use std::str;
 
 pub const NAME: &'static str = "refresh_token";
 
-error_chain! {
-    errors {
-        CenterDeviceRefrehTokenFailed {
-            description("failed to refresh access token")
-            display("failed to refresh access token")
-        }
-
-        OutputFailed {
-            description("output failed")
-            display("output failed")
-        }
-    }
-}
-
 pub fn build_sub_cli() -> App<'static, 'static> {
     SubCommand::with_name(NAME)
         .about("Re-fresh access token")
 }
 
-pub fn call(_: Option<&ArgMatches>, config: &Config) -> Result<()> {
+pub fn call(_: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
     info(format!("Refreshing access token"));
-    let json = client::refresh_token(
-        config.centerdevice.refresh_token.as_ref().unwrap(),
-        &config.centerdevice.client_id,
-        &config.centerdevice.client_secret,
-    ).chain_err(|| ErrorKind::CenterDeviceRefrehTokenFailed)?;
+    let session = session(config).chain_err(|| "failed to refresh access token")?;
+    session.refresh().chain_err(|| "failed to refresh access token")?;
+    let json = serde_json::to_string(&*session.credential()).chain_err(|| "JSON serialization failed")?;
 
     output(&json, &config.general.output_format)
 }
@@ -45,16 +33,18 @@ pub fn call(_: Option<&ArgMatches>, config: &Config) -> Result<()> {
 fn output(json: &str, format: &OutputFormat) -> Result<()> {
     match *format {
         OutputFormat::HUMAN => output_human(json),
-        OutputFormat::JSON => output::as_json(json).chain_err(|| ErrorKind::OutputFailed),
+        OutputFormat::JSON | OutputFormat::NDJSON => output::as_json(json).chain_err(|| ErrorKind::OutputFailed),
     }
 }
 
 fn output_human(json: &str) -> Result<()> {
-    let result: CenterDeviceToken = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
-    let days = result.expires_in / 60 / 60 / 24;
-    msgln(format!("Received refreshed access token expiring in {} days. Please change the following line in your configuration, section '[centerdevice]'.",
-                  days));
-    msgln(format!("\naccess_token = '{}'\n", result.access_token));
+    let result: Credential = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
+    if let Some(expires_at) = result.expires_at {
+        let days = expires_at.saturating_sub(unix_now()) / 60 / 60 / 24;
+        msgln(format!("Refreshed access token expiring in {} days and stored it in the credential store.", days));
+    } else {
+        msgln("Refreshed access token and stored it in the credential store.");
+    }
 
     Ok(())
 }
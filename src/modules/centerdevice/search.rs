@@ -1,30 +1,21 @@
 use super::client;
+use super::session;
 
-use config::{Config, OutputFormat};
+use config::OutputFormat;
+use errors::*;
+use modules::Context;
 use utils::console::*;
 use utils::output;
 
 use clap::{App, Arg, ArgMatches, SubCommand};
 use serde_json;
+use serde_json::Value;
 use std::fmt;
+use std::io;
 use std::str;
 
 pub const NAME: &'static str = "search";
 
-error_chain! {
-    errors {
-        CenterDeviceSearchFailed {
-            description("failed to search for documents")
-            display("failed to search for documents")
-        }
-
-        OutputFailed {
-            description("output failed")
-            display("output failed")
-        }
-    }
-}
-
 pub fn build_sub_cli() -> App<'static, 'static> {
     SubCommand::with_name(NAME)
         .about("Search for documents in CenterDevice")
@@ -49,7 +40,8 @@ pub fn build_sub_cli() -> App<'static, 'static> {
             .help("Add fulltext to search"))
 }
 
-pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
     let args = args.unwrap();
 
     let filenames: Option<Vec<&str>> = args.values_of("filename").map(|c| c.collect());
@@ -65,9 +57,15 @@ pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
     if named == client::search::NamedSearches::PublicCollections {
         info("Including public collections");
     }
-    let json = client::search_documents(
-        config.centerdevice.access_token.as_ref().unwrap(), filenames, tags, fulltext, named)
-        .chain_err(|| ErrorKind::CenterDeviceSearchFailed)?;
+    let session = session(config).chain_err(|| "failed to search for documents")?;
+    if config.general.output_format.is_json() {
+        let stdout = io::stdout();
+        client::search_documents_to(&session, filenames, tags, fulltext, named, stdout.lock())
+            .chain_err(|| "failed to search for documents")?;
+        return Ok(());
+    }
+    let json = client::search_documents(&session, filenames, tags, fulltext, named)
+        .chain_err(|| "failed to search for documents")?;
 
     output(&json, &config.general.output_format)
 }
@@ -76,7 +74,7 @@ pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
 fn output(json: &str, format: &OutputFormat) -> Result<()> {
     match *format {
         OutputFormat::HUMAN => output_human(json),
-        OutputFormat::JSON => output::as_json(json).chain_err(|| ErrorKind::OutputFailed),
+        OutputFormat::JSON | OutputFormat::NDJSON => output::as_json(json).chain_err(|| ErrorKind::OutputFailed),
     }
 }
 
@@ -120,15 +118,68 @@ impl fmt::Display for Representations {
 }
 
 fn output_human(json: &str) -> Result<()> {
+    if let Some(template) = output::template() {
+        let result: Value = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
+        let items = result.get("documents").and_then(|items| items.as_array()).cloned().unwrap_or_default();
+        msg(template.render_all(&items));
+        return Ok(());
+    }
+    msg(render_human(json)?);
+
+    Ok(())
+}
+
+fn render_human(json: &str) -> Result<String> {
     let result: SearchResult = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
-    msgln(format!("Found {} document(s) matching the search parameters:", result.hits));
+    let mut out = format!("Found {} document(s) matching the search parameters:\n", result.hits);
 
     if let Some(documents) = result.documents {
         for d in documents {
-            msgln(format!("* {}: '{}', version {}, {} bytes, uploaded {}, created {}, {}",
+            out.push_str(&format!("* {}: '{}', version {}, {} bytes, uploaded {}, created {}, {}\n",
                           d.id, d.filename, d.version, d.size, d.version_date, d.upload_date, d.representations));
         }
     }
 
-    Ok(())
-}
\ No newline at end of file
+    Ok(out)
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing::golden::*;
+
+    #[test]
+    fn test_render_human() {
+        let json = fixture("centerdevice/search.json");
+
+        assert_golden("centerdevice/search.txt", &render_human(&json).unwrap());
+    }
+
+    #[test]
+    fn test_render_json() {
+        let json = fixture("centerdevice/search.json");
+
+        assert_golden("centerdevice/search.json", &output::render_json(&json, true).unwrap());
+    }
+}
+
+#[cfg(all(test, feature = "live-tests"))]
+mod live_test {
+    use super::*;
+    use testing::live;
+
+    #[test]
+    fn test_search_documents() {
+        let access_token = match live::credential("RAT_LIVE_CENTERDEVICE_ACCESS_TOKEN") {
+            Some(access_token) => access_token,
+            None => return,
+        };
+        let config = live::config(&format!(
+            "[centerdevice]\nclient_id = 'live-test'\nclient_secret = 'live-test'\naccess_token = '{}'\n", access_token));
+        let session = live::check(session(&config).chain_err(|| "failed to create session"));
+
+        let json = live::check(client::search_documents(
+            &session, None, None, None, client::search::NamedSearches::PublicCollections));
+
+        live::check(render_human(&json));
+    }
+}
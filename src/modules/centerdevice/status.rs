@@ -1,4 +1,6 @@
 use config::{Config, OutputFormat};
+use errors::*;
+use modules::Context;
 use utils::console::*;
 use utils::output;
 
@@ -11,15 +13,6 @@ use webbrowser;
 
 pub const NAME: &'static str = "status";
 
-error_chain! {
-    errors {
-       CenterDeviceStatusFailed {
-            description("failed to get CenterDevice status")
-            display("failed to get CenterDevice status")
-        }
-    }
-}
-
 #[derive(Deserialize, Debug)]
 enum Status {
     Okay,
@@ -88,7 +81,6 @@ struct Checks {
     checks: Vec<Vec<Check>>,
 }
 
-
 #[derive(Deserialize, Debug)]
 struct Check {
     hostname: String,
@@ -127,16 +119,17 @@ pub fn build_sub_cli() -> App<'static, 'static> {
             .help("Open status in web browser"))
 }
 
-pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
     let details = args.ok_or(false).unwrap().is_present("details");
     let browser = args.ok_or(false).unwrap().is_present("browser");
 
     if browser {
         info("Opening CenterDevice Status in default browser ...");
-        browse(config, details).chain_err(|| ErrorKind::CenterDeviceStatusFailed)
+        browse(config, details).chain_err(|| "failed to get CenterDevice status")
     } else {
         info("Getting CenterDevice Status ...");
-        status(config, details).chain_err(|| ErrorKind::CenterDeviceStatusFailed)
+        status(config, details).chain_err(|| "failed to get CenterDevice status")
     }
 }
 
@@ -147,7 +140,7 @@ fn browse(config: &Config, details: bool) -> Result<()> {
         webbrowser::open("http://status.centerdevice.de")
     }.chain_err(|| "Failed to open default browser")?;
 
-    if config.general.output_format == OutputFormat::JSON { msgln("{}"); }
+    if config.general.output_format.is_json() { msgln("{}"); }
 
     Ok(())
 }
@@ -162,7 +155,7 @@ fn get_centerdevice_status_json() -> Result<String> {
     let mut response = Client::new()
         .get(url)
         .send()
-        .chain_err(|| ErrorKind::CenterDeviceStatusFailed)?;
+        .chain_err(|| "failed to get CenterDevice status")?;
 
     let mut buffer = Vec::new();
     response.read_to_end(&mut buffer).chain_err(|| "Failed to read HTTP response")?;
@@ -174,8 +167,8 @@ fn get_centerdevice_status_json() -> Result<String> {
 fn output(json: &str, format: &OutputFormat, details: bool) -> Result<()> {
     match *format {
         OutputFormat::HUMAN => output_human(json, details),
-        OutputFormat::JSON => output::as_json(json)
-            .chain_err(|| ErrorKind::CenterDeviceStatusFailed),
+        OutputFormat::JSON | OutputFormat::NDJSON => output::as_json(json)
+            .chain_err(|| "failed to get CenterDevice status"),
     }
 }
 
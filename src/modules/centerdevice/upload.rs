@@ -1,8 +1,13 @@
 use super::client;
+use super::session;
 use super::client::collections::CollectionsResult;
 
 use cache::Cache;
-use config::{Config, OutputFormat};
+use super::NAME as MODULE_NAME;
+use config::OutputFormat;
+use credentials;
+use errors::*;
+use modules::Context;
 use utils::console::*;
 use utils::output;
 
@@ -16,30 +21,6 @@ use std::str;
 
 pub const NAME: &'static str = "upload";
 
-error_chain! {
-    errors {
-        CenterDeviceUploadFailed {
-            description("failed to upload document")
-            display("failed to upload document")
-        }
-
-        MimeTypeParsingFailed(mime_type: String) {
-            description("failed to parse mime type")
-            display("failed to parse mime type '{}'", mime_type)
-        }
-
-        MimeTypeGuessFailed {
-            description("failed to guess mime type")
-            display("failed to guess mime type")
-        }
-
-        OutputFailed {
-            description("output failed")
-            display("output failed")
-        }
-    }
-}
-
 
 
 pub fn build_sub_cli() -> App<'static, 'static> {
@@ -83,15 +64,17 @@ pub fn build_sub_cli() -> App<'static, 'static> {
             .help("file to upload"))
 }
 
-pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
     let args = args.unwrap();
+    credentials::ensure_writable(config, MODULE_NAME).chain_err(|| "failed to upload document")?;
 
     let file = args.value_of("file").unwrap();
     let file_path = Path::new(file);
     let mime_type: Mime = if let Some(mt) = args.value_of("mime-type") {
-        mt.parse().map_err(|_| ErrorKind::MimeTypeParsingFailed(mt.to_string()))?
+        mt.parse().map_err(|_| ErrorKind::Parse(format!("mime type '{}'", mt)))?
     } else {
-        guess_mime_type_opt(&file_path).ok_or(ErrorKind::MimeTypeGuessFailed)?
+        guess_mime_type_opt(&file_path).ok_or("failed to guess mime type")?
     };
     let filename = if let Some(filename) = args.value_of("filename") {
         filename
@@ -120,16 +103,22 @@ pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
         }
     }
 
+    if ctx.dry_run {
+        msgln(format!("Dry run: would upload file '{}' as '{}' with mime type '{}'.", file, filename, mime_type));
+        return Ok(());
+    }
+
     info(format!("Uploading file '{}' ...", filename));
+    let session = session(config).chain_err(|| "failed to upload document")?;
     let json = client::upload_document(
-        config.centerdevice.access_token.as_ref().unwrap(),
+        &session,
         file_path,
         filename,
         mime_type,
         title,
         tags,
         collections
-    ).chain_err(|| ErrorKind::CenterDeviceUploadFailed)?;
+    ).chain_err(|| "failed to upload document")?;
 
     output(&json, &config.general.output_format)
 }
@@ -138,7 +127,7 @@ pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
 fn output(json: &str, format: &OutputFormat) -> Result<()> {
     match *format {
         OutputFormat::HUMAN => output_human(json),
-        OutputFormat::JSON => output::as_json(json).chain_err(|| ErrorKind::OutputFailed),
+        OutputFormat::JSON | OutputFormat::NDJSON => output::as_json(json).chain_err(|| ErrorKind::OutputFailed),
     }
 }
 
@@ -0,0 +1,165 @@
+use config::{Config, OutputFormat};
+use errors::*;
+use modules::Context;
+use net::http;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{self, Value};
+
+pub const NAME: &'static str = "get";
+
+static REDACTED: &'static str = "********";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Prints the effective configuration including defaults; secrets are redacted")
+        .arg(Arg::with_name("key")
+            .index(1)
+            .help("Prints only this setting or section, e.g., 'general.verbosity' or 'pocket'"))
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let args = args.unwrap();
+
+    let settings = effective(ctx.config)?;
+    let selected = match args.value_of("key") {
+        Some(key) => settings.pointer(&pointer(key)).cloned()
+            .ok_or_else(|| ErrorKind::Config(format!("setting '{}' is not set", key)))?,
+        None => settings,
+    };
+
+    output(args.value_of("key"), &selected, &ctx.output_format())
+}
+
+/// The configuration as it is applied, i.e., with command line overrides and defaults of unset settings. Output formats
+/// and verbosities are given by name as accepted by `rat config set`.
+fn effective(config: &Config) -> Result<Value> {
+    let general = &config.general;
+    let http_settings = general.http_settings();
+    let retry_policy = general.retry_policy();
+    let mut settings = serde_json::to_value(config).chain_err(|| "JSON serialization failed")?;
+    {
+        let mut set = |key: &str, value: Value| if let Some(setting) = settings.pointer_mut(&pointer(key)) {
+            *setting = value;
+        };
+        set("general.output_format", Value::String(format!("{:?}", general.output_format)));
+        set("general.verbosity", Value::String(format!("{:?}", general.verbosity)));
+        set("general.credential_store", Value::String(general.credential_store.clone().unwrap_or_else(|| "file".to_string())));
+        set("general.interactive_reauth", Value::Bool(general.interactive_reauth.unwrap_or(false)));
+        set("general.max_concurrency", Value::from(general.max_concurrency() as u64));
+        set("general.retry_max_attempts", Value::from(retry_policy.max_attempts));
+        set("general.retry_base_delay_ms", Value::from(general.retry_base_delay_ms.unwrap_or(::net::retry::DEFAULT_BASE_DELAY_MS)));
+        set("general.retry_jitter", Value::Bool(retry_policy.jitter));
+        set("general.connect_timeout_secs", Value::from(general.connect_timeout_secs.unwrap_or(http::DEFAULT_CONNECT_TIMEOUT_SECS)));
+        set("pocket.batch_size", Value::from(config.pocket.batch_size() as u64));
+        set("general.read_timeout_secs", Value::from(general.read_timeout_secs.unwrap_or(http::DEFAULT_READ_TIMEOUT_SECS)));
+        if let Some(proxy) = http_settings.proxy {
+            set("general.proxy", Value::String(proxy));
+        }
+        if !http_settings.no_proxy.is_empty() {
+            set("general.no_proxy", Value::Array(http_settings.no_proxy.into_iter().map(Value::String).collect()));
+        }
+        for (section, name, _) in config.secrets() {
+            set(&format!("{}.{}", section, name), Value::String(REDACTED.to_string()));
+        }
+    }
+
+    Ok(without_nulls(settings))
+}
+
+/// JSON pointer of a dotted key
+fn pointer(key: &str) -> String {
+    key.split('.').map(|part| format!("/{}", part)).collect()
+}
+
+/// Unset settings are left out instead of being shown as `null`
+fn without_nulls(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(map.into_iter()
+            .filter(|&(_, ref value)| !value.is_null())
+            .map(|(key, value)| (key, without_nulls(value)))
+            .filter(|&(_, ref value)| value.as_object().map(|map| !map.is_empty()).unwrap_or(true))
+            .collect()),
+        value => value,
+    }
+}
+
+fn output(key: Option<&str>, settings: &Value, format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            match *settings {
+                // Single settings are printed bare, so scripts can use them right away
+                Value::String(ref value) => msgln(value.as_str()),
+                Value::Object(_) => {
+                    let mut lines = Vec::new();
+                    flatten(key.unwrap_or(""), settings, &mut lines);
+                    msg(lines.iter().map(|line| format!("{}\n", line)).collect::<String>());
+                }
+                ref value => msgln(value.to_string()),
+            }
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(settings).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+/// One line 'key = value' per setting with the value as JSON, which is valid TOML for strings, numbers, and arrays
+fn flatten(prefix: &str, value: &Value, lines: &mut Vec<String>) {
+    match *value {
+        Value::Object(ref map) => for (key, value) in map {
+            let key = if prefix.is_empty() { key.to_string() } else { format!("{}.{}", prefix, key) };
+            flatten(&key, value, lines);
+        },
+        ref value => lines.push(format!("{} = {}", prefix, value)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rat_core::testing::ConfigBuilder;
+
+    fn config() -> Config {
+        ConfigBuilder::new("config-get").general("max_concurrency", "8").sections(r#"
+            [slack]
+            client_id = 'client id'
+            client_secret = 'client secret'
+
+            [slack.accounts.work]
+            access_token = 'work token'
+        "#).build()
+    }
+
+    #[test]
+    fn test_effective_applies_defaults_and_redacts_secrets() {
+        let settings = effective(&config()).unwrap();
+
+        assert_eq!(settings.pointer("/general/verbosity"), Some(&Value::String("QUIET".to_string())));
+        assert_eq!(settings.pointer("/general/max_concurrency"), Some(&Value::from(8u64)));
+        assert_eq!(settings.pointer("/general/retry_max_attempts"), Some(&Value::from(::net::retry::DEFAULT_MAX_ATTEMPTS)));
+        assert_eq!(settings.pointer("/centerdevice/client_id"), Some(&Value::String("client id".to_string())));
+        assert_eq!(settings.pointer("/centerdevice/client_secret"), Some(&Value::String(REDACTED.to_string())));
+        assert_eq!(settings.pointer("/slack/accounts/work/access_token"), Some(&Value::String(REDACTED.to_string())));
+        assert_eq!(settings.pointer("/slack/access_token"), None);
+    }
+
+    #[test]
+    fn test_flatten() {
+        let settings = effective(&config()).unwrap();
+        let mut lines = Vec::new();
+
+        flatten("slack", settings.pointer("/slack").unwrap(), &mut lines);
+
+        assert_eq!(lines, vec![
+            r#"slack.accounts.work.access_token = "********""#,
+            r#"slack.client_id = "client id""#,
+            r#"slack.client_secret = "********""#,
+        ]);
+    }
+}
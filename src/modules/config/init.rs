@@ -0,0 +1,123 @@
+use clients::pocket;
+use errors::*;
+use net::{concurrent, http, retry};
+use utils::console::*;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+pub const NAME: &'static str = "init";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Writes a new configuration file with commented defaults")
+        .arg(Arg::with_name("force")
+            .long("force")
+            .help("Overwrites an existing configuration file"))
+}
+
+pub fn call(args: Option<&ArgMatches>, path: &Path) -> Result<()> {
+    let args = args.unwrap();
+
+    if path.exists() && !args.is_present("force") {
+        bail!(ErrorKind::Config(format!("configuration file '{}' already exists; use '--force' to overwrite it", path.display())));
+    }
+    let cache_dir = env::home_dir().map(|home| home.join(".cache").join("rat")).unwrap_or_else(|| env::temp_dir().join("rat"));
+    // E.g., '~/.config/rat' of the default location
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).chain_err(|| format!("Could not create directory '{}'", dir.display()))?;
+    }
+    File::create(path)
+        .and_then(|mut file| file.write_all(template(&cache_dir.to_string_lossy()).as_bytes()))
+        .chain_err(|| format!("Could not write configuration file '{}'", path.display()))?;
+    msgln(format!("Wrote configuration file '{}'; set the credentials of the services you use with 'rat config set'.", path.display()));
+
+    Ok(())
+}
+
+/// Required settings are set; optional settings are commented out and show their defaults.
+fn template(cache_dir: &str) -> String {
+    format!(r#"# Configuration of rat -- the REST API tool; cf. https://github.com/lukaspustina/rat
+# Change settings with 'rat config set <section>.<setting> <value>' and check them with 'rat config validate'.
+
+[general]
+cache_dir = '{cache_dir}'
+output_format = {{ format = 'HUMAN' }}       # HUMAN, JSON, or NDJSON
+verbosity = {{ verbosity = 'NORMAL' }}       # QUIET, NORMAL, VERBOSE, or TRACE
+# credential_store = 'file'                 # or 'encrypted' or 'keyring'
+# passphrase_command = 'pass show rat'
+# interactive_reauth = false
+# max_concurrency = {max_concurrency}
+# cache_max_size_mb = 500
+# cache_max_age = '90days'
+# response_cache_ttl = '5min'
+# retry_max_attempts = {retry_max_attempts}
+# retry_base_delay_ms = {retry_base_delay_ms}
+# retry_jitter = true
+# proxy = 'http://proxy.example.com:3128'   # defaults to environment variable 'https_proxy'
+# no_proxy = ['localhost']                  # defaults to environment variable 'no_proxy'
+# ca_bundle = '/etc/ssl/corporate-ca.pem'
+# connect_timeout_secs = {connect_timeout_secs}
+# read_timeout_secs = {read_timeout_secs}
+
+[centerdevice]
+client_id = '<client id>'
+client_secret = '<client secret>'
+
+[feeds]
+# rust = 'https://blog.rust-lang.org/feed.xml'
+
+[github]
+# access_token = '<personal access token>'
+
+[instapaper]
+# consumer_key = '<consumer key>'
+# consumer_secret = '<consumer secret>'
+# username = '<email address>'
+
+[pinboard]
+# api_token = '<user>:<token>'
+
+[pocket]
+# consumer_key = '<consumer key>'
+# batch_size = {batch_size}
+# queue_offline = false
+
+# [rules.github]
+# domain = 'github.com'
+# add_tags = ['code']
+
+[slack]
+client_id = '<client id>'
+client_secret = '<client secret>'
+
+[wallabag]
+# base_url = 'https://app.wallabag.it'
+# client_id = '<client id>'
+# client_secret = '<client secret>'
+# username = '<user name>'
+"#,
+            cache_dir = cache_dir,
+            max_concurrency = concurrent::DEFAULT_MAX_CONCURRENCY,
+            batch_size = pocket::DEFAULT_BATCH_SIZE,
+            retry_max_attempts = retry::DEFAULT_MAX_ATTEMPTS,
+            retry_base_delay_ms = retry::DEFAULT_BASE_DELAY_MS,
+            connect_timeout_secs = http::DEFAULT_CONNECT_TIMEOUT_SECS,
+            read_timeout_secs = http::DEFAULT_READ_TIMEOUT_SECS)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::validate::problems;
+
+    #[test]
+    fn test_template_is_valid() {
+        let template = template("/tmp/rat");
+
+        assert_eq!(problems(&template), vec![]);
+    }
+}
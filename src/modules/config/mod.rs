@@ -0,0 +1,124 @@
+use config::OutputFormat;
+use errors::*;
+use modules::{Context, Module};
+
+use clap::{App, ArgMatches, SubCommand};
+use std::path::Path;
+
+pub const NAME: &'static str = "config";
+
+mod get;
+mod init;
+mod set;
+mod validate;
+
+pub struct Configuration;
+
+impl Module for Configuration {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn about(&self) -> &'static str {
+        "Manages the configuration file"
+    }
+
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        SubCommand::with_name(NAME)
+            .about(self.about())
+            .subcommand(get::build_sub_cli())
+            .subcommand(init::build_sub_cli())
+            .subcommand(set::build_sub_cli())
+            .subcommand(validate::build_sub_cli())
+    }
+
+    fn call(&self, cli_args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        let subcommand = cli_args.unwrap();
+        let subcommand_name = subcommand.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+        match subcommand_name {
+            get::NAME => get::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            _ => Ok(())
+        }
+    }
+}
+
+/// Whether the command works on the configuration file itself instead of the loaded configuration. These commands have
+/// to work while the file is missing or invalid, so they are dispatched before it is loaded; cf. `call_file_command`.
+pub fn is_file_command(cli_args: &ArgMatches) -> bool {
+    match cli_args.subcommand_name() {
+        Some(init::NAME) | Some(set::NAME) | Some(validate::NAME) => true,
+        _ => false,
+    }
+}
+
+pub fn call_file_command(cli_args: &ArgMatches, path: &Path, format: &OutputFormat) -> Result<()> {
+    let subcommand_name = cli_args.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+    let args = cli_args.subcommand_matches(subcommand_name);
+    match subcommand_name {
+        init::NAME => init::call(args, path),
+        set::NAME => set::call(args, path),
+        validate::NAME => validate::call(args, path, format),
+        _ => Ok(())
+    }.chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))
+}
+
+/// Dotted name of the table of a header line like '[pocket.accounts.work]'
+fn header(line: &str) -> Option<String> {
+    let line = line.trim();
+    if !line.starts_with('[') || line.starts_with("[[") {
+        return None;
+    }
+    line[1..].split(']').next().map(|header| dotted(header.split('.')))
+}
+
+/// Name of the setting of a line like 'consumer_key = "..."'
+fn key_of(line: &str) -> Option<String> {
+    let line = line.trim();
+    if line.starts_with('#') || line.starts_with('[') {
+        return None;
+    }
+    let mut parts = line.splitn(2, '=');
+    match (parts.next(), parts.next()) {
+        (Some(key), Some(_)) if !key.trim().is_empty() => Some(dotted(key.split('.'))),
+        _ => None,
+    }
+}
+
+fn dotted<'a, I: Iterator<Item = &'a str>>(parts: I) -> String {
+    parts.map(|part| part.trim().trim_matches('"').trim_matches('\'')).collect::<Vec<&str>>().join(".")
+}
+
+/// Line number, starting at 1, of a setting or section given by its dotted key
+fn line_of(content: &str, key: &str) -> Option<usize> {
+    let mut section = String::new();
+    for (i, line) in content.lines().enumerate() {
+        if let Some(header) = header(line) {
+            if header == key {
+                return Some(i + 1);
+            }
+            section = header;
+        } else if let Some(name) = key_of(line) {
+            let full = if section.is_empty() { name } else { format!("{}.{}", section, name) };
+            if full == key {
+                return Some(i + 1);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_line_of() {
+        let content = "[general]\ncache_dir = '/tmp'\n\n[pocket.accounts.\"work\"] # Pocket at work\naccess_token = 'token'\n";
+
+        assert_eq!(line_of(content, "general.cache_dir"), Some(2));
+        assert_eq!(line_of(content, "pocket.accounts.work"), Some(4));
+        assert_eq!(line_of(content, "pocket.accounts.work.access_token"), Some(5));
+        assert_eq!(line_of(content, "pocket.access_token"), None);
+    }
+}
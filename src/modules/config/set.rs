@@ -0,0 +1,203 @@
+use super::{header, key_of};
+use super::validate::problems;
+use config;
+use errors::*;
+use utils::console::*;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use toml;
+
+pub const NAME: &'static str = "set";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Sets a setting in the configuration file, e.g., 'rat config set pocket.consumer_key <consumer key>'")
+        .arg(Arg::with_name("key")
+            .index(1)
+            .required(true)
+            .help("Section and name of the setting, e.g., 'general.verbosity' or 'pocket.accounts.work.access_token'"))
+        .arg(Arg::with_name("value")
+            .index(2)
+            .required(true)
+            .help("Value of the setting; numbers, booleans, and arrays are given as in TOML, e.g., '30' or \"['localhost']\""))
+}
+
+pub fn call(args: Option<&ArgMatches>, path: &Path) -> Result<()> {
+    let args = args.unwrap();
+    let key = args.value_of("key").unwrap();
+    let value = args.value_of("value").unwrap();
+
+    let mut content = String::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_string(&mut content))
+        .chain_err(|| format!("Could not read configuration file '{}'; create one with 'rat config init'", path.display()))?;
+    let content = set(&content, key, value)?;
+    File::create(path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .chain_err(|| format!("Could not write configuration file '{}'", path.display()))?;
+    // The value is not shown, because it may be a secret
+    msgln(format!("Set '{}' in '{}'.", key, path.display()));
+    if !problems(&content).is_empty() {
+        warning("The configuration file still has problems; cf. 'rat config validate'.");
+    }
+
+    Ok(())
+}
+
+/// Replaces the setting in its section, or a commented out default of it, and adds it at the end of the section or
+/// file otherwise. Unlike serializing the parsed file again, this keeps comments and formatting.
+fn set(content: &str, key: &str, value: &str) -> Result<String> {
+    if !config::is_setting(key) {
+        bail!(ErrorKind::Config(format!("unknown setting '{}'; cf. 'rat config get' for the current settings", key)));
+    }
+    let (section, name) = match key.rfind('.') {
+        Some(at) => (&key[..at], &key[at + 1..]),
+        None => ("", key),
+    };
+
+    // Values that are not valid TOML are taken as strings, e.g., consumer keys; a string setting keeps its value as
+    // string even if it looks like a number, e.g., a numeric client ID
+    let quoted = quote(value);
+    let mut candidates = vec![toml_value(key, value)];
+    if candidates[0] != quoted {
+        candidates.push(quoted);
+    }
+    let mut updated = String::new();
+    for candidate in candidates {
+        updated = replace(content, section, name, &format!("{} = {}", name, candidate));
+        if updated.parse::<toml::Value>().is_err() {
+            bail!(ErrorKind::Config(format!("could not set '{}' in the configuration file; please edit it manually", key)));
+        }
+        let problem_at_key = problems(&updated).iter().any(|problem| problem.message.contains(&format!("`{}`", key)));
+        if !problem_at_key {
+            break;
+        }
+    }
+
+    Ok(updated)
+}
+
+fn toml_value(key: &str, value: &str) -> String {
+    if let Some(&(_, tag)) = config::TAGGED_SETTINGS.iter().find(|&&(setting, _)| setting == key) {
+        return format!("{{ {} = {} }}", tag, quote(&value.to_uppercase()));
+    }
+    if format!("value = {}", value).parse::<toml::Value>().is_ok() {
+        value.to_string()
+    } else {
+        quote(value)
+    }
+}
+
+fn quote(value: &str) -> String {
+    if !value.contains('\'') && !value.contains('\n') {
+        format!("'{}'", value)
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+    }
+}
+
+fn replace(content: &str, section: &str, name: &str, line: &str) -> String {
+    let mut lines: Vec<String> = content.lines().map(|line| line.to_string()).collect();
+    let mut current = String::new();
+    let mut existing = None;
+    let mut commented = None;
+    let mut end_of_section = None;
+    for (i, l) in lines.iter().enumerate() {
+        if let Some(header) = header(l) {
+            current = header;
+            continue;
+        }
+        if current != section {
+            continue;
+        }
+        if existing.is_none() && key_of(l).map(|key| key == name).unwrap_or(false) {
+            existing = Some(i);
+        }
+        if commented.is_none() && key_of(l.trim().trim_matches('#')).map(|key| key == name).unwrap_or(false) {
+            commented = Some(i);
+        }
+        if !l.trim().is_empty() {
+            end_of_section = Some(i + 1);
+        }
+    }
+
+    match (existing.or(commented), end_of_section) {
+        (Some(i), _) => lines[i] = line.to_string(),
+        (None, Some(i)) => lines.insert(i, line.to_string()),
+        // Settings without section precede all sections
+        (None, None) if section.is_empty() => lines.insert(0, line.to_string()),
+        (None, None) => {
+            if lines.last().map(|l| !l.trim().is_empty()).unwrap_or(false) {
+                lines.push(String::new());
+            }
+            lines.push(format!("[{}]", section));
+            lines.push(line.to_string());
+        }
+    }
+    join(lines)
+}
+
+fn join(lines: Vec<String>) -> String {
+    lines.into_iter().map(|line| format!("{}\n", line)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static CONFIG: &'static str = "[general]
+cache_dir = '/tmp/rat-config-set-test'
+output_format = { format = 'HUMAN' }
+verbosity = { verbosity = 'QUIET' }
+# max_concurrency = 4
+
+[centerdevice]
+client_id = 'client id'
+client_secret = 'client secret' # from the developer portal
+
+[slack]
+client_id = 'client id'
+client_secret = 'client secret'
+";
+
+    #[test]
+    fn test_set_replaces_setting() {
+        let content = set(CONFIG, "centerdevice.client_secret", "new secret").unwrap();
+
+        assert_eq!(content, CONFIG.replace("client_secret = 'client secret' # from the developer portal", "client_secret = 'new secret'"));
+    }
+
+    #[test]
+    fn test_set_uncomments_default() {
+        let content = set(CONFIG, "general.max_concurrency", "8").unwrap();
+
+        assert_eq!(content, CONFIG.replace("# max_concurrency = 4", "max_concurrency = 8"));
+    }
+
+    #[test]
+    fn test_set_tagged_setting() {
+        let content = set(CONFIG, "general.output_format", "json").unwrap();
+
+        assert_eq!(content, CONFIG.replace("{ format = 'HUMAN' }", "{ format = 'JSON' }"));
+    }
+
+    #[test]
+    fn test_set_adds_setting_and_section() {
+        let content = set(CONFIG, "general.proxy", "http://proxy:3128").unwrap();
+        let content = set(&content, "pocket.consumer_key", "1234-abcd").unwrap();
+        let content = set(&content, "slack.client_id", "1234").unwrap();
+
+        assert_eq!(content, CONFIG
+            .replace("# max_concurrency = 4\n", "# max_concurrency = 4\nproxy = 'http://proxy:3128'\n")
+            .replace("slack]\nclient_id = 'client id'", "slack]\nclient_id = '1234'")
+            + "\n[pocket]\nconsumer_key = '1234-abcd'\n");
+    }
+
+    #[test]
+    fn test_set_rejects_unknown_setting() {
+        assert!(set(CONFIG, "general.verbositiy", "QUIET").is_err());
+    }
+}
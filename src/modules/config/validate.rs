@@ -0,0 +1,209 @@
+use super::line_of;
+use config::{self, Config, OutputFormat};
+use errors::*;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, ArgMatches, SubCommand};
+use serde_json;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use toml;
+
+pub const NAME: &'static str = "validate";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Checks the configuration file for syntax errors, missing or invalid values, and unknown settings")
+}
+
+/// A problem of the configuration file, by line if it can be attributed to one
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Problem {
+    #[serde(skip_serializing_if = "Option::is_none")] pub line: Option<usize>,
+    pub message: String,
+}
+
+pub fn call(_: Option<&ArgMatches>, path: &Path, format: &OutputFormat) -> Result<()> {
+    let mut content = String::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_string(&mut content))
+        .chain_err(|| format!("Could not read configuration file '{}'; create one with 'rat config init'", path.display()))?;
+
+    let problems = problems(&content);
+    output(path, &problems, format)?;
+    if !problems.is_empty() {
+        bail!(ErrorKind::Config(format!("{} problem(s) found in '{}'", problems.len(), path.display())));
+    }
+
+    Ok(())
+}
+
+/// Unknown settings are reported as well, because they are silently ignored when the configuration is read, so a typo
+/// of an optional setting would go unnoticed.
+pub fn problems(content: &str) -> Vec<Problem> {
+    let value = match content.parse::<toml::Value>() {
+        Ok(value) => value,
+        Err(e) => return vec![problem(content, "", &e.to_string())],
+    };
+
+    let mut problems = Vec::new();
+    unknown_settings(content, "", &value, &mut problems);
+    match toml::from_str::<Config>(content) {
+        Ok(_) => problems.extend(profile_problems(content, &value)),
+        Err(e) => problems.push(problem(content, "", &e.to_string())),
+    }
+    problems.sort_by_key(|problem| problem.line);
+
+    problems
+}
+
+fn unknown_settings(content: &str, section: &str, value: &toml::Value, problems: &mut Vec<Problem>) {
+    let table = match *value {
+        toml::Value::Table(ref table) => table,
+        _ => return,
+    };
+    for (name, value) in table {
+        let key = if section.is_empty() { name.to_string() } else { format!("{}.{}", section, name) };
+        if config::is_setting(&key) {
+            continue;
+        }
+        match *value {
+            toml::Value::Table(_) if config::is_section(&key) => unknown_settings(content, &key, value, problems),
+            _ => problems.push(Problem { line: line_of(content, &key), message: format!("unknown setting '{}'", key) }),
+        }
+    }
+}
+
+/// Profiles are checked applied to the rest of the file, because they may override any setting.
+fn profile_problems(content: &str, value: &toml::Value) -> Vec<Problem> {
+    let names: Vec<String> = value.as_table()
+        .and_then(|table| table.get("profiles"))
+        .and_then(|profiles| profiles.as_table())
+        .map(|profiles| profiles.keys().cloned().collect())
+        .unwrap_or_default();
+    names.iter().filter_map(|name| {
+        let section = format!("profiles.{}", name);
+        let config = match config::apply_profile(value, name) {
+            Ok(config) => config,
+            Err(e) => return Some(Problem { line: line_of(content, &section), message: e.to_string() }),
+        };
+        config.try_into::<Config>().err().map(|e| {
+            let problem = problem(content, &section, &e.to_string());
+            Problem { line: problem.line, message: format!("{} in profile '{}'", problem.message, name) }
+        })
+    }).collect()
+}
+
+/// Errors of the TOML parser end with the line, errors of the deserialization name the key they occurred at, e.g.,
+/// "missing field `client_id` for key `slack`". Keys of errors in a profile are relative to its `section`.
+fn problem(content: &str, section: &str, error: &str) -> Problem {
+    let mut message = error.to_string();
+    let mut line = None;
+    if let Some(at) = message.rfind(" at line ") {
+        line = message[at + " at line ".len()..].parse().ok();
+        message.truncate(at);
+    }
+    if line.is_none() {
+        if let Some(at) = message.rfind(" for key `") {
+            let key = message[at + " for key `".len()..].trim_matches('`').to_string();
+            line = if section.is_empty() {
+                line_of(content, &key)
+            } else {
+                line_of(content, &format!("{}.{}", section, key)).or_else(|| line_of(content, section))
+            };
+        }
+    }
+
+    Problem { line: line, message: message }
+}
+
+fn output(path: &Path, problems: &[Problem], format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            if problems.is_empty() {
+                msgln(format!("Configuration file '{}' is valid.", path.display()));
+            }
+            for problem in problems {
+                match problem.line {
+                    Some(line) => error(format!("{}:{}: {}", path.display(), line, problem.message)),
+                    None => error(format!("{}: {}", path.display(), problem.message)),
+                }
+            }
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(problems).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static CONFIG: &'static str = r#"[general]
+cache_dir = '/tmp/rat-config-validate-test'
+output_format = { format = 'HUMAN' }
+verbosity = { verbosity = 'QUIET' }
+
+[centerdevice]
+client_id = 'client id'
+client_secret = 'client secret'
+
+[slack]
+client_id = 'client id'
+client_secret = 'client secret'
+"#;
+
+    #[test]
+    fn test_problems_of_valid_config() {
+        assert_eq!(problems(CONFIG), vec![]);
+    }
+
+    #[test]
+    fn test_problems_reports_syntax_error_by_line() {
+        let content = CONFIG.replace("client_id = 'client id'\nclient_secret", "client_id = 'client id\nclient_secret");
+
+        let problems = problems(&content);
+
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, Some(7));
+    }
+
+    #[test]
+    fn test_problems_reports_unknown_settings_by_line() {
+        let content = CONFIG.replace("verbosity = ", "verbositiy = ").replace("[slack]", "[pocket.accounts.work]\ntoken = 'x'\n\n[slack]");
+
+        let problems = problems(&content);
+
+        assert_eq!(problems, vec![
+            Problem { line: Some(1), message: "missing field `verbosity` for key `general`".to_string() },
+            Problem { line: Some(4), message: "unknown setting 'general.verbositiy'".to_string() },
+            Problem { line: Some(11), message: "unknown setting 'pocket.accounts.work.token'".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn test_problems_reports_invalid_profile_by_line() {
+        let content = format!("{}\n[profiles.work.general]\nmax_concurrency = 'many'\n", CONFIG);
+
+        let problems = problems(&content);
+
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, Some(15));
+        assert!(problems[0].message.ends_with("in profile 'work'"));
+    }
+
+    #[test]
+    fn test_problems_reports_zero_batch_size() {
+        let content = format!("{}\n[pocket]\nbatch_size = 0\n", CONFIG);
+
+        let problems = problems(&content);
+
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].message.contains("batch_size must be at least 1"), "{}", problems[0].message);
+    }
+}
@@ -0,0 +1,42 @@
+use errors::*;
+use modules::{Context, Module};
+
+use clap::{App, ArgMatches, SubCommand};
+
+pub const NAME: &'static str = "dev";
+
+#[cfg(feature = "profiling")]
+mod profile;
+mod sanitize_fixture;
+
+pub struct Dev;
+
+impl Module for Dev {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn about(&self) -> &'static str {
+        "Tools for developing rat"
+    }
+
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        let app = SubCommand::with_name(NAME).about(self.about());
+        #[cfg(feature = "profiling")]
+        let app = app.subcommand(profile::build_sub_cli());
+        app.subcommand(sanitize_fixture::build_sub_cli())
+    }
+
+    fn call(&self, cli_args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        let subcommand = cli_args.unwrap();
+        let subcommand_name = subcommand.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+        match subcommand_name {
+            #[cfg(feature = "profiling")]
+            profile::NAME => profile::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            sanitize_fixture::NAME => sanitize_fixture::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            _ => Ok(())
+        }
+    }
+}
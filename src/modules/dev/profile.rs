@@ -0,0 +1,93 @@
+use config::OutputFormat;
+use errors::*;
+use modules::{self, Context};
+use utils::alloc;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::time::Instant;
+
+pub const NAME: &'static str = "profile";
+
+static BIN_NAME: &'static str = "rat";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Runs a command and reports its run time, allocations, and the binary size")
+        .setting(AppSettings::Hidden)
+        .setting(AppSettings::TrailingVarArg)
+        .arg(Arg::with_name("command")
+            .index(1)
+            .multiple(true)
+            .required(true)
+            .allow_hyphen_values(true)
+            .help("Command to profile, e.g., 'pocket local stats'"))
+}
+
+/// Heap usage of a command; cf. `utils::alloc`.
+#[derive(Serialize, Debug)]
+struct Profile {
+    command: String,
+    elapsed_ms: u64,
+    allocations: usize,
+    allocated_bytes: usize,
+    peak_bytes: usize,
+    binary_bytes: Option<u64>,
+}
+
+/// The command runs in this process, so its allocations can be counted. Its output goes to stdout as usual; the
+/// profile goes to stderr.
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let command: Vec<&str> = args.unwrap().values_of("command").unwrap().collect();
+    let registry = modules::registry();
+    let module = registry.iter().find(|module| module.name() == command[0])
+        .ok_or_else(|| format!("Unknown command '{}'", command[0]))?;
+    let module_args = modules::parse_module_args(module.as_ref(), BIN_NAME, &command[1..]).unwrap_or_else(|e| e.exit());
+
+    alloc::reset_peak();
+    let before = alloc::stats();
+    let start = Instant::now();
+    let result = module.call(Some(&module_args), ctx);
+    let elapsed = start.elapsed();
+    let stats = alloc::stats().since(&before);
+
+    let profile = Profile {
+        command: command.join(" "),
+        elapsed_ms: elapsed.as_secs() * 1000 + u64::from(elapsed.subsec_nanos() / 1_000_000),
+        allocations: stats.allocations,
+        allocated_bytes: stats.allocated_bytes,
+        peak_bytes: stats.peak_bytes,
+        binary_bytes: env::current_exe().and_then(fs::metadata).map(|m| m.len()).ok(),
+    };
+    report(&profile, &ctx.output_format())?;
+
+    result
+}
+
+fn report(profile: &Profile, format: &OutputFormat) -> Result<()> {
+    let report = match *format {
+        OutputFormat::HUMAN => render_profile(profile),
+        OutputFormat::JSON | OutputFormat::NDJSON => serde_json::to_string(profile).chain_err(|| "JSON serialization failed")?,
+    };
+    writeln!(io::stderr(), "{}", report).chain_err(|| ErrorKind::OutputFailed)
+}
+
+fn render_profile(profile: &Profile) -> String {
+    let mut out = format!("Profile of '{}':\n", profile.command);
+    out.push_str(&format!("  run time:    {} ms\n", profile.elapsed_ms));
+    if alloc::is_counting() {
+        out.push_str(&format!("  allocations: {} ({} bytes)\n", profile.allocations, profile.allocated_bytes));
+        out.push_str(&format!("  peak heap:   {} bytes\n", profile.peak_bytes));
+    } else {
+        out.push_str("  allocations: not counted by this binary\n");
+    }
+    if let Some(binary_bytes) = profile.binary_bytes {
+        out.push_str(&format!("  binary size: {} bytes\n", binary_bytes));
+    }
+    out.pop();
+
+    out
+}
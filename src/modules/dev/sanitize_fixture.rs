@@ -0,0 +1,162 @@
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::secrets;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io;
+use std::io::{Read, Write};
+use std::path::Path;
+
+pub const NAME: &'static str = "sanitize-fixture";
+
+static FIXTURE_DIR: &'static str = "test/data";
+
+/// Keys whose values identify or authenticate an account. Keys ending in 'token' or 'email' are sensitive as well.
+static SENSITIVE_KEYS: &'static [&'static str] = &[
+    "account", "account_id", "client_id", "client_secret", "code", "consumer_key", "owner", "password", "team",
+    "team_id", "user", "user_id", "username",
+];
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Strips account identifiers and tokens from a recorded API response")
+        .arg(Arg::with_name("response")
+            .index(1)
+            .required(true)
+            .help("File with the recorded JSON response; '-' reads from stdin"))
+        .arg(Arg::with_name("fixture")
+            .index(2)
+            .help("Fixture to write, relative to 'test/data', e.g., 'pocket/list.json'; prints the fixture if omitted"))
+}
+
+pub fn call(args: Option<&ArgMatches>, _: &Context) -> Result<()> {
+    let args = args.unwrap();
+    let response = read_response(args.value_of("response").unwrap())?;
+
+    let mut json: Value = serde_json::from_str(&response).chain_err(|| ErrorKind::Parse("recorded response".to_string()))?;
+    let mut placeholders = Placeholders::default();
+    sanitize(&mut json, None, &mut placeholders);
+    let fixture = serde_json::to_string_pretty(&json).chain_err(|| "JSON serialization failed")? + "\n";
+
+    match args.value_of("fixture") {
+        Some(name) => {
+            let path = Path::new(FIXTURE_DIR).join(name);
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).chain_err(|| "Failed to create fixture directory")?;
+            }
+            File::create(&path).and_then(|mut f| f.write_all(fixture.as_bytes()))
+                .chain_err(|| format!("Failed to write fixture '{}'", path.display()))?;
+            msgln(format!("Replaced {} sensitive value(s) and wrote fixture '{}'.", placeholders.len(), path.display()));
+        }
+        None => msg(fixture),
+    }
+
+    Ok(())
+}
+
+fn read_response(path: &str) -> Result<String> {
+    let mut response = String::new();
+    if path == "-" {
+        io::stdin().read_to_string(&mut response).chain_err(|| "Failed to read response from stdin")?;
+    } else {
+        File::open(path).and_then(|mut f| f.read_to_string(&mut response))
+            .chain_err(|| format!("Failed to read response '{}'", path))?;
+    }
+
+    Ok(response)
+}
+
+/// Replaces sensitive values by placeholders. The same value always gets the same placeholder, so references between
+/// parts of a response survive, and the fixture does not change if the same response is sanitized again.
+#[derive(Default, Debug)]
+struct Placeholders {
+    by_value: HashMap<String, usize>,
+}
+
+impl Placeholders {
+    fn index(&mut self, value: &str) -> usize {
+        let next = self.by_value.len() + 1;
+        *self.by_value.entry(value.to_string()).or_insert(next)
+    }
+
+    fn len(&self) -> usize {
+        self.by_value.len()
+    }
+}
+
+fn is_sensitive(key: &str) -> bool {
+    let key = key.to_lowercase();
+    SENSITIVE_KEYS.contains(&key.as_str()) || key.ends_with("token") || key.ends_with("email")
+}
+
+/// Replaces all values below a sensitive key by placeholders named after the key. Secrets known from the configuration
+/// are masked in all other strings.
+fn sanitize(value: &mut Value, sensitive_key: Option<&str>, placeholders: &mut Placeholders) {
+    let replacement = match *value {
+        Value::Object(ref mut map) => {
+            for (key, v) in map.iter_mut() {
+                let key = sensitive_key.or_else(|| if is_sensitive(key) { Some(key.as_str()) } else { None });
+                sanitize(v, key, placeholders);
+            }
+            None
+        }
+        Value::Array(ref mut values) => {
+            for v in values.iter_mut() {
+                sanitize(v, sensitive_key, placeholders);
+            }
+            None
+        }
+        Value::String(ref s) => Some(match sensitive_key {
+            Some(key) => Value::String(format!("{}-{}", key, placeholders.index(s))),
+            None => Value::String(secrets::redact(s)),
+        }),
+        Value::Number(ref n) => sensitive_key.map(|_| Value::from(placeholders.index(&n.to_string()) as u64)),
+        Value::Bool(_) | Value::Null => None,
+    };
+    if let Some(replacement) = replacement {
+        *value = replacement;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sanitized(json: &str) -> Value {
+        let mut json: Value = serde_json::from_str(json).unwrap();
+        sanitize(&mut json, None, &mut Placeholders::default());
+        json
+    }
+
+    #[test]
+    fn test_sanitize() {
+        let json = sanitized(r#"{
+            "access_token": "abc123",
+            "user": {"name": "Jane", "id": 42},
+            "team_id": "T0001",
+            "documents": [{"id": "doc-1", "owner": "T0001", "contactEmail": "jane@example.com"}],
+            "status": 1
+        }"#);
+
+        assert_eq!(json["access_token"].as_str(), Some("access_token-1"));
+        assert_eq!(json["documents"][0]["contactEmail"].as_str(), Some("contactEmail-2"));
+        assert_eq!(json["documents"][0]["id"].as_str(), Some("doc-1"));
+        assert_eq!(json["documents"][0]["owner"].as_str(), Some("owner-3"));
+        assert_eq!(json["team_id"].as_str(), Some("team_id-3"));
+        assert_eq!(json["user"]["id"].as_u64(), Some(4));
+        assert_eq!(json["user"]["name"].as_str(), Some("user-5"));
+        assert_eq!(json["status"].as_u64(), Some(1));
+    }
+
+    #[test]
+    fn test_sanitize_is_deterministic() {
+        let response = r#"{"list": {"1": {"item_id": "1", "username": "jane"}}, "refresh_token": "xyz"}"#;
+
+        assert_eq!(sanitized(response), sanitized(&serde_json::to_string(&sanitized(response)).unwrap()));
+    }
+}
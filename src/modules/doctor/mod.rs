@@ -0,0 +1,41 @@
+use errors::*;
+use modules::{auth, Context, Module};
+
+use clap::{App, ArgMatches, SubCommand};
+
+pub const NAME: &'static str = "doctor";
+
+pub struct Doctor;
+
+impl Module for Doctor {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn about(&self) -> &'static str {
+        "Checks rat's setup, i.e., the health of the access tokens of all modules"
+    }
+
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        SubCommand::with_name(NAME)
+            .about(self.about())
+    }
+
+    fn call(&self, _: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        auth::token_health(ctx).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rat_core::testing;
+
+    #[test]
+    fn test_doctor_reports_token_health() {
+        let config = testing::config("doctor", "");
+        let ctx = Context::new(&config);
+
+        assert!(Doctor.call(None, &ctx).is_ok());
+    }
+}
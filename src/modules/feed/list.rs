@@ -0,0 +1,234 @@
+use super::client;
+use super::client::Entry;
+use config::OutputFormat;
+use errors::*;
+use modules::{bookmarks, xfer, Context};
+use modules::bookmarks::Bookmark;
+use net::concurrent;
+use utils::console::*;
+use utils::datetime;
+use utils::output;
+
+use chrono::{DateTime, NaiveDateTime, UTC};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use regex::Regex;
+use serde_json;
+use std::io::Write;
+use std::time::Duration;
+use tabwriter::TabWriter;
+
+pub const NAME: &'static str = "list";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("List entries of configured feeds, newest first")
+        .arg(Arg::with_name("feed")
+            .long("feed")
+            .short("f")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .help("Lists only entries of feed <feed> from section '[feeds]'; may be repeated"))
+        .arg(Arg::with_name("since")
+            .long("since")
+            .takes_value(true)
+            .value_name("time")
+            .help("Lists only entries published since <time>, a duration ago like '2w 3d' or a date like '2023-01-01'"))
+        .arg(Arg::with_name("filter")
+            .long("filter")
+            .takes_value(true)
+            .help("Lists only entries with titles matching the regular expression <filter>"))
+        .arg(Arg::with_name("count")
+            .long("count")
+            .takes_value(true)
+            .default_value("30")
+            .help("Lists at most <count> entries"))
+        .arg(bookmarks::list_arg()
+            .conflicts_with("save-to"))
+        .arg(Arg::with_name("save-to")
+            .long("save-to")
+            .takes_value(true)
+            .value_name("service")
+            .possible_values(&xfer::SERVICES)
+            .help("Saves the listed entries to <service> instead of printing them, e.g., to read them later in Pocket"))
+        .arg(Arg::with_name("tag")
+            .long("tag")
+            .short("t")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .requires("save-to")
+            .help("Adds a tag to all saved entries; may be repeated"))
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+
+    let feeds: Vec<(String, String)> = match args.values_of("feed") {
+        Some(names) => names.map(|name| match config.feeds.get(name) {
+            Some(url) => Ok((name.to_string(), url.clone())),
+            None => bail!("Unknown feed '{}'; please add it to section '[feeds]'", name),
+        }).collect::<Result<_>>()?,
+        None => config.feeds.iter().map(|(name, url)| (name.clone(), url.clone())).collect(),
+    };
+    let since = datetime::Range::parse(args.value_of("since"), None).chain_err(|| "Invalid time filter")?;
+    let regex = match args.value_of("filter") {
+        Some(filter) => Some(Regex::new(filter).chain_err(|| format!("Invalid filter '{}'", filter))?),
+        None => None,
+    };
+    let count = args.value_of("count").unwrap();
+    let count = match count.parse::<usize>() {
+        Ok(count) if count > 0 => count,
+        _ => bail!("Invalid count '{}'; expected a positive number", count),
+    };
+
+    let policy = config.general.retry_policy();
+    let results = concurrent::map_bounded(feeds.clone(), config.general.max_concurrency(), move |(name, url)| {
+        client::fetch(&policy, &name, &url)
+    });
+    let mut entries = Vec::new();
+    let mut failed = 0;
+    for ((name, _), result) in feeds.iter().zip(results) {
+        match result {
+            Ok(feed_entries) => entries.extend(feed_entries),
+            Err(e) => {
+                error(format!("Failed to get feed '{}' because {}", name, e));
+                failed += 1;
+            }
+        }
+    }
+
+    entries.retain(|e| {
+        let published = match e.time {
+            Some(time) => since.contains(Duration::from_secs(time)),
+            None => since.is_unbounded(),
+        };
+        published && regex.as_ref().map(|r| r.is_match(&e.title)).unwrap_or(true)
+    });
+    // Entries without time of publication go last
+    entries.sort_by(|a, b| b.time.cmp(&a.time));
+    entries.truncate(count);
+
+    if let Some(service) = args.value_of("save-to") {
+        if entries.is_empty() {
+            msgln("No entries to save.");
+        } else {
+            let tags: Vec<&str> = args.values_of("tag").map(|tags| tags.collect()).unwrap_or_default();
+            let bookmarks: Vec<Bookmark> = entries.iter().map(|e| to_bookmark(e, &tags)).collect();
+            xfer::add(service, ctx, &bookmarks)?;
+        }
+    } else if args.is_present("bookmarks") {
+        let bookmarks: Vec<Bookmark> = entries.iter().map(|e| to_bookmark(e, &[])).collect();
+        bookmarks::print_all(&bookmarks)?;
+    } else {
+        output(&entries, &ctx.output_format())?;
+    }
+    if failed > 0 {
+        bail!(ErrorKind::PartialFailure(feeds.len() - failed, failed));
+    }
+
+    Ok(())
+}
+
+/// Entries are saved now, so the time of publication is not the time of saving.
+fn to_bookmark(entry: &Entry, tags: &[&str]) -> Bookmark {
+    let mut bookmark = Bookmark { title: entry.title.clone(), ..Bookmark::new(entry.url.as_str()) };
+    bookmark.add_tags(tags);
+
+    bookmark
+}
+
+fn output(entries: &[Entry], format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            if let Some(template) = output::template() {
+                msg(template.render_all(entries));
+            } else {
+                msg(render_human(entries)?);
+            }
+            Ok(())
+        }
+        OutputFormat::JSON => {
+            let json = serde_json::to_string(entries).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+        OutputFormat::NDJSON => {
+            for entry in entries {
+                let json = serde_json::to_string(entry).chain_err(|| "JSON serialization failed")?;
+                output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn render_human(entries: &[Entry]) -> Result<String> {
+    let mut tw = TabWriter::new(vec![]);
+    for e in entries {
+        let date = e.time.map(|secs| {
+            let dt = DateTime::<UTC>::from_utc(NaiveDateTime::from_timestamp(secs as i64, 0), UTC);
+            dt.format("%Y-%m-%d").to_string()
+        }).unwrap_or_else(|| "-".to_string());
+        writeln!(&mut tw, "{}\t{}\t{}\t{}", date, e.feed, e.title, e.url).chain_err(|| ErrorKind::OutputFailed)?;
+    }
+    tw.flush().chain_err(|| ErrorKind::OutputFailed)?;
+    let out = String::from_utf8(tw.into_inner().unwrap()).chain_err(|| ErrorKind::OutputFailed)?;
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use modules::feed::Feed;
+    use net::mock::MockClient;
+    use testing::harness::*;
+
+    use std::rc::Rc;
+
+    #[test]
+    fn test_render_human() {
+        let entries = vec![
+            Entry { feed: "rust".to_string(), id: "1".to_string(), title: "Rust 1.19".to_string(), url: "https://example.com/1".to_string(), time: Some(1500508800) },
+            Entry { feed: "twir".to_string(), id: "2".to_string(), title: "TWiR".to_string(), url: "https://example.com/2".to_string(), time: None },
+        ];
+
+        let out = render_human(&entries).unwrap();
+
+        assert_eq!(out, "2017-07-20  rust  Rust 1.19  https://example.com/1\n\
+                         -           twir  TWiR       https://example.com/2\n");
+    }
+
+    #[cfg(feature = "pocket")]
+    #[test]
+    fn test_list_saves_to_pocket() {
+        let config = config("feed-save-to", "[feeds]\nexample = 'https://example.com/feed.xml'\n\n\
+                                             [pocket]\nconsumer_key = 'key'\naccess_token = 'token'");
+        let rss = r#"<rss version="2.0"><channel>
+            <item><title>Older</title><link>https://example.com/older</link><pubDate>Tue, 11 Jul 2017 00:00:00 GMT</pubDate></item>
+            <item><title>Newer</title><link>https://example.com/newer</link><pubDate>Thu, 20 Jul 2017 00:00:00 GMT</pubDate></item>
+            </channel></rss>"#;
+        let mock = Rc::new(MockClient::new()
+            .respond(200, &[], rss)
+            .respond(200, &[], r#"{"item":{"item_id":"1","title":"Newer"},"status":1}"#));
+
+        run(&Feed, &["list", "--count", "1", "--save-to", "pocket", "--tag", "feed"], &Context::new(&config), &mock).unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("GET /feed.xml HTTP/1.1\r\n"));
+        assert!(requests[1].starts_with("POST /v3/add"));
+        assert!(requests[1].contains(r#""url":"https://example.com/newer""#));
+        assert!(requests[1].contains(r#""tags":"feed""#));
+    }
+
+    #[test]
+    fn test_list_unknown_feed() {
+        let config = config("feed-unknown", "[feeds]\nexample = 'https://example.com/feed.xml'");
+        let mock = Rc::new(MockClient::new());
+
+        assert!(run(&Feed, &["list", "--feed", "other"], &Context::new(&config), &mock).is_err());
+        assert!(mock.requests().is_empty());
+    }
+}
@@ -0,0 +1,47 @@
+pub use clients::feed::NAME;
+
+use clients::feed::client;
+use config::Config;
+use errors::*;
+use modules::{Context, Module};
+
+use clap::{App, ArgMatches, SubCommand};
+
+mod list;
+
+fn ensure_feeds(config: &Config) -> Result<()> {
+    if config.feeds.is_empty() {
+        bail!("No feeds configured; please add feeds to section '[feeds]' like 'rust = \"https://blog.rust-lang.org/feed.xml\"'");
+    }
+
+    Ok(())
+}
+
+pub struct Feed;
+
+impl Module for Feed {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn about(&self) -> &'static str {
+        "Feed: Entries of RSS and Atom feeds."
+    }
+
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        SubCommand::with_name(NAME)
+            .about(self.about())
+            .subcommand(list::build_sub_cli())
+    }
+
+    fn call(&self, cli_args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        ensure_feeds(ctx.config).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+        let subcommand = cli_args.unwrap();
+        let subcommand_name = subcommand.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+        match subcommand_name {
+            list::NAME => list::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            _ => Ok(())
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use errors::*;
+use modules::{Context, Module};
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+pub const NAME: &'static str = "flush";
+
+pub struct Flush;
+
+impl Module for Flush {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn about(&self) -> &'static str {
+        "Sends the actions queued by '--queue' while the service could not be reached"
+    }
+
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        SubCommand::with_name(NAME)
+            .about(self.about())
+            .arg(Arg::with_name("discard-failed")
+                .long("discard-failed")
+                .help("Removes actions that fail again from the queue instead of keeping them for the next flush"))
+    }
+
+    #[allow(unused_variables)]
+    fn call(&self, cli_args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        let discard_failed = cli_args.map(|args| args.is_present("discard-failed")).unwrap_or(false);
+
+        #[cfg(feature = "pocket")]
+        ::modules::pocket::flush(ctx, discard_failed).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+
+        Ok(())
+    }
+}
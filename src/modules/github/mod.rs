@@ -0,0 +1,60 @@
+pub use clients::github::{NAME, GitHubConfig};
+
+use clients::github::client;
+use config::Config;
+use credentials;
+use errors::*;
+use modules::{Context, Module};
+
+use clap::{App, ArgMatches, SubCommand};
+
+mod notifications;
+mod stars;
+
+/// GitHub is used with a personal access token, which is either configured or injected by the service manager.
+fn ensure_access_token(config: &Config) -> Result<()> {
+    if config.github.access_token.is_none() && !credentials::is_injected(config, NAME) {
+        bail!("No access token available; please set 'access_token' in section '[github]'");
+    }
+
+    Ok(())
+}
+
+fn parse_count(count: &str) -> Result<usize> {
+    match count.parse::<usize>() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => bail!("Invalid count '{}'; expected a positive number", count),
+    }
+}
+
+pub struct GitHub;
+
+impl Module for GitHub {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn about(&self) -> &'static str {
+        "GitHub: Starred repositories and notifications."
+    }
+
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        SubCommand::with_name(NAME)
+            .about(self.about())
+            .subcommand(notifications::build_sub_cli())
+            .subcommand(stars::build_sub_cli())
+    }
+
+    fn call(&self, cli_args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        ensure_access_token(ctx.config).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+        let subcommand = cli_args.unwrap();
+        let subcommand_name = subcommand.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+        match subcommand_name {
+            notifications::NAME => notifications::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            stars::NAME => stars::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            _ => Ok(())
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use super::{client, parse_count};
+use super::client::Notification;
+use super::NAME as MODULE_NAME;
+use config::OutputFormat;
+use credentials;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::io::Write;
+use tabwriter::TabWriter;
+
+pub const NAME: &'static str = "notifications";
+pub const NAME_LIST: &'static str = "list";
+pub const NAME_DONE: &'static str = "done";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Notifications of your inbox")
+        .subcommand(SubCommand::with_name(NAME_LIST)
+            .about("List unread notifications, most recently updated first")
+            .arg(Arg::with_name("all")
+                .long("all")
+                .help("Lists read notifications, too"))
+            .arg(Arg::with_name("count")
+                .long("count")
+                .takes_value(true)
+                .default_value("50")
+                .help("Lists at most <count> notifications")))
+        .subcommand(SubCommand::with_name(NAME_DONE)
+            .about("Mark notifications as done, i.e., remove them from the inbox")
+            .arg(Arg::with_name("id")
+                .index(1)
+                .multiple(true)
+                .required(true)
+                .help("notification id as listed")))
+}
+
+/// The result of marking one notification as done
+#[derive(Serialize, Debug)]
+struct Done<'a> {
+    id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")] error: Option<String>,
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    let subcommand_name = args.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+    let format = &config.general.output_format;
+
+    match subcommand_name {
+        NAME_LIST => {
+            let args = args.subcommand_matches(NAME_LIST).unwrap();
+            let count = parse_count(args.value_of("count").unwrap())?;
+            let notifications = client::notifications(config, args.is_present("all"), count)
+                .chain_err(|| "failed to list notifications")?;
+            output(&notifications, format, render_notifications)
+        }
+        NAME_DONE => {
+            credentials::ensure_writable(config, MODULE_NAME).chain_err(|| "failed to mark notifications as done")?;
+            let ids: Vec<&str> = args.subcommand_matches(NAME_DONE).unwrap().values_of("id").unwrap().collect();
+            if ctx.dry_run {
+                msgln(format!("Dry run: would mark {} notification(s) as done.", ids.len()));
+                return Ok(());
+            }
+
+            info(format!("Marking {} notification(s) as done ...", ids.len()));
+            let results: Vec<Done> = ids.into_iter().map(|id| {
+                let result = client::done(config, id);
+                // The root cause is the most specific, e.g., the HTTP status
+                Done { id: id, error: result.err().and_then(|e| e.iter().last().map(|c| c.to_string())) }
+            }).collect();
+            output(&results, format, |results| Ok(render_done(results)))?;
+            let failed = results.iter().filter(|r| r.error.is_some()).count();
+            if failed > 0 {
+                bail!(ErrorKind::PartialFailure(results.len() - failed, failed));
+            }
+
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn output<T: ::serde::Serialize, F: Fn(&[T]) -> Result<String>>(items: &[T], format: &OutputFormat, render: F) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            if let Some(template) = output::template() {
+                msg(template.render_all(items));
+            } else {
+                msg(render(items)?);
+            }
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(items).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_notifications(notifications: &[Notification]) -> Result<String> {
+    let mut tw = TabWriter::new(vec![]);
+    for n in notifications {
+        let unread = if n.unread { "*" } else { "" };
+        writeln!(&mut tw, "{}\t{}{}\t{}\t{}\t{}\t{}", n.id, unread, n.subject.title, n.subject.subject_type, n.repository.full_name,
+                 n.reason, n.updated_at)
+            .chain_err(|| ErrorKind::OutputFailed)?;
+    }
+    tw.flush().chain_err(|| ErrorKind::OutputFailed)?;
+    let mut out = String::from_utf8(tw.into_inner().unwrap()).chain_err(|| ErrorKind::OutputFailed)?;
+    out.push_str(&format!("Received {} notification(s).\n", notifications.len()));
+
+    Ok(out)
+}
+
+fn render_done(results: &[Done]) -> String {
+    let mut out = String::new();
+    for result in results.iter().filter(|r| r.error.is_some()) {
+        out.push_str(&format!("Notification {} failed: {}\n", result.id, result.error.as_ref().unwrap()));
+    }
+    let successful = results.iter().filter(|r| r.error.is_none()).count();
+    out.push_str(&format!("{} notification(s) done, {} failed.\n", successful, results.len() - successful));
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render_done() {
+        let results = vec![
+            Done { id: "1", error: None },
+            Done { id: "2", error: Some("API request failed with status 404".to_string()) },
+        ];
+
+        assert_eq!(render_done(&results), "Notification 2 failed: API request failed with status 404\n1 notification(s) done, 1 failed.\n");
+    }
+}
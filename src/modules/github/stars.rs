@@ -0,0 +1,73 @@
+use super::{client, parse_count};
+use super::client::Repository;
+use config::OutputFormat;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::io::Write;
+use tabwriter::TabWriter;
+
+pub const NAME: &'static str = "stars";
+pub const NAME_LIST: &'static str = "list";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Starred repositories")
+        .subcommand(SubCommand::with_name(NAME_LIST)
+            .about("List starred repositories, most recently starred first")
+            .arg(Arg::with_name("count")
+                .long("count")
+                .takes_value(true)
+                .default_value("30")
+                .help("Lists at most <count> repositories")))
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    let subcommand_name = args.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+
+    match subcommand_name {
+        NAME_LIST => {
+            let count = parse_count(args.subcommand_matches(NAME_LIST).unwrap().value_of("count").unwrap())?;
+            let repositories = client::starred(config, count).chain_err(|| "failed to list starred repositories")?;
+            output(&repositories, &config.general.output_format)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn output(repositories: &[Repository], format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            if let Some(template) = output::template() {
+                msg(template.render_all(repositories));
+            } else {
+                msg(render_human(repositories)?);
+            }
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(repositories).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(repositories: &[Repository]) -> Result<String> {
+    let mut tw = TabWriter::new(vec![]);
+    for r in repositories {
+        writeln!(&mut tw, "{}\t{} stars\t{}\t{}", r.full_name, r.stargazers_count, r.language.as_ref().map(|l| l.as_str()).unwrap_or(""),
+                 r.description.as_ref().map(|d| d.as_str()).unwrap_or(""))
+            .chain_err(|| ErrorKind::OutputFailed)?;
+    }
+    tw.flush().chain_err(|| ErrorKind::OutputFailed)?;
+    let mut out = String::from_utf8(tw.into_inner().unwrap()).chain_err(|| ErrorKind::OutputFailed)?;
+    out.push_str(&format!("Received {} repositories.\n", repositories.len()));
+
+    Ok(out)
+}
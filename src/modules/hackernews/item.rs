@@ -0,0 +1,95 @@
+use super::client;
+use super::client::Item;
+use config::OutputFormat;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use chrono::{DateTime, NaiveDateTime, UTC};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+
+pub const NAME: &'static str = "item";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Show a story, comment, job, or poll")
+        .arg(Arg::with_name("id")
+            .index(1)
+            .required(true)
+            .help("item id, e.g., from https://news.ycombinator.com/item?id=<id>"))
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+
+    let id = args.value_of("id").unwrap();
+    let id = id.parse::<u64>().chain_err(|| format!("Invalid item id '{}'", id))?;
+    let item = client::item(&config.general.retry_policy(), id).chain_err(|| format!("failed to get item {}", id))?;
+
+    output(&item, &ctx.output_format())
+}
+
+fn output(item: &Item, format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            if let Some(template) = output::template() {
+                msgln(template.render_item(item));
+            } else {
+                msg(render_human(item));
+            }
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(item).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(item: &Item) -> String {
+    let mut out = String::new();
+    if let Some(ref title) = item.title {
+        out.push_str(&format!("{}\n", title));
+    }
+    if let Some(ref url) = item.url {
+        out.push_str(&format!("{}\n", url));
+    }
+    out.push_str(&format!("{} by {} at {}", item.item_type, item.by.as_ref().map(|b| b.as_str()).unwrap_or("[deleted]"), format_time(item.time)));
+    if let Some(score) = item.score {
+        out.push_str(&format!(", {} points", score));
+    }
+    if let Some(descendants) = item.descendants {
+        out.push_str(&format!(", {} comments", descendants));
+    }
+    out.push_str(&format!("\n{}\n", item.item_url()));
+    // The text is HTML; it is shown as is
+    if let Some(ref text) = item.text {
+        out.push_str(&format!("\n{}\n", text));
+    }
+
+    out
+}
+
+fn format_time(unix_ts: u64) -> String {
+    let dt = DateTime::<UTC>::from_utc(NaiveDateTime::from_timestamp(unix_ts as i64, 0), UTC);
+    dt.to_rfc3339()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render_human() {
+        let item: Item = serde_json::from_str(r#"{"by":"norvig","id":2921983,"parent":2921506,"text":"Aw shucks",
+                                                 "time":1314211127,"type":"comment"}"#).unwrap();
+
+        assert_eq!(render_human(&item), "comment by norvig at 2011-08-24T18:38:47+00:00\n\
+                                         https://news.ycombinator.com/item?id=2921983\n\
+                                         \n\
+                                         Aw shucks\n");
+    }
+}
@@ -0,0 +1,46 @@
+pub use clients::hackernews::NAME;
+
+use clients::hackernews::client;
+use errors::*;
+use modules::{Context, Module};
+
+use clap::{App, ArgMatches, SubCommand};
+
+mod item;
+mod stories;
+
+pub struct HackerNews;
+
+impl Module for HackerNews {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn about(&self) -> &'static str {
+        "Hacker News: Stories and discussions from news.ycombinator.com."
+    }
+
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        let mut subcommand = SubCommand::with_name(NAME)
+            .about(self.about())
+            .subcommand(item::build_sub_cli());
+        for s in stories::build_sub_cli() {
+            subcommand = subcommand.subcommand(s);
+        }
+
+        subcommand
+    }
+
+    fn call(&self, cli_args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        let subcommand = cli_args.unwrap();
+        let subcommand_name = subcommand.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+        match subcommand_name {
+            item::NAME => item::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            stories::NAME_TOP | stories::NAME_NEW =>
+                stories::call(subcommand_name, subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            _ => Ok(())
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use super::client;
+use super::client::{Item, Stories};
+use config::OutputFormat;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::io::Write;
+use tabwriter::TabWriter;
+
+pub const NAME_TOP: &'static str = "top";
+pub const NAME_NEW: &'static str = "new";
+
+pub fn build_sub_cli() -> Vec<App<'static, 'static>> {
+    vec![
+        SubCommand::with_name(NAME_TOP)
+            .about("List top stories")
+            .arg(count_arg()),
+        SubCommand::with_name(NAME_NEW)
+            .about("List newest stories")
+            .arg(count_arg()),
+    ]
+}
+
+fn count_arg() -> Arg<'static, 'static> {
+    Arg::with_name("count")
+        .long("count")
+        .takes_value(true)
+        .default_value("30")
+        .help("Lists at most <count> stories; Hacker News returns at most 500")
+}
+
+pub fn call(list: &str, args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+
+    let stories = match list {
+        NAME_NEW => Stories::New,
+        _ => Stories::Top,
+    };
+    let count = args.value_of("count").unwrap();
+    let count = match count.parse::<usize>() {
+        Ok(count) if count > 0 && count <= client::MAX_STORIES => count,
+        _ => bail!("Invalid count '{}'; expected a number from 1 to {}", count, client::MAX_STORIES),
+    };
+
+    let items = client::stories(&config.general.retry_policy(), stories, count, config.general.max_concurrency())
+        .chain_err(|| format!("failed to list {} stories", list))?;
+
+    output(&items, &ctx.output_format())
+}
+
+/// NDJSON prints one story per line, e.g., to save the links by `rat pocket add`.
+fn output(items: &[Item], format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            if let Some(template) = output::template() {
+                msg(template.render_all(items));
+            } else {
+                msg(render_human(items)?);
+            }
+            Ok(())
+        }
+        OutputFormat::JSON => {
+            let json = serde_json::to_string(items).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+        OutputFormat::NDJSON => {
+            for item in items {
+                let json = serde_json::to_string(item).chain_err(|| "JSON serialization failed")?;
+                output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn render_human(items: &[Item]) -> Result<String> {
+    let mut tw = TabWriter::new(vec![]);
+    for (rank, item) in items.iter().enumerate() {
+        writeln!(&mut tw, "{}.\t{}\t{} points\t{} comments\t{}",
+                 rank + 1,
+                 item.title.as_ref().map(|t| t.as_str()).unwrap_or(""),
+                 item.score.unwrap_or(0),
+                 item.descendants.unwrap_or(0),
+                 item.link())
+            .chain_err(|| ErrorKind::OutputFailed)?;
+    }
+    tw.flush().chain_err(|| ErrorKind::OutputFailed)?;
+    let out = String::from_utf8(tw.into_inner().unwrap()).chain_err(|| ErrorKind::OutputFailed)?;
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render_human() {
+        let items: Vec<Item> = serde_json::from_str(r#"[
+            {"by":"pg","descendants":15,"id":8863,"score":111,"time":1175714200,"title":"My YC app","type":"story","url":"http://www.getdropbox.com/u/2/screencast.html"},
+            {"by":"tel","descendants":16,"id":121003,"score":25,"time":1203647620,"title":"Ask HN: The Arc Effect","type":"story"}
+        ]"#).unwrap();
+
+        let out = render_human(&items).unwrap();
+
+        assert_eq!(out, "1.  My YC app               111 points  15 comments  http://www.getdropbox.com/u/2/screencast.html\n\
+                         2.  Ask HN: The Arc Effect  25 points   16 comments  https://news.ycombinator.com/item?id=121003\n");
+    }
+}
@@ -0,0 +1,118 @@
+use super::client;
+use super::NAME as MODULE_NAME;
+use config::OutputFormat;
+use credentials;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::collections::HashSet;
+
+pub const NAME_ARCHIVE: &'static str = "archive";
+pub const NAME_DELETE: &'static str = "delete";
+
+pub fn build_sub_cli() -> Vec<App<'static, 'static>> {
+    vec![
+        SubCommand::with_name(NAME_ARCHIVE)
+            .about("Archive saved bookmarks")
+            .arg(Arg::with_name("id")
+                .index(1)
+                .multiple(true)
+                .required(true)
+                .help("bookmark id")),
+        SubCommand::with_name(NAME_DELETE)
+            .about("Delete saved bookmarks permanently")
+            .arg(Arg::with_name("id")
+                .index(1)
+                .multiple(true)
+                .required(true)
+                .help("bookmark id")),
+    ]
+}
+
+/// The result of an action on one bookmark
+#[derive(Serialize, Debug)]
+struct ActionResult<'a> {
+    bookmark_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")] error: Option<String>,
+}
+
+/// Unlike Pocket, Instapaper takes one bookmark per request, so the bookmarks are sent one after the other.
+pub fn call(action: &str, args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    credentials::ensure_writable(config, MODULE_NAME)
+        .chain_err(|| format!("action '{}' failed to apply to Instapaper bookmark", action))?;
+    let ids = unique_ids(args.values_of("id").unwrap());
+
+    if ctx.dry_run {
+        msgln(format!("Dry run: would send {} action for {} bookmark(s).", action, ids.len()));
+        return Ok(());
+    }
+
+    info(format!("Sending {} action for {} bookmark(s) ...", action, ids.len()));
+    let results: Vec<ActionResult> = ids.into_iter().map(|id| {
+        let result = match action {
+            NAME_ARCHIVE => client::archive(config, id).map(|_| ()),
+            _ => client::delete(config, id),
+        };
+        // The root cause is the most specific, e.g., the HTTP status
+        ActionResult { bookmark_id: id, error: result.err().and_then(|e| e.iter().last().map(|c| c.to_string())) }
+    }).collect();
+
+    output(&results, &config.general.output_format)?;
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+    if failed > 0 {
+        bail!(ErrorKind::PartialFailure(results.len() - failed, failed));
+    }
+
+    Ok(())
+}
+
+/// Drops repeated ids, keeping the order of the first occurrences, so every bookmark gets exactly one result.
+fn unique_ids<'a, I: Iterator<Item = &'a str>>(ids: I) -> Vec<&'a str> {
+    let mut seen = HashSet::new();
+    ids.filter(|id| seen.insert(*id)).collect()
+}
+
+fn output(results: &[ActionResult], format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            msg(render_human(results));
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(results).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(results: &[ActionResult]) -> String {
+    let mut out = String::new();
+    for result in results.iter().filter(|r| r.error.is_some()) {
+        out.push_str(&format!("Bookmark {} failed: {}\n", result.bookmark_id, result.error.as_ref().unwrap()));
+    }
+    let successful = results.iter().filter(|r| r.error.is_none()).count();
+    out.push_str(&format!("{} action(s) successful, {} failed.\n", successful, results.len() - successful));
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render_human() {
+        let results = vec![
+            ActionResult { bookmark_id: "1", error: None },
+            ActionResult { bookmark_id: "2", error: Some("Invalid or missing bookmark_id".to_string()) },
+        ];
+
+        assert_eq!(render_human(&results), "Bookmark 2 failed: Invalid or missing bookmark_id\n1 action(s) successful, 1 failed.\n");
+    }
+}
@@ -0,0 +1,119 @@
+use super::client;
+use super::NAME as MODULE_NAME;
+use config::OutputFormat;
+use credentials;
+use errors::*;
+use modules::Context;
+use modules::bookmarks::{self, Bookmark};
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::io::Write;
+use tabwriter::TabWriter;
+
+pub const NAME: &'static str = "add";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Save bookmarks")
+        .arg(Arg::with_name("title")
+            .long("title")
+            .takes_value(true)
+            .help("Sets the title instead of the one Instapaper determines"))
+        .arg(Arg::with_name("description")
+            .long("description")
+            .takes_value(true)
+            .help("Sets a description of the bookmarks"))
+        .arg(Arg::with_name("url")
+            .index(1)
+            .multiple(true)
+            .required_unless("bookmarks")
+            .help("URLs to save"))
+        .arg(bookmarks::add_arg())
+}
+
+/// The result of adding one URL
+#[derive(Serialize, Debug)]
+struct Added {
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")] bookmark_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")] title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")] error: Option<String>,
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let args = args.unwrap();
+
+    let urls = args.values_of("url").map(|urls| urls.map(|url| url.to_string()).collect()).unwrap_or_default();
+    let bookmarks = bookmarks::to_add(args, urls, args.value_of("title"), &[])?;
+
+    add(ctx, &bookmarks, args.value_of("description"))
+}
+
+/// Instapaper has folders instead of tags, so the tags of bookmark records are dropped.
+pub fn add_bookmarks(ctx: &Context, bookmarks: &[Bookmark]) -> Result<()> {
+    add(ctx, bookmarks, None)
+}
+
+/// URLs are added one after the other, because Instapaper limits the rate of requests per user.
+fn add(ctx: &Context, bookmarks: &[Bookmark], description: Option<&str>) -> Result<()> {
+    let config = ctx.config;
+    credentials::ensure_writable(config, MODULE_NAME).chain_err(|| "failed to add Instapaper bookmarks")?;
+
+    if ctx.dry_run {
+        msgln(format!("Dry run: would add {} bookmark(s).", bookmarks.len()));
+        return Ok(());
+    }
+
+    info(format!("Adding {} bookmark(s) ...", bookmarks.len()));
+    let added: Vec<Added> = bookmarks.iter().map(|b| {
+        let title = if b.title.is_empty() { None } else { Some(b.title.as_str()) };
+        match client::add(config, &b.url, title, description) {
+            Ok(bookmark) => Added { url: b.url.clone(), bookmark_id: Some(bookmark.bookmark_id), title: Some(bookmark.title), error: None },
+            // The root cause is the most specific, e.g., the HTTP status
+            Err(e) => Added { url: b.url.clone(), bookmark_id: None, title: None, error: e.iter().last().map(|c| c.to_string()) },
+        }
+    }).collect();
+
+    output(&added, &config.general.output_format)?;
+    let failed = added.iter().filter(|a| a.error.is_some()).count();
+    if failed > 0 {
+        bail!(ErrorKind::PartialFailure(added.len() - failed, failed));
+    }
+
+    Ok(())
+}
+
+fn output(added: &[Added], format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            msg(render_human(added)?);
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(added).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(added: &[Added]) -> Result<String> {
+    let mut tw = TabWriter::new(vec![]);
+    for a in added {
+        match (&a.bookmark_id, &a.error) {
+            (&Some(bookmark_id), _) =>
+                writeln!(&mut tw, "added\t{}\t{}", bookmark_id, match a.title {
+                    Some(ref title) if !title.is_empty() => title,
+                    _ => &a.url,
+                }),
+            (&None, error) =>
+                writeln!(&mut tw, "failed\t{}\t{}", a.url, error.as_ref().map(|e| e.as_str()).unwrap_or("")),
+        }.chain_err(|| ErrorKind::OutputFailed)?;
+    }
+    tw.flush().chain_err(|| ErrorKind::OutputFailed)?;
+    let out = String::from_utf8(tw.into_inner().unwrap()).chain_err(|| ErrorKind::OutputFailed)?;
+
+    Ok(out)
+}
@@ -0,0 +1,152 @@
+use super::client;
+use super::client::Folder;
+use config::OutputFormat;
+use errors::*;
+use modules::{self, Context};
+use modules::bookmarks::{self, Bookmark};
+use utils::console::*;
+use utils::output;
+
+use chrono::{DateTime, NaiveDateTime, UTC};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::io::Write;
+use std::time::Duration;
+use tabwriter::TabWriter;
+
+pub const NAME: &'static str = "list";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("List saved bookmarks")
+        .arg(Arg::with_name("folder")
+            .long("folder")
+            .short("f")
+            .takes_value(true)
+            .possible_values(&["unread", "starred", "archive"])
+            .default_value("unread")
+            .help("Select bookmarks to list"))
+        .arg(Arg::with_name("count")
+            .long("count")
+            .takes_value(true)
+            .default_value("25")
+            .help("Lists at most <count> bookmarks, most recently saved first; Instapaper returns at most 500"))
+        .args(&modules::added_args())
+        .arg(bookmarks::list_arg())
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+
+    let folder: Folder = args.value_of("folder").unwrap().into();
+    let count = args.value_of("count").unwrap();
+    let count = match count.parse::<usize>() {
+        Ok(count) if count > 0 && count <= client::MAX_LIMIT => count,
+        _ => bail!("Invalid count '{}'; expected a number from 1 to {}", count, client::MAX_LIMIT),
+    };
+
+    let added = modules::added_range(args)?;
+
+    let mut bookmarks = client::list(config, &folder, count).chain_err(|| "failed to list Instapaper bookmarks")?;
+    bookmarks.retain(|b| added.contains(Duration::from_secs(b.time)));
+
+    if args.is_present("bookmarks") {
+        return bookmarks::print_all(&bookmarks.iter().map(to_bookmark).collect::<Vec<_>>());
+    }
+    output(&bookmarks, &config.general.output_format)
+}
+
+/// Instapaper lists at most 500 bookmarks per folder.
+pub fn bookmarks(ctx: &Context) -> Result<Vec<Bookmark>> {
+    let mut bookmarks = Vec::new();
+    for folder in &[Folder::unread, Folder::archive] {
+        let listed = client::list(ctx.config, folder, client::MAX_LIMIT).chain_err(|| "failed to list Instapaper bookmarks")?;
+        bookmarks.extend(listed.iter().map(to_bookmark));
+    }
+
+    Ok(bookmarks)
+}
+
+/// Instapaper has folders instead of tags.
+fn to_bookmark(bookmark: &client::Bookmark) -> Bookmark {
+    Bookmark {
+        url: bookmark.url.clone(),
+        title: bookmark.title.clone(),
+        tags: Vec::new(),
+        time_added: if bookmark.time > 0 { Some(bookmark.time) } else { None },
+        time_updated: None,
+    }
+}
+
+fn output(bookmarks: &[client::Bookmark], format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            if let Some(template) = output::template() {
+                msg(template.render_all(bookmarks));
+            } else {
+                msg(render_human(bookmarks)?);
+            }
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(bookmarks).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(bookmarks: &[client::Bookmark]) -> Result<String> {
+    let mut tw = TabWriter::new(vec![]);
+    for b in bookmarks {
+        let title = if b.title.is_empty() { &b.url } else { &b.title };
+        let starred = if b.starred == "1" { "*" } else { "" };
+        writeln!(&mut tw, "{}\t{}{}\t{}\tadded {}", b.bookmark_id, starred, title, b.url, format_time(b.time))
+            .chain_err(|| ErrorKind::OutputFailed)?;
+    }
+    tw.flush().chain_err(|| ErrorKind::OutputFailed)?;
+    let mut out = String::from_utf8(tw.into_inner().unwrap()).chain_err(|| ErrorKind::OutputFailed)?;
+    out.push_str(&format!("Received {} bookmark(s).\n", bookmarks.len()));
+
+    Ok(out)
+}
+
+fn format_time(unix_ts: u64) -> String {
+    let dt = DateTime::<UTC>::from_utc(NaiveDateTime::from_timestamp(unix_ts as i64, 0), UTC);
+    dt.to_rfc3339()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render_human() {
+        let bookmarks = vec![
+            client::Bookmark {
+                bookmark_id: 42,
+                url: "https://example.com/a".to_string(),
+                title: "A".to_string(),
+                description: "".to_string(),
+                time: 1500000000,
+                starred: "1".to_string(),
+                progress: 0.0,
+            },
+            client::Bookmark {
+                bookmark_id: 7,
+                url: "https://example.com/b".to_string(),
+                title: "".to_string(),
+                description: "".to_string(),
+                time: 1500000000,
+                starred: "0".to_string(),
+                progress: 0.5,
+            },
+        ];
+
+        let out = render_human(&bookmarks).unwrap();
+
+        assert_eq!(out, "42  *A                     https://example.com/a  added 2017-07-14T02:40:00+00:00\n\
+                         7   https://example.com/b  https://example.com/b  added 2017-07-14T02:40:00+00:00\n\
+                         Received 2 bookmark(s).\n");
+    }
+}
@@ -0,0 +1,98 @@
+pub use clients::instapaper::{NAME, InstapaperConfig};
+
+use clients::instapaper::client;
+use config::Config;
+use credentials;
+use errors::*;
+use utils::console::*;
+use modules::{Context, Module};
+use modules::bookmarks::Bookmark;
+
+use clap::{App, ArgMatches, SubCommand};
+
+mod actions;
+mod add;
+mod auth;
+mod list;
+
+fn ensure_consumer(config: &Config) -> Result<()> {
+    if config.instapaper.consumer_key.is_empty() || config.instapaper.consumer_secret.is_empty() {
+        bail!("No consumer key and secret available; please set 'consumer_key' and 'consumer_secret' in section '[instapaper]'");
+    }
+
+    Ok(())
+}
+
+/// The bookmarks of folders 'unread' and 'archive' as bookmark records; cf. `rat xfer`.
+pub fn bookmarks(ctx: &Context) -> Result<Vec<Bookmark>> {
+    ensure_consumer(ctx.config)?;
+    list::bookmarks(ctx)
+}
+
+pub fn add_bookmarks(ctx: &Context, bookmarks: &[Bookmark]) -> Result<()> {
+    ensure_consumer(ctx.config)?;
+    add::add_bookmarks(ctx, bookmarks)
+}
+
+/// Exchanges user name and password for an access token; both are taken from section '[instapaper]' or asked for.
+/// Instapaper does not support scopes, so a read-only login only marks the stored token as read-only.
+pub fn login(config: &Config, username: Option<&str>, read_only: bool) -> Result<()> {
+    ensure_consumer(config)?;
+    let username = match username.or_else(|| config.instapaper.username.as_ref().map(|u| u.as_str())) {
+        Some(username) => username.to_string(),
+        None => read_line("Email or user name: ").chain_err(|| "Failed to read user name")?,
+    };
+    let password = match config.instapaper.password {
+        Some(ref password) => password.clone(),
+        None => read_secret("Password (empty if none): ").chain_err(|| "Failed to read password")?,
+    };
+    client::auth(config, &username, &password).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+    if read_only {
+        credentials::mark_read_only(config, NAME).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+    }
+
+    Ok(())
+}
+
+pub struct Instapaper;
+
+impl Module for Instapaper {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn about(&self) -> &'static str {
+        "Instapaper: A simple tool to save web pages for reading later."
+    }
+
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        let mut subcommand = SubCommand::with_name(NAME)
+            .about(self.about())
+            .subcommand(add::build_sub_cli())
+            .subcommand(auth::build_sub_cli())
+            .subcommand(list::build_sub_cli());
+        for s in actions::build_sub_cli() {
+            subcommand = subcommand.subcommand(s);
+        }
+
+        subcommand
+    }
+
+    fn call(&self, cli_args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        ensure_consumer(ctx.config).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+        let subcommand = cli_args.unwrap();
+        let subcommand_name = subcommand.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+        match subcommand_name {
+            add::NAME => add::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            auth::NAME => auth::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            list::NAME => list::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            actions::NAME_ARCHIVE | actions::NAME_DELETE =>
+                actions::call(subcommand_name, subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            _ => Ok(())
+        }
+    }
+}
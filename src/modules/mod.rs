@@ -1,4 +1,306 @@
+use config::{Config, OutputFormat, Verbosity};
+use errors::*;
+use net::http;
+use utils::datetime;
+
+use clap;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use hyper::Client;
+
+pub mod api;
+pub mod auth;
+pub mod bookmarks;
+pub mod cache;
+#[cfg(feature = "centerdevice")]
 pub mod centerdevice;
+pub mod config;
+pub mod dev;
+pub mod doctor;
+#[cfg(feature = "feed")]
+pub mod feed;
+pub mod flush;
+#[cfg(feature = "github")]
+pub mod github;
+#[cfg(feature = "hackernews")]
+pub mod hackernews;
+#[cfg(feature = "instapaper")]
+pub mod instapaper;
+#[cfg(feature = "pinboard")]
+pub mod pinboard;
+#[cfg(feature = "pocket")]
 pub mod pocket;
+#[cfg(feature = "slack")]
 pub mod slack;
+#[cfg(feature = "stocks")]
 pub mod stocks;
+#[cfg(feature = "wallabag")]
+pub mod wallabag;
+pub mod xfer;
+
+/// A module provides a top level command, e.g., `rat pocket`, and its sub commands.
+pub trait Module {
+    fn name(&self) -> &'static str;
+
+    fn about(&self) -> &'static str;
+
+    fn build_sub_cli(&self) -> App<'static, 'static>;
+
+    /// Placeholder of the command in `rat`'s top level CLI. It takes all arguments verbatim, so the module's complete
+    /// `App` is only built for the command actually dispatched; cf. `parse_module_args`.
+    fn build_stub_cli(&self) -> App<'static, 'static> {
+        SubCommand::with_name(self.name())
+            .about(self.about())
+            .setting(AppSettings::AllowLeadingHyphen)
+            .setting(AppSettings::DisableHelpFlags)
+            .setting(AppSettings::DisableVersion)
+            .setting(AppSettings::TrailingVarArg)
+            .arg(Arg::with_name("args")
+                .multiple(true)
+                .allow_hyphen_values(true))
+    }
+
+    fn call(&self, args: Option<&ArgMatches>, ctx: &Context) -> Result<()>;
+
+    /// False for modules left out at compile time; cf. `Absent`.
+    fn is_included(&self) -> bool {
+        true
+    }
+}
+
+/// Registers a service module if its Cargo feature is enabled, and `Absent` in its place otherwise.
+macro_rules! service {
+    ($modules:ident, $feature:tt, $module:expr) => {
+        #[cfg(feature = $feature)]
+        $modules.push(Box::new($module));
+        #[cfg(not(feature = $feature))]
+        $modules.push(Box::new(Absent($feature)));
+    };
+}
+
+/// All modules in the order of `rat --help`. Service modules left out at compile time are registered as `Absent`, so
+/// their commands explain how to get them instead of failing as unknown commands; cf. README, section 'Slim Builds'.
+/// The feature of a service module is named like its command. Adding a module takes its `mod` declaration, a line here,
+/// and for service modules the feature in Cargo.toml.
+pub fn registry() -> Vec<Box<Module>> {
+    let mut modules: Vec<Box<Module>> = Vec::new();
+    modules.push(Box::new(api::Api));
+    modules.push(Box::new(auth::Auth));
+    modules.push(Box::new(cache::Cache));
+    service!(modules, "centerdevice", centerdevice::CenterDevice);
+    modules.push(Box::new(config::Configuration));
+    modules.push(Box::new(dev::Dev));
+    modules.push(Box::new(doctor::Doctor));
+    service!(modules, "feed", feed::Feed);
+    modules.push(Box::new(flush::Flush));
+    service!(modules, "github", github::GitHub);
+    service!(modules, "hackernews", hackernews::HackerNews);
+    service!(modules, "instapaper", instapaper::Instapaper);
+    service!(modules, "pinboard", pinboard::Pinboard);
+    service!(modules, "pocket", pocket::Pocket);
+    service!(modules, "slack", slack::Slack);
+    service!(modules, "stocks", stocks::Stocks);
+    service!(modules, "wallabag", wallabag::Wallabag);
+    modules.push(Box::new(xfer::Xfer));
+
+    modules
+}
+
+/// Arguments captured by a module's stub command; cf. `Module::build_stub_cli`.
+pub fn stub_args<'a>(stub_matches: Option<&'a ArgMatches>) -> Vec<&'a str> {
+    stub_matches
+        .and_then(|matches| matches.values_of("args"))
+        .map(|values| values.collect())
+        .unwrap_or_default()
+}
+
+/// Parses the arguments captured by the module's stub command with the module's complete `App`. `bin_name` is only
+/// used for usage and help messages. Help requests and usage errors are returned as `clap::Error`, so the caller
+/// decides when to exit.
+pub fn parse_module_args(module: &Module, bin_name: &str, module_args: &[&str]) -> clap::Result<ArgMatches<'static>> {
+    let mut args = vec![bin_name, module.name()];
+    args.extend_from_slice(module_args);
+    let matches = App::new(bin_name.to_string())
+        .subcommand(module.build_sub_cli())
+        .get_matches_from_safe(args)?;
+
+    Ok(matches.subcommand_matches(module.name()).cloned().unwrap_or_default())
+}
+
+/// Whether a module has been compiled into this build. Names of modules rat does not know at all count as included,
+/// because they may be generic OAuth modules configured in section '[oauth.<module>]'.
+pub fn is_included(module_name: &str) -> bool {
+    registry().iter().find(|module| module.name() == module_name).map(|module| module.is_included()).unwrap_or(true)
+}
+
+/// Commands whose human output may be longer than a screen, e.g., listings, as '<module> <command>'. Interactive and
+/// long-running commands like 'pocket browse' or 'pocket watch' are never paged; cf. `is_paged`.
+static PAGED_COMMANDS: &'static [&'static str] = &[
+    "centerdevice collections", "centerdevice search", "feed list", "github notifications", "github stars",
+    "hackernews new", "hackernews top", "instapaper list", "pinboard list", "pinboard tags", "pocket apply-rules",
+    "pocket dedupe", "pocket diff", "pocket digest", "pocket list", "pocket local", "pocket query", "pocket stats",
+    "slack channels", "slack users", "wallabag list",
+];
+
+/// Whether the human output of a module's command may be shown in a pager; cf. `utils::pager`.
+pub fn is_paged(module_name: &str, module_args: &ArgMatches) -> bool {
+    let command = format!("{} {}", module_name, module_args.subcommand_name().unwrap_or(""));
+    PAGED_COMMANDS.contains(&command.as_str())
+}
+
+/// Stand-in for a service module left out at compile time. It accepts any arguments, so that scripts written for a
+/// full build get a helpful error instead of a usage message.
+pub struct Absent(pub &'static str);
+
+impl Module for Absent {
+    fn name(&self) -> &'static str {
+        self.0
+    }
+
+    fn about(&self) -> &'static str {
+        "Not included in this build"
+    }
+
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        SubCommand::with_name(self.0)
+            .about(self.about())
+            .setting(AppSettings::Hidden)
+            .setting(AppSettings::AllowExternalSubcommands)
+            .setting(AppSettings::TrailingVarArg)
+            .arg(Arg::with_name("args")
+                .multiple(true)
+                .allow_hyphen_values(true))
+    }
+
+    fn build_stub_cli(&self) -> App<'static, 'static> {
+        self.build_sub_cli()
+    }
+
+    fn call(&self, _: Option<&ArgMatches>, _: &Context) -> Result<()> {
+        bail!(ErrorKind::ModuleNotIncluded(self.0.to_string()))
+    }
+
+    fn is_included(&self) -> bool {
+        false
+    }
+}
+
+/// Arguments `--added-since` and `--added-before` of list commands, which filter the received items; cf. `added_range`.
+pub fn added_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("added-since")
+            .long("added-since")
+            .takes_value(true)
+            .value_name("time")
+            .help("Lists only items added since <time>, a duration ago like '2w 3d' or a date like '2023-01-01'"),
+        Arg::with_name("added-before")
+            .long("added-before")
+            .takes_value(true)
+            .value_name("time")
+            .help("Lists only items added before <time>, a duration ago like '2w 3d' or a date like '2023-01-01'"),
+    ]
+}
+
+pub fn added_range(args: &ArgMatches) -> Result<datetime::Range> {
+    datetime::Range::parse(args.value_of("added-since"), args.value_of("added-before")).chain_err(|| "Invalid time filter")
+}
+
+/// Everything a command needs besides its arguments. Cross-cutting settings like dry runs are kept here, so commands
+/// do not need to find them in the configuration or the global arguments.
+#[derive(Debug)]
+pub struct Context<'a> {
+    pub config: &'a Config,
+    /// Commands that modify data only report what they would do
+    pub dry_run: bool,
+    /// `--account all`; commands that support it query all named accounts of their module; cf. `Config::accounts`
+    pub all_accounts: bool,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        Context { config: config, dry_run: false, all_accounts: false }
+    }
+
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn with_all_accounts(mut self, all_accounts: bool) -> Self {
+        self.all_accounts = all_accounts;
+        self
+    }
+
+    pub fn output_format(&self) -> OutputFormat {
+        self.config.general.output_format
+    }
+
+    /// Progress bars only make sense for humans and would interfere with quiet or verbose output.
+    pub fn show_progress(&self) -> bool {
+        self.config.general.output_format == OutputFormat::HUMAN && self.config.general.verbosity <= Verbosity::NORMAL
+    }
+
+    pub fn http_client(&self) -> http::Result<Client> {
+        http::tls_client()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rat_core::testing;
+
+    #[test]
+    fn test_registry_contains_all_modules() {
+        let names: Vec<&str> = registry().iter().map(|m| m.name()).collect();
+
+        assert_eq!(names, vec!["api", "auth", "cache", "centerdevice", "config", "dev", "doctor", "feed", "flush", "github", "hackernews", "instapaper", "pinboard", "pocket", "slack", "stocks", "wallabag", "xfer"]);
+    }
+
+    #[test]
+    fn test_is_included() {
+        assert_eq!(is_included("pocket"), cfg!(feature = "pocket"));
+        assert!(is_included("auth"));
+        assert!(is_included("my-oauth-service"));
+    }
+
+    #[test]
+    fn test_absent_module_accepts_any_args() {
+        let app = App::new("rat").subcommand(Absent("pocket").build_sub_cli());
+
+        let matches = app.get_matches_from_safe(vec!["rat", "pocket", "list", "--since", "1d"]);
+
+        assert!(matches.is_ok());
+    }
+
+    #[test]
+    fn test_stub_passes_args_to_module() {
+        let app = App::new("rat").subcommand(Absent("pocket").build_stub_cli());
+        let stub = app.get_matches_from(vec!["rat", "pocket", "list", "--since", "1d"]);
+
+        let args = parse_module_args(&Absent("pocket"), "rat", &stub_args(stub.subcommand_matches("pocket"))).unwrap();
+
+        let values: Vec<&str> = args.values_of("args").unwrap().collect();
+        assert_eq!(values, vec!["list", "--since", "1d"]);
+    }
+
+    #[test]
+    fn test_stub_passes_help_to_module() {
+        let app = App::new("rat").subcommand(auth::Auth.build_stub_cli());
+        let stub = app.get_matches_from(vec!["rat", "auth", "--help"]);
+
+        let error = parse_module_args(&auth::Auth, "rat", &stub_args(stub.subcommand_matches("auth"))).unwrap_err();
+
+        assert_eq!(error.kind, clap::ErrorKind::HelpDisplayed);
+    }
+
+    #[test]
+    fn test_absent_module_fails_with_usage_error() {
+        let config = testing::config("modules", "");
+        let ctx = Context::new(&config);
+
+        let error = Absent("pocket").call(None, &ctx).unwrap_err();
+
+        assert_eq!(error.category(), Category::Usage);
+    }
+}
@@ -0,0 +1,124 @@
+use super::client;
+use super::client::AddRequest;
+use super::NAME as MODULE_NAME;
+use credentials;
+use errors::*;
+use modules::Context;
+use modules::bookmarks::{self, Bookmark};
+use utils::console::*;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+pub const NAME: &'static str = "add";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Save a bookmark")
+        .arg(Arg::with_name("title")
+            .long("title")
+            .takes_value(true)
+            .help("Sets the title; defaults to the URL"))
+        .arg(Arg::with_name("description")
+            .long("description")
+            .takes_value(true)
+            .help("Sets a description of the bookmark"))
+        .arg(Arg::with_name("tags")
+            .long("tag")
+            .short("t")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .help("Adds a tag; may be repeated"))
+        .arg(Arg::with_name("toread")
+            .long("toread")
+            .help("Marks the bookmark as unread"))
+        .arg(Arg::with_name("private")
+            .long("private")
+            .help("Hides the bookmark from other users"))
+        .arg(Arg::with_name("replace")
+            .long("replace")
+            .help("Replaces an existing bookmark of the URL"))
+        .arg(Arg::with_name("url")
+            .index(1)
+            .required_unless("bookmarks")
+            .help("URL to save"))
+        .arg(bookmarks::add_arg())
+}
+
+/// Settings of all bookmarks of one add command
+#[derive(Debug, Default)]
+struct Settings<'a> {
+    description: Option<&'a str>,
+    to_read: bool,
+    private: bool,
+    replace: bool,
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let args = args.unwrap();
+
+    let urls = args.value_of("url").map(|url| vec![url.to_string()]).unwrap_or_default();
+    let tags: Vec<&str> = args.values_of("tags").map(|tags| tags.collect()).unwrap_or_default();
+    let bookmarks = bookmarks::to_add(args, urls, args.value_of("title"), &tags)?;
+    let settings = Settings {
+        description: args.value_of("description"),
+        to_read: args.is_present("toread"),
+        private: args.is_present("private"),
+        replace: args.is_present("replace"),
+    };
+
+    add(ctx, &bookmarks, &settings)
+}
+
+/// Adds bookmark records with Pinboard's defaults, i.e., public and read
+pub fn add_bookmarks(ctx: &Context, bookmarks: &[Bookmark]) -> Result<()> {
+    add(ctx, bookmarks, &Settings::default())
+}
+
+/// Bookmarks are added one after the other, because Pinboard allows only one call every three seconds.
+fn add(ctx: &Context, bookmarks: &[Bookmark], settings: &Settings) -> Result<()> {
+    let config = ctx.config;
+    credentials::ensure_writable(config, MODULE_NAME).chain_err(|| "failed to add Pinboard bookmarks")?;
+
+    if ctx.dry_run {
+        if bookmarks.len() == 1 {
+            msgln(format!("Dry run: would add bookmark '{}'.", bookmarks[0].url));
+        } else {
+            msgln(format!("Dry run: would add {} bookmark(s).", bookmarks.len()));
+        }
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for b in bookmarks {
+        // Pinboard separates tags by spaces
+        let tags = b.tags.iter().map(|t| t.replace(' ', "_")).collect::<Vec<_>>().join(" ");
+        let time = b.time_added_iso8601();
+        let request = AddRequest {
+            url: &b.url,
+            title: b.title_or_url(),
+            description: settings.description,
+            tags: if tags.is_empty() { None } else { Some(&tags) },
+            to_read: settings.to_read,
+            private: settings.private,
+            replace: settings.replace,
+            time: time.as_ref().map(|t| t.as_str()),
+        };
+
+        info(format!("Adding bookmark '{}' ...", b.url));
+        match client::add(config, &request) {
+            Ok(()) => msgln(format!("Added bookmark '{}'.", b.url)),
+            Err(e) if bookmarks.len() == 1 => return Err(e).chain_err(|| format!("failed to add Pinboard bookmark '{}'", b.url)),
+            // The root cause is the most specific, e.g., Pinboard's result code
+            Err(e) => {
+                error(format!("Failed to add bookmark '{}' because {}", b.url, e.iter().last().map(|c| c.to_string()).unwrap_or_default()));
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        bail!(ErrorKind::PartialFailure(bookmarks.len() - failed, failed));
+    }
+
+    Ok(())
+}
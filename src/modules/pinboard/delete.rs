@@ -0,0 +1,81 @@
+use super::client;
+use super::NAME as MODULE_NAME;
+use config::OutputFormat;
+use credentials;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+
+pub const NAME: &'static str = "delete";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Delete saved bookmarks")
+        .arg(Arg::with_name("url")
+            .index(1)
+            .multiple(true)
+            .required(true)
+            .help("URL of bookmark"))
+}
+
+/// The result of deleting one bookmark
+#[derive(Serialize, Debug)]
+struct Deleted<'a> {
+    url: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")] error: Option<String>,
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    credentials::ensure_writable(config, MODULE_NAME).chain_err(|| "failed to delete Pinboard bookmarks")?;
+    let urls: Vec<&str> = args.values_of("url").unwrap().collect();
+
+    if ctx.dry_run {
+        msgln(format!("Dry run: would delete {} bookmark(s).", urls.len()));
+        return Ok(());
+    }
+
+    info(format!("Deleting {} bookmark(s) ...", urls.len()));
+    let results: Vec<Deleted> = urls.into_iter().map(|url| {
+        let result = client::delete(config, url);
+        // The root cause is the most specific, e.g., Pinboard's result code
+        Deleted { url: url, error: result.err().and_then(|e| e.iter().last().map(|c| c.to_string())) }
+    }).collect();
+
+    output(&results, &config.general.output_format)?;
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+    if failed > 0 {
+        bail!(ErrorKind::PartialFailure(results.len() - failed, failed));
+    }
+
+    Ok(())
+}
+
+fn output(results: &[Deleted], format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            msg(render_human(results));
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(results).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(results: &[Deleted]) -> String {
+    let mut out = String::new();
+    for result in results.iter().filter(|r| r.error.is_some()) {
+        out.push_str(&format!("Bookmark {} failed: {}\n", result.url, result.error.as_ref().unwrap()));
+    }
+    let successful = results.iter().filter(|r| r.error.is_none()).count();
+    out.push_str(&format!("{} bookmark(s) deleted, {} failed.\n", successful, results.len() - successful));
+
+    out
+}
@@ -0,0 +1,152 @@
+use super::client;
+use super::client::Post;
+use config::OutputFormat;
+use errors::*;
+use modules::{self, Context};
+use modules::bookmarks::{self, Bookmark};
+use utils::console::*;
+use utils::datetime;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::io::Write;
+use tabwriter::TabWriter;
+
+pub const NAME: &'static str = "list";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("List saved bookmarks")
+        .arg(Arg::with_name("tag")
+            .long("tag")
+            .short("t")
+            .takes_value(true)
+            .help("Lists only bookmarks with this tag"))
+        .arg(Arg::with_name("count")
+            .long("count")
+            .takes_value(true)
+            .default_value("25")
+            .conflicts_with("all")
+            .help("Lists at most <count> bookmarks, most recently saved first; Pinboard returns at most 100"))
+        .arg(Arg::with_name("all")
+            .long("all")
+            .help("Lists all bookmarks; Pinboard allows this only once every five minutes"))
+        .args(&modules::added_args())
+        .arg(bookmarks::list_arg())
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+
+    let tag = args.value_of("tag");
+    let count = if args.is_present("all") {
+        None
+    } else {
+        let count = args.value_of("count").unwrap();
+        match count.parse::<usize>() {
+            Ok(count) if count > 0 && count <= client::MAX_RECENT => Some(count),
+            _ => bail!("Invalid count '{}'; expected a number from 1 to {}", count, client::MAX_RECENT),
+        }
+    };
+
+    let added = modules::added_range(args)?;
+
+    let mut posts = client::list(config, tag, count).chain_err(|| "failed to list Pinboard bookmarks")?;
+    posts.retain(|p| added.contains_iso8601(&p.time));
+
+    if args.is_present("bookmarks") {
+        return bookmarks::print_all(&posts.iter().map(to_bookmark).collect::<Vec<_>>());
+    }
+    output(&posts, &config.general.output_format)
+}
+
+pub fn bookmarks(ctx: &Context) -> Result<Vec<Bookmark>> {
+    let posts = client::list(ctx.config, None, None).chain_err(|| "failed to list Pinboard bookmarks")?;
+
+    Ok(posts.iter().map(to_bookmark).collect())
+}
+
+fn to_bookmark(post: &Post) -> Bookmark {
+    Bookmark {
+        url: post.href.clone(),
+        title: post.description.clone(),
+        tags: post.tags.split_whitespace().map(|t| t.to_string()).collect(),
+        time_added: datetime::parse_iso8601(&post.time).ok().map(|t| t.as_secs()),
+        time_updated: None,
+    }
+}
+
+fn output(posts: &[Post], format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            if let Some(template) = output::template() {
+                msg(template.render_all(posts));
+            } else {
+                msg(render_human(posts)?);
+            }
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(posts).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(posts: &[Post]) -> Result<String> {
+    let mut tw = TabWriter::new(vec![]);
+    for p in posts {
+        let title = if p.description.is_empty() { &p.href } else { &p.description };
+        let to_read = if p.toread == "yes" { "*" } else { "" };
+        writeln!(&mut tw, "{}{}\t{}\t[{}]\tadded {}", to_read, title, p.href, p.tags, p.time)
+            .chain_err(|| ErrorKind::OutputFailed)?;
+    }
+    tw.flush().chain_err(|| ErrorKind::OutputFailed)?;
+    let mut out = String::from_utf8(tw.into_inner().unwrap()).chain_err(|| ErrorKind::OutputFailed)?;
+    out.push_str(&format!("Received {} bookmark(s).\n", posts.len()));
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn post(href: &str, description: &str, toread: &str, tags: &str) -> Post {
+        Post {
+            href: href.to_string(),
+            description: description.to_string(),
+            extended: "".to_string(),
+            hash: "".to_string(),
+            time: "2017-07-14T02:40:00Z".to_string(),
+            shared: "no".to_string(),
+            toread: toread.to_string(),
+            tags: tags.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render_human() {
+        let posts = vec![
+            post("https://example.com/a", "A", "yes", "rust cli"),
+            post("https://example.com/b", "", "no", ""),
+        ];
+
+        let out = render_human(&posts).unwrap();
+
+        assert_eq!(out, "*A                     https://example.com/a  [rust cli]  added 2017-07-14T02:40:00Z\n\
+                         https://example.com/b  https://example.com/b  []          added 2017-07-14T02:40:00Z\n\
+                         Received 2 bookmark(s).\n");
+    }
+
+    #[test]
+    fn test_to_bookmark() {
+        let bookmark = to_bookmark(&post("https://example.com/a", "A", "yes", "rust cli"));
+
+        assert_eq!(bookmark.title, "A");
+        assert_eq!(bookmark.tags, vec!["rust", "cli"]);
+        assert_eq!(bookmark.time_added, Some(1500000000));
+    }
+}
@@ -0,0 +1,120 @@
+pub use clients::pinboard::{NAME, PinboardConfig};
+
+use clients::pinboard::client;
+use config::Config;
+use credentials;
+use errors::*;
+use modules::{Context, Module};
+use modules::bookmarks::Bookmark;
+
+use clap::{App, ArgMatches, SubCommand};
+
+mod add;
+mod delete;
+mod list;
+mod tags;
+
+/// Pinboard has no OAuth; the API token is either configured or injected by the service manager.
+fn ensure_api_token(config: &Config) -> Result<()> {
+    if config.pinboard.api_token.is_none() && !credentials::is_injected(config, NAME) {
+        bail!("No API token available; please set 'api_token' in section '[pinboard]'");
+    }
+
+    Ok(())
+}
+
+/// All bookmarks as bookmark records; cf. `rat xfer`.
+pub fn bookmarks(ctx: &Context) -> Result<Vec<Bookmark>> {
+    ensure_api_token(ctx.config)?;
+    list::bookmarks(ctx)
+}
+
+pub fn add_bookmarks(ctx: &Context, bookmarks: &[Bookmark]) -> Result<()> {
+    ensure_api_token(ctx.config)?;
+    add::add_bookmarks(ctx, bookmarks)
+}
+
+pub struct Pinboard;
+
+impl Module for Pinboard {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn about(&self) -> &'static str {
+        "Pinboard: Social bookmarking for introverts."
+    }
+
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        SubCommand::with_name(NAME)
+            .about(self.about())
+            .subcommand(add::build_sub_cli())
+            .subcommand(delete::build_sub_cli())
+            .subcommand(list::build_sub_cli())
+            .subcommand(tags::build_sub_cli())
+    }
+
+    fn call(&self, cli_args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        ensure_api_token(ctx.config).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+        let subcommand = cli_args.unwrap();
+        let subcommand_name = subcommand.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+        match subcommand_name {
+            add::NAME => add::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            delete::NAME => delete::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            list::NAME => list::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            tags::NAME => tags::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            _ => Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use errors::Category;
+    use net::mock::MockClient;
+    use testing::harness::*;
+
+    use std::rc::Rc;
+
+    #[test]
+    fn test_add() {
+        let config = config("pinboard-add", "[pinboard]\napi_token = 'user:token'");
+        let mock = Rc::new(MockClient::new().respond(200, &[], r#"{"result_code":"done"}"#));
+
+        run(&Pinboard, &["add", "--tag", "rust", "--tag", "cli", "https://example.com"], &Context::new(&config), &mock).unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("GET /v1/posts/add?"));
+        assert!(requests[0].contains("url=https%3A%2F%2Fexample.com"));
+        assert!(requests[0].contains("tags=rust+cli"));
+    }
+
+    #[test]
+    fn test_add_dry_run_sends_nothing() {
+        let config = config("pinboard-add-dry-run", "[pinboard]\napi_token = 'user:token'");
+        let mock = Rc::new(MockClient::new());
+
+        run(&Pinboard, &["add", "https://example.com"], &Context::new(&config).with_dry_run(true), &mock).unwrap();
+
+        assert!(mock.requests().is_empty());
+    }
+
+    #[test]
+    fn test_delete_reports_partial_failure() {
+        let config = config("pinboard-delete", "[pinboard]\napi_token = 'user:token'");
+        let mock = Rc::new(MockClient::new()
+            .respond(200, &[], r#"{"result_code":"done"}"#)
+            .respond(200, &[], r#"{"result_code":"item not found"}"#));
+
+        let result = run(&Pinboard, &["delete", "https://example.com/1", "https://example.com/2"], &Context::new(&config), &mock);
+
+        assert_eq!(result.unwrap_err().category(), Category::PartialFailure);
+        assert_eq!(mock.requests().len(), 2);
+    }
+}
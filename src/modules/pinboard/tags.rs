@@ -0,0 +1,57 @@
+use super::client;
+use config::OutputFormat;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, ArgMatches, SubCommand};
+use serde_json;
+use std::io::Write;
+use tabwriter::TabWriter;
+
+pub const NAME: &'static str = "tags";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("List tags with their number of bookmarks")
+}
+
+#[derive(Serialize, Debug)]
+struct Tag<'a> {
+    tag: &'a str,
+    count: u64,
+}
+
+pub fn call(_: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+
+    let tags = client::tags(config).chain_err(|| "failed to get Pinboard tags")?;
+
+    output(&tags, &config.general.output_format)
+}
+
+fn output(tags: &[(String, u64)], format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            msg(render_human(tags)?);
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let tags: Vec<Tag> = tags.iter().map(|&(ref tag, count)| Tag { tag: tag, count: count }).collect();
+            let json = serde_json::to_string(&tags).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(tags: &[(String, u64)]) -> Result<String> {
+    let mut tw = TabWriter::new(vec![]);
+    for &(ref tag, count) in tags {
+        writeln!(&mut tw, "{}\t{}", tag, count).chain_err(|| ErrorKind::OutputFailed)?;
+    }
+    tw.flush().chain_err(|| ErrorKind::OutputFailed)?;
+    let out = String::from_utf8(tw.into_inner().unwrap()).chain_err(|| ErrorKind::OutputFailed)?;
+
+    Ok(out)
+}
@@ -1,11 +1,16 @@
-use super::client;
+use super::{client, queue};
 use super::client::send::ActionRequest;
-use config::{Config, OutputFormat};
+use super::NAME as MODULE_NAME;
+use config::OutputFormat;
+use credentials;
+use errors::*;
+use modules::Context;
 use utils::console::*;
 use utils::output;
 
 use clap::{App, Arg, ArgMatches, SubCommand};
 use serde_json;
+use std::collections::HashSet;
 use std::str;
 
 pub const NAME_ARCHIVE: &'static str = "archive";
@@ -15,19 +20,6 @@ pub const NAME_FAVORITE: &'static str = "favorite";
 pub const NAME_UNFAVORITE: &'static str = "unfavorite";
 pub const NAME_DELETE: &'static str = "delete";
 
-error_chain! {
-    errors {
-       PocketActionFailed(action: String) {
-            description("action failed to apply to Pocket articles")
-            display("action '{}' failed to apply to Pocket article", action)
-       }
-       OutputFailed {
-            description("output failed")
-            display("output failed")
-       }
-    }
-}
-
 pub fn build_sub_cli() -> Vec<App<'static, 'static>> {
     let mut subcommands: Vec<App<'static, 'static>> = vec![];
     // TODO: Yeah, this screams macro
@@ -38,7 +30,8 @@ pub fn build_sub_cli() -> Vec<App<'static, 'static>> {
                 .index(1)
                 .multiple(true)
                 .required(true)
-                .help("article id")),
+                .help("article id"))
+            .arg(queue::arg()),
     );
     subcommands.push(
         SubCommand::with_name(NAME_READD)
@@ -47,7 +40,8 @@ pub fn build_sub_cli() -> Vec<App<'static, 'static>> {
                 .index(1)
                 .multiple(true)
                 .required(true)
-                .help("article id")),
+                .help("article id"))
+            .arg(queue::arg()),
     );
     subcommands.push(
         SubCommand::with_name(NAME_FAVORITE)
@@ -56,7 +50,8 @@ pub fn build_sub_cli() -> Vec<App<'static, 'static>> {
                 .index(1)
                 .multiple(true)
                 .required(true)
-                .help("article id")),
+                .help("article id"))
+            .arg(queue::arg()),
     );
     subcommands.push(
         SubCommand::with_name(NAME_UNFAVORITE)
@@ -65,7 +60,8 @@ pub fn build_sub_cli() -> Vec<App<'static, 'static>> {
                 .index(1)
                 .multiple(true)
                 .required(true)
-                .help("article id")),
+                .help("article id"))
+            .arg(queue::arg()),
     );
     subcommands.push(
         SubCommand::with_name(NAME_DELETE)
@@ -74,26 +70,57 @@ pub fn build_sub_cli() -> Vec<App<'static, 'static>> {
             .index(1)
             .multiple(true)
             .required(true)
-            .help("article id")),
+            .help("article id"))
+            .arg(queue::arg()),
     );
     subcommands
 }
 
-pub fn call(action: &str, args: Option<&ArgMatches>, config: &Config) -> Result<()> {
+pub fn call(action: &str, args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
     let args = args.unwrap();
-    let ids = args.values_of("id").unwrap();
-    let actions: Vec<ActionRequest> = ids.map(|id| ActionRequest::new(action, id)).collect();
+    credentials::ensure_writable(config, MODULE_NAME).chain_err(|| format!("action '{}' failed to apply to Pocket article", action))?;
+    let ids = unique_ids(args.values_of("id").unwrap());
+    let actions: Vec<ActionRequest> = ids.into_iter().map(|id| ActionRequest::new(action, id)).collect();
+
+    if ctx.dry_run {
+        msgln(format!("Dry run: would send {} action for {} article(s).", action, actions.len()));
+        return Ok(());
+    }
 
     info(format!("Sending {} action for {} article(s) ...", action, actions.len()));
-    let json = client::send(config, &actions).chain_err(|| ErrorKind::PocketActionFailed(action.to_string()))?;
+    let mut progress = Progress::new(actions.len(), "articles", ctx.show_progress());
+    let result = client::send_batched(config, &actions, config.pocket.batch_size(), |n| progress.inc(n));
+    progress.finish();
+    let json = match result {
+        Err(ref e) if queue::is_requested(args, config) && e.is_offline() => return queue::push(config, &actions),
+        result => result.chain_err(|| format!("action '{}' failed to apply to Pocket article", action))?,
+    };
+
+    output(&json, &config.general.output_format)?;
+    check_failures(&json)
+}
+
+/// Drops repeated ids, keeping the order of the first occurrences, so every article gets exactly one result.
+fn unique_ids<'a, I: Iterator<Item = &'a str>>(ids: I) -> Vec<&'a str> {
+    let mut seen = HashSet::new();
+    ids.filter(|id| seen.insert(*id)).collect()
+}
 
-    output(&json, &config.general.output_format)
+fn check_failures(json: &str) -> Result<()> {
+    let result: ActionResults = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
+    let failed = result.action_results.iter().filter(|b| !**b).count();
+    if failed > 0 {
+        bail!(ErrorKind::PartialFailure(result.action_results.len() - failed, failed));
+    }
+
+    Ok(())
 }
 
 fn output(json: &str, format: &OutputFormat) -> Result<()> {
     match *format {
         OutputFormat::HUMAN => output_human(json),
-        OutputFormat::JSON => output::as_json(json).chain_err(|| ErrorKind::OutputFailed),
+        OutputFormat::JSON | OutputFormat::NDJSON => output::as_json(json).chain_err(|| ErrorKind::OutputFailed),
     }
 }
 
@@ -104,15 +131,67 @@ struct ActionResults {
 }
 
 fn output_human(json: &str) -> Result<()> {
+    msg(render_human(json)?);
+
+    Ok(())
+}
+
+fn render_human(json: &str) -> Result<String> {
     let result: ActionResults = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
 
-    if result.status == 1 {
-        msgln(format!("Received {} results.", result.action_results.len()));
+    let mut out = if result.status == 1 {
+        format!("Received {} results.\n", result.action_results.len())
     } else {
-        msgln("Action failed.");
-    }
+        "Action failed.\n".to_string()
+    };
     let successful: usize = result.action_results.iter().filter(|b| **b).collect::<Vec<_>>().len();
-    msgln(format!("{} action(s) successful, {} failed.", successful, result.action_results.len() - successful));
+    out.push_str(&format!("{} action(s) successful, {} failed.\n", successful, result.action_results.len() - successful));
 
-    Ok(())
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use modules::pocket::Pocket;
+    use net::mock::MockClient;
+    use testing::golden::*;
+    use testing::harness::*;
+
+    use std::rc::Rc;
+
+    #[test]
+    fn test_render_human() {
+        let json = fixture("pocket/actions.json");
+
+        assert_golden("pocket/actions.txt", &render_human(&json).unwrap());
+    }
+
+    #[test]
+    fn test_render_json() {
+        let json = fixture("pocket/actions.json");
+
+        assert_golden("pocket/actions.json", &output::render_json(&json, true).unwrap());
+    }
+
+    #[test]
+    fn test_unique_ids() {
+        let ids = vec!["3", "1", "3", "2", "1"];
+
+        assert_eq!(unique_ids(ids.into_iter()), vec!["3", "1", "2"]);
+    }
+
+    #[test]
+    fn test_archive_reports_partial_failure() {
+        let config = config("pocket-archive", "[pocket]\nconsumer_key = 'key'\naccess_token = 'token'");
+        let mock = Rc::new(MockClient::new().respond(200, &[], &fixture("pocket/actions.json")));
+
+        let result = run(&Pocket, &["archive", "1", "2", "3"], &Context::new(&config), &mock);
+
+        assert_eq!(result.unwrap_err().category(), Category::PartialFailure);
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("GET /v3/send?"));
+        assert!(requests[0].contains("%22action%22%3A%22archive%22"));
+    }
 }
@@ -0,0 +1,233 @@
+use super::{queue, request_auth};
+use super::client;
+use super::client::add::Request;
+use super::client::send::AddAction;
+use super::NAME as MODULE_NAME;
+use cache::responses::ResponseCache;
+use config::OutputFormat;
+use credentials;
+use errors::*;
+use modules::Context;
+use modules::bookmarks::{self, Bookmark};
+use net::concurrent;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::io;
+use std::io::{BufRead, Write};
+use tabwriter::TabWriter;
+
+pub const NAME: &'static str = "add";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Save articles")
+        .arg(Arg::with_name("tags")
+            .long("tags")
+            .short("t")
+            .takes_value(true)
+            .multiple(true)
+            .require_delimiter(true)
+            .help("Tags the articles, e.g., 'rust,cli'"))
+        .arg(Arg::with_name("title")
+            .long("title")
+            .takes_value(true)
+            .help("Sets the title if Pocket cannot determine it"))
+        .arg(Arg::with_name("url")
+            .index(1)
+            .multiple(true)
+            .help("URLs to save; reads one URL per line from stdin if none or '-' is given"))
+        .arg(bookmarks::add_arg())
+        .arg(queue::arg())
+}
+
+/// The result of adding one URL
+#[derive(Serialize, Debug)]
+struct Added {
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")] item_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")] title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")] error: Option<String>,
+    /// Pocket could not be reached and the URL is queued; cf. `rat flush`
+    #[serde(skip_serializing_if = "is_false")] queued: bool,
+}
+
+fn is_false(b: &bool) -> bool {
+    !*b
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let args = args.unwrap();
+
+    let stdin = io::stdin();
+    let urls = urls(args, stdin.lock())?;
+    if urls.is_empty() && !args.is_present("bookmarks") {
+        bail!("No URLs to add");
+    }
+    let tags: Vec<&str> = args.values_of("tags").map(|t| t.collect()).unwrap_or_default();
+    let bookmarks = bookmarks::to_add(args, urls, args.value_of("title"), &tags)?;
+
+    add(ctx, &bookmarks, queue::is_requested(args, ctx.config))
+}
+
+/// Transfers queue URLs only if setting 'queue_offline' is set; cf. `rat xfer`.
+pub fn add_bookmarks(ctx: &Context, bookmarks: &[Bookmark]) -> Result<()> {
+    add(ctx, bookmarks, ctx.config.pocket.queue_offline)
+}
+
+/// With `queue`, URLs that fail because Pocket cannot be reached are queued instead.
+fn add(ctx: &Context, bookmarks: &[Bookmark], queue: bool) -> Result<()> {
+    let config = ctx.config;
+    credentials::ensure_writable(config, MODULE_NAME).chain_err(|| "failed to add Pocket articles")?;
+
+    if ctx.dry_run {
+        msgln(format!("Dry run: would add {} article(s).", bookmarks.len()));
+        return Ok(());
+    }
+
+    info(format!("Adding {} article(s) ...", bookmarks.len()));
+    let auth = request_auth(config).chain_err(|| "failed to add Pocket articles")?;
+    let results = concurrent::map_bounded(bookmarks.to_vec(), config.general.max_concurrency(), move |bookmark| {
+        let tags = bookmark.tags.join(",");
+        let request = Request {
+            url: &bookmark.url,
+            title: if bookmark.title.is_empty() { None } else { Some(&bookmark.title) },
+            tags: if tags.is_empty() { None } else { Some(&tags) },
+            time: bookmark.time_added,
+        };
+        client::add(&auth, &request)
+    });
+    let mut offline = Vec::new();
+    let added: Vec<Added> = bookmarks.iter().zip(results).map(|(bookmark, result)| match result {
+        Ok(item) => Added { url: bookmark.url.clone(), item_id: Some(item.item_id), title: item.title, error: None, queued: false },
+        Err(ref e) if queue && e.is_offline() => {
+            offline.push(bookmark);
+            Added { url: bookmark.url.clone(), item_id: None, title: None, error: None, queued: true }
+        }
+        // The root cause is the most specific, e.g., the HTTP status
+        Err(e) => Added { url: bookmark.url.clone(), item_id: None, title: None, error: e.iter().last().map(|c| c.to_string()), queued: false },
+    }).collect();
+    if !offline.is_empty() {
+        let tags: Vec<String> = offline.iter().map(|b| b.tags.join(",")).collect();
+        let times: Vec<Option<String>> = offline.iter().map(|b| b.time_added.map(|t| t.to_string())).collect();
+        let actions: Vec<AddAction> = offline.iter().zip(&tags).zip(&times).map(|((b, tags), time)| AddAction::new(
+            &b.url,
+            if b.title.is_empty() { None } else { Some(&b.title) },
+            if tags.is_empty() { None } else { Some(tags) },
+            time.as_ref().map(|t| t.as_str()),
+        )).collect();
+        queue::push(config, &actions)?;
+    }
+
+    let failed = added.iter().filter(|a| a.error.is_some()).count();
+    if failed + offline.len() < added.len() {
+        // Listings must show the new articles
+        ResponseCache::new(config, MODULE_NAME).clear();
+    }
+    output(&added, &config.general.output_format)?;
+    if failed > 0 {
+        bail!(ErrorKind::PartialFailure(added.len() - failed, failed));
+    }
+
+    Ok(())
+}
+
+/// The URL arguments, or the URLs read from `stdin` without arguments or with the single argument '-'
+fn urls<R: BufRead>(args: &ArgMatches, stdin: R) -> Result<Vec<String>> {
+    let urls: Vec<&str> = args.values_of("url").map(|urls| urls.collect()).unwrap_or_default();
+    match urls.as_slice() {
+        [] if args.is_present("bookmarks") => Ok(Vec::new()),
+        [] | ["-"] => read_urls(stdin),
+        _ => Ok(urls.iter().map(|url| url.to_string()).collect()),
+    }
+}
+
+/// Skips blank lines
+fn read_urls<R: BufRead>(reader: R) -> Result<Vec<String>> {
+    let mut urls = Vec::new();
+    for line in reader.lines() {
+        let line = line.chain_err(|| "Failed to read URLs from stdin")?;
+        let url = line.trim();
+        if !url.is_empty() {
+            urls.push(url.to_string());
+        }
+    }
+
+    Ok(urls)
+}
+
+fn output(added: &[Added], format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            msg(render_human(added)?);
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(added).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(added: &[Added]) -> Result<String> {
+    let mut tw = TabWriter::new(vec![]);
+    for a in added {
+        match (&a.item_id, &a.error) {
+            (&None, &None) if a.queued => writeln!(&mut tw, "queued\t{}\tPocket unreachable", a.url),
+            (&Some(ref item_id), _) =>
+                writeln!(&mut tw, "added\t{}\t{}", item_id, match a.title {
+                    Some(ref title) if !title.is_empty() => title,
+                    _ => &a.url,
+                }),
+            (&None, error) =>
+                writeln!(&mut tw, "failed\t{}\t{}", a.url, error.as_ref().map(|e| e.as_str()).unwrap_or("")),
+        }.chain_err(|| ErrorKind::OutputFailed)?;
+    }
+    tw.flush().chain_err(|| ErrorKind::OutputFailed)?;
+    let out = String::from_utf8(tw.into_inner().unwrap()).chain_err(|| ErrorKind::OutputFailed)?;
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_read_urls() {
+        let input = "https://example.com/a\n\n  https://example.com/b  \n";
+
+        let urls = read_urls(input.as_bytes()).unwrap();
+
+        assert_eq!(urls, vec!["https://example.com/a", "https://example.com/b"]);
+    }
+
+    #[test]
+    fn test_urls_from_stdin_for_dash() {
+        let app = build_sub_cli();
+        let stdin = "https://example.com/a\n";
+
+        let dash = urls(&app.clone().get_matches_from(vec!["add", "-"]), stdin.as_bytes()).unwrap();
+        let none = urls(&app.clone().get_matches_from(vec!["add"]), stdin.as_bytes()).unwrap();
+        let given = urls(&app.get_matches_from(vec!["add", "https://example.com/b"]), stdin.as_bytes()).unwrap();
+
+        assert_eq!(dash, vec!["https://example.com/a"]);
+        assert_eq!(none, vec!["https://example.com/a"]);
+        assert_eq!(given, vec!["https://example.com/b"]);
+    }
+
+    #[test]
+    fn test_render_human() {
+        let added = vec![
+            Added { url: "https://example.com/a".to_string(), item_id: Some("1".to_string()), title: Some("A".to_string()), error: None, queued: false },
+            Added { url: "https://example.com/b".to_string(), item_id: None, title: None, error: Some("timeout".to_string()), queued: false },
+            Added { url: "https://example.com/c".to_string(), item_id: None, title: None, error: None, queued: true },
+        ];
+
+        let out = render_human(&added).unwrap();
+
+        assert_eq!(out, "added   1                      A\nfailed  https://example.com/b  timeout\nqueued  https://example.com/c  Pocket unreachable\n");
+    }
+}
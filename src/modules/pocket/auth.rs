@@ -1,19 +1,11 @@
 use super::client;
-use config::Config;
+use errors::*;
+use modules::Context;
 
 use clap::{App, Arg, ArgMatches, SubCommand};
 
 pub const NAME: &'static str = "auth";
 
-error_chain! {
-    errors {
-       PocketAuthFailed {
-            description("failed to authenticate with Pocket")
-            display("failed to authenticate with Pocket")
-        }
-    }
-}
-
 
 pub fn build_sub_cli() -> App<'static, 'static> {
     SubCommand::with_name(NAME)
@@ -21,9 +13,14 @@ pub fn build_sub_cli() -> App<'static, 'static> {
         .arg(Arg::with_name("browser")
             .long("browser")
             .help("Open authentication page in default web browser"))
+        .arg(Arg::with_name("manual")
+            .long("manual")
+            .help("Confirm authentication by pressing return instead of receiving the redirect locally"))
 }
 
-pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
     let open_browser = args.ok_or(false).unwrap().is_present("browser");
-    client::auth(config, open_browser).chain_err(|| ErrorKind::PocketAuthFailed)
+    let manual = args.ok_or(false).unwrap().is_present("manual");
+    client::auth(config, open_browser, manual).chain_err(|| "failed to authenticate with Pocket")
 }
@@ -0,0 +1,533 @@
+use super::client;
+use super::client::list::{Article, DetailType, Request, Sort};
+use super::NAME as MODULE_NAME;
+use credentials;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{self, Value};
+use std::cmp;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use webbrowser;
+
+pub const NAME: &'static str = "browse";
+
+const HELP: &'static str = "j/k move  / search  t tag  o open  a archive  f favorite  d delete  e tags  q apply and quit  Q discard";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Browse articles in a full-screen terminal UI; changes are sent to Pocket on quit")
+        .arg(Arg::with_name("state")
+            .long("state")
+            .short("s")
+            .takes_value(true)
+            .possible_values(&["unread", "archive", "all"])
+            .default_value("unread")
+            .help("Select articles to browse"))
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    if !is_terminal(0) || !is_terminal(1) {
+        bail!("'pocket browse' needs a terminal; use 'pocket list' in scripts");
+    }
+
+    let request = Request {
+        state: Some(args.value_of("state").unwrap().into()),
+        tag: None,
+        sort: Some(Sort::newest),
+        detailType: DetailType::complete,
+        search: None,
+        since: None,
+        favorite: None,
+        count: None,
+        offset: None,
+    };
+    let mut items = Vec::new();
+    client::list::list_streamed(config, &request, None, None, |article| {
+        if !article.is_deleted() {
+            items.push(Item::from(article));
+        }
+        Ok(())
+    }).chain_err(|| "failed to browse Pocket articles")?;
+
+    let mut browser = Browser::new(items);
+    {
+        let terminal = Terminal::enter().chain_err(|| "Failed to set up terminal")?;
+        run(&mut browser, &terminal).chain_err(|| "Failed to run terminal UI")?;
+    }
+    if !browser.apply {
+        msgln("Discarded all changes.");
+        return Ok(());
+    }
+
+    let actions = browser.actions();
+    if actions.is_empty() {
+        return Ok(());
+    }
+    credentials::ensure_writable(config, MODULE_NAME).chain_err(|| "failed to apply changes to Pocket articles")?;
+    if ctx.dry_run {
+        msgln(format!("Dry run: would send {} action(s).", actions.len()));
+        return Ok(());
+    }
+
+    info(format!("Sending {} action(s) ...", actions.len()));
+    let json = client::send_batched(config, &actions, config.pocket.batch_size(), |_| {})
+        .chain_err(|| "failed to apply changes to Pocket articles")?;
+    let results: Value = serde_json::from_str(&json).chain_err(|| "JSON parsing failed")?;
+    let results = results.get("action_results").and_then(|r| r.as_array()).cloned().unwrap_or_default();
+    let failed = results.iter().filter(|r| **r == Value::Bool(false)).count();
+    msgln(format!("{} action(s) successful, {} failed.", results.len() - failed, failed));
+    if failed > 0 {
+        bail!(ErrorKind::PartialFailure(results.len() - failed, failed));
+    }
+
+    Ok(())
+}
+
+fn run(browser: &mut Browser, terminal: &Terminal) -> io::Result<()> {
+    let stdin = io::stdin();
+    let mut stdin = stdin.lock();
+    let mut buffer = [0u8; 32];
+    loop {
+        let (rows, columns) = terminal.size();
+        terminal.draw(&browser.render(rows, columns))?;
+        let read = stdin.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(());
+        }
+        for key in parse_keys(&buffer[..read]) {
+            match browser.handle(key) {
+                Step::Continue => {}
+                Step::Open(url) => if let Err(e) = webbrowser::open(&url) {
+                    browser.status = format!("Failed to open browser: {}", e);
+                },
+                Step::Quit => return Ok(()),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Key {
+    Char(char),
+    Up,
+    Down,
+    Enter,
+    Backspace,
+    Esc,
+    CtrlC,
+}
+
+/// Keys of one read from the terminal; escape sequences of a key arrive in a single read.
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    match bytes {
+        b"\x1b[A" | b"\x1bOA" => return vec![Key::Up],
+        b"\x1b[B" | b"\x1bOB" => return vec![Key::Down],
+        b"\x1b" => return vec![Key::Esc],
+        _ if bytes.first() == Some(&0x1b) => return Vec::new(),
+        _ => {}
+    }
+    String::from_utf8_lossy(bytes).chars().map(|c| match c {
+        '\r' | '\n' => Key::Enter,
+        '\x7f' | '\x08' => Key::Backspace,
+        '\x03' => Key::CtrlC,
+        c => Key::Char(c),
+    }).collect()
+}
+
+/// An article and the changes to send on quit
+#[derive(Debug)]
+struct Item {
+    id: String,
+    title: String,
+    url: String,
+    tags: Vec<String>,
+    favorite: bool,
+    archived: bool,
+    deleted: bool,
+    original_tags: Vec<String>,
+    original_favorite: bool,
+    original_archived: bool,
+}
+
+impl From<Article> for Item {
+    fn from(article: Article) -> Self {
+        let tags: Vec<String> = article.tag_names().into_iter().map(|t| t.to_string()).collect();
+        let title = article.title().to_string();
+        let url = article.url().to_string();
+        Item {
+            id: article.item_id,
+            title: title,
+            url: url,
+            original_tags: tags.clone(),
+            tags: tags,
+            favorite: article.favorite == "1",
+            archived: article.status == "1",
+            deleted: false,
+            original_favorite: article.favorite == "1",
+            original_archived: article.status == "1",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Mode {
+    Normal,
+    Search,
+    Tag(String),
+    EditTags(String),
+}
+
+#[derive(Debug, PartialEq)]
+enum Step {
+    Continue,
+    Open(String),
+    Quit,
+}
+
+struct Browser {
+    items: Vec<Item>,
+    query: String,
+    tag: Option<String>,
+    /// Index into the visible items
+    selected: usize,
+    mode: Mode,
+    status: String,
+    /// Whether the changes are sent on quit
+    apply: bool,
+}
+
+impl Browser {
+    fn new(items: Vec<Item>) -> Self {
+        Browser { items: items, query: String::new(), tag: None, selected: 0, mode: Mode::Normal, status: String::new(), apply: false }
+    }
+
+    /// Indexes of the items matching the search and the tag filter
+    fn visible(&self) -> Vec<usize> {
+        let query = self.query.to_lowercase();
+        self.items.iter().enumerate()
+            .filter(|&(_, item)| self.tag.as_ref().map(|tag| item.tags.contains(tag)).unwrap_or(true))
+            .filter(|&(_, item)| is_fuzzy_match(&query, &format!("{} {}", item.title, item.url).to_lowercase()))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    fn selected_item(&mut self) -> Option<&mut Item> {
+        let visible = self.visible();
+        match visible.get(self.selected) {
+            Some(&i) => Some(&mut self.items[i]),
+            None => None,
+        }
+    }
+
+    fn handle(&mut self, key: Key) -> Step {
+        self.status.clear();
+        let mode = ::std::mem::replace(&mut self.mode, Mode::Normal);
+        match mode {
+            Mode::Normal => return self.handle_normal(key),
+            Mode::Search => match key {
+                Key::Enter => {}
+                Key::Esc => self.query.clear(),
+                Key::Backspace => {
+                    self.query.pop();
+                    self.mode = Mode::Search;
+                }
+                Key::Char(c) => {
+                    self.query.push(c);
+                    self.mode = Mode::Search;
+                }
+                _ => self.mode = Mode::Search,
+            },
+            Mode::Tag(mut input) => match key {
+                Key::Enter => self.tag = if input.trim().is_empty() { None } else { Some(input.trim().to_string()) },
+                Key::Esc => {}
+                Key::Backspace => {
+                    input.pop();
+                    self.mode = Mode::Tag(input);
+                }
+                Key::Char(c) => {
+                    input.push(c);
+                    self.mode = Mode::Tag(input);
+                }
+                _ => self.mode = Mode::Tag(input),
+            },
+            Mode::EditTags(mut input) => match key {
+                Key::Enter => if let Some(item) = self.selected_item() {
+                    item.tags = input.split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect();
+                    item.tags.sort();
+                    item.tags.dedup();
+                },
+                Key::Esc => {}
+                Key::Backspace => {
+                    input.pop();
+                    self.mode = Mode::EditTags(input);
+                }
+                Key::Char(c) => {
+                    input.push(c);
+                    self.mode = Mode::EditTags(input);
+                }
+                _ => self.mode = Mode::EditTags(input),
+            },
+        }
+        self.selected = cmp::min(self.selected, self.visible().len().saturating_sub(1));
+
+        Step::Continue
+    }
+
+    fn handle_normal(&mut self, key: Key) -> Step {
+        let count = self.visible().len();
+        match key {
+            Key::Char('j') | Key::Down => self.selected = cmp::min(self.selected + 1, count.saturating_sub(1)),
+            Key::Char('k') | Key::Up => self.selected = self.selected.saturating_sub(1),
+            Key::Char('g') => self.selected = 0,
+            Key::Char('G') => self.selected = count.saturating_sub(1),
+            Key::Char('/') => self.mode = Mode::Search,
+            Key::Char('t') => self.mode = Mode::Tag(self.tag.clone().unwrap_or_default()),
+            Key::Esc => {
+                self.query.clear();
+                self.tag = None;
+                self.selected = 0;
+            }
+            Key::Char('o') | Key::Enter => if let Some(item) = self.selected_item() {
+                return Step::Open(item.url.clone());
+            },
+            Key::Char('a') => if let Some(item) = self.selected_item() {
+                item.archived = !item.archived;
+            },
+            Key::Char('f') => if let Some(item) = self.selected_item() {
+                item.favorite = !item.favorite;
+            },
+            Key::Char('d') => if let Some(item) = self.selected_item() {
+                item.deleted = !item.deleted;
+            },
+            Key::Char('e') => if let Some(tags) = self.selected_item().map(|item| item.tags.join(",")) {
+                self.mode = Mode::EditTags(tags);
+            },
+            Key::Char('q') => {
+                self.apply = true;
+                return Step::Quit;
+            }
+            Key::Char('Q') | Key::CtrlC => return Step::Quit,
+            _ => {}
+        }
+
+        Step::Continue
+    }
+
+    /// The actions for all changed items; deleted items are only deleted.
+    fn actions(&self) -> Vec<Value> {
+        let mut actions = Vec::new();
+        let mut push = |action: &str, item: &Item, tags: Option<String>| {
+            let mut value = json_object(&[("action", action), ("item_id", &item.id)]);
+            if let (Some(tags), Some(map)) = (tags, value.as_object_mut()) {
+                map.insert("tags".to_string(), Value::String(tags));
+            }
+            actions.push(value);
+        };
+        for item in &self.items {
+            if item.deleted {
+                push("delete", item, None);
+                continue;
+            }
+            if item.archived != item.original_archived {
+                push(if item.archived { "archive" } else { "readd" }, item, None);
+            }
+            if item.favorite != item.original_favorite {
+                push(if item.favorite { "favorite" } else { "unfavorite" }, item, None);
+            }
+            if item.tags != item.original_tags {
+                if item.tags.is_empty() {
+                    push("tags_clear", item, None);
+                } else {
+                    push("tags_replace", item, Some(item.tags.join(",")));
+                }
+            }
+        }
+
+        actions
+    }
+
+    fn render(&self, rows: usize, columns: usize) -> String {
+        let visible = self.visible();
+        let list_rows = rows.saturating_sub(2);
+        let first = if self.selected >= list_rows { self.selected + 1 - list_rows } else { 0 };
+
+        let mut header = format!("rat pocket browse -- {} of {} article(s)", visible.len(), self.items.len());
+        if !self.query.is_empty() {
+            header.push_str(&format!(", search '{}'", self.query));
+        }
+        if let Some(ref tag) = self.tag {
+            header.push_str(&format!(", tag '{}'", tag));
+        }
+        let mut out = format!("\x1b[H\x1b[2J\x1b[1m{}\x1b[0m\r\n", truncate(&header, columns));
+        for (row, &i) in visible.iter().enumerate().skip(first).take(list_rows) {
+            let item = &self.items[i];
+            let markers = format!("{}{}{}",
+                                  if item.deleted { 'D' } else if item.archived { 'A' } else { ' ' },
+                                  if item.favorite { '*' } else { ' ' },
+                                  if item.tags != item.original_tags { '~' } else { ' ' });
+            let tags = if item.tags.is_empty() { String::new() } else { format!(" [{}]", item.tags.join(",")) };
+            let line = truncate(&format!("{} {}{}", markers, item.title, tags), columns);
+            if row == self.selected {
+                out.push_str(&format!("\x1b[7m{}\x1b[0m\r\n", line));
+            } else {
+                out.push_str(&format!("{}\r\n", line));
+            }
+        }
+        let footer = match self.mode {
+            Mode::Normal if !self.status.is_empty() => self.status.clone(),
+            Mode::Normal => HELP.to_string(),
+            Mode::Search => format!("/{}", self.query),
+            Mode::Tag(ref input) => format!("tag: {}", input),
+            Mode::EditTags(ref input) => format!("tags: {}", input),
+        };
+        out.push_str(&format!("\x1b[{};1H{}", rows, truncate(&footer, columns)));
+
+        out
+    }
+}
+
+fn json_object(fields: &[(&str, &str)]) -> Value {
+    Value::Object(fields.iter().map(|&(k, v)| (k.to_string(), Value::String(v.to_string()))).collect())
+}
+
+/// Whether all characters of `query` appear in `text` in the same order
+fn is_fuzzy_match(query: &str, text: &str) -> bool {
+    let mut chars = text.chars();
+    query.chars().all(|q| chars.any(|c| c == q))
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+/// Raw mode and the alternate screen of the terminal while browsing; the previous settings are restored on drop.
+struct Terminal {
+    settings: String,
+}
+
+impl Terminal {
+    fn enter() -> Result<Self> {
+        let settings = stty(&["-g"])?;
+        stty(&["raw", "-echo"])?;
+        let terminal = Terminal { settings: settings.trim().to_string() };
+        terminal.draw("\x1b[?1049h\x1b[?25l").chain_err(|| "Failed to switch to the alternate screen")?;
+
+        Ok(terminal)
+    }
+
+    /// Rows and columns; 24x80 if unknown
+    fn size(&self) -> (usize, usize) {
+        stty(&["size"]).ok()
+            .and_then(|size| {
+                let mut size = size.split_whitespace().map(|n| n.parse::<usize>().ok());
+                match (size.next(), size.next()) {
+                    (Some(Some(rows)), Some(Some(columns))) => Some((rows, columns)),
+                    _ => None,
+                }
+            })
+            .unwrap_or((24, 80))
+    }
+
+    fn draw(&self, screen: &str) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        stdout.write_all(screen.as_bytes())?;
+        stdout.flush()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = self.draw("\x1b[?25h\x1b[?1049l");
+        let _ = stty(&[&self.settings]);
+    }
+}
+
+fn stty(args: &[&str]) -> Result<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).output()
+        .chain_err(|| "Failed to run 'stty'")?;
+    if !output.status.success() {
+        bail!("'stty {}' failed", args.join(" "));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn item(id: &str, title: &str, tags: &[&str]) -> Item {
+        let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+        Item {
+            id: id.to_string(),
+            title: title.to_string(),
+            url: format!("https://example.com/{}", id),
+            original_tags: tags.clone(),
+            tags: tags,
+            favorite: false,
+            archived: false,
+            deleted: false,
+            original_favorite: false,
+            original_archived: false,
+        }
+    }
+
+    fn browser() -> Browser {
+        Browser::new(vec![item("1", "Rust 1.20 released", &["rust"]), item("2", "Go modules", &[]), item("3", "Rust in production", &[])])
+    }
+
+    fn keys(browser: &mut Browser, keys: &str) {
+        for c in keys.chars() {
+            browser.handle(if c == '\n' { Key::Enter } else { Key::Char(c) });
+        }
+    }
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(parse_keys(b"\x1b[A"), vec![Key::Up]);
+        assert_eq!(parse_keys(b"\x1b"), vec![Key::Esc]);
+        assert_eq!(parse_keys(b"j\r\x7f"), vec![Key::Char('j'), Key::Enter, Key::Backspace]);
+    }
+
+    #[test]
+    fn test_fuzzy_search_and_tag_filter() {
+        let mut browser = browser();
+
+        keys(&mut browser, "/rstprd\n");
+        assert_eq!(browser.visible(), vec![2]);
+
+        browser.handle(Key::Esc);
+        keys(&mut browser, "trust\n");
+        assert_eq!(browser.visible(), vec![0]);
+    }
+
+    #[test]
+    fn test_actions() {
+        let mut browser = browser();
+
+        keys(&mut browser, "afjdjecli, rust\n");
+        let actions = serde_json::to_string(&browser.actions()).unwrap();
+
+        assert_eq!(actions, r#"[{"action":"archive","item_id":"1"},{"action":"favorite","item_id":"1"},{"action":"delete","item_id":"2"},{"action":"tags_replace","item_id":"3","tags":"cli,rust"}]"#);
+        assert_eq!(browser.handle(Key::Char('q')), Step::Quit);
+        assert!(browser.apply);
+    }
+
+    #[test]
+    fn test_render() {
+        let mut browser = browser();
+        keys(&mut browser, "jf");
+
+        let screen = browser.render(5, 40);
+
+        assert!(screen.contains("rat pocket browse -- 3 of 3 article(s)"));
+        assert!(screen.contains("\x1b[7m *  Go modules\x1b[0m\r\n"));
+        assert!(screen.ends_with("\x1b[5;1Hj/k move  / search  t tag  o open  a arc"));
+    }
+}
@@ -0,0 +1,265 @@
+use super::{client, fetch_library};
+use super::client::list::{Article, State};
+use super::client::send::ActionRequest;
+use super::NAME as MODULE_NAME;
+use config::OutputFormat;
+use credentials;
+use errors::*;
+use modules::Context;
+use net::concurrent;
+use net::http;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use hyper::client::RedirectPolicy;
+use hyper::header::{Connection, Location};
+use hyper::status::StatusCode;
+use hyper::Url;
+use serde_json;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use tabwriter::TabWriter;
+
+pub const NAME: &'static str = "check";
+
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Check the links of saved articles and report dead and redirected ones")
+        .arg(Arg::with_name("state")
+            .long("state")
+            .short("s")
+            .takes_value(true)
+            .possible_values(&["unread", "archive", "all"])
+            .default_value("all")
+            .help("Select articles to check"))
+        .arg(Arg::with_name("archive-dead")
+            .long("archive-dead")
+            .conflicts_with("delete-dead")
+            .help("Archives articles whose links are gone, i.e., answer 404 or 410"))
+        .arg(Arg::with_name("delete-dead")
+            .long("delete-dead")
+            .help("Deletes articles whose links are gone, i.e., answer 404 or 410"))
+}
+
+/// What checking a link found; links that answer successfully are not reported.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum Finding {
+    Ok,
+    /// 404 Not Found or 410 Gone
+    Dead { status: u16 },
+    Redirected { status: u16, location: String },
+    /// Any other unsuccessful status, e.g., 403 or 503, which may well be temporary
+    Failed { status: u16 },
+    Unreachable { reason: String },
+}
+
+#[derive(Serialize, Debug)]
+struct Problem {
+    item_id: String,
+    title: String,
+    url: String,
+    finding: Finding,
+}
+
+#[derive(Serialize, Debug)]
+struct CheckResult {
+    checked: usize,
+    problems: Vec<Problem>,
+    /// Action result per dead article if dead articles have been archived or deleted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    action_results: Option<Vec<bool>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ActionResults {
+    action_results: Vec<bool>,
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    let state: State = args.value_of("state").unwrap().into();
+    let action = if args.is_present("archive-dead") {
+        Some("archive")
+    } else if args.is_present("delete-dead") {
+        Some("delete")
+    } else {
+        None
+    };
+    if action.is_some() {
+        credentials::ensure_writable(config, MODULE_NAME).chain_err(|| "failed to check Pocket articles")?;
+    }
+
+    let articles: Vec<Article> = fetch_library(config, state, false).chain_err(|| "failed to check Pocket articles")?
+        .into_iter()
+        .filter(|article| !article.url().is_empty())
+        .collect();
+
+    info(format!("Checking links of {} article(s) ...", articles.len()));
+    let checked = articles.len();
+    let progress = Arc::new(Mutex::new(Progress::new(checked, "links", ctx.show_progress())));
+    let findings = {
+        let progress = progress.clone();
+        let urls = articles.iter().map(|a| a.url().to_string()).collect();
+        concurrent::map_bounded(urls, config.general.max_concurrency(), move |url: String| {
+            let finding = check(&url);
+            progress.lock().map(|mut p| p.inc(1)).unwrap_or(());
+            finding
+        })
+    };
+    progress.lock().map(|p| p.finish()).unwrap_or(());
+
+    let problems: Vec<Problem> = articles.into_iter().zip(findings)
+        .filter(|&(_, ref finding)| *finding != Finding::Ok)
+        .map(|(a, finding)| Problem { title: a.title().to_string(), url: a.url().to_string(), item_id: a.item_id, finding: finding })
+        .collect();
+    let mut result = CheckResult { checked: checked, problems: problems, action_results: None };
+
+    let dead: Vec<&str> = result.problems.iter()
+        .filter(|p| match p.finding { Finding::Dead { .. } => true, _ => false })
+        .map(|p| p.item_id.as_str())
+        .collect();
+    if let (Some(action), false) = (action, dead.is_empty()) {
+        if ctx.dry_run {
+            msgln(format!("Dry run: would send {} action for {} dead article(s).", action, dead.len()));
+        } else {
+            info(format!("Sending {} action for {} dead article(s) ...", action, dead.len()));
+            let actions: Vec<ActionRequest> = dead.iter().map(|id| ActionRequest::new(action, id)).collect();
+            let mut progress = Progress::new(actions.len(), "articles", ctx.show_progress());
+            let sent = client::send_batched(config, &actions, config.pocket.batch_size(), |n| progress.inc(n));
+            progress.finish();
+            let json = sent.chain_err(|| format!("action '{}' failed to apply to dead Pocket articles", action))?;
+            let sent: ActionResults = serde_json::from_str(&json).chain_err(|| "JSON parsing failed")?;
+            result.action_results = Some(sent.action_results);
+        }
+    }
+
+    output(&result, action, &config.general.output_format)?;
+    if let Some(ref results) = result.action_results {
+        let failed = results.iter().filter(|r| !**r).count();
+        if failed > 0 {
+            bail!(ErrorKind::PartialFailure(results.len() - failed, failed));
+        }
+    }
+
+    Ok(())
+}
+
+/// Asks with HEAD first, and with GET if the server does not support HEAD or fails to answer it. Redirects are not
+/// followed, so they can be reported.
+fn check(url: &str) -> Finding {
+    match request(url, true) {
+        Ok((StatusCode::MethodNotAllowed, _)) | Ok((StatusCode::NotImplemented, _)) | Err(_) => {}
+        Ok((status, location)) => return classify(url, status, location),
+    }
+    match request(url, false) {
+        Ok((status, location)) => classify(url, status, location),
+        Err(e) => Finding::Unreachable { reason: e.to_string() },
+    }
+}
+
+fn request(url: &str, head: bool) -> Result<(StatusCode, Option<String>)> {
+    let mut client = http::tls_client().chain_err(|| "Could not create TLS client")?;
+    client.set_redirect_policy(RedirectPolicy::FollowNone);
+    let response = if head { client.head(url) } else { client.get(url) }
+        .header(Connection::close())
+        .send()
+        .chain_err(|| ErrorKind::Network(format!("could not fetch '{}'", url)))?;
+    let location = response.headers.get::<Location>().map(|l| l.to_string());
+
+    Ok((response.status, location))
+}
+
+fn classify(url: &str, status: StatusCode, location: Option<String>) -> Finding {
+    let code = status.to_u16();
+    match code {
+        _ if status.is_success() => Finding::Ok,
+        404 | 410 => Finding::Dead { status: code },
+        _ if status.is_redirection() && location.is_some() => {
+            let location = location.unwrap();
+            // Location may be relative to the URL
+            let location = Url::parse(url).and_then(|url| url.join(&location)).map(|l| l.to_string()).unwrap_or(location);
+            Finding::Redirected { status: code, location: location }
+        }
+        _ => Finding::Failed { status: code },
+    }
+}
+
+fn output(result: &CheckResult, action: Option<&str>, format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            let stdout = io::stdout();
+            let mut out = stdout.lock();
+            out.write_all(render_human(result, action).as_bytes()).chain_err(|| ErrorKind::OutputFailed)
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(result).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(result: &CheckResult, action: Option<&str>) -> String {
+    let mut tw = TabWriter::new(vec![]);
+    let (mut dead, mut redirected, mut other) = (0, 0, 0);
+    for p in &result.problems {
+        let finding = match p.finding {
+            Finding::Dead { status } => { dead += 1; format!("dead ({})", status) }
+            Finding::Redirected { status, ref location } => { redirected += 1; format!("redirected ({}) to {}", status, location) }
+            Finding::Failed { status } => { other += 1; format!("failed ({})", status) }
+            Finding::Unreachable { ref reason } => { other += 1; format!("unreachable: {}", reason) }
+            Finding::Ok => continue,
+        };
+        let _ = writeln!(&mut tw, "* {}:\t'{}' {}\t{}", p.item_id, p.title, p.url, finding);
+    }
+    let _ = writeln!(&mut tw, "Checked {} link(s): {} dead, {} redirected, {} failed or unreachable.",
+                     result.checked, dead, redirected, other);
+    if let (Some(action), Some(ref results)) = (action, result.action_results.as_ref()) {
+        let successful = results.iter().filter(|r| **r).count();
+        let _ = writeln!(&mut tw, "{} action successful for {} of {} dead article(s).", action, successful, results.len());
+    }
+    let _ = tw.flush();
+
+    String::from_utf8(tw.into_inner().unwrap_or_default()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let url = "https://example.com/blog/post";
+
+        assert_eq!(classify(url, StatusCode::Ok, None), Finding::Ok);
+        assert_eq!(classify(url, StatusCode::Gone, None), Finding::Dead { status: 410 });
+        assert_eq!(classify(url, StatusCode::MovedPermanently, Some("/posts/1".to_string())),
+                   Finding::Redirected { status: 301, location: "https://example.com/posts/1".to_string() });
+        assert_eq!(classify(url, StatusCode::Found, None), Finding::Failed { status: 302 });
+        assert_eq!(classify(url, StatusCode::ServiceUnavailable, None), Finding::Failed { status: 503 });
+    }
+
+    #[test]
+    fn test_render_human() {
+        let result = CheckResult {
+            checked: 3,
+            problems: vec![
+                Problem { item_id: "1".to_string(), title: "Gone".to_string(), url: "https://example.com/gone".to_string(),
+                          finding: Finding::Dead { status: 404 } },
+                Problem { item_id: "22".to_string(), title: "Moved".to_string(), url: "http://example.com".to_string(),
+                          finding: Finding::Redirected { status: 301, location: "https://example.com/".to_string() } },
+            ],
+            action_results: Some(vec![true]),
+        };
+
+        assert_eq!(render_human(&result, Some("archive")), "\
+* 1:   'Gone' https://example.com/gone  dead (404)
+* 22:  'Moved' http://example.com       redirected (301) to https://example.com/
+Checked 3 link(s): 1 dead, 1 redirected, 0 failed or unreachable.
+archive action successful for 1 of 1 dead article(s).
+");
+    }
+}
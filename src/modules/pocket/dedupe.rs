@@ -0,0 +1,222 @@
+use super::{client, fetch_library};
+use super::client::list::{Article, State};
+use super::client::send::ActionRequest;
+use super::NAME as MODULE_NAME;
+use config::OutputFormat;
+use credentials;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use tabwriter::TabWriter;
+
+pub const NAME: &'static str = "dedupe";
+
+/// Query parameters that only track where a link was found; `utm_*` parameters are matched by prefix.
+static TRACKING_PARAMETERS: &'static [&'static str] = &[
+    "_hsenc", "_hsmi", "dclid", "fbclid", "gclid", "igshid", "mc_cid", "mc_eid", "msclkid", "yclid",
+];
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Find articles saved more than once and delete the duplicates")
+        .arg(Arg::with_name("keep")
+            .long("keep")
+            .takes_value(true)
+            .possible_values(&["oldest", "newest"])
+            .default_value("oldest")
+            .help("Select the copy to keep of each duplicate article"))
+        .arg(Arg::with_name("delete")
+            .long("delete")
+            .help("Deletes all copies but the kept one; only lists the duplicates otherwise"))
+}
+
+/// Articles with the same canonical URL
+#[derive(Serialize, Debug, PartialEq)]
+struct Group {
+    url: String,
+    keep: String,
+    duplicates: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct DedupeResult {
+    groups: Vec<Group>,
+    /// Action result per duplicate if duplicates have been deleted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    action_results: Option<Vec<bool>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ActionResults {
+    action_results: Vec<bool>,
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    let keep_newest = args.value_of("keep") == Some("newest");
+    let delete = args.is_present("delete");
+    if delete {
+        credentials::ensure_writable(config, MODULE_NAME).chain_err(|| "failed to dedupe Pocket articles")?;
+    }
+
+    let articles: Vec<Article> = fetch_library(config, State::all, false).chain_err(|| "failed to dedupe Pocket articles")?
+        .into_iter()
+        .filter(|article| !article.url().is_empty())
+        .collect();
+
+    let groups = group(&articles, keep_newest);
+    let mut result = DedupeResult { groups: groups, action_results: None };
+    let duplicates: Vec<&str> = result.groups.iter().flat_map(|g| g.duplicates.iter().map(|id| id.as_str())).collect();
+    if delete && !duplicates.is_empty() {
+        if ctx.dry_run {
+            msgln(format!("Dry run: would delete {} duplicate article(s).", duplicates.len()));
+        } else {
+            info(format!("Deleting {} duplicate article(s) ...", duplicates.len()));
+            let actions: Vec<ActionRequest> = duplicates.iter().map(|id| ActionRequest::new("delete", id)).collect();
+            let mut progress = Progress::new(actions.len(), "articles", ctx.show_progress());
+            let sent = client::send_batched(config, &actions, config.pocket.batch_size(), |n| progress.inc(n));
+            progress.finish();
+            let json = sent.chain_err(|| "action 'delete' failed to apply to duplicate Pocket articles")?;
+            let sent: ActionResults = serde_json::from_str(&json).chain_err(|| "JSON parsing failed")?;
+            result.action_results = Some(sent.action_results);
+        }
+    }
+
+    output(&result, &articles, &config.general.output_format)?;
+    if let Some(ref results) = result.action_results {
+        let failed = results.iter().filter(|r| !**r).count();
+        if failed > 0 {
+            bail!(ErrorKind::PartialFailure(results.len() - failed, failed));
+        }
+    }
+
+    Ok(())
+}
+
+/// Groups the articles by canonical URL and keeps the oldest or newest article of each group; articles whose time
+/// added cannot be parsed count as oldest.
+fn group(articles: &[Article], keep_newest: bool) -> Vec<Group> {
+    let mut by_url: BTreeMap<String, Vec<&Article>> = BTreeMap::new();
+    for a in articles {
+        by_url.entry(canonical_url(a.url())).or_insert_with(Vec::new).push(a);
+    }
+
+    by_url.into_iter()
+        .filter(|&(_, ref copies)| copies.len() > 1)
+        .map(|(url, mut copies)| {
+            copies.sort_by_key(|a| (a.time_added().ok(), a.item_id.clone()));
+            if keep_newest {
+                copies.reverse();
+            }
+            let keep = copies.remove(0).item_id.clone();
+            Group { url: url, keep: keep, duplicates: copies.iter().map(|a| a.item_id.clone()).collect() }
+        })
+        .collect()
+}
+
+/// URL without scheme, 'www.', fragment, tracking parameters, and trailing slashes, with the host in lower case, so
+/// copies of the same article saved from different places compare equal.
+fn canonical_url(url: &str) -> String {
+    let url = url.trim();
+    let url = url.splitn(2, "://").last().unwrap_or(url);
+    let url = url.splitn(2, '#').next().unwrap_or(url);
+    let (location, query) = match url.find('?') {
+        Some(i) => (&url[..i], Some(&url[i + 1..])),
+        None => (url, None),
+    };
+    let (host, path) = match location.find('/') {
+        Some(i) => (&location[..i], &location[i..]),
+        None => (location, ""),
+    };
+    let host = host.to_lowercase();
+    let host = if host.starts_with("www.") { host[4..].to_string() } else { host };
+
+    let mut canonical = format!("{}{}", host, path);
+    while canonical.ends_with('/') {
+        canonical.pop();
+    }
+    let parameters: Vec<&str> = query.unwrap_or("").split('&')
+        .filter(|p| {
+            let name = p.splitn(2, '=').next().unwrap_or("");
+            !name.is_empty() && !name.starts_with("utm_") && !TRACKING_PARAMETERS.contains(&name)
+        })
+        .collect();
+    if !parameters.is_empty() {
+        canonical.push('?');
+        canonical.push_str(&parameters.join("&"));
+    }
+
+    canonical
+}
+
+fn output(result: &DedupeResult, articles: &[Article], format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            let stdout = io::stdout();
+            let mut out = stdout.lock();
+            out.write_all(render_human(result, articles).as_bytes()).chain_err(|| ErrorKind::OutputFailed)
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(result).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(result: &DedupeResult, articles: &[Article]) -> String {
+    let describe = |id: &str| {
+        articles.iter().find(|a| a.item_id == id)
+            .map(|a| format!("'{}' {}", a.title(), a.url()))
+            .unwrap_or_default()
+    };
+    let mut tw = TabWriter::new(vec![]);
+    for g in &result.groups {
+        let _ = writeln!(&mut tw, "{}", g.url);
+        let _ = writeln!(&mut tw, "  keep\t{}:\t{}", g.keep, describe(&g.keep));
+        for id in &g.duplicates {
+            let _ = writeln!(&mut tw, "  duplicate\t{}:\t{}", id, describe(id));
+        }
+    }
+    let duplicates: usize = result.groups.iter().map(|g| g.duplicates.len()).sum();
+    let _ = writeln!(&mut tw, "Found {} duplicate(s) of {} article(s).", duplicates, result.groups.len());
+    if let Some(ref results) = result.action_results {
+        let successful = results.iter().filter(|r| **r).count();
+        let _ = writeln!(&mut tw, "Deleted {} of {} duplicate(s).", successful, results.len());
+    }
+    let _ = tw.flush();
+
+    String::from_utf8(tw.into_inner().unwrap_or_default()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_canonical_url() {
+        assert_eq!(canonical_url("https://www.Example.com/Post/?utm_source=rss&id=1&fbclid=x#comments"), "example.com/Post?id=1");
+        assert_eq!(canonical_url("http://example.com/Post?utm_medium=social"), "example.com/Post");
+        assert_eq!(canonical_url("https://example.com/"), "example.com");
+    }
+
+    #[test]
+    fn test_group() {
+        let articles: Vec<Article> = serde_json::from_str(r#"[
+            {"item_id":"1","resolved_url":"https://example.com/post","time_added":"300"},
+            {"item_id":"2","resolved_url":"http://www.example.com/post/?utm_source=rss","time_added":"100"},
+            {"item_id":"3","resolved_url":"https://example.com/other","time_added":"200"},
+            {"item_id":"4","resolved_url":"https://example.com/post#top","time_added":"200"}
+        ]"#).unwrap();
+
+        assert_eq!(group(&articles, false),
+                   vec![Group { url: "example.com/post".to_string(), keep: "2".to_string(), duplicates: vec!["4".to_string(), "1".to_string()] }]);
+        assert_eq!(group(&articles, true)[0].keep, "1");
+    }
+}
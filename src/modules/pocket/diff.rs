@@ -0,0 +1,222 @@
+use super::{client, fetch_library};
+use super::client::list::{Article, State};
+use config::OutputFormat;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+pub const NAME: &'static str = "diff";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Shows the articles added, archived, deleted, and retagged between two snapshots of 'pocket list -o json'")
+        .arg(Arg::with_name("old")
+            .index(1)
+            .required(true)
+            .help("Older snapshot"))
+        .arg(Arg::with_name("new")
+            .index(2)
+            .help("Newer snapshot; defaults to all current articles"))
+}
+
+/// An article that changed between the snapshots
+#[derive(Serialize, Debug, PartialEq)]
+struct Item {
+    item_id: String,
+    title: String,
+    url: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags_added: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags_removed: Vec<String>,
+}
+
+impl<'a> From<&'a Article> for Item {
+    fn from(article: &'a Article) -> Self {
+        Item {
+            item_id: article.item_id.clone(),
+            title: article.title().to_string(),
+            url: article.url().to_string(),
+            tags_added: Vec::new(),
+            tags_removed: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Default, PartialEq)]
+struct Diff {
+    added: Vec<Item>,
+    archived: Vec<Item>,
+    readded: Vec<Item>,
+    deleted: Vec<Item>,
+    retagged: Vec<Item>,
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let args = args.unwrap();
+    let old = read_snapshot(Path::new(args.value_of("old").unwrap()))?;
+    let new = match args.value_of("new") {
+        Some(path) => read_snapshot(Path::new(path))?,
+        None => fetch(ctx)?,
+    };
+
+    output(&diff(&old, &new), ctx.output_format())
+}
+
+fn read_snapshot(path: &Path) -> Result<BTreeMap<String, Article>> {
+    let file = File::open(path).chain_err(|| format!("Could not open snapshot '{}'", path.display()))?;
+    let mut articles = BTreeMap::new();
+    client::list::stream(BufReader::new(file), |article| {
+        articles.insert(article.item_id.clone(), article);
+        Ok(())
+    }).chain_err(|| format!("Could not read snapshot '{}'; expected the output of 'rat -o json pocket list'", path.display()))?;
+
+    Ok(articles)
+}
+
+/// All current articles with their tags, which Pocket only sends with details
+fn fetch(ctx: &Context) -> Result<BTreeMap<String, Article>> {
+    let articles = fetch_library(ctx.config, State::all, true).chain_err(|| "failed to list current Pocket articles")?;
+
+    Ok(articles.into_iter().map(|article| (article.item_id.clone(), article)).collect())
+}
+
+/// Articles missing from the new snapshot count as deleted. Tags are only compared if both snapshots have tags, i.e.,
+/// were listed with details, because articles listed without details have no tags at all.
+fn diff(old: &BTreeMap<String, Article>, new: &BTreeMap<String, Article>) -> Diff {
+    let has_tags = |articles: &BTreeMap<String, Article>| articles.values().any(|a| !a.tags.is_empty());
+    let compare_tags = has_tags(old) && has_tags(new);
+    let is_archived = |article: &Article| article.status == "1";
+
+    let mut diff = Diff::default();
+    for (id, article) in new {
+        if article.is_deleted() {
+            continue;
+        }
+        let before = match old.get(id) {
+            Some(before) if !before.is_deleted() => before,
+            _ => {
+                diff.added.push(article.into());
+                continue;
+            }
+        };
+        if is_archived(article) && !is_archived(before) {
+            diff.archived.push(article.into());
+        } else if !is_archived(article) && is_archived(before) {
+            diff.readded.push(article.into());
+        }
+        if compare_tags {
+            let tags: BTreeSet<&str> = article.tag_names().into_iter().collect();
+            let tags_before: BTreeSet<&str> = before.tag_names().into_iter().collect();
+            if tags != tags_before {
+                let mut item: Item = article.into();
+                item.tags_added = tags.difference(&tags_before).map(|t| t.to_string()).collect();
+                item.tags_removed = tags_before.difference(&tags).map(|t| t.to_string()).collect();
+                diff.retagged.push(item);
+            }
+        }
+    }
+    for (id, article) in old {
+        let deleted = new.get(id).map(|a| a.is_deleted()).unwrap_or(true);
+        if deleted && !article.is_deleted() {
+            diff.deleted.push(article.into());
+        }
+    }
+
+    diff
+}
+
+fn output(diff: &Diff, format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::HUMAN => {
+            msg(render_human(diff));
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(diff).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(diff: &Diff) -> String {
+    let mut out = String::new();
+    let sections = [("Added", "+", &diff.added), ("Archived", "a", &diff.archived), ("Readded", "r", &diff.readded),
+                    ("Deleted", "-", &diff.deleted), ("Retagged", "~", &diff.retagged)];
+    for &(heading, marker, items) in &sections {
+        if items.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("{} ({}):\n", heading, items.len()));
+        for item in items.iter() {
+            let mut tags: Vec<String> = item.tags_added.iter().map(|t| format!("+{}", t)).collect();
+            tags.extend(item.tags_removed.iter().map(|t| format!("-{}", t)));
+            let tags = if tags.is_empty() { String::new() } else { format!(" [{}]", tags.join(" ")) };
+            out.push_str(&format!("  {} {}: '{}' {}{}\n", marker, item.item_id, item.title, item.url, tags));
+        }
+    }
+    out.push_str(&format!("{} added, {} archived, {} readded, {} deleted, {} retagged.\n", diff.added.len(),
+                          diff.archived.len(), diff.readded.len(), diff.deleted.len(), diff.retagged.len()));
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn snapshot(json: &str) -> BTreeMap<String, Article> {
+        let mut articles = BTreeMap::new();
+        client::list::stream(json.as_bytes(), |article| {
+            articles.insert(article.item_id.clone(), article);
+            Ok(())
+        }).unwrap();
+        articles
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = snapshot(r#"{"status":1,"complete":1,"list":{
+            "1":{"item_id":"1","resolved_title":"Kept","resolved_url":"https://example.com/1","status":"0","tags":{"rust":{"item_id":"1","tag":"rust"}}},
+            "2":{"item_id":"2","resolved_title":"Done","resolved_url":"https://example.com/2","status":"0"},
+            "3":{"item_id":"3","resolved_title":"Gone","resolved_url":"https://example.com/3","status":"0"},
+            "4":{"item_id":"4","resolved_title":"Back","resolved_url":"https://example.com/4","status":"1"}}}"#);
+        let new = snapshot(r#"{"status":1,"complete":1,"list":{
+            "1":{"item_id":"1","resolved_title":"Kept","resolved_url":"https://example.com/1","status":"0","tags":{"cli":{"item_id":"1","tag":"cli"}}},
+            "2":{"item_id":"2","resolved_title":"Done","resolved_url":"https://example.com/2","status":"1"},
+            "4":{"item_id":"4","resolved_title":"Back","resolved_url":"https://example.com/4","status":"0"},
+            "5":{"item_id":"5","resolved_title":"New","resolved_url":"https://example.com/5","status":"0"}}}"#);
+
+        let diff = diff(&old, &new);
+
+        assert_eq!(render_human(&diff), "\
+Added (1):
+  + 5: 'New' https://example.com/5
+Archived (1):
+  a 2: 'Done' https://example.com/2
+Readded (1):
+  r 4: 'Back' https://example.com/4
+Deleted (1):
+  - 3: 'Gone' https://example.com/3
+Retagged (1):
+  ~ 1: 'Kept' https://example.com/1 [+cli -rust]
+1 added, 1 archived, 1 readded, 1 deleted, 1 retagged.
+");
+    }
+
+    #[test]
+    fn test_diff_ignores_tags_of_snapshots_without_details() {
+        let old = snapshot(r#"{"status":1,"complete":1,"list":{"1":{"item_id":"1","status":"0"}}}"#);
+        let new = snapshot(r#"{"status":1,"complete":1,"list":{"1":{"item_id":"1","status":"0","tags":{"rust":{"item_id":"1","tag":"rust"}}}}}"#);
+
+        assert_eq!(diff(&old, &new), Diff::default());
+    }
+}
@@ -0,0 +1,154 @@
+use super::open_index;
+use super::client::list::Article;
+use super::list::{HumanDisplay, HumanOutput};
+use cache::index::Index;
+use config::OutputFormat;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+use utils::time::unix_now;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::collections::{BTreeSet, HashMap};
+
+pub const NAME: &'static str = "digest";
+
+/// Periods and their lengths in seconds
+static PERIODS: &'static [(&'static str, u64)] = &[("day", 86_400), ("week", 7 * 86_400), ("month", 30 * 86_400)];
+
+/// Number of domains shown by the digest
+const TOP_DOMAINS: usize = 5;
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Summarizes the articles added, archived, and deleted recently according to the syncs of the local index")
+        .arg(Arg::with_name("period")
+            .long("period")
+            .takes_value(true)
+            .possible_values(&["day", "week", "month"])
+            .default_value("week")
+            .help("Period to summarize"))
+}
+
+#[derive(Serialize, Debug)]
+struct Digest<'a> {
+    period: &'a str,
+    syncs: usize,
+    added: Vec<&'a Article>,
+    archived: Vec<&'a Article>,
+    deleted: usize,
+    domains: Vec<(String, usize)>,
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let period = args.unwrap().value_of("period").unwrap();
+    let secs = PERIODS.iter().find(|&&(name, _)| name == period).map(|&(_, secs)| secs).unwrap();
+
+    let index = open_index(config)?;
+    if index.since().is_none() {
+        warning("The local index has never been synced; please run 'rat pocket sync' first.");
+    }
+    let digest = digest(&index, period, unix_now().saturating_sub(secs))?;
+
+    match config.general.output_format {
+        OutputFormat::HUMAN => {
+            msg(render_digest(&digest)?);
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(&digest).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+/// Only the deltas of the syncs within the period are read; articles are looked up in the index by id.
+fn digest<'a>(index: &'a Index<Article>, period: &'a str, since: u64) -> Result<Digest<'a>> {
+    let changes = index.changes(since).chain_err(|| "failed to read change log of local index")?;
+    let (mut added, mut updated, mut deleted) = (BTreeSet::new(), BTreeSet::new(), BTreeSet::new());
+    for delta in &changes {
+        added.extend(delta.added.iter().map(|id| id.as_str()));
+        updated.extend(delta.updated.iter().map(|id| id.as_str()));
+        deleted.extend(delta.deleted.iter().map(|id| id.as_str()));
+    }
+
+    let added: Vec<&Article> = added.iter().filter_map(|id| index.get(id)).collect();
+    let archived: Vec<&Article> = updated.iter()
+        .filter_map(|id| index.get(id))
+        .filter(|a| a.status == "1")
+        .collect();
+    let mut domains: HashMap<String, usize> = HashMap::new();
+    for domain in added.iter().filter_map(|a| a.domain()) {
+        *domains.entry(domain).or_insert(0) += 1;
+    }
+    let mut domains: Vec<(String, usize)> = domains.into_iter().collect();
+    domains.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    domains.truncate(TOP_DOMAINS);
+
+    Ok(Digest {
+        period: period,
+        syncs: changes.len(),
+        added: added,
+        archived: archived,
+        deleted: deleted.len(),
+        domains: domains,
+    })
+}
+
+fn render_digest(digest: &Digest) -> Result<String> {
+    let human_output: HumanOutput = vec!["id", "title", "url"].into();
+    let mut out = format!("Last {}: {} article(s) added, {} archived, {} deleted.\n",
+                          digest.period, digest.added.len(), digest.archived.len(), digest.deleted);
+    if !digest.domains.is_empty() {
+        out.push_str("Top domains of added articles:\n");
+    }
+    for &(ref domain, count) in &digest.domains {
+        out.push_str(&format!("* {}: {}\n", domain, count));
+    }
+    for &(title, ref articles) in &[("Added", &digest.added), ("Archived", &digest.archived)] {
+        if articles.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("{}:\n", title));
+        for a in articles.iter() {
+            out.push_str(&format!("  {}\n", a.human_display(&human_output)?));
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing::harness;
+
+    fn articles(json: &str) -> Vec<Article> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_digest_from_change_log() {
+        let config = harness::config("pocket-digest", "");
+        let mut index = open_index(&config).unwrap();
+        index.apply(articles(r#"[
+            {"item_id":"1","resolved_title":"Rust 1.20","resolved_url":"https://blog.rust-lang.org/1.20","status":"0"},
+            {"item_id":"2","resolved_title":"Go","resolved_url":"https://golang.org","status":"0"}]"#), vec![], 10).unwrap();
+        index.apply(articles(r#"[
+            {"item_id":"1","resolved_title":"Rust 1.20","resolved_url":"https://blog.rust-lang.org/1.20","status":"1"},
+            {"item_id":"3","resolved_title":"Rust 1.21","resolved_url":"https://www.blog.rust-lang.org/1.21","status":"0"}]"#),
+            vec!["2".to_string()], 20).unwrap();
+
+        let digest = digest(&index, "week", 15).unwrap();
+
+        assert_eq!(digest.syncs, 1);
+        assert_eq!(digest.added.iter().map(|a| a.item_id.as_str()).collect::<Vec<_>>(), vec!["3"]);
+        assert_eq!(digest.archived.iter().map(|a| a.item_id.as_str()).collect::<Vec<_>>(), vec!["1"]);
+        assert_eq!(digest.deleted, 1);
+        assert_eq!(digest.domains, vec![("blog.rust-lang.org".to_string(), 1)]);
+        assert!(render_digest(&digest).unwrap().starts_with("Last week: 1 article(s) added, 1 archived, 1 deleted.\n"));
+    }
+}
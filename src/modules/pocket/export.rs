@@ -0,0 +1,171 @@
+use super::client::list::{Article, State};
+use super::fetch_library;
+use super::snapshot::escape;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Write};
+
+pub const NAME: &'static str = "export";
+
+/// Heading of the Markdown group of articles without tags
+const UNTAGGED: &'static str = "Untagged";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Export the complete library as bookmarks file, Markdown, or OPML")
+        .arg(Arg::with_name("format")
+            .long("format")
+            .takes_value(true)
+            .possible_values(&["html", "md", "opml"])
+            .default_value("html")
+            .help("Netscape bookmarks HTML as imported by browsers, Markdown links grouped by tag, or OPML"))
+        .arg(Arg::with_name("out")
+            .long("out")
+            .takes_value(true)
+            .value_name("FILE")
+            .help("Writes the export to <FILE> instead of stdout"))
+        .arg(Arg::with_name("state")
+            .long("state")
+            .short("s")
+            .takes_value(true)
+            .possible_values(&["unread", "archive", "all"])
+            .default_value("all")
+            .help("Select articles to export"))
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    let state: State = args.value_of("state").unwrap().into();
+
+    let articles: Vec<Article> = fetch_library(config, state, true).chain_err(|| "failed to export Pocket articles")?
+        .into_iter()
+        .filter(|article| !article.url().is_empty())
+        .collect();
+
+    let rendered = match args.value_of("format").unwrap() {
+        "md" => render_markdown(&articles),
+        "opml" => render_opml(&articles),
+        _ => render_bookmarks(&articles),
+    };
+    match args.value_of("out") {
+        Some(path) => {
+            let mut file = File::create(path).chain_err(|| format!("Failed to create export file '{}'", path))?;
+            file.write_all(rendered.as_bytes()).chain_err(|| format!("Failed to write export file '{}'", path))?;
+            msgln(format!("Exported {} article(s) to '{}'.", articles.len(), path));
+        }
+        None => {
+            io::stdout().write_all(rendered.as_bytes()).chain_err(|| ErrorKind::OutputFailed)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Renders the Netscape bookmark file format that browsers and bookmark services import.
+fn render_bookmarks(articles: &[Article]) -> String {
+    let mut out = String::from("<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+        <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+        <TITLE>Pocket</TITLE>\n\
+        <H1>Pocket</H1>\n\
+        <DL><p>\n");
+    for a in articles {
+        out.push_str(&format!("    <DT><A HREF=\"{}\" ADD_DATE=\"{}\" TAGS=\"{}\">{}</A>\n",
+                              escape(a.url()), escape(&a.time_added), escape(&a.tag_names().join(",")), escape(a.title())));
+    }
+    out.push_str("</DL><p>\n");
+
+    out
+}
+
+/// Renders one section per tag in alphabetical order; articles with several tags are listed in each of their sections.
+fn render_markdown(articles: &[Article]) -> String {
+    let mut by_tag: BTreeMap<&str, Vec<&Article>> = BTreeMap::new();
+    let mut untagged = Vec::new();
+    for a in articles {
+        let tags = a.tag_names();
+        if tags.is_empty() {
+            untagged.push(a);
+        }
+        for tag in tags {
+            by_tag.entry(tag).or_insert_with(Vec::new).push(a);
+        }
+    }
+
+    let mut out = String::new();
+    let groups = by_tag.into_iter().chain(if untagged.is_empty() { None } else { Some((UNTAGGED, untagged)) });
+    for (tag, articles) in groups {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("## {}\n\n", tag));
+        for a in articles {
+            out.push_str(&format!("- [{}]({})\n", a.title().replace('[', "\\[").replace(']', "\\]"), a.url()));
+        }
+    }
+
+    out
+}
+
+fn render_opml(articles: &[Article]) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <opml version=\"2.0\">\n\
+        <head>\n  <title>Pocket</title>\n</head>\n\
+        <body>\n");
+    for a in articles {
+        out.push_str(&format!("  <outline type=\"link\" text=\"{}\" url=\"{}\"", escape(a.title()), escape(a.url())));
+        let tags = a.tag_names();
+        if !tags.is_empty() {
+            out.push_str(&format!(" category=\"{}\"", escape(&tags.join(","))));
+        }
+        out.push_str("/>\n");
+    }
+    out.push_str("</body>\n</opml>\n");
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use serde_json;
+
+    fn articles() -> Vec<Article> {
+        serde_json::from_str(r#"[
+            {"item_id":"1","resolved_title":"Rust & more","resolved_url":"https://www.rust-lang.org","time_added":"1500000000",
+             "status":"0","tags":{"rust":{"item_id":"1","tag":"rust"},"lang":{"item_id":"1","tag":"lang"}}},
+            {"item_id":"2","resolved_title":"","resolved_url":"https://example.com","time_added":"1400000000","status":"1"}
+        ]"#).unwrap()
+    }
+
+    #[test]
+    fn test_render_bookmarks() {
+        let html = render_bookmarks(&articles());
+
+        assert!(html.starts_with("<!DOCTYPE NETSCAPE-Bookmark-file-1>\n"));
+        assert!(html.contains("    <DT><A HREF=\"https://www.rust-lang.org\" ADD_DATE=\"1500000000\" TAGS=\"lang,rust\">Rust &amp; more</A>\n"));
+        assert!(html.contains("    <DT><A HREF=\"https://example.com\" ADD_DATE=\"1400000000\" TAGS=\"\">https://example.com</A>\n"));
+        assert!(html.ends_with("</DL><p>\n"));
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        assert_eq!(render_markdown(&articles()), "## lang\n\n- [Rust & more](https://www.rust-lang.org)\n\
+                                                   \n## rust\n\n- [Rust & more](https://www.rust-lang.org)\n\
+                                                   \n## Untagged\n\n- [https://example.com](https://example.com)\n");
+    }
+
+    #[test]
+    fn test_render_opml() {
+        let opml = render_opml(&articles());
+
+        assert!(opml.contains("  <outline type=\"link\" text=\"Rust &amp; more\" url=\"https://www.rust-lang.org\" category=\"lang,rust\"/>\n"));
+        assert!(opml.contains("  <outline type=\"link\" text=\"https://example.com\" url=\"https://example.com\"/>\n"));
+    }
+}
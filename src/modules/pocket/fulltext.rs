@@ -0,0 +1,274 @@
+use super::{open_index, read, snapshot};
+use super::client::list::Article;
+use super::NAME as MODULE_NAME;
+use cache::search::{self, SearchIndex};
+use config::{Config, OutputFormat};
+use errors::*;
+use modules::Context;
+use net::concurrent;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+
+pub const NAME_INDEX: &'static str = "index";
+pub const NAME_QUERY: &'static str = "query";
+
+const SEARCH_INDEX_NAME: &'static str = "search";
+
+/// Weights of the fields of an article; matches in titles count three times as much as matches in the text.
+const TITLE_WEIGHT: u32 = 3;
+const TAGS_WEIGHT: u32 = 2;
+const EXCERPT_WEIGHT: u32 = 1;
+const TEXT_WEIGHT: u32 = 1;
+
+/// Characters of highlighted excerpts
+const SNIPPET_CHARS: usize = 160;
+
+pub fn build_sub_cli() -> Vec<App<'static, 'static>> {
+    vec![
+        SubCommand::with_name(NAME_INDEX)
+            .about("Builds the local full-text index of the articles of the local index for 'query'; cf. 'sync'")
+            .arg(Arg::with_name("text")
+                .long("text")
+                .help("Indexes the text of the articles, too; uses snapshots if available and downloads the others"))
+            .arg(Arg::with_name("rebuild")
+                .long("rebuild")
+                .help("Discards the full-text index and indexes all articles again")),
+        SubCommand::with_name(NAME_QUERY)
+            .about("Searches the local full-text index for articles with all words; best matches first")
+            .arg(Arg::with_name("count")
+                .long("count")
+                .takes_value(true)
+                .default_value("20")
+                .help("Lists at most <count> articles"))
+            .arg(Arg::with_name("words")
+                .index(1)
+                .multiple(true)
+                .required(true)
+                .help("words to search for in titles, tags, excerpts, and indexed texts")),
+    ]
+}
+
+pub fn call(command: &str, args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let args = args.unwrap();
+    match command {
+        NAME_INDEX => index(args, ctx),
+        _ => query(args, ctx),
+    }
+}
+
+fn open_search_index(config: &Config) -> Result<SearchIndex> {
+    SearchIndex::open(config, MODULE_NAME, SEARCH_INDEX_NAME).chain_err(|| "failed to open full-text index")
+}
+
+#[derive(Serialize, Debug, Default)]
+struct IndexResult {
+    indexed: usize,
+    with_text: usize,
+    /// Articles that left the local index since the last indexing
+    removed: usize,
+    /// Articles indexed without text, because their text could not be downloaded
+    without_text: usize,
+    articles: usize,
+}
+
+/// Indexes only articles added or changed since the last indexing, and with `--text` the articles whose text is not
+/// indexed yet.
+fn index(args: &ArgMatches, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let articles = open_index(config)?;
+    if articles.since().is_none() {
+        bail!("The local index has never been synced; please run 'rat pocket sync' first");
+    }
+    let mut search_index = open_search_index(config)?;
+    let with_text = args.is_present("text");
+
+    let mut result = IndexResult::default();
+    let removed: Vec<String> = search_index.ids().into_iter()
+        .filter(|id| args.is_present("rebuild") || articles.get(id).map(|a| a.is_deleted()).unwrap_or(true))
+        .map(|id| id.to_string())
+        .collect();
+    for id in &removed {
+        search_index.remove(id);
+        result.removed += articles.get(id).map(|a| if a.is_deleted() { 1 } else { 0 }).unwrap_or(1);
+    }
+    let outdated: Vec<&Article> = articles.items().into_iter()
+        .filter(|a| !a.is_deleted() && !search_index.is_current(&a.item_id, &a.time_updated, with_text))
+        .collect();
+    let texts = if with_text { texts(config, &outdated) } else { outdated.iter().map(|_| None).collect() };
+
+    for (article, text) in outdated.iter().zip(texts) {
+        let tags = article.tag_names().join(" ");
+        let mut fields = vec![(article.title(), TITLE_WEIGHT), (tags.as_str(), TAGS_WEIGHT), (article.excerpt.as_str(), EXCERPT_WEIGHT)];
+        if let Some(ref text) = text {
+            fields.push((text.as_str(), TEXT_WEIGHT));
+            result.with_text += 1;
+        } else if with_text {
+            result.without_text += 1;
+        }
+        search_index.insert(&article.item_id, &article.time_updated, &fields, text.is_some());
+        result.indexed += 1;
+    }
+    search_index.save().chain_err(|| "failed to save full-text index")?;
+    result.articles = search_index.len();
+
+    if result.without_text > 0 {
+        warning(format!("Failed to get the text of {} article(s); they are indexed without text and retried by the next 'index --text'.",
+                        result.without_text));
+    }
+    match config.general.output_format {
+        OutputFormat::HUMAN => {
+            msgln(format!("Indexed {} article(s), {} with text; removed {}. The full-text index holds {} article(s).",
+                          result.indexed, result.with_text, result.removed, result.articles));
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(&result).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+/// Texts of the articles in order, `None` for articles whose text could not be downloaded.
+fn texts(config: &Config, articles: &[&Article]) -> Vec<Option<String>> {
+    if !articles.is_empty() {
+        info(format!("Getting the text of {} article(s) ...", articles.len()));
+    }
+    let snapshot_dir = snapshot::default_dir(config);
+    let jobs: Vec<(String, String)> = articles.iter().map(|a| (a.item_id.clone(), a.url().to_string())).collect();
+    concurrent::map_bounded(jobs, config.general.max_concurrency(), move |(item_id, url)| {
+        match read::page(&snapshot_dir, &item_id, &url) {
+            Ok(page) => Some(page.paragraphs.join("\n")),
+            Err(e) => {
+                verboseln(format!("Failed to get the text of article {} because {}", item_id, e));
+                None
+            }
+        }
+    })
+}
+
+/// An article found by `query`
+#[derive(Serialize, Debug)]
+struct Found<'a> {
+    item_id: &'a str,
+    score: f64,
+    title: &'a str,
+    url: &'a str,
+    /// Part of the excerpt containing words of the query
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snippet: Option<String>,
+}
+
+fn query(args: &ArgMatches, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let count = args.value_of("count").unwrap();
+    let count = match count.parse::<usize>() {
+        Ok(count) if count > 0 => count,
+        _ => bail!("Invalid count '{}'; expected a positive number", count),
+    };
+    let query = args.values_of("words").unwrap().collect::<Vec<_>>().join(" ");
+
+    let search_index = open_search_index(config)?;
+    if search_index.is_empty() {
+        bail!("The full-text index is empty; please run 'rat pocket index' first");
+    }
+    let articles = open_index(config)?;
+    let found: Vec<Found> = search_index.search(&query).into_iter()
+        .filter_map(|hit| articles.get(&hit.id).map(|a| (hit, a)))
+        .take(count)
+        .map(|(hit, a)| Found {
+            item_id: &a.item_id,
+            score: (hit.score * 1000.0).round() / 1000.0,
+            title: a.title(),
+            url: a.url(),
+            snippet: search::highlight(&a.excerpt, &query, SNIPPET_CHARS, |w| w.to_string()),
+        })
+        .collect();
+
+    match config.general.output_format {
+        OutputFormat::HUMAN => {
+            msg(render_human(&found, &query));
+            Ok(())
+        }
+        OutputFormat::JSON => {
+            let json = serde_json::to_string(&found).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+        OutputFormat::NDJSON => {
+            for f in &found {
+                let json = serde_json::to_string(f).chain_err(|| "JSON serialization failed")?;
+                output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Words of the query are emphasized in titles and excerpts.
+fn render_human(found: &[Found], query: &str) -> String {
+    let mut out = String::new();
+    for f in found {
+        let title = search::highlight(f.title, query, f.title.chars().count(), |w| emphasize(w)).unwrap_or_else(|| f.title.to_string());
+        out.push_str(&format!("* {}: '{}' {}\n", f.item_id, title, f.url));
+        if let Some(ref snippet) = f.snippet {
+            let snippet = search::highlight(snippet, query, SNIPPET_CHARS, |w| emphasize(w)).unwrap_or_else(|| snippet.clone());
+            out.push_str(&format!("    {}\n", snippet));
+        }
+    }
+    out.push_str(&format!("Found {} article(s).\n", found.len()));
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cache::index::Index;
+    use modules::pocket::Pocket;
+    use net::mock::MockClient;
+    use testing::harness::*;
+
+    use std::rc::Rc;
+
+    #[test]
+    fn test_render_human() {
+        let found = vec![
+            Found { item_id: "1", score: 1.5, title: "Rust in production", url: "https://example.com/1",
+                    snippet: Some("…how we run Rust services…".to_string()) },
+            Found { item_id: "2", score: 0.5, title: "Go", url: "https://example.com/2", snippet: None },
+        ];
+
+        assert_eq!(render_human(&found, "rust"), "* 1: '*Rust* in production' https://example.com/1\n\
+                                                  \x20   …how we run *Rust* services…\n\
+                                                  * 2: 'Go' https://example.com/2\n\
+                                                  Found 2 article(s).\n");
+    }
+
+    #[test]
+    fn test_index_and_query() {
+        let config = config("pocket-fulltext", "[pocket]\nconsumer_key = 'key'\naccess_token = 'token'");
+        let articles: Vec<Article> = serde_json::from_str(r#"[
+            {"item_id":"1","resolved_title":"Rust in production","resolved_url":"https://example.com/1","time_updated":"10",
+             "status":"0","excerpt":"How we run Rust services at scale."},
+            {"item_id":"2","resolved_title":"Go","resolved_url":"https://example.com/2","time_updated":"10","status":"0",
+             "tags":{"rust":{"item_id":"2","tag":"rust"}}},
+            {"item_id":"3","resolved_title":"Haskell","resolved_url":"https://example.com/3","time_updated":"10","status":"0"}
+        ]"#).unwrap();
+        let mut index: Index<Article> = open_index(&config).unwrap();
+        index.apply(articles, vec![], 10).unwrap();
+        let mock = Rc::new(MockClient::new());
+        let ctx = Context::new(&config);
+
+        run(&Pocket, &["index"], &ctx, &mock).unwrap();
+        run(&Pocket, &["query", "rust"], &ctx, &mock).unwrap();
+
+        let search_index = open_search_index(&config).unwrap();
+        assert_eq!(search_index.len(), 3);
+        let mut ids: Vec<String> = search_index.search("rust").into_iter().map(|h| h.id).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["1", "2"]);
+        assert!(mock.requests().is_empty());
+    }
+}
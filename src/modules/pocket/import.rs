@@ -0,0 +1,427 @@
+use super::client;
+use super::client::send::AddAction;
+use super::snapshot::normalize;
+use super::NAME as MODULE_NAME;
+use cache::checkpoint::Checkpoint;
+use config::OutputFormat;
+use credentials;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{self, Value};
+use std::fs::File;
+use std::io::{Read, Write};
+use tabwriter::TabWriter;
+
+pub const NAME: &'static str = "import";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Import bookmarks from a bookmarks file or CSV")
+        .arg(Arg::with_name("file")
+            .index(1)
+            .required(true)
+            .help("Netscape bookmarks HTML as exported by browsers and 'rat pocket export', or CSV of url,title,tags"))
+        .arg(Arg::with_name("format")
+            .long("format")
+            .takes_value(true)
+            .possible_values(&["html", "csv"])
+            .help("Format of <file>; derived from its extension if omitted"))
+        .arg(Arg::with_name("batch-size")
+            .long("batch-size")
+            .takes_value(true)
+            .help("Number of articles added per request; defaults to 'batch_size' of section '[pocket]'"))
+        .arg(Arg::with_name("resume")
+            .long("resume")
+            .help("Skips the articles a previous import of the same file added before it stopped"))
+}
+
+/// Checkpoint of the batches sent, so an import that stopped can be resumed; cf. `--resume`
+const CHECKPOINT_NAME: &'static str = "import";
+
+/// A bookmark to add
+#[derive(Debug, PartialEq)]
+struct Entry {
+    url: String,
+    title: Option<String>,
+    tags: Vec<String>,
+    /// Unix timestamp of the time the bookmark was saved
+    time: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct ImportResult {
+    imported: usize,
+    failed: Vec<String>,
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    credentials::ensure_writable(config, MODULE_NAME).chain_err(|| "failed to import Pocket articles")?;
+
+    let path = args.value_of("file").unwrap();
+    let batch_size = match args.value_of("batch-size") {
+        Some(batch_size) => match batch_size.parse::<usize>() {
+            Ok(size) if size > 0 => size,
+            _ => bail!("Invalid batch size '{}'; expected a positive number", batch_size),
+        },
+        None => config.pocket.batch_size(),
+    };
+    let format = args.value_of("format")
+        .unwrap_or_else(|| if path.to_lowercase().ends_with(".csv") { "csv" } else { "html" });
+    let mut content = String::new();
+    File::open(path).and_then(|mut f| f.read_to_string(&mut content))
+        .chain_err(|| format!("Failed to read import file '{}'", path))?;
+    let entries = match format {
+        "csv" => parse_csv(&content)?,
+        _ => parse_bookmarks(&content),
+    };
+    if entries.is_empty() {
+        bail!("No bookmarks found in '{}'", path);
+    }
+    let checkpoint = Checkpoint::open(config, MODULE_NAME, CHECKPOINT_NAME, &content);
+    let done = checkpoint.done();
+    let entries = if args.is_present("resume") {
+        if done > 0 {
+            info(format!("Resuming after {} of {} article(s) ...", done, entries.len()));
+        }
+        &entries[done.min(entries.len())..]
+    } else {
+        if done > 0 {
+            info(format!("A previous import of '{}' stopped after {} article(s); add --resume to skip them.", path, done));
+        }
+        &entries[..]
+    };
+
+    if ctx.dry_run {
+        msg(render_entries(entries)?);
+        msgln(format!("Dry run: would import {} article(s).", entries.len()));
+        return Ok(());
+    }
+
+    let result = import(ctx, entries, batch_size, &checkpoint).chain_err(|| "failed to import Pocket articles")?;
+    checkpoint.clear().chain_err(|| "failed to remove import checkpoint")?;
+    output(&result, &config.general.output_format)?;
+    if !result.failed.is_empty() {
+        bail!(ErrorKind::PartialFailure(result.imported, result.failed.len()));
+    }
+
+    Ok(())
+}
+
+/// Sends the entries in batches and records every batch sent in `checkpoint`. If Pocket rejects the access token, the
+/// user is asked to authenticate again if `interactive_reauth` is enabled, and the batch is sent again; otherwise, and
+/// if a batch cannot be sent at all, the import stops and may be resumed by `--resume`.
+fn import(ctx: &Context, entries: &[Entry], batch_size: usize, checkpoint: &Checkpoint) -> Result<ImportResult> {
+    let config = ctx.config;
+    let tags: Vec<String> = entries.iter().map(|e| e.tags.join(",")).collect();
+    let actions: Vec<AddAction> = entries.iter().zip(tags.iter())
+        .map(|(e, tags)| AddAction::new(
+            &e.url,
+            e.title.as_ref().map(|t| t.as_str()),
+            if tags.is_empty() { None } else { Some(tags.as_str()) },
+            e.time.as_ref().map(|t| t.as_str())))
+        .collect();
+
+    info(format!("Importing {} article(s) ...", entries.len()));
+    let mut progress = Progress::new(actions.len(), "articles", ctx.show_progress());
+    // Resumed imports count from the start of the file
+    let skipped = checkpoint.done();
+    let mut result = ImportResult { imported: 0, failed: Vec::new() };
+    let mut sent = 0;
+    for (batch, batch_entries) in actions.chunks(batch_size).zip(entries.chunks(batch_size)) {
+        let mut reauthenticated = false;
+        let json = loop {
+            match client::send_batched(config, batch, batch_size, |_| {}) {
+                Err(ref e) if e.category() == Category::Auth && !reauthenticated
+                    && config.general.interactive_reauth.unwrap_or(false) => {
+                    progress.finish();
+                    warning("Pocket rejected the access token; please authenticate again.");
+                    super::login(config, false, false).chain_err(|| "Re-authentication failed")?;
+                    info("Re-authenticated; continuing ...");
+                    reauthenticated = true;
+                }
+                result => break result,
+            }
+        };
+        let added = match json.and_then(|json| parse_action_results(&json)) {
+            Ok(added) => added,
+            Err(e) => {
+                progress.finish();
+                return Err(e).chain_err(|| format!("import stopped after {} article(s); run it again with --resume to continue",
+                                                   skipped + sent));
+            }
+        };
+        for (entry, ok) in batch_entries.iter().zip(added) {
+            if ok {
+                result.imported += 1;
+            } else {
+                result.failed.push(entry.url.clone());
+            }
+        }
+        sent += batch.len();
+        checkpoint.save(skipped + sent).chain_err(|| "failed to write import checkpoint")?;
+        progress.inc(batch.len());
+    }
+    progress.finish();
+
+    Ok(result)
+}
+
+/// Pocket answers each add action with the added item, or `false` if it failed.
+fn parse_action_results(json: &str) -> Result<Vec<bool>> {
+    let json: Value = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
+    let results = json.get("action_results").and_then(|r| r.as_array())
+        .ok_or_else(|| Error::from("Pocket did not send action results"))?;
+
+    Ok(results.iter().map(|r| *r != Value::Bool(false)).collect())
+}
+
+/// Reads the links of a Netscape bookmarks file, skipping those that are not web pages, e.g., Firefox's 'place:' queries.
+fn parse_bookmarks(html: &str) -> Vec<Entry> {
+    // ASCII lower case keeps byte positions, so positions found in `lower` apply to `html`
+    let lower = html.to_ascii_lowercase();
+    let mut entries = Vec::new();
+    let mut pos = 0;
+    while let Some(start) = lower[pos..].find("<a ").map(|i| pos + i) {
+        let tag_end = match lower[start..].find('>') {
+            Some(i) => start + i,
+            None => break,
+        };
+        let end = lower[tag_end..].find("</a>").map(|i| tag_end + i).unwrap_or_else(|| lower.len());
+        let attributes = &html[start + 2..tag_end];
+        if let Some(url) = attribute(attributes, "href").filter(|u| u.starts_with("http://") || u.starts_with("https://")) {
+            let title = normalize(&html[tag_end + 1..end]);
+            let tags = attribute(attributes, "tags")
+                .map(|t| t.split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect())
+                .unwrap_or_else(Vec::new);
+            entries.push(Entry {
+                url: url,
+                title: if title.is_empty() { None } else { Some(title) },
+                tags: tags,
+                time: attribute(attributes, "add_date").filter(|t| !t.is_empty()),
+            });
+        }
+        pos = end;
+    }
+
+    entries
+}
+
+/// Finds a double quoted attribute by its case-insensitive name.
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let lower = attributes.to_ascii_lowercase();
+    let pattern = format!("{}=\"", name);
+    let mut pos = 0;
+    while let Some(start) = lower[pos..].find(&pattern).map(|i| pos + i) {
+        let value_start = start + pattern.len();
+        let value_end = lower[value_start..].find('"').map(|i| value_start + i)?;
+        if lower[..start].ends_with(char::is_whitespace) {
+            return Some(normalize(&attributes[value_start..value_end]));
+        }
+        pos = value_end;
+    }
+
+    None
+}
+
+/// Reads rows of url, title, and tags; a header row may name the columns in any order, e.g., 'title,url,time_added,tags'.
+/// Tags are separated by '|' or ','.
+fn parse_csv(csv: &str) -> Result<Vec<Entry>> {
+    let mut lines = csv.lines().enumerate().filter(|&(_, line)| !line.trim().is_empty()).peekable();
+    let mut columns = vec!["url".to_string(), "title".to_string(), "tags".to_string()];
+    if let Some(&(_, header)) = lines.peek() {
+        let fields: Vec<String> = parse_csv_line(header).into_iter().map(|f| f.trim().to_lowercase()).collect();
+        if fields.iter().any(|f| f == "url") {
+            columns = fields;
+            lines.next();
+        }
+    }
+    let column = |fields: &[String], name: &str| -> Option<String> {
+        columns.iter().position(|c| c == name)
+            .and_then(|i| fields.get(i))
+            .map(|f| f.trim().to_string())
+            .filter(|f| !f.is_empty())
+    };
+
+    let mut entries = Vec::new();
+    for (number, line) in lines {
+        let fields = parse_csv_line(line);
+        let url = column(&fields, "url").ok_or_else(|| Error::from(format!("Line {} has no URL", number + 1)))?;
+        let tags = column(&fields, "tags")
+            .map(|t| t.split(|c| c == '|' || c == ',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect())
+            .unwrap_or_else(Vec::new);
+        entries.push(Entry {
+            url: url,
+            title: column(&fields, "title"),
+            tags: tags,
+            time: column(&fields, "time_added"),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Splits a line into fields; fields may be quoted, with quotes doubled inside.
+fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(field.split_off(0)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+fn render_entries(entries: &[Entry]) -> Result<String> {
+    let mut tw = TabWriter::new(vec![]);
+    for e in entries {
+        writeln!(&mut tw, "{}\t{}\t{}", e.url, e.title.as_ref().map(|t| t.as_str()).unwrap_or(""), e.tags.join(","))
+            .chain_err(|| ErrorKind::OutputFailed)?;
+    }
+    tw.flush().chain_err(|| ErrorKind::OutputFailed)?;
+    let out = String::from_utf8(tw.into_inner().unwrap()).chain_err(|| ErrorKind::OutputFailed)?;
+
+    Ok(out)
+}
+
+fn output(result: &ImportResult, format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            msg(render_human(result));
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(result).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(result: &ImportResult) -> String {
+    let mut out = String::new();
+    for url in &result.failed {
+        out.push_str(&format!("failed\t{}\n", url));
+    }
+    out.push_str(&format!("{} article(s) imported, {} failed.\n", result.imported, result.failed.len()));
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_bookmarks() {
+        let html = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+            <DL><p>
+                <DT><H3>Rust</H3>
+                <DL><p>
+                    <DT><A HREF="https://www.rust-lang.org/?a=1&amp;b=2" ADD_DATE="1500000000" TAGS="rust,lang">Rust &amp; more</A>
+                    <DT><a href="place:sort=8" add_date="1400000000">Recent</a>
+                    <DT><a data-href="x" href="https://example.com">
+                        Example</a>
+                </DL><p>
+            </DL><p>"#;
+
+        let entries = parse_bookmarks(html);
+
+        assert_eq!(entries, vec![
+            Entry {
+                url: "https://www.rust-lang.org/?a=1&b=2".to_string(),
+                title: Some("Rust & more".to_string()),
+                tags: vec!["rust".to_string(), "lang".to_string()],
+                time: Some("1500000000".to_string()),
+            },
+            Entry { url: "https://example.com".to_string(), title: Some("Example".to_string()), tags: vec![], time: None },
+        ]);
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = "https://example.com/a,\"A, \"\"quoted\"\"\",rust|cli\n\nhttps://example.com/b\n";
+
+        let entries = parse_csv(csv).unwrap();
+
+        assert_eq!(entries, vec![
+            Entry {
+                url: "https://example.com/a".to_string(),
+                title: Some("A, \"quoted\"".to_string()),
+                tags: vec!["rust".to_string(), "cli".to_string()],
+                time: None,
+            },
+            Entry { url: "https://example.com/b".to_string(), title: None, tags: vec![], time: None },
+        ]);
+    }
+
+    #[test]
+    fn test_parse_csv_with_header() {
+        let csv = "title,url,time_added,tags,status\nA,https://example.com/a,1500000000,rust,unread\n";
+
+        let entries = parse_csv(csv).unwrap();
+
+        assert_eq!(entries, vec![Entry {
+            url: "https://example.com/a".to_string(),
+            title: Some("A".to_string()),
+            tags: vec!["rust".to_string()],
+            time: Some("1500000000".to_string()),
+        }]);
+    }
+
+    #[test]
+    fn test_parse_action_results() {
+        let json = r#"{"action_results":[{"item_id":"1"},false],"status":1}"#;
+
+        assert_eq!(parse_action_results(json).unwrap(), vec![true, false]);
+    }
+
+    #[test]
+    fn test_resume_stopped_import() {
+        use modules::pocket::Pocket;
+        use net::mock::MockClient;
+        use testing::harness::*;
+        use std::env;
+        use std::fs;
+        use std::rc::Rc;
+
+        let config = config("pocket-import-resume", "[pocket]\nconsumer_key = 'key'\naccess_token = 'token'");
+        let ctx = Context::new(&config);
+        let path = env::temp_dir().join("rat-pocket-import-resume.csv");
+        fs::write(&path, "https://example.com/a\nhttps://example.com/b\nhttps://example.com/c\n").unwrap();
+        let args = ["import", path.to_str().unwrap(), "--batch-size", "1"];
+
+        let mock = Rc::new(MockClient::new()
+            .respond(200, &[], r#"{"action_results":[{"item_id":"1"}],"status":1}"#)
+            .respond(401, &[], ""));
+        let result = run(&Pocket, &args, &ctx, &mock);
+
+        assert_eq!(result.unwrap_err().category(), Category::Auth);
+        assert_eq!(mock.requests().len(), 2);
+
+        let mock = Rc::new(MockClient::new()
+            .respond(200, &[], r#"{"action_results":[{"item_id":"2"}],"status":1}"#)
+            .respond(200, &[], r#"{"action_results":[{"item_id":"3"}],"status":1}"#));
+        run(&Pocket, &[args[0], args[1], args[2], args[3], "--resume"], &ctx, &mock).unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains("example.com%2Fb"));
+        assert!(requests[1].contains("example.com%2Fc"));
+    }
+}
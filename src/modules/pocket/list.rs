@@ -1,27 +1,41 @@
-use super::client;
-use super::client::list::{Article, ListResult, Request};
+use super::{access_token, client, ensure_consumer_key, fetch_library, open_index, sync};
+use super::NAME as MODULE_NAME;
+use super::client::list::{Article, ListResult, ListSummary, Request, Sort, State};
 use config::{Config, OutputFormat};
+use errors::*;
+use modules::Context;
+use modules::bookmarks::{self, Bookmark};
+use net::concurrent;
 use utils::console::*;
 use utils::output;
-use utils::time;
+use utils::datetime;
 
 use chrono::{DateTime, NaiveDateTime, UTC};
 use clap::{App, Arg, ArgMatches, SubCommand};
+use regex::Regex;
 use serde_json;
+use std::cmp;
+use std::collections::HashMap;
+use std::io;
+#[cfg(test)]
+use std::io::Read;
 use std::io::Write;
-use std::str;
+use std::time::Duration;
 use tabwriter::TabWriter;
 
 pub const NAME: &'static str = "list";
 
-error_chain! {
-    errors {
-       PocketListFailed {
-            description("failed to list Pocket articles")
-            display("failed to list Pocket articles")
-        }
-    }
-}
+/// Initial page size of `--all`
+const DEFAULT_PAGE_SIZE: &'static str = "100";
+
+/// Titles and URLs are not shortened further to fit the terminal
+const MIN_SHORTENED_WIDTH: usize = 20;
+
+/// Indentation of the excerpts of `--details`
+const EXCERPT_INDENT: &'static str = "    ";
+
+/// Columns of `--columns`
+const COLUMNS: [&'static str; 9] = ["id", "title", "url", "domain", "tags", "time_added", "read_time", "status", "favorite"];
 
 pub fn build_sub_cli() -> App<'static, 'static> {
     SubCommand::with_name(NAME)
@@ -29,7 +43,7 @@ pub fn build_sub_cli() -> App<'static, 'static> {
         .arg(Arg::with_name("details")
             .long("details")
             .short("d")
-            .help("Select details for articles"))
+            .help("Select details for articles; shows the estimated reading time, tags, and excerpt"))
         .arg(Arg::with_name("tag")
             .long("tag")
             .short("t")
@@ -44,33 +58,97 @@ pub fn build_sub_cli() -> App<'static, 'static> {
             .help("Select articles to list"))
         .arg(Arg::with_name("since")
             .long("since")
+            .visible_alias("added-since")
             .takes_value(true)
-            .help("Select articles added since <duration> ago; e.g. '2w 3d 12m. Truncates fields from original JSON output."))
+            .help("Select articles added since <time>, a duration ago like '2w 3d 12m' or a date like '2023-01-01'. Truncates fields from original JSON output."))
         .arg(Arg::with_name("until")
             .long("until")
+            .visible_alias("added-before")
             .takes_value(true)
-            .help("Select articles added until <duration> ago; e.g. '2w 3d 12m. Truncates fields from original JSON output."))
+            .help("Select articles added until <time>, a duration ago like '2w 3d 12m' or a date like '2023-01-01'. Truncates fields from original JSON output."))
         .arg(Arg::with_name("sort")
             .long("sort")
             .takes_value(true)
-            .possible_values(&["newest", "oldest", "title", "site"])
+            .possible_values(&["newest", "oldest", "title", "site", "readtime"])
             .default_value("newest")
-            .help("Select sort order"))
+            .help("Select sort order; 'readtime' lists the quickest reads first and articles without word count last"))
         .arg(Arg::with_name("output")
             .long("output")
             .short("o")
             .takes_value(true)
             .multiple(true)
             .require_delimiter(true)
-            .possible_values(&["id", "title", "url", "t_added"])
+            .possible_values(&["id", "title", "url", "t_added", "read_time"])
             .default_value("id,title,url,t_added")
             .help("Select human output field; default all"))
+        .arg(Arg::with_name("columns")
+            .long("columns")
+            .takes_value(true)
+            .multiple(true)
+            .require_delimiter(true)
+            .possible_values(&COLUMNS)
+            .help("Prints human output as table of the given columns instead, e.g., 'title,url,tags,time_added'"))
+        .arg(Arg::with_name("borders")
+            .long("borders")
+            .requires("columns")
+            .help("Draws borders around the cells of the table"))
+        .arg(Arg::with_name("favorites-only")
+            .long("favorites-only")
+            .conflicts_with("no-favorites")
+            .help("Select favorite articles only"))
+        .arg(Arg::with_name("no-favorites")
+            .long("no-favorites")
+            .help("Select articles that are not favorites only"))
+        .arg(Arg::with_name("count")
+            .long("count")
+            .takes_value(true)
+            .conflicts_with("all")
+            .help("Lists at most <count> articles"))
+        .arg(Arg::with_name("offset")
+            .long("offset")
+            .takes_value(true)
+            .requires("count")
+            .help("Skips the first <offset> articles; requires --count"))
+        .arg(Arg::with_name("all")
+            .long("all")
+            .help("Fetches articles page by page, several pages concurrently; cf. 'max_concurrency' in section '[general]'"))
+        .arg(Arg::with_name("page-size")
+            .long("page-size")
+            .takes_value(true)
+            .requires("all")
+            .help("Sets the initial number of articles per page for --all; adapts to the response times [default: 100]"))
+        .arg(Arg::with_name("sync")
+            .long("sync")
+            .conflicts_with_all(&["all", "count", "tag"])
+            .help("Syncs the local index and lists its articles; only changes since the last sync are downloaded, and the last sync is listed if Pocket cannot be reached"))
+        .arg(Arg::with_name("search-query")
+            .long("search")
+            .takes_value(true)
+            .value_name("query")
+            .conflicts_with("search")
+            .help("Select articles with <query> in title or url; same as the search term"))
+        .arg(Arg::with_name("filter")
+            .long("filter")
+            .takes_value(true)
+            .value_name("regex")
+            .help("Select articles whose title or url matches <regex>; applied to the received articles"))
+        .arg(Arg::with_name("min-words")
+            .long("min-words")
+            .takes_value(true)
+            .help("Select articles with at least <min-words> words; skips articles without word count"))
+        .arg(Arg::with_name("max-words")
+            .long("max-words")
+            .takes_value(true)
+            .help("Select articles with at most <max-words> words; skips articles without word count"))
+        .arg(bookmarks::list_arg()
+            .conflicts_with("sync"))
         .arg(Arg::with_name("search")
             .index(1)
             .help("Select articles with search term in title or url"))
 }
 
-pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
     let args = args.unwrap();
 
     let state = Some(args.value_of("state").unwrap().into());
@@ -80,61 +158,652 @@ pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()> {
         None
     };
     let sort = Some(args.value_of("sort").unwrap().into());
-    let detail_type = args.is_present("details").into();
-    let search = args.value_of("search");
+    let by_read_time = args.value_of("sort") == Some("readtime");
+    let columns: Option<Vec<&str>> = args.values_of("columns").map(|c| c.collect());
+    let search = args.value_of("search").or_else(|| args.value_of("search-query"));
+    let regex = match args.value_of("filter") {
+        Some(filter) => Some(Regex::new(filter).chain_err(|| format!("Invalid filter '{}'", filter))?),
+        None => None,
+    };
+    let min_words = parse_number(args.value_of("min-words"), "minimum number of words")?;
+    let max_words = parse_number(args.value_of("max-words"), "maximum number of words")?;
+    // Pocket sends tags and word counts only with details
+    let with_details = columns.as_ref().map(|c| c.contains(&"tags") || c.contains(&"read_time")).unwrap_or(false)
+        || args.is_present("bookmarks") || by_read_time || min_words.is_some() || max_words.is_some()
+        || args.values_of("output").map(|mut o| o.any(|o| o == "read_time")).unwrap_or(false);
+    let details = args.is_present("details") || with_details;
     let since = if let Some(since) = args.value_of("since") {
-        let unix_ts = time::parse_duration(since).chain_err(|| "Could not parse since duration")?;
+        let unix_ts = datetime::parse(since).chain_err(|| "Could not parse since time")?;
         Some(unix_ts)
     } else {
         None
     };
     let until = if let Some(until) = args.value_of("until") {
-        let unix_ts = time::parse_duration(until).chain_err(|| "Could not parse until duration")?;
+        let unix_ts = datetime::parse(until).chain_err(|| "Could not parse until time")?;
         Some(unix_ts)
     } else {
         None
     };
-    let human_output: HumanOutput = args.values_of("output").map(|c| c.collect::<Vec<&str>>()).unwrap().into();
-
+    let page_size = if args.is_present("all") {
+        let page_size = args.value_of("page-size").unwrap_or(DEFAULT_PAGE_SIZE);
+        Some(page_size.parse::<usize>().chain_err(|| format!("Invalid page size '{}'", page_size))?)
+    } else {
+        None
+    };
+    let favorite = if args.is_present("favorites-only") {
+        Some(1)
+    } else if args.is_present("no-favorites") {
+        Some(0)
+    } else {
+        None
+    };
+    let count = parse_number(args.value_of("count"), "count")?;
+    let offset = parse_number(args.value_of("offset"), "offset")?;
+    let mut human_output: HumanOutput = args.values_of("output").map(|c| c.collect::<Vec<&str>>()).unwrap().into();
+    human_output.read_time |= args.is_present("details");
+    human_output.details = args.is_present("details");
+    human_output.width = output::terminal_width();
+    human_output.table = columns.map(|columns| TableLayout {
+        columns: columns.iter().map(|c| c.to_string()).collect(),
+        borders: args.is_present("borders"),
+    });
+    if ctx.all_accounts {
+        if args.is_present("sync") || args.is_present("bookmarks") {
+            bail!("'--sync' and '--bookmarks' cannot be used with '--account all'");
+        }
+        let query = Query {
+            state: state,
+            tag: value.map(|t| t.to_string()),
+            sort: sort,
+            details: details,
+            search: search.map(|s| s.to_string()),
+            favorite: favorite,
+            count: count,
+            offset: offset,
+            since: since,
+            until: until,
+            page_size: page_size,
+            regex: regex,
+            min_words: min_words.map(|w| w as u64),
+            max_words: max_words.map(|w| w as u64),
+            by_read_time: by_read_time,
+        };
+        return output_accounts(config, query, args.value_of("sort").unwrap(), &human_output);
+    }
+    if args.is_present("sync") {
+        if by_read_time || min_words.is_some() || max_words.is_some() {
+            bail!("The local index does not store word counts; '--sort readtime', '--min-words', and '--max-words' cannot be used with '--sync'");
+        }
+        let selection = Selection {
+            state: args.value_of("state").unwrap(),
+            search: search,
+            since: since,
+            until: until,
+            sort: args.value_of("sort").unwrap(),
+            filter: regex.as_ref(),
+            favorite: favorite,
+        };
+        return output_synced(config, &selection, &human_output).chain_err(|| "failed to list Pocket articles");
+    }
     let request = Request {
-        consumer_key: &config.pocket.consumer_key,
-        access_token: config.pocket.access_token.as_ref().unwrap(),
         state: state,
         tag: value,
         sort: sort,
-        detailType: detail_type,
+        detailType: details.into(),
         search: search,
+        since: None,
+        favorite: favorite,
+        count: count,
+        offset: offset,
     };
 
-    let json = client::list(config, &request, since, until).chain_err(|| ErrorKind::PocketListFailed)?;
+    let filter = Filter {
+        regex: regex.as_ref(),
+        min_words: min_words.map(|w| w as u64),
+        max_words: max_words.map(|w| w as u64),
+        by_read_time: by_read_time,
+    };
 
-    output(&json, &config.general.output_format, &human_output)
+    if args.is_present("bookmarks") {
+        return receive(config, &request, since, until, page_size, &filter, |a| bookmarks::print(&to_bookmark(&a)))
+            .map(|_| ())
+            .chain_err(|| "failed to list Pocket articles");
+    }
+    output(config, &request, since, until, page_size, &filter, &human_output).chain_err(|| "failed to list Pocket articles")
 }
 
+pub fn bookmarks(ctx: &Context) -> Result<Vec<Bookmark>> {
+    let bookmarks = fetch_library(ctx.config, State::all, true).chain_err(|| "failed to list Pocket articles")?
+        .iter()
+        .filter(|article| !article.url().is_empty())
+        .map(to_bookmark)
+        .collect();
+
+    Ok(bookmarks)
+}
 
-fn output(json: &str, format: &OutputFormat, human_output: &HumanOutput) -> Result<()> {
-    match *format {
-        OutputFormat::HUMAN => output_human(json, human_output),
-        OutputFormat::JSON => output::as_json(json)
-            .chain_err(|| ErrorKind::PocketListFailed),
+fn to_bookmark(article: &Article) -> Bookmark {
+    Bookmark {
+        url: article.url().to_string(),
+        title: if article.title() == article.url() { String::new() } else { article.title().to_string() },
+        tags: article.tag_names().iter().map(|t| t.to_string()).collect(),
+        time_added: article.time_added().ok().map(|t| t.as_secs()),
+        time_updated: article.time_updated.parse().ok(),
     }
 }
 
-fn output_human(json: &str, human_output: &HumanOutput) -> Result<()> {
-    let list: ListResult = serde_json::from_str(json).chain_err(|| "JSON parsing failed")?;
+fn parse_number(value: Option<&str>, name: &str) -> Result<Option<usize>> {
+    match value {
+        Some(value) => value.parse::<usize>().map(Some).chain_err(|| format!("Invalid {} '{}'", name, value)),
+        None => Ok(None),
+    }
+}
 
-    if list.status == 1 {
-        msgln(format!("Received {} article(s).", list.list.values().len()));
-    } else {
-        msgln("Receiving articles failed.");
+/// Selects and orders the received articles where Pocket's API cannot
+#[derive(Debug, Default)]
+struct Filter<'a> {
+    regex: Option<&'a Regex>,
+    min_words: Option<u64>,
+    max_words: Option<u64>,
+    by_read_time: bool,
+}
+
+impl<'a> Filter<'a> {
+    fn is_empty(&self) -> bool {
+        self.regex.is_none() && self.min_words.is_none() && self.max_words.is_none() && !self.by_read_time
+    }
+
+    /// Articles without word count only match without word limits.
+    fn matches(&self, article: &Article) -> bool {
+        if self.min_words.is_some() || self.max_words.is_some() {
+            match article.words() {
+                Some(words) if self.min_words.map(|min| words >= min).unwrap_or(true)
+                    && self.max_words.map(|max| words <= max).unwrap_or(true) => {}
+                _ => return false,
+            }
+        }
+
+        matches(article, self.regex)
+    }
+}
+
+/// Passes the matching articles to `on_article` as they are received, or after all of them have been received if they
+/// are sorted by reading time. With a page size, the articles are fetched page by page; cf. `client::list::list_all`.
+fn receive<F>(config: &Config, request: &Request, since: Option<Duration>, until: Option<Duration>, page_size: Option<usize>,
+              filter: &Filter, mut on_article: F) -> Result<ListSummary>
+    where F: FnMut(Article) -> Result<()>
+{
+    let mut sorted = Vec::new();
+    let summary = {
+        let on_received = |a: Article| {
+            if !filter.matches(&a) {
+                Ok(())
+            } else if filter.by_read_time {
+                sorted.push(a);
+                Ok(())
+            } else {
+                on_article(a)
+            }
+        };
+        match page_size {
+            Some(page_size) => client::list::list_all(config, request, since, until, page_size, on_received)?,
+            None => client::list::list_streamed(config, request, since, until, on_received)?,
+        }
+    };
+    if filter.by_read_time {
+        sort_by_read_time(&mut sorted);
+        for a in sorted {
+            on_article(a)?;
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Quickest reads first; articles without word count last in the order received
+fn sort_by_read_time(articles: &mut [Article]) {
+    articles.sort_by_key(|a| {
+        let read_time = a.read_time();
+        (read_time.is_none(), read_time)
+    });
+}
+
+/// Articles are printed while the response is still being received; cf. `client::list::list_streamed` and `receive`.
+/// Only without a filter, JSON responses are passed through. NDJSON is printed article by article like human output.
+fn output(config: &Config, request: &Request, since: Option<Duration>, until: Option<Duration>, page_size: Option<usize>,
+          filter: &Filter, human_output: &HumanOutput) -> Result<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    match config.general.output_format {
+        OutputFormat::HUMAN => {
+            let mut count = 0;
+            let mut printer = Printer::new(human_output);
+            let summary = receive(config, request, since, until, page_size, filter, |a| {
+                count += 1;
+                printer.print(&a, &mut out)
+            })?;
+            printer.finish(&mut out)?;
+            printer.summary(&summary, count, &mut out)
+        }
+        OutputFormat::NDJSON => {
+            receive(config, request, since, until, page_size, filter, |a| {
+                output::as_json(&render_ndjson(&a)?).chain_err(|| ErrorKind::OutputFailed)
+            })?;
+            Ok(())
+        }
+        OutputFormat::JSON if page_size.is_some() || !filter.is_empty() => {
+            let mut list = HashMap::new();
+            let summary = receive(config, request, since, until, page_size, filter, |a| {
+                list.insert(a.item_id.clone(), a);
+                Ok(())
+            })?;
+            let result = ListResult { status: summary.status, complete: summary.complete, list: list };
+            let json = serde_json::to_string(&result).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+        OutputFormat::JSON if since.is_none() && until.is_none() && output::selector().is_none() => {
+            let mut json = output::JsonWriter::new(&mut out);
+            client::list::list_to(config, request, &mut json)?;
+            writeln!(json.into_inner()).chain_err(|| ErrorKind::OutputFailed)
+        }
+        OutputFormat::JSON => {
+            let json = client::list(config, request, since, until)?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+/// Selection of `--account all`. The accounts are queried concurrently, so unlike `Request` and `Filter` it owns its
+/// values.
+#[derive(Debug, Clone)]
+struct Query {
+    state: Option<State>,
+    tag: Option<String>,
+    sort: Option<Sort>,
+    details: bool,
+    search: Option<String>,
+    favorite: Option<u8>,
+    count: Option<usize>,
+    offset: Option<usize>,
+    since: Option<Duration>,
+    until: Option<Duration>,
+    page_size: Option<usize>,
+    regex: Option<Regex>,
+    min_words: Option<u64>,
+    max_words: Option<u64>,
+    by_read_time: bool,
+}
+
+/// Lists the articles of all named accounts, each with the name of its account. The merged articles are sorted again,
+/// and `--count` applies to each account as well as to the merged list. Accounts that fail are reported and skipped.
+fn output_accounts(config: &Config, query: Query, sort: &str, human_output: &HumanOutput) -> Result<()> {
+    let accounts = config.accounts(MODULE_NAME)?;
+    if accounts.is_empty() {
+        bail!("No accounts configured; please add sections like '[pocket.accounts.<name>]'");
+    }
+    let total = accounts.len();
+
+    // Access tokens are looked up one account after the other, so re-authentication prompts do not interleave
+    let mut ready = Vec::new();
+    for (name, config) in accounts {
+        let usable = ensure_consumer_key(&config)
+            .and_then(|_| access_token(&config).chain_err(|| "no access token available"));
+        match usable {
+            Ok(_) => ready.push((name, config)),
+            Err(e) => error(format!("Failed to list articles of account '{}' because {}", name, e)),
+        }
+    }
+    let count = query.count;
+    let results = concurrent::map_bounded(ready, config.general.max_concurrency(), move |(name, config)| {
+        let articles = receive_account(&config, &query);
+        (name, articles)
+    });
+    let mut articles = Vec::new();
+    let mut listed = 0;
+    for (name, result) in results {
+        match result {
+            Ok(received) => {
+                listed += 1;
+                articles.extend(received.into_iter().map(|a| (name.clone(), a)));
+            }
+            Err(e) => error(format!("Failed to list articles of account '{}' because {}", name, e)),
+        }
+    }
+    sort_merged(&mut articles, sort);
+    if let Some(count) = count {
+        articles.truncate(count);
     }
-    for a in list.list.values() {
-        msgln(a.human_display(human_output).chain_err(|| "Human output failed")?);
+
+    output_merged(&articles, config.general.output_format, human_output)?;
+    if listed < total {
+        bail!(ErrorKind::PartialFailure(listed, total - listed));
     }
 
     Ok(())
 }
 
+fn receive_account(config: &Config, query: &Query) -> Result<Vec<Article>> {
+    let request = Request {
+        state: query.state,
+        tag: query.tag.as_ref().map(|t| t.as_str()),
+        sort: query.sort,
+        detailType: query.details.into(),
+        search: query.search.as_ref().map(|s| s.as_str()),
+        since: None,
+        favorite: query.favorite,
+        count: query.count,
+        offset: query.offset,
+    };
+    let filter = Filter {
+        regex: query.regex.as_ref(),
+        min_words: query.min_words,
+        max_words: query.max_words,
+        by_read_time: query.by_read_time,
+    };
+    let mut articles = Vec::new();
+    receive(config, &request, query.since, query.until, query.page_size, &filter, |a| {
+        articles.push(a);
+        Ok(())
+    })?;
+
+    Ok(articles)
+}
+
+/// Sorts like Pocket sorts a single account; cf. `Selection::select`
+fn sort_merged(articles: &mut [(String, Article)], sort: &str) {
+    match sort {
+        "oldest" => articles.sort_by_key(|&(_, ref a)| a.time_added().ok()),
+        "title" => articles.sort_by_key(|&(_, ref a)| a.title().to_lowercase()),
+        "site" => articles.sort_by_key(|&(_, ref a)| a.domain()),
+        "readtime" => articles.sort_by_key(|&(_, ref a)| {
+            let read_time = a.read_time();
+            (read_time.is_none(), read_time)
+        }),
+        _ => articles.sort_by(|&(_, ref a), &(_, ref b)| b.time_added().ok().cmp(&a.time_added().ok())),
+    }
+}
+
+/// JSON is an array of articles instead of Pocket's map by item id, because item ids are only unique per account.
+fn output_merged(articles: &[(String, Article)], format: OutputFormat, human_output: &HumanOutput) -> Result<()> {
+    match format {
+        OutputFormat::HUMAN => {
+            msg(render_merged(articles, human_output)?);
+            Ok(())
+        }
+        OutputFormat::JSON => {
+            let values = articles.iter().map(|&(ref account, ref a)| with_account(a, account)).collect::<Result<Vec<_>>>()?;
+            let json = serde_json::to_string(&values).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+        OutputFormat::NDJSON => {
+            for &(ref account, ref a) in articles {
+                let json = serde_json::to_string(&with_account(a, account)?).chain_err(|| "JSON serialization failed")?;
+                output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn render_merged(articles: &[(String, Article)], human_output: &HumanOutput) -> Result<String> {
+    let mut out = Vec::new();
+    let mut printer = Printer::for_accounts(human_output);
+    for &(ref account, ref a) in articles {
+        printer.print_in(account, a, &mut out)?;
+    }
+    printer.finish(&mut out)?;
+    if printer.template.is_none() {
+        writeln!(out, "Received {} article(s).", articles.len()).chain_err(|| ErrorKind::OutputFailed)?;
+    }
+
+    String::from_utf8(out).chain_err(|| "Human output failed")
+}
+
+/// The article as JSON object with the name of its account as field 'account'
+fn with_account(article: &Article, account: &str) -> Result<serde_json::Value> {
+    let mut value = serde_json::to_value(article).chain_err(|| "JSON serialization failed")?;
+    if let Some(object) = value.as_object_mut() {
+        object.insert("account".to_string(), serde_json::Value::String(account.to_string()));
+    }
+
+    Ok(value)
+}
+
+fn matches(article: &Article, filter: Option<&Regex>) -> bool {
+    filter.map(|r| r.is_match(article.title()) || r.is_match(article.url())).unwrap_or(true)
+}
+
+/// Selects articles of the local index like Pocket selects them for a list request
+#[derive(Debug)]
+struct Selection<'a> {
+    state: &'a str,
+    search: Option<&'a str>,
+    since: Option<Duration>,
+    until: Option<Duration>,
+    sort: &'a str,
+    filter: Option<&'a Regex>,
+    favorite: Option<u8>,
+}
+
+impl<'a> Selection<'a> {
+    fn select<'b>(&self, articles: Vec<&'b Article>) -> Result<Vec<&'b Article>> {
+        let search = self.search.map(|s| s.to_lowercase());
+        let mut selected = Vec::new();
+        for article in articles {
+            let state = match self.state {
+                "unread" => article.status == "0",
+                "archive" => article.status == "1",
+                _ => !article.is_deleted(),
+            };
+            let found = search.as_ref().map(|s| {
+                article.title().to_lowercase().contains(s) || article.url().to_lowercase().contains(s)
+            }).unwrap_or(true);
+            let favorite = self.favorite.map(|f| article.favorite == f.to_string()).unwrap_or(true);
+            if state && found && favorite && matches(article, self.filter)
+                && client::list::is_within(article, &self.since, &self.until)? {
+                selected.push(article);
+            }
+        }
+
+        match self.sort {
+            "oldest" => selected.sort_by_key(|a| a.time_added().ok()),
+            "title" => selected.sort_by_key(|a| a.title().to_lowercase()),
+            "site" => selected.sort_by_key(|a| a.domain()),
+            _ => selected.sort_by(|a, b| b.time_added().ok().cmp(&a.time_added().ok())),
+        }
+
+        Ok(selected)
+    }
+}
+
+/// Same shape as `ListResult`
+#[derive(Serialize, Debug)]
+struct LocalListResult<'a> {
+    status: i32,
+    complete: i32,
+    list: HashMap<&'a str, &'a Article>,
+}
+
+/// Lists the articles of the local index after syncing it; cf. `sync::sync`. If the sync fails, e.g., because Pocket
+/// cannot be reached, the articles of the last sync are listed.
+fn output_synced(config: &Config, selection: &Selection, human_output: &HumanOutput) -> Result<()> {
+    let mut index = open_index(config)?;
+    if let Err(e) = sync::sync(config, &mut index) {
+        if index.since().is_none() {
+            return Err(e);
+        }
+        warning(format!("Failed to sync local index, listing articles of last sync because {}", e));
+    }
+    let articles = selection.select(index.items())?;
+
+    match config.general.output_format {
+        OutputFormat::HUMAN => {
+            let stdout = io::stdout();
+            let mut out = stdout.lock();
+            let mut printer = Printer::new(human_output);
+            for article in &articles {
+                printer.print(article, &mut out)?;
+            }
+            printer.finish(&mut out)?;
+            if printer.template.is_some() {
+                return Ok(());
+            }
+            writeln!(out, "Listed {} article(s) of the local index.", articles.len()).chain_err(|| ErrorKind::OutputFailed)
+        }
+        OutputFormat::NDJSON => {
+            for article in &articles {
+                let json = serde_json::to_string(article).chain_err(|| "JSON serialization failed")?;
+                output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)?;
+            }
+            Ok(())
+        }
+        OutputFormat::JSON => {
+            let list = articles.iter().map(|a| (a.item_id.as_str(), *a)).collect();
+            let result = LocalListResult { status: 1, complete: 1, list: list };
+            let json = serde_json::to_string(&result).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_article<W: Write>(article: &Article, human_output: &HumanOutput, out: &mut W) -> Result<()> {
+    let line = article.human_display(human_output).chain_err(|| "Human output failed")?;
+    writeln!(out, "{}", line).chain_err(|| ErrorKind::OutputFailed)
+}
+
+/// Prints articles one line each as they arrive, or collects them for a table if columns are selected; tables need all
+/// rows to align the columns. The template of `--format` takes precedence over both, and suppresses the summary.
+struct Printer<'a> {
+    human_output: &'a HumanOutput,
+    template: Option<&'static output::Template>,
+    table: Option<output::Table>,
+}
+
+impl<'a> Printer<'a> {
+    fn new(human_output: &'a HumanOutput) -> Self {
+        Printer::with_account_column(human_output, false)
+    }
+
+    /// Tables get a first column 'ACCOUNT'; cf. `print_in`.
+    fn for_accounts(human_output: &'a HumanOutput) -> Self {
+        Printer::with_account_column(human_output, true)
+    }
+
+    fn with_account_column(human_output: &'a HumanOutput, account: bool) -> Self {
+        let table = human_output.table.as_ref().map(|layout| {
+            let account = if account { Some("ACCOUNT".to_string()) } else { None };
+            output::Table::new(account.into_iter().chain(layout.columns.iter().map(|c| c.to_uppercase())).collect())
+                .borders(layout.borders)
+                .max_width(output::terminal_width())
+        });
+        Printer { human_output: human_output, template: output::template(), table: table }
+    }
+
+    fn print<W: Write>(&mut self, article: &Article, out: &mut W) -> Result<()> {
+        if let Some(template) = self.template {
+            return writeln!(out, "{}", template.render_item(article)).chain_err(|| ErrorKind::OutputFailed);
+        }
+        match (&mut self.table, &self.human_output.table) {
+            (&mut Some(ref mut table), &Some(ref layout)) => {
+                let row = layout.columns.iter().map(|c| column(article, c)).collect::<Result<Vec<String>>>()?;
+                table.add_row(row);
+                Ok(())
+            }
+            _ => render_article(article, self.human_output, out),
+        }
+    }
+
+    /// Prints an article of `--account all` with the name of its account.
+    fn print_in<W: Write>(&mut self, account: &str, article: &Article, out: &mut W) -> Result<()> {
+        if let Some(template) = self.template {
+            return writeln!(out, "{}", template.render_item(&with_account(article, account)?)).chain_err(|| ErrorKind::OutputFailed);
+        }
+        match (&mut self.table, &self.human_output.table) {
+            (&mut Some(ref mut table), &Some(ref layout)) => {
+                let mut row = vec![account.to_string()];
+                for c in &layout.columns {
+                    row.push(column(article, c)?);
+                }
+                table.add_row(row);
+                Ok(())
+            }
+            _ => {
+                let line = article.human_display(self.human_output).chain_err(|| "Human output failed")?;
+                // Lines start with '* '
+                writeln!(out, "* [{}] {}", account, &line[2..]).chain_err(|| ErrorKind::OutputFailed)
+            }
+        }
+    }
+
+    fn finish<W: Write>(&self, out: &mut W) -> Result<()> {
+        match self.table {
+            Some(ref table) if !table.is_empty() => write!(out, "{}", table.render()).chain_err(|| ErrorKind::OutputFailed),
+            _ => Ok(()),
+        }
+    }
+
+    fn summary<W: Write>(&self, summary: &ListSummary, count: usize, out: &mut W) -> Result<()> {
+        if self.template.is_some() {
+            return Ok(());
+        }
+        render_summary(summary, count, out)
+    }
+}
+
+fn column(article: &Article, column: &str) -> Result<String> {
+    let value = match column {
+        "id" => article.item_id.clone(),
+        "title" => article.title().to_string(),
+        "url" => article.url().to_string(),
+        "domain" => article.domain().unwrap_or_default(),
+        "tags" => article.tag_names().join(","),
+        "time_added" => format_time(article.time_added().chain_err(|| "Failed to parse time")?),
+        "read_time" => article.read_time().map(format_read_time).unwrap_or_default(),
+        "status" => match article.status.as_str() {
+            "1" => "archived",
+            "2" => "deleted",
+            _ => "unread",
+        }.to_string(),
+        "favorite" => if article.favorite == "1" { "yes" } else { "no" }.to_string(),
+        _ => bail!("Unknown column '{}'", column),
+    };
+
+    Ok(value)
+}
+
+fn format_time(d: Duration) -> String {
+    let dt = DateTime::<UTC>::from_utc(NaiveDateTime::from_timestamp(d.as_secs() as i64, d.subsec_nanos()), UTC);
+    dt.to_rfc3339()
+}
+
+fn format_read_time(minutes: u64) -> String {
+    format!("{} min", minutes)
+}
+
+fn render_summary<W: Write>(summary: &ListSummary, count: usize, out: &mut W) -> Result<()> {
+    if summary.status == 1 {
+        writeln!(out, "Received {} article(s).", count)
+    } else {
+        writeln!(out, "Receiving articles failed.")
+    }.chain_err(|| ErrorKind::OutputFailed)
+}
+
+/// One line of `--output ndjson`
+fn render_ndjson(article: &Article) -> Result<String> {
+    serde_json::to_string(article).chain_err(|| "JSON serialization failed")
+}
+
+#[cfg(test)]
+fn render_human<R: Read>(reader: R, human_output: &HumanOutput) -> Result<String> {
+    let mut out = Vec::new();
+    let mut printer = Printer::new(human_output);
+    let summary = client::list::stream(reader, |a| printer.print(&a, &mut out))?;
+    printer.finish(&mut out)?;
+    let count = summary.count;
+    printer.summary(&summary, count, &mut out)?;
+
+    Ok(String::from_utf8(out).chain_err(|| "Human output failed")?)
+}
 
 #[derive(Debug)]
 pub struct HumanOutput {
@@ -142,6 +811,20 @@ pub struct HumanOutput {
     pub title: bool,
     pub url: bool,
     pub t_added: bool,
+    /// Shown only if known; cf. `Article::read_time`
+    pub read_time: bool,
+    /// Tags and excerpt, which Pocket only sends with details
+    pub details: bool,
+    /// Replaces the fields above
+    pub table: Option<TableLayout>,
+    /// Width of the terminal; longer titles and URLs are shortened, and excerpts wrapped
+    pub width: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct TableLayout {
+    pub columns: Vec<String>,
+    pub borders: bool,
 }
 
 impl<'a> From<Vec<&'a str>> for HumanOutput {
@@ -150,39 +833,312 @@ impl<'a> From<Vec<&'a str>> for HumanOutput {
         let title = v.contains(&"title");
         let url = v.contains(&"url");
         let t_added = v.contains(&"t_added");
+        let read_time = v.contains(&"read_time");
 
-        HumanOutput { id: id, title: title, url: url, t_added: t_added }
+        HumanOutput { id: id, title: title, url: url, t_added: t_added, read_time: read_time, details: false, table: None, width: None }
     }
 }
 
-trait HumanDisplay {
+pub trait HumanDisplay {
     fn human_display(&self, human_output: &HumanOutput) -> Result<String>;
 }
 
 impl HumanDisplay for Article {
     fn human_display(&self, human_output: &HumanOutput) -> Result<String> {
-        let mut tw = TabWriter::new(vec![]);
-
-        let _ = write!(&mut tw, "* ");
-        if human_output.id {
-            let _ = write!(&mut tw, "{}:\t", self.item_id.clone());
+        let (mut title, mut url) = (self.title().to_string(), self.url().to_string());
+        let mut line = human_line(self, human_output, &title, &url)?;
+        // Titles are shortened first, because the URL identifies the article
+        if let Some(width) = human_output.width {
+            let overflow = line.chars().count().saturating_sub(width);
+            if overflow > 0 {
+                title = shorten(&title, overflow);
+                line = human_line(self, human_output, &title, &url)?;
+            }
+            let overflow = line.chars().count().saturating_sub(width);
+            if overflow > 0 {
+                url = shorten(&url, overflow);
+                line = human_line(self, human_output, &title, &url)?;
+            }
         }
-        if human_output.title {
-            let _ = write!(&mut tw, "'{}' ", &self.resolved_title);
+        if human_output.details && !self.excerpt.is_empty() {
+            let excerpt = self.excerpt.trim();
+            match human_output.width {
+                Some(width) => for excerpt_line in output::wrap(excerpt, width.saturating_sub(EXCERPT_INDENT.len())) {
+                    line.push_str(&format!("\n{}{}", EXCERPT_INDENT, excerpt_line));
+                },
+                None => line.push_str(&format!("\n{}{}", EXCERPT_INDENT, excerpt)),
+            }
         }
-        if human_output.url {
-            let _ = write!(&mut tw, "{} ", self.resolved_url.clone());
+
+        Ok(line)
+    }
+}
+
+/// Shortens `text` by `overflow` characters, but not below `MIN_SHORTENED_WIDTH`
+fn shorten(text: &str, overflow: usize) -> String {
+    let length = text.chars().count();
+    output::truncate(text, cmp::max(length.saturating_sub(overflow), cmp::min(length, MIN_SHORTENED_WIDTH)))
+}
+
+/// The first line of `human_display` with the given title and URL
+fn human_line(article: &Article, human_output: &HumanOutput, title: &str, url: &str) -> Result<String> {
+    let mut tw = TabWriter::new(vec![]);
+
+    let _ = write!(&mut tw, "* ");
+    if human_output.id {
+        let _ = write!(&mut tw, "{}:\t", article.item_id.clone());
+    }
+    if human_output.title {
+        let _ = write!(&mut tw, "'{}' ", title);
+    }
+    if human_output.url {
+        let _ = write!(&mut tw, "{} ", url);
+    }
+    if human_output.t_added {
+        let d = article.time_added().chain_err(|| "Failed to parse time")?;
+        let _ = write!(&mut tw, "added {}", format_time(d));
+    }
+    if let (true, Some(minutes)) = (human_output.read_time, article.read_time()) {
+        let _ = write!(&mut tw, "{}({} read)", if human_output.t_added { " " } else { "" }, format_read_time(minutes));
+    }
+    if human_output.details {
+        let tags = article.tag_names();
+        if !tags.is_empty() {
+            let _ = write!(&mut tw, " [{}]", tags.join(","));
         }
-        if human_output.t_added {
-            let d = self.time_added().chain_err(|| "Failed to parse time")?;
-            let dt = DateTime::<UTC>::from_utc(
-                NaiveDateTime::from_timestamp(d.as_secs() as i64, d.subsec_nanos()), UTC);
-            let _ = write!(&mut tw, "added {}", &dt.to_rfc3339());
+    }
+
+    tw.flush().unwrap();
+    let out_str = String::from_utf8(tw.into_inner().unwrap()).unwrap();
+
+    Ok(out_str)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use modules::pocket::Pocket;
+    use net::mock::MockClient;
+    use testing::golden::*;
+    use testing::harness::*;
+
+    use std::rc::Rc;
+
+    #[test]
+    fn test_render_human() {
+        let json = fixture("pocket/list.json");
+        let all: HumanOutput = vec!["id", "title", "url", "t_added"].into();
+        let titles: HumanOutput = vec!["title"].into();
+
+        assert_golden("pocket/list_all.txt", &render_human(json.as_bytes(), &all).unwrap());
+        assert_golden("pocket/list_titles.txt", &render_human(json.as_bytes(), &titles).unwrap());
+    }
+
+    #[test]
+    fn test_render_json() {
+        let json = fixture("pocket/list.json");
+        let mut ndjson = String::new();
+        client::list::stream(json.as_bytes(), |a| {
+            ndjson.push_str(&render_ndjson(&a)?);
+            ndjson.push('\n');
+            Ok(())
+        }).unwrap();
+
+        assert_golden("pocket/list.json", &output::render_json(&json, true).unwrap());
+        assert_golden("pocket/list.ndjson", &ndjson);
+    }
+
+    #[test]
+    fn test_human_display_fits_width() {
+        let article: Article = serde_json::from_str(r#"{"item_id":"1","resolved_title":"Announcing the Rust 2018 edition release candidate",
+            "resolved_url":"https://blog.rust-lang.org/2018/11/announcing-the-rust-2018-edition-release-candidate.html",
+            "excerpt":"Rust 2018 is the first edition since 2015 and brings non-lexical lifetimes."}"#).unwrap();
+        let mut human_output: HumanOutput = vec!["title", "url"].into();
+        human_output.details = true;
+        human_output.width = Some(60);
+
+        // The URL is followed by a space
+        assert_eq!(article.human_display(&human_output).unwrap(),
+                   "* 'Announcing the Rust…' https://blog.rust-lang.org/2018/1… \n\
+                    \x20   Rust 2018 is the first edition since 2015 and brings\n\
+                    \x20   non-lexical lifetimes.");
+    }
+
+    #[test]
+    fn test_render_table() {
+        let json = r#"{"status":1,"complete":1,"list":{"1":{"item_id":"1","resolved_title":"Rust 1.20 released",
+            "resolved_url":"https://blog.rust-lang.org/2017/08/31/Rust-1.20.html","time_added":"1504188000",
+            "tags":{"rust":{"item_id":"1","tag":"rust"},"release":{"item_id":"1","tag":"release"}}}}}"#;
+        let mut human_output: HumanOutput = vec![].into();
+        human_output.table = Some(TableLayout { columns: vec!["title".to_string(), "tags".to_string(), "status".to_string()], borders: false });
+
+        let out = render_human(json.as_bytes(), &human_output).unwrap();
+
+        assert_eq!(out, "\
+TITLE               TAGS          STATUS
+Rust 1.20 released  release,rust  unread
+Received 1 article(s).
+");
+    }
+
+    #[test]
+    fn test_to_bookmark() {
+        let article: Article = serde_json::from_str(r#"{"item_id":"1","resolved_title":"Rust 1.20 released",
+            "resolved_url":"https://blog.rust-lang.org/2017/08/31/Rust-1.20.html","time_added":"1504188000",
+            "time_updated":"1504189000","tags":{"rust":{"item_id":"1","tag":"rust"},"release":{"item_id":"1","tag":"release"}}}"#).unwrap();
+        let untitled: Article = serde_json::from_str(r#"{"item_id":"2","given_url":"https://example.com"}"#).unwrap();
+
+        let bookmark = to_bookmark(&article);
+
+        assert_eq!(bookmark.title, "Rust 1.20 released");
+        assert_eq!(bookmark.tags, vec!["release", "rust"]);
+        assert_eq!((bookmark.time_added, bookmark.time_updated), (Some(1504188000), Some(1504189000)));
+        assert_eq!(to_bookmark(&untitled), Bookmark::new("https://example.com"));
+    }
+
+    #[test]
+    fn test_human_display_details() {
+        let article: Article = serde_json::from_str(r#"{"item_id":"1","given_title":"Rust 1.20 released",
+            "given_url":"https://blog.rust-lang.org/2017/08/31/Rust-1.20.html","word_count":"400",
+            "excerpt":"The Rust team is happy to announce the latest version of Rust, 1.20.0. ",
+            "tags":{"rust":{"item_id":"1","tag":"rust"},"release":{"item_id":"1","tag":"release"}}}"#).unwrap();
+        let mut human_output: HumanOutput = vec!["title", "url"].into();
+        human_output.read_time = true;
+        human_output.details = true;
+
+        assert_eq!(article.human_display(&human_output).unwrap(), "\
+* 'Rust 1.20 released' https://blog.rust-lang.org/2017/08/31/Rust-1.20.html (2 min read) [release,rust]
+    The Rust team is happy to announce the latest version of Rust, 1.20.0.");
+    }
+
+    fn article(id: &str, title: &str, time_added: &str, status: &str) -> Article {
+        Article {
+            item_id: id.to_string(),
+            resolved_title: title.to_string(),
+            resolved_url: format!("https://example.com/{}", id),
+            time_added: time_added.to_string(),
+            time_updated: time_added.to_string(),
+            status: status.to_string(),
+            favorite: if id == "1" { "1" } else { "0" }.to_string(),
+            ..Default::default()
         }
+    }
 
-        tw.flush().unwrap();
-        let out_str = String::from_utf8(tw.into_inner().unwrap()).unwrap();
+    #[test]
+    fn test_selection_select() {
+        let articles = vec![
+            article("1", "Rust 1.0", "100", "0"),
+            article("2", "Go", "300", "0"),
+            article("3", "Rust 2018", "200", "0"),
+            article("4", "Rust archived", "400", "1"),
+        ];
+        let mut selection = Selection { state: "unread", search: Some("rust"), since: None, until: None, sort: "newest", filter: None, favorite: None };
 
-        Ok(out_str)
+        let ids = |selected: Vec<&Article>| selected.iter().map(|a| a.item_id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(selection.select(articles.iter().collect()).unwrap()), vec!["3", "1"]);
+        selection.state = "all";
+        selection.sort = "oldest";
+        assert_eq!(ids(selection.select(articles.iter().collect()).unwrap()), vec!["1", "3", "4"]);
+        selection.favorite = Some(1);
+        assert_eq!(ids(selection.select(articles.iter().collect()).unwrap()), vec!["1"]);
+        selection.favorite = None;
+        selection.since = Some(Duration::from_secs(150));
+        selection.until = Some(Duration::from_secs(350));
+        selection.search = None;
+        assert_eq!(ids(selection.select(articles.iter().collect()).unwrap()), vec!["3", "2"]);
+        let filter = Regex::new("^Rust \\d+$").unwrap();
+        selection.filter = Some(&filter);
+        assert_eq!(ids(selection.select(articles.iter().collect()).unwrap()), vec!["3"]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_filter_by_words_and_read_time() {
+        let mut articles = vec![article("1", "Long", "100", "0"), article("2", "Unknown", "200", "0"),
+                                article("3", "Short", "300", "0"), article("4", "Medium", "400", "0")];
+        articles[0].word_count = "5000".to_string();
+        articles[2].word_count = "300".to_string();
+        articles[3].word_count = "1000".to_string();
+        articles[3].time_to_read = Some(4);
+        let filter = Filter { regex: None, min_words: Some(500), max_words: None, by_read_time: true };
+
+        let matching: Vec<&str> = articles.iter().filter(|a| filter.matches(a)).map(|a| a.item_id.as_str()).collect();
+        assert_eq!(matching, vec!["1", "4"]);
+        sort_by_read_time(&mut articles);
+        assert_eq!(articles.iter().map(|a| a.item_id.as_str()).collect::<Vec<_>>(), vec!["3", "4", "1", "2"]);
+        let human_output: HumanOutput = vec!["title", "read_time"].into();
+        assert_eq!(articles[1].human_display(&human_output).unwrap(), "* 'Medium' (4 min read)");
+    }
+
+    #[test]
+    fn test_render_merged() {
+        let mut articles: Vec<(String, Article)> = vec![
+            ("team".to_string(), serde_json::from_str(r#"{"item_id":"1","resolved_title":"Older","time_added":"1400000000"}"#).unwrap()),
+            ("personal".to_string(), serde_json::from_str(r#"{"item_id":"1","resolved_title":"Newer","time_added":"1500000000"}"#).unwrap()),
+        ];
+        let mut human_output: HumanOutput = vec!["title"].into();
+
+        sort_merged(&mut articles, "newest");
+
+        assert_eq!(render_merged(&articles, &human_output).unwrap(), "* [personal] 'Newer' \n* [team] 'Older' \nReceived 2 article(s).\n");
+        human_output.table = Some(TableLayout { columns: vec!["id".to_string(), "title".to_string()], borders: false });
+        assert_eq!(render_merged(&articles, &human_output).unwrap(), "\
+ACCOUNT   ID  TITLE
+personal  1   Newer
+team      1   Older
+Received 2 article(s).
+");
+        assert_eq!(with_account(&articles[0].1, "personal").unwrap().pointer("/account"), Some(&serde_json::Value::String("personal".to_string())));
+    }
+
+    #[test]
+    fn test_list_all_accounts() {
+        let mut config = config("pocket-list-all-accounts", "[pocket]\nconsumer_key = 'key'\n\n\
+                                                         [pocket.accounts.personal]\naccess_token = 'personal token'\n\n\
+                                                         [pocket.accounts.team]\naccess_token = 'team token'");
+        // The mock only answers requests of the test's thread
+        config.general.max_concurrency = Some(1);
+        let mock = Rc::new(MockClient::new()
+            .respond(200, &[], r#"{"status":1,"complete":1,"list":{"1":{"item_id":"1","resolved_title":"Mine","time_added":"1500000000"}}}"#)
+            .respond(500, &[], ""));
+
+        let result = run(&Pocket, &["list"], &Context::new(&config).with_all_accounts(true), &mock);
+
+        assert_eq!(result.unwrap_err().category(), Category::PartialFailure);
+        let requests = mock.requests();
+        assert!(requests[0].contains(r#""access_token":"personal token""#));
+        assert!(requests[1].contains(r#""access_token":"team token""#));
+    }
+}
+
+#[cfg(all(test, feature = "live-tests"))]
+mod live_test {
+    use super::*;
+    use super::client::list::{DetailType, State};
+    use testing::live;
+
+    #[test]
+    fn test_list() {
+        let (consumer_key, access_token) = match (live::credential("RAT_LIVE_POCKET_CONSUMER_KEY"),
+                                                  live::credential("RAT_LIVE_POCKET_ACCESS_TOKEN")) {
+            (Some(consumer_key), Some(access_token)) => (consumer_key, access_token),
+            _ => return,
+        };
+        let config = live::config(&format!("[pocket]\nconsumer_key = '{}'\naccess_token = '{}'\n", consumer_key, access_token));
+        let request = Request {
+            state: Some(State::all),
+            tag: None,
+            sort: None,
+            detailType: DetailType::simple,
+            search: None,
+            since: None,
+            favorite: None,
+            count: None,
+            offset: None,
+        };
+
+        let json = live::check(client::list(&config, &request, None, None));
+        let human_output: HumanOutput = vec!["id", "title", "url", "t_added"].into();
+
+        live::check(render_human(json.as_bytes(), &human_output));
+    }
+}
@@ -0,0 +1,164 @@
+use super::open_index;
+use super::client::list::Article;
+use super::list::{HumanDisplay, HumanOutput};
+use cache::index::{Index, words};
+use config::OutputFormat;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde::Serialize;
+use serde_json;
+
+pub const NAME: &'static str = "local";
+pub const NAME_SEARCH: &'static str = "search";
+pub const NAME_STATS: &'static str = "stats";
+pub const NAME_DUPES: &'static str = "dupes";
+
+/// Number of domains shown by stats
+const TOP_DOMAINS: usize = 10;
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Queries the local index of all articles without contacting Pocket; cf. 'sync'")
+        .subcommand(SubCommand::with_name(NAME_SEARCH)
+            .about("Search for articles with all words in title or url")
+            .arg(Arg::with_name("words")
+                .index(1)
+                .multiple(true)
+                .required(true)
+                .help("words to search for")))
+        .subcommand(SubCommand::with_name(NAME_STATS)
+            .about("Show numbers of articles by state and top domains"))
+        .subcommand(SubCommand::with_name(NAME_DUPES)
+            .about("Show articles saved more than once with the same url"))
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    let subcommand_name = args.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+
+    let index = open_index(config)?;
+    if index.since().is_none() {
+        warning("The local index has never been synced; please run 'rat pocket sync' first.");
+    }
+    let format = &config.general.output_format;
+    match subcommand_name {
+        NAME_SEARCH => {
+            let terms: Vec<&str> = args.subcommand_matches(NAME_SEARCH).unwrap().values_of("words").unwrap().collect();
+            let words = words(&terms.join(" "));
+            let words: Vec<&str> = words.iter().map(|s| s.as_str()).collect();
+            output(&index.lookup_all("word", &words), format, render_articles)
+        }
+        NAME_STATS => output(&stats(&index), format, render_stats),
+        NAME_DUPES => {
+            let dupes: Vec<Dupes> = index.duplicates("url").into_iter()
+                .map(|(url, articles)| Dupes { url: url, articles: articles })
+                .collect();
+            output(&dupes, format, render_dupes)
+        }
+        _ => Ok(()),
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct Stats<'a> {
+    articles: usize,
+    unread: usize,
+    archived: usize,
+    domains: Vec<(&'a str, usize)>,
+}
+
+#[derive(Serialize, Debug)]
+struct Dupes<'a> {
+    url: &'a str,
+    articles: Vec<&'a Article>,
+}
+
+fn stats<'a>(index: &'a Index<Article>) -> Stats<'a> {
+    let mut domains = index.counts("domain");
+    domains.truncate(TOP_DOMAINS);
+
+    Stats {
+        articles: index.len(),
+        unread: index.items().iter().filter(|a| a.status == "0").count(),
+        archived: index.items().iter().filter(|a| a.status == "1").count(),
+        domains: domains,
+    }
+}
+
+fn output<T: Serialize>(result: &T, format: &OutputFormat, render_human: fn(&T) -> Result<String>) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            msg(render_human(result)?);
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(result).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_articles(articles: &Vec<&Article>) -> Result<String> {
+    if let Some(template) = output::template() {
+        return Ok(template.render_all(articles));
+    }
+    let human_output: HumanOutput = vec!["id", "title", "url"].into();
+    let mut out = format!("Found {} article(s).\n", articles.len());
+    for a in articles {
+        out.push_str(&a.human_display(&human_output)?);
+        out.push('\n');
+    }
+
+    Ok(out)
+}
+
+fn render_stats(stats: &Stats) -> Result<String> {
+    let mut out = format!("{} article(s), {} unread, {} archived.\n", stats.articles, stats.unread, stats.archived);
+    if !stats.domains.is_empty() {
+        out.push_str("Top domains:\n");
+    }
+    for &(domain, count) in &stats.domains {
+        out.push_str(&format!("* {}: {}\n", domain, count));
+    }
+
+    Ok(out)
+}
+
+fn render_dupes(dupes: &Vec<Dupes>) -> Result<String> {
+    let human_output: HumanOutput = vec!["id", "title"].into();
+    let mut out = format!("Found {} url(s) saved more than once.\n", dupes.len());
+    for d in dupes {
+        out.push_str(&format!("{}\n", d.url));
+        for a in &d.articles {
+            out.push_str(&format!("  {}\n", a.human_display(&human_output)?));
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing::harness;
+
+    #[test]
+    fn test_stats() {
+        let config = harness::config("pocket-local", "");
+        let articles: Vec<Article> = serde_json::from_str(r#"[
+            {"item_id":"1","resolved_title":"Rust 1.20","resolved_url":"https://blog.rust-lang.org/1.20","status":"0"},
+            {"item_id":"2","resolved_title":"Rust 1.20","resolved_url":"http://blog.rust-lang.org/1.20/","status":"1"},
+            {"item_id":"3","resolved_title":"Go","resolved_url":"https://golang.org","status":"0"}]"#).unwrap();
+        let mut index = open_index(&config).unwrap();
+        index.apply(articles, vec![], 1).unwrap();
+
+        assert_eq!(render_stats(&stats(&index)).unwrap(),
+                   "3 article(s), 2 unread, 1 archived.\nTop domains:\n* blog.rust-lang.org: 2\n* golang.org: 1\n");
+        assert_eq!(index.duplicates("url").len(), 1);
+    }
+}
@@ -1,45 +1,207 @@
-use config::Config;
+pub use clients::pocket::{NAME, PocketConfig, PocketAccountConfig};
+
+use cache::index::Index;
+use clients::pocket::{access_token, client, request_auth};
+use clients::pocket::client::list::{Article, DetailType, Request, Sort, State};
+use config::{ALL_ACCOUNTS, Config};
+use credentials;
 use errors::*;
+use modules::{Context, Module};
+use modules::bookmarks::Bookmark;
 
 use clap::{App, ArgMatches, SubCommand};
 
-pub const NAME: &'static str = "pocket";
-
-mod client;
 mod actions;
+mod add;
 mod auth;
+mod browse;
+mod check;
+mod dedupe;
+mod diff;
+mod digest;
+mod export;
+mod fulltext;
+mod import;
 mod list;
+mod local;
+mod open;
+mod queue;
+mod read;
+mod rules;
+mod snapshot;
+mod stats;
+mod sync;
+mod watch;
+
+/// Local index of all articles, kept up to date by `sync`
+const INDEX_NAME: &'static str = "articles";
+
+/// Articles per page of `fetch_library`
+const LIBRARY_PAGE_SIZE: usize = 100;
+
+fn ensure_consumer_key(config: &Config) -> Result<()> {
+    if config.pocket.consumer_key.is_empty() {
+        bail!("No consumer key available; please set 'consumer_key' in section '[pocket]'");
+    }
+
+    Ok(())
+}
+
+/// All articles of `state`, newest first, fetched page by page like `list --all`; deleted articles are left out. Pocket
+/// sends tags, word counts, and more only with `details`.
+fn fetch_library(config: &Config, state: State, details: bool) -> Result<Vec<Article>> {
+    let request = Request {
+        state: Some(state),
+        tag: None,
+        sort: Some(Sort::newest),
+        detailType: DetailType::from(details),
+        search: None,
+        since: None,
+        favorite: None,
+        count: None,
+        offset: None,
+    };
+    let mut articles = Vec::new();
+    client::list::list_all(config, &request, None, None, LIBRARY_PAGE_SIZE, |article| {
+        if !article.is_deleted() {
+            articles.push(article);
+        }
+        Ok(())
+    })?;
+
+    Ok(articles)
+}
+
+/// All articles as bookmark records; cf. `rat xfer`.
+pub fn bookmarks(ctx: &Context) -> Result<Vec<Bookmark>> {
+    ensure_consumer_key(ctx.config)?;
+    list::bookmarks(ctx)
+}
+
+pub fn add_bookmarks(ctx: &Context, bookmarks: &[Bookmark]) -> Result<()> {
+    ensure_consumer_key(ctx.config)?;
+    add::add_bookmarks(ctx, bookmarks)
+}
+
+/// Sends the actions queued while Pocket could not be reached; cf. `rat flush`.
+pub fn flush(ctx: &Context, discard_failed: bool) -> Result<()> {
+    ensure_consumer_key(ctx.config)?;
+    queue::flush(ctx, discard_failed)
+}
+
+fn open_index(config: &Config) -> Result<Index<Article>> {
+    Index::open(config, NAME, INDEX_NAME).chain_err(|| "failed to open local index")
+}
+
+/// Pocket does not support scopes, so a read-only login only marks the stored token as read-only.
+pub fn login(config: &Config, open_browser: bool, read_only: bool) -> Result<()> {
+    ensure_consumer_key(config)?;
+    client::auth(config, open_browser, false).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+    if read_only {
+        credentials::mark_read_only(config, NAME).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+    }
 
-#[derive(Debug, Deserialize)]
-pub struct PocketConfig {
-    pub consumer_key: String,
-    pub access_token: Option<String>,
+    Ok(())
 }
 
-pub fn build_sub_cli() -> App<'static, 'static> {
-    let mut subcommand = SubCommand::with_name(NAME)
-        .about("Pocket: When you find something you want to view later, put it in Pocket.")
-        .subcommand(auth::build_sub_cli())
-        .subcommand(list::build_sub_cli());
-    for s in actions::build_sub_cli() {
-        subcommand = subcommand.subcommand(s);
+pub struct Pocket;
+
+impl Module for Pocket {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn about(&self) -> &'static str {
+        "Pocket: When you find something you want to view later, put it in Pocket."
     }
 
-    subcommand
-}
-
-pub fn call(cli_args: Option<&ArgMatches>, config: &Config) -> Result<()> {
-    let subcommand = cli_args.unwrap();
-    let subcommand_name = subcommand.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
-    match subcommand_name {
-        auth::NAME => auth::call(subcommand.subcommand_matches(subcommand_name), config)
-            .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
-        list::NAME => list::call(subcommand.subcommand_matches(subcommand_name), config)
-            .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
-        actions::NAME_ARCHIVE | actions::NAME_READD | actions::NAME_FAVORITE
-        | actions::NAME_UNFAVORITE | actions::NAME_DELETE =>
-            actions::call(subcommand_name, subcommand.subcommand_matches(subcommand_name), config)
-            .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
-        _ => Ok(())
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        let mut subcommand = SubCommand::with_name(NAME)
+            .about(self.about())
+            .subcommand(add::build_sub_cli())
+            .subcommand(auth::build_sub_cli())
+            .subcommand(browse::build_sub_cli())
+            .subcommand(check::build_sub_cli())
+            .subcommand(dedupe::build_sub_cli())
+            .subcommand(diff::build_sub_cli())
+            .subcommand(digest::build_sub_cli())
+            .subcommand(export::build_sub_cli())
+            .subcommand(import::build_sub_cli())
+            .subcommand(list::build_sub_cli())
+            .subcommand(local::build_sub_cli())
+            .subcommand(open::build_sub_cli())
+            .subcommand(read::build_sub_cli())
+            .subcommand(rules::build_sub_cli())
+            .subcommand(snapshot::build_sub_cli())
+            .subcommand(stats::build_sub_cli())
+            .subcommand(sync::build_sub_cli())
+            .subcommand(watch::build_sub_cli());
+        for s in actions::build_sub_cli() {
+            subcommand = subcommand.subcommand(s);
+        }
+        for s in fulltext::build_sub_cli() {
+            subcommand = subcommand.subcommand(s);
+        }
+
+        subcommand
+    }
+
+    fn call(&self, cli_args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        let subcommand = cli_args.unwrap();
+        let subcommand_name = subcommand.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+        if ctx.all_accounts && subcommand_name != list::NAME {
+            bail!("'--account {}' is only supported by 'pocket {}'", ALL_ACCOUNTS, list::NAME);
+        }
+        // Accounts may have consumer keys of their own; cf. `list::call`
+        if !ctx.all_accounts {
+            ensure_consumer_key(ctx.config).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+        }
+        match subcommand_name {
+            add::NAME => add::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            auth::NAME => auth::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            browse::NAME => browse::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            check::NAME => check::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            dedupe::NAME => dedupe::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            diff::NAME => diff::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            digest::NAME => digest::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            export::NAME => export::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            import::NAME => import::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            list::NAME => list::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            local::NAME => local::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            open::NAME => open::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            read::NAME => read::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            rules::NAME => rules::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            snapshot::NAME => snapshot::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            stats::NAME => stats::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            sync::NAME => sync::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            watch::NAME => watch::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            actions::NAME_ARCHIVE | actions::NAME_READD | actions::NAME_FAVORITE
+            | actions::NAME_UNFAVORITE | actions::NAME_DELETE =>
+                actions::call(subcommand_name, subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            fulltext::NAME_INDEX | fulltext::NAME_QUERY =>
+                fulltext::call(subcommand_name, subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            _ => Ok(())
+        }
     }
 }
+
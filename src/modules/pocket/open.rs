@@ -0,0 +1,164 @@
+use super::{client, open_index};
+use super::client::list::{Article, DetailType, Request, Sort, State};
+use config::{Config, OutputFormat};
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use rand::{self, Rng};
+use serde_json;
+use webbrowser;
+
+pub const NAME: &'static str = "open";
+
+/// Arguments up to this number are positions in the list of unread articles; Pocket's article ids are much larger.
+const MAX_POSITION: usize = 9999;
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Open articles in the web browser")
+        .arg(Arg::with_name("article")
+            .index(1)
+            .multiple(true)
+            .required_unless_one(&["random", "newest"])
+            .help("article id, or position in the list of unread articles, newest first, e.g., 1 for the newest"))
+        .arg(Arg::with_name("random")
+            .long("random")
+            .conflicts_with_all(&["article", "newest"])
+            .help("Opens a random unread article"))
+        .arg(Arg::with_name("newest")
+            .long("newest")
+            .conflicts_with("article")
+            .help("Opens the newest unread article"))
+}
+
+/// An article to open
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Link {
+    pub item_id: String,
+    pub title: String,
+    pub url: String,
+}
+
+impl<'a> From<&'a Article> for Link {
+    fn from(article: &'a Article) -> Self {
+        Link { item_id: article.item_id.clone(), title: article.title().to_string(), url: article.url().to_string() }
+    }
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+
+    let links: Vec<Link> = if args.is_present("random") {
+        let unread = unread(config)?;
+        let article = rand::thread_rng().choose(&unread).ok_or_else(|| Error::from("There are no unread articles"))?;
+        vec![article.into()]
+    } else if args.is_present("newest") {
+        let unread = unread(config)?;
+        vec![unread.first().ok_or_else(|| Error::from("There are no unread articles"))?.into()]
+    } else {
+        resolve(config, &args.values_of("article").unwrap().collect::<Vec<_>>())?
+    };
+
+    match ctx.output_format() {
+        OutputFormat::HUMAN => {
+            for link in &links {
+                msgln(format!("Opening {} {}", link.title, link.url));
+                webbrowser::open(&link.url).chain_err(|| format!("Failed to open '{}' in the web browser", link.url))?;
+            }
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(&links).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+/// Resolves article ids and positions as described for the argument 'article'. Article ids are looked up in the local
+/// index first, so only unknown ids require the list of all articles.
+pub fn resolve(config: &Config, articles: &[&str]) -> Result<Vec<Link>> {
+    let index = open_index(config)?;
+    let mut unread = None;
+    let mut all = None;
+    let mut links = Vec::new();
+    for &article in articles {
+        let link = match article.parse::<usize>() {
+            Ok(position) if position <= MAX_POSITION => {
+                if unread.is_none() {
+                    unread = Some(self::unread(config)?);
+                }
+                at_position(unread.as_ref().unwrap(), position)?
+            }
+            _ => match index.get(article) {
+                Some(a) if !a.url().is_empty() => a.into(),
+                _ => {
+                    if all.is_none() {
+                        all = Some(list(config, State::all)?);
+                    }
+                    all.as_ref().unwrap().iter().find(|a| a.item_id == article).map(Link::from)
+                        .ok_or_else(|| Error::from(format!("There is no article with id {}", article)))?
+                }
+            },
+        };
+        links.push(link);
+    }
+
+    Ok(links)
+}
+
+fn at_position(articles: &[Article], position: usize) -> Result<Link> {
+    match position.checked_sub(1).and_then(|i| articles.get(i)) {
+        Some(article) => Ok(article.into()),
+        None => bail!("There is no unread article at position {}; there are {} unread article(s)", position, articles.len()),
+    }
+}
+
+/// Unread articles, newest first, as listed by 'pocket list'
+fn unread(config: &Config) -> Result<Vec<Article>> {
+    list(config, State::unread)
+}
+
+fn list(config: &Config, state: State) -> Result<Vec<Article>> {
+    let request = Request {
+        state: Some(state),
+        tag: None,
+        sort: Some(Sort::newest),
+        detailType: DetailType::simple,
+        search: None,
+        since: None,
+        favorite: None,
+        count: None,
+        offset: None,
+    };
+    let mut articles = Vec::new();
+    client::list::list_streamed(config, &request, None, None, |article| {
+        if !article.is_deleted() && !article.url().is_empty() {
+            articles.push(article);
+        }
+        Ok(())
+    }).chain_err(|| "failed to list Pocket articles")?;
+
+    Ok(articles)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_at_position() {
+        let articles: Vec<Article> = serde_json::from_str(r#"[
+            {"item_id":"2","resolved_title":"Newest","resolved_url":"https://example.com/2","status":"0"},
+            {"item_id":"1","resolved_title":"Oldest","resolved_url":"https://example.com/1","status":"0"}
+        ]"#).unwrap();
+
+        assert_eq!(at_position(&articles, 2).unwrap(),
+                   Link { item_id: "1".to_string(), title: "Oldest".to_string(), url: "https://example.com/1".to_string() });
+        assert!(at_position(&articles, 0).is_err());
+        assert!(at_position(&articles, 3).is_err());
+    }
+}
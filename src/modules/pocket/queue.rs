@@ -0,0 +1,206 @@
+use super::client;
+use super::NAME as MODULE_NAME;
+use cache::queue::{Entry, Queue};
+use config::{Config, OutputFormat};
+use credentials;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{Arg, ArgMatches};
+use serde::Serialize;
+use serde_json::{self, Value};
+use std::io::Write;
+use tabwriter::TabWriter;
+
+/// Send actions that could not be sent yet; cf. `rat flush`
+const QUEUE_NAME: &'static str = "queue";
+
+pub fn arg() -> Arg<'static, 'static> {
+    Arg::with_name("queue")
+        .long("queue")
+        .help("Queues the actions if Pocket cannot be reached, e.g., while offline; 'rat flush' sends them later")
+}
+
+/// Whether failed actions may be queued, by `--queue` or setting 'queue_offline' of section '[pocket]'.
+pub fn is_requested(args: &ArgMatches, config: &Config) -> bool {
+    args.is_present("queue") || config.pocket.queue_offline
+}
+
+/// Appends send actions to the queue of the selected account.
+pub fn push<T: Serialize>(config: &Config, actions: &[T]) -> Result<()> {
+    let actions = actions.iter()
+        .map(|a| serde_json::to_value(a).chain_err(|| "JSON serialization failed"))
+        .collect::<Result<Vec<Value>>>()?;
+    let count = actions.len();
+    Queue::open(config, MODULE_NAME, QUEUE_NAME).push(actions).chain_err(|| "failed to queue Pocket actions")?;
+    msgln(format!("Pocket is unreachable; queued {} action(s). Run 'rat flush' once you are online again.", count));
+
+    Ok(())
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+enum Status {
+    queued,
+    sent,
+    failed,
+}
+
+/// The result of replaying one queued action
+#[derive(Serialize, Debug)]
+struct Replayed {
+    action: String,
+    /// Article id or, for added articles, the URL
+    target: String,
+    queued: u64,
+    status: Status,
+}
+
+impl<'a> From<&'a Entry> for Replayed {
+    fn from(entry: &'a Entry) -> Self {
+        let field = |name: &str| entry.action.get(name).and_then(|v| v.as_str()).unwrap_or("").to_string();
+        let target = if entry.action.get("url").is_some() { field("url") } else { field("item_id") };
+        Replayed { action: field("action"), target: target, queued: entry.queued, status: Status::queued }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct FlushResult {
+    actions: Vec<Replayed>,
+    /// Actions left in the queue
+    remaining: usize,
+}
+
+/// Sends the queued actions in order. Actions Pocket rejects stay in the queue unless `discard_failed` is set; actions
+/// of batches that could not reach Pocket always stay, so if Pocket is still unreachable, the queue stays as it is.
+pub fn flush(ctx: &Context, discard_failed: bool) -> Result<()> {
+    let config = ctx.config;
+    let queue = Queue::open(config, MODULE_NAME, QUEUE_NAME);
+    let entries = queue.entries().chain_err(|| "failed to read queued Pocket actions")?;
+    if entries.is_empty() {
+        msgln("No queued Pocket actions.");
+        return Ok(());
+    }
+    let mut result = FlushResult { actions: entries.iter().map(Replayed::from).collect(), remaining: entries.len() };
+    if ctx.dry_run {
+        msgln(format!("Dry run: would send {} queued Pocket action(s).", entries.len()));
+        return output(&result, config.general.output_format);
+    }
+    credentials::ensure_writable(config, MODULE_NAME).chain_err(|| "failed to send queued Pocket actions")?;
+
+    info(format!("Sending {} queued action(s) ...", entries.len()));
+    let actions: Vec<&Value> = entries.iter().map(|e| &e.action).collect();
+    let mut progress = Progress::new(actions.len(), "actions", ctx.show_progress());
+    let sent = client::send_batched(config, &actions, config.pocket.batch_size(), |n| progress.inc(n));
+    progress.finish();
+    let json = sent.chain_err(|| "failed to send queued Pocket actions; they stay queued")?;
+    let json: Value = serde_json::from_str(&json).chain_err(|| "JSON parsing failed")?;
+    let results = json.get("action_results").and_then(|r| r.as_array()).ok_or("Pocket did not send action results")?;
+    let offline = |i: usize| json.get("action_errors")
+        .and_then(|errors| errors.get(i))
+        .and_then(|error| error.get("offline"))
+        .and_then(|offline| offline.as_bool())
+        .unwrap_or(false);
+
+    let mut kept = Vec::new();
+    for (i, ((replayed, entry), r)) in result.actions.iter_mut().zip(&entries).zip(results).enumerate() {
+        // Add actions result in the added item
+        if r.as_bool().unwrap_or_else(|| r.is_object()) {
+            replayed.status = Status::sent;
+        } else if offline(i) {
+            kept.push(entry.clone());
+        } else {
+            replayed.status = Status::failed;
+            if !discard_failed {
+                kept.push(entry.clone());
+            }
+        }
+    }
+    result.remaining = kept.len();
+    queue.replace_head(entries.len(), kept).chain_err(|| "failed to update queued Pocket actions")?;
+
+    output(&result, config.general.output_format)?;
+    let failed = result.actions.iter().filter(|a| a.status != Status::sent).count();
+    if failed > 0 {
+        bail!(ErrorKind::PartialFailure(result.actions.len() - failed, failed));
+    }
+
+    Ok(())
+}
+
+fn output(result: &FlushResult, format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::HUMAN => {
+            msg(render_human(result)?);
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(result).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(result: &FlushResult) -> Result<String> {
+    let mut tw = TabWriter::new(vec![]);
+    for a in &result.actions {
+        writeln!(&mut tw, "{:?}\t{}\t{}", a.status, a.action, a.target).chain_err(|| ErrorKind::OutputFailed)?;
+    }
+    tw.flush().chain_err(|| ErrorKind::OutputFailed)?;
+    let mut out = String::from_utf8(tw.into_inner().unwrap()).chain_err(|| ErrorKind::OutputFailed)?;
+    let sent = result.actions.iter().filter(|a| a.status == Status::sent).count();
+    out.push_str(&format!("{} action(s) sent; {} left in the queue.\n", sent, result.remaining));
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use modules::flush::Flush;
+    use modules::pocket::Pocket;
+    use net::mock::MockClient;
+    use testing::harness::*;
+
+    use std::rc::Rc;
+
+    #[test]
+    fn test_queue_offline_actions_and_flush() {
+        let config = config("pocket-queue", "[pocket]\nconsumer_key = 'key'\naccess_token = 'token'");
+        let ctx = Context::new(&config);
+        let queue = Queue::open(&config, MODULE_NAME, QUEUE_NAME);
+
+        // Without a queued response, the mock refuses all connections
+        run(&Pocket, &["archive", "--queue", "1", "2"], &ctx, &Rc::new(MockClient::new())).unwrap();
+        assert!(run(&Pocket, &["favorite", "3"], &ctx, &Rc::new(MockClient::new())).is_err());
+        assert_eq!(queue.entries().unwrap().len(), 2);
+
+        let mock = Rc::new(MockClient::new().respond(200, &[], r#"{"action_results":[true,false],"status":1}"#));
+        let result = run(&Flush, &[], &ctx, &mock);
+
+        assert_eq!(result.unwrap_err().category(), Category::PartialFailure);
+        assert!(mock.requests()[0].contains("%22action%22%3A%22archive%22"));
+        let entries = queue.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(Replayed::from(&entries[0]).target, "2");
+    }
+
+    #[test]
+    fn test_flush_keeps_actions_of_offline_batches() {
+        let config = config("pocket-queue-offline", "[pocket]\nconsumer_key = 'key'\naccess_token = 'token'\nbatch_size = 1");
+        let ctx = Context::new(&config);
+        let queue = Queue::open(&config, MODULE_NAME, QUEUE_NAME);
+        run(&Pocket, &["archive", "--queue", "1", "2"], &ctx, &Rc::new(MockClient::new())).unwrap();
+
+        // Pocket rejects the first action; then the connection is lost
+        let mock = Rc::new(MockClient::new().respond(200, &[], r#"{"action_results":[false],"status":1}"#));
+        let result = run(&Flush, &["--discard-failed"], &ctx, &mock);
+
+        assert_eq!(result.unwrap_err().category(), Category::PartialFailure);
+        let entries = queue.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(Replayed::from(&entries[0]).target, "2");
+    }
+}
@@ -0,0 +1,152 @@
+use super::open::{self, Link};
+use super::snapshot::{self, Page};
+use config::{Config, OutputFormat};
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::env;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+pub const NAME: &'static str = "read";
+
+/// Line width if stdout is not a terminal
+const DEFAULT_WIDTH: usize = 80;
+
+/// Longer lines are hard to read, even in wide terminals.
+const MAX_WIDTH: usize = 100;
+
+const DEFAULT_PAGER: &'static str = "less";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Read an article in the terminal")
+        .arg(Arg::with_name("article")
+            .index(1)
+            .required(true)
+            .help("article id, or position in the list of unread articles as for 'open'"))
+        .arg(Arg::with_name("width")
+            .long("width")
+            .takes_value(true)
+            .help("Wraps lines at <width> characters; defaults to the terminal width, but at most 100"))
+        .arg(Arg::with_name("pager")
+            .long("pager")
+            .help("Shows the article in $PAGER, or 'less' if unset"))
+}
+
+/// Readable content of an article
+#[derive(Serialize, Debug)]
+struct Content {
+    item_id: String,
+    title: String,
+    url: String,
+    paragraphs: Vec<String>,
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    let width = match args.value_of("width") {
+        Some(width) => width.parse::<usize>().chain_err(|| format!("Invalid width '{}'", width))?,
+        None => output::terminal_width().unwrap_or(DEFAULT_WIDTH).min(MAX_WIDTH),
+    };
+
+    let link = open::resolve(config, &[args.value_of("article").unwrap()])?.remove(0);
+    let page = content(config, &link).chain_err(|| format!("Failed to read article {}", link.item_id))?;
+    let title = if !page.title.is_empty() { page.title } else if !link.title.is_empty() { link.title } else { link.url.clone() };
+    let content = Content { item_id: link.item_id, title: title, url: link.url, paragraphs: page.paragraphs };
+
+    match ctx.output_format() {
+        OutputFormat::HUMAN => {
+            let text = render(&content, width);
+            if args.is_present("pager") {
+                page_through(&text)
+            } else {
+                io::stdout().write_all(text.as_bytes()).chain_err(|| ErrorKind::OutputFailed)
+            }
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(&content).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn content(config: &Config, link: &Link) -> Result<Page> {
+    page(&snapshot::default_dir(config), &link.item_id, &link.url)
+}
+
+/// Uses the snapshot of the article in `snapshot_dir` if there is one, so articles saved by 'snapshot' can be read
+/// offline.
+pub fn page(snapshot_dir: &Path, item_id: &str, url: &str) -> Result<Page> {
+    let path = snapshot_dir.join(format!("{}.html", item_id));
+    let mut html = String::new();
+    if File::open(&path).and_then(|mut f| f.read_to_string(&mut html)).is_ok() {
+        verboseln(format!("Reading snapshot '{}'", path.display()));
+        let mut page = snapshot::extract(&html)?;
+        // Snapshots start with title and URL
+        let header = page.paragraphs.iter().take_while(|p| **p == page.title || **p == url).count();
+        page.paragraphs.drain(..header);
+        return Ok(page);
+    }
+
+    let body = snapshot::fetch(url)?;
+    snapshot::extract(&String::from_utf8_lossy(&body))
+}
+
+/// Renders Markdown-like plain text: the title as heading, the URL, and the paragraphs wrapped at `width`.
+fn render(content: &Content, width: usize) -> String {
+    let mut text = format!("# {}\n\n<{}>\n", content.title, content.url);
+    for paragraph in &content.paragraphs {
+        text.push('\n');
+        for line in output::wrap(paragraph, width) {
+            text.push_str(&line);
+            text.push('\n');
+        }
+    }
+
+    text
+}
+
+fn page_through(text: &str) -> Result<()> {
+    let pager = env::var("PAGER").ok().and_then(|p| if p.trim().is_empty() { None } else { Some(p) })
+        .unwrap_or_else(|| DEFAULT_PAGER.to_string());
+    let mut child = Command::new("sh").arg("-c").arg(&pager).stdin(Stdio::piped()).spawn()
+        .chain_err(|| format!("Failed to start pager '{}'", pager))?;
+    {
+        let stdin = child.stdin.as_mut().unwrap();
+        // The pager closes its input if the user quits early.
+        match stdin.write_all(text.as_bytes()) {
+            Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+            result => result.chain_err(|| ErrorKind::OutputFailed)?,
+        }
+    }
+    child.wait().chain_err(|| format!("Pager '{}' failed", pager))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let content = Content {
+            item_id: "1".to_string(),
+            title: "Rust 1.20".to_string(),
+            url: "https://blog.rust-lang.org/1.20".to_string(),
+            paragraphs: vec!["Associated constants are finally here.".to_string(), "Enjoy!".to_string()],
+        };
+
+        assert_eq!(render(&content, 20), "# Rust 1.20\n\n<https://blog.rust-lang.org/1.20>\n\
+                                          \nAssociated constants\nare finally here.\n\
+                                          \nEnjoy!\n");
+    }
+}
@@ -0,0 +1,349 @@
+use super::{client, fetch_library};
+use super::client::list::{Article, State};
+use super::NAME as MODULE_NAME;
+use clients::pocket::RuleConfig;
+use config::{Config, OutputFormat};
+use credentials;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use regex::Regex;
+use serde_json;
+use std::collections::BTreeSet;
+
+pub const NAME: &'static str = "apply-rules";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Apply the rules of section '[rules]' to all articles, e.g., to tag or archive them; preview with '--dry-run'")
+        .arg(Arg::with_name("rule")
+            .long("rule")
+            .short("r")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .help("Applies only this rule; may be given multiple times"))
+}
+
+/// A rule with its regular expressions compiled
+#[derive(Debug)]
+struct Rule<'a> {
+    name: &'a str,
+    config: &'a RuleConfig,
+    title: Option<Regex>,
+    url: Option<Regex>,
+}
+
+/// Tags, archived, and favorite of an article as changed by the rules applied so far
+#[derive(Debug, Clone, PartialEq)]
+struct Marks {
+    tags: BTreeSet<String>,
+    archived: bool,
+    favorite: bool,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+struct Action {
+    action: &'static str,
+    item_id: String,
+    /// Comma separated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tags: Option<String>,
+}
+
+/// The actions for an article and the rules that matched it
+#[derive(Serialize, Debug, PartialEq)]
+struct Change {
+    item_id: String,
+    title: String,
+    url: String,
+    rules: Vec<String>,
+    actions: Vec<Action>,
+}
+
+#[derive(Serialize, Debug)]
+struct RulesResult {
+    changes: Vec<Change>,
+    /// Action result per action if the actions have been sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    action_results: Option<Vec<bool>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ActionResults {
+    action_results: Vec<bool>,
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    let selected: Option<Vec<&str>> = args.values_of("rule").map(|names| names.collect());
+    let rules = compile(config, selected.as_ref().map(|names| names.as_slice()))?;
+    if !ctx.dry_run {
+        credentials::ensure_writable(config, MODULE_NAME).chain_err(|| "failed to apply rules to Pocket articles")?;
+    }
+
+    let needs_words = rules.iter().any(|r| r.config.min_words.is_some() || r.config.max_words.is_some());
+    let articles = fetch_library(config, State::all, needs_words).chain_err(|| "failed to apply rules to Pocket articles")?;
+
+    let mut result = RulesResult { changes: plan(&rules, &articles), action_results: None };
+    let actions: Vec<&Action> = result.changes.iter().flat_map(|c| c.actions.iter()).collect();
+    if !actions.is_empty() {
+        if ctx.dry_run {
+            msgln(format!("Dry run: would send {} action(s) for {} article(s).", actions.len(), result.changes.len()));
+        } else {
+            info(format!("Sending {} action(s) for {} article(s) ...", actions.len(), result.changes.len()));
+            let mut progress = Progress::new(actions.len(), "actions", ctx.show_progress());
+            let sent = client::send_batched(config, &actions, config.pocket.batch_size(), |n| progress.inc(n));
+            progress.finish();
+            let json = sent.chain_err(|| "failed to apply rules to Pocket articles")?;
+            let sent: ActionResults = serde_json::from_str(&json).chain_err(|| "JSON parsing failed")?;
+            result.action_results = Some(sent.action_results);
+        }
+    }
+
+    output(&result, &config.general.output_format)?;
+    if let Some(ref results) = result.action_results {
+        let failed = results.iter().filter(|r| !**r).count();
+        if failed > 0 {
+            bail!(ErrorKind::PartialFailure(results.len() - failed, failed));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks and compiles the configured rules, or the selected ones, in order of their names.
+fn compile<'a>(config: &'a Config, selected: Option<&[&str]>) -> Result<Vec<Rule<'a>>> {
+    if config.rules.is_empty() {
+        bail!("No rules configured; please add rules in sections '[rules.<name>]'");
+    }
+    if let Some(selected) = selected {
+        if let Some(unknown) = selected.iter().find(|name| !config.rules.contains_key(**name)) {
+            bail!("Unknown rule '{}'; configured rules are {}", unknown,
+                  config.rules.keys().map(|name| format!("'{}'", name)).collect::<Vec<_>>().join(", "));
+        }
+    }
+
+    let regex = |name: &str, setting: &str, pattern: &Option<String>| -> Result<Option<Regex>> {
+        match *pattern {
+            Some(ref pattern) => Regex::new(pattern)
+                .map(Some)
+                .chain_err(|| format!("Invalid regular expression '{}' of 'rules.{}.{}'", pattern, name, setting)),
+            None => Ok(None),
+        }
+    };
+    config.rules.iter()
+        .filter(|&(name, _)| selected.map(|selected| selected.contains(&name.as_str())).unwrap_or(true))
+        .map(|(name, rule)| {
+            if !rule.has_actions() {
+                bail!("Rule '{}' has no actions; please set 'add_tags', 'remove_tags', 'archive', or 'favorite'", name);
+            }
+            match rule.state.as_ref().map(|s| s.as_str()) {
+                None | Some("unread") | Some("archive") => {}
+                Some(state) => bail!("Invalid state '{}' of 'rules.{}.state'; expected 'unread' or 'archive'", state, name),
+            }
+            Ok(Rule {
+                name: name,
+                config: rule,
+                title: regex(name, "title", &rule.title)?,
+                url: regex(name, "url", &rule.url)?,
+            })
+        })
+        .collect()
+}
+
+/// Applies the rules in order to every article, so a rule sees the tags added or removed by the rules before it, and
+/// returns the actions for the articles whose tags, archived, or favorite changed.
+fn plan(rules: &[Rule], articles: &[Article]) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for article in articles {
+        let original = Marks {
+            tags: article.tag_names().into_iter().map(|t| t.to_string()).collect(),
+            archived: article.status == "1",
+            favorite: article.favorite == "1",
+        };
+        let mut marks = original.clone();
+        let mut matched = Vec::new();
+        for rule in rules {
+            if !matches(rule, article, &marks) {
+                continue;
+            }
+            for tag in &rule.config.add_tags {
+                marks.tags.insert(tag.clone());
+            }
+            for tag in &rule.config.remove_tags {
+                marks.tags.remove(tag);
+            }
+            marks.archived |= rule.config.archive;
+            marks.favorite |= rule.config.favorite;
+            matched.push(rule.name.to_string());
+        }
+        if marks == original {
+            continue;
+        }
+
+        let mut actions = Vec::new();
+        let mut push = |action: &'static str, tags: Option<String>| {
+            actions.push(Action { action: action, item_id: article.item_id.clone(), tags: tags });
+        };
+        let added: Vec<&str> = marks.tags.difference(&original.tags).map(|t| t.as_str()).collect();
+        if !added.is_empty() {
+            push("tags_add", Some(added.join(",")));
+        }
+        let removed: Vec<&str> = original.tags.difference(&marks.tags).map(|t| t.as_str()).collect();
+        if !removed.is_empty() {
+            push("tags_remove", Some(removed.join(",")));
+        }
+        if marks.archived && !original.archived {
+            push("archive", None);
+        }
+        if marks.favorite && !original.favorite {
+            push("favorite", None);
+        }
+        changes.push(Change {
+            item_id: article.item_id.clone(),
+            title: article.title().to_string(),
+            url: article.url().to_string(),
+            rules: matched,
+            actions: actions,
+        });
+    }
+
+    changes
+}
+
+fn matches(rule: &Rule, article: &Article, marks: &Marks) -> bool {
+    let config = rule.config;
+    let domain = config.domain.as_ref().map(|domain| {
+        let domain = domain.to_lowercase();
+        article.domain().map(|d| d == domain || d.ends_with(&format!(".{}", domain))).unwrap_or(false)
+    });
+    let state = config.state.as_ref().map(|state| marks.archived == (state == "archive"));
+    let words = article.words();
+    let min_words = config.min_words.map(|min| words.map(|w| w >= min).unwrap_or(false));
+    let max_words = config.max_words.map(|max| words.map(|w| w <= max).unwrap_or(false));
+
+    domain.unwrap_or(true)
+        && rule.title.as_ref().map(|r| r.is_match(article.title())).unwrap_or(true)
+        && rule.url.as_ref().map(|r| r.is_match(article.url())).unwrap_or(true)
+        && config.tag.as_ref().map(|tag| marks.tags.contains(tag)).unwrap_or(true)
+        && state.unwrap_or(true)
+        && min_words.unwrap_or(true)
+        && max_words.unwrap_or(true)
+}
+
+fn output(result: &RulesResult, format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            msg(render_human(result));
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(result).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(result: &RulesResult) -> String {
+    let mut out = String::new();
+    for c in &result.changes {
+        let actions: Vec<String> = c.actions.iter().map(|a| match (a.action, a.tags.as_ref()) {
+            ("tags_add", Some(tags)) => tags.split(',').map(|t| format!("+{}", t)).collect::<Vec<_>>().join(" "),
+            ("tags_remove", Some(tags)) => tags.split(',').map(|t| format!("-{}", t)).collect::<Vec<_>>().join(" "),
+            (action, _) => action.to_string(),
+        }).collect();
+        out.push_str(&format!("* {}: '{}' {}\n", c.item_id, c.title, c.url));
+        out.push_str(&format!("    {} (rules: {})\n", actions.join(" "), c.rules.join(", ")));
+    }
+    let actions: usize = result.changes.iter().map(|c| c.actions.len()).sum();
+    out.push_str(&format!("Found {} action(s) for {} article(s).\n", actions, result.changes.len()));
+    if let Some(ref results) = result.action_results {
+        let successful = results.iter().filter(|r| **r).count();
+        out.push_str(&format!("Applied {} of {} action(s).\n", successful, results.len()));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use modules::pocket::Pocket;
+    use net::mock::MockClient;
+    use testing::harness::*;
+
+    use std::rc::Rc;
+
+    const RULES: &'static str = r#"
+        [pocket]
+        consumer_key = 'key'
+        access_token = 'token'
+
+        [rules.a-github]
+        domain = 'github.com'
+        add_tags = ['code']
+
+        [rules.b-long-code]
+        tag = 'code'
+        min_words = 3000
+        add_tags = ['long']
+        remove_tags = ['later']
+        archive = true
+
+        [rules.c-rust]
+        title = '(?i)\brust\b'
+        state = 'unread'
+        favorite = true
+    "#;
+
+    fn articles() -> Vec<Article> {
+        serde_json::from_str(r#"[
+            {"item_id":"1","resolved_title":"Rust tools","resolved_url":"https://gist.github.com/1","status":"0","favorite":"0",
+             "word_count":"4000","tags":{"later":{"item_id":"1","tag":"later"}}},
+            {"item_id":"2","resolved_title":"Trust","resolved_url":"https://example.com/2","status":"0","favorite":"0"},
+            {"item_id":"3","resolved_title":"Rust","resolved_url":"https://github.com/3","status":"1","favorite":"0",
+             "tags":{"code":{"item_id":"3","tag":"code"}}}
+        ]"#).unwrap()
+    }
+
+    #[test]
+    fn test_plan() {
+        let config = config("pocket-rules-plan", RULES);
+        let rules = compile(&config, None).unwrap();
+
+        let changes = plan(&rules, &articles());
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].rules, vec!["a-github", "b-long-code"]);
+        let actions = serde_json::to_string(&changes[0].actions).unwrap();
+        assert_eq!(actions, r#"[{"action":"tags_add","item_id":"1","tags":"code,long"},{"action":"tags_remove","item_id":"1","tags":"later"},{"action":"archive","item_id":"1"}]"#);
+    }
+
+    #[test]
+    fn test_compile_rejects_invalid_rules() {
+        let config = config("pocket-rules-invalid", "[rules.broken]\ntitle = '('\narchive = true\n\n[rules.idle]\ndomain = 'example.com'");
+
+        assert!(compile(&config, Some(&["broken"])).unwrap_err().to_string().contains("rules.broken.title"));
+        assert!(compile(&config, Some(&["idle"])).unwrap_err().to_string().contains("no actions"));
+        assert!(compile(&config, Some(&["missing"])).unwrap_err().to_string().contains("Unknown rule 'missing'"));
+    }
+
+    #[test]
+    fn test_dry_run_sends_no_actions() {
+        let config = config("pocket-rules-dry-run", RULES);
+        let mock = Rc::new(MockClient::new()
+            .respond(200, &[], r#"{"status":1,"complete":1,"list":{"2":{"item_id":"2","resolved_title":"Rust","resolved_url":"https://example.com/2","status":"0"}}}"#));
+
+        run(&Pocket, &["apply-rules"], &Context::new(&config).with_dry_run(true), &mock).unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("/v3/get"));
+        assert!(requests[0].contains("complete"));
+    }
+}
@@ -0,0 +1,355 @@
+use super::open_index;
+use super::client::list::Article;
+use cache::Cache;
+use config::{Config, OutputFormat};
+use errors::*;
+use modules::Context;
+use net::concurrent::Pipeline;
+use net::http;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use hyper::header::Connection;
+use indicatif::{ProgressBar, ProgressStyle};
+use serde_json;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+pub const NAME: &'static str = "snapshot";
+
+const CACHE_NAME: &'static str = "snapshots";
+
+/// Pages are truncated beyond this size
+const MAX_PAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Workers of each of the CPU-bound stages; fetching uses `general.max_concurrency` workers
+const CPU_WORKERS: usize = 2;
+
+static STAGES: &'static [&'static str] = &["fetch", "extract", "convert", "write"];
+
+/// Elements whose content is never part of the article
+static SKIPPED_ELEMENTS: &'static [&'static str] = &[
+    "aside", "footer", "form", "header", "nav", "noscript", "script", "style", "svg",
+];
+
+/// Elements separating paragraphs
+static BLOCK_ELEMENTS: &'static [&'static str] = &[
+    "article", "blockquote", "br", "div", "h1", "h2", "h3", "h4", "h5", "h6", "li", "p", "pre", "section", "tr",
+];
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Saves readable copies of articles of the local index for offline reading; cf. 'sync'")
+        .arg(Arg::with_name("ids")
+            .index(1)
+            .multiple(true)
+            .help("ids of articles to save; all unread articles without a snapshot if omitted"))
+        .arg(Arg::with_name("dir")
+            .long("dir")
+            .takes_value(true)
+            .value_name("DIR")
+            .help("Directory for the snapshots; defaults to the cache directory"))
+        .arg(Arg::with_name("force")
+            .long("force")
+            .help("Replaces existing snapshots"))
+}
+
+struct Job {
+    id: String,
+    url: String,
+    path: PathBuf,
+}
+
+/// Readable content of a web page
+#[derive(Debug, PartialEq)]
+pub struct Page {
+    pub title: String,
+    pub paragraphs: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct SnapshotResult {
+    written: Vec<String>,
+    failed: Vec<Failure>,
+}
+
+#[derive(Serialize, Debug)]
+struct Failure {
+    id: String,
+    reason: String,
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    let dir = args.value_of("dir").map(PathBuf::from).unwrap_or_else(|| default_dir(config));
+
+    let index = open_index(config)?;
+    let articles: Vec<&Article> = match args.values_of("ids") {
+        Some(ids) => ids
+            .map(|id| index.get(id).ok_or_else(|| {
+                Error::from(format!("article {} is not in the local index; please run 'rat pocket sync' first", id))
+            }))
+            .collect::<Result<_>>()?,
+        None => index.items().into_iter().filter(|a| a.status == "0").collect(),
+    };
+    let jobs: Vec<Job> = articles.into_iter()
+        .filter(|a| !a.url().is_empty())
+        .map(|a| Job { id: a.item_id.clone(), url: a.url().to_string(), path: dir.join(format!("{}.html", a.item_id)) })
+        .filter(|job| args.is_present("force") || !job.path.exists())
+        .collect();
+    fs::create_dir_all(&dir).chain_err(|| format!("Failed to create snapshot directory '{}'", dir.display()))?;
+
+    let count = jobs.len();
+    let progress_bar = if ctx.show_progress() && count > 0 {
+        let pb = ProgressBar::new(count as u64);
+        pb.set_style(ProgressStyle::default_bar()
+            .template("[{elapsed_precise}] [{bar:40.blue/blue}] {pos}/{len} {msg} {spinner:.blue}")
+        );
+        Some(pb)
+    } else {
+        None
+    };
+    let results = snapshot(jobs, config.general.max_concurrency(), progress_bar);
+
+    let mut result = SnapshotResult { written: Vec::new(), failed: Vec::new() };
+    for (job, written) in results {
+        match written {
+            Ok(()) => result.written.push(job.path.display().to_string()),
+            Err(e) => {
+                error(format!("Failed to save snapshot of article {} because {}", job.id, e));
+                result.failed.push(Failure { id: job.id, reason: e.to_string() });
+            }
+        }
+    }
+
+    output(&result, &dir, &config.general.output_format)?;
+    if !result.failed.is_empty() {
+        bail!(ErrorKind::PartialFailure(result.written.len(), result.failed.len()));
+    }
+
+    Ok(())
+}
+
+pub fn default_dir(config: &Config) -> PathBuf {
+    Cache::new(config, super::NAME, CACHE_NAME).dir()
+}
+
+/// Downloads, extraction, conversion, and writing run concurrently in their own pools; cf. `Pipeline`.
+fn snapshot(jobs: Vec<Job>, fetch_workers: usize, progress_bar: Option<ProgressBar>) -> Vec<(Job, Result<()>)> {
+    let mut pipeline = Pipeline::new(jobs);
+    if let Some(pb) = progress_bar {
+        let finished: Mutex<HashMap<&'static str, usize>> = Mutex::new(HashMap::new());
+        pipeline = pipeline.with_progress(move |stage, count| {
+            let mut finished = finished.lock().unwrap();
+            finished.insert(stage, count);
+            let message: Vec<String> = STAGES.iter()
+                .map(|s| format!("{} {}", s, finished.get(s).cloned().unwrap_or(0)))
+                .collect();
+            pb.set_message(&message.join(", "));
+            if stage == "write" {
+                pb.set_position(count as u64);
+            }
+        });
+    }
+
+    pipeline
+        .stage("fetch", fetch_workers, |job: Job| {
+            let body = fetch(&job.url);
+            (job, body)
+        })
+        .stage("extract", CPU_WORKERS, |(job, body): (Job, Result<Vec<u8>>)| {
+            let page = body.and_then(|body| extract(&String::from_utf8_lossy(&body)));
+            (job, page)
+        })
+        .stage("convert", CPU_WORKERS, |(job, page): (Job, Result<Page>)| {
+            let html = page.map(|page| convert(&page, &job.url));
+            (job, html)
+        })
+        .stage("write", 1, |(job, html): (Job, Result<String>)| {
+            let written = html.and_then(|html| write(&job.path, &html));
+            (job, written)
+        })
+        .collect()
+}
+
+pub fn fetch(url: &str) -> Result<Vec<u8>> {
+    let client = http::tls_client().chain_err(|| "Could not create TLS client")?;
+    let mut response = client.get(url).header(Connection::close()).send()
+        .chain_err(|| ErrorKind::Network(format!("could not fetch '{}'", url)))?;
+    http::check_status(&mut response)?;
+
+    let mut body = Vec::new();
+    response.take(MAX_PAGE_BYTES).read_to_end(&mut body).chain_err(|| "Could not read response body")?;
+
+    Ok(body)
+}
+
+/// Keeps the text of the first 'article' element, or else of the body, without navigation, scripts, and the like.
+pub fn extract(html: &str) -> Result<Page> {
+    let title = element(html, "title").map(|title| normalize(title)).unwrap_or_default();
+    let content = element(html, "article").or_else(|| element(html, "body")).unwrap_or(html);
+    let paragraphs = paragraphs(content);
+    if paragraphs.is_empty() {
+        bail!("page has no readable content");
+    }
+
+    Ok(Page { title: title, paragraphs: paragraphs })
+}
+
+/// Inner HTML of the first element `name`; an element without end tag extends to the end of the document.
+fn element<'a>(html: &'a str, name: &str) -> Option<&'a str> {
+    // ASCII lower case keeps byte offsets valid for `html`
+    let lower = html.to_ascii_lowercase();
+    let open = format!("<{}", name);
+    let mut from = 0;
+    while let Some(i) = lower[from..].find(&open) {
+        let start = from + i;
+        let after_name = start + open.len();
+        from = after_name;
+        match lower[after_name..].chars().next() {
+            Some(c) if c == '>' || c.is_whitespace() => {}
+            _ => continue,
+        }
+        let content_start = after_name + lower[after_name..].find('>')? + 1;
+        let content_end = lower[content_start..].find(&format!("</{}", name))
+            .map(|i| content_start + i)
+            .unwrap_or_else(|| html.len());
+        return Some(&html[content_start..content_end]);
+    }
+
+    None
+}
+
+fn paragraphs(html: &str) -> Vec<String> {
+    let mut paragraphs = Vec::new();
+    let mut text = String::new();
+    let mut skipping: Option<String> = None;
+    let mut rest = html;
+    while let Some(lt) = rest.find('<') {
+        if skipping.is_none() {
+            text.push_str(&rest[..lt]);
+        }
+        rest = &rest[lt..];
+        if rest.starts_with("<!--") {
+            rest = rest.find("-->").map(|i| &rest[i + 3..]).unwrap_or("");
+            continue;
+        }
+        let gt = match rest.find('>') {
+            Some(gt) => gt,
+            None => { rest = ""; break }
+        };
+        let tag = &rest[1..gt];
+        rest = &rest[gt + 1..];
+
+        let closing = tag.starts_with('/');
+        let name: String = tag.chars().skip_while(|&c| c == '/').take_while(|c| c.is_alphanumeric()).collect();
+        let name = name.to_ascii_lowercase();
+        if let Some(skipped) = skipping.take() {
+            if !(closing && name == skipped) {
+                skipping = Some(skipped);
+            }
+            continue;
+        }
+        if !closing && !tag.ends_with('/') && SKIPPED_ELEMENTS.contains(&name.as_str()) {
+            skipping = Some(name);
+        } else if BLOCK_ELEMENTS.contains(&name.as_str()) {
+            push_paragraph(&mut paragraphs, &mut text);
+        }
+    }
+    if skipping.is_none() {
+        text.push_str(rest);
+    }
+    push_paragraph(&mut paragraphs, &mut text);
+
+    paragraphs
+}
+
+fn push_paragraph(paragraphs: &mut Vec<String>, text: &mut String) {
+    let paragraph = normalize(text);
+    if !paragraph.is_empty() {
+        paragraphs.push(paragraph);
+    }
+    text.clear();
+}
+
+/// Collapses white space and decodes the most common entities.
+pub fn normalize(text: &str) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    words.join(" ")
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Renders a page as a minimal standalone HTML document that any reader can display.
+fn convert(page: &Page, url: &str) -> String {
+    let title = escape(if page.title.is_empty() { url } else { &page.title });
+    let mut html = format!("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n</head>\n<body>\n\
+                            <h1>{0}</h1>\n<p><a href=\"{1}\">{1}</a></p>\n", title, escape(url));
+    for paragraph in &page.paragraphs {
+        html.push_str(&format!("<p>{}</p>\n", escape(paragraph)));
+    }
+    html.push_str("</body>\n</html>\n");
+
+    html
+}
+
+fn write(path: &Path, html: &str) -> Result<()> {
+    File::create(path).and_then(|mut f| f.write_all(html.as_bytes()))
+        .chain_err(|| format!("Failed to write snapshot '{}'", path.display()))
+}
+
+fn output(result: &SnapshotResult, dir: &Path, format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            msgln(format!("Saved {} snapshot(s) to '{}'.", result.written.len(), dir.display()));
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(result).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_extract_and_convert() {
+        let html = r#"<html><head><TITLE>Rust 1.20 &amp; more</TITLE><script>var x = "<p>";</script></head>
+            <body><nav><a href="/">Home</a></nav>
+            <Article class="post"><h1>Rust 1.20</h1><p>Associated
+               constants are <em>finally</em> here.<br>Enjoy!</p><!-- <p>comment</p> -->
+            <footer>Share</footer></Article><aside>Ads</aside></body></html>"#;
+
+        let page = extract(html).unwrap();
+
+        assert_eq!(page, Page {
+            title: "Rust 1.20 & more".to_string(),
+            paragraphs: vec!["Rust 1.20".to_string(), "Associated constants are finally here.".to_string(), "Enjoy!".to_string()],
+        });
+        let converted = convert(&page, "https://blog.rust-lang.org/1.20");
+        assert!(converted.contains("<title>Rust 1.20 &amp; more</title>"));
+        assert!(converted.contains("<p>Associated constants are finally here.</p>\n<p>Enjoy!</p>\n</body>"));
+    }
+
+    #[test]
+    fn test_extract_without_content_fails() {
+        assert!(extract("<html><body><script>alert(1)</script></body></html>").is_err());
+    }
+}
@@ -0,0 +1,214 @@
+use super::fetch_library;
+use super::client::list::{Article, State};
+use config::OutputFormat;
+use errors::*;
+use modules::Context;
+use utils::output;
+
+use chrono::NaiveDateTime;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+pub const NAME: &'static str = "stats";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Show statistics of the library, e.g., articles by state, tag, and domain")
+        .arg(Arg::with_name("top")
+            .long("top")
+            .takes_value(true)
+            .default_value("10")
+            .help("Shows only the <top> tags and domains with the most articles in human output"))
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+struct Stats {
+    total: usize,
+    unread: usize,
+    archived: usize,
+    favorites: usize,
+    by_tag: BTreeMap<String, usize>,
+    by_domain: BTreeMap<String, usize>,
+    /// By month as 'YYYY-MM'
+    added_per_month: BTreeMap<String, usize>,
+    /// Of the articles Pocket has counted the words of
+    #[serde(skip_serializing_if = "Option::is_none")]
+    average_word_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    oldest_unread: Option<OldestUnread>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+struct OldestUnread {
+    item_id: String,
+    title: String,
+    url: String,
+    time_added: String,
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    let top = args.value_of("top").unwrap();
+    let top = top.parse::<usize>().chain_err(|| format!("Invalid top '{}'", top))?;
+
+    // Pocket sends tags and word counts only with details
+    let articles = fetch_library(config, State::all, true).chain_err(|| "failed to compute Pocket statistics")?;
+
+    let stats = compute(&articles);
+    match ctx.output_format() {
+        OutputFormat::HUMAN => {
+            let stdout = io::stdout();
+            let mut out = stdout.lock();
+            out.write_all(render_human(&stats, top).as_bytes()).chain_err(|| ErrorKind::OutputFailed)
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(&stats).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn compute(articles: &[Article]) -> Stats {
+    let mut stats = Stats {
+        total: articles.len(),
+        unread: 0,
+        archived: 0,
+        favorites: 0,
+        by_tag: BTreeMap::new(),
+        by_domain: BTreeMap::new(),
+        added_per_month: BTreeMap::new(),
+        average_word_count: None,
+        oldest_unread: None,
+    };
+    let (mut words, mut counted) = (0, 0);
+    let mut oldest_unread: Option<(u64, &Article)> = None;
+    for a in articles {
+        match a.status.as_str() {
+            "1" => stats.archived += 1,
+            _ => stats.unread += 1,
+        }
+        if a.favorite == "1" {
+            stats.favorites += 1;
+        }
+        for tag in a.tag_names() {
+            *stats.by_tag.entry(tag.to_string()).or_insert(0) += 1;
+        }
+        if let Some(domain) = a.domain() {
+            *stats.by_domain.entry(domain).or_insert(0) += 1;
+        }
+        if let Some(w) = a.words() {
+            words += w;
+            counted += 1;
+        }
+        if let Ok(added) = a.time_added() {
+            let month = NaiveDateTime::from_timestamp(added.as_secs() as i64, 0).format("%Y-%m").to_string();
+            *stats.added_per_month.entry(month).or_insert(0) += 1;
+            let is_older = oldest_unread.map(|(oldest, _)| added.as_secs() < oldest).unwrap_or(true);
+            if a.status != "1" && is_older {
+                oldest_unread = Some((added.as_secs(), a));
+            }
+        }
+    }
+    if counted > 0 {
+        stats.average_word_count = Some(words / counted);
+    }
+    stats.oldest_unread = oldest_unread.map(|(_, a)| OldestUnread {
+        item_id: a.item_id.clone(),
+        title: a.title().to_string(),
+        url: a.url().to_string(),
+        time_added: a.time_added.clone(),
+    });
+
+    stats
+}
+
+/// Most frequent first, and alphabetically among equally frequent ones
+fn top_counts(counts: &BTreeMap<String, usize>, top: usize) -> Vec<(&str, usize)> {
+    let mut sorted: Vec<(&str, usize)> = counts.iter().map(|(name, count)| (name.as_str(), *count)).collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1));
+    sorted.truncate(top);
+    sorted
+}
+
+fn render_human(stats: &Stats, top: usize) -> String {
+    let mut out = String::new();
+
+    let mut overview = output::Table::new(vec!["ARTICLES", "COUNT"]);
+    overview.add_row(vec!["total".to_string(), stats.total.to_string()]);
+    overview.add_row(vec!["unread".to_string(), stats.unread.to_string()]);
+    overview.add_row(vec!["archived".to_string(), stats.archived.to_string()]);
+    overview.add_row(vec!["favorites".to_string(), stats.favorites.to_string()]);
+    if let Some(words) = stats.average_word_count {
+        overview.add_row(vec!["average words".to_string(), words.to_string()]);
+    }
+    out.push_str(&overview.render());
+
+    for &(header, counts) in &[("TAG", &stats.by_tag), ("DOMAIN", &stats.by_domain)] {
+        let rows = top_counts(counts, top);
+        if rows.is_empty() {
+            continue;
+        }
+        let mut table = output::Table::new(vec![header, "COUNT"]).max_width(output::terminal_width());
+        for (name, count) in rows {
+            table.add_row(vec![name.to_string(), count.to_string()]);
+        }
+        out.push('\n');
+        out.push_str(&table.render());
+    }
+
+    if !stats.added_per_month.is_empty() {
+        let mut table = output::Table::new(vec!["ADDED IN", "COUNT"]);
+        for (month, count) in &stats.added_per_month {
+            table.add_row(vec![month.clone(), count.to_string()]);
+        }
+        out.push('\n');
+        out.push_str(&table.render());
+    }
+
+    if let Some(ref oldest) = stats.oldest_unread {
+        let added = oldest.time_added.parse::<i64>().ok()
+            .map(|secs| NaiveDateTime::from_timestamp(secs, 0).format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        out.push_str(&format!("\nOldest unread article, added {}: {}: '{}' {}\n", added, oldest.item_id, oldest.title, oldest.url));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn articles() -> Vec<Article> {
+        serde_json::from_str(r#"[
+            {"item_id":"1","resolved_title":"Rust 1.20","resolved_url":"https://www.rust-lang.org/1.20","time_added":"1504188000",
+             "status":"0","favorite":"1","word_count":"1000","tags":{"rust":{"item_id":"1","tag":"rust"}}},
+            {"item_id":"2","resolved_title":"Go","resolved_url":"https://golang.org","time_added":"1500000000","status":"1",
+             "word_count":"500","tags":{"go":{"item_id":"2","tag":"go"},"rust":{"item_id":"2","tag":"rust"}}},
+            {"item_id":"3","resolved_title":"Cargo","resolved_url":"https://rust-lang.org/cargo","time_added":"1504000000","status":"0"}
+        ]"#).unwrap()
+    }
+
+    #[test]
+    fn test_compute() {
+        let stats = compute(&articles());
+
+        assert_eq!((stats.total, stats.unread, stats.archived, stats.favorites), (3, 2, 1, 1));
+        assert_eq!(stats.by_tag.get("rust"), Some(&2));
+        assert_eq!(stats.by_domain.get("rust-lang.org"), Some(&2));
+        assert_eq!(stats.added_per_month.iter().map(|(m, c)| (m.as_str(), *c)).collect::<Vec<_>>(), vec![("2017-07", 1), ("2017-08", 2)]);
+        assert_eq!(stats.average_word_count, Some(750));
+        assert_eq!(stats.oldest_unread.map(|o| o.item_id), Some("3".to_string()));
+    }
+
+    #[test]
+    fn test_top_counts() {
+        let stats = compute(&articles());
+
+        assert_eq!(top_counts(&stats.by_tag, 1), vec![("rust", 2)]);
+        assert_eq!(top_counts(&stats.by_tag, 10), vec![("rust", 2), ("go", 1)]);
+    }
+}
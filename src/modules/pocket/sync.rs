@@ -0,0 +1,92 @@
+use super::{client, open_index};
+use super::client::list::{Article, DetailType, Request, State};
+use cache::index::{Delta, Index};
+use config::{Config, OutputFormat};
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+use utils::time::unix_now;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+
+pub const NAME: &'static str = "sync";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Synchronizes the local index of all articles; only changes since the last sync are downloaded")
+        .arg(Arg::with_name("full")
+            .long("full")
+            .help("Discards the local index and downloads all articles again"))
+}
+
+#[derive(Serialize, Debug)]
+struct SyncResult {
+    updated: usize,
+    deleted: usize,
+    articles: usize,
+    full: bool,
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+
+    let mut index = open_index(config)?;
+    if args.is_present("full") {
+        index.clear().chain_err(|| "failed to clear local index")?;
+    }
+    let full = index.since().is_none();
+    let delta = sync(config, &mut index)?;
+
+    let result = SyncResult {
+        updated: delta.added.len() + delta.updated.len(),
+        deleted: delta.deleted.len(),
+        articles: index.len(),
+        full: full,
+    };
+    output(&result, &config.general.output_format)
+}
+
+/// Downloads all changes since the last sync into the local index; the first sync downloads all articles.
+pub fn sync(config: &Config, index: &mut Index<Article>) -> Result<Delta> {
+    let request = Request {
+        state: Some(State::all),
+        tag: None,
+        sort: None,
+        detailType: DetailType::simple,
+        search: None,
+        since: index.since(),
+        favorite: None,
+        count: None,
+        offset: None,
+    };
+
+    let (mut updated, mut deleted) = (Vec::new(), Vec::new());
+    let summary = client::list::list_streamed(config, &request, None, None, |article| {
+        if article.is_deleted() {
+            deleted.push(article.item_id);
+        } else {
+            updated.push(article);
+        }
+        Ok(())
+    }).chain_err(|| "failed to sync Pocket articles")?;
+
+    index.apply(updated, deleted, summary.since.unwrap_or_else(unix_now)).chain_err(|| "failed to update local index")
+}
+
+fn output(result: &SyncResult, format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            let kind = if result.full { "Full sync" } else { "Sync" };
+            msgln(format!("{}: {} article(s) added or updated, {} deleted; {} article(s) in local index.",
+                          kind, result.updated, result.deleted, result.articles));
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(result).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use super::{open_index, sync};
+use super::client::list::Article;
+use super::list::{HumanDisplay, HumanOutput};
+use cache::index::{Delta, Index};
+use config::OutputFormat;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+use utils::time::parse_interval;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+
+pub const NAME: &'static str = "watch";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Syncs the local index periodically and shows the articles added, updated, or deleted by each sync")
+        .arg(Arg::with_name("interval")
+            .long("interval")
+            .takes_value(true)
+            .default_value("5m")
+            .help("Time between syncs, e.g., '30s' or '1h'"))
+        .arg(Arg::with_name("exec")
+            .long("exec")
+            .value_name("command")
+            .takes_value(true)
+            .help("Runs <command> by 'sh -c' for each added article, which is passed as JSON on stdin and by the \
+                   environment variables RAT_ITEM_ID, RAT_TITLE, and RAT_URL"))
+        .arg(Arg::with_name("notify")
+            .long("notify")
+            .help("Shows a desktop notification for the articles added by each sync"))
+}
+
+/// Titles listed by a notification at most
+const NOTIFICATION_TITLES: usize = 5;
+
+/// Changes of one sync with the articles resolved from the local index.
+#[derive(Serialize, Debug)]
+struct Changes<'a> {
+    at: u64,
+    added: Vec<&'a Article>,
+    updated: Vec<&'a Article>,
+    deleted: &'a [String],
+}
+
+/// One change per line of NDJSON output
+#[derive(Serialize, Debug)]
+struct Event<'a> {
+    change: &'static str,
+    item_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    article: Option<&'a Article>,
+}
+
+/// Each sync only downloads the delta since the previous sync; nothing is compared to earlier snapshots.
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    let interval = parse_interval(args.value_of("interval").unwrap())
+        .chain_err(|| "Could not parse interval duration")?;
+    let hook = args.value_of("exec");
+    let notify = args.is_present("notify");
+
+    let mut index = open_index(config)?;
+    if index.since().is_none() {
+        info("Initial sync of the local index; this may take a while.");
+        sync::sync(config, &mut index)?;
+    }
+    info(format!("Watching {} article(s); syncing every {}s.", index.len(), interval.as_secs()));
+
+    loop {
+        thread::sleep(interval);
+        // A failed sync does not end the watch; the next sync picks up all changes since the last successful one
+        match sync::sync(config, &mut index) {
+            Ok(ref delta) if !delta.is_empty() => {
+                let changes = changes(&index, delta);
+                output(&changes, &config.general.output_format)?;
+                // Hooks and notifications must not end the watch either
+                for a in changes.added.iter().filter(|_| hook.is_some()) {
+                    if let Err(e) = run_hook(hook.unwrap(), a) {
+                        warning(format!("Hook for article {} failed because {}.", a.item_id, e));
+                    }
+                }
+                if notify && !changes.added.is_empty() {
+                    let (summary, body) = notification(&changes.added);
+                    if let Err(e) = show_notification(&summary, &body) {
+                        warning(format!("Notification failed because {}.", e));
+                    }
+                }
+            }
+            Ok(_) => {}
+            Err(e) => warning(format!("Sync failed because {}; retrying in {}s.", e, interval.as_secs())),
+        }
+    }
+}
+
+fn changes<'a>(index: &'a Index<Article>, delta: &'a Delta) -> Changes<'a> {
+    Changes {
+        at: delta.at,
+        added: delta.added.iter().filter_map(|id| index.get(id)).collect(),
+        updated: delta.updated.iter().filter_map(|id| index.get(id)).collect(),
+        deleted: &delta.deleted,
+    }
+}
+
+fn output(changes: &Changes, format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            msg(render_changes(changes)?);
+            Ok(())
+        }
+        OutputFormat::JSON => {
+            let json = serde_json::to_string(changes).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+        OutputFormat::NDJSON => {
+            for event in events(changes) {
+                let json = serde_json::to_string(&event).chain_err(|| "JSON serialization failed")?;
+                output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn events<'a>(changes: &'a Changes) -> Vec<Event<'a>> {
+    let articles = changes.added.iter().map(|a| ("added", *a))
+        .chain(changes.updated.iter().map(|a| (if a.status == "1" { "archived" } else { "updated" }, *a)));
+    articles.map(|(change, a)| Event { change: change, item_id: &a.item_id, article: Some(a) })
+        .chain(changes.deleted.iter().map(|id| Event { change: "deleted", item_id: id, article: None }))
+        .collect()
+}
+
+fn run_hook(command: &str, article: &Article) -> Result<()> {
+    let json = serde_json::to_string(article).chain_err(|| "JSON serialization failed")?;
+    let mut child = Command::new("sh").arg("-c").arg(command)
+        .env("RAT_ITEM_ID", &article.item_id)
+        .env("RAT_TITLE", article.title())
+        .env("RAT_URL", article.url())
+        .stdin(Stdio::piped())
+        .spawn()
+        .chain_err(|| format!("Failed to start '{}'", command))?;
+    {
+        let stdin = child.stdin.as_mut().unwrap();
+        // Hooks need not read their input
+        match stdin.write_all(json.as_bytes()) {
+            Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+            result => result.chain_err(|| format!("Failed to pass article to '{}'", command))?,
+        }
+    }
+    let status = child.wait().chain_err(|| format!("'{}' failed", command))?;
+    if !status.success() {
+        bail!("'{}' exited with {}", command, status);
+    }
+
+    Ok(())
+}
+
+/// Summary and body of one notification for all added articles, so a large sync does not flood the desktop
+fn notification(added: &[&Article]) -> (String, String) {
+    let summary = match added.len() {
+        1 => "New article in Pocket".to_string(),
+        n => format!("{} new articles in Pocket", n),
+    };
+    let mut titles: Vec<&str> = added.iter().take(NOTIFICATION_TITLES).map(|a| a.title()).collect();
+    let more = format!("and {} more", added.len().saturating_sub(NOTIFICATION_TITLES));
+    if added.len() > NOTIFICATION_TITLES {
+        titles.push(&more);
+    }
+
+    (summary, titles.join("\n"))
+}
+
+#[cfg(target_os = "macos")]
+fn show_notification(summary: &str, body: &str) -> Result<()> {
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+    let script = format!("display notification {} with title {}", quote(body), quote(summary));
+    notify_with(Command::new("osascript").arg("-e").arg(script))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn show_notification(summary: &str, body: &str) -> Result<()> {
+    notify_with(Command::new("notify-send").arg("--app-name=rat").arg(summary).arg(body))
+}
+
+fn notify_with(command: &mut Command) -> Result<()> {
+    let status = command.stdin(Stdio::null()).stdout(Stdio::null()).status()
+        .chain_err(|| "no notification tool found; 'notify-send' is needed on Linux")?;
+    if !status.success() {
+        bail!("notification tool exited with {}", status);
+    }
+
+    Ok(())
+}
+
+fn render_changes(changes: &Changes) -> Result<String> {
+    let human_output: HumanOutput = vec!["id", "title", "url"].into();
+    let mut out = String::new();
+    for a in &changes.added {
+        out.push_str(&format!("{:9}{}\n", "added", a.human_display(&human_output)?));
+    }
+    for a in &changes.updated {
+        let kind = if a.status == "1" { "archived" } else { "updated" };
+        out.push_str(&format!("{:9}{}\n", kind, a.human_display(&human_output)?));
+    }
+    for id in changes.deleted {
+        out.push_str(&format!("{:9}* {}\n", "deleted", id));
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn articles() -> Vec<Article> {
+        serde_json::from_str(r#"[
+            {"item_id":"1","resolved_title":"Rust 1.20","resolved_url":"https://blog.rust-lang.org/1.20","status":"0"},
+            {"item_id":"2","resolved_title":"Go","resolved_url":"https://golang.org","status":"1"}
+        ]"#).unwrap()
+    }
+
+    #[test]
+    fn test_events() {
+        let articles = articles();
+        let deleted = vec!["3".to_string()];
+        let changes = Changes { at: 1, added: vec![&articles[0]], updated: vec![&articles[1]], deleted: &deleted };
+
+        let events: Vec<(&str, &str, bool)> = events(&changes).iter().map(|e| (e.change, e.item_id, e.article.is_some())).collect();
+
+        assert_eq!(events, vec![("added", "1", true), ("archived", "2", true), ("deleted", "3", false)]);
+    }
+
+    #[test]
+    fn test_notification() {
+        let articles = articles();
+
+        assert_eq!(notification(&[&articles[0]]), ("New article in Pocket".to_string(), "Rust 1.20".to_string()));
+        let many: Vec<&Article> = (0..7).map(|i| &articles[i % 2]).collect();
+        let (summary, body) = notification(&many);
+        assert_eq!(summary, "7 new articles in Pocket");
+        assert_eq!(body.lines().count(), 6);
+        assert_eq!(body.lines().last(), Some("and 2 more"));
+    }
+}
@@ -1,19 +1,11 @@
 use super::client;
-use config::Config;
+use errors::*;
+use modules::Context;
 
 use clap::{App, Arg, ArgMatches, SubCommand};
 
 pub const NAME: &'static str = "auth";
 
-error_chain! {
-    errors {
-       SlackAuthFailed {
-            description("failed to authenticate with Slack")
-            display("failed to authenticate with Slack")
-        }
-    }
-}
-
 pub fn build_sub_cli() -> App<'static, 'static> {
     SubCommand::with_name(NAME)
         .about("Runs authentication process to generate access token")
@@ -22,7 +14,8 @@ pub fn build_sub_cli() -> App<'static, 'static> {
             .help("Open authentication page in default web browser"))
 }
 
-pub fn call(args: Option<&ArgMatches>, config: &Config) -> Result<()>  {
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
     let open_browser = args.ok_or(false).unwrap().is_present("browser");
-    client::auth(config, open_browser).chain_err(|| ErrorKind::SlackAuthFailed)
+    client::auth(config, open_browser, false).chain_err(|| "failed to authenticate with Slack")
 }
@@ -0,0 +1,95 @@
+use super::client;
+use super::client::Channel;
+use config::OutputFormat;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::io::Write;
+use tabwriter::TabWriter;
+
+pub const NAME: &'static str = "channels";
+pub const NAME_LIST: &'static str = "list";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Public channels")
+        .subcommand(SubCommand::with_name(NAME_LIST)
+            .about("List public channels by name; channels you have joined are marked by '*'")
+            .arg(Arg::with_name("archived")
+                .long("archived")
+                .help("Lists archived channels, too")))
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    let subcommand_name = args.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+
+    match subcommand_name {
+        NAME_LIST => {
+            let archived = args.subcommand_matches(NAME_LIST).unwrap().is_present("archived");
+            let mut channels = client::channels(config, archived).chain_err(|| "failed to list Slack channels")?;
+            channels.sort_by(|a, b| a.name.cmp(&b.name));
+            output(&channels, &config.general.output_format)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn output(channels: &[Channel], format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            if let Some(template) = output::template() {
+                msg(template.render_all(channels));
+            } else {
+                msg(render_human(channels)?);
+            }
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(channels).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(channels: &[Channel]) -> Result<String> {
+    let mut tw = TabWriter::new(vec![]);
+    for c in channels {
+        let member = if c.is_member { "*" } else { "" };
+        let archived = if c.is_archived { " (archived)" } else { "" };
+        writeln!(&mut tw, "{}#{}{}\t{}\t{} members\t{}", member, c.name, archived, c.id, c.num_members, c.topic.value)
+            .chain_err(|| ErrorKind::OutputFailed)?;
+    }
+    tw.flush().chain_err(|| ErrorKind::OutputFailed)?;
+    let mut out = String::from_utf8(tw.into_inner().unwrap()).chain_err(|| ErrorKind::OutputFailed)?;
+    out.push_str(&format!("Received {} channel(s).\n", channels.len()));
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::client::Topic;
+
+    #[test]
+    fn test_render_human() {
+        let channels = vec![
+            Channel { id: "C1".to_string(), name: "general".to_string(), is_archived: false, is_member: true, num_members: 12,
+                      topic: Topic { value: "Everything".to_string() } },
+            Channel { id: "C2".to_string(), name: "old".to_string(), is_archived: true, is_member: false, num_members: 0,
+                      topic: Topic::default() },
+        ];
+
+        let out = render_human(&channels).unwrap();
+
+        assert_eq!(out, "*#general        C1  12 members  Everything\n\
+                         #old (archived)  C2  0 members   \n\
+                         Received 2 channel(s).\n");
+    }
+}
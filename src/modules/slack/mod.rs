@@ -1,32 +1,60 @@
+pub use clients::slack::{NAME, SlackConfig, SlackAccountConfig};
+
+use clients::slack::{access_token, client};
 use config::Config;
 use errors::*;
+use modules::{Context, Module};
 
 use clap::{App, ArgMatches, SubCommand};
 
-pub const NAME: &'static str = "slack";
-
 mod auth;
-mod client;
+mod channels;
+mod send;
+mod users;
 
-#[derive(Debug, Deserialize)]
-pub struct SlackConfig {
-    pub client_id: String,
-    pub client_secret: String,
-    pub access_token: Option<String>,
+pub fn login(config: &Config, open_browser: bool, read_only: bool) -> Result<()> {
+    client::auth(config, open_browser, read_only).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))
 }
 
-pub fn build_sub_cli() -> App<'static, 'static> {
-    SubCommand::with_name(NAME)
-        .about("Slack")
-        .subcommand(auth::build_sub_cli())
+pub fn revoke(config: &Config) -> Result<()> {
+    let access_token = access_token(config).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+    client::revoke(&access_token).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))
 }
 
-pub fn call(cli_args: Option<&ArgMatches>, config: &Config) -> Result<()> {
-    let subcommand = cli_args.unwrap();
-    let subcommand_name = subcommand.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
-    match subcommand_name {
-        auth::NAME => auth::call(subcommand.subcommand_matches(subcommand_name), config)
-            .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
-        _ => Ok(())
+pub struct Slack;
+
+impl Module for Slack {
+    fn name(&self) -> &'static str {
+        NAME
     }
-}
\ No newline at end of file
+
+    fn about(&self) -> &'static str {
+        "Slack: Where work happens."
+    }
+
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        SubCommand::with_name(NAME)
+            .about(self.about())
+            .subcommand(auth::build_sub_cli())
+            .subcommand(channels::build_sub_cli())
+            .subcommand(send::build_sub_cli())
+            .subcommand(users::build_sub_cli())
+    }
+
+    fn call(&self, cli_args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        let subcommand = cli_args.unwrap();
+        let subcommand_name = subcommand.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+        match subcommand_name {
+            auth::NAME => auth::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            channels::NAME => channels::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            send::NAME => send::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            users::NAME => users::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            _ => Ok(())
+        }
+    }
+}
+
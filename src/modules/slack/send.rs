@@ -0,0 +1,100 @@
+use super::client;
+use super::client::Posted;
+use super::NAME as MODULE_NAME;
+use config::OutputFormat;
+use credentials;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::io::{self, Read};
+
+pub const NAME: &'static str = "send";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Send a message as yourself")
+        .arg(Arg::with_name("channel")
+            .long("channel")
+            .short("c")
+            .takes_value(true)
+            .required(true)
+            .help("Sends to a channel like '#general' or a channel ID, or to a user ID as direct message"))
+        .arg(Arg::with_name("code")
+            .long("code")
+            .help("Sends the text as preformatted block, e.g., to keep the columns of rat's output aligned"))
+        .arg(Arg::with_name("text")
+            .index(1)
+            .help("Text to send; reads it from stdin if not given, e.g., the output of another rat command"))
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    credentials::ensure_writable(config, MODULE_NAME).chain_err(|| "failed to send Slack message")?;
+
+    let channel = args.value_of("channel").unwrap();
+    let text = match args.value_of("text") {
+        Some(text) => text.to_string(),
+        None => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text).chain_err(|| "Failed to read message from stdin")?;
+            text
+        }
+    };
+    let text = format_text(&text, args.is_present("code"))?;
+
+    if ctx.dry_run {
+        msgln(format!("Dry run: would send {} line(s) to '{}'.", text.lines().count(), channel));
+        return Ok(());
+    }
+
+    let posted = client::send(config, channel, &text).chain_err(|| format!("failed to send Slack message to '{}'", channel))?;
+    output(channel, &posted, &config.general.output_format)
+}
+
+/// Trailing line breaks are dropped, so piped output does not end in an empty line.
+fn format_text(text: &str, code: bool) -> Result<String> {
+    let mut end = text.len();
+    while end > 0 && (text.as_bytes()[end - 1] == b'\n' || text.as_bytes()[end - 1] == b'\r') {
+        end -= 1;
+    }
+    let text = &text[..end];
+    if text.trim().is_empty() {
+        bail!("No message to send");
+    }
+
+    if code {
+        Ok(format!("```\n{}\n```", text))
+    } else {
+        Ok(text.to_string())
+    }
+}
+
+fn output(channel: &str, posted: &Posted, format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            msgln(format!("Sent message to '{}'.", channel));
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(posted).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_format_text() {
+        assert_eq!(format_text("id  title\n1   Rust\n\n", false).unwrap(), "id  title\n1   Rust");
+        assert_eq!(format_text("id  title\n1   Rust\n", true).unwrap(), "```\nid  title\n1   Rust\n```");
+        assert!(format_text(" \n", false).is_err());
+    }
+}
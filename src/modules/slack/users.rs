@@ -0,0 +1,74 @@
+use super::client;
+use super::client::User;
+use config::OutputFormat;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::io::Write;
+use tabwriter::TabWriter;
+
+pub const NAME: &'static str = "users";
+pub const NAME_LIST: &'static str = "list";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Members of the team")
+        .subcommand(SubCommand::with_name(NAME_LIST)
+            .about("List members of the team by name; their IDs address direct messages of 'send'")
+            .arg(Arg::with_name("all")
+                .long("all")
+                .help("Lists deactivated users and bots, too")))
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    let subcommand_name = args.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+
+    match subcommand_name {
+        NAME_LIST => {
+            let all = args.subcommand_matches(NAME_LIST).unwrap().is_present("all");
+            let mut users = client::users(config).chain_err(|| "failed to list Slack users")?;
+            if !all {
+                users.retain(|u| !u.deleted && !u.is_bot);
+            }
+            users.sort_by(|a, b| a.name.cmp(&b.name));
+            output(&users, &config.general.output_format)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn output(users: &[User], format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            if let Some(template) = output::template() {
+                msg(template.render_all(users));
+            } else {
+                msg(render_human(users)?);
+            }
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(users).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(users: &[User]) -> Result<String> {
+    let mut tw = TabWriter::new(vec![]);
+    for u in users {
+        let status = if u.deleted { "deactivated" } else if u.is_bot { "bot" } else { "" };
+        writeln!(&mut tw, "@{}\t{}\t{}\t{}", u.name, u.id, u.real_name, status).chain_err(|| ErrorKind::OutputFailed)?;
+    }
+    tw.flush().chain_err(|| ErrorKind::OutputFailed)?;
+    let mut out = String::from_utf8(tw.into_inner().unwrap()).chain_err(|| ErrorKind::OutputFailed)?;
+    out.push_str(&format!("Received {} user(s).\n", users.len()));
+
+    Ok(out)
+}
@@ -1,63 +1,91 @@
-use config::{Config, OutputFormat};
+pub use clients::stocks::{NAME, StockPrice};
+
+use clients::stocks::comdirect;
+use config::OutputFormat;
 use errors::*;
+use modules::{Context, Module};
+use net::concurrent;
 use utils::console::*;
 use utils::output;
 
 use clap::{App, Arg, ArgMatches, SubCommand};
 use serde_json;
-use std::fmt;
 
-mod comdirect;
+pub struct Stocks;
 
-pub const NAME: &'static str = "stocks";
+impl Module for Stocks {
+    fn name(&self) -> &'static str {
+        NAME
+    }
 
+    fn about(&self) -> &'static str {
+        "Stocks scrapes current stock prices from comdirect.de"
+    }
 
-#[derive(Serialize, Debug)]
-pub struct StockPrice {
-    pub name: String,
-    pub wkn: String,
-    pub date: String,
-    pub price: f32,
-    pub currency: String,
-}
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        SubCommand::with_name(NAME)
+            .about(self.about())
+            .arg(Arg::with_name("search")
+                .index(1)
+                .required(true)
+                .multiple(true)
+                .help("search terms like company name, ISIN, WKN, or symbol; multiple terms are scraped concurrently"))
+    }
 
-pub fn build_sub_cli() -> App<'static, 'static> {
-    SubCommand::with_name(NAME)
-        .about("Stocks scrapes current stock prices from comdirect.de")
-        .arg(Arg::with_name("search")
-            .index(1)
-            .required(true)
-            .help("search term like company name, ISIN, WKN, or symbol"))
-}
+    fn call(&self, cli_args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        let args = cli_args.unwrap();
+        let searches: Vec<String> = args.values_of("search").unwrap().map(|s| s.to_string()).collect();
 
-pub fn call(cli_args: Option<&ArgMatches>, config: &Config) -> Result<()> {
-    let args = cli_args.unwrap();
-    let search = args.value_of("search").unwrap().to_string();
+        if searches.len() == 1 {
+            let stock_price = comdirect::scrape_stock_price(searches[0].clone())
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+            return output(&[stock_price], false, &ctx.output_format());
+        }
 
-    let stock_price = comdirect::scrape_stock_price(search)
-        .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+        let count = searches.len();
+        let results = concurrent::map_bounded(searches.clone(), ctx.config.general.max_concurrency(), comdirect::scrape_stock_price);
+        let mut stock_prices = Vec::new();
+        for (search, result) in searches.iter().zip(results) {
+            match result {
+                Ok(stock_price) => stock_prices.push(stock_price),
+                Err(e) => error(format!("Failed to scrape stock price for '{}' because {}", search, e)),
+            }
+        }
 
-    output(&stock_price, &config.general.output_format)
+        output(&stock_prices, true, &ctx.output_format())?;
+        if stock_prices.len() < count {
+            bail!(ErrorKind::PartialFailure(stock_prices.len(), count - stock_prices.len()));
+        }
+
+        Ok(())
+    }
 }
 
-fn output(stock_price: &StockPrice, format: &OutputFormat) -> Result<()> {
+
+/// The result of a single search is rendered as JSON object, the results of several searches as JSON array, even if
+/// only one of them succeeded, so the shape depends on the command line only.
+fn output(stock_prices: &[StockPrice], several_searches: bool, format: &OutputFormat) -> Result<()> {
     match *format {
-        OutputFormat::HUMAN => output_human(stock_price),
-        OutputFormat::JSON => {
-            let json = serde_json::to_string(stock_price).chain_err(|| "Failed to serialize JSON")?;
+        OutputFormat::HUMAN => output_human(stock_prices),
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = if !several_searches && stock_prices.len() == 1 {
+                serde_json::to_string(&stock_prices[0])
+            } else {
+                serde_json::to_string(stock_prices)
+            }.chain_err(|| "Failed to serialize JSON")?;
             output::as_json(&json).chain_err(|| "Output failed")
         },
     }
 }
 
-impl fmt::Display for StockPrice {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} ({}) is at {} {} on {}.", self.name, self.wkn, self.price, self.currency, self.date)
+fn output_human(stock_prices: &[StockPrice]) -> Result<()> {
+    if let Some(template) = output::template() {
+        msg(template.render_all(stock_prices));
+        return Ok(());
+    }
+    for stock_price in stock_prices {
+        msgln(format!("{}", stock_price));
     }
-}
-
-fn output_human(stock_price: &StockPrice) -> Result<()> {
-    msgln(format!("{}", stock_price));
 
     Ok(())
 }
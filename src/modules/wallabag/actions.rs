@@ -0,0 +1,104 @@
+use super::{client, session};
+use super::NAME as MODULE_NAME;
+use config::OutputFormat;
+use credentials;
+use errors::*;
+use modules::Context;
+use utils::console::*;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::collections::HashSet;
+
+pub const NAME_ARCHIVE: &'static str = "archive";
+pub const NAME_DELETE: &'static str = "delete";
+
+pub fn build_sub_cli() -> Vec<App<'static, 'static>> {
+    vec![
+        SubCommand::with_name(NAME_ARCHIVE)
+            .about("Archive saved entries")
+            .arg(Arg::with_name("id")
+                .index(1)
+                .multiple(true)
+                .required(true)
+                .help("entry id")),
+        SubCommand::with_name(NAME_DELETE)
+            .about("Delete saved entries permanently")
+            .arg(Arg::with_name("id")
+                .index(1)
+                .multiple(true)
+                .required(true)
+                .help("entry id")),
+    ]
+}
+
+/// The result of an action on one entry
+#[derive(Serialize, Debug)]
+struct ActionResult<'a> {
+    entry_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")] error: Option<String>,
+}
+
+/// Wallabag's API takes one entry per request, so the entries are sent one after the other.
+pub fn call(action: &str, args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+    credentials::ensure_writable(config, MODULE_NAME)
+        .chain_err(|| format!("action '{}' failed to apply to Wallabag entry", action))?;
+    let ids = unique_ids(args.values_of("id").unwrap());
+
+    if ctx.dry_run {
+        msgln(format!("Dry run: would send {} action for {} entries.", action, ids.len()));
+        return Ok(());
+    }
+
+    let session = session(config).chain_err(|| format!("action '{}' failed to apply to Wallabag entry", action))?;
+    info(format!("Sending {} action for {} entries ...", action, ids.len()));
+    let results: Vec<ActionResult> = ids.into_iter().map(|id| {
+        let result = match action {
+            NAME_ARCHIVE => client::archive(&session, config, id).map(|_| ()),
+            _ => client::delete(&session, config, id),
+        };
+        // The root cause is the most specific, e.g., the HTTP status
+        ActionResult { entry_id: id, error: result.err().and_then(|e| e.iter().last().map(|c| c.to_string())) }
+    }).collect();
+
+    output(&results, &config.general.output_format)?;
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+    if failed > 0 {
+        bail!(ErrorKind::PartialFailure(results.len() - failed, failed));
+    }
+
+    Ok(())
+}
+
+/// Drops repeated ids, keeping the order of the first occurrences, so every entry gets exactly one result.
+fn unique_ids<'a, I: Iterator<Item = &'a str>>(ids: I) -> Vec<&'a str> {
+    let mut seen = HashSet::new();
+    ids.filter(|id| seen.insert(*id)).collect()
+}
+
+fn output(results: &[ActionResult], format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            msg(render_human(results));
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(results).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(results: &[ActionResult]) -> String {
+    let mut out = String::new();
+    for result in results.iter().filter(|r| r.error.is_some()) {
+        out.push_str(&format!("Entry {} failed: {}\n", result.entry_id, result.error.as_ref().unwrap()));
+    }
+    let successful = results.iter().filter(|r| r.error.is_none()).count();
+    out.push_str(&format!("{} action(s) successful, {} failed.\n", successful, results.len() - successful));
+
+    out
+}
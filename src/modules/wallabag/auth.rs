@@ -0,0 +1,20 @@
+use errors::*;
+use modules::Context;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+pub const NAME: &'static str = "auth";
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("Exchanges user name and password for an access token; the password is not stored")
+        .arg(Arg::with_name("username")
+            .long("username")
+            .takes_value(true)
+            .help("User name; defaults to 'username' in section '[wallabag]'"))
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let username = args.and_then(|args| args.value_of("username"));
+    super::login(ctx.config, username, false).chain_err(|| "failed to authenticate with Wallabag")
+}
@@ -0,0 +1,169 @@
+use super::{client, session};
+use super::client::{Entry, Filter};
+use config::OutputFormat;
+use errors::*;
+use modules::{self, Context};
+use modules::bookmarks::{self, Bookmark};
+use utils::console::*;
+use utils::datetime;
+use utils::output;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json;
+use std::io::Write;
+use tabwriter::TabWriter;
+
+pub const NAME: &'static str = "list";
+
+/// Most recent entries of `bookmarks`, requested as a single page like `list --count`
+const BOOKMARKS_COUNT: usize = 5000;
+
+pub fn build_sub_cli() -> App<'static, 'static> {
+    SubCommand::with_name(NAME)
+        .about("List saved entries")
+        .arg(Arg::with_name("state")
+            .long("state")
+            .short("s")
+            .takes_value(true)
+            .possible_values(&["unread", "archive", "all"])
+            .default_value("unread")
+            .help("Select entries to list"))
+        .arg(Arg::with_name("starred")
+            .long("starred")
+            .help("Lists only starred entries"))
+        .arg(Arg::with_name("tag")
+            .long("tag")
+            .short("t")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .help("Lists only entries with this tag; may be repeated"))
+        .arg(Arg::with_name("count")
+            .long("count")
+            .takes_value(true)
+            .default_value("25")
+            .help("Lists at most <count> entries, most recently saved first"))
+        .args(&modules::added_args())
+        .arg(bookmarks::list_arg())
+}
+
+pub fn call(args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+    let config = ctx.config;
+    let args = args.unwrap();
+
+    let filter = Filter {
+        archived: match args.value_of("state").unwrap() {
+            "archive" => Some(true),
+            "all" => None,
+            _ => Some(false),
+        },
+        starred: if args.is_present("starred") { Some(true) } else { None },
+        tags: args.values_of("tag").map(|tags| tags.collect()).unwrap_or_default(),
+    };
+    let count = args.value_of("count").unwrap();
+    let count = match count.parse::<usize>() {
+        Ok(count) if count > 0 => count,
+        _ => bail!("Invalid count '{}'; expected a positive number", count),
+    };
+
+    let added = modules::added_range(args)?;
+
+    let session = session(config).chain_err(|| "failed to list Wallabag entries")?;
+    let mut entries = client::list(&session, config, &filter, count).chain_err(|| "failed to list Wallabag entries")?;
+    entries.retain(|e| added.contains_iso8601(&e.created_at));
+
+    if args.is_present("bookmarks") {
+        return bookmarks::print_all(&entries.iter().map(to_bookmark).collect::<Vec<_>>());
+    }
+    output(&entries, &config.general.output_format)
+}
+
+pub fn bookmarks(ctx: &Context) -> Result<Vec<Bookmark>> {
+    let config = ctx.config;
+    let filter = Filter::default();
+    let session = session(config).chain_err(|| "failed to list Wallabag entries")?;
+    let entries = client::list(&session, config, &filter, BOOKMARKS_COUNT).chain_err(|| "failed to list Wallabag entries")?;
+
+    Ok(entries.iter().map(to_bookmark).collect())
+}
+
+fn to_bookmark(entry: &Entry) -> Bookmark {
+    Bookmark {
+        url: entry.url.clone(),
+        title: entry.title.clone(),
+        tags: entry.tags.iter().map(|t| t.label.clone()).collect(),
+        time_added: datetime::parse_iso8601(&entry.created_at).ok().map(|t| t.as_secs()),
+        time_updated: None,
+    }
+}
+
+fn output(entries: &[Entry], format: &OutputFormat) -> Result<()> {
+    match *format {
+        OutputFormat::HUMAN => {
+            if let Some(template) = output::template() {
+                msg(template.render_all(entries));
+            } else {
+                msg(render_human(entries)?);
+            }
+            Ok(())
+        }
+        OutputFormat::JSON | OutputFormat::NDJSON => {
+            let json = serde_json::to_string(entries).chain_err(|| "JSON serialization failed")?;
+            output::as_json(&json).chain_err(|| ErrorKind::OutputFailed)
+        }
+    }
+}
+
+fn render_human(entries: &[Entry]) -> Result<String> {
+    let mut tw = TabWriter::new(vec![]);
+    for e in entries {
+        let title = if e.title.is_empty() { &e.url } else { &e.title };
+        let starred = if e.is_starred == 1 { "*" } else { "" };
+        let tags: Vec<&str> = e.tags.iter().map(|t| t.label.as_str()).collect();
+        writeln!(&mut tw, "{}\t{}{}\t{}\t[{}]\tadded {}", e.id, starred, title, e.url, tags.join(", "), e.created_at)
+            .chain_err(|| ErrorKind::OutputFailed)?;
+    }
+    tw.flush().chain_err(|| ErrorKind::OutputFailed)?;
+    let mut out = String::from_utf8(tw.into_inner().unwrap()).chain_err(|| ErrorKind::OutputFailed)?;
+    out.push_str(&format!("Received {} entries.\n", entries.len()));
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clients::wallabag::client::Tag;
+
+    #[test]
+    fn test_render_human() {
+        let entries = vec![
+            Entry {
+                id: 42,
+                url: "https://example.com/a".to_string(),
+                title: "A".to_string(),
+                is_archived: 0,
+                is_starred: 1,
+                created_at: "2017-07-14T02:40:00+0000".to_string(),
+                reading_time: 3,
+                tags: vec![Tag { label: "rust".to_string() }, Tag { label: "cli".to_string() }],
+            },
+            Entry {
+                id: 7,
+                url: "https://example.com/b".to_string(),
+                title: "".to_string(),
+                is_archived: 0,
+                is_starred: 0,
+                created_at: "2017-07-14T02:40:00+0000".to_string(),
+                reading_time: 0,
+                tags: vec![],
+            },
+        ];
+
+        let out = render_human(&entries).unwrap();
+
+        assert_eq!(out, "42  *A                     https://example.com/a  [rust, cli]  added 2017-07-14T02:40:00+0000\n\
+                         7   https://example.com/b  https://example.com/b  []           added 2017-07-14T02:40:00+0000\n\
+                         Received 2 entries.\n");
+    }
+}
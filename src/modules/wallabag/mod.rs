@@ -0,0 +1,113 @@
+pub use clients::wallabag::{NAME, WallabagConfig};
+
+use clients::wallabag::{client, oauth_client};
+use config::Config;
+use credentials;
+use errors::*;
+use modules::{Context, Module};
+use modules::bookmarks::Bookmark;
+use net::oauth::oauth2;
+use net::oauth::oauth2::TokenSession;
+use utils::console::*;
+
+use clap::{App, ArgMatches, SubCommand};
+
+mod actions;
+mod add;
+mod auth;
+mod list;
+
+fn ensure_client(config: &Config) -> Result<()> {
+    let wallabag = &config.wallabag;
+    if wallabag.base_url.is_empty() || wallabag.client_id.is_empty() || wallabag.client_secret.is_empty() {
+        bail!("No Wallabag instance or API client available; please set 'base_url', 'client_id', and 'client_secret' in section '[wallabag]'");
+    }
+
+    Ok(())
+}
+
+/// The most recent entries as bookmark records; cf. `rat xfer`.
+pub fn bookmarks(ctx: &Context) -> Result<Vec<Bookmark>> {
+    ensure_client(ctx.config)?;
+    list::bookmarks(ctx)
+}
+
+pub fn add_bookmarks(ctx: &Context, bookmarks: &[Bookmark]) -> Result<()> {
+    ensure_client(ctx.config)?;
+    add::add_bookmarks(ctx, bookmarks)
+}
+
+/// Access tokens expire after an hour and are refreshed by the refresh token; if that has expired, too, and
+/// `interactive_reauth` is enabled, the user is asked to log in again.
+pub fn session<'a>(config: &'a Config) -> oauth2::Result<TokenSession<'a>> {
+    let session = TokenSession::new(config, NAME, oauth_client(config), None)?.with_reauth(reauth);
+
+    Ok(session)
+}
+
+fn reauth(config: &Config, _: &str) -> ::std::result::Result<(), String> {
+    login(config, None, false).map_err(|e| e.to_string())
+}
+
+/// Exchanges user name and password for an access token; both are taken from section '[wallabag]' or asked for.
+/// Wallabag does not support scopes, so a read-only login only marks the stored token as read-only.
+pub fn login(config: &Config, username: Option<&str>, read_only: bool) -> Result<()> {
+    ensure_client(config)?;
+    let username = match username.or_else(|| config.wallabag.username.as_ref().map(|u| u.as_str())) {
+        Some(username) => username.to_string(),
+        None => read_line("User name: ").chain_err(|| "Failed to read user name")?,
+    };
+    let password = match config.wallabag.password {
+        Some(ref password) => password.clone(),
+        None => read_secret("Password: ").chain_err(|| "Failed to read password")?,
+    };
+    client::auth(config, &username, &password).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+    if read_only {
+        credentials::mark_read_only(config, NAME).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+    }
+
+    Ok(())
+}
+
+pub struct Wallabag;
+
+impl Module for Wallabag {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn about(&self) -> &'static str {
+        "Wallabag: A self-hostable application for saving web pages."
+    }
+
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        let mut subcommand = SubCommand::with_name(NAME)
+            .about(self.about())
+            .subcommand(add::build_sub_cli())
+            .subcommand(auth::build_sub_cli())
+            .subcommand(list::build_sub_cli());
+        for s in actions::build_sub_cli() {
+            subcommand = subcommand.subcommand(s);
+        }
+
+        subcommand
+    }
+
+    fn call(&self, cli_args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        ensure_client(ctx.config).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+        let subcommand = cli_args.unwrap();
+        let subcommand_name = subcommand.subcommand_name().ok_or_else(|| ErrorKind::NoSubcommandSpecified(NAME.to_string()))?;
+        match subcommand_name {
+            add::NAME => add::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            auth::NAME => auth::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            list::NAME => list::call(subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            actions::NAME_ARCHIVE | actions::NAME_DELETE =>
+                actions::call(subcommand_name, subcommand.subcommand_matches(subcommand_name), ctx)
+                .chain_err(|| ErrorKind::ModuleFailed(NAME.to_string())),
+            _ => Ok(())
+        }
+    }
+}
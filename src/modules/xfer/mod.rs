@@ -0,0 +1,149 @@
+use errors::*;
+use modules::{self, Context, Module};
+use modules::bookmarks::Bookmark;
+use utils::console::*;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::time::Duration;
+
+pub const NAME: &'static str = "xfer";
+
+/// Services that list and add bookmark records; cf. `modules::bookmarks`
+pub const SERVICES: [&'static str; 4] = ["instapaper", "pinboard", "pocket", "wallabag"];
+
+pub struct Xfer;
+
+impl Module for Xfer {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn about(&self) -> &'static str {
+        "Transfers bookmarks from one service to another"
+    }
+
+    fn build_sub_cli(&self) -> App<'static, 'static> {
+        SubCommand::with_name(NAME)
+            .about(self.about())
+            .arg(Arg::with_name("from")
+                .long("from")
+                .takes_value(true)
+                .required(true)
+                .possible_values(&SERVICES)
+                .help("Service to copy all bookmarks from; Instapaper sends at most 500 per folder"))
+            .arg(Arg::with_name("to")
+                .long("to")
+                .takes_value(true)
+                .required(true)
+                .possible_values(&SERVICES)
+                .help("Service to add the bookmarks to; URLs saved already are updated or kept as the service does"))
+            .arg(Arg::with_name("tag")
+                .long("tag")
+                .short("t")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .help("Adds a tag to all transferred bookmarks, e.g., 'from-pocket'; may be repeated"))
+            .args(&modules::added_args())
+    }
+
+    fn call(&self, cli_args: Option<&ArgMatches>, ctx: &Context) -> Result<()> {
+        let args = cli_args.unwrap();
+        let from = args.value_of("from").unwrap();
+        let to = args.value_of("to").unwrap();
+        if from == to {
+            bail!("Cannot transfer bookmarks from {} to itself", from);
+        }
+        let added = modules::added_range(args)?;
+        let tags: Vec<&str> = args.values_of("tag").map(|tags| tags.collect()).unwrap_or_default();
+
+        let mut bookmarks = list(from, ctx).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))?;
+        bookmarks.retain(|b| match b.time_added {
+            Some(time_added) => added.contains(Duration::from_secs(time_added)),
+            None => added.is_unbounded(),
+        });
+        for b in &mut bookmarks {
+            b.add_tags(&tags);
+        }
+        if bookmarks.is_empty() {
+            msgln(format!("No bookmarks to transfer from {}.", from));
+            return Ok(());
+        }
+
+        info(format!("Transferring {} bookmark(s) from {} to {} ...", bookmarks.len(), from, to));
+        add(to, ctx, &bookmarks).chain_err(|| ErrorKind::ModuleFailed(NAME.to_string()))
+    }
+}
+
+fn list(service: &str, ctx: &Context) -> Result<Vec<Bookmark>> {
+    match service {
+        #[cfg(feature = "instapaper")]
+        "instapaper" => modules::instapaper::bookmarks(ctx),
+        #[cfg(feature = "pinboard")]
+        "pinboard" => modules::pinboard::bookmarks(ctx),
+        #[cfg(feature = "pocket")]
+        "pocket" => modules::pocket::bookmarks(ctx),
+        #[cfg(feature = "wallabag")]
+        "wallabag" => modules::wallabag::bookmarks(ctx),
+        _ => {
+            let _ = ctx;
+            bail!(ErrorKind::ModuleNotIncluded(service.to_string()))
+        }
+    }
+}
+
+pub fn add(service: &str, ctx: &Context, bookmarks: &[Bookmark]) -> Result<()> {
+    match service {
+        #[cfg(feature = "instapaper")]
+        "instapaper" => modules::instapaper::add_bookmarks(ctx, bookmarks),
+        #[cfg(feature = "pinboard")]
+        "pinboard" => modules::pinboard::add_bookmarks(ctx, bookmarks),
+        #[cfg(feature = "pocket")]
+        "pocket" => modules::pocket::add_bookmarks(ctx, bookmarks),
+        #[cfg(feature = "wallabag")]
+        "wallabag" => modules::wallabag::add_bookmarks(ctx, bookmarks),
+        _ => {
+            let _ = (ctx, bookmarks);
+            bail!(ErrorKind::ModuleNotIncluded(service.to_string()))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "pinboard", feature = "pocket"))]
+mod test {
+    use super::*;
+    use net::mock::MockClient;
+    use testing::harness::*;
+
+    use std::rc::Rc;
+
+    #[test]
+    fn test_xfer_from_pinboard_to_pocket() {
+        let config = config("xfer", "[pinboard]\napi_token = 'user:token'\n\n\
+                                     [pocket]\nconsumer_key = 'key'\naccess_token = 'token'");
+        let mock = Rc::new(MockClient::new()
+            .respond(200, &[], r#"[{"href":"https://example.com","description":"Example","time":"2017-07-14T02:40:00Z","tags":"rust"}]"#)
+            .respond(200, &[], r#"{"item":{"item_id":"1","title":"Example"},"status":1}"#));
+
+        run(&Xfer, &["--from", "pinboard", "--to", "pocket", "--tag", "from-pinboard"], &Context::new(&config), &mock).unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("GET /v1/posts/all?"));
+        assert!(requests[1].starts_with("POST /v3/add"));
+        assert!(requests[1].contains(r#""tags":"rust,from-pinboard""#));
+        assert!(requests[1].contains(r#""time":1500000000"#));
+    }
+
+    #[test]
+    fn test_xfer_dry_run_adds_nothing() {
+        let config = config("xfer-dry-run", "[pinboard]\napi_token = 'user:token'\n\n\
+                                             [pocket]\nconsumer_key = 'key'\naccess_token = 'token'");
+        let mock = Rc::new(MockClient::new()
+            .respond(200, &[], r#"[{"href":"https://example.com","description":"Example","time":"2017-07-14T02:40:00Z","tags":""}]"#));
+
+        run(&Xfer, &["--from", "pinboard", "--to", "pocket"], &Context::new(&config).with_dry_run(true), &mock).unwrap();
+
+        assert_eq!(mock.requests().len(), 1);
+    }
+}
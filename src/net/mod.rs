@@ -1,4 +0,0 @@
-pub mod oauth;
-
-pub mod http;
-
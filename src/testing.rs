@@ -0,0 +1,113 @@
+/// Golden-file assertions for output rendering tests. Fixtures are read from `test/data`, golden files from
+/// `test/golden`. Run the tests with `RAT_UPDATE_GOLDEN=1` to rewrite the golden files after an intended change.
+#[cfg(any(feature = "centerdevice", feature = "pocket"))]
+pub mod golden {
+    use std::env;
+    use std::fs::{self, File};
+    use std::io::{Read, Write};
+    use std::path::Path;
+
+    static DATA_DIR: &'static str = "test/data";
+    static GOLDEN_DIR: &'static str = "test/golden";
+    static UPDATE_ENV: &'static str = "RAT_UPDATE_GOLDEN";
+
+    pub fn fixture(name: &str) -> String {
+        read(&Path::new(DATA_DIR).join(name)).expect(&format!("fixture '{}' is missing", name))
+    }
+
+    pub fn assert_golden(name: &str, actual: &str) {
+        let path = Path::new(GOLDEN_DIR).join(name);
+        if env::var(UPDATE_ENV).is_ok() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            File::create(&path).and_then(|mut f| f.write_all(actual.as_bytes())).unwrap();
+            return;
+        }
+        let expected = read(&path).expect(&format!("golden file '{}' is missing; run with {}=1", name, UPDATE_ENV));
+
+        assert!(expected == actual, "output differs from golden file '{}'\n--- expected\n{}--- actual\n{}", name, expected, actual);
+    }
+
+    fn read(path: &Path) -> Option<String> {
+        let mut content = String::new();
+        File::open(path).and_then(|mut f| f.read_to_string(&mut content)).ok().map(|_| content)
+    }
+}
+
+/// End-to-end tests of module commands without network access. `run` parses the arguments like `rat` does and calls
+/// the module while a `MockClient` answers all HTTP requests, e.g., with fixtures from `test/data`. Tests assert on the
+/// result and on the requests the mock recorded.
+#[cfg(any(feature = "feed", feature = "pinboard", feature = "pocket"))]
+pub mod harness {
+    use config::Config;
+    use errors::Result;
+    use modules::{self, Context, Module};
+    use net::http::with_client;
+    use net::mock::MockClient;
+
+    use rat_core::testing;
+    use std::rc::Rc;
+
+    /// Creates a configuration with an empty cache directory of its own per `test` and process, so tests neither share
+    /// responses and credentials with each other nor with the user. `sections` is added to the configuration, e.g., a
+    /// token.
+    pub fn config(test: &str, sections: &str) -> Config {
+        testing::config(&format!("harness-{}", test), sections)
+    }
+
+    /// Runs the command `args` of `module`, e.g., `&["add", "https://example.com"]`, with `mock` as HTTP client.
+    pub fn run<M: Module>(module: &M, args: &[&str], ctx: &Context, mock: &Rc<MockClient>) -> Result<()> {
+        let matches = modules::parse_module_args(module, "rat", args).expect("invalid arguments");
+
+        with_client(mock.clone(), || module.call(Some(&matches), ctx))
+    }
+}
+
+/// Support for contract tests against the live provider APIs. Credentials are read from environment variables; a test
+/// without its credentials is skipped. Cf. README, section 'Live Tests'.
+#[cfg(feature = "live-tests")]
+pub mod live {
+    use config::Config;
+    use errors::Result;
+    use utils::secrets;
+
+    use rat_core::testing::ConfigBuilder;
+    use std::env;
+
+    /// Returns the value of environment variable `name` and masks it in all diagnostic messages, or `None` if it is
+    /// not set, in which case the caller should skip the test.
+    pub fn credential(name: &str) -> Option<String> {
+        match env::var(name) {
+            Ok(ref value) if !value.is_empty() => {
+                secrets::register(value.as_str());
+                Some(value.clone())
+            }
+            _ => {
+                println!("skipping live test, because environment variable {} is not set", name);
+                None
+            }
+        }
+    }
+
+    /// Creates a configuration with its own cache directory, so stored credentials of the user are never used or
+    /// overwritten. `sections` is added to the configuration and may override the defaults for a module. Failed
+    /// requests are retried with the default delays to go easy on the providers.
+    pub fn config(sections: &str) -> Config {
+        ConfigBuilder::new("live")
+            .general("output_format", "{ format = 'JSON' }")
+            .without_general("retry_base_delay_ms")
+            .sections(sections)
+            .build()
+    }
+
+    /// Unwraps `result` or fails the test with the redacted error and its causes, so failures never leak credentials.
+    pub fn check<T>(result: Result<T>) -> T {
+        match result {
+            Ok(value) => value,
+            Err(e) => {
+                let causes: Vec<String> = e.iter().map(|c| secrets::redact(&c.to_string())).collect();
+                panic!("live test failed: {}", causes.join(": caused by: "));
+            }
+        }
+    }
+}
+